@@ -0,0 +1,292 @@
+use std::fmt::Debug;
+
+use crate::*;
+
+/// A chain of owned buffers, which could be used as a vectored buffer.
+///
+/// The buffers could be of different types, and appending a buffer doesn't
+/// copy the data. It is useful to build a message from a header and a body
+/// without concatenating them.
+///
+/// # Examples
+///
+/// ```
+/// use compio_buf::{BufChain, IoBuf, IoVectoredBuf};
+///
+/// let mut chain = BufChain::new();
+/// chain.push(b"GET / HTTP/1.1\r\n");
+/// chain.push(String::from("Host: example.com\r\n"));
+/// chain.push(b"\r\n".to_vec());
+///
+/// assert_eq!(chain.len(), 37);
+/// let bufs = chain
+///     .as_dyn_bufs()
+///     .map(|b| b.as_slice())
+///     .collect::<Vec<_>>();
+/// assert_eq!(bufs[1], b"Host: example.com\r\n");
+/// ```
+#[derive(Default)]
+pub struct BufChain {
+    bufs: Vec<Box<dyn IoBuf>>,
+    len: usize,
+}
+
+impl BufChain {
+    /// Create an empty [`BufChain`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty [`BufChain`] with space for at least `capacity`
+    /// buffers.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bufs: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    /// Append a buffer to the end of the chain.
+    pub fn push(&mut self, buf: impl IoBuf) {
+        self.len += buf.buf_len();
+        self.bufs.push(Box::new(buf));
+    }
+
+    /// Move all buffers of `other` to the end of the chain.
+    pub fn append(&mut self, other: &mut Self) {
+        self.len += std::mem::take(&mut other.len);
+        self.bufs.append(&mut other.bufs);
+    }
+
+    /// Total initialized length of all buffers.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if there are no initialized bytes in the chain.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of the buffers in the chain.
+    pub fn buf_count(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Remove all buffers from the chain.
+    pub fn clear(&mut self) {
+        self.bufs.clear();
+        self.len = 0;
+    }
+}
+
+impl Debug for BufChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufChain")
+            .field("bufs", &self.bufs.len())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl<B: IoBuf> Extend<B> for BufChain {
+    fn extend<T: IntoIterator<Item = B>>(&mut self, iter: T) {
+        for buf in iter {
+            self.push(buf);
+        }
+    }
+}
+
+impl<B: IoBuf> FromIterator<B> for BufChain {
+    fn from_iter<T: IntoIterator<Item = B>>(iter: T) -> Self {
+        let mut chain = Self::new();
+        chain.extend(iter);
+        chain
+    }
+}
+
+impl IoVectoredBuf for BufChain {
+    fn as_dyn_bufs(&self) -> impl Iterator<Item = &dyn IoBuf> {
+        self.bufs.iter().map(|buf| &**buf)
+    }
+
+    fn owned_iter(self) -> Result<OwnedIter<impl OwnedIterator<Inner = Self>>, Self>
+    where
+        Self: Sized,
+    {
+        IndexedIter::new(self, 0).map(OwnedIter::new)
+    }
+}
+
+impl IoIndexedBuf for BufChain {
+    fn buf_nth(&self, n: usize) -> Option<&dyn IoBuf> {
+        self.bufs.get(n).map(|buf| &**buf)
+    }
+}
+
+/// A chain of owned mutable buffers, which could be used as a vectored buffer
+/// to read into.
+///
+/// It is the sink side of [`BufChain`]: the buffers are filled in order, and
+/// each of them keeps its own initialized length.
+///
+/// # Examples
+///
+/// ```
+/// use compio_buf::{BufChainMut, IoBuf, IoVectoredBuf, SetBufInit};
+///
+/// let mut chain = BufChainMut::new();
+/// chain.push(Vec::with_capacity(4));
+/// chain.push([0u8; 8]);
+///
+/// assert_eq!(chain.capacity(), 12);
+/// assert_eq!(chain.len(), 8);
+///
+/// unsafe { chain.set_buf_init(6) };
+/// let lens = chain
+///     .as_dyn_bufs()
+///     .map(|b| b.buf_len())
+///     .collect::<Vec<_>>();
+/// assert_eq!(lens, [4, 8]);
+/// ```
+#[derive(Default)]
+pub struct BufChainMut {
+    bufs: Vec<Box<dyn ChainBufMut>>,
+}
+
+impl BufChainMut {
+    /// Create an empty [`BufChainMut`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty [`BufChainMut`] with space for at least `capacity`
+    /// buffers.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            bufs: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Append a buffer to the end of the chain.
+    pub fn push(&mut self, buf: impl IoBufMut) {
+        self.bufs.push(Box::new(buf));
+    }
+
+    /// Move all buffers of `other` to the end of the chain.
+    pub fn append(&mut self, other: &mut Self) {
+        self.bufs.append(&mut other.bufs);
+    }
+
+    /// Total initialized length of all buffers.
+    pub fn len(&self) -> usize {
+        self.bufs.iter().map(|buf| buf.buf_len()).sum()
+    }
+
+    /// Returns `true` if there are no initialized bytes in the chain.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Total capacity of all buffers.
+    pub fn capacity(&self) -> usize {
+        self.bufs.iter().map(|buf| buf.buf_capacity()).sum()
+    }
+
+    /// Number of the buffers in the chain.
+    pub fn buf_count(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Remove all buffers from the chain.
+    pub fn clear(&mut self) {
+        self.bufs.clear();
+    }
+}
+
+impl Debug for BufChainMut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BufChainMut")
+            .field("bufs", &self.bufs.len())
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<B: IoBufMut> Extend<B> for BufChainMut {
+    fn extend<T: IntoIterator<Item = B>>(&mut self, iter: T) {
+        for buf in iter {
+            self.push(buf);
+        }
+    }
+}
+
+impl<B: IoBufMut> FromIterator<B> for BufChainMut {
+    fn from_iter<T: IntoIterator<Item = B>>(iter: T) -> Self {
+        let mut chain = Self::new();
+        chain.extend(iter);
+        chain
+    }
+}
+
+impl IoVectoredBuf for BufChainMut {
+    fn as_dyn_bufs(&self) -> impl Iterator<Item = &dyn IoBuf> {
+        self.bufs.iter().map(|buf| buf.as_dyn_buf())
+    }
+
+    fn owned_iter(self) -> Result<OwnedIter<impl OwnedIterator<Inner = Self>>, Self>
+    where
+        Self: Sized,
+    {
+        IndexedIter::new(self, 0).map(OwnedIter::new)
+    }
+}
+
+impl IoIndexedBuf for BufChainMut {
+    fn buf_nth(&self, n: usize) -> Option<&dyn IoBuf> {
+        self.bufs.get(n).map(|buf| buf.as_dyn_buf())
+    }
+}
+
+impl SetBufInit for BufChainMut {
+    unsafe fn set_buf_init(&mut self, len: usize) {
+        default_set_buf_init(self.bufs.iter_mut().map(|buf| &mut **buf), len)
+    }
+}
+
+impl IoVectoredBufMut for BufChainMut {
+    fn as_dyn_mut_bufs(&mut self) -> impl Iterator<Item = &mut dyn IoBufMut> {
+        self.bufs.iter_mut().map(|buf| buf.as_dyn_buf_mut())
+    }
+
+    fn owned_iter_mut(self) -> Result<OwnedIter<impl OwnedIteratorMut<Inner = Self>>, Self>
+    where
+        Self: Sized,
+    {
+        IndexedIter::new(self, 0).map(OwnedIter::new)
+    }
+}
+
+impl IoIndexedBufMut for BufChainMut {
+    fn buf_nth_mut(&mut self, n: usize) -> Option<&mut dyn IoBufMut> {
+        self.bufs.get_mut(n).map(|buf| buf.as_dyn_buf_mut())
+    }
+}
+
+/// A mutable buffer which could be viewed as both [`IoBuf`] and [`IoBufMut`]
+/// trait objects.
+trait ChainBufMut: IoBufMut {
+    fn as_dyn_buf(&self) -> &dyn IoBuf;
+
+    fn as_dyn_buf_mut(&mut self) -> &mut dyn IoBufMut;
+}
+
+impl<T: IoBufMut> ChainBufMut for T {
+    fn as_dyn_buf(&self) -> &dyn IoBuf {
+        self
+    }
+
+    fn as_dyn_buf_mut(&mut self) -> &mut dyn IoBufMut {
+        self
+    }
+}
@@ -0,0 +1,180 @@
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+
+use crate::*;
+
+macro_rules! put_int {
+    ($($t:ty => $be:ident, $le:ident;)*) => {
+        $(
+            #[doc = concat!("Append a `", stringify!($t), "` in big-endian byte order.")]
+            fn $be(&mut self, v: $t) -> &mut Self {
+                self.put_slice(&v.to_be_bytes())
+            }
+
+            #[doc = concat!("Append a `", stringify!($t), "` in little-endian byte order.")]
+            fn $le(&mut self, v: $t) -> &mut Self {
+                self.put_slice(&v.to_le_bytes())
+            }
+        )*
+    };
+}
+
+/// Builder methods to encode values into the end of an owned buffer.
+///
+/// ```
+/// use compio_buf::BufPut;
+///
+/// let mut buf = vec![];
+/// buf.put_u8(1)
+///     .put_u16(0x0203)
+///     .put_u32_le(0x07060504)
+///     .put_slice(b"end");
+/// assert_eq!(buf, b"\x01\x02\x03\x04\x05\x06\x07end");
+/// ```
+pub trait BufPut {
+    /// Append the bytes of `src`.
+    fn put_slice(&mut self, src: &[u8]) -> &mut Self;
+
+    /// Append a `u8`.
+    fn put_u8(&mut self, v: u8) -> &mut Self {
+        self.put_slice(&[v])
+    }
+
+    /// Append an `i8`.
+    fn put_i8(&mut self, v: i8) -> &mut Self {
+        self.put_slice(&v.to_be_bytes())
+    }
+
+    put_int! {
+        u16 => put_u16, put_u16_le;
+        i16 => put_i16, put_i16_le;
+        u32 => put_u32, put_u32_le;
+        i32 => put_i32, put_i32_le;
+        u64 => put_u64, put_u64_le;
+        i64 => put_i64, put_i64_le;
+    }
+}
+
+impl<#[cfg(feature = "allocator_api")] A: Allocator + 'static> BufPut for vec_alloc!(u8, A) {
+    fn put_slice(&mut self, src: &[u8]) -> &mut Self {
+        self.extend_from_slice(src);
+        self
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl BufPut for bytes::BytesMut {
+    fn put_slice(&mut self, src: &[u8]) -> &mut Self {
+        self.extend_from_slice(src);
+        self
+    }
+}
+
+macro_rules! get_int {
+    ($($t:ty => $be:ident, $le:ident;)*) => {
+        $(
+            #[doc = concat!("Read a `", stringify!($t), "` in big-endian byte order.")]
+            pub fn $be(&mut self) -> Option<$t> {
+                self.get_array().map(<$t>::from_be_bytes)
+            }
+
+            #[doc = concat!("Read a `", stringify!($t), "` in little-endian byte order.")]
+            pub fn $le(&mut self) -> Option<$t> {
+                self.get_array().map(<$t>::from_le_bytes)
+            }
+        )*
+    };
+}
+
+/// A cursor to decode values from the initialized part of an owned buffer.
+///
+/// All methods return `None` without advancing the cursor if there are not
+/// enough remaining bytes.
+///
+/// ```
+/// use compio_buf::BufCursor;
+///
+/// let mut cursor = BufCursor::new(b"\x01\x02\x03\x04\x05\x06\x07end".to_vec());
+/// assert_eq!(cursor.get_u8(), Some(1));
+/// assert_eq!(cursor.get_u16(), Some(0x0203));
+/// assert_eq!(cursor.get_u32_le(), Some(0x07060504));
+/// assert_eq!(cursor.get_slice(3), Some(&b"end"[..]));
+/// assert_eq!(cursor.get_u8(), None);
+/// ```
+#[derive(Debug)]
+pub struct BufCursor<T> {
+    buffer: T,
+    pos: usize,
+}
+
+impl<T: IoBuf> BufCursor<T> {
+    get_int! {
+        u16 => get_u16, get_u16_le;
+        i16 => get_i16, get_i16_le;
+        u32 => get_u32, get_u32_le;
+        i32 => get_i32, get_i32_le;
+        u64 => get_u64, get_u64_le;
+        i64 => get_i64, get_i64_le;
+    }
+
+    /// Create [`BufCursor`] at the start of `buffer`.
+    pub fn new(buffer: T) -> Self {
+        Self { buffer, pos: 0 }
+    }
+
+    /// Current position in the buffer.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Set the position in the buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pos` is greater than the initialized length.
+    pub fn set_position(&mut self, pos: usize) {
+        assert!(pos <= self.buffer.buf_len());
+        self.pos = pos;
+    }
+
+    /// The bytes not read yet.
+    pub fn remaining(&self) -> &[u8] {
+        &self.buffer.as_slice()[self.pos..]
+    }
+
+    /// Skip `len` bytes.
+    pub fn advance(&mut self, len: usize) -> Option<()> {
+        self.get_slice(len).map(|_| ())
+    }
+
+    /// Read `len` bytes.
+    pub fn get_slice(&mut self, len: usize) -> Option<&[u8]> {
+        let start = self.pos;
+        let end = start.checked_add(len)?;
+        let slice = self.buffer.as_slice().get(start..end)?;
+        self.pos = end;
+        Some(slice)
+    }
+
+    fn get_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.get_slice(N).map(|s| s.try_into().unwrap())
+    }
+
+    /// Read a `u8`.
+    pub fn get_u8(&mut self) -> Option<u8> {
+        self.get_array().map(u8::from_be_bytes)
+    }
+
+    /// Read an `i8`.
+    pub fn get_i8(&mut self) -> Option<i8> {
+        self.get_array().map(i8::from_be_bytes)
+    }
+}
+
+impl<T> IntoInner for BufCursor<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
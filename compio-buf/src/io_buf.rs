@@ -673,7 +673,7 @@ impl<T: IoBufMut, const N: usize> SetBufInit for arrayvec::ArrayVec<T, N> {
     }
 }
 
-unsafe fn default_set_buf_init<'a, B: IoBufMut>(
+pub(crate) unsafe fn default_set_buf_init<'a, B: IoBufMut + ?Sized>(
     iter: impl IntoIterator<Item = &'a mut B>,
     mut len: usize,
 ) {
@@ -29,6 +29,15 @@ pub use io_buf::*;
 mod slice;
 pub use slice::*;
 
+mod raw;
+pub use raw::*;
+
+mod chain;
+pub use chain::*;
+
+mod codec;
+pub use codec::*;
+
 mod iter;
 pub use iter::*;
 
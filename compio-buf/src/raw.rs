@@ -0,0 +1,127 @@
+use std::{
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+};
+
+use crate::*;
+
+/// A buffer backed by externally allocated memory.
+///
+/// This type wraps memory that is not managed by the Rust allocator, such as a
+/// mapped dmabuf, or host memory pinned and registered by a GPU driver, so
+/// that it could be passed to read and send operations directly. The memory
+/// is kept alive by an `owner`, which is dropped together with the buffer.
+///
+/// Whether the kernel could transfer data into such memory without extra
+/// copies depends on the platform and the device driver.
+///
+/// # Examples
+///
+/// ```
+/// use compio_buf::{IoBuf, RawBuf};
+///
+/// let mut mem = vec![0u8; 16];
+/// let ptr = mem.as_mut_ptr();
+/// let buf = unsafe { RawBuf::new(ptr, 0, 16, mem) };
+/// assert_eq!(buf.buf_len(), 0);
+/// assert_eq!(buf.buf_capacity(), 16);
+/// ```
+pub struct RawBuf<O> {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+    owner: O,
+}
+
+impl<O> RawBuf<O> {
+    /// Create [`RawBuf`] from a raw pointer and its owner.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must be non-null, and valid for reads and writes of `capacity`
+    ///   bytes as long as `owner` is alive, even if `owner` is moved.
+    /// * The first `len` bytes must be initialized, and `len <= capacity`.
+    /// * The memory must not be accessed through other pointers while the
+    ///   buffer is used by an operation.
+    pub unsafe fn new(ptr: *mut u8, len: usize, capacity: usize, owner: O) -> Self {
+        debug_assert!(len <= capacity);
+        Self {
+            ptr: NonNull::new_unchecked(ptr),
+            len,
+            capacity,
+            owner,
+        }
+    }
+
+    /// Gets a reference to the owner of the memory.
+    pub fn owner(&self) -> &O {
+        &self.owner
+    }
+
+    /// Gets a mutable reference to the owner of the memory.
+    pub fn owner_mut(&mut self) -> &mut O {
+        &mut self.owner
+    }
+
+    /// Consumes the buffer, returning the pointer, the initialized length and
+    /// the owner.
+    pub fn into_raw_parts(self) -> (*mut u8, usize, O) {
+        (self.ptr.as_ptr(), self.len, self.owner)
+    }
+}
+
+// SAFETY: the memory is only accessed through the buffer, and its lifetime is
+// bound to the owner.
+unsafe impl<O: Send> Send for RawBuf<O> {}
+unsafe impl<O: Sync> Sync for RawBuf<O> {}
+
+impl<O> Deref for RawBuf<O> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<O> DerefMut for RawBuf<O> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<O: 'static> IoBuf for RawBuf<O> {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    fn buf_len(&self) -> usize {
+        self.len
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<O: 'static> IoBufMut for RawBuf<O> {
+    fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr.as_ptr()
+    }
+}
+
+impl<O> SetBufInit for RawBuf<O> {
+    unsafe fn set_buf_init(&mut self, len: usize) {
+        debug_assert!(len <= self.capacity);
+        if self.len < len {
+            self.len = len;
+        }
+    }
+}
+
+impl<O> IntoInner for RawBuf<O> {
+    type Inner = O;
+
+    fn into_inner(self) -> Self::Inner {
+        self.owner
+    }
+}
@@ -22,6 +22,19 @@ use crate::*;
 ///
 /// assert_eq!(&slice[..], b"hello");
 /// ```
+///
+/// Converting a slice back into the full buffer
+///
+/// ```
+/// use compio_buf::{IntoInner, IoBuf};
+///
+/// let buf = b"hello world".to_vec();
+/// let slice = buf.slice(6..);
+/// assert_eq!(&slice[..], b"world");
+///
+/// let buf = slice.into_inner();
+/// assert_eq!(buf, b"hello world");
+/// ```
 pub struct Slice<T> {
     buffer: T,
     begin: usize,
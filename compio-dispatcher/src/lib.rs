@@ -187,6 +187,23 @@ impl DispatcherBuilder {
         self
     }
 
+    /// Override the options with the `COMPIO_*` environment variables. The
+    /// number of worker threads is read from `COMPIO_WORKER_THREADS`, and the
+    /// others are passed to [`ProactorBuilder::load_env`].
+    pub fn load_env(mut self) -> io::Result<Self> {
+        if let Ok(value) = std::env::var("COMPIO_WORKER_THREADS") {
+            let nthreads = value.trim().parse().map_err(|_| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid value of COMPIO_WORKER_THREADS: {value:?}"),
+                )
+            })?;
+            self = self.worker_threads(nthreads);
+        }
+        self.proactor_builder.load_env()?;
+        Ok(self)
+    }
+
     /// Build the [`Dispatcher`].
     pub fn build(self) -> io::Result<Dispatcher> {
         Dispatcher::new_impl(self)
@@ -1,18 +1,65 @@
 use std::{
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
 
-use crossbeam_channel::{bounded, Receiver, Sender, TrySendError};
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TrySendError};
 
 type BoxClosure = Box<dyn FnOnce() + Send>;
 
-struct CounterGuard(Arc<AtomicUsize>);
+/// What [`AsyncifyPool`] does with the closures dispatched when all its
+/// threads are busy and it could not spawn more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QueuePolicy {
+    /// Reject them at once. The blocking operations fail with `EBUSY`, or
+    /// `ERROR_BUSY` on Windows.
+    #[default]
+    Reject,
+    /// Queue at most this number of them, and reject the others.
+    Bounded(usize),
+    /// Queue at most this number of them. The others are rejected by the
+    /// pool, but the runtime waits for the room to submit them again,
+    /// instead of failing the operations.
+    Wait(usize),
+    /// Queue all of them. The operations may wait for a long time under a
+    /// flood, and the queue grows without a limit.
+    Unbounded,
+}
+
+/// A snapshot of the counters of [`AsyncifyPool`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PoolStats {
+    /// The spawned threads.
+    pub threads: usize,
+    /// The threads running a closure.
+    pub active: usize,
+    /// The closures waiting for a thread.
+    pub queued: usize,
+    /// The closures rejected since the pool is created.
+    pub rejected: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    threads: AtomicUsize,
+    active: AtomicUsize,
+    rejected: AtomicU64,
+}
+
+struct CounterGuard<'a>(&'a AtomicUsize);
+
+impl<'a> CounterGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::AcqRel);
+        Self(counter)
+    }
+}
 
-impl Drop for CounterGuard {
+impl Drop for CounterGuard<'_> {
     fn drop(&mut self) {
         self.0.fetch_sub(1, Ordering::AcqRel);
     }
@@ -20,13 +67,13 @@ impl Drop for CounterGuard {
 
 fn worker(
     receiver: Receiver<BoxClosure>,
-    counter: Arc<AtomicUsize>,
+    counters: Arc<Counters>,
     timeout: Duration,
 ) -> impl FnOnce() {
     move || {
-        counter.fetch_add(1, Ordering::AcqRel);
-        let _guard = CounterGuard(counter);
+        let _guard = CounterGuard::new(&counters.threads);
         while let Ok(f) = receiver.recv_timeout(timeout) {
+            let _active = CounterGuard::new(&counters.active);
             f();
         }
     }
@@ -37,48 +84,79 @@ fn worker(
 pub struct AsyncifyPool {
     sender: Sender<BoxClosure>,
     receiver: Receiver<BoxClosure>,
-    counter: Arc<AtomicUsize>,
+    counters: Arc<Counters>,
     thread_limit: usize,
     recv_timeout: Duration,
+    policy: QueuePolicy,
 }
 
 impl AsyncifyPool {
     /// Create [`AsyncifyPool`] with thread number limit and channel receive
     /// timeout.
     pub fn new(thread_limit: usize, recv_timeout: Duration) -> Self {
-        let (sender, receiver) = bounded(0);
+        Self::with_policy(thread_limit, recv_timeout, QueuePolicy::Reject)
+    }
+
+    /// Create [`AsyncifyPool`] like [`AsyncifyPool::new`], with the policy
+    /// when all the threads are busy.
+    pub fn with_policy(thread_limit: usize, recv_timeout: Duration, policy: QueuePolicy) -> Self {
+        let (sender, receiver) = match policy {
+            QueuePolicy::Reject => bounded(0),
+            QueuePolicy::Bounded(len) | QueuePolicy::Wait(len) => bounded(len),
+            QueuePolicy::Unbounded => unbounded(),
+        };
         Self {
             sender,
             receiver,
-            counter: Arc::new(AtomicUsize::new(0)),
+            counters: Arc::default(),
             thread_limit,
             recv_timeout,
+            policy,
+        }
+    }
+
+    /// The policy when all the threads are busy.
+    pub fn policy(&self) -> QueuePolicy {
+        self.policy
+    }
+
+    /// Get the current counters of the pool.
+    pub fn stats(&self) -> PoolStats {
+        PoolStats {
+            threads: self.counters.threads.load(Ordering::Acquire),
+            active: self.counters.active.load(Ordering::Acquire),
+            queued: self.sender.len(),
+            rejected: self.counters.rejected.load(Ordering::Acquire),
         }
     }
 
     /// Send a closure to another thread. Usually the user should not use it.
     pub fn dispatch<F: FnOnce() + Send + 'static>(&self, f: F) -> Result<(), F> {
-        match self.sender.try_send(Box::new(f) as BoxClosure) {
-            Ok(_) => Ok(()),
-            Err(e) => match e {
-                TrySendError::Full(f) => {
-                    if self.counter.load(Ordering::Acquire) >= self.thread_limit {
-                        // Safety: we can ensure the type
-                        Err(*unsafe { Box::from_raw(Box::into_raw(f).cast()) })
-                    } else {
-                        std::thread::spawn(worker(
-                            self.receiver.clone(),
-                            self.counter.clone(),
-                            self.recv_timeout,
-                        ));
-                        self.sender.send(f).expect("the channel should not be full");
-                        Ok(())
-                    }
-                }
-                TrySendError::Disconnected(_) => {
+        let threads = self.counters.threads.load(Ordering::Acquire);
+        let idle = threads > self.counters.active.load(Ordering::Acquire);
+        let mut f = Box::new(f) as BoxClosure;
+        // Don't queue the closure if another thread could run it now.
+        if idle || threads >= self.thread_limit {
+            match self.sender.try_send(f) {
+                Ok(_) => return Ok(()),
+                Err(TrySendError::Full(rejected)) => f = rejected,
+                Err(TrySendError::Disconnected(_)) => {
                     unreachable!("receiver should not all disconnected")
                 }
-            },
+            }
+        }
+        if self.counters.threads.load(Ordering::Acquire) >= self.thread_limit {
+            self.counters.rejected.fetch_add(1, Ordering::AcqRel);
+            // Safety: we can ensure the type
+            Err(*unsafe { Box::from_raw(Box::into_raw(f).cast()) })
+        } else {
+            std::thread::spawn(worker(
+                self.receiver.clone(),
+                self.counters.clone(),
+                self.recv_timeout,
+            ));
+            self.sender.send(f).expect("the channel should not be full");
+            Ok(())
         }
     }
 }
@@ -0,0 +1,333 @@
+use std::{
+    alloc::{alloc, dealloc, Layout},
+    cell::RefCell,
+    fmt, io,
+    ops::{Deref, DerefMut},
+    ptr::NonNull,
+    rc::Rc,
+};
+
+use compio_buf::{IoBuf, IoBufMut, SetBufInit};
+
+/// A pool of buffers of the same length, for the receives which pick a buffer
+/// only when the data arrives.
+///
+/// On io-uring driver, the buffers are provided to the kernel as a ring, and
+/// a pending [`RecvMulti`](crate::op::RecvMulti) selects one for each packet
+/// of data, so that an idle connection doesn't hold any buffer. On other
+/// drivers, or on the kernels before 5.19, the buffers are
+/// [taken](BufferPool::take) by the caller for the ordinary receives.
+///
+/// It is created by
+/// [`Proactor::create_buffer_pool`](crate::Proactor::create_buffer_pool)
+/// and cloned cheaply. The buffers are returned to the pool when the
+/// [`BorrowedBuffer`]s are dropped.
+#[derive(Clone)]
+pub struct BufferPool {
+    inner: Rc<Inner>,
+}
+
+struct Inner {
+    buffers: NonNull<u8>,
+    buffer_len: usize,
+    entries: u16,
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    ring: Option<ring::BufRing>,
+    // The buffers not borrowed, if they are not provided to the kernel.
+    free: RefCell<Vec<u16>>,
+}
+
+impl BufferPool {
+    // Only the pools of io-uring driver are provided to the kernel.
+    #[allow(dead_code)]
+    pub(crate) fn new(entries: u16, buffer_len: usize) -> io::Result<Self> {
+        if !entries.is_power_of_two() || entries > 1 << 15 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the entries should be a power of 2, at most 32768",
+            ));
+        }
+        if buffer_len == 0 || buffer_len > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the buffer length should be in (0, u32::MAX]",
+            ));
+        }
+        let layout = Self::layout(entries, buffer_len)?;
+        let buffers = NonNull::new(unsafe { alloc(layout) })
+            .ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))?;
+        Ok(Self {
+            inner: Rc::new(Inner {
+                buffers,
+                buffer_len,
+                entries,
+                #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                ring: None,
+                free: RefCell::new((0..entries).rev().collect()),
+            }),
+        })
+    }
+
+    /// Create a pool providing its buffers to the kernel as the ring of
+    /// `bgid`, which should be registered after.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn with_ring(entries: u16, buffer_len: usize, bgid: u16) -> io::Result<Self> {
+        let mut pool = Self::new(entries, buffer_len)?;
+        let inner = Rc::get_mut(&mut pool.inner).expect("the pool should be unique");
+        let ring = ring::BufRing::new(entries, bgid)?;
+        for bid in std::mem::take(inner.free.get_mut()) {
+            ring.push(bid, inner.buffer(bid), buffer_len);
+        }
+        inner.ring = Some(ring);
+        Ok(pool)
+    }
+
+    fn layout(entries: u16, buffer_len: usize) -> io::Result<Layout> {
+        buffer_len
+            .checked_mul(entries as usize)
+            .and_then(|size| Layout::from_size_align(size, 1).ok())
+            .ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))
+    }
+
+    /// The number of the buffers.
+    pub fn entries(&self) -> u16 {
+        self.inner.entries
+    }
+
+    /// The length of each buffer.
+    pub fn buffer_len(&self) -> usize {
+        self.inner.buffer_len
+    }
+
+    /// Whether the buffers are provided to the kernel, and could only be
+    /// selected by [`RecvMulti`](crate::op::RecvMulti).
+    pub fn is_provided(&self) -> bool {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            self.inner.ring.is_some()
+        }
+        #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+        {
+            false
+        }
+    }
+
+    /// Take an empty buffer to receive into, or `None` if all of them are
+    /// borrowed. It always returns `None` if the buffers are provided to the
+    /// kernel.
+    pub fn take(&self) -> Option<BorrowedBuffer> {
+        let bid = self.inner.free.borrow_mut().pop()?;
+        Some(BorrowedBuffer {
+            pool: self.clone(),
+            bid,
+            len: 0,
+        })
+    }
+
+    /// Take the buffer selected by the kernel for a completion with the
+    /// result `res` and `flags`, or `None` if no buffer is selected.
+    ///
+    /// # Safety
+    ///
+    /// The completion should be of an operation selecting a buffer from this
+    /// pool, and the buffer should not have been taken.
+    pub unsafe fn take_selected(&self, res: usize, flags: u32) -> Option<BorrowedBuffer> {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            self.inner.ring.as_ref()?;
+            let bid = io_uring::cqueue::buffer_select(flags)?;
+            Some(BorrowedBuffer {
+                pool: self.clone(),
+                bid,
+                len: res.min(self.inner.buffer_len),
+            })
+        }
+        #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+        {
+            let _ = (res, flags);
+            None
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn ring_id(&self) -> Option<(u64, u16, u16)> {
+        self.inner
+            .ring
+            .as_ref()
+            .map(|ring| (ring.addr(), self.inner.entries, ring.bgid()))
+    }
+
+    #[allow(dead_code)]
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    fn reuse(&self, bid: u16) {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        if let Some(ring) = &self.inner.ring {
+            ring.push(bid, self.inner.buffer(bid), self.inner.buffer_len);
+            return;
+        }
+        self.inner.free.borrow_mut().push(bid);
+    }
+}
+
+impl Inner {
+    fn buffer(&self, bid: u16) -> *mut u8 {
+        unsafe { self.buffers.as_ptr().add(bid as usize * self.buffer_len) }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let layout =
+            BufferPool::layout(self.entries, self.buffer_len).expect("the layout should be valid");
+        unsafe { dealloc(self.buffers.as_ptr(), layout) }
+    }
+}
+
+impl fmt::Debug for BufferPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufferPool")
+            .field("entries", &self.entries())
+            .field("buffer_len", &self.buffer_len())
+            .field("provided", &self.is_provided())
+            .finish()
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`], which is returned to the pool
+/// when dropped.
+pub struct BorrowedBuffer {
+    pool: BufferPool,
+    bid: u16,
+    len: usize,
+}
+
+impl Deref for BorrowedBuffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.pool.inner.buffer(self.bid), self.len) }
+    }
+}
+
+impl DerefMut for BorrowedBuffer {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        unsafe { std::slice::from_raw_parts_mut(self.pool.inner.buffer(self.bid), self.len) }
+    }
+}
+
+impl IoBuf for BorrowedBuffer {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.pool.inner.buffer(self.bid)
+    }
+
+    fn buf_len(&self) -> usize {
+        self.len
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.pool.buffer_len()
+    }
+}
+
+impl IoBufMut for BorrowedBuffer {
+    fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+        self.pool.inner.buffer(self.bid)
+    }
+}
+
+impl SetBufInit for BorrowedBuffer {
+    unsafe fn set_buf_init(&mut self, len: usize) {
+        if self.len < len {
+            self.len = len.min(self.pool.buffer_len());
+        }
+    }
+}
+
+impl Drop for BorrowedBuffer {
+    fn drop(&mut self) {
+        self.pool.reuse(self.bid);
+    }
+}
+
+impl fmt::Debug for BorrowedBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BorrowedBuffer")
+            .field("bid", &self.bid)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod ring {
+    use std::{
+        alloc::{alloc_zeroed, dealloc, Layout},
+        cell::Cell,
+        io,
+        ptr::NonNull,
+        sync::atomic::{AtomicU16, Ordering},
+    };
+
+    use io_uring::types::BufRingEntry;
+
+    // The ring shared with the kernel, which should be page aligned.
+    pub struct BufRing {
+        entries: NonNull<BufRingEntry>,
+        layout: Layout,
+        mask: u16,
+        bgid: u16,
+        tail: Cell<u16>,
+    }
+
+    impl BufRing {
+        pub fn new(entries: u16, bgid: u16) -> io::Result<Self> {
+            let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(4096) as usize;
+            let layout =
+                Layout::from_size_align(entries as usize * size_of::<BufRingEntry>(), page)
+                    .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            let entries_ptr = NonNull::new(unsafe { alloc_zeroed(layout) } as *mut BufRingEntry)
+                .ok_or_else(|| io::Error::from(io::ErrorKind::OutOfMemory))?;
+            Ok(Self {
+                entries: entries_ptr,
+                layout,
+                mask: entries - 1,
+                bgid,
+                tail: Cell::new(0),
+            })
+        }
+
+        pub fn addr(&self) -> u64 {
+            self.entries.as_ptr() as u64
+        }
+
+        pub fn bgid(&self) -> u16 {
+            self.bgid
+        }
+
+        // Provide a buffer to the kernel.
+        pub fn push(&self, bid: u16, addr: *mut u8, len: usize) {
+            let tail = self.tail.get();
+            unsafe {
+                let entry = &mut *self.entries.as_ptr().add((tail & self.mask) as usize);
+                entry.set_addr(addr as u64);
+                entry.set_len(len as u32);
+                entry.set_bid(bid);
+            }
+            let tail = tail.wrapping_add(1);
+            self.tail.set(tail);
+            // The tail overlays the reserved field of the first entry.
+            let shared =
+                unsafe { &*(BufRingEntry::tail(self.entries.as_ptr()) as *const AtomicU16) };
+            shared.store(tail, Ordering::Release);
+        }
+    }
+
+    impl Drop for BufRing {
+        fn drop(&mut self) {
+            unsafe { dealloc(self.entries.as_ptr() as *mut u8, self.layout) }
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Read the `COMPIO_*` environment variables.
+
+use std::{env, io, str::FromStr};
+
+/// Read and parse the environment variable `name`, if it is set.
+pub(crate) fn var<T: FromStr>(name: &str) -> io::Result<Option<T>> {
+    match env::var(name) {
+        Ok(value) => value.trim().parse().map(Some).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid value of {name}: {value:?}"),
+            )
+        }),
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(_)) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{name} is not unicode"),
+        )),
+    }
+}
+
+/// Read a boolean environment variable, `1`, `0`, `true` or `false`.
+pub(crate) fn flag(name: &str) -> io::Result<bool> {
+    match var::<String>(name)?.as_deref() {
+        None | Some("0" | "false") => Ok(false),
+        Some("1" | "true") => Ok(true),
+        Some(value) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid value of {name}: {value:?}"),
+        )),
+    }
+}
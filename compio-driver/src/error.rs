@@ -0,0 +1,191 @@
+use std::{
+    error, fmt, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+};
+
+use crate::RawFd;
+
+/// An IO error with the context of the failed operation: the kind of the
+/// operation, the fd, the path and the peer address.
+///
+/// The APIs still return [`io::Error`] with the OS error code, and the
+/// context is opted in by the callers with [`ResultExt::context`]. It
+/// converts into an [`io::Error`] of the same
+/// kind, which displays the context and could be recovered with
+/// [`Error::get`]. The converted error has no
+/// [`raw_os_error`](io::Error::raw_os_error) of its own, and the OS error
+/// code is kept in its source, as returned by [`Error::os_error`]:
+///
+/// ```
+/// use std::io;
+///
+/// use compio_driver::{Error, ResultExt};
+///
+/// fn recv() -> io::Result<usize> {
+///     let res: io::Result<usize> = Err(io::ErrorKind::ConnectionReset.into());
+///     let len = res
+///         .context("recv")
+///         .map_err(|e| e.with_peer("1.2.3.4:80".parse().unwrap()))?;
+///     Ok(len)
+/// }
+///
+/// let err = recv().unwrap_err();
+/// assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+/// assert_eq!(err.to_string(), "recv (peer 1.2.3.4:80): connection reset");
+/// assert_eq!(Error::get(&err).unwrap().op(), "recv");
+/// ```
+pub struct Error {
+    op: &'static str,
+    fd: Option<RawFd>,
+    path: Option<PathBuf>,
+    peer: Option<SocketAddr>,
+    source: io::Error,
+}
+
+// The handle is only kept to be displayed, and never used.
+#[cfg(windows)]
+unsafe impl Send for Error {}
+#[cfg(windows)]
+unsafe impl Sync for Error {}
+
+impl Error {
+    /// Create an error of the operation `op`, e.g. `"recv"`.
+    pub fn new(op: &'static str, source: io::Error) -> Self {
+        Self {
+            op,
+            fd: None,
+            path: None,
+            peer: None,
+            source,
+        }
+    }
+
+    /// Set the fd of the operation.
+    pub fn with_fd(mut self, fd: RawFd) -> Self {
+        self.fd = Some(fd);
+        self
+    }
+
+    /// Set the path of the operation, e.g. the opened file or the Unix
+    /// socket address.
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Set the peer address of the operation.
+    pub fn with_peer(mut self, peer: SocketAddr) -> Self {
+        self.peer = Some(peer);
+        self
+    }
+
+    /// The kind of the operation.
+    pub fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// The fd of the operation.
+    pub fn fd(&self) -> Option<RawFd> {
+        self.fd
+    }
+
+    /// The path of the operation.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// The peer address of the operation.
+    pub fn peer(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+
+    /// The kind of the underlying error.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.source.kind()
+    }
+
+    /// The OS error code of the underlying error.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        self.source.raw_os_error()
+    }
+
+    /// The underlying error.
+    pub fn io_error(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Unwrap the underlying error, dropping the context.
+    pub fn into_io_error(self) -> io::Error {
+        self.source
+    }
+
+    /// Get the context of an [`io::Error`] converted from [`Error`].
+    pub fn get(err: &io::Error) -> Option<&Self> {
+        err.get_ref()?.downcast_ref()
+    }
+
+    /// The OS error code of an [`io::Error`], which may be converted from
+    /// [`Error`].
+    pub fn os_error(err: &io::Error) -> Option<i32> {
+        err.raw_os_error()
+            .or_else(|| Self::get(err)?.raw_os_error())
+    }
+}
+
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("Error");
+        s.field("op", &self.op);
+        if let Some(fd) = &self.fd {
+            s.field("fd", fd);
+        }
+        if let Some(path) = &self.path {
+            s.field("path", path);
+        }
+        if let Some(peer) = &self.peer {
+            s.field("peer", peer);
+        }
+        s.field("source", &self.source).finish()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.op)?;
+        if let Some(fd) = &self.fd {
+            write!(f, " on fd {fd:?}")?;
+        }
+        match (&self.path, &self.peer) {
+            (Some(path), Some(peer)) => write!(f, " ({}, peer {peer})", path.display())?,
+            (Some(path), None) => write!(f, " ({})", path.display())?,
+            (None, Some(peer)) => write!(f, " (peer {peer})")?,
+            (None, None) => {}
+        }
+        write!(f, ": {}", self.source)
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        io::Error::new(err.kind(), err)
+    }
+}
+
+/// Attach the context of the operation to the errors.
+pub trait ResultExt<T> {
+    /// Wrap the error into [`Error`] of the operation `op`.
+    fn context(self, op: &'static str) -> Result<T, Error>;
+}
+
+impl<T> ResultExt<T> for io::Result<T> {
+    fn context(self, op: &'static str) -> Result<T, Error> {
+        self.map_err(|e| Error::new(op, e))
+    }
+}
@@ -14,10 +14,9 @@ pub use driver_type::DriverType;
 pub(crate) use iour::{sockaddr_storage, socklen_t};
 pub use iour::{OpCode as IourOpCode, OpEntry};
 pub use poll::{Decision, OpCode as PollOpCode};
-use slab::Slab;
 
 pub(crate) use crate::unix::RawOp;
-use crate::{OutEntries, ProactorBuilder};
+use crate::{AsyncifyPool, BufferPool, OutEntries, ProactorBuilder, Registry};
 
 mod driver_type {
     use std::sync::atomic::{AtomicU8, Ordering};
@@ -79,12 +78,19 @@ mod driver_type {
             Fsync::CODE,
             Accept::CODE,
             Connect::CODE,
+            Recv::CODE,
+            Send::CODE,
             RecvMsg::CODE,
             SendMsg::CODE,
             AsyncCancel::CODE,
             OpenAt::CODE,
             Close::CODE,
             Shutdown::CODE,
+            Splice::CODE,
+            // The multishot ops share the opcodes of the oneshot ones, and
+            // fall back to them on the kernels without the multishot flags.
+            AcceptMulti::CODE,
+            RecvMulti::CODE,
             // Linux kernel 5.19
             #[cfg(any(feature = "io-uring-seq128", feature = "io-uring-cqe32"))]
             Socket::CODE,
@@ -101,6 +107,13 @@ mod driver_type {
     }
 }
 
+pub(crate) fn sock_nonempty(flags: u32) -> Option<bool> {
+    match DriverType::current() {
+        DriverType::Poll => None,
+        DriverType::IoUring => iour::sock_nonempty(flags),
+    }
+}
+
 /// Fused [`OpCode`]
 ///
 /// This trait encapsulates both operation for `io-uring` and `polling`
@@ -139,14 +152,25 @@ impl Driver {
         }
     }
 
-    pub fn cancel(&mut self, user_data: usize, registry: &mut Slab<RawOp>) {
+    pub fn cancel(&mut self, user_data: usize, registry: &mut Registry) {
         match &mut self.fuse {
             FuseDriver::Poll(driver) => driver.cancel(user_data, registry),
             FuseDriver::IoUring(driver) => driver.cancel(user_data, registry),
         }
     }
 
-    pub fn push(&mut self, user_data: usize, op: &mut RawOp) -> Poll<io::Result<usize>> {
+    pub fn interrupt(&mut self, user_data: usize, registry: &mut Registry) {
+        match &mut self.fuse {
+            FuseDriver::Poll(driver) => driver.interrupt(user_data, registry),
+            FuseDriver::IoUring(driver) => driver.interrupt(user_data, registry),
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        user_data: usize,
+        op: &mut RawOp,
+    ) -> io::Result<Poll<io::Result<usize>>> {
         match &mut self.fuse {
             FuseDriver::Poll(driver) => driver.push(user_data, op),
             FuseDriver::IoUring(driver) => driver.push(user_data, op),
@@ -164,6 +188,31 @@ impl Driver {
         }
     }
 
+    pub fn thread_pool(&self) -> &AsyncifyPool {
+        match &self.fuse {
+            FuseDriver::Poll(driver) => driver.thread_pool(),
+            FuseDriver::IoUring(driver) => driver.thread_pool(),
+        }
+    }
+
+    pub fn create_buffer_pool(
+        &mut self,
+        entries: u16,
+        buffer_len: usize,
+    ) -> io::Result<BufferPool> {
+        match &mut self.fuse {
+            FuseDriver::Poll(driver) => driver.create_buffer_pool(entries, buffer_len),
+            FuseDriver::IoUring(driver) => driver.create_buffer_pool(entries, buffer_len),
+        }
+    }
+
+    pub fn release_buffer_pool(&mut self, pool: BufferPool) -> io::Result<()> {
+        match &mut self.fuse {
+            FuseDriver::Poll(driver) => driver.release_buffer_pool(pool),
+            FuseDriver::IoUring(driver) => driver.release_buffer_pool(pool),
+        }
+    }
+
     pub fn handle(&self) -> io::Result<NotifyHandle> {
         let fuse = match &self.fuse {
             FuseDriver::Poll(driver) => FuseNotifyHandle::Poll(driver.handle()?),
@@ -92,8 +92,55 @@ mod iour { pub use crate::sys::iour::{op::*, OpCode}; }
 mod poll { pub use crate::sys::poll::{op::*, OpCode}; }
 
 op!(<T: IoBufMut> RecvFrom(fd: RawFd, buffer: T));
+impl<T: IoBufMut> RecvFrom<T> {
+    /// Create a new `RecvFrom` with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
+        match DriverType::current() {
+            DriverType::Poll => Self {
+                inner: RecvFromInner::Poll(poll::RecvFrom::with_flags(fd, buffer, flags)),
+            },
+            DriverType::IoUring => Self {
+                inner: RecvFromInner::IoUring(iour::RecvFrom::with_flags(fd, buffer, flags)),
+            },
+        }
+    }
+}
 op!(<T: IoBuf> SendTo(fd: RawFd, buffer: T, addr: SockAddr));
+impl<T: IoBuf> SendTo<T> {
+    /// Create a new `SendTo` with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, addr: SockAddr, flags: i32) -> Self {
+        match DriverType::current() {
+            DriverType::Poll => Self {
+                inner: SendToInner::Poll(poll::SendTo::with_flags(fd, buffer, addr, flags)),
+            },
+            DriverType::IoUring => Self {
+                inner: SendToInner::IoUring(iour::SendTo::with_flags(fd, buffer, addr, flags)),
+            },
+        }
+    }
+}
+
+op!(<T: IoBuf> ConnectWithData(fd: RawFd, buffer: T, addr: SockAddr));
 op!(<T: IoVectoredBufMut> RecvFromVectored(fd: RawFd, buffer: T));
 op!(<T: IoVectoredBuf> SendToVectored(fd: RawFd, buffer: T, addr: SockAddr));
 op!(<> FileStat(fd: RawFd));
 op!(<> PathStat(path: CString, follow_symlink: bool));
+
+impl PathStat {
+    /// Create [`PathStat`] with a relative path resolved from the directory
+    /// `dirfd`.
+    pub fn with_dirfd(dirfd: RawFd, path: CString, follow_symlink: bool) -> Self {
+        match DriverType::current() {
+            DriverType::Poll => Self {
+                inner: PathStatInner::Poll(poll::PathStat::with_dirfd(dirfd, path, follow_symlink)),
+            },
+            DriverType::IoUring => Self {
+                inner: PathStatInner::IoUring(iour::PathStat::with_dirfd(
+                    dirfd,
+                    path,
+                    follow_symlink,
+                )),
+            },
+        }
+    }
+}
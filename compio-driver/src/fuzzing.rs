@@ -0,0 +1,153 @@
+//! The entry points of the fuzz targets.
+//!
+//! They drive the same code as the [`Proactor`](crate::Proactor), without a
+//! kernel, so the op lifecycle could be fuzzed anywhere.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use compio_buf::BufResult;
+
+use crate::{cancel_op, complete_op, op::Asyncify, Entry, RawOp, Registry};
+
+type Op = Asyncify<Box<dyn FnOnce() -> BufResult<usize, ()> + Send + Sync>, ()>;
+
+struct Guard(Arc<AtomicUsize>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    // Submitted to the kernel, and the future is waiting.
+    Pending,
+    // Submitted to the kernel, and the future is dropped.
+    Cancelled,
+    // Completed, and the future hasn't popped it.
+    Completed,
+}
+
+/// Feed an arbitrary sequence of submissions, completions, cancellations
+/// and pops into the op registry.
+///
+/// Each pair of bytes is an action and the index of the op it applies to.
+/// The sequence is kept valid: the kernel completes an op once, and the
+/// runtime pops or cancels an op once. The late completions and
+/// cancellations of the removed ops are mixed in, and should be ignored. It
+/// panics if an op is leaked, dropped twice or misattributed.
+pub fn op_lifecycle(data: &[u8]) {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let mut registry = Registry::with_capacity(0);
+    let mut ops = Vec::<(usize, State)>::new();
+    let mut removed = Vec::<usize>::new();
+    let mut pushed = 0;
+
+    let pick = |ops: &[(usize, State)], index: u8, states: &[State]| {
+        let candidates = ops
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, state))| states.contains(state))
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        (!candidates.is_empty()).then(|| candidates[index as usize % candidates.len()])
+    };
+
+    for chunk in data.chunks_exact(2) {
+        let (action, index) = (chunk[0], chunk[1]);
+        match action % 5 {
+            0 => {
+                let guard = Guard(dropped.clone());
+                let f: Box<dyn FnOnce() -> BufResult<usize, ()> + Send + Sync> =
+                    Box::new(move || {
+                        drop(guard);
+                        BufResult(Ok(0), ())
+                    });
+                let (user_data, _) =
+                    registry.insert(|user_data| RawOp::from_box(user_data, Box::new(Op::new(f))));
+                assert!(
+                    !removed.contains(&user_data),
+                    "the user data {user_data} is reused"
+                );
+                ops.push((user_data, State::Pending));
+                pushed += 1;
+            }
+            1 => {
+                let Some(i) = pick(&ops, index, &[State::Pending, State::Cancelled]) else {
+                    continue;
+                };
+                let (user_data, state) = ops[i];
+                let res = Ok(index as usize);
+                match complete_op(&mut registry, Entry::new(user_data, res)) {
+                    Some(key) => {
+                        assert_eq!(key, user_data);
+                        assert_eq!(state, State::Pending);
+                        ops[i].1 = State::Completed;
+                    }
+                    None => {
+                        assert_eq!(state, State::Cancelled);
+                        assert!(!registry.contains(user_data));
+                        removed.push(ops.remove(i).0);
+                    }
+                }
+            }
+            2 => {
+                let Some(i) = pick(&ops, index, &[State::Pending, State::Completed]) else {
+                    continue;
+                };
+                let (user_data, state) = ops[i];
+                if cancel_op(&mut registry, user_data) {
+                    assert_eq!(state, State::Completed);
+                    removed.push(ops.remove(i).0);
+                } else {
+                    assert_eq!(state, State::Pending);
+                    ops[i].1 = State::Cancelled;
+                }
+            }
+            3 => {
+                let Some(i) = pick(&ops, index, &[State::Completed]) else {
+                    continue;
+                };
+                let (user_data, _) = ops.remove(i);
+                pop(&mut registry, user_data);
+                removed.push(user_data);
+            }
+            _ => {
+                if removed.is_empty() {
+                    continue;
+                }
+                let user_data = removed[index as usize % removed.len()];
+                let res = Err(std::io::Error::from_raw_os_error(0));
+                assert_eq!(complete_op(&mut registry, Entry::new(user_data, res)), None);
+                assert!(!cancel_op(&mut registry, user_data));
+                assert!(!registry.contains(user_data));
+            }
+        }
+    }
+
+    // The kernel completes the rest, and the runtime pops them.
+    for (user_data, state) in ops {
+        if state == State::Completed
+            || complete_op(&mut registry, Entry::new(user_data, Ok(0))).is_some()
+        {
+            pop(&mut registry, user_data);
+        }
+    }
+    assert!(registry.is_empty(), "the registry is not empty");
+    assert_eq!(
+        dropped.load(Ordering::Relaxed),
+        pushed,
+        "the ops are leaked"
+    );
+}
+
+fn pop(registry: &mut Registry, user_data: usize) {
+    let op = registry.try_remove(user_data).expect("the op should exist");
+    let BufResult(res, op) = unsafe { op.into_box::<Op>() };
+    res.expect("the op should succeed");
+    drop(op);
+}
@@ -15,7 +15,6 @@ use std::{
 
 use compio_buf::{arrayvec::ArrayVec, BufResult};
 use compio_log::{instrument, trace};
-use slab::Slab;
 use windows_sys::Win32::{
     Foundation::{
         RtlNtStatusToDosError, ERROR_BAD_COMMAND, ERROR_BUSY, ERROR_HANDLE_EOF,
@@ -35,7 +34,7 @@ use windows_sys::Win32::{
     },
 };
 
-use crate::{syscall, AsyncifyPool, Entry, OutEntries, ProactorBuilder};
+use crate::{syscall, AsyncifyPool, BufferPool, Entry, OutEntries, ProactorBuilder, Registry};
 
 pub(crate) mod op;
 
@@ -258,7 +257,7 @@ impl Driver {
         Ok(())
     }
 
-    pub fn cancel(&mut self, user_data: usize, registry: &mut Slab<RawOp>) {
+    pub fn cancel(&mut self, user_data: usize, registry: &mut Registry) {
         instrument!(compio_log::Level::TRACE, "cancel", user_data);
         trace!("cancel RawOp");
         self.cancelled.insert(user_data);
@@ -271,23 +270,37 @@ impl Driver {
         }
     }
 
-    pub fn push(&mut self, user_data: usize, op: &mut RawOp) -> Poll<io::Result<usize>> {
+    pub fn interrupt(&mut self, user_data: usize, registry: &mut Registry) {
+        instrument!(compio_log::Level::TRACE, "interrupt", user_data);
+        if let Some(op) = registry.get_mut(user_data) {
+            let overlapped_ptr = op.as_mut_ptr();
+            let op = op.as_op_pin();
+            // The op completes with `ERROR_OPERATION_ABORTED` if cancelled.
+            unsafe { op.cancel(overlapped_ptr.cast()) }.ok();
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        user_data: usize,
+        op: &mut RawOp,
+    ) -> io::Result<Poll<io::Result<usize>>> {
         instrument!(compio_log::Level::TRACE, "push", user_data);
         if self.cancelled.remove(&user_data) {
             trace!("pushed RawOp already cancelled");
-            Poll::Ready(Err(io::Error::from_raw_os_error(
+            Ok(Poll::Ready(Err(io::Error::from_raw_os_error(
                 ERROR_OPERATION_ABORTED as _,
-            )))
+            ))))
         } else {
             trace!("push RawOp");
             let optr = op.as_mut_ptr();
             let op_pin = op.as_op_pin();
             if op_pin.is_overlapped() {
-                unsafe { op_pin.operate(optr.cast()) }
+                Ok(unsafe { op_pin.operate(optr.cast()) })
             } else if self.push_blocking(op) {
-                Poll::Pending
+                Ok(Poll::Pending)
             } else {
-                Poll::Ready(Err(io::Error::from_raw_os_error(ERROR_BUSY as _)))
+                Err(io::Error::from_raw_os_error(ERROR_BUSY as _))
             }
         }
     }
@@ -360,6 +373,22 @@ impl Driver {
         Ok(())
     }
 
+    pub fn thread_pool(&self) -> &AsyncifyPool {
+        &self.pool
+    }
+
+    pub fn create_buffer_pool(
+        &mut self,
+        entries: u16,
+        buffer_len: usize,
+    ) -> io::Result<BufferPool> {
+        BufferPool::new(entries, buffer_len)
+    }
+
+    pub fn release_buffer_pool(&mut self, _pool: BufferPool) -> io::Result<()> {
+        Ok(())
+    }
+
     pub fn handle(&self) -> io::Result<NotifyHandle> {
         self.handle_for(Self::NOTIFY)
     }
@@ -438,6 +467,7 @@ pub(crate) struct RawOp {
     // completes; the runtime holds the strong ref until the future is dropped.
     cancelled: bool,
     result: Option<io::Result<usize>>,
+    flags: u32,
 }
 
 impl RawOp {
@@ -448,6 +478,7 @@ impl RawOp {
             op: unsafe { NonNull::new_unchecked(Box::into_raw(op)) },
             cancelled: false,
             result: None,
+            flags: 0,
         }
     }
 
@@ -473,6 +504,14 @@ impl RawOp {
         self.result.is_some()
     }
 
+    pub fn set_flags(&mut self, flags: u32) {
+        self.flags = flags;
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
     /// # Safety
     /// The caller should ensure the correct type.
     ///
@@ -483,6 +522,21 @@ impl RawOp {
         let overlapped: Box<Overlapped<T>> = Box::from_raw(this.op.cast().as_ptr());
         BufResult(this.result.take().unwrap(), overlapped.op)
     }
+
+    // The operation is wrapped in `Overlapped`, so the allocation can't be
+    // reused.
+    pub(crate) fn from_box<T: OpCode + 'static>(user_data: usize, op: Box<T>) -> Self {
+        Self::new(user_data, *op)
+    }
+
+    /// # Safety
+    /// The caller should ensure the correct type.
+    ///
+    /// # Panics
+    /// This function will panic if the result has not been set.
+    pub unsafe fn into_box<T: OpCode>(self) -> BufResult<usize, Box<T>> {
+        self.into_inner::<T>().map_buffer(Box::new)
+    }
 }
 
 impl Drop for RawOp {
@@ -11,7 +11,7 @@ use std::{
 };
 
 use aligned_array::{Aligned, A8};
-use compio_buf::{BufResult, IntoInner, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use compio_buf::{BufResult, IntoInner, IoBuf, IoBufMut, IoSlice, IoVectoredBuf, IoVectoredBufMut};
 #[cfg(not(feature = "once_cell_try"))]
 use once_cell::sync::OnceCell as OnceLock;
 use socket2::SockAddr;
@@ -26,10 +26,11 @@ use windows_sys::{
         },
         Networking::WinSock::{
             closesocket, setsockopt, shutdown, socklen_t, WSAIoctl, WSARecv, WSARecvFrom, WSASend,
-            WSASendTo, LPFN_ACCEPTEX, LPFN_CONNECTEX, LPFN_GETACCEPTEXSOCKADDRS, SD_BOTH,
-            SD_RECEIVE, SD_SEND, SIO_GET_EXTENSION_FUNCTION_POINTER, SOCKADDR, SOCKADDR_STORAGE,
-            SOL_SOCKET, SO_UPDATE_ACCEPT_CONTEXT, SO_UPDATE_CONNECT_CONTEXT, WSAID_ACCEPTEX,
-            WSAID_CONNECTEX, WSAID_GETACCEPTEXSOCKADDRS,
+            WSASendMsg, WSASendTo, LPFN_ACCEPTEX, LPFN_CONNECTEX, LPFN_GETACCEPTEXSOCKADDRS,
+            LPFN_TRANSMITFILE, SD_BOTH, SD_RECEIVE, SD_SEND, SIO_GET_EXTENSION_FUNCTION_POINTER,
+            SOCKADDR, SOCKADDR_STORAGE, SOL_SOCKET, SO_UPDATE_ACCEPT_CONTEXT,
+            SO_UPDATE_CONNECT_CONTEXT, WSABUF, WSAID_ACCEPTEX, WSAID_CONNECTEX,
+            WSAID_GETACCEPTEXSOCKADDRS, WSAID_TRANSMITFILE, WSAMSG,
         },
         Security::SECURITY_ATTRIBUTES,
         Storage::FileSystem::{
@@ -199,6 +200,20 @@ impl OpCode for OpenFile {
     }
 }
 
+impl<T: OpCode> OpCode for Hinted<T> {
+    fn is_overlapped(&self) -> bool {
+        self.op.is_overlapped()
+    }
+
+    unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        self.op_pin().operate(optr)
+    }
+
+    unsafe fn cancel(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> io::Result<()> {
+        self.op_pin().cancel(optr)
+    }
+}
+
 impl OpCode for CloseFile {
     fn is_overlapped(&self) -> bool {
         false
@@ -603,6 +618,129 @@ impl OpCode for Accept {
     }
 }
 
+/// Accept a connection and receive the first block of data with `AcceptEx`.
+///
+/// The data is received at the start of the buffer, and the local and remote
+/// addresses are stored at the end of its capacity, so the capacity should be
+/// large enough for both.
+pub struct AcceptWithData<T: IoBufMut> {
+    pub(crate) fd: RawFd,
+    pub(crate) accept_fd: RawFd,
+    pub(crate) buffer: T,
+    _p: PhantomPinned,
+}
+
+impl<T: IoBufMut> AcceptWithData<T> {
+    /// Create [`AcceptWithData`]. `accept_fd` should not be bound.
+    pub fn new(fd: RawFd, accept_fd: RawFd, buffer: T) -> Self {
+        Self {
+            fd,
+            accept_fd,
+            buffer,
+            _p: PhantomPinned,
+        }
+    }
+
+    fn data_len(&self) -> io::Result<usize> {
+        self.buffer
+            .buf_capacity()
+            .checked_sub(ACCEPT_BUFFER_SIZE)
+            .filter(|len| *len > 0)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the buffer is too small to receive the data and addresses",
+                )
+            })
+    }
+
+    /// Update accept context.
+    pub fn update_context(&self) -> io::Result<()> {
+        syscall!(
+            SOCKET,
+            setsockopt(
+                self.accept_fd as _,
+                SOL_SOCKET,
+                SO_UPDATE_ACCEPT_CONTEXT,
+                &self.fd as *const _ as _,
+                std::mem::size_of_val(&self.fd) as _,
+            )
+        )?;
+        Ok(())
+    }
+
+    /// Get the remote address from the end of the buffer.
+    pub fn addr(&self) -> io::Result<SockAddr> {
+        let get_addrs_fn = GET_ADDRS
+            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_GETACCEPTEXSOCKADDRS))?
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "cannot retrieve GetAcceptExSockAddrs",
+                )
+            })?;
+        let data_len = self.data_len()?;
+        let mut local_addr: *mut SOCKADDR = null_mut();
+        let mut local_addr_len = 0;
+        let mut remote_addr: *mut SOCKADDR = null_mut();
+        let mut remote_addr_len = 0;
+        unsafe {
+            get_addrs_fn(
+                self.buffer.as_buf_ptr() as *const _,
+                data_len as _,
+                ACCEPT_ADDR_BUFFER_SIZE as _,
+                ACCEPT_ADDR_BUFFER_SIZE as _,
+                &mut local_addr,
+                &mut local_addr_len,
+                &mut remote_addr,
+                &mut remote_addr_len,
+            );
+        }
+        Ok(unsafe {
+            SockAddr::new(
+                remote_addr.cast::<SOCKADDR_STORAGE>().read_unaligned(),
+                remote_addr_len,
+            )
+        })
+    }
+}
+
+impl<T: IoBufMut> IntoInner for AcceptWithData<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<T: IoBufMut> OpCode for AcceptWithData<T> {
+    unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        let accept_fn = ACCEPT_EX
+            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_ACCEPTEX))?
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve AcceptEx")
+            })?;
+        let data_len = self.data_len()?;
+        let this = self.get_unchecked_mut();
+        let mut received = 0;
+        let res = accept_fn(
+            this.fd as _,
+            this.accept_fd as _,
+            this.buffer.as_mut_slice().as_mut_ptr() as _,
+            data_len as _,
+            ACCEPT_ADDR_BUFFER_SIZE as _,
+            ACCEPT_ADDR_BUFFER_SIZE as _,
+            &mut received,
+            optr,
+        );
+        win32_result(res, received)
+    }
+
+    unsafe fn cancel(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> io::Result<()> {
+        cancel(self.fd, optr)
+    }
+}
+
 static CONNECT_EX: OnceLock<LPFN_CONNECTEX> = OnceLock::new();
 
 impl Connect {
@@ -647,19 +785,142 @@ impl OpCode for Connect {
     }
 }
 
+/// Connect to a remote address with `ConnectEx`, sending the data after the
+/// connection is established, or in the SYN if `TCP_FASTOPEN` is set.
+///
+/// It resolves to the length sent.
+pub struct ConnectWithData<T: IoBuf> {
+    pub(crate) fd: RawFd,
+    pub(crate) buffer: T,
+    pub(crate) addr: SockAddr,
+    _p: PhantomPinned,
+}
+
+impl<T: IoBuf> ConnectWithData<T> {
+    /// Create [`ConnectWithData`]. `fd` should be bound.
+    pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self {
+            fd,
+            buffer,
+            addr,
+            _p: PhantomPinned,
+        }
+    }
+
+    /// Update connect context.
+    pub fn update_context(&self) -> io::Result<()> {
+        syscall!(
+            SOCKET,
+            setsockopt(
+                self.fd as _,
+                SOL_SOCKET,
+                SO_UPDATE_CONNECT_CONTEXT,
+                null(),
+                0,
+            )
+        )?;
+        Ok(())
+    }
+}
+
+impl<T: IoBuf> IntoInner for ConnectWithData<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
+impl<T: IoBuf> OpCode for ConnectWithData<T> {
+    unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        let connect_fn = CONNECT_EX
+            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_CONNECTEX))?
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve ConnectEx")
+            })?;
+        let slice = self.buffer.as_slice();
+        let mut sent = 0;
+        let res = connect_fn(
+            self.fd as _,
+            self.addr.as_ptr(),
+            self.addr.len(),
+            slice.as_ptr() as _,
+            slice.len() as _,
+            &mut sent,
+            optr,
+        );
+        win32_result(res, sent)
+    }
+
+    unsafe fn cancel(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> io::Result<()> {
+        cancel(self.fd, optr)
+    }
+}
+
+static TRANSMIT_FILE: OnceLock<LPFN_TRANSMITFILE> = OnceLock::new();
+
+/// Send a file to a connected socket with `TransmitFile`, without copying it
+/// through the userspace.
+pub struct TransmitFile {
+    pub(crate) fd: RawFd,
+    pub(crate) file: RawFd,
+    pub(crate) offset: u64,
+    pub(crate) len: u32,
+}
+
+impl TransmitFile {
+    /// Create [`TransmitFile`] sending `len` bytes of `file` at `offset` to
+    /// the socket `fd`. A zero `len` sends the rest of the file.
+    pub fn new(fd: RawFd, file: RawFd, offset: u64, len: u32) -> Self {
+        Self {
+            fd,
+            file,
+            offset,
+            len,
+        }
+    }
+}
+
+impl OpCode for TransmitFile {
+    unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        if let Some(overlapped) = optr.as_mut() {
+            overlapped.Anonymous.Anonymous.Offset = (self.offset & 0xFFFFFFFF) as _;
+            overlapped.Anonymous.Anonymous.OffsetHigh = (self.offset >> 32) as _;
+        }
+        let transmit_fn = TRANSMIT_FILE
+            .get_or_try_init(|| get_wsa_fn(self.fd, WSAID_TRANSMITFILE))?
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "cannot retrieve TransmitFile")
+            })?;
+        let res = transmit_fn(self.fd as _, self.file as _, self.len, 0, optr, null(), 0);
+        win32_result(res, 0)
+    }
+
+    unsafe fn cancel(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> io::Result<()> {
+        cancel(self.fd, optr)
+    }
+}
+
 /// Receive data from remote.
 pub struct Recv<T: IoBufMut> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBufMut> Recv<T> {
     /// Create [`Recv`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`Recv`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
             fd,
             buffer,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -676,6 +937,22 @@ impl<T: IoBufMut> IntoInner for Recv<T> {
 impl<T: IoBufMut> OpCode for Recv<T> {
     unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
         let fd = self.fd as _;
+        let flags = self.flags;
+        if flags != 0 {
+            let buffer = self.get_unchecked_mut().buffer.as_io_slice_mut();
+            let mut flags = flags as _;
+            let mut received = 0;
+            let res = WSARecv(
+                fd as _,
+                &buffer as *const _ as _,
+                1,
+                &mut received,
+                &mut flags,
+                optr,
+                None,
+            );
+            return winsock_result(res, received);
+        }
         let slice = self.get_unchecked_mut().buffer.as_mut_slice();
         let mut transferred = 0;
         let res = ReadFile(
@@ -746,15 +1023,22 @@ impl<T: IoVectoredBufMut> OpCode for RecvVectored<T> {
 pub struct Send<T: IoBuf> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBuf> Send<T> {
     /// Create [`Send`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`Send`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
             fd,
             buffer,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -770,6 +1054,20 @@ impl<T: IoBuf> IntoInner for Send<T> {
 
 impl<T: IoBuf> OpCode for Send<T> {
     unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        if self.flags != 0 {
+            let buffer = self.buffer.as_io_slice();
+            let mut sent = 0;
+            let res = WSASend(
+                self.fd as _,
+                &buffer as *const _ as _,
+                1,
+                &mut sent,
+                self.flags as _,
+                optr,
+                None,
+            );
+            return winsock_result(res, sent);
+        }
         let slice = self.buffer.as_slice();
         let mut transferred = 0;
         let res = WriteFile(
@@ -840,17 +1138,24 @@ pub struct RecvFrom<T: IoBufMut> {
     pub(crate) buffer: T,
     pub(crate) addr: SOCKADDR_STORAGE,
     pub(crate) addr_len: socklen_t,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBufMut> RecvFrom<T> {
     /// Create [`RecvFrom`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`RecvFrom`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
             fd,
             buffer,
             addr: unsafe { std::mem::zeroed() },
             addr_len: std::mem::size_of::<SOCKADDR_STORAGE>() as _,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -869,7 +1174,7 @@ impl<T: IoBufMut> OpCode for RecvFrom<T> {
         let this = self.get_unchecked_mut();
         let fd = this.fd;
         let buffer = this.buffer.as_io_slice_mut();
-        let mut flags = 0;
+        let mut flags = this.flags as _;
         let mut received = 0;
         let res = WSARecvFrom(
             fd as _,
@@ -951,16 +1256,23 @@ pub struct SendTo<T: IoBuf> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
     pub(crate) addr: SockAddr,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBuf> SendTo<T> {
     /// Create [`SendTo`].
     pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self::with_flags(fd, buffer, addr, 0)
+    }
+
+    /// Create [`SendTo`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, addr: SockAddr, flags: i32) -> Self {
         Self {
             fd,
             buffer,
             addr,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -983,7 +1295,7 @@ impl<T: IoBuf> OpCode for SendTo<T> {
             &buffer as *const _ as _,
             1,
             &mut sent,
-            0,
+            self.flags as _,
             self.addr.as_ptr(),
             self.addr.len(),
             optr,
@@ -1048,6 +1360,66 @@ impl<T: IoVectoredBuf> OpCode for SendToVectored<T> {
     }
 }
 
+/// Send data to specified address accompanied by ancillary data from vectored
+/// buffer.
+pub struct SendMsg<T: IoVectoredBuf, C: IoBuf> {
+    msg: WSAMSG,
+    fd: RawFd,
+    buffer: T,
+    control: C,
+    addr: SockAddr,
+    slices: Vec<IoSlice>,
+    _p: PhantomPinned,
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> SendMsg<T, C> {
+    /// Create [`SendMsg`].
+    pub fn new(fd: RawFd, buffer: T, control: C, addr: SockAddr) -> Self {
+        Self {
+            msg: unsafe { std::mem::zeroed() },
+            fd,
+            buffer,
+            control,
+            addr,
+            slices: vec![],
+            _p: PhantomPinned,
+        }
+    }
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> IntoInner for SendMsg<T, C> {
+    type Inner = (T, C);
+
+    fn into_inner(self) -> Self::Inner {
+        (self.buffer, self.control)
+    }
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> OpCode for SendMsg<T, C> {
+    unsafe fn operate(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> Poll<io::Result<usize>> {
+        let this = self.get_unchecked_mut();
+        this.slices = this.buffer.as_io_slices();
+        this.msg = WSAMSG {
+            name: this.addr.as_ptr() as _,
+            namelen: this.addr.len(),
+            lpBuffers: this.slices.as_mut_ptr() as _,
+            dwBufferCount: this.slices.len() as _,
+            Control: WSABUF {
+                len: this.control.buf_len() as _,
+                buf: this.control.as_buf_ptr() as _,
+            },
+            dwFlags: 0,
+        };
+        let mut sent = 0;
+        let res = WSASendMsg(this.fd as _, &this.msg, 0, &mut sent, optr, None);
+        winsock_result(res, sent)
+    }
+
+    unsafe fn cancel(self: Pin<&mut Self>, optr: *mut OVERLAPPED) -> io::Result<()> {
+        cancel(self.fd, optr)
+    }
+}
+
 /// Connect a named pipe server.
 pub struct ConnectNamedPipe {
     pub(crate) fd: RawFd,
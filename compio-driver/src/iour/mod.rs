@@ -2,8 +2,14 @@
 #[allow(unused_imports)]
 pub use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 use std::{
-    collections::VecDeque, io, os::fd::OwnedFd, pin::Pin, ptr::NonNull, sync::Arc, task::Poll,
-    time::Duration,
+    collections::VecDeque,
+    io,
+    os::fd::OwnedFd,
+    pin::Pin,
+    ptr::NonNull,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
 };
 
 use compio_log::{instrument, trace};
@@ -30,7 +36,7 @@ use io_uring::{
 pub(crate) use libc::{sockaddr_storage, socklen_t};
 use slab::Slab;
 
-use crate::{syscall, AsyncifyPool, Entry, OutEntries, ProactorBuilder};
+use crate::{syscall, AsyncifyPool, BufferPool, Entry, OutEntries, ProactorBuilder, Registry};
 
 pub(crate) mod op;
 pub(crate) use crate::unix::RawOp;
@@ -64,11 +70,24 @@ pub trait OpCode {
     /// Create submission entry.
     fn create_entry(self: Pin<&mut Self>) -> OpEntry;
 
+    /// Create submission entry with the I/O priority of [`Hinted`]. The
+    /// operations without the `ioprio` field ignore it.
+    ///
+    /// [`Hinted`]: crate::op::Hinted
+    fn create_entry_with_ioprio(self: Pin<&mut Self>, _ioprio: u16) -> OpEntry {
+        self.create_entry()
+    }
+
     /// Call the operation in a blocking way. This method will only be called if
     /// [`create_entry`] returns [`OpEntry::Blocking`].
     fn call_blocking(self: Pin<&mut Self>) -> io::Result<usize> {
         unreachable!("this operation is asynchronous")
     }
+
+    /// Release a successful result with the flags of its completion, which
+    /// is discarded because the operation has been cancelled, e.g. close the
+    /// accepted socket, or return the selected buffer to its pool.
+    fn release_result(self: Pin<&mut Self>, _res: usize, _flags: u32) {}
 }
 
 /// Low-level driver of io-uring.
@@ -79,6 +98,12 @@ pub(crate) struct Driver {
     notifier_registered: bool,
     pool: AsyncifyPool,
     pool_completed: Arc<SegQueue<Entry>>,
+    submit_batch: usize,
+    submit_deadline: Option<Duration>,
+    // The time the oldest entry in `squeue` is pushed.
+    pending_since: Option<Instant>,
+    // The pools with registered buffer rings, indexed by the group id.
+    buffer_pools: Slab<BufferPool>,
 }
 
 impl Driver {
@@ -88,13 +113,21 @@ impl Driver {
     pub fn new(builder: &ProactorBuilder) -> io::Result<Self> {
         instrument!(compio_log::Level::TRACE, "new", ?builder);
         trace!("new iour driver");
+        let mut inner = IoUring::builder();
+        if let Some(idle) = builder.sqpoll_idle {
+            inner.setup_sqpoll(idle.as_millis() as _);
+        }
         Ok(Self {
-            inner: IoUring::builder().build(builder.capacity)?,
+            inner: inner.build(builder.capacity)?,
             squeue: VecDeque::with_capacity(builder.capacity as usize),
             notifier: Notifier::new()?,
             notifier_registered: false,
             pool: builder.create_or_get_thread_pool(),
             pool_completed: Arc::new(SegQueue::new()),
+            submit_batch: builder.submit_batch,
+            submit_deadline: builder.submit_deadline,
+            pending_since: None,
+            buffer_pools: Slab::new(),
         })
     }
 
@@ -167,6 +200,31 @@ impl Driver {
         ended_ops
     }
 
+    /// Submit the pending entries without waiting, if there are enough of
+    /// them or the oldest one has waited long enough.
+    fn submit_pending(&mut self) {
+        if self.squeue.len() < self.submit_batch {
+            // The clock is only read with a deadline.
+            let Some(deadline) = self.submit_deadline else {
+                return;
+            };
+            let now = Instant::now();
+            let since = *self.pending_since.get_or_insert(now);
+            if now.duration_since(since) < deadline {
+                return;
+            }
+        }
+        trace!("submit {} pending entries early", self.squeue.len());
+        if self.flush_submissions() {
+            self.pending_since = None;
+        }
+        // The entries are left in the ring on failure, and submitted on the
+        // next poll.
+        if let Err(_e) = self.inner.submit() {
+            trace!("early submission failed: {_e:?}");
+        }
+    }
+
     fn poll_entries(&mut self, entries: &mut impl Extend<Entry>) {
         while let Some(entry) = self.pool_completed.pop() {
             entries.extend(Some(entry));
@@ -189,7 +247,7 @@ impl Driver {
         Ok(())
     }
 
-    pub fn cancel(&mut self, user_data: usize, _registry: &mut Slab<RawOp>) {
+    pub fn cancel(&mut self, user_data: usize, _registry: &mut Registry) {
         instrument!(compio_log::Level::TRACE, "cancel", user_data);
         trace!("cancel RawOp");
         #[allow(clippy::useless_conversion)]
@@ -201,7 +259,16 @@ impl Driver {
         );
     }
 
-    pub fn push(&mut self, user_data: usize, op: &mut RawOp) -> Poll<io::Result<usize>> {
+    pub fn interrupt(&mut self, user_data: usize, registry: &mut Registry) {
+        // The interrupted op completes with `ECANCELED`.
+        self.cancel(user_data, registry);
+    }
+
+    pub fn push(
+        &mut self,
+        user_data: usize,
+        op: &mut RawOp,
+    ) -> io::Result<Poll<io::Result<usize>>> {
         instrument!(compio_log::Level::TRACE, "push", user_data);
         let op_pin = op.as_pin();
         trace!("push RawOp");
@@ -210,18 +277,20 @@ impl Driver {
                 #[allow(clippy::useless_conversion)]
                 self.squeue
                     .push_back(entry.user_data(user_data as _).into());
-                Poll::Pending
+                self.submit_pending();
+                Ok(Poll::Pending)
             }
             #[cfg(feature = "io-uring-sqe128")]
             OpEntry::Submission128(_entry) => {
                 self.squeue.push_back(_entry.user_data(user_data as _));
-                Poll::Pending
+                self.submit_pending();
+                Ok(Poll::Pending)
             }
             OpEntry::Blocking => {
                 if self.push_blocking(user_data, op)? {
-                    Poll::Pending
+                    Ok(Poll::Pending)
                 } else {
-                    Poll::Ready(Err(io::Error::from_raw_os_error(libc::EBUSY)))
+                    Err(io::Error::from_raw_os_error(libc::EBUSY))
                 }
             }
         }
@@ -271,6 +340,7 @@ impl Driver {
         }
         // Anyway we need to submit once, no matter there are entries in squeue.
         trace!("start polling");
+        self.pending_since = None;
         loop {
             let ended = self.flush_submissions();
 
@@ -286,6 +356,52 @@ impl Driver {
         Ok(())
     }
 
+    pub fn thread_pool(&self) -> &AsyncifyPool {
+        &self.pool
+    }
+
+    pub fn create_buffer_pool(
+        &mut self,
+        entries: u16,
+        buffer_len: usize,
+    ) -> io::Result<BufferPool> {
+        // The buffer rings of 5.19 are useless without the multishot receive
+        // of 6.0, and the plain pools are received into one by one.
+        if crate::tune::kernel_version().is_none_or(|v| v < (6, 0)) {
+            return BufferPool::new(entries, buffer_len);
+        }
+        let entry = self.buffer_pools.vacant_entry();
+        let bgid =
+            u16::try_from(entry.key()).map_err(|_| io::Error::from_raw_os_error(libc::ENOBUFS))?;
+        let pool = BufferPool::with_ring(entries, buffer_len, bgid)?;
+        let (addr, entries, bgid) = pool.ring_id().expect("the pool should have a ring");
+        match unsafe {
+            self.inner
+                .submitter()
+                .register_buf_ring(addr, entries, bgid)
+        } {
+            Ok(()) => Ok(entry.insert(pool).clone()),
+            // The buffer rings are not supported.
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => {
+                BufferPool::new(entries, buffer_len)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn release_buffer_pool(&mut self, pool: BufferPool) -> io::Result<()> {
+        let Some((_, _, bgid)) = pool.ring_id() else {
+            return Ok(());
+        };
+        match self.buffer_pools.get(bgid as usize) {
+            Some(registered) if registered.ptr_eq(&pool) => {}
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidInput)),
+        }
+        self.inner.submitter().unregister_buf_ring(bgid)?;
+        self.buffer_pools.remove(bgid as usize);
+        Ok(())
+    }
+
     pub fn handle(&self) -> io::Result<NotifyHandle> {
         self.notifier.handle()
     }
@@ -309,7 +425,31 @@ fn create_entry(entry: CEntry) -> Entry {
     } else {
         Ok(result as _)
     };
-    Entry::new(entry.user_data() as _, result)
+    let mut res = Entry::new(entry.user_data() as _, result);
+    res.set_flags(entry.flags());
+    res
+}
+
+// The kernels before 5.19 never set `IORING_CQE_F_SOCK_NONEMPTY`.
+pub(crate) fn sock_nonempty(flags: u32) -> Option<bool> {
+    static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    SUPPORTED
+        .get_or_init(|| crate::tune::kernel_version().is_some_and(|v| v >= (5, 19)))
+        .then(|| io_uring::cqueue::sock_nonempty(flags))
+}
+
+// `IORING_OP_SEND_ZC` is added in Linux 6.0.
+pub(crate) fn send_zc_supported() -> bool {
+    static SUPPORTED: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        let probe = || {
+            let uring = io_uring::IoUring::new(2)?;
+            let mut probe = io_uring::Probe::new();
+            uring.submitter().register_probe(&mut probe)?;
+            io::Result::Ok(probe.is_supported(io_uring::opcode::SendZc::CODE))
+        };
+        probe().unwrap_or(false)
+    })
 }
 
 fn timespec(duration: std::time::Duration) -> Timespec {
@@ -1,4 +1,4 @@
-use std::{ffi::CString, marker::PhantomPinned, os::fd::RawFd, pin::Pin};
+use std::{ffi::CString, io, marker::PhantomPinned, os::fd::RawFd, pin::Pin};
 
 use compio_buf::{
     BufResult, IntoInner, IoBuf, IoBufMut, IoSlice, IoSliceMut, IoVectoredBuf, IoVectoredBufMut,
@@ -38,7 +38,16 @@ impl<
 
 impl OpCode for OpenFile {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
-        opcode::OpenAt::new(Fd(libc::AT_FDCWD), self.path.as_ptr())
+        if self.how.resolve != 0 {
+            return opcode::OpenAt2::new(
+                Fd(self.dirfd),
+                self.path.as_ptr(),
+                std::ptr::addr_of!(self.how).cast(),
+            )
+            .build()
+            .into();
+        }
+        opcode::OpenAt::new(Fd(self.dirfd), self.path.as_ptr())
             .flags(self.flags)
             .mode(self.mode)
             .build()
@@ -46,6 +55,40 @@ impl OpCode for OpenFile {
     }
 }
 
+impl<T: OpCode> OpCode for Hinted<T> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let force_async = self.force_async;
+        let entry = match self.ioprio {
+            Some(ioprio) => self.op_pin().create_entry_with_ioprio(ioprio),
+            None => self.op_pin().create_entry(),
+        };
+        match entry {
+            OpEntry::Submission(mut entry) => {
+                if force_async {
+                    entry = entry.flags(io_uring::squeue::Flags::ASYNC);
+                }
+                entry.into()
+            }
+            #[cfg(feature = "io-uring-sqe128")]
+            OpEntry::Submission128(mut entry) => {
+                if force_async {
+                    entry = entry.flags(io_uring::squeue::Flags::ASYNC);
+                }
+                entry.into()
+            }
+            entry => entry,
+        }
+    }
+
+    fn call_blocking(self: Pin<&mut Self>) -> io::Result<usize> {
+        self.op_pin().call_blocking()
+    }
+
+    fn release_result(self: Pin<&mut Self>, res: usize, flags: u32) {
+        self.op_pin().release_result(res, flags)
+    }
+}
+
 impl OpCode for CloseFile {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
         opcode::Close::new(Fd(self.fd)).build().into()
@@ -92,6 +135,7 @@ impl IntoInner for FileStat {
 
 /// Get metadata from path.
 pub struct PathStat {
+    pub(crate) dirfd: RawFd,
     pub(crate) path: CString,
     pub(crate) stat: libc::statx,
     pub(crate) follow_symlink: bool,
@@ -100,7 +144,14 @@ pub struct PathStat {
 impl PathStat {
     /// Create [`PathStat`].
     pub fn new(path: CString, follow_symlink: bool) -> Self {
+        Self::with_dirfd(libc::AT_FDCWD, path, follow_symlink)
+    }
+
+    /// Create [`PathStat`] with a relative path resolved from the directory
+    /// `dirfd`.
+    pub fn with_dirfd(dirfd: RawFd, path: CString, follow_symlink: bool) -> Self {
         Self {
+            dirfd,
             path,
             stat: unsafe { std::mem::zeroed() },
             follow_symlink,
@@ -115,7 +166,7 @@ impl OpCode for PathStat {
             flags |= libc::AT_SYMLINK_NOFOLLOW;
         }
         opcode::Statx::new(
-            Fd(libc::AT_FDCWD),
+            Fd(self.dirfd),
             self.path.as_ptr(),
             std::ptr::addr_of_mut!(self.stat).cast(),
         )
@@ -135,11 +186,16 @@ impl IntoInner for PathStat {
 
 impl<T: IoBufMut> OpCode for ReadAt<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        self.create_entry_with_ioprio(0)
+    }
+
+    fn create_entry_with_ioprio(self: Pin<&mut Self>, ioprio: u16) -> OpEntry {
         let fd = Fd(self.fd);
         let offset = self.offset;
         let slice = unsafe { self.get_unchecked_mut() }.buffer.as_mut_slice();
         opcode::Read::new(fd, slice.as_mut_ptr() as _, slice.len() as _)
             .offset(offset)
+            .ioprio(ioprio)
             .build()
             .into()
     }
@@ -147,6 +203,10 @@ impl<T: IoBufMut> OpCode for ReadAt<T> {
 
 impl<T: IoVectoredBufMut> OpCode for ReadVectoredAt<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        self.create_entry_with_ioprio(0)
+    }
+
+    fn create_entry_with_ioprio(self: Pin<&mut Self>, ioprio: u16) -> OpEntry {
         let this = unsafe { self.get_unchecked_mut() };
         this.slices = unsafe { this.buffer.as_io_slices_mut() };
         opcode::Readv::new(
@@ -155,6 +215,7 @@ impl<T: IoVectoredBufMut> OpCode for ReadVectoredAt<T> {
             this.slices.len() as _,
         )
         .offset(this.offset)
+        .ioprio(ioprio)
         .build()
         .into()
     }
@@ -162,9 +223,14 @@ impl<T: IoVectoredBufMut> OpCode for ReadVectoredAt<T> {
 
 impl<T: IoBuf> OpCode for WriteAt<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        self.create_entry_with_ioprio(0)
+    }
+
+    fn create_entry_with_ioprio(self: Pin<&mut Self>, ioprio: u16) -> OpEntry {
         let slice = self.buffer.as_slice();
         opcode::Write::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
             .offset(self.offset)
+            .ioprio(ioprio)
             .build()
             .into()
     }
@@ -172,6 +238,10 @@ impl<T: IoBuf> OpCode for WriteAt<T> {
 
 impl<T: IoVectoredBuf> OpCode for WriteVectoredAt<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        self.create_entry_with_ioprio(0)
+    }
+
+    fn create_entry_with_ioprio(self: Pin<&mut Self>, ioprio: u16) -> OpEntry {
         let this = unsafe { self.get_unchecked_mut() };
         this.slices = unsafe { this.buffer.as_io_slices() };
         opcode::Write::new(
@@ -180,6 +250,7 @@ impl<T: IoVectoredBuf> OpCode for WriteVectoredAt<T> {
             this.slices.len() as _,
         )
         .offset(this.offset)
+        .ioprio(ioprio)
         .build()
         .into()
     }
@@ -225,6 +296,38 @@ impl OpCode for Accept {
     }
 }
 
+impl OpCode for AcceptMulti {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        opcode::AcceptMulti::new(Fd(self.fd)).build().into()
+    }
+
+    fn release_result(self: Pin<&mut Self>, res: usize, _flags: u32) {
+        // Close the connection which will never be popped.
+        unsafe { libc::close(res as _) };
+    }
+}
+
+impl OpCode for RecvMulti {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        match self.pool.ring_id() {
+            Some((_, _, bgid)) => opcode::RecvMulti::new(Fd(self.fd), bgid).build().into(),
+            None => OpEntry::Blocking,
+        }
+    }
+
+    fn call_blocking(self: Pin<&mut Self>) -> io::Result<usize> {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the buffers are not provided to the kernel",
+        ))
+    }
+
+    fn release_result(self: Pin<&mut Self>, res: usize, flags: u32) {
+        // Return the buffer which will never be taken.
+        drop(unsafe { self.pool.take_selected(res, flags) });
+    }
+}
+
 impl OpCode for Connect {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
         opcode::Connect::new(Fd(self.fd), self.addr.as_ptr(), self.addr.len())
@@ -236,10 +339,18 @@ impl OpCode for Connect {
 impl<T: IoBufMut> OpCode for Recv<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
         let fd = self.fd;
+        let flags = self.flags;
         let slice = unsafe { self.get_unchecked_mut() }.buffer.as_mut_slice();
-        opcode::Read::new(Fd(fd), slice.as_mut_ptr() as _, slice.len() as _)
-            .build()
-            .into()
+        if let Some(flags) = flags {
+            opcode::Recv::new(Fd(fd), slice.as_mut_ptr() as _, slice.len() as _)
+                .flags(flags)
+                .build()
+                .into()
+        } else {
+            opcode::Read::new(Fd(fd), slice.as_mut_ptr() as _, slice.len() as _)
+                .build()
+                .into()
+        }
     }
 }
 
@@ -260,9 +371,32 @@ impl<T: IoVectoredBufMut> OpCode for RecvVectored<T> {
 impl<T: IoBuf> OpCode for Send<T> {
     fn create_entry(self: Pin<&mut Self>) -> OpEntry {
         let slice = self.buffer.as_slice();
-        opcode::Write::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
-            .build()
-            .into()
+        if self.flags != 0 {
+            opcode::Send::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
+                .flags(self.flags)
+                .build()
+                .into()
+        } else {
+            opcode::Write::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
+                .build()
+                .into()
+        }
+    }
+}
+
+impl<T: IoBuf> OpCode for SendZc<T> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let slice = self.buffer.as_slice();
+        if super::send_zc_supported() {
+            opcode::SendZc::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
+                .build()
+                .into()
+        } else {
+            opcode::Send::new(Fd(self.fd), slice.as_ptr(), slice.len() as _)
+                .flags(libc::MSG_NOSIGNAL)
+                .build()
+                .into()
+        }
     }
 }
 
@@ -284,15 +418,17 @@ struct RecvFromHeader {
     pub(crate) fd: RawFd,
     pub(crate) addr: sockaddr_storage,
     pub(crate) msg: libc::msghdr,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl RecvFromHeader {
-    pub fn new(fd: RawFd) -> Self {
+    pub fn new(fd: RawFd, flags: i32) -> Self {
         Self {
             fd,
             addr: unsafe { std::mem::zeroed() },
             msg: unsafe { std::mem::zeroed() },
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -308,6 +444,7 @@ impl RecvFromHeader {
             msg_flags: 0,
         };
         opcode::RecvMsg::new(Fd(self.fd), &mut self.msg)
+            .flags(self.flags as _)
             .build()
             .into()
     }
@@ -327,8 +464,13 @@ pub struct RecvFrom<T: IoBufMut> {
 impl<T: IoBufMut> RecvFrom<T> {
     /// Create [`RecvFrom`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`RecvFrom`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
-            header: RecvFromHeader::new(fd),
+            header: RecvFromHeader::new(fd, flags),
             buffer,
             // SAFETY: We never use this slice.
             slice: [unsafe { IoSliceMut::from_slice(&mut []) }],
@@ -364,7 +506,7 @@ impl<T: IoVectoredBufMut> RecvFromVectored<T> {
     /// Create [`RecvFromVectored`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
         Self {
-            header: RecvFromHeader::new(fd),
+            header: RecvFromHeader::new(fd, 0),
             buffer,
             slice: vec![],
         }
@@ -392,15 +534,17 @@ struct SendToHeader {
     pub(crate) fd: RawFd,
     pub(crate) addr: SockAddr,
     pub(crate) msg: libc::msghdr,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl SendToHeader {
-    pub fn new(fd: RawFd, addr: SockAddr) -> Self {
+    pub fn new(fd: RawFd, addr: SockAddr, flags: i32) -> Self {
         Self {
             fd,
             addr,
             msg: unsafe { std::mem::zeroed() },
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -415,7 +559,10 @@ impl SendToHeader {
             msg_controllen: 0,
             msg_flags: 0,
         };
-        opcode::SendMsg::new(Fd(self.fd), &self.msg).build().into()
+        opcode::SendMsg::new(Fd(self.fd), &self.msg)
+            .flags(self.flags as _)
+            .build()
+            .into()
     }
 }
 
@@ -429,8 +576,13 @@ pub struct SendTo<T: IoBuf> {
 impl<T: IoBuf> SendTo<T> {
     /// Create [`SendTo`].
     pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self::with_flags(fd, buffer, addr, 0)
+    }
+
+    /// Create [`SendTo`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, addr: SockAddr, flags: i32) -> Self {
         Self {
-            header: SendToHeader::new(fd, addr),
+            header: SendToHeader::new(fd, addr, flags),
             buffer,
             // SAFETY: We never use this slice.
             slice: [unsafe { IoSlice::from_slice(&[]) }],
@@ -454,6 +606,46 @@ impl<T: IoBuf> IntoInner for SendTo<T> {
     }
 }
 
+/// Connect to a remote address with TCP Fast Open, sending the data in the
+/// SYN.
+///
+/// It resolves to the length sent. It fails with `EINPROGRESS` if the SYN is
+/// sent without the data, e.g. without a TFO cookie of the server, and the
+/// data should be sent after the connection is established.
+pub struct ConnectWithData<T: IoBuf> {
+    header: SendToHeader,
+    buffer: T,
+    slice: [IoSlice; 1],
+}
+
+impl<T: IoBuf> ConnectWithData<T> {
+    /// Create [`ConnectWithData`].
+    pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self {
+            header: SendToHeader::new(fd, addr, libc::MSG_FASTOPEN),
+            buffer,
+            // SAFETY: We never use this slice.
+            slice: [unsafe { IoSlice::from_slice(&[]) }],
+        }
+    }
+}
+
+impl<T: IoBuf> OpCode for ConnectWithData<T> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.slice[0] = unsafe { this.buffer.as_io_slice() };
+        this.header.create_entry(&mut this.slice)
+    }
+}
+
+impl<T: IoBuf> IntoInner for ConnectWithData<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
 /// Send data to specified address from vectored buffer.
 pub struct SendToVectored<T: IoVectoredBuf> {
     header: SendToHeader,
@@ -465,7 +657,7 @@ impl<T: IoVectoredBuf> SendToVectored<T> {
     /// Create [`SendToVectored`].
     pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
         Self {
-            header: SendToHeader::new(fd, addr),
+            header: SendToHeader::new(fd, addr, 0),
             buffer,
             slice: vec![],
         }
@@ -487,3 +679,81 @@ impl<T: IoVectoredBuf> IntoInner for SendToVectored<T> {
         self.buffer
     }
 }
+
+impl<T: IoVectoredBuf, C: IoBuf> OpCode for SendMsg<T, C> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msg();
+        opcode::SendMsg::new(Fd(this.fd), &this.msg).build().into()
+    }
+}
+
+impl<T: IoVectoredBufMut, C: IoBufMut> OpCode for RecvMsg<T, C> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msg();
+        opcode::RecvMsg::new(Fd(this.fd), &mut this.msg)
+            .flags(this.flags as _)
+            .build()
+            .into()
+    }
+}
+
+// There are no opcodes of `sendmmsg` and `recvmmsg`, so they are called once
+// in the thread pool. They fail with `EAGAIN` on a nonblocking socket instead
+// of waiting, and the callers wait for the readiness with `PollOnce`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> OpCode for SendMmsg<T> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        OpEntry::Blocking
+    }
+
+    fn call_blocking(self: Pin<&mut Self>) -> io::Result<usize> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msgs();
+        let res = crate::syscall!(this.call())?;
+        Ok(res as _)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBufMut> OpCode for RecvMmsg<T> {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        OpEntry::Blocking
+    }
+
+    fn call_blocking(self: Pin<&mut Self>) -> io::Result<usize> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msgs();
+        let res = crate::syscall!(this.call())?;
+        Ok(res as _)
+    }
+}
+
+impl OpCode for PollOnce {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        let flags = match self.interest {
+            Interest::Readable => libc::POLLIN,
+            Interest::Writable => libc::POLLOUT,
+        };
+        opcode::PollAdd::new(Fd(self.fd), flags as _).build().into()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl OpCode for Splice {
+    fn create_entry(self: Pin<&mut Self>) -> OpEntry {
+        // -1 means the current position of the fd.
+        let offset = |offset: Option<u64>| offset.map(|offset| offset as i64).unwrap_or(-1);
+        opcode::Splice::new(
+            Fd(self.fd_in),
+            offset(self.offset_in),
+            Fd(self.fd_out),
+            offset(self.offset_out),
+            self.len.min(u32::MAX as usize) as _,
+        )
+        .flags(self.flags)
+        .build()
+        .into()
+    }
+}
@@ -16,11 +16,13 @@ use std::{io, task::Poll, time::Duration};
 
 use compio_buf::BufResult;
 use compio_log::{instrument, trace};
-use slab::Slab;
 
 mod key;
 pub use key::Key;
 
+mod registry;
+use registry::Registry;
+
 pub mod op;
 #[cfg(unix)]
 #[cfg_attr(docsrs, doc(cfg(all())))]
@@ -29,6 +31,17 @@ mod unix;
 mod asyncify;
 pub use asyncify::*;
 
+mod buffer_pool;
+pub use buffer_pool::*;
+
+mod env;
+mod error;
+pub use error::{Error, ResultExt};
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+mod tune;
+
 cfg_if::cfg_if! {
     if #[cfg(windows)] {
         #[path = "iocp/mod.rs"]
@@ -152,11 +165,15 @@ impl<K, R> PushEntry<K, R> {
     }
 }
 
+/// The return type of [`Proactor::try_push`], with the error and the
+/// operation if the driver rejects it.
+pub type TryPushEntry<K, R, T> = Result<PushEntry<K, R>, (io::Error, T)>;
+
 /// Low-level actions of completion-based IO.
 /// It owns the operations to keep the driver safe.
 pub struct Proactor {
     driver: Driver,
-    ops: Slab<RawOp>,
+    ops: Registry,
 }
 
 impl Proactor {
@@ -173,7 +190,7 @@ impl Proactor {
     fn with_builder(builder: &ProactorBuilder) -> io::Result<Self> {
         Ok(Self {
             driver: Driver::new(builder)?,
-            ops: Slab::with_capacity(builder.capacity as _),
+            ops: Registry::with_capacity(builder.capacity as _),
         })
     }
 
@@ -207,30 +224,91 @@ impl Proactor {
     /// after push.
     pub fn cancel(&mut self, user_data: usize) {
         instrument!(compio_log::Level::DEBUG, "cancel", user_data);
-        if let Some(op) = self.ops.get_mut(user_data) {
-            if op.set_cancelled() {
-                // The op is completed.
-                trace!("cancel and remove {}", user_data);
-                self.ops.remove(user_data);
-                return;
-            }
+        if !self.ops.contains(user_data) || cancel_op(&mut self.ops, user_data) {
+            return;
         }
         self.driver.cancel(user_data, &mut self.ops);
     }
 
+    /// Interrupt a pending operation with the user-defined data, so that it
+    /// completes early with an error, e.g. `ECANCELED`, if it hasn't
+    /// completed yet.
+    ///
+    /// Unlike [`Proactor::cancel`], the operation is still returned from
+    /// [`Proactor::poll`] and [`Proactor::pop`], with its buffers. The blocking
+    /// operations on the thread pool couldn't be interrupted.
+    pub fn interrupt(&mut self, user_data: usize) {
+        instrument!(compio_log::Level::DEBUG, "interrupt", user_data);
+        if self.ops.contains(user_data) {
+            self.driver.interrupt(user_data, &mut self.ops);
+        }
+    }
+
     /// Push an operation into the driver, and return the unique key, called
     /// user-defined data, associated with it.
+    ///
+    /// If the driver rejects the operation before submitting it, the error is
+    /// returned as the result. See [`Proactor::try_push`].
     pub fn push<T: OpCode + 'static>(&mut self, op: T) -> PushEntry<Key<T>, BufResult<usize, T>> {
-        let entry = self.ops.vacant_entry();
-        let user_data = entry.key();
-        let op = RawOp::new(user_data, op);
-        let op = entry.insert(op);
-        match self.driver.push(user_data, op) {
-            Poll::Pending => PushEntry::Pending(unsafe { Key::new(user_data) }),
-            Poll::Ready(res) => {
+        self.push_boxed(Box::new(op))
+            .map_ready(|res| res.map_buffer(|op| *op))
+    }
+
+    /// Push an operation like [`Proactor::push`], or get it back if the
+    /// driver rejects it before submitting it.
+    ///
+    /// [`PushEntry::Pending`] means the operation is queued, and
+    /// [`PushEntry::Ready`] means it completed inline, successfully or not.
+    /// An operation is rejected when it cannot be started at all, e.g. when
+    /// the blocking thread pool is full, or the fd cannot be registered to
+    /// the poller. The caller could retry it later, or fall back to another
+    /// way, like running it on its own thread.
+    pub fn try_push<T: OpCode + 'static>(
+        &mut self,
+        op: T,
+    ) -> TryPushEntry<Key<T>, BufResult<usize, T>, T> {
+        match self.try_push_boxed(Box::new(op)) {
+            Ok(entry) => Ok(entry.map_ready(|res| res.map_buffer(|op| *op))),
+            Err((e, op)) => Err((e, *op)),
+        }
+    }
+
+    /// Push an operation allocated by the caller, like [`Proactor::push`].
+    ///
+    /// The allocation is returned with the result from
+    /// [`Proactor::pop_boxed_with_flags`], so it could be reused for the next
+    /// operation. It is not reused on IOCP driver.
+    pub fn push_boxed<T: OpCode + 'static>(
+        &mut self,
+        op: Box<T>,
+    ) -> PushEntry<Key<T>, BufResult<usize, Box<T>>> {
+        match self.try_push_boxed(op) {
+            Ok(entry) => entry,
+            Err((e, op)) => PushEntry::Ready(BufResult(Err(e), op)),
+        }
+    }
+
+    /// Push an operation allocated by the caller, like
+    /// [`Proactor::try_push`].
+    pub fn try_push_boxed<T: OpCode + 'static>(
+        &mut self,
+        op: Box<T>,
+    ) -> TryPushEntry<Key<T>, BufResult<usize, Box<T>>, Box<T>> {
+        let (user_data, op) = self.ops.insert(|user_data| RawOp::from_box(user_data, op));
+        let res = self.driver.push(user_data, op);
+        match res {
+            Ok(Poll::Pending) => Ok(PushEntry::Pending(unsafe { Key::new(user_data) })),
+            Ok(Poll::Ready(res)) => {
                 let mut op = self.ops.remove(user_data);
                 op.set_result(res);
-                PushEntry::Ready(unsafe { op.into_inner::<T>() })
+                Ok(PushEntry::Ready(unsafe { op.into_box::<T>() }))
+            }
+            Err(e) => {
+                trace!("push {} rejected: {}", user_data, e);
+                let mut op = self.ops.remove(user_data);
+                op.set_result(Err(e));
+                let BufResult(res, op) = unsafe { op.into_box::<T>() };
+                Err((res.unwrap_err(), op))
             }
         }
     }
@@ -255,14 +333,70 @@ impl Proactor {
     /// This function will panic if the requested operation has not been
     /// completed.
     pub fn pop<T: OpCode>(&mut self, user_data: Key<T>) -> BufResult<usize, T> {
+        self.pop_with_flags(user_data).0
+    }
+
+    /// Get the pushed operations from the completion entries, with the flags
+    /// of the completion.
+    ///
+    /// The flags are the raw CQE flags, like `IORING_CQE_F_SOCK_NONEMPTY` and
+    /// `IORING_CQE_F_MORE`, on io-uring driver, and `0` on other drivers.
+    ///
+    /// # Panics
+    /// This function will panic if the requested operation has not been
+    /// completed.
+    pub fn pop_with_flags<T: OpCode>(&mut self, user_data: Key<T>) -> (BufResult<usize, T>, u32) {
+        let (res, flags) = self.pop_boxed_with_flags(user_data);
+        (res.map_buffer(|op| *op), flags)
+    }
+
+    /// Get the operations pushed by [`Proactor::push_boxed`] with their
+    /// allocations, like [`Proactor::pop_with_flags`].
+    ///
+    /// # Panics
+    /// This function will panic if the requested operation has not been
+    /// completed.
+    pub fn pop_boxed_with_flags<T: OpCode>(
+        &mut self,
+        user_data: Key<T>,
+    ) -> (BufResult<usize, Box<T>>, u32) {
         instrument!(compio_log::Level::DEBUG, "pop", ?user_data);
         let op = self
             .ops
             .try_remove(*user_data)
             .expect("the entry should be valid");
         trace!("poped {}", *user_data);
+        let flags = op.flags();
         // Safety: user cannot create key with safe code, so the type should be correct
-        unsafe { op.into_inner::<T>() }
+        (unsafe { op.into_box::<T>() }, flags)
+    }
+
+    /// Get the next result of a multishot operation, like [`op::AcceptMulti`],
+    /// which has not completed yet. The results are returned in the order
+    /// they arrive, and the final result is popped with [`Proactor::pop`]
+    /// after [`Proactor::has_result`] returns `true`.
+    ///
+    /// Only io-uring driver produces multishot results, and `None` is always
+    /// returned on other drivers.
+    pub fn pop_multishot<T: OpCode>(&mut self, user_data: &Key<T>) -> Option<io::Result<usize>> {
+        self.pop_multishot_with_flags(user_data).map(|(res, _)| res)
+    }
+
+    /// Get the next result of a multishot operation with the flags of its
+    /// completion, like the buffer selected by [`op::RecvMulti`].
+    pub fn pop_multishot_with_flags<T: OpCode>(
+        &mut self,
+        user_data: &Key<T>,
+    ) -> Option<(io::Result<usize>, u32)> {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            self.ops.get_mut(**user_data)?.pop_more()
+        }
+        #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+        {
+            let _ = user_data;
+            None
+        }
     }
 
     /// Query if the operation has completed.
@@ -273,6 +407,34 @@ impl Proactor {
             .unwrap_or_default()
     }
 
+    /// Create a [`BufferPool`] of `entries` buffers, each of `buffer_len`
+    /// bytes. The `entries` should be a power of 2, at most 32768.
+    ///
+    /// On io-uring driver, the buffers are provided to the kernel for
+    /// [`op::RecvMulti`] if the kernel supports it, i.e. since Linux 6.0,
+    /// and the pool is kept
+    /// registered until it is released with
+    /// [`Proactor::release_buffer_pool`] or the proactor is dropped.
+    pub fn create_buffer_pool(
+        &mut self,
+        entries: u16,
+        buffer_len: usize,
+    ) -> io::Result<BufferPool> {
+        self.driver.create_buffer_pool(entries, buffer_len)
+    }
+
+    /// Stop providing the buffers of the pool to the kernel. The pending
+    /// [`op::RecvMulti`] of the pool fails after that, and the borrowed
+    /// buffers are still valid.
+    pub fn release_buffer_pool(&mut self, pool: BufferPool) -> io::Result<()> {
+        self.driver.release_buffer_pool(pool)
+    }
+
+    /// The thread pool running the blocking operations.
+    pub fn thread_pool(&self) -> &AsyncifyPool {
+        self.driver.thread_pool()
+    }
+
     /// Create a notify handle to interrupt the inner driver.
     pub fn handle(&self) -> io::Result<NotifyHandle> {
         self.driver.handle()
@@ -295,16 +457,42 @@ impl AsRawFd for Proactor {
     }
 }
 
+/// Tells from the flags of a completed receive, e.g. returned by
+/// [`Proactor::pop_with_flags`], whether more data is pending on the socket.
+///
+/// It is `None` if the driver doesn't report it, i.e. the driver isn't
+/// io-uring, or the kernel is older than 5.19.
+pub fn sock_nonempty(flags: u32) -> Option<bool> {
+    cfg_if::cfg_if! {
+        if #[cfg(all(target_os = "linux", feature = "io-uring"))] {
+            sys::sock_nonempty(flags)
+        } else {
+            let _ = flags;
+            None
+        }
+    }
+}
+
 /// An completed entry returned from kernel.
 #[derive(Debug)]
 pub(crate) struct Entry {
     user_data: usize,
     result: io::Result<usize>,
+    flags: u32,
 }
 
 impl Entry {
     pub(crate) fn new(user_data: usize, result: io::Result<usize>) -> Self {
-        Self { user_data, result }
+        Self {
+            user_data,
+            result,
+            flags: 0,
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn set_flags(&mut self, flags: u32) {
+        self.flags = flags;
     }
 
     /// The user-defined data returned by [`Proactor::push`].
@@ -312,6 +500,11 @@ impl Entry {
         self.user_data
     }
 
+    /// The flags of the completion.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
     /// The result of the operation.
     pub fn into_result(self) -> io::Result<usize> {
         self.result
@@ -322,37 +515,92 @@ impl Entry {
 // marked as `cancelled`, it will be removed from the registry.
 struct OutEntries<'a, 'b, E> {
     entries: &'b mut E,
-    registry: &'a mut Slab<RawOp>,
+    registry: &'a mut Registry,
 }
 
 impl<'a, 'b, E> OutEntries<'a, 'b, E> {
-    pub fn new(entries: &'b mut E, registry: &'a mut Slab<RawOp>) -> Self {
+    pub fn new(entries: &'b mut E, registry: &'a mut Registry) -> Self {
         Self { entries, registry }
     }
 
     #[allow(dead_code)]
-    pub fn registry(&mut self) -> &mut Slab<RawOp> {
+    pub fn registry(&mut self) -> &mut Registry {
         self.registry
     }
 }
 
 impl<E: Extend<usize>> Extend<Entry> for OutEntries<'_, '_, E> {
     fn extend<T: IntoIterator<Item = Entry>>(&mut self, iter: T) {
-        self.entries.extend(iter.into_iter().filter_map(|e| {
-            let user_data = e.user_data();
-            if self.registry[user_data].set_result(e.into_result()) {
-                self.registry.remove(user_data);
-                None
-            } else {
-                Some(user_data)
-            }
-        }))
+        self.entries.extend(
+            iter.into_iter()
+                .filter_map(|e| complete_op(self.registry, e)),
+        )
+    }
+}
+
+// Marks the op cancelled, and removes it if it has completed. Returns `true`
+// if it is removed.
+fn cancel_op(registry: &mut Registry, user_data: usize) -> bool {
+    if let Some(op) = registry.get_mut(user_data) {
+        if op.set_cancelled() {
+            // The op is completed.
+            trace!("cancel and remove {}", user_data);
+            registry.remove(user_data);
+            return true;
+        }
+    }
+    false
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+const IORING_CQE_F_NOTIF: u32 = 1 << 3;
+
+// Sets the result of the op, and removes it if it has been cancelled. Returns
+// the user data if the op should be popped.
+fn complete_op(registry: &mut Registry, entry: Entry) -> Option<usize> {
+    let user_data = entry.user_data();
+    let Some(op) = registry.get_mut(user_data) else {
+        // A late completion of a removed op.
+        trace!("ignore the stale completion {}", user_data);
+        return None;
+    };
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    if entry.flags() & IORING_CQE_F_NOTIF != 0 {
+        // The notification of a zero-copy send, after the buffer is released.
+        // The op completes with the result of the send.
+        let (res, flags) = op.pop_more().unwrap_or((Ok(0), 0));
+        op.set_flags(flags);
+        return if op.set_result(res) {
+            registry.remove(user_data);
+            None
+        } else {
+            Some(user_data)
+        };
+    }
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    if io_uring::cqueue::more(entry.flags()) {
+        // More completions of the multishot op will follow.
+        let flags = entry.flags();
+        return op
+            .push_more(entry.into_result(), flags)
+            .then_some(user_data);
+    }
+    op.set_flags(entry.flags());
+    if op.set_result(entry.into_result()) {
+        registry.remove(user_data);
+        None
+    } else {
+        Some(user_data)
     }
 }
 
 #[derive(Debug, Clone)]
 enum ThreadPoolBuilder {
-    Create { limit: usize, recv_limit: Duration },
+    Create {
+        limit: usize,
+        recv_limit: Duration,
+        policy: QueuePolicy,
+    },
     Reuse(AsyncifyPool),
 }
 
@@ -367,12 +615,17 @@ impl ThreadPoolBuilder {
         Self::Create {
             limit: 256,
             recv_limit: Duration::from_secs(60),
+            policy: QueuePolicy::Reject,
         }
     }
 
     pub fn create_or_reuse(&self) -> AsyncifyPool {
         match self {
-            Self::Create { limit, recv_limit } => AsyncifyPool::new(*limit, *recv_limit),
+            Self::Create {
+                limit,
+                recv_limit,
+                policy,
+            } => AsyncifyPool::with_policy(*limit, *recv_limit, *policy),
             Self::Reuse(pool) => pool.clone(),
         }
     }
@@ -383,6 +636,9 @@ impl ThreadPoolBuilder {
 pub struct ProactorBuilder {
     capacity: u32,
     pool_builder: ThreadPoolBuilder,
+    submit_batch: usize,
+    submit_deadline: Option<Duration>,
+    sqpoll_idle: Option<Duration>,
 }
 
 impl Default for ProactorBuilder {
@@ -397,7 +653,63 @@ impl ProactorBuilder {
         Self {
             capacity: 1024,
             pool_builder: ThreadPoolBuilder::new(),
+            submit_batch: usize::MAX,
+            submit_deadline: None,
+            sqpoll_idle: None,
+        }
+    }
+
+    /// Choose the capacity and the thread number limit of the inner thread
+    /// pool from the CPU count, the memory size and the kernel version,
+    /// instead of the fixed defaults. Before Linux 5.12, the ring is also
+    /// kept within `RLIMIT_MEMLOCK`.
+    ///
+    /// The options set after this call override the chosen values.
+    pub fn autotune(&mut self) -> &mut Self {
+        let tuned = tune::autotune();
+        trace!(
+            "autotune: capacity {}, thread pool limit {}",
+            tuned.capacity,
+            tuned.thread_limit
+        );
+        self.capacity(tuned.capacity)
+            .thread_pool_limit(tuned.thread_limit)
+    }
+
+    /// Override the options with the environment variables, so that the
+    /// deployments could be tuned without recompiling:
+    /// * `COMPIO_AUTOTUNE`: `1` or `true` to call [`autotune`], before the
+    ///   other variables.
+    /// * `COMPIO_RING_ENTRIES`: the [`capacity`].
+    /// * `COMPIO_SQPOLL`: the idle milliseconds of [`sqpoll`].
+    /// * `COMPIO_BLOCKING_THREADS`: the [`thread_pool_limit`].
+    /// * `COMPIO_SUBMIT_BATCH`: the [`submit_batch_size`].
+    ///
+    /// It fails if any of the variables is invalid. The log level of the
+    /// driver events is controlled by the `tracing` subscriber instead.
+    ///
+    /// [`autotune`]: Self::autotune
+    /// [`capacity`]: Self::capacity
+    /// [`sqpoll`]: Self::sqpoll
+    /// [`thread_pool_limit`]: Self::thread_pool_limit
+    /// [`submit_batch_size`]: Self::submit_batch_size
+    pub fn load_env(&mut self) -> io::Result<&mut Self> {
+        if env::flag("COMPIO_AUTOTUNE")? {
+            self.autotune();
+        }
+        if let Some(capacity) = env::var("COMPIO_RING_ENTRIES")? {
+            self.capacity(capacity);
+        }
+        if let Some(idle) = env::var("COMPIO_SQPOLL")? {
+            self.sqpoll(Duration::from_millis(idle));
+        }
+        if let Some(limit) = env::var("COMPIO_BLOCKING_THREADS")? {
+            self.thread_pool_limit(limit);
         }
+        if let Some(size) = env::var("COMPIO_SUBMIT_BATCH")? {
+            self.submit_batch_size(size);
+        }
+        Ok(self)
     }
 
     /// Set the capacity of the inner event queue or submission queue, if
@@ -407,6 +719,37 @@ impl ProactorBuilder {
         self
     }
 
+    /// Poll the submission queue with a kernel thread, which sleeps after
+    /// being idle for `idle`. It saves the system calls to submit, at the
+    /// cost of a busy kernel thread.
+    ///
+    /// Only the io-uring driver supports it.
+    pub fn sqpoll(&mut self, idle: Duration) -> &mut Self {
+        self.sqpoll_idle = Some(idle);
+        self
+    }
+
+    /// Submit the pending operations as soon as there are `size` of them,
+    /// instead of waiting for the next poll. It submits the long running
+    /// batches earlier, at the cost of more system calls. By default, the
+    /// operations are only submitted when polling.
+    ///
+    /// Only the io-uring driver queues the operations.
+    pub fn submit_batch_size(&mut self, size: usize) -> &mut Self {
+        self.submit_batch = size.max(1);
+        self
+    }
+
+    /// Submit the pending operations when a new one is pushed and the oldest
+    /// one has been waiting for `deadline`, to bound the added latency of
+    /// batching. The operations are also submitted when polling.
+    ///
+    /// Only the io-uring driver queues the operations.
+    pub fn submit_deadline(&mut self, deadline: Duration) -> &mut Self {
+        self.submit_deadline = Some(deadline);
+        self
+    }
+
     /// Set the thread number limit of the inner thread pool, if exists. The
     /// default value is 256.
     ///
@@ -429,6 +772,17 @@ impl ProactorBuilder {
         self
     }
 
+    /// Set what the inner thread pool does when all its threads are busy. The
+    /// default is [`QueuePolicy::Reject`].
+    ///
+    /// It will be ignored if `reuse_thread_pool` is set.
+    pub fn thread_pool_policy(&mut self, value: QueuePolicy) -> &mut Self {
+        if let ThreadPoolBuilder::Create { policy, .. } = &mut self.pool_builder {
+            *policy = value;
+        }
+        self
+    }
+
     /// Set to reuse an existing [`AsyncifyPool`] in this proactor.
     pub fn reuse_thread_pool(&mut self, pool: AsyncifyPool) -> &mut Self {
         self.pool_builder = ThreadPoolBuilder::Reuse(pool);
@@ -8,14 +8,24 @@ use std::{marker::PhantomPinned, net::Shutdown};
 use compio_buf::{BufResult, IntoInner, IoBuf, IoBufMut, SetBufInit};
 use socket2::SockAddr;
 
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+pub use crate::sys::op::ConnectWithData;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::sys::op::Splice;
+#[cfg(windows)]
+pub use crate::sys::op::TransmitFile;
 pub use crate::sys::op::{
     Accept, FileStat, OpenFile, PathStat, Recv, RecvFrom, RecvFromVectored, RecvVectored, Send,
-    SendTo, SendToVectored, SendVectored,
+    SendMsg, SendTo, SendToVectored, SendVectored,
 };
-#[cfg(windows)]
-pub use crate::sys::op::{ConnectNamedPipe, FileMetadata};
 #[cfg(unix)]
-pub use crate::sys::op::{ReadVectoredAt, WriteVectoredAt};
+pub use crate::sys::op::{
+    AcceptMulti, Interest, PollOnce, ReadVectoredAt, RecvMsg, RecvMulti, SendZc, WriteVectoredAt,
+};
+#[cfg(windows)]
+pub use crate::sys::op::{AcceptWithData, ConnectNamedPipe, FileMetadata};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use crate::sys::op::{RecvMmsg, SendMmsg};
 use crate::sys::{sockaddr_storage, socklen_t, RawFd};
 
 /// Trait to update the buffer length inside the [`BufResult`].
@@ -92,6 +102,56 @@ impl<F, D> IntoInner for Asyncify<F, D> {
     }
 }
 
+/// An operation with the submission hints for the driver.
+///
+/// The hints only take effect with the io-uring driver, and are ignored by
+/// the others.
+pub struct Hinted<T> {
+    pub(crate) op: T,
+    pub(crate) force_async: bool,
+    pub(crate) ioprio: Option<u16>,
+}
+
+impl<T> Hinted<T> {
+    /// Create [`Hinted`] without any hints.
+    pub fn new(op: T) -> Self {
+        Self {
+            op,
+            force_async: false,
+            ioprio: None,
+        }
+    }
+
+    /// Always punt the operation to the io-wq workers (`IOSQE_ASYNC`),
+    /// instead of trying it inline on submission first. It saves the
+    /// submission time for the operations known to block, e.g. the file
+    /// reads on a cold cache.
+    pub fn force_async(mut self, force_async: bool) -> Self {
+        self.force_async = force_async;
+        self
+    }
+
+    /// Set the I/O priority of the operation, as the value of `ioprio_set(2)`,
+    /// i.e. the class in the top 3 bits and the level in the others. Only the
+    /// positional reads and writes of files take it.
+    pub fn ioprio(mut self, ioprio: u16) -> Self {
+        self.ioprio = Some(ioprio);
+        self
+    }
+
+    pub(crate) fn op_pin(self: std::pin::Pin<&mut Self>) -> std::pin::Pin<&mut T> {
+        unsafe { self.map_unchecked_mut(|this| &mut this.op) }
+    }
+}
+
+impl<T> IntoInner for Hinted<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.op
+    }
+}
+
 /// Close the file fd.
 pub struct CloseFile {
     pub(crate) fd: RawFd,
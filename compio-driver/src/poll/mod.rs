@@ -17,12 +17,12 @@ use compio_log::{instrument, trace};
 use crossbeam_queue::SegQueue;
 pub(crate) use libc::{sockaddr_storage, socklen_t};
 use polling::{Event, Events, Poller};
-use slab::Slab;
 
-use crate::{syscall, AsyncifyPool, Entry, OutEntries, ProactorBuilder};
+use crate::{syscall, AsyncifyPool, BufferPool, Entry, OutEntries, ProactorBuilder, Registry};
 
 pub(crate) mod op;
 
+pub use crate::unix::op::Interest;
 pub(crate) use crate::unix::RawOp;
 
 /// Abstraction of operations.
@@ -88,15 +88,6 @@ pub struct WaitArg {
     pub interest: Interest,
 }
 
-/// The interest of the operation
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Interest {
-    /// Represents a read operation.
-    Readable,
-    /// Represents a write operation.
-    Writable,
-}
-
 #[derive(Debug, Default)]
 struct FdQueue {
     read_queue: VecDeque<usize>,
@@ -139,6 +130,20 @@ impl FdQueue {
         None
     }
 
+    pub fn remove(&mut self, user_data: usize) -> bool {
+        for queue in [&mut self.read_queue, &mut self.write_queue] {
+            if let Some(pos) = queue.iter().position(|u| *u == user_data) {
+                queue.remove(pos);
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn drain(&mut self) -> impl Iterator<Item = usize> + '_ {
+        self.read_queue.drain(..).chain(self.write_queue.drain(..))
+    }
+
     pub fn clear(&mut self) {
         self.read_queue.clear();
         self.write_queue.clear();
@@ -151,6 +156,8 @@ pub(crate) struct Driver {
     poll: Arc<Poller>,
     registry: HashMap<RawFd, FdQueue>,
     cancelled: HashSet<usize>,
+    // The interrupted ops to be completed in the next poll.
+    interrupted: Vec<usize>,
     pool: AsyncifyPool,
     pool_completed: Arc<SegQueue<Entry>>,
 }
@@ -171,6 +178,7 @@ impl Driver {
             poll: Arc::new(Poller::new()?),
             registry: HashMap::new(),
             cancelled: HashSet::new(),
+            interrupted: Vec::new(),
             pool: builder.create_or_get_thread_pool(),
             pool_completed: Arc::new(SegQueue::new()),
         })
@@ -214,29 +222,53 @@ impl Driver {
         Ok(())
     }
 
-    pub fn cancel(&mut self, user_data: usize, _registry: &mut Slab<RawOp>) {
+    pub fn cancel(&mut self, user_data: usize, _registry: &mut Registry) {
         self.cancelled.insert(user_data);
     }
 
-    pub fn push(&mut self, user_data: usize, op: &mut RawOp) -> Poll<io::Result<usize>> {
+    pub fn interrupt(&mut self, user_data: usize, _registry: &mut Registry) {
+        // The blocking ops are not in the queues, and can't be interrupted.
+        let Some((fd, queue)) = self
+            .registry
+            .iter_mut()
+            .find_map(|(fd, queue)| queue.remove(user_data).then_some((fd, queue)))
+        else {
+            return;
+        };
+        let event = queue.event(*fd as usize);
+        unsafe {
+            let fd = BorrowedFd::borrow_raw(*fd);
+            // The stale interest is only a spurious wakeup.
+            self.poll.modify(fd, event).ok();
+        }
+        self.interrupted.push(user_data);
+    }
+
+    pub fn push(
+        &mut self,
+        user_data: usize,
+        op: &mut RawOp,
+    ) -> io::Result<Poll<io::Result<usize>>> {
         if self.cancelled.remove(&user_data) {
-            Poll::Ready(Err(io::Error::from_raw_os_error(libc::ETIMEDOUT)))
+            Ok(Poll::Ready(Err(io::Error::from_raw_os_error(
+                libc::ETIMEDOUT,
+            ))))
         } else {
             let op_pin = op.as_pin();
             match op_pin.pre_submit() {
                 Ok(Decision::Wait(arg)) => {
                     self.submit(user_data, arg)?;
-                    Poll::Pending
+                    Ok(Poll::Pending)
                 }
-                Ok(Decision::Completed(res)) => Poll::Ready(Ok(res)),
+                Ok(Decision::Completed(res)) => Ok(Poll::Ready(Ok(res))),
                 Ok(Decision::Blocking(event)) => {
                     if self.push_blocking(user_data, op, event) {
-                        Poll::Pending
+                        Ok(Poll::Pending)
                     } else {
-                        Poll::Ready(Err(io::Error::from_raw_os_error(libc::EBUSY)))
+                        Err(io::Error::from_raw_os_error(libc::EBUSY))
                     }
                 }
-                Err(err) => Poll::Ready(Err(err)),
+                Err(err) => Ok(Poll::Ready(Err(err))),
             }
         }
     }
@@ -270,11 +302,30 @@ impl Driver {
         timeout: Option<Duration>,
         mut entries: OutEntries<impl Extend<usize>>,
     ) -> io::Result<()> {
+        // Don't wait if some ops are already interrupted.
+        let timeout = if self.interrupted.is_empty() {
+            timeout
+        } else {
+            Some(Duration::ZERO)
+        };
+        // The new events are appended to the list.
+        self.events.clear();
         self.poll.wait(&mut self.events, timeout)?;
-        if self.events.is_empty() && self.pool_completed.is_empty() && timeout.is_some() {
+        if self.events.is_empty()
+            && self.pool_completed.is_empty()
+            && self.interrupted.is_empty()
+            && timeout.is_some()
+        {
             return Err(io::Error::from_raw_os_error(libc::ETIMEDOUT));
         }
+        for user_data in self.interrupted.drain(..) {
+            self.cancelled.remove(&user_data);
+            let res = Err(io::Error::from_raw_os_error(libc::ECANCELED));
+            entries.extend(Some(Entry::new(user_data, res)));
+        }
         while let Some(entry) = self.pool_completed.pop() {
+            // The blocking ops can't be cancelled.
+            self.cancelled.remove(&entry.user_data());
             entries.extend(Some(entry));
         }
         for event in self.events.iter() {
@@ -304,11 +355,40 @@ impl Driver {
             }
             let renew_event = queue.event(fd as _);
             let fd = BorrowedFd::borrow_raw(fd);
-            self.poll.modify(fd, renew_event)?;
+            if let Err(e) = self.poll.modify(fd, renew_event) {
+                // The fd may be closed after the event is reported, e.g. by a
+                // blocking close or a foreign library. Fail the operations
+                // waiting for it instead of the driver.
+                let code = e.raw_os_error().unwrap_or(libc::EBADF);
+                for user_data in queue.drain() {
+                    if self.cancelled.remove(&user_data) {
+                        entries.extend(Some(entry_cancelled(user_data)));
+                    } else {
+                        let res = Err(io::Error::from_raw_os_error(code));
+                        entries.extend(Some(Entry::new(user_data, res)));
+                    }
+                }
+            }
         }
         Ok(())
     }
 
+    pub fn thread_pool(&self) -> &AsyncifyPool {
+        &self.pool
+    }
+
+    pub fn create_buffer_pool(
+        &mut self,
+        entries: u16,
+        buffer_len: usize,
+    ) -> io::Result<BufferPool> {
+        BufferPool::new(entries, buffer_len)
+    }
+
+    pub fn release_buffer_pool(&mut self, _pool: BufferPool) -> io::Result<()> {
+        Ok(())
+    }
+
     pub fn handle(&self) -> io::Result<NotifyHandle> {
         Ok(NotifyHandle::new(self.poll.clone()))
     }
@@ -4,9 +4,9 @@ use compio_buf::{
     BufResult, IntoInner, IoBuf, IoBufMut, IoSlice, IoSliceMut, IoVectoredBuf, IoVectoredBufMut,
 };
 #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
-use libc::open;
+use libc::openat;
 #[cfg(all(target_os = "linux", target_env = "gnu"))]
-use libc::open64 as open;
+use libc::openat64 as openat;
 #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "hurd")))]
 use libc::{pread, preadv, pwrite, pwritev};
 #[cfg(any(target_os = "linux", target_os = "android", target_os = "hurd"))]
@@ -18,6 +18,26 @@ use super::{sockaddr_storage, socklen_t, syscall, Decision, OpCode, RawFd};
 use crate::op::*;
 pub use crate::unix::op::*;
 
+// The platforms without it use `SO_NOSIGPIPE` instead.
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+const MSG_NOSIGNAL: i32 = libc::MSG_NOSIGNAL;
+#[cfg(not(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+const MSG_NOSIGNAL: i32 = 0;
+
 impl<
     D: std::marker::Send + 'static,
     F: (FnOnce() -> BufResult<usize, D>) + std::marker::Send + std::marker::Sync + 'static,
@@ -46,7 +66,18 @@ impl OpCode for OpenFile {
     }
 
     fn on_event(self: Pin<&mut Self>, _: &Event) -> Poll<io::Result<usize>> {
-        Poll::Ready(Ok(syscall!(open(
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        if self.how.resolve != 0 {
+            return Poll::Ready(Ok(syscall!(libc::syscall(
+                libc::SYS_openat2,
+                self.dirfd,
+                self.path.as_ptr(),
+                std::ptr::addr_of!(self.how),
+                std::mem::size_of::<libc::open_how>()
+            ))? as _));
+        }
+        Poll::Ready(Ok(syscall!(openat(
+            self.dirfd,
             self.path.as_ptr(),
             self.flags,
             self.mode as libc::c_int
@@ -54,6 +85,16 @@ impl OpCode for OpenFile {
     }
 }
 
+impl<T: OpCode> OpCode for Hinted<T> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        self.op_pin().pre_submit()
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        self.op_pin().on_event(event)
+    }
+}
+
 impl OpCode for CloseFile {
     fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
         Ok(Decision::blocking_dummy())
@@ -117,6 +158,7 @@ impl IntoInner for FileStat {
 
 /// Get metadata from path.
 pub struct PathStat {
+    pub(crate) dirfd: RawFd,
     pub(crate) path: CString,
     pub(crate) stat: libc::stat,
     pub(crate) follow_symlink: bool,
@@ -125,7 +167,14 @@ pub struct PathStat {
 impl PathStat {
     /// Create [`PathStat`].
     pub fn new(path: CString, follow_symlink: bool) -> Self {
+        Self::with_dirfd(libc::AT_FDCWD, path, follow_symlink)
+    }
+
+    /// Create [`PathStat`] with a relative path resolved from the directory
+    /// `dirfd`.
+    pub fn with_dirfd(dirfd: RawFd, path: CString, follow_symlink: bool) -> Self {
         Self {
+            dirfd,
             path,
             stat: unsafe { std::mem::zeroed() },
             follow_symlink,
@@ -147,7 +196,7 @@ impl OpCode for PathStat {
             }
             let mut s: libc::statx = unsafe { std::mem::zeroed() };
             syscall!(libc::statx(
-                libc::AT_FDCWD,
+                self.dirfd,
                 self.path.as_ptr(),
                 flags,
                 0,
@@ -158,12 +207,17 @@ impl OpCode for PathStat {
         }
         #[cfg(not(any(target_os = "linux", target_os = "android")))]
         {
-            let f = if self.follow_symlink {
-                libc::stat
+            let flags = if self.follow_symlink {
+                0
             } else {
-                libc::lstat
+                libc::AT_SYMLINK_NOFOLLOW
             };
-            Poll::Ready(Ok(syscall!(f(self.path.as_ptr(), &mut self.stat))? as _))
+            Poll::Ready(Ok(syscall!(libc::fstatat(
+                self.dirfd,
+                self.path.as_ptr(),
+                &mut self.stat,
+                flags
+            ))? as _))
         }
     }
 }
@@ -348,6 +402,34 @@ impl OpCode for Accept {
     }
 }
 
+impl OpCode for AcceptMulti {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        syscall!(
+            libc::accept(self.fd, std::ptr::null_mut(), std::ptr::null_mut()),
+            wait_readable(self.fd)
+        )
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.readable);
+
+        syscall!(break libc::accept(self.fd, std::ptr::null_mut(), std::ptr::null_mut()))
+    }
+}
+
+impl OpCode for RecvMulti {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the multishot receive is only supported by io-uring driver",
+        ))
+    }
+
+    fn on_event(self: Pin<&mut Self>, _event: &Event) -> Poll<io::Result<usize>> {
+        unreachable!("the multishot receive is never submitted")
+    }
+}
+
 impl OpCode for Connect {
     fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
         syscall!(
@@ -388,8 +470,13 @@ impl<T: IoBufMut> OpCode for Recv<T> {
         debug_assert!(event.readable);
 
         let fd = self.fd;
+        let flags = self.flags;
         let slice = unsafe { self.get_unchecked_mut() }.buffer.as_mut_slice();
-        syscall!(break libc::read(fd, slice.as_mut_ptr() as _, slice.len()))
+        if let Some(flags) = flags {
+            syscall!(break libc::recv(fd, slice.as_mut_ptr() as _, slice.len(), flags))
+        } else {
+            syscall!(break libc::read(fd, slice.as_mut_ptr() as _, slice.len()))
+        }
     }
 }
 
@@ -416,7 +503,24 @@ impl<T: IoBuf> OpCode for Send<T> {
         debug_assert!(event.writable);
 
         let slice = self.buffer.as_slice();
-        syscall!(break libc::write(self.fd, slice.as_ptr() as _, slice.len()))
+        if self.flags != 0 {
+            syscall!(break libc::send(self.fd, slice.as_ptr() as _, slice.len(), self.flags))
+        } else {
+            syscall!(break libc::write(self.fd, slice.as_ptr() as _, slice.len()))
+        }
+    }
+}
+
+impl<T: IoBuf> OpCode for SendZc<T> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        Ok(Decision::wait_writable(self.fd))
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.writable);
+
+        let slice = self.buffer.as_slice();
+        syscall!(break libc::send(self.fd, slice.as_ptr() as _, slice.len(), MSG_NOSIGNAL))
     }
 }
 
@@ -440,17 +544,24 @@ pub struct RecvFrom<T: IoBufMut> {
     pub(crate) buffer: T,
     pub(crate) addr: sockaddr_storage,
     pub(crate) addr_len: socklen_t,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBufMut> RecvFrom<T> {
     /// Create [`RecvFrom`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`RecvFrom`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
             fd,
             buffer,
             addr: unsafe { std::mem::zeroed() },
             addr_len: std::mem::size_of::<sockaddr_storage>() as _,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -463,7 +574,7 @@ impl<T: IoBufMut> RecvFrom<T> {
             fd,
             slice.as_mut_ptr() as _,
             slice.len(),
-            0,
+            this.flags,
             &mut this.addr as *mut _ as _,
             &mut this.addr_len,
         )
@@ -560,16 +671,23 @@ pub struct SendTo<T: IoBuf> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
     pub(crate) addr: SockAddr,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBuf> SendTo<T> {
     /// Create [`SendTo`].
     pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self::with_flags(fd, buffer, addr, 0)
+    }
+
+    /// Create [`SendTo`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, addr: SockAddr, flags: i32) -> Self {
         Self {
             fd,
             buffer,
             addr,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -580,7 +698,7 @@ impl<T: IoBuf> SendTo<T> {
             self.fd,
             slice.as_ptr() as _,
             slice.len(),
-            0,
+            self.flags,
             self.addr.as_ptr(),
             self.addr.len(),
         )
@@ -607,6 +725,63 @@ impl<T: IoBuf> IntoInner for SendTo<T> {
     }
 }
 
+/// Connect to a remote address with TCP Fast Open, sending the data in the
+/// SYN.
+///
+/// It resolves to the length sent. It fails with `EINPROGRESS` if the SYN is
+/// sent without the data, e.g. without a TFO cookie of the server, and the
+/// data should be sent after the connection is established.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct ConnectWithData<T: IoBuf> {
+    pub(crate) fd: RawFd,
+    pub(crate) buffer: T,
+    pub(crate) addr: SockAddr,
+    _p: PhantomPinned,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> ConnectWithData<T> {
+    /// Create [`ConnectWithData`].
+    pub fn new(fd: RawFd, buffer: T, addr: SockAddr) -> Self {
+        Self {
+            fd,
+            buffer,
+            addr,
+            _p: PhantomPinned,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> OpCode for ConnectWithData<T> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        // A retried `sendto` would fail with `EISCONN`, so it isn't waited.
+        let slice = self.buffer.as_slice();
+        let res = syscall!(libc::sendto(
+            self.fd,
+            slice.as_ptr() as _,
+            slice.len(),
+            libc::MSG_FASTOPEN,
+            self.addr.as_ptr(),
+            self.addr.len(),
+        ))?;
+        Ok(Decision::Completed(res as _))
+    }
+
+    fn on_event(self: Pin<&mut Self>, _event: &Event) -> Poll<io::Result<usize>> {
+        unreachable!("the fast open connect is never waited")
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> IntoInner for ConnectWithData<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
 /// Send data to specified address from vectored buffer.
 pub struct SendToVectored<T: IoVectoredBuf> {
     pub(crate) fd: RawFd,
@@ -669,3 +844,125 @@ impl<T: IoVectoredBuf> IntoInner for SendToVectored<T> {
         self.buffer
     }
 }
+
+impl<T: IoVectoredBuf, C: IoBuf> SendMsg<T, C> {
+    unsafe fn call(&self) -> libc::ssize_t {
+        libc::sendmsg(self.fd, &self.msg, 0)
+    }
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> OpCode for SendMsg<T, C> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msg();
+        syscall!(this.call(), wait_writable(this.fd))
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.writable);
+
+        syscall!(break self.call())
+    }
+}
+
+impl<T: IoVectoredBufMut, C: IoBufMut> RecvMsg<T, C> {
+    unsafe fn call(&mut self) -> libc::ssize_t {
+        libc::recvmsg(self.fd, &mut self.msg, self.flags)
+    }
+}
+
+impl<T: IoVectoredBufMut, C: IoBufMut> OpCode for RecvMsg<T, C> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msg();
+        syscall!(this.call(), wait_readable(this.fd))
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.readable);
+
+        let this = unsafe { self.get_unchecked_mut() };
+        // The lengths may be clobbered by the last try.
+        this.set_msg();
+        syscall!(break this.call())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> OpCode for SendMmsg<T> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msgs();
+        syscall!(this.call(), wait_writable(this.fd))
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.writable);
+
+        let this = unsafe { self.get_unchecked_mut() };
+        syscall!(break this.call())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBufMut> OpCode for RecvMmsg<T> {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        let this = unsafe { self.get_unchecked_mut() };
+        this.set_msgs();
+        syscall!(this.call(), wait_readable(this.fd))
+    }
+
+    fn on_event(self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.readable);
+
+        let this = unsafe { self.get_unchecked_mut() };
+        // The lengths may be clobbered by the last try.
+        this.set_msgs();
+        syscall!(break this.call())
+    }
+}
+
+impl OpCode for PollOnce {
+    fn pre_submit(self: Pin<&mut Self>) -> io::Result<Decision> {
+        Ok(Decision::wait_for(self.fd, self.interest))
+    }
+
+    fn on_event(self: Pin<&mut Self>, _: &Event) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(0))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Splice {
+    fn call(&mut self) -> libc::ssize_t {
+        let mut offset_in = self.offset_in.map(|offset| offset as libc::loff_t);
+        let mut offset_out = self.offset_out.map(|offset| offset as libc::loff_t);
+        unsafe {
+            libc::splice(
+                self.fd_in,
+                offset_in
+                    .as_mut()
+                    .map_or(std::ptr::null_mut(), |offset| offset),
+                self.fd_out,
+                offset_out
+                    .as_mut()
+                    .map_or(std::ptr::null_mut(), |offset| offset),
+                self.len,
+                self.flags | libc::SPLICE_F_NONBLOCK,
+            )
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl OpCode for Splice {
+    fn pre_submit(mut self: Pin<&mut Self>) -> io::Result<Decision> {
+        syscall!(self.call(), wait_writable(self.fd_out))
+    }
+
+    fn on_event(mut self: Pin<&mut Self>, event: &Event) -> Poll<io::Result<usize>> {
+        debug_assert!(event.writable);
+
+        syscall!(break self.call())
+    }
+}
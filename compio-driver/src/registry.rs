@@ -0,0 +1,102 @@
+use std::ops::{Index, IndexMut};
+
+use slab::Slab;
+
+use crate::sys::RawOp;
+
+// The user data of an op is its slot in the slab, with the generation of the
+// slot in the high bits. A slot is reused after its op is removed, and the
+// generation makes sure that a late completion or cancellation of the removed
+// op is not attributed to the new one.
+const KEY_BITS: u32 = if usize::BITS >= 64 { 32 } else { 20 };
+const KEY_MASK: usize = (1 << KEY_BITS) - 1;
+const GEN_MASK: usize = usize::MAX >> KEY_BITS;
+
+/// The operations owned by the proactor, indexed by their user data.
+pub(crate) struct Registry {
+    ops: Slab<RawOp>,
+    generations: Vec<usize>,
+}
+
+impl Registry {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            ops: Slab::with_capacity(capacity),
+            generations: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn split(user_data: usize) -> (usize, usize) {
+        (user_data & KEY_MASK, user_data >> KEY_BITS)
+    }
+
+    /// Insert the op created with its user data.
+    pub fn insert(&mut self, f: impl FnOnce(usize) -> RawOp) -> (usize, &mut RawOp) {
+        let entry = self.ops.vacant_entry();
+        let key = entry.key();
+        // The top keys are left for the reserved user data of the drivers.
+        assert!(key < KEY_MASK - 1, "too many operations in flight");
+        if key == self.generations.len() {
+            self.generations.push(0);
+        }
+        let generation = (self.generations[key] + 1) & GEN_MASK;
+        self.generations[key] = generation;
+        let user_data = (generation << KEY_BITS) | key;
+        (user_data, entry.insert(f(user_data)))
+    }
+
+    pub fn get(&self, user_data: usize) -> Option<&RawOp> {
+        let (key, generation) = Self::split(user_data);
+        if self.generations.get(key) == Some(&generation) {
+            self.ops.get(key)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, user_data: usize) -> Option<&mut RawOp> {
+        let (key, generation) = Self::split(user_data);
+        if self.generations.get(key) == Some(&generation) {
+            self.ops.get_mut(key)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains(&self, user_data: usize) -> bool {
+        self.get(user_data).is_some()
+    }
+
+    pub fn try_remove(&mut self, user_data: usize) -> Option<RawOp> {
+        if self.contains(user_data) {
+            self.ops.try_remove(Self::split(user_data).0)
+        } else {
+            None
+        }
+    }
+
+    pub fn remove(&mut self, user_data: usize) -> RawOp {
+        self.try_remove(user_data)
+            .expect("the user data should be valid")
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+impl Index<usize> for Registry {
+    type Output = RawOp;
+
+    fn index(&self, user_data: usize) -> &RawOp {
+        self.get(user_data).expect("the user data should be valid")
+    }
+}
+
+impl IndexMut<usize> for Registry {
+    fn index_mut(&mut self, user_data: usize) -> &mut RawOp {
+        self.get_mut(user_data)
+            .expect("the user data should be valid")
+    }
+}
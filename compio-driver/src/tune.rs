@@ -0,0 +1,123 @@
+//! Probe the system to choose the default sizes of [`ProactorBuilder`].
+//!
+//! [`ProactorBuilder`]: crate::ProactorBuilder
+
+const GIB: u64 = 1 << 30;
+
+pub(crate) struct Tuned {
+    pub capacity: u32,
+    pub thread_limit: usize,
+}
+
+pub(crate) fn autotune() -> Tuned {
+    let cpus = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let memory = total_memory();
+    let kernel = kernel_version();
+
+    let mut capacity = match memory {
+        Some(m) if m < GIB => 256,
+        Some(m) if m >= 8 * GIB && cpus >= 4 => 4096,
+        _ => 1024,
+    };
+    // The kernels before 5.5 drop the completions when the completion queue
+    // overflows, so use a larger ring to avoid it.
+    if kernel.is_some_and(|v| v < (5, 5)) {
+        capacity *= 2;
+    }
+    // The kernels before 5.12 charge the rings to `RLIMIT_MEMLOCK`, which is
+    // only 64 KiB on many systems, and fail to create a larger ring.
+    if kernel.is_some_and(|v| v < (5, 12)) {
+        if let Some(limit) = memlock_limit() {
+            while capacity > 1 && ring_size(capacity) > limit {
+                capacity /= 2;
+            }
+        }
+    }
+    // The blocking operations are mostly waiting for the disks, so more
+    // threads than the CPUs help.
+    let thread_limit = (cpus * 16).clamp(64, 512);
+    Tuned {
+        capacity,
+        thread_limit,
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn total_memory() -> Option<u64> {
+    let mut info: libc::sysinfo = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sysinfo(&mut info) } != 0 {
+        return None;
+    }
+    Some(info.totalram as u64 * info.mem_unit as u64)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+fn total_memory() -> Option<u64> {
+    let pages = unsafe { libc::sysconf(libc::_SC_PHYS_PAGES) };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if pages <= 0 || page_size <= 0 {
+        return None;
+    }
+    Some(pages as u64 * page_size as u64)
+}
+
+#[cfg(windows)]
+fn total_memory() -> Option<u64> {
+    use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+
+    let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as _;
+    if unsafe { GlobalMemoryStatusEx(&mut status) } == 0 {
+        return None;
+    }
+    Some(status.ullTotalPhys)
+}
+
+/// The memory charged for a ring of `entries`, estimated as the kernel does
+/// in pages: the ring header with the indices and the completions, which are
+/// twice as many as the entries, and then the submission entries.
+fn ring_size(entries: u32) -> u64 {
+    const PAGE: u64 = 4096;
+
+    let entries = entries as u64;
+    let rings = (320 + entries * (2 * 16 + 4)).div_ceil(PAGE);
+    let sqes = (entries * 64).div_ceil(PAGE);
+    (rings + sqes) * PAGE
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn memlock_limit() -> Option<u64> {
+    let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) } != 0
+        || limit.rlim_cur == libc::RLIM_INFINITY
+    {
+        return None;
+    }
+    Some(limit.rlim_cur as u64)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn memlock_limit() -> Option<u64> {
+    None
+}
+
+/// The major and minor version of the Linux kernel.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn kernel_version() -> Option<(u32, u32)> {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return None;
+    }
+    let release = unsafe { std::ffi::CStr::from_ptr(name.release.as_ptr()) };
+    let mut parts = release.to_str().ok()?.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub(crate) fn kernel_version() -> Option<(u32, u32)> {
+    None
+}
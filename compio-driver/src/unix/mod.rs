@@ -3,6 +3,8 @@
 
 pub(crate) mod op;
 
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use std::collections::VecDeque;
 use std::{io, mem::ManuallyDrop, pin::Pin, ptr::NonNull};
 
 use compio_buf::BufResult;
@@ -15,15 +17,21 @@ pub(crate) struct RawOp {
     // completes; the runtime holds the strong ref until the future is dropped.
     cancelled: bool,
     result: Option<io::Result<usize>>,
+    flags: u32,
+    // The results of a multishot op before the final one, with their flags.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    more: VecDeque<(io::Result<usize>, u32)>,
 }
 
 impl RawOp {
-    pub(crate) fn new(_user_data: usize, op: impl OpCode + 'static) -> Self {
-        let op = Box::new(op);
+    pub(crate) fn from_box<T: OpCode + 'static>(_user_data: usize, op: Box<T>) -> Self {
         Self {
             op: unsafe { NonNull::new_unchecked(Box::into_raw(op as Box<dyn OpCode>)) },
             cancelled: false,
             result: None,
+            flags: 0,
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            more: VecDeque::new(),
         }
     }
 
@@ -45,20 +53,70 @@ impl RawOp {
         self.result.is_some()
     }
 
+    /// Queue a result of a multishot op, or release it if the op has been
+    /// cancelled. Returns `true` if it is queued.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub fn push_more(&mut self, res: io::Result<usize>, flags: u32) -> bool {
+        if self.cancelled {
+            if let Ok(res) = res {
+                self.as_pin().release_result(res, flags);
+            }
+            false
+        } else {
+            self.more.push_back((res, flags));
+            true
+        }
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub fn pop_more(&mut self) -> Option<(io::Result<usize>, u32)> {
+        self.more.pop_front()
+    }
+
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    fn release_all_more(&mut self) {
+        while let Some((res, flags)) = self.more.pop_front() {
+            if let Ok(res) = res {
+                self.as_pin().release_result(res, flags);
+            }
+        }
+    }
+
+    pub fn set_flags(&mut self, flags: u32) {
+        self.flags = flags;
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
     /// # Safety
     /// The caller should ensure the correct type.
     ///
     /// # Panics
     /// This function will panic if the result has not been set.
-    pub unsafe fn into_inner<T: OpCode>(self) -> BufResult<usize, T> {
+    pub unsafe fn into_box<T: OpCode>(self) -> BufResult<usize, Box<T>> {
         let mut this = ManuallyDrop::new(self);
-        let op = *Box::from_raw(this.op.cast().as_ptr());
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            this.release_all_more();
+            drop(std::mem::take(&mut this.more));
+        }
+        let op = Box::from_raw(this.op.cast().as_ptr());
         BufResult(this.result.take().unwrap(), op)
     }
 }
 
 impl Drop for RawOp {
     fn drop(&mut self) {
+        #[cfg(all(target_os = "linux", feature = "io-uring"))]
+        {
+            self.release_all_more();
+            if let Some(Ok(res)) = self.result {
+                let flags = self.flags;
+                self.as_pin().release_result(res, flags);
+            }
+        }
         if self.has_result() {
             let _ = unsafe { Box::from_raw(self.op.as_ptr()) };
         }
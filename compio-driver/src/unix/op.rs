@@ -6,19 +6,55 @@ use compio_buf::{
 use libc::{sockaddr_storage, socklen_t};
 use socket2::SockAddr;
 
-use crate::{op::*, sys::RawFd};
+use crate::{op::*, sys::RawFd, BufferPool};
 
 /// Open or create a file with flags and mode.
 pub struct OpenFile {
+    pub(crate) dirfd: RawFd,
     pub(crate) path: CString,
     pub(crate) flags: i32,
     pub(crate) mode: libc::mode_t,
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) how: libc::open_how,
 }
 
 impl OpenFile {
     /// Create [`OpenFile`].
     pub fn new(path: CString, flags: i32, mode: libc::mode_t) -> Self {
-        Self { path, flags, mode }
+        Self::with_dirfd(libc::AT_FDCWD, path, flags, mode)
+    }
+
+    /// Create [`OpenFile`] with a relative path resolved from the directory
+    /// `dirfd`.
+    pub fn with_dirfd(dirfd: RawFd, path: CString, flags: i32, mode: libc::mode_t) -> Self {
+        Self {
+            dirfd,
+            path,
+            flags,
+            mode,
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            how: unsafe { std::mem::zeroed() },
+        }
+    }
+
+    /// Create [`OpenFile`] with the `RESOLVE_*` flags of `openat2`. The file
+    /// is opened with `openat2` if `resolve` is not zero.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn with_resolve(
+        dirfd: RawFd,
+        path: CString,
+        flags: i32,
+        mode: libc::mode_t,
+        resolve: u64,
+    ) -> Self {
+        let mut op = Self::with_dirfd(dirfd, path, flags, mode);
+        op.how.flags = flags as _;
+        // `openat2` rejects the mode if the file is not created.
+        if flags & libc::O_CREAT != 0 || flags & libc::O_TMPFILE == libc::O_TMPFILE {
+            op.how.mode = mode as _;
+        }
+        op.how.resolve = resolve;
+        op
     }
 }
 
@@ -135,23 +171,90 @@ impl Accept {
 
     /// Get the remote address from the inner buffer.
     pub fn into_addr(self) -> SockAddr {
+        self.addr()
+    }
+
+    /// Get the remote address from the inner buffer without consuming it.
+    pub fn addr(&self) -> SockAddr {
         unsafe { SockAddr::new(self.buffer, self.addr_len) }
     }
+
+    /// Reset the op to accept on `fd` again, reusing the address buffer and
+    /// the allocation of a boxed op.
+    pub fn reset(&mut self, fd: RawFd) {
+        self.fd = fd;
+        self.addr_len = std::mem::size_of::<sockaddr_storage>() as _;
+    }
+}
+
+/// Accept connections repeatedly, without the remote addresses.
+///
+/// On io-uring driver, it is a multishot accept, which completes once for
+/// every connection until it fails or is cancelled. The connections are
+/// popped with [`Proactor::pop_multishot`](crate::Proactor::pop_multishot)
+/// before the final result. On other drivers, it accepts only one connection.
+pub struct AcceptMulti {
+    pub(crate) fd: RawFd,
+}
+
+impl AcceptMulti {
+    /// Create [`AcceptMulti`].
+    pub fn new(fd: RawFd) -> Self {
+        Self { fd }
+    }
+}
+
+/// Receive data repeatedly into the buffers selected from a [`BufferPool`].
+///
+/// It is a multishot receive of io-uring driver, which completes once for
+/// every packet of data until it fails, reaches the end, runs out of the
+/// buffers, or is cancelled. The results are popped with
+/// [`Proactor::pop_multishot_with_flags`](crate::Proactor::pop_multishot_with_flags),
+/// and the buffers are taken with [`BufferPool::take_selected`]. The pool
+/// should be provided to the kernel, or the operation fails. It is not
+/// supported by other drivers.
+#[cfg_attr(not(all(target_os = "linux", feature = "io-uring")), allow(dead_code))]
+pub struct RecvMulti {
+    pub(crate) fd: RawFd,
+    pub(crate) pool: BufferPool,
+}
+
+impl RecvMulti {
+    /// Create [`RecvMulti`].
+    pub fn new(fd: RawFd, pool: &BufferPool) -> Self {
+        Self {
+            fd,
+            pool: pool.clone(),
+        }
+    }
 }
 
 /// Receive data from remote.
 pub struct Recv<T: IoBufMut> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
+    // `None` reads the fd, which may not be a socket.
+    pub(crate) flags: Option<i32>,
     _p: PhantomPinned,
 }
 
 impl<T: IoBufMut> Recv<T> {
-    /// Create [`Recv`].
+    /// Create [`Recv`]. It reads the fd, which could be a pipe.
     pub fn new(fd: RawFd, buffer: T) -> Self {
         Self {
             fd,
             buffer,
+            flags: None,
+            _p: PhantomPinned,
+        }
+    }
+
+    /// Create [`Recv`] with the `MSG_*` flags. The fd should be a socket.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
+        Self {
+            fd,
+            buffer,
+            flags: Some(flags),
             _p: PhantomPinned,
         }
     }
@@ -197,15 +300,22 @@ impl<T: IoVectoredBufMut> IntoInner for RecvVectored<T> {
 pub struct Send<T: IoBuf> {
     pub(crate) fd: RawFd,
     pub(crate) buffer: T,
+    pub(crate) flags: i32,
     _p: PhantomPinned,
 }
 
 impl<T: IoBuf> Send<T> {
     /// Create [`Send`].
     pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self::with_flags(fd, buffer, 0)
+    }
+
+    /// Create [`Send`] with the `MSG_*` flags.
+    pub fn with_flags(fd: RawFd, buffer: T, flags: i32) -> Self {
         Self {
             fd,
             buffer,
+            flags,
             _p: PhantomPinned,
         }
     }
@@ -219,6 +329,36 @@ impl<T: IoBuf> IntoInner for Send<T> {
     }
 }
 
+/// Send data to remote without copying it, with `IORING_OP_SEND_ZC` on
+/// io-uring driver.
+///
+/// It completes with the length sent after the kernel notifies that the
+/// buffer is no longer referenced. It is an ordinary send on other drivers.
+pub struct SendZc<T: IoBuf> {
+    pub(crate) fd: RawFd,
+    pub(crate) buffer: T,
+    _p: PhantomPinned,
+}
+
+impl<T: IoBuf> SendZc<T> {
+    /// Create [`SendZc`].
+    pub fn new(fd: RawFd, buffer: T) -> Self {
+        Self {
+            fd,
+            buffer,
+            _p: PhantomPinned,
+        }
+    }
+}
+
+impl<T: IoBuf> IntoInner for SendZc<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.buffer
+    }
+}
+
 /// Send data to remote from vectored buffer.
 pub struct SendVectored<T: IoVectoredBuf> {
     pub(crate) fd: RawFd,
@@ -246,3 +386,339 @@ impl<T: IoVectoredBuf> IntoInner for SendVectored<T> {
         self.buffer
     }
 }
+
+/// Send data to specified address accompanied by ancillary data from vectored
+/// buffer.
+pub struct SendMsg<T: IoVectoredBuf, C: IoBuf> {
+    pub(crate) msg: libc::msghdr,
+    pub(crate) fd: RawFd,
+    pub(crate) buffer: T,
+    pub(crate) control: C,
+    pub(crate) addr: Option<SockAddr>,
+    pub(crate) slices: Vec<IoSlice>,
+    _p: PhantomPinned,
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> SendMsg<T, C> {
+    /// Create [`SendMsg`].
+    pub fn new(fd: RawFd, buffer: T, control: C, addr: SockAddr) -> Self {
+        Self::with_addr(fd, buffer, control, Some(addr))
+    }
+
+    /// Create [`SendMsg`] to the connected peer of a socket.
+    pub fn connected(fd: RawFd, buffer: T, control: C) -> Self {
+        Self::with_addr(fd, buffer, control, None)
+    }
+
+    fn with_addr(fd: RawFd, buffer: T, control: C, addr: Option<SockAddr>) -> Self {
+        Self {
+            msg: unsafe { std::mem::zeroed() },
+            fd,
+            buffer,
+            control,
+            addr,
+            slices: vec![],
+            _p: PhantomPinned,
+        }
+    }
+
+    pub(crate) fn set_msg(&mut self) {
+        self.slices = unsafe { self.buffer.as_io_slices() };
+        if let Some(addr) = &self.addr {
+            self.msg.msg_name = addr.as_ptr() as _;
+            self.msg.msg_namelen = addr.len();
+        }
+        self.msg.msg_iov = self.slices.as_mut_ptr() as _;
+        self.msg.msg_iovlen = self.slices.len() as _;
+        if self.control.buf_len() > 0 {
+            self.msg.msg_control = self.control.as_buf_ptr() as _;
+            self.msg.msg_controllen = self.control.buf_len() as _;
+        }
+    }
+}
+
+impl<T: IoVectoredBuf, C: IoBuf> IntoInner for SendMsg<T, C> {
+    type Inner = (T, C);
+
+    fn into_inner(self) -> Self::Inner {
+        (self.buffer, self.control)
+    }
+}
+
+/// Receive data and source address with ancillary data into vectored buffer.
+pub struct RecvMsg<T: IoVectoredBufMut, C: IoBufMut> {
+    pub(crate) msg: libc::msghdr,
+    pub(crate) fd: RawFd,
+    pub(crate) buffer: T,
+    pub(crate) control: C,
+    pub(crate) addr: sockaddr_storage,
+    pub(crate) slices: Vec<IoSliceMut>,
+    pub(crate) flags: i32,
+    _p: PhantomPinned,
+}
+
+impl<T: IoVectoredBufMut, C: IoBufMut> RecvMsg<T, C> {
+    /// Create [`RecvMsg`].
+    pub fn new(fd: RawFd, buffer: T, control: C) -> Self {
+        Self::with_flags(fd, buffer, control, 0)
+    }
+
+    /// Create [`RecvMsg`] with the `MSG_*` flags, e.g. `MSG_CMSG_CLOEXEC`.
+    pub fn with_flags(fd: RawFd, buffer: T, control: C, flags: i32) -> Self {
+        Self {
+            msg: unsafe { std::mem::zeroed() },
+            fd,
+            buffer,
+            control,
+            addr: unsafe { std::mem::zeroed() },
+            slices: vec![],
+            flags,
+            _p: PhantomPinned,
+        }
+    }
+
+    pub(crate) fn set_msg(&mut self) {
+        self.slices = unsafe { self.buffer.as_io_slices_mut() };
+        self.msg.msg_name = &mut self.addr as *mut _ as _;
+        self.msg.msg_namelen = std::mem::size_of_val(&self.addr) as _;
+        self.msg.msg_iov = self.slices.as_mut_ptr() as _;
+        self.msg.msg_iovlen = self.slices.len() as _;
+        self.msg.msg_control = self.control.as_buf_mut_ptr() as _;
+        self.msg.msg_controllen = self.control.buf_capacity() as _;
+    }
+}
+
+impl<T: IoVectoredBufMut, C: IoBufMut> IntoInner for RecvMsg<T, C> {
+    /// The buffers, the source address and the length of the received
+    /// control messages.
+    type Inner = ((T, C), sockaddr_storage, socklen_t, usize);
+
+    fn into_inner(self) -> Self::Inner {
+        (
+            (self.buffer, self.control),
+            self.addr,
+            self.msg.msg_namelen,
+            self.msg.msg_controllen as _,
+        )
+    }
+}
+
+/// Send the datagrams in one operation, i.e. `sendmmsg`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct SendMmsg<T: IoBuf> {
+    pub(crate) fd: RawFd,
+    pub(crate) buffers: Vec<T>,
+    pub(crate) addrs: Vec<SockAddr>,
+    pub(crate) slices: Vec<IoSlice>,
+    pub(crate) msgs: Vec<libc::mmsghdr>,
+    _p: PhantomPinned,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> SendMmsg<T> {
+    /// Create [`SendMmsg`]. The datagrams are sent to the connected peer if
+    /// `addrs` is empty, or to the addresses of the same index.
+    ///
+    /// # Panics
+    ///
+    /// If `addrs` is not empty, and its length differs from `buffers`.
+    pub fn new(fd: RawFd, buffers: Vec<T>, addrs: Vec<SockAddr>) -> Self {
+        assert!(
+            addrs.is_empty() || addrs.len() == buffers.len(),
+            "one address per datagram"
+        );
+        Self {
+            fd,
+            buffers,
+            addrs,
+            slices: vec![],
+            msgs: vec![],
+            _p: PhantomPinned,
+        }
+    }
+
+    pub(crate) fn set_msgs(&mut self) {
+        self.slices = self
+            .buffers
+            .iter()
+            .map(|buffer| unsafe { buffer.as_io_slice() })
+            .collect();
+        self.msgs = self
+            .slices
+            .iter_mut()
+            .enumerate()
+            .map(|(i, slice)| {
+                let mut msg: libc::mmsghdr = unsafe { std::mem::zeroed() };
+                if let Some(addr) = self.addrs.get(i) {
+                    msg.msg_hdr.msg_name = addr.as_ptr() as _;
+                    msg.msg_hdr.msg_namelen = addr.len();
+                }
+                msg.msg_hdr.msg_iov = slice as *mut _ as _;
+                msg.msg_hdr.msg_iovlen = 1;
+                msg
+            })
+            .collect();
+    }
+
+    pub(crate) unsafe fn call(&mut self) -> libc::c_int {
+        libc::sendmmsg(self.fd, self.msgs.as_mut_ptr(), self.msgs.len() as _, 0)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBuf> IntoInner for SendMmsg<T> {
+    /// The buffers, the addresses, and the length sent of each datagram.
+    type Inner = (Vec<T>, Vec<SockAddr>, Vec<usize>);
+
+    fn into_inner(self) -> Self::Inner {
+        let lens = self.msgs.iter().map(|msg| msg.msg_len as usize).collect();
+        (self.buffers, self.addrs, lens)
+    }
+}
+
+/// Receive the datagrams in one operation, i.e. `recvmmsg`. It waits for one
+/// datagram, and receives the others arrived.
+///
+/// On the io-uring driver, it doesn't wait, and fails with `EAGAIN` if no
+/// datagram has arrived, so wait for the fd to be readable with [`PollOnce`]
+/// first. The same applies to [`SendMmsg`] and the writability.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct RecvMmsg<T: IoBufMut> {
+    pub(crate) fd: RawFd,
+    pub(crate) buffers: Vec<T>,
+    pub(crate) addrs: Vec<sockaddr_storage>,
+    pub(crate) slices: Vec<IoSliceMut>,
+    pub(crate) msgs: Vec<libc::mmsghdr>,
+    _p: PhantomPinned,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBufMut> RecvMmsg<T> {
+    /// Create [`RecvMmsg`], receiving a datagram per buffer at most.
+    pub fn new(fd: RawFd, buffers: Vec<T>) -> Self {
+        let addrs = vec![unsafe { std::mem::zeroed() }; buffers.len()];
+        Self {
+            fd,
+            buffers,
+            addrs,
+            slices: vec![],
+            msgs: vec![],
+            _p: PhantomPinned,
+        }
+    }
+
+    pub(crate) fn set_msgs(&mut self) {
+        self.slices = self
+            .buffers
+            .iter_mut()
+            .map(|buffer| unsafe { buffer.as_io_slice_mut() })
+            .collect();
+        self.msgs = self
+            .slices
+            .iter_mut()
+            .zip(&mut self.addrs)
+            .map(|(slice, addr)| {
+                let mut msg: libc::mmsghdr = unsafe { std::mem::zeroed() };
+                msg.msg_hdr.msg_name = addr as *mut _ as _;
+                msg.msg_hdr.msg_namelen = std::mem::size_of_val(addr) as _;
+                msg.msg_hdr.msg_iov = slice as *mut _ as _;
+                msg.msg_hdr.msg_iovlen = 1;
+                msg
+            })
+            .collect();
+    }
+
+    pub(crate) unsafe fn call(&mut self) -> libc::c_int {
+        libc::recvmmsg(
+            self.fd,
+            self.msgs.as_mut_ptr(),
+            self.msgs.len() as _,
+            libc::MSG_WAITFORONE,
+            std::ptr::null_mut(),
+        )
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl<T: IoBufMut> IntoInner for RecvMmsg<T> {
+    /// The buffers, and the length and the source address of each datagram.
+    type Inner = (Vec<T>, Vec<(usize, sockaddr_storage, socklen_t)>);
+
+    fn into_inner(self) -> Self::Inner {
+        let msgs = self
+            .msgs
+            .iter()
+            .zip(self.addrs)
+            .map(|(msg, addr)| (msg.msg_len as usize, addr, msg.msg_hdr.msg_namelen))
+            .collect();
+        (self.buffers, msgs)
+    }
+}
+
+/// Move data between two fds without copying it through the userspace, one
+/// of which should be a pipe.
+///
+/// An offset of `None` means the current position of the fd, and it should
+/// be `None` for a pipe. On the polling driver, it waits for `fd_out` to be
+/// writable.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub struct Splice {
+    pub(crate) fd_in: RawFd,
+    pub(crate) offset_in: Option<u64>,
+    pub(crate) fd_out: RawFd,
+    pub(crate) offset_out: Option<u64>,
+    pub(crate) len: usize,
+    pub(crate) flags: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Splice {
+    /// Create [`Splice`].
+    pub fn new(
+        fd_in: RawFd,
+        offset_in: Option<u64>,
+        fd_out: RawFd,
+        offset_out: Option<u64>,
+        len: usize,
+    ) -> Self {
+        Self {
+            fd_in,
+            offset_in,
+            fd_out,
+            offset_out,
+            len,
+            flags: 0,
+        }
+    }
+
+    /// Set the `SPLICE_F_*` flags.
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// The readiness to wait for with [`PollOnce`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interest {
+    /// Represents a read operation.
+    Readable,
+    /// Represents a write operation.
+    Writable,
+}
+
+/// Wait for the fd to be ready once, without doing any IO.
+///
+/// It is useful to drive the libraries which read and write the fd by
+/// themselves.
+pub struct PollOnce {
+    pub(crate) fd: RawFd,
+    pub(crate) interest: Interest,
+}
+
+impl PollOnce {
+    /// Create [`PollOnce`].
+    pub fn new(fd: RawFd, interest: Interest) -> Self {
+        Self { fd, interest }
+    }
+}
@@ -0,0 +1,19 @@
+use compio_driver::ProactorBuilder;
+
+#[test]
+fn load_env() {
+    // There is only one test in this binary to set the variables.
+    std::env::set_var("COMPIO_AUTOTUNE", "1");
+    std::env::set_var("COMPIO_RING_ENTRIES", "256");
+    std::env::set_var("COMPIO_BLOCKING_THREADS", "8");
+    ProactorBuilder::new().load_env().unwrap().build().unwrap();
+
+    std::env::set_var("COMPIO_RING_ENTRIES", "many");
+    let err = ProactorBuilder::new().load_env().err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(err.to_string().contains("COMPIO_RING_ENTRIES"));
+
+    std::env::set_var("COMPIO_RING_ENTRIES", "256");
+    std::env::set_var("COMPIO_AUTOTUNE", "yes");
+    assert!(ProactorBuilder::new().load_env().is_err());
+}
@@ -0,0 +1,62 @@
+use std::{error::Error as _, io};
+
+use compio_driver::{Error, ResultExt};
+
+#[test]
+fn context() {
+    let res: io::Result<()> = Err(io::Error::from_raw_os_error(104));
+    let err = res.context("recv").unwrap_err();
+    assert_eq!(err.op(), "recv");
+    assert_eq!(err.raw_os_error(), Some(104));
+    assert!(err.path().is_none());
+    assert!(err.source().is_some());
+
+    let err = Error::new("open", io::ErrorKind::NotFound.into()).with_path("/a/b");
+    assert_eq!(err.to_string(), "open (/a/b): entity not found");
+
+    let err = err.with_peer("[::1]:80".parse().unwrap());
+    assert_eq!(
+        err.to_string(),
+        "open (/a/b, peer [::1]:80): entity not found"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn with_fd() {
+    let err = Error::new("recv", io::ErrorKind::ConnectionReset.into())
+        .with_fd(42)
+        .with_peer("1.2.3.4:80".parse().unwrap());
+    assert_eq!(err.fd(), Some(42));
+    assert_eq!(
+        err.to_string(),
+        "recv on fd 42 (peer 1.2.3.4:80): connection reset"
+    );
+}
+
+#[test]
+fn into_io_error() {
+    let err: io::Error = Error::new("send", io::Error::from_raw_os_error(32)).into();
+    assert_eq!(err.kind(), io::Error::from_raw_os_error(32).kind());
+    let ctx = Error::get(&err).unwrap();
+    assert_eq!(ctx.op(), "send");
+    assert_eq!(ctx.raw_os_error(), Some(32));
+    assert!(err.to_string().starts_with("send: "));
+
+    // Not converted from the context.
+    assert!(Error::get(&io::Error::other("x")).is_none());
+    assert!(Error::get(&io::ErrorKind::Other.into()).is_none());
+}
+
+#[test]
+fn os_error() {
+    let err: io::Error = Error::new("recv", io::Error::from_raw_os_error(104)).into();
+    assert_eq!(err.raw_os_error(), None);
+    assert_eq!(Error::os_error(&err), Some(104));
+    let source = err.get_ref().unwrap().source().unwrap();
+    let source = source.downcast_ref::<io::Error>().unwrap();
+    assert_eq!(source.raw_os_error(), Some(104));
+
+    assert_eq!(Error::os_error(&io::Error::from_raw_os_error(32)), Some(32));
+    assert_eq!(Error::os_error(&io::ErrorKind::Other.into()), None);
+}
@@ -1,9 +1,9 @@
 use std::{io, time::Duration};
 
-use compio_buf::{arrayvec::ArrayVec, BufResult};
+use compio_buf::{arrayvec::ArrayVec, BufResult, IntoInner};
 use compio_driver::{
-    op::{Asyncify, CloseFile, OpenFile, ReadAt},
-    OpCode, Proactor, PushEntry, RawFd,
+    op::{Asyncify, CloseFile, Hinted, OpenFile, ReadAt},
+    OpCode, Proactor, ProactorBuilder, PushEntry, RawFd,
 };
 
 #[cfg(windows)]
@@ -125,6 +125,133 @@ fn register_multiple() {
     push_and_wait(&mut driver, op);
 }
 
+#[test]
+fn hinted() {
+    let mut driver = Proactor::new().unwrap();
+
+    let op = open_file_op();
+    let (fd, _) = push_and_wait(&mut driver, op);
+    let fd = fd as RawFd;
+    driver.attach(fd).unwrap();
+
+    // Best effort class, level 7.
+    let op = Hinted::new(ReadAt::new(fd, 0, Vec::with_capacity(9)))
+        .force_async(true)
+        .ioprio((2 << 13) | 7);
+    let (len, op) = push_and_wait(&mut driver, op);
+    assert_eq!(len, 9);
+    let mut buffer = op.into_inner().into_inner();
+    unsafe { buffer.set_len(len) };
+    assert_eq!(buffer, b"[package]");
+
+    let op = CloseFile::new(fd);
+    push_and_wait(&mut driver, op);
+}
+
+#[test]
+fn submit_batch() {
+    const TASK_LEN: usize = 4;
+
+    let mut driver = ProactorBuilder::new()
+        .submit_batch_size(2)
+        .submit_deadline(Duration::from_millis(1))
+        .build()
+        .unwrap();
+
+    let op = open_file_op();
+    let (fd, _) = push_and_wait(&mut driver, op);
+    let fd = fd as RawFd;
+    driver.attach(fd).unwrap();
+
+    let mut keys = vec![];
+    let mut results = vec![];
+    for _i in 0..TASK_LEN {
+        match driver.push(ReadAt::new(fd, 0, Vec::with_capacity(9))) {
+            PushEntry::Pending(key) => keys.push(key),
+            PushEntry::Ready(res) => results.push(res.unwrap()),
+        }
+    }
+
+    let mut entries = ArrayVec::<usize, TASK_LEN>::new();
+    while entries.len() < keys.len() {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    results.extend(keys.into_iter().map(|key| driver.pop(key).unwrap()));
+    assert_eq!(results.len(), TASK_LEN);
+    for (len, op) in results {
+        assert_eq!(len, 9);
+        let mut buffer = op.into_inner();
+        unsafe { buffer.set_len(len) };
+        assert_eq!(buffer, b"[package]");
+    }
+
+    let op = CloseFile::new(fd);
+    push_and_wait(&mut driver, op);
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring", not(feature = "polling")))]
+#[test]
+fn submit_batch_early() {
+    use compio_driver::op::Send;
+
+    let mut driver = ProactorBuilder::new().submit_batch_size(2).build().unwrap();
+
+    let mut fds = [0; 2];
+    assert_eq!(
+        unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC | libc::O_NONBLOCK) },
+        0
+    );
+    let [rx, tx] = fds;
+    let read = || {
+        let mut buffer = [0u8; 8];
+        let len = unsafe { libc::read(rx, buffer.as_mut_ptr().cast(), buffer.len()) };
+        buffer[..len.max(0) as usize].to_vec()
+    };
+
+    // The first write is queued until the batch is full, without polling.
+    let first = driver.push(Send::new(tx, b"a".to_vec()));
+    assert!(matches!(first, PushEntry::Pending(_)));
+    assert_eq!(read(), b"");
+    let second = driver.push(Send::new(tx, b"b".to_vec()));
+    assert!(matches!(second, PushEntry::Pending(_)));
+    assert_eq!(read(), b"ab");
+
+    let mut entries = ArrayVec::<usize, 2>::new();
+    while entries.len() < 2 {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    for key in [first, second] {
+        let PushEntry::Pending(key) = key else {
+            unreachable!()
+        };
+        assert_eq!(driver.pop(key).unwrap().0, 1);
+    }
+    unsafe {
+        libc::close(rx);
+        libc::close(tx);
+    }
+}
+
+#[test]
+fn autotune() {
+    let mut driver = ProactorBuilder::new().autotune().build().unwrap();
+
+    let op = open_file_op();
+    let (fd, _) = push_and_wait(&mut driver, op);
+    let fd = fd as RawFd;
+    driver.attach(fd).unwrap();
+
+    let op = ReadAt::new(fd, 0, Vec::with_capacity(9));
+    let (len, op) = push_and_wait(&mut driver, op);
+    assert_eq!(len, 9);
+    let mut buffer = op.into_inner();
+    unsafe { buffer.set_len(len) };
+    assert_eq!(buffer, b"[package]");
+
+    let op = CloseFile::new(fd);
+    push_and_wait(&mut driver, op);
+}
+
 #[test]
 fn notify() {
     let mut driver = Proactor::new().unwrap();
@@ -0,0 +1,51 @@
+#![cfg(all(target_os = "linux", feature = "io-uring"))]
+
+use std::{
+    io::Write,
+    net::{TcpListener, TcpStream},
+    os::fd::AsRawFd,
+};
+
+use compio_buf::{arrayvec::ArrayVec, BufResult};
+use compio_driver::{op::RecvFrom, Proactor, PushEntry};
+
+const IORING_CQE_F_SOCK_NONEMPTY: u32 = 1 << 2;
+
+fn recv_with_flags(driver: &mut Proactor, fd: i32, len: usize) -> (usize, u32) {
+    match driver.push(RecvFrom::new(fd, Vec::with_capacity(len))) {
+        PushEntry::Ready(BufResult(res, _)) => (res.unwrap(), 0),
+        PushEntry::Pending(user_data) => {
+            let mut entries = ArrayVec::<usize, 1>::new();
+            while entries.is_empty() {
+                driver.poll(None, &mut entries).unwrap();
+            }
+            let (BufResult(res, _), flags) = driver.pop_with_flags(user_data);
+            (res.unwrap(), flags)
+        }
+    }
+}
+
+#[test]
+fn sock_nonempty() {
+    // Skip on the kernels before 5.19, or the polling driver chosen by fusion.
+    if compio_driver::sock_nonempty(0).is_none() {
+        return;
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut tx = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (rx, _) = listener.accept().unwrap();
+    tx.write_all(b"hello world").unwrap();
+
+    let mut driver = Proactor::new().unwrap();
+    let fd = rx.as_raw_fd();
+    driver.attach(fd).unwrap();
+
+    let (len, flags) = recv_with_flags(&mut driver, fd, 5);
+    assert_eq!(len, 5);
+    assert_ne!(flags & IORING_CQE_F_SOCK_NONEMPTY, 0);
+
+    let (len, flags) = recv_with_flags(&mut driver, fd, 6);
+    assert_eq!(len, 6);
+    assert_eq!(flags & IORING_CQE_F_SOCK_NONEMPTY, 0);
+}
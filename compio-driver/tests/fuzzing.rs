@@ -0,0 +1,31 @@
+#![cfg(feature = "fuzzing")]
+
+use compio_driver::fuzzing::op_lifecycle;
+
+#[test]
+fn lifecycle_cases() {
+    // Push, complete, pop.
+    op_lifecycle(&[0, 0, 1, 0, 3, 0]);
+    // Push, cancel, complete.
+    op_lifecycle(&[0, 0, 2, 0, 1, 0]);
+    // Push, complete, cancel.
+    op_lifecycle(&[0, 0, 1, 0, 2, 0]);
+    // Leave the ops in all states at the end.
+    op_lifecycle(&[0, 0, 0, 0, 0, 0, 1, 0, 2, 1, 0, 0]);
+}
+
+#[test]
+fn lifecycle_random() {
+    let mut seed = 0x2545_f491_4f6c_dd1du64;
+    for _ in 0..256 {
+        let data = (0..128)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect::<Vec<_>>();
+        op_lifecycle(&data);
+    }
+}
@@ -0,0 +1,49 @@
+use std::{
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use compio_buf::{arrayvec::ArrayVec, BufResult};
+use compio_driver::{op::Asyncify, Proactor, PushEntry};
+
+#[test]
+fn cancel_then_reuse() {
+    let mut driver = Proactor::new().unwrap();
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let rx = Mutex::new(rx);
+    let op = Asyncify::new(move || {
+        rx.lock().unwrap().recv().unwrap();
+        BufResult(Ok(7), ())
+    });
+    let PushEntry::Pending(old) = driver.push(op) else {
+        unreachable!("the blocking op should be pending");
+    };
+    let old = *old;
+    driver.cancel(old);
+
+    // Let the cancelled op complete, and its slot be freed.
+    tx.send(()).unwrap();
+    let mut entries = ArrayVec::<usize, 1>::new();
+    driver
+        .poll(Some(Duration::from_secs(1)), &mut entries)
+        .unwrap();
+    assert!(entries.is_empty());
+
+    let op = Asyncify::new(|| BufResult(Ok(42), ()));
+    let key = match driver.push(op) {
+        PushEntry::Pending(key) => key,
+        PushEntry::Ready(_) => unreachable!("the blocking op should be pending"),
+    };
+    assert_ne!(*key, old);
+
+    // The late cancellation of the old op shouldn't reach the new one.
+    driver.cancel(old);
+
+    while entries.is_empty() {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    assert_eq!(entries[0], *key);
+    let BufResult(res, _) = driver.pop(key);
+    assert_eq!(res.unwrap(), 42);
+}
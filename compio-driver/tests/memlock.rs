@@ -0,0 +1,84 @@
+#![cfg(target_os = "linux")]
+
+// The test changes the limits of the whole process, so it is in its own
+// binary.
+
+use std::ffi::CString;
+
+use compio_buf::{arrayvec::ArrayVec, IntoInner};
+use compio_driver::{
+    op::{CloseFile, OpenFile, ReadAt},
+    ProactorBuilder, PushEntry, RawFd,
+};
+
+#[test]
+fn autotune_memlock() {
+    // The default limit of many systems. The kernels before 5.12 fail to
+    // create a ring larger than it.
+    let mut limit: libc::rlimit = unsafe { std::mem::zeroed() };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut limit) },
+        0
+    );
+    limit.rlim_cur = limit.rlim_cur.min(64 << 10);
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_MEMLOCK, &limit) }, 0);
+
+    let mut driver = ProactorBuilder::new().autotune().build().unwrap();
+
+    let op = OpenFile::new(
+        CString::new("Cargo.toml").unwrap(),
+        libc::O_CLOEXEC | libc::O_RDONLY,
+        0o666,
+    );
+    let fd = match driver.push(op) {
+        PushEntry::Ready(res) => res.unwrap().0,
+        PushEntry::Pending(key) => {
+            let mut entries = ArrayVec::<usize, 1>::new();
+            while entries.is_empty() {
+                driver.poll(None, &mut entries).unwrap();
+            }
+            driver.pop(key).unwrap().0
+        }
+    } as RawFd;
+    driver.attach(fd).unwrap();
+
+    // More reads than the smallest tuned ring, to fill it.
+    const TASK_LEN: usize = 512;
+    let mut keys = vec![];
+    let mut completed = 0;
+    for _ in 0..TASK_LEN {
+        match driver.push(ReadAt::new(fd, 0, Vec::with_capacity(9))) {
+            PushEntry::Pending(key) => keys.push(key),
+            PushEntry::Ready(res) => {
+                assert_eq!(res.unwrap().0, 9);
+                completed += 1;
+            }
+        }
+    }
+    let mut entries = ArrayVec::<usize, TASK_LEN>::new();
+    while entries.len() < keys.len() {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    for key in keys {
+        let (len, op) = driver.pop(key).unwrap();
+        assert_eq!(len, 9);
+        let mut buffer = op.into_inner();
+        unsafe { buffer.set_len(len) };
+        assert_eq!(buffer, b"[package]");
+        completed += 1;
+    }
+    assert_eq!(completed, TASK_LEN);
+
+    match driver.push(CloseFile::new(fd)) {
+        PushEntry::Ready(res) => {
+            res.unwrap();
+        }
+        PushEntry::Pending(key) => {
+            let mut entries = ArrayVec::<usize, 1>::new();
+            while entries.is_empty() {
+                driver.poll(None, &mut entries).unwrap();
+            }
+            driver.pop(key).unwrap();
+        }
+    }
+}
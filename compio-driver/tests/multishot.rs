@@ -0,0 +1,39 @@
+#![cfg(all(target_os = "linux", feature = "io-uring", not(feature = "polling")))]
+
+use std::{
+    net::{TcpListener, TcpStream},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+};
+
+use compio_buf::arrayvec::ArrayVec;
+use compio_driver::{op::AcceptMulti, Proactor, PushEntry};
+
+#[test]
+fn accept_multi() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let mut driver = Proactor::new().unwrap();
+    let PushEntry::Pending(key) = driver.push(AcceptMulti::new(listener.as_raw_fd())) else {
+        unreachable!("the accept should be pending");
+    };
+
+    let mut clients = vec![];
+    for _ in 0..2 {
+        clients.push(TcpStream::connect(addr).unwrap());
+        let mut entries = ArrayVec::<usize, 1>::new();
+        while entries.is_empty() {
+            driver.poll(None, &mut entries).unwrap();
+        }
+        assert_eq!(entries[0], *key);
+        // One submission accepts both connections.
+        let fd = driver.pop_multishot(&key).unwrap().unwrap();
+        let _ = unsafe { OwnedFd::from_raw_fd(fd as _) };
+        assert!(!driver.has_result(*key));
+    }
+
+    driver.cancel(*key);
+    let mut entries = ArrayVec::<usize, 1>::new();
+    driver.poll(None, &mut entries).unwrap();
+    assert!(entries.is_empty());
+}
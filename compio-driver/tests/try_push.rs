@@ -0,0 +1,103 @@
+use std::{
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use compio_buf::{arrayvec::ArrayVec, BufResult};
+use compio_driver::{op::Asyncify, Proactor, ProactorBuilder, PushEntry, QueuePolicy};
+
+fn poll_once(driver: &mut Proactor) -> usize {
+    let mut entries = ArrayVec::<usize, 1>::new();
+    while entries.is_empty() {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    entries[0]
+}
+
+#[test]
+fn rejected_when_pool_full() {
+    let mut driver = ProactorBuilder::new().thread_pool_limit(1).build().unwrap();
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let rx = Mutex::new(rx);
+    let op = Asyncify::new(move || {
+        rx.lock().unwrap().recv().unwrap();
+        BufResult(Ok(1), ())
+    });
+    let Ok(PushEntry::Pending(key)) = driver.try_push(op) else {
+        unreachable!("the blocking op should be queued");
+    };
+
+    // The only thread of the pool is busy.
+    let op = Asyncify::new(|| BufResult(Ok(2), ()));
+    let Err((err, mut op)) = driver.try_push(op) else {
+        unreachable!("the blocking op should be rejected");
+    };
+    #[cfg(unix)]
+    assert_eq!(err.raw_os_error(), Some(libc::EBUSY));
+    #[cfg(windows)]
+    let _ = err;
+
+    tx.send(()).unwrap();
+    assert_eq!(poll_once(&mut driver), *key);
+    assert_eq!(driver.pop(key).0.unwrap(), 1);
+
+    // The rejected op is returned as is, and could be pushed again.
+    let key = loop {
+        match driver.try_push(op) {
+            Ok(PushEntry::Pending(key)) => break key,
+            Ok(PushEntry::Ready(_)) => unreachable!("the blocking op should be queued"),
+            Err((_, rejected)) => {
+                // Wait for the thread to be idle.
+                op = rejected;
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    };
+    assert_eq!(poll_once(&mut driver), *key);
+    assert_eq!(driver.pop(key).0.unwrap(), 2);
+}
+
+#[test]
+fn bounded_queue() {
+    let mut driver = ProactorBuilder::new()
+        .thread_pool_limit(1)
+        .thread_pool_policy(QueuePolicy::Bounded(1))
+        .build()
+        .unwrap();
+
+    let (tx, rx) = mpsc::channel::<()>();
+    let rx = Mutex::new(rx);
+    let op = Asyncify::new(move || {
+        rx.lock().unwrap().recv().unwrap();
+        BufResult(Ok(1), ())
+    });
+    let Ok(PushEntry::Pending(first)) = driver.try_push(op) else {
+        unreachable!("the blocking op should be running");
+    };
+    // Wait for the thread to take the first op.
+    while driver.thread_pool().stats().active == 0 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let op = Asyncify::new(|| BufResult(Ok(2), ()));
+    let Ok(PushEntry::Pending(second)) = driver.try_push(op) else {
+        unreachable!("the blocking op should be queued");
+    };
+    let op = Asyncify::new(|| BufResult(Ok(3), ()));
+    assert!(driver.try_push(op).is_err());
+
+    let stats = driver.thread_pool().stats();
+    assert_eq!(stats.threads, 1);
+    assert_eq!(stats.active, 1);
+    assert_eq!(stats.queued, 1);
+    assert_eq!(stats.rejected, 1);
+
+    tx.send(()).unwrap();
+    let mut entries = ArrayVec::<usize, 2>::new();
+    while entries.len() < 2 {
+        driver.poll(None, &mut entries).unwrap();
+    }
+    assert_eq!(driver.pop(first).0.unwrap(), 1);
+    assert_eq!(driver.pop(second).0.unwrap(), 2);
+}
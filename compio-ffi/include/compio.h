@@ -0,0 +1,61 @@
+/*
+ * The C API of compio.
+ *
+ * An operation is started with a callback and its user data, and the
+ * callback is called with the result when `compio_runtime_run`, or the
+ * Rust side sharing the runtime, runs the runtime. The results are
+ * non-negative on success, and the negated OS error codes on failure, or
+ * `COMPIO_ERROR_UNKNOWN` if the error has no code. All the functions should
+ * be called on the thread creating the runtime.
+ */
+
+#ifndef COMPIO_H
+#define COMPIO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define COMPIO_ERROR_UNKNOWN INTPTR_MIN
+
+#ifdef _WIN32
+typedef void *compio_fd;
+#else
+typedef int compio_fd;
+#endif
+
+typedef struct compio_runtime compio_runtime;
+
+typedef void (*compio_callback)(void *user_data, intptr_t result);
+
+intptr_t compio_runtime_new(compio_runtime **out);
+void compio_runtime_free(compio_runtime *rt);
+void compio_runtime_run(compio_runtime *rt);
+
+/* The result is the listening socket. */
+void compio_listen(compio_runtime *rt, const char *addr,
+                   compio_callback callback, void *user_data);
+/* The result is the connected socket. */
+void compio_connect(compio_runtime *rt, const char *addr,
+                    compio_callback callback, void *user_data);
+/* The result is the accepted socket. */
+void compio_accept(compio_runtime *rt, compio_fd fd,
+                   compio_callback callback, void *user_data);
+/* The buffer should be valid until the callback is called. */
+void compio_recv(compio_runtime *rt, compio_fd fd, uint8_t *buf, size_t len,
+                 compio_callback callback, void *user_data);
+void compio_send(compio_runtime *rt, compio_fd fd, const uint8_t *buf,
+                 size_t len, compio_callback callback, void *user_data);
+void compio_close(compio_runtime *rt, compio_fd fd,
+                  compio_callback callback, void *user_data);
+void compio_sleep(compio_runtime *rt, uint64_t millis,
+                  compio_callback callback, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* COMPIO_H */
@@ -0,0 +1,458 @@
+//! The C API of compio, for the components written in other languages to
+//! share the runtime, and the ring of io-uring, with the Rust code.
+//!
+//! An operation is started with a callback and its user data, and the
+//! callback is called with the result when [`compio_runtime_run`] runs the
+//! runtime. The results are non-negative on success, e.g. the bytes
+//! transferred or the new socket, and the negated OS error codes on failure,
+//! or [`COMPIO_ERROR_UNKNOWN`] if the error has no code.
+//!
+//! The runtime is created by C with [`compio_runtime_new`], or by Rust and
+//! handed to C with [`compio_runtime::from_runtime`], so that the operations
+//! of both sides run on the same driver.
+//!
+//! The sockets are owned by the caller, and should be closed with
+//! [`compio_close`]. All the functions should be called on the thread
+//! creating the runtime. The declarations for C are in `include/compio.h`.
+
+#![allow(non_camel_case_types)]
+#![warn(missing_docs)]
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    ffi::{c_char, c_void, CStr},
+    future::{poll_fn, Future},
+    io,
+    mem::ManuallyDrop,
+    net::SocketAddr,
+    rc::Rc,
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use ::compio_runtime::{FromRawFd, IntoRawFd, RawFd, Runtime, TryAsRawFd};
+use compio_buf::{BufResult, IoBuf, IoBufMut, SetBufInit};
+use compio_driver::op::{CloseSocket, Recv, Send};
+use compio_net::{TcpListener, TcpStream};
+
+/// The result of an error without an OS error code.
+pub const COMPIO_ERROR_UNKNOWN: isize = isize::MIN;
+
+#[cfg(unix)]
+const EINVAL: i32 = libc::EINVAL;
+#[cfg(windows)]
+const EINVAL: i32 = windows_sys::Win32::Networking::WinSock::WSAEINVAL;
+
+/// A socket, i.e. a file descriptor on Unix, or a `SOCKET` on Windows.
+pub type compio_fd = RawFd;
+
+/// The callback of an operation, called with its user data and result.
+pub type compio_callback = Option<unsafe extern "C" fn(user_data: *mut c_void, result: isize)>;
+
+/// The runtime shared with the Rust code.
+pub struct compio_runtime {
+    runtime: Runtime,
+    state: Rc<State>,
+}
+
+#[derive(Default)]
+struct State {
+    // The operations not completed, and the waker of `compio_runtime_run`.
+    pending: Cell<usize>,
+    waker: RefCell<Option<Waker>>,
+    attached: RefCell<HashSet<RawFd>>,
+    // The listeners are borrowed from the caller, and never closed.
+    listeners: RefCell<HashMap<RawFd, Rc<ManuallyDrop<TcpListener>>>>,
+}
+
+impl State {
+    // Prepare a socket of the caller for the driver once.
+    fn attach(&self, fd: RawFd) -> io::Result<()> {
+        if self.attached.borrow().contains(&fd) {
+            return Ok(());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::fd::BorrowedFd;
+
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            socket2::SockRef::from(&fd).set_nonblocking(true)?;
+        }
+        Runtime::current().attach(fd)?;
+        self.attached.borrow_mut().insert(fd);
+        Ok(())
+    }
+
+    fn listener(&self, fd: RawFd) -> io::Result<Rc<ManuallyDrop<TcpListener>>> {
+        if let Some(listener) = self.listeners.borrow().get(&fd) {
+            return Ok(listener.clone());
+        }
+        #[cfg(unix)]
+        {
+            use std::os::fd::BorrowedFd;
+
+            let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+            socket2::SockRef::from(&fd).set_nonblocking(true)?;
+        }
+        let listener = Rc::new(ManuallyDrop::new(unsafe { TcpListener::from_raw_fd(fd) }));
+        self.listeners.borrow_mut().insert(fd, listener.clone());
+        Ok(listener)
+    }
+
+    fn forget(&self, fd: RawFd) {
+        self.attached.borrow_mut().remove(&fd);
+        if let Some(listener) = self.listeners.borrow_mut().remove(&fd) {
+            release(listener);
+        }
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        for (_, listener) in self.listeners.get_mut().drain() {
+            release(listener);
+        }
+    }
+}
+
+// Give the socket of a listener back to the caller.
+fn release(listener: Rc<ManuallyDrop<TcpListener>>) {
+    if let Ok(listener) = Rc::try_unwrap(listener) {
+        let _ = ManuallyDrop::into_inner(listener).into_raw_fd();
+    }
+}
+
+impl compio_runtime {
+    /// Wrap a runtime of the Rust side for the C code. The callbacks are
+    /// called whenever the runtime runs, e.g. in [`Runtime::block_on`] of the
+    /// Rust side, or in [`compio_runtime_run`].
+    ///
+    /// The pointer should be freed with [`compio_runtime_free`], which only
+    /// drops this handle of the runtime.
+    pub fn from_runtime(runtime: Runtime) -> *mut Self {
+        Box::into_raw(Box::new(Self {
+            runtime,
+            state: Rc::default(),
+        }))
+    }
+
+    fn spawn(
+        &self,
+        op: impl Future<Output = io::Result<usize>> + 'static,
+        callback: compio_callback,
+        user_data: *mut c_void,
+    ) {
+        let state = self.state.clone();
+        state.pending.set(state.pending.get() + 1);
+        self.runtime
+            .spawn(async move {
+                let res = op.await;
+                state.pending.set(state.pending.get() - 1);
+                if let Some(callback) = callback {
+                    unsafe { callback(user_data, into_result(res)) };
+                }
+                if state.pending.get() == 0 {
+                    if let Some(waker) = state.waker.take() {
+                        waker.wake();
+                    }
+                }
+            })
+            .detach();
+    }
+}
+
+fn into_result(res: io::Result<usize>) -> isize {
+    match res {
+        Ok(res) => res as isize,
+        Err(e) => compio_driver::Error::os_error(&e)
+            .map(|code| -(code as isize))
+            .unwrap_or(COMPIO_ERROR_UNKNOWN),
+    }
+}
+
+// A buffer of the caller, which is valid until the callback is called.
+struct RawBuf {
+    ptr: *mut u8,
+    len: usize,
+    init: usize,
+}
+
+impl IoBuf for RawBuf {
+    fn as_buf_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    fn buf_len(&self) -> usize {
+        self.init
+    }
+
+    fn buf_capacity(&self) -> usize {
+        self.len
+    }
+}
+
+impl IoBufMut for RawBuf {
+    fn as_buf_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+}
+
+impl SetBufInit for RawBuf {
+    unsafe fn set_buf_init(&mut self, len: usize) {
+        self.init = self.init.max(len);
+    }
+}
+
+/// Create a runtime, and write it to `out`. Returns 0, or the negated OS
+/// error code.
+///
+/// # Safety
+///
+/// `out` should be valid for writing a pointer.
+#[no_mangle]
+pub unsafe extern "C" fn compio_runtime_new(out: *mut *mut compio_runtime) -> isize {
+    match Runtime::new() {
+        Ok(runtime) => {
+            out.write(compio_runtime::from_runtime(runtime));
+            0
+        }
+        Err(e) => into_result(Err(e)),
+    }
+}
+
+/// Free a runtime. The callbacks of the pending operations are never called.
+///
+/// # Safety
+///
+/// `rt` should be created by [`compio_runtime_new`] or
+/// [`compio_runtime::from_runtime`], or null.
+#[no_mangle]
+pub unsafe extern "C" fn compio_runtime_free(rt: *mut compio_runtime) {
+    if !rt.is_null() {
+        drop(Box::from_raw(rt));
+    }
+}
+
+/// Run the runtime until all the operations complete, including the ones
+/// started by the callbacks.
+///
+/// # Safety
+///
+/// `rt` should be valid, and it should not be called from a callback.
+#[no_mangle]
+pub unsafe extern "C" fn compio_runtime_run(rt: *mut compio_runtime) {
+    let rt = &*rt;
+    rt.runtime.block_on(poll_fn(|cx| {
+        if rt.state.pending.get() == 0 {
+            Poll::Ready(())
+        } else {
+            *rt.state.waker.borrow_mut() = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }))
+}
+
+/// Connect to `addr`, an IP address with the port like `127.0.0.1:80`. The
+/// result is the connected socket.
+///
+/// # Safety
+///
+/// `rt` should be valid, and `addr` should be a string ending with NUL.
+#[no_mangle]
+pub unsafe extern "C" fn compio_connect(
+    rt: *mut compio_runtime,
+    addr: *const c_char,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    let addr = parse_addr(addr);
+    let state = rt.state.clone();
+    rt.spawn(
+        async move {
+            let stream = TcpStream::connect(addr?).await?;
+            let fd = stream.into_raw_fd();
+            state.attached.borrow_mut().insert(fd);
+            Ok(fd as usize)
+        },
+        callback,
+        user_data,
+    )
+}
+
+/// Bind a socket to `addr`, an IP address with the port like `0.0.0.0:80`,
+/// and listen on it. The result is the listening socket.
+///
+/// # Safety
+///
+/// `rt` should be valid, and `addr` should be a string ending with NUL.
+#[no_mangle]
+pub unsafe extern "C" fn compio_listen(
+    rt: *mut compio_runtime,
+    addr: *const c_char,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    let addr = parse_addr(addr);
+    let state = rt.state.clone();
+    rt.spawn(
+        async move {
+            let listener = TcpListener::bind(addr?).await?;
+            let fd = listener.try_as_raw_fd()?;
+            state
+                .listeners
+                .borrow_mut()
+                .insert(fd, Rc::new(ManuallyDrop::new(listener)));
+            Ok(fd as usize)
+        },
+        callback,
+        user_data,
+    )
+}
+
+unsafe fn parse_addr(addr: *const c_char) -> io::Result<SocketAddr> {
+    CStr::from_ptr(addr)
+        .to_str()
+        .ok()
+        .and_then(|addr| addr.parse::<SocketAddr>().ok())
+        .ok_or_else(|| io::Error::from_raw_os_error(EINVAL))
+}
+
+/// Accept a connection from the listening socket `fd`. The result is the
+/// accepted socket.
+///
+/// # Safety
+///
+/// `rt` should be valid.
+#[no_mangle]
+pub unsafe extern "C" fn compio_accept(
+    rt: *mut compio_runtime,
+    fd: compio_fd,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    let state = rt.state.clone();
+    rt.spawn(
+        async move {
+            let listener = state.listener(fd)?;
+            let (stream, _) = listener.accept().await?;
+            Ok(stream.into_raw_fd() as usize)
+        },
+        callback,
+        user_data,
+    )
+}
+
+/// Receive at most `len` bytes into `buf`. The result is the bytes received,
+/// and 0 if the peer has shut down the connection.
+///
+/// # Safety
+///
+/// `rt` should be valid, and `buf` should be valid for writing `len` bytes
+/// until the callback is called.
+#[no_mangle]
+pub unsafe extern "C" fn compio_recv(
+    rt: *mut compio_runtime,
+    fd: compio_fd,
+    buf: *mut u8,
+    len: usize,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    let state = rt.state.clone();
+    let buffer = RawBuf {
+        ptr: buf,
+        len,
+        init: 0,
+    };
+    rt.spawn(
+        async move {
+            state.attach(fd)?;
+            let BufResult(res, _) = Runtime::current().submit(Recv::new(fd, buffer)).await;
+            res
+        },
+        callback,
+        user_data,
+    )
+}
+
+/// Send at most `len` bytes from `buf`. The result is the bytes sent.
+///
+/// # Safety
+///
+/// `rt` should be valid, and `buf` should be valid for reading `len` bytes
+/// until the callback is called.
+#[no_mangle]
+pub unsafe extern "C" fn compio_send(
+    rt: *mut compio_runtime,
+    fd: compio_fd,
+    buf: *const u8,
+    len: usize,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    let state = rt.state.clone();
+    let buffer = RawBuf {
+        ptr: buf as *mut u8,
+        len,
+        init: len,
+    };
+    rt.spawn(
+        async move {
+            state.attach(fd)?;
+            let BufResult(res, _) = Runtime::current().submit(Send::new(fd, buffer)).await;
+            res
+        },
+        callback,
+        user_data,
+    )
+}
+
+/// Close a socket. The result is 0.
+///
+/// # Safety
+///
+/// `rt` should be valid, and `fd` should not be used after.
+#[no_mangle]
+pub unsafe extern "C" fn compio_close(
+    rt: *mut compio_runtime,
+    fd: compio_fd,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    rt.state.forget(fd);
+    rt.spawn(
+        async move { Runtime::current().submit(CloseSocket::new(fd)).await.0 },
+        callback,
+        user_data,
+    )
+}
+
+/// Wait for `millis` milliseconds. The result is 0.
+///
+/// # Safety
+///
+/// `rt` should be valid.
+#[no_mangle]
+pub unsafe extern "C" fn compio_sleep(
+    rt: *mut compio_runtime,
+    millis: u64,
+    callback: compio_callback,
+    user_data: *mut c_void,
+) {
+    let rt = &*rt;
+    rt.spawn(
+        async move {
+            ::compio_runtime::time::sleep(Duration::from_millis(millis)).await;
+            Ok(0)
+        },
+        callback,
+        user_data,
+    )
+}
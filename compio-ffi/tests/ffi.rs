@@ -0,0 +1,182 @@
+#![cfg(unix)]
+
+use std::{
+    ffi::{c_void, CString},
+    os::fd::{FromRawFd, IntoRawFd},
+    ptr::null_mut,
+    time::{Duration, Instant},
+};
+
+use ::compio_runtime::Runtime;
+use compio_ffi::*;
+
+unsafe extern "C" fn store(user_data: *mut c_void, result: isize) {
+    *(user_data as *mut isize) = result;
+}
+
+fn slot(result: &mut isize) -> *mut c_void {
+    result as *mut isize as *mut c_void
+}
+
+#[test]
+fn connect_send_recv() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = CString::new(listener.local_addr().unwrap().to_string()).unwrap();
+    let listener = listener.into_raw_fd();
+
+    unsafe {
+        let mut rt = null_mut();
+        assert_eq!(compio_runtime_new(&mut rt), 0);
+
+        let (mut client, mut server) = (-1, -1);
+        compio_connect(rt, addr.as_ptr(), Some(store), slot(&mut client));
+        compio_accept(rt, listener, Some(store), slot(&mut server));
+        compio_runtime_run(rt);
+        assert!(client >= 0);
+        assert!(server >= 0);
+
+        let mut buf = [0u8; 16];
+        let (mut sent, mut received) = (-1, -1);
+        compio_send(
+            rt,
+            client as _,
+            b"hello".as_ptr(),
+            5,
+            Some(store),
+            slot(&mut sent),
+        );
+        compio_recv(
+            rt,
+            server as _,
+            buf.as_mut_ptr(),
+            buf.len(),
+            Some(store),
+            slot(&mut received),
+        );
+        compio_runtime_run(rt);
+        assert_eq!(sent, 5);
+        assert_eq!(received, 5);
+        assert_eq!(&buf[..5], b"hello");
+
+        let mut closed = [-1; 3];
+        compio_close(rt, client as _, Some(store), slot(&mut closed[0]));
+        compio_close(rt, server as _, Some(store), slot(&mut closed[1]));
+        compio_close(rt, listener, Some(store), slot(&mut closed[2]));
+        compio_runtime_run(rt);
+        assert_eq!(closed, [0; 3]);
+
+        compio_runtime_free(rt);
+    }
+}
+
+unsafe extern "C" fn sleep_again(user_data: *mut c_void, result: isize) {
+    assert_eq!(result, 0);
+    let (rt, count) = &mut *(user_data as *mut (*mut compio_runtime, usize));
+    *count += 1;
+    if *count < 3 {
+        compio_sleep(*rt, 1, Some(sleep_again), user_data);
+    }
+}
+
+#[test]
+fn run_until_callbacks_done() {
+    unsafe {
+        let mut rt = null_mut();
+        assert_eq!(compio_runtime_new(&mut rt), 0);
+
+        // The operations started by the callbacks are waited too.
+        let mut state = (rt, 0usize);
+        let start = Instant::now();
+        compio_sleep(
+            rt,
+            1,
+            Some(sleep_again),
+            &mut state as *mut _ as *mut c_void,
+        );
+        compio_runtime_run(rt);
+        assert_eq!(state.1, 3);
+        assert!(start.elapsed() >= Duration::from_millis(3));
+
+        let mut err = 0;
+        let bad = CString::new("not an address").unwrap();
+        compio_connect(rt, bad.as_ptr(), Some(store), slot(&mut err));
+        compio_runtime_run(rt);
+        assert_eq!(err, -(libc::EINVAL as isize));
+
+        compio_runtime_free(rt);
+    }
+}
+
+#[test]
+fn connect_refused() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = CString::new(listener.local_addr().unwrap().to_string()).unwrap();
+    drop(listener);
+
+    unsafe {
+        let mut rt = null_mut();
+        assert_eq!(compio_runtime_new(&mut rt), 0);
+
+        let mut err = 0;
+        compio_connect(rt, addr.as_ptr(), Some(store), slot(&mut err));
+        compio_runtime_run(rt);
+        assert_eq!(err, -(libc::ECONNREFUSED as isize));
+
+        compio_runtime_free(rt);
+    }
+}
+
+#[test]
+fn listen() {
+    unsafe {
+        let mut rt = null_mut();
+        assert_eq!(compio_runtime_new(&mut rt), 0);
+
+        let mut listener = -1;
+        let addr = CString::new("127.0.0.1:0").unwrap();
+        compio_listen(rt, addr.as_ptr(), Some(store), slot(&mut listener));
+        compio_runtime_run(rt);
+        assert!(listener >= 0);
+
+        let local = std::net::TcpListener::from_raw_fd(listener as _);
+        let addr = CString::new(local.local_addr().unwrap().to_string()).unwrap();
+        let listener = local.into_raw_fd();
+        let (mut client, mut server) = (-1, -1);
+        compio_connect(rt, addr.as_ptr(), Some(store), slot(&mut client));
+        compio_accept(rt, listener, Some(store), slot(&mut server));
+        compio_runtime_run(rt);
+        assert!(client >= 0);
+        assert!(server >= 0);
+
+        let mut closed = [-1; 3];
+        compio_close(rt, client as _, Some(store), slot(&mut closed[0]));
+        compio_close(rt, server as _, Some(store), slot(&mut closed[1]));
+        compio_close(rt, listener, Some(store), slot(&mut closed[2]));
+        compio_runtime_run(rt);
+        assert_eq!(closed, [0; 3]);
+
+        compio_runtime_free(rt);
+    }
+}
+
+#[test]
+fn shared_runtime() {
+    let runtime = Runtime::new().unwrap();
+    let rt = compio_runtime::from_runtime(runtime.clone());
+    runtime.block_on(async {
+        let listener = compio_net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = CString::new(listener.local_addr().unwrap().to_string()).unwrap();
+
+        // The operation of C runs while the Rust side runs the runtime.
+        let client = Box::into_raw(Box::new(-1isize));
+        unsafe { compio_connect(rt, addr.as_ptr(), Some(store), client.cast()) };
+        let (stream, _) = listener.accept().await.unwrap();
+        while unsafe { client.read_volatile() } < 0 {
+            ::compio_runtime::time::sleep(Duration::from_millis(1)).await;
+        }
+        let client = unsafe { Box::from_raw(client) };
+        drop(stream);
+        drop(unsafe { std::net::TcpStream::from_raw_fd(*client as _) });
+    });
+    unsafe { compio_runtime_free(rt) };
+}
@@ -0,0 +1,190 @@
+use std::{
+    ffi::{OsStr, OsString},
+    io,
+    os::{
+        fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+        unix::ffi::OsStrExt,
+    },
+    path::Path,
+};
+
+use compio_driver::op::OpenFile;
+use compio_runtime::Runtime;
+
+use crate::{metadata_at, path_string, File, Metadata, OpenOptions};
+
+/// A reference to an open directory on the filesystem.
+///
+/// The relative paths passed to its methods are resolved from the directory
+/// instead of the current working directory, so the directory path is only
+/// resolved once, and the directory could still be accessed after it is
+/// moved. Note that absolute paths and `..` components are not restricted.
+///
+/// ```
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let dir = compio_fs::Dir::open(".").await.unwrap();
+/// let meta = dir.metadata("Cargo.toml").await.unwrap();
+/// assert!(meta.is_file());
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct Dir {
+    fd: OwnedFd,
+}
+
+impl Dir {
+    /// Opens a directory.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_at(libc::AT_FDCWD, path).await
+    }
+
+    async fn open_at(dirfd: RawFd, path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path_string(path)?;
+        let op = OpenFile::with_dirfd(
+            dirfd,
+            path,
+            libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC,
+            0,
+        );
+        let fd = Runtime::current().submit(op).await.0? as RawFd;
+        Ok(unsafe { Self::from_raw_fd(fd) })
+    }
+
+    /// Opens a directory relative to this one.
+    pub async fn open_dir(&self, path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::open_at(self.as_raw_fd(), path).await
+    }
+
+    /// Opens a file relative to this directory with the specified options.
+    pub async fn open_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+    ) -> io::Result<File> {
+        options.open_at(self.as_raw_fd(), path).await
+    }
+
+    /// Queries the metadata of a path relative to this directory.
+    pub async fn metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        metadata_at(self.as_raw_fd(), path, true).await
+    }
+
+    /// Queries the metadata of a path relative to this directory without
+    /// following symlinks.
+    pub async fn symlink_metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        metadata_at(self.as_raw_fd(), path, false).await
+    }
+
+    /// Returns the names of the entries in this directory, except `.` and
+    /// `..`.
+    pub async fn read_dir(&self) -> io::Result<Vec<OsString>> {
+        // Open the directory again, so that the position of the stream isn't
+        // shared with this handle.
+        let dir = self.open_dir(".").await?;
+        Runtime::current()
+            .spawn_blocking(move || {
+                let mut dir = nix::dir::Dir::from_fd(dir.into_raw_fd())?;
+                let mut names = vec![];
+                for entry in dir.iter() {
+                    let entry = entry?;
+                    let name = entry.file_name().to_bytes();
+                    if name != b"." && name != b".." {
+                        names.push(OsStr::from_bytes(name).to_os_string());
+                    }
+                }
+                Ok(names)
+            })
+            .await
+    }
+
+    /// Creates a new independently owned handle to the directory.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            fd: self.fd.try_clone()?,
+        })
+    }
+}
+
+impl AsFd for Dir {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+impl AsRawFd for Dir {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Dir {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self {
+            fd: OwnedFd::from_raw_fd(fd),
+        }
+    }
+}
+
+impl IntoRawFd for Dir {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_raw_fd()
+    }
+}
+
+/// A directory which confines all paths beneath it.
+///
+/// It is the building block of static file servers: the untrusted paths from
+/// the requests could be passed to it directly, and any path escaping the
+/// root with `..` components, absolute paths or symlinks fails.
+///
+/// The paths are opened with [`ResolveFlags::BENEATH`], so they are checked
+/// atomically while being resolved. On Linux, it is done by `openat2`, and all
+/// paths fail if `openat2` is unavailable, i.e. before Linux 5.6 or blocked by
+/// seccomp. On other platforms, the paths are opened one component at a time
+/// without following any symlink, so all symlinks are rejected.
+///
+/// [`ResolveFlags::BENEATH`]: crate::ResolveFlags::BENEATH
+///
+/// ```
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let root = compio_fs::SandboxedDir::open(".").await.unwrap();
+/// assert!(root.metadata("Cargo.toml").await.unwrap().is_file());
+/// assert!(root.metadata("../Cargo.toml").await.is_err());
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct SandboxedDir {
+    dir: Dir,
+}
+
+impl SandboxedDir {
+    /// Opens a directory as the root.
+    pub async fn open(root: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            dir: Dir::open(root).await?,
+        })
+    }
+
+    /// Opens a file beneath the root with the specified options.
+    pub async fn open_file(
+        &self,
+        path: impl AsRef<Path>,
+        options: &OpenOptions,
+    ) -> io::Result<File> {
+        let mut options = options.clone();
+        options.add_resolve(crate::ResolveFlags::BENEATH | crate::ResolveFlags::NO_MAGICLINKS);
+        self.dir.open_file(path, &options).await
+    }
+
+    /// Queries the metadata of a path beneath the root.
+    pub async fn metadata(&self, path: impl AsRef<Path>) -> io::Result<Metadata> {
+        // `statx` doesn't support the resolve flags, so open the path, only as
+        // a location on Linux.
+        let mut options = OpenOptions::new();
+        options.read(true);
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        options.custom_flags(libc::O_PATH);
+        let file = self.open_file(path, &options).await?;
+        file.metadata().await
+    }
+}
@@ -154,6 +154,21 @@ impl File {
     pub async fn sync_data(&self) -> io::Result<()> {
         self.sync_impl(true).await
     }
+
+    /// Sends `len` bytes of the file at `offset` to the connected socket,
+    /// returning the bytes sent, which are fewer if the file ends first.
+    ///
+    /// The file is sent without copying it through the userspace, with
+    /// `splice` on Linux and `TransmitFile` on Windows. Other platforms fall
+    /// back to reading the file into a buffer.
+    pub async fn send_to_socket(
+        &self,
+        socket: &impl TryAsRawFd,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<usize> {
+        compio_net::send_file(self, socket, offset, len).await
+    }
 }
 
 impl AsyncReadAt for File {
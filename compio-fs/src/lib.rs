@@ -12,6 +12,14 @@ pub use open_options::*;
 mod metadata;
 pub use metadata::*;
 
+mod serve;
+pub use serve::*;
+
+#[cfg(unix)]
+mod dir;
+#[cfg(unix)]
+pub use dir::*;
+
 #[cfg(windows)]
 pub mod named_pipe;
 
@@ -8,6 +8,8 @@ mod sys;
 
 use std::{io, path::Path, time::SystemTime};
 
+use futures_util::{Stream, StreamExt};
+
 /// Given a path, query the file system to get information about a file,
 /// directory, etc.
 pub async fn metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
@@ -19,6 +21,50 @@ pub async fn symlink_metadata(path: impl AsRef<Path>) -> io::Result<Metadata> {
     sys::symlink_metadata(path).await.map(Metadata)
 }
 
+/// Query the metadata of many paths, with at most `concurrency` queries in
+/// flight at the same time.
+///
+/// The results are yielded in the order of completion, together with the
+/// paths they belong to.
+///
+/// ```
+/// use futures_util::StreamExt;
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let results = compio_fs::metadata_batch(["Cargo.toml", "src"], 16)
+///     .collect::<Vec<_>>()
+///     .await;
+/// assert_eq!(results.len(), 2);
+/// # })
+/// ```
+///
+/// # Panics
+///
+/// Panics if `concurrency` is zero.
+pub fn metadata_batch<P: AsRef<Path>>(
+    paths: impl IntoIterator<Item = P>,
+    concurrency: usize,
+) -> impl Stream<Item = (P, io::Result<Metadata>)> {
+    assert!(concurrency > 0, "concurrency must be positive");
+    futures_util::stream::iter(paths)
+        .map(|path| async move {
+            let res = metadata(&path).await;
+            (path, res)
+        })
+        .buffer_unordered(concurrency)
+}
+
+#[cfg(unix)]
+pub(crate) async fn metadata_at(
+    dirfd: compio_driver::RawFd,
+    path: impl AsRef<Path>,
+    follow_symlink: bool,
+) -> io::Result<Metadata> {
+    sys::metadata_at(dirfd, path, follow_symlink)
+        .await
+        .map(Metadata)
+}
+
 /// Changes the permissions found on a file or a directory.
 pub async fn set_permissions(path: impl AsRef<Path>, perm: Permissions) -> io::Result<()> {
     sys::set_permissions(path, perm.0).await
@@ -6,14 +6,22 @@ use std::{
 };
 
 use compio_buf::{BufResult, IntoInner};
-use compio_driver::{op::PathStat, syscall};
+use compio_driver::{op::PathStat, syscall, RawFd};
 use compio_runtime::Runtime;
 
 use crate::path_string;
 
 async fn metadata_impl(path: impl AsRef<Path>, follow_symlink: bool) -> io::Result<Metadata> {
+    metadata_at(libc::AT_FDCWD, path, follow_symlink).await
+}
+
+pub async fn metadata_at(
+    dirfd: RawFd,
+    path: impl AsRef<Path>,
+    follow_symlink: bool,
+) -> io::Result<Metadata> {
     let path = path_string(path)?;
-    let op = PathStat::new(path, follow_symlink);
+    let op = PathStat::with_dirfd(dirfd, path, follow_symlink);
     let BufResult(res, op) = Runtime::current().submit(op).await;
     res.map(|_| Metadata::from_stat(op.into_inner()))
 }
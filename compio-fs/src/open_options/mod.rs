@@ -258,8 +258,87 @@ impl OpenOptions {
         self
     }
 
+    /// Restricts how the path is resolved, e.g. to prevent untrusted paths
+    /// escaping a directory with symlinks or `..` components.
+    ///
+    /// On Linux, they are passed to `openat2` and checked by the kernel, and
+    /// the open fails if `openat2` is unavailable, i.e. before Linux 5.6 or
+    /// blocked by seccomp. On other platforms, the path is opened one
+    /// component at a time without following any symlink, so
+    /// [`ResolveFlags::BENEATH`] and [`ResolveFlags::IN_ROOT`] reject all
+    /// symlinks, and [`ResolveFlags::NO_XDEV`] is unsupported.
+    #[cfg(unix)]
+    pub fn resolve(&mut self, flags: ResolveFlags) -> &mut Self {
+        self.0.resolve(flags.0);
+        self
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn add_resolve(&mut self, flags: ResolveFlags) -> &mut Self {
+        self.0.add_resolve(flags.0);
+        self
+    }
+
     /// Opens a file at `path` with the options specified by `self`.
     pub async fn open(&self, path: impl AsRef<Path>) -> io::Result<File> {
         self.0.open(path).await
     }
+
+    #[cfg(unix)]
+    pub(crate) async fn open_at(
+        &self,
+        dirfd: compio_driver::RawFd,
+        path: impl AsRef<Path>,
+    ) -> io::Result<File> {
+        self.0.open_at(dirfd, path).await
+    }
+}
+
+/// The flags to restrict the path resolution of [`OpenOptions::resolve`].
+///
+/// They could be combined with `|`.
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResolveFlags(u64);
+
+#[cfg(unix)]
+impl ResolveFlags {
+    /// Rejects the paths resolved outside of the starting directory, i.e.
+    /// the current working directory or the [`Dir`](crate::Dir).
+    pub const BENEATH: Self = Self(0x08);
+    /// Treats the starting directory as the root, so the absolute paths and
+    /// `..` components can't escape it.
+    pub const IN_ROOT: Self = Self(0x10);
+    /// Rejects the paths with the magic links, e.g. `/proc/self/fd/*`.
+    pub const NO_MAGICLINKS: Self = Self(0x02);
+    /// Rejects the paths with any symlinks.
+    pub const NO_SYMLINKS: Self = Self(0x04);
+    /// Rejects the paths crossing the mount points.
+    pub const NO_XDEV: Self = Self(0x01);
+
+    /// No restrictions.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Whether all flags of `other` are set.
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[cfg(unix)]
+impl std::ops::BitOr for ResolveFlags {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[cfg(unix)]
+impl std::ops::BitOrAssign for ResolveFlags {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
 }
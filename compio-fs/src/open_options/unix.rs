@@ -14,6 +14,7 @@ pub struct OpenOptions {
     create_new: bool,
     custom_flags: i32,
     mode: libc::mode_t,
+    resolve: u64,
 }
 
 impl OpenOptions {
@@ -26,6 +27,7 @@ impl OpenOptions {
             create_new: false,
             custom_flags: 0,
             mode: 0o666,
+            resolve: 0,
         }
     }
 
@@ -57,6 +59,14 @@ impl OpenOptions {
         self.mode = mode as libc::mode_t;
     }
 
+    pub fn resolve(&mut self, resolve: u64) {
+        self.resolve = resolve;
+    }
+
+    pub fn add_resolve(&mut self, resolve: u64) {
+        self.resolve |= resolve;
+    }
+
     fn get_access_mode(&self) -> io::Result<libc::c_int> {
         match (self.read, self.write) {
             (true, false) => Ok(libc::O_RDONLY),
@@ -81,6 +91,10 @@ impl OpenOptions {
     }
 
     pub async fn open(&self, p: impl AsRef<Path>) -> io::Result<File> {
+        self.open_at(libc::AT_FDCWD, p).await
+    }
+
+    pub async fn open_at(&self, dirfd: RawFd, p: impl AsRef<Path>) -> io::Result<File> {
         let mut flags = libc::O_CLOEXEC
             | self.get_access_mode()?
             | self.get_creation_mode()?
@@ -89,8 +103,81 @@ impl OpenOptions {
         if cfg!(not(any(target_os = "linux", target_os = "android"))) {
             flags |= libc::O_NONBLOCK;
         }
-        let p = path_string(p)?;
-        let op = OpenFile::new(p, flags, self.mode);
+        if self.resolve != 0 {
+            // Fail closed if `openat2` is unavailable, i.e. before Linux 5.6 or
+            // blocked by seccomp.
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            {
+                let path = path_string(p)?;
+                let op = OpenFile::with_resolve(dirfd, path, flags, self.mode, self.resolve);
+                let fd = Runtime::current().submit(op).await.0? as RawFd;
+                return Ok(unsafe { File::from_raw_fd(fd) });
+            }
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            return self.open_walk(dirfd, p.as_ref(), flags).await;
+        }
+        let op = OpenFile::with_dirfd(dirfd, path_string(p)?, flags, self.mode);
+        let fd = Runtime::current().submit(op).await.0? as RawFd;
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// The replacement of `openat2`: opens the path one component at a time
+    /// with `O_NOFOLLOW`. The `..` components go back to the directories
+    /// opened before instead of being resolved by the filesystem, and any
+    /// symlink in the path fails with `ELOOP`, which is stricter than
+    /// `openat2`. [`ResolveFlags::NO_XDEV`] is unsupported.
+    ///
+    /// [`ResolveFlags::NO_XDEV`]: super::ResolveFlags::NO_XDEV
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    async fn open_walk(&self, dirfd: RawFd, path: &Path, flags: libc::c_int) -> io::Result<File> {
+        use std::{
+            os::fd::{AsRawFd, OwnedFd},
+            path::Component,
+        };
+
+        use super::ResolveFlags;
+
+        let resolve = ResolveFlags(self.resolve);
+        if resolve.contains(ResolveFlags::NO_XDEV) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "RESOLVE_NO_XDEV is unsupported without openat2",
+            ));
+        }
+        let in_root = resolve.contains(ResolveFlags::IN_ROOT);
+        let escape = || io::Error::from_raw_os_error(libc::EXDEV);
+
+        // The directories opened beneath `dirfd`.
+        let mut dirs: Vec<OwnedFd> = vec![];
+        let mut name = None;
+        let mut components = path.components().peekable();
+        while let Some(component) = components.next() {
+            match component {
+                Component::Prefix(_) | Component::RootDir if in_root => dirs.clear(),
+                Component::Prefix(_) | Component::RootDir => return Err(escape()),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if dirs.pop().is_none() && !in_root {
+                        return Err(escape());
+                    }
+                }
+                Component::Normal(last) if components.peek().is_none() => name = Some(last),
+                Component::Normal(dir) => {
+                    let parent = dirs.last().map_or(dirfd, |dir| dir.as_raw_fd());
+                    let op = OpenFile::with_dirfd(
+                        parent,
+                        path_string(dir)?,
+                        libc::O_RDONLY | libc::O_DIRECTORY | libc::O_NOFOLLOW | libc::O_CLOEXEC,
+                        0,
+                    );
+                    let fd = Runtime::current().submit(op).await.0? as RawFd;
+                    dirs.push(unsafe { OwnedFd::from_raw_fd(fd) });
+                }
+            }
+        }
+        let parent = dirs.last().map_or(dirfd, |dir| dir.as_raw_fd());
+        let name = path_string(name.map_or(Path::new("."), Path::new))?;
+        let op = OpenFile::with_dirfd(parent, name, flags | libc::O_NOFOLLOW, self.mode);
         let fd = Runtime::current().submit(op).await.0? as RawFd;
         Ok(unsafe { File::from_raw_fd(fd) })
     }
@@ -516,13 +516,11 @@ async fn is_fifo(file: &File) -> io::Result<bool> {
 
 /// Sets file's flags with O_NONBLOCK by fcntl.
 fn set_nonblocking(file: &impl TryAsRawFd) -> io::Result<()> {
-    if cfg!(not(all(target_os = "linux", feature = "io-uring"))) {
-        let fd = file.try_as_raw_fd()?;
-        let current_flags = syscall!(libc::fcntl(fd, libc::F_GETFL))?;
-        let flags = current_flags | libc::O_NONBLOCK;
-        if flags != current_flags {
-            syscall!(libc::fcntl(fd, libc::F_SETFL, flags))?;
-        }
+    let fd = file.try_as_raw_fd()?;
+    let current_flags = syscall!(libc::fcntl(fd, libc::F_GETFL))?;
+    let flags = current_flags | libc::O_NONBLOCK;
+    if flags != current_flags {
+        syscall!(libc::fcntl(fd, libc::F_SETFL, flags))?;
     }
     Ok(())
 }
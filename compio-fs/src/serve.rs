@@ -0,0 +1,119 @@
+use std::{io, ops::Range, path::Path};
+
+use compio_buf::{BufResult, IntoInner, IoBuf};
+use compio_io::{AsyncReadAt, AsyncWrite, AsyncWriteExt};
+use compio_runtime::TryAsRawFd;
+
+use crate::File;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Sends the content of a file to `stream`, and returns the number of bytes
+/// sent.
+///
+/// The file is opened and checked to be a regular file. If `range` is
+/// specified, only the bytes in it are sent, and it should be within the
+/// file, otherwise an error of [`io::ErrorKind::InvalidInput`] is returned
+/// before sending anything, e.g. to answer `416 Range Not Satisfiable`.
+///
+/// The data is sent with [`compio_net::send_file`]. If the stream doesn't
+/// support it, e.g. it is not a socket on Windows, the data is read and sent
+/// in chunks with one reused buffer instead.
+///
+/// ```
+/// use compio_io::AsyncReadExt;
+/// use compio_net::{TcpListener, TcpStream};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+/// let addr = listener.local_addr().unwrap();
+/// let (mut tx, (mut rx, _)) =
+///     futures_util::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+///
+/// let len = compio_fs::serve_file(&mut tx, "Cargo.toml", Some(0..9))
+///     .await
+///     .unwrap();
+/// assert_eq!(len, 9);
+/// let (_, body) = rx.read_exact(vec![0; 9]).await.unwrap();
+/// assert_eq!(body, b"[package]");
+/// # })
+/// ```
+pub async fn serve_file<W: AsyncWrite + TryAsRawFd>(
+    stream: &mut W,
+    path: impl AsRef<Path>,
+    range: Option<Range<u64>>,
+) -> io::Result<u64> {
+    let file = File::open(path).await?;
+    let meta = file.metadata().await?;
+    if !meta.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only regular files could be served",
+        ));
+    }
+    let len = meta.len();
+    let range = range.unwrap_or(0..len);
+    if range.start > range.end || range.end > len {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the range is out of the file",
+        ));
+    }
+
+    // The first chunk is sent alone, so that the fallback only happens when
+    // nothing has been sent. An error of the rest is returned as is, because
+    // the bytes sent before it are unknown.
+    let want = (range.end - range.start) as usize;
+    let first = want.min(CHUNK_SIZE);
+    match compio_net::send_file(&file, &*stream, range.start, first).await {
+        Ok(sent) if sent < first => return Err(truncated()),
+        Ok(_) => {}
+        Err(e) if unsupported(&e) => return copy(&file, stream, range).await,
+        Err(e) => return Err(e),
+    }
+    if first < want {
+        let rest = want - first;
+        let sent =
+            compio_net::send_file(&file, &*stream, range.start + first as u64, rest).await?;
+        if sent < rest {
+            return Err(truncated());
+        }
+    }
+    Ok(want as u64)
+}
+
+// The errors of a stream which `send_file` doesn't support. They are
+// reported by the first splice or transmission, before anything is sent.
+fn unsupported(e: &io::Error) -> bool {
+    #[cfg(unix)]
+    let codes = [libc::EINVAL, libc::ENOSYS, libc::ENOTSOCK, libc::EOPNOTSUPP];
+    #[cfg(windows)]
+    let codes = [windows_sys::Win32::Networking::WinSock::WSAENOTSOCK];
+    e.raw_os_error().is_some_and(|code| codes.contains(&code))
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "the file is truncated while serving",
+    )
+}
+
+async fn copy<W: AsyncWrite>(file: &File, stream: &mut W, range: Range<u64>) -> io::Result<u64> {
+    let mut pos = range.start;
+    let mut buffer = Vec::with_capacity(CHUNK_SIZE.min((range.end - pos) as usize));
+    while pos < range.end {
+        let want = buffer.capacity().min((range.end - pos) as usize);
+        buffer.clear();
+        let BufResult(res, slice) = file.read_at(buffer.slice(..want), pos).await;
+        buffer = slice.into_inner();
+        if res? == 0 {
+            return Err(truncated());
+        }
+        let BufResult(res, buf) = stream.write_all(buffer).await;
+        buffer = buf;
+        res?;
+        pos += buffer.len() as u64;
+    }
+    Ok(pos - range.start)
+}
@@ -117,3 +117,183 @@ async fn poll_once(future: impl std::future::Future) {
     })
     .await;
 }
+
+#[compio_macros::test]
+async fn open_error() {
+    use compio_driver::ResultExt;
+
+    let err = File::open("not-exist").await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    #[cfg(unix)]
+    assert_eq!(err.raw_os_error(), Some(libc::ENOENT));
+    assert!(compio_driver::Error::get(&err).is_none());
+
+    let err: std::io::Error = File::open("not-exist")
+        .await
+        .context("open")
+        .map_err(|e| e.with_path("not-exist"))
+        .unwrap_err()
+        .into();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    let ctx = compio_driver::Error::get(&err).unwrap();
+    assert_eq!(ctx.op(), "open");
+    assert_eq!(ctx.path(), Some(std::path::Path::new("not-exist")));
+    #[cfg(unix)]
+    assert_eq!(compio_driver::Error::os_error(&err), Some(libc::ENOENT));
+}
+
+#[compio_macros::test]
+async fn metadata_batch() {
+    use futures_util::StreamExt;
+
+    let mut results = compio_fs::metadata_batch(["Cargo.toml", "src", "not-exist"], 2)
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by_key(|(path, _)| *path);
+    assert_eq!(results.len(), 3);
+    assert!(results[0].1.as_ref().unwrap().is_file());
+    assert_eq!(
+        results[1].1.as_ref().err().unwrap().kind(),
+        std::io::ErrorKind::NotFound
+    );
+    assert!(results[2].1.as_ref().unwrap().is_dir());
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn dir() {
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(temp.path().join("sub")).unwrap();
+    std::fs::write(temp.path().join("sub/hello.txt"), HELLO).unwrap();
+
+    let dir = compio_fs::Dir::open(temp.path()).await.unwrap();
+    let sub = dir.open_dir("sub").await.unwrap();
+    assert!(dir.metadata("sub").await.unwrap().is_dir());
+    assert_eq!(
+        sub.metadata("hello.txt").await.unwrap().len(),
+        HELLO.len() as u64
+    );
+    assert_eq!(sub.read_dir().await.unwrap(), ["hello.txt"]);
+
+    let file = sub
+        .open_file("hello.txt", compio_fs::OpenOptions::new().read(true))
+        .await
+        .unwrap();
+    read_hello(&file).await;
+
+    // The directory is still accessible after it is moved.
+    std::fs::rename(temp.path().join("sub"), temp.path().join("moved")).unwrap();
+    assert!(sub.metadata("hello.txt").await.unwrap().is_file());
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn resolve_beneath() {
+    use compio_fs::{Dir, OpenOptions, ResolveFlags};
+
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::create_dir(temp.path().join("root")).unwrap();
+    std::fs::write(temp.path().join("root/hello.txt"), HELLO).unwrap();
+    std::fs::write(temp.path().join("secret.txt"), HELLO).unwrap();
+    std::os::unix::fs::symlink("../secret.txt", temp.path().join("root/link")).unwrap();
+
+    let dir = Dir::open(temp.path().join("root")).await.unwrap();
+    let mut options = OpenOptions::new();
+    options.read(true).resolve(ResolveFlags::BENEATH);
+    let file = dir.open_file("hello.txt", &options).await.unwrap();
+    read_hello(&file).await;
+    assert!(dir.open_file("../secret.txt", &options).await.is_err());
+    #[cfg(target_os = "linux")]
+    assert!(dir.open_file("link", &options).await.is_err());
+
+    options.resolve(ResolveFlags::NO_SYMLINKS);
+    assert!(dir.open_file("link", &options).await.is_err());
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn sandboxed_dir() {
+    use compio_fs::{OpenOptions, SandboxedDir};
+
+    let temp = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(temp.path().join("root/sub")).unwrap();
+    std::fs::write(temp.path().join("root/sub/hello.txt"), HELLO).unwrap();
+    std::fs::write(temp.path().join("secret.txt"), HELLO).unwrap();
+    std::os::unix::fs::symlink("../secret.txt", temp.path().join("root/link")).unwrap();
+    std::os::unix::fs::symlink("..", temp.path().join("root/parent")).unwrap();
+    std::os::unix::fs::symlink("../created.txt", temp.path().join("root/dangling")).unwrap();
+
+    let root = SandboxedDir::open(temp.path().join("root")).await.unwrap();
+    let mut options = OpenOptions::new();
+    options.read(true);
+    let file = root
+        .open_file("sub/../sub/hello.txt", &options)
+        .await
+        .unwrap();
+    read_hello(&file).await;
+    assert!(root.metadata("sub").await.unwrap().is_dir());
+
+    for path in [
+        "../secret.txt",
+        "link",
+        "parent/secret.txt",
+        "sub/../../secret.txt",
+    ] {
+        assert!(root.open_file(path, &options).await.is_err());
+        assert!(root.metadata(path).await.is_err());
+    }
+
+    // The dangling symlink can't create a file outside of the root.
+    let mut options = OpenOptions::new();
+    options.write(true).create(true);
+    assert!(root.open_file("dangling", &options).await.is_err());
+    assert!(!temp.path().join("created.txt").exists());
+    assert!(root
+        .open_file(temp.path().join("secret.txt"), &options)
+        .await
+        .is_err());
+}
+
+#[compio_macros::test]
+async fn serve_file() {
+    use compio_io::AsyncReadExt;
+    use compio_net::{TcpListener, TcpStream};
+
+    let mut file = tempfile();
+    file.write_all(HELLO).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+
+    let len = compio_fs::serve_file(&mut tx, file.path(), None)
+        .await
+        .unwrap();
+    assert_eq!(len, HELLO.len() as u64);
+    let (_, body) = rx.read_exact(vec![0; HELLO.len()]).await.unwrap();
+    assert_eq!(body, HELLO);
+
+    let len = compio_fs::serve_file(&mut tx, file.path(), Some(6..11))
+        .await
+        .unwrap();
+    assert_eq!(len, 5);
+    let (_, body) = rx.read_exact(vec![0; 5]).await.unwrap();
+    assert_eq!(body, b"world");
+
+    let err = compio_fs::serve_file(&mut tx, file.path(), Some(6..100))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    // Larger than one chunk, so the rest is sent after the first chunk.
+    let data = (0..300 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+    let mut file = tempfile();
+    file.write_all(&data).unwrap();
+    let (len, body) = futures_util::join!(
+        compio_fs::serve_file(&mut tx, file.path(), Some(1..data.len() as u64)),
+        rx.read_exact(vec![0; data.len() - 1])
+    );
+    assert_eq!(len.unwrap(), data.len() as u64 - 1);
+    assert_eq!(body.1, data[1..]);
+}
@@ -1,10 +1,16 @@
 //! Compat wrappers for interop with other crates.
 
-use std::io::{self, BufRead, Read, Write};
+use std::{
+    io::{self, BufRead, Read, Write},
+    pin::Pin,
+    rc::Rc,
+    task::{ready, Context, Poll},
+};
 
 use compio_buf::{BufResult, IntoInner, IoBuf, IoBufMut, SetBufInit};
+use futures_util::future::LocalBoxFuture;
 
-use crate::{buffer::Buffer, util::DEFAULT_BUF_SIZE};
+use crate::{buffer::Buffer, util::DEFAULT_BUF_SIZE, AsyncWriteExt};
 
 /// A wrapper for [`AsyncRead`](crate::AsyncRead) +
 /// [`AsyncWrite`](crate::AsyncWrite), providing sync traits impl. The sync
@@ -61,9 +67,8 @@ impl<S> SyncStream<S> {
 impl<S> Read for SyncStream<S> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut slice = self.fill_buf()?;
-        slice.read(buf).map(|res| {
-            self.consume(res);
-            res
+        slice.read(buf).inspect(|res| {
+            self.consume(*res);
         })
     }
 
@@ -158,3 +163,179 @@ impl<S: crate::AsyncWrite> SyncStream<S> {
         Ok(len)
     }
 }
+
+/// A wrapper for streams whose references implement both [`AsyncRead`] and
+/// [`AsyncWrite`], providing poll-based [`futures_util::AsyncRead`] and
+/// [`futures_util::AsyncWrite`] impl.
+///
+/// It bridges the completion-based IO to the libraries built on the poll-based
+/// traits, e.g. SSH or HTTP implementations. The pending operations own the
+/// internal buffers, so the read and write halves could be polled
+/// concurrently, and dropping the wrapper cancels them safely.
+///
+/// [`AsyncRead`]: crate::AsyncRead
+/// [`AsyncWrite`]: crate::AsyncWrite
+pub struct AsyncStream<S> {
+    stream: Rc<S>,
+    read_buffer: Vec<u8>,
+    read_pos: usize,
+    read_future: Option<LocalBoxFuture<'static, BufResult<usize, Vec<u8>>>>,
+    write_buffer: Vec<u8>,
+    write_future: Option<LocalBoxFuture<'static, BufResult<(), Vec<u8>>>>,
+    shutdown_future: Option<LocalBoxFuture<'static, io::Result<()>>>,
+    capacity: usize,
+}
+
+impl<S> AsyncStream<S> {
+    /// Create [`AsyncStream`] with the stream and default buffer size.
+    pub fn new(stream: S) -> Self {
+        Self::with_capacity(DEFAULT_BUF_SIZE, stream)
+    }
+
+    /// Create [`AsyncStream`] with the stream and buffer size. A zero `cap` is
+    /// raised to 1, as the writes could never be buffered otherwise.
+    pub fn with_capacity(cap: usize, stream: S) -> Self {
+        let cap = cap.max(1);
+        Self {
+            stream: Rc::new(stream),
+            read_buffer: Vec::with_capacity(cap),
+            read_pos: 0,
+            read_future: None,
+            write_buffer: Vec::with_capacity(cap),
+            write_future: None,
+            shutdown_future: None,
+            capacity: cap,
+        }
+    }
+
+    /// Get the reference of the inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+}
+
+impl<S> std::fmt::Debug for AsyncStream<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncStream")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S: 'static> AsyncStream<S>
+where
+    for<'a> &'a S: crate::AsyncWrite,
+{
+    /// Poll the pending write, putting the buffer back when it completes.
+    fn poll_write_future(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(future) = &mut self.write_future {
+            let BufResult(res, mut buffer) = ready!(future.as_mut().poll(cx));
+            self.write_future = None;
+            buffer.clear();
+            self.write_buffer = buffer;
+            res?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Start writing all buffered data and flushing the stream.
+    fn start_flush(&mut self) {
+        if self.write_future.is_none() && !self.write_buffer.is_empty() {
+            let stream = self.stream.clone();
+            let buffer = std::mem::take(&mut self.write_buffer);
+            self.write_future = Some(Box::pin(async move {
+                let mut stream = &*stream;
+                let BufResult(res, buffer) = stream.write_all(buffer).await;
+                let res = match res {
+                    Ok(()) => crate::AsyncWrite::flush(&mut stream).await,
+                    Err(e) => Err(e),
+                };
+                BufResult(res, buffer)
+            }));
+        }
+    }
+}
+
+impl<S: 'static> futures_util::AsyncRead for AsyncStream<S>
+where
+    for<'a> &'a S: crate::AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        loop {
+            let rest = &this.read_buffer[this.read_pos..];
+            if !rest.is_empty() {
+                let len = rest.len().min(buf.len());
+                buf[..len].copy_from_slice(&rest[..len]);
+                this.read_pos += len;
+                return Poll::Ready(Ok(len));
+            }
+            let future = this.read_future.get_or_insert_with(|| {
+                let stream = this.stream.clone();
+                let mut buffer = std::mem::take(&mut this.read_buffer);
+                this.read_pos = 0;
+                buffer.clear();
+                buffer.reserve(this.capacity);
+                Box::pin(async move { crate::AsyncRead::read(&mut &*stream, buffer).await })
+            });
+            let BufResult(res, buffer) = ready!(future.as_mut().poll(cx));
+            this.read_future = None;
+            this.read_buffer = buffer;
+            this.read_pos = 0;
+            if res? == 0 {
+                return Poll::Ready(Ok(0));
+            }
+        }
+    }
+}
+
+impl<S: 'static> futures_util::AsyncWrite for AsyncStream<S>
+where
+    for<'a> &'a S: crate::AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        loop {
+            ready!(this.poll_write_future(cx))?;
+            let len = (this.capacity - this.write_buffer.len()).min(buf.len());
+            if len > 0 {
+                this.write_buffer.extend_from_slice(&buf[..len]);
+                return Poll::Ready(Ok(len));
+            }
+            this.start_flush();
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_future(cx))?;
+        this.start_flush();
+        this.poll_write_future(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        let this = self.get_mut();
+        let future = this.shutdown_future.get_or_insert_with(|| {
+            let stream = this.stream.clone();
+            Box::pin(async move { crate::AsyncWrite::shutdown(&mut &*stream).await })
+        });
+        let res = ready!(future.as_mut().poll(cx));
+        this.shutdown_future = None;
+        Poll::Ready(res)
+    }
+}
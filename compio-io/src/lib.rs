@@ -23,6 +23,10 @@
 //! - [`AsyncWriteExt`]: Extension trait for [`AsyncWrite`]
 //! - [`AsyncWriteAtExt`]: Extension trait for [`AsyncWriteAt`]
 //!
+//! ### Sharing
+//!
+//! - [`SharedWriter`]: An async writer shared by multiple tasks
+//!
 //!
 //! [`IoBufMut`]: compio_buf::IoBufMut
 //! [`IoBuf`]: compio_buf::IoBuf
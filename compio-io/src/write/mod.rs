@@ -9,9 +9,11 @@ use crate::IoResult;
 mod buf;
 #[macro_use]
 mod ext;
+mod shared;
 
 pub use buf::*;
 pub use ext::*;
+pub use shared::*;
 
 /// # AsyncWrite
 ///
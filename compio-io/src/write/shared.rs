@@ -0,0 +1,237 @@
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, MutexGuard, PoisonError},
+};
+
+use compio_buf::{BufResult, IoBuf};
+use futures_util::lock::Mutex;
+
+use crate::{AsyncWrite, AsyncWriteExt, IoResult};
+
+#[derive(Debug, Default)]
+struct Batch {
+    buffer: Vec<u8>,
+    // The id of the batch being filled.
+    filling: u64,
+    // The tasks waiting for the batch being filled.
+    waiters: usize,
+    // The errors of the failed batches, with the count of the waiters which
+    // have not got them.
+    errors: HashMap<u64, (io::Error, usize)>,
+}
+
+impl Batch {
+    // Get the result of the written batch `id` for one of its waiters.
+    fn take_result(&mut self, id: u64) -> IoResult<()> {
+        let Some((error, waiters)) = self.errors.get_mut(&id) else {
+            return Ok(());
+        };
+        let error = clone_error(error);
+        *waiters -= 1;
+        if *waiters == 0 {
+            self.errors.remove(&id);
+        }
+        Err(error)
+    }
+}
+
+// Fails the batch for its other waiters if the write is cancelled, e.g. the
+// writing task is dropped in the middle of it.
+struct WriteGuard<'a> {
+    batch: &'a std::sync::Mutex<Batch>,
+    id: u64,
+    others: usize,
+}
+
+impl Drop for WriteGuard<'_> {
+    fn drop(&mut self) {
+        if self.others > 0 {
+            let error = io::Error::new(io::ErrorKind::Interrupted, "the batch write is cancelled");
+            lock(self.batch)
+                .errors
+                .insert(self.id, (error, self.others));
+        }
+    }
+}
+
+// Removes a waiter dropped before it gets the writer, so that its batch
+// doesn't wait for it.
+struct WaiterGuard<'a> {
+    batch: &'a std::sync::Mutex<Batch>,
+    id: u64,
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        let mut batch = lock(self.batch);
+        if batch.filling == self.id {
+            batch.waiters -= 1;
+        } else {
+            batch.take_result(self.id).ok();
+        }
+    }
+}
+
+fn lock(batch: &std::sync::Mutex<Batch>) -> MutexGuard<'_, Batch> {
+    batch.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn clone_error(e: &io::Error) -> io::Error {
+    match e.raw_os_error() {
+        Some(code) => io::Error::from_raw_os_error(code),
+        None => io::Error::new(e.kind(), e.to_string()),
+    }
+}
+
+#[derive(Debug)]
+struct Shared<W> {
+    writer: Mutex<W>,
+    batch: std::sync::Mutex<Batch>,
+}
+
+/// A writer shared by multiple tasks, which serializes the whole messages
+/// sent by them.
+///
+/// Each message is written entirely before the next one, so the messages
+/// from the different tasks don't interleave. [`send_coalesced`] further
+/// merges the messages sent while the writer is busy into one write.
+///
+/// ```
+/// use compio_io::SharedWriter;
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+///
+/// let writer = SharedWriter::new(vec![]);
+/// let other = writer.clone();
+/// futures_util::try_join!(
+///     writer.send_coalesced(b"hello "),
+///     other.send_coalesced(b"world")
+/// )
+/// .unwrap();
+/// drop(other);
+/// assert_eq!(writer.into_inner().unwrap(), b"hello world");
+/// # })
+/// ```
+///
+/// [`send_coalesced`]: SharedWriter::send_coalesced
+#[derive(Debug)]
+pub struct SharedWriter<W> {
+    inner: Arc<Shared<W>>,
+}
+
+impl<W> Clone for SharedWriter<W> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<W: AsyncWrite> SharedWriter<W> {
+    /// Create [`SharedWriter`].
+    pub fn new(writer: W) -> Self {
+        Self {
+            inner: Arc::new(Shared {
+                writer: Mutex::new(writer),
+                batch: std::sync::Mutex::default(),
+            }),
+        }
+    }
+
+    /// Write the entire buffer, after the previous sends complete.
+    pub async fn send<T: IoBuf>(&self, buf: T) -> BufResult<(), T> {
+        let mut writer = self.inner.writer.lock().await;
+        // Write the coalesced messages sent before this one.
+        if let Err(e) = self.write_batch(&mut writer, None).await {
+            return BufResult(Err(e), buf);
+        }
+        writer.write_all(buf).await
+    }
+
+    /// Write the entire slice, coalescing it with the messages sent while
+    /// the writer is busy.
+    ///
+    /// The data is copied into a shared buffer. When the writer is available,
+    /// the buffered messages of all tasks are written at once, and all of the
+    /// tasks get the same result. The data is still written if the task is
+    /// cancelled after the copy, and an empty slice is not sent at all.
+    pub async fn send_coalesced(&self, data: &[u8]) -> IoResult<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let id = {
+            let mut batch = lock(&self.inner.batch);
+            batch.buffer.extend_from_slice(data);
+            batch.waiters += 1;
+            batch.filling
+        };
+        let guard = WaiterGuard {
+            batch: &self.inner.batch,
+            id,
+        };
+        let mut writer = self.inner.writer.lock().await;
+        std::mem::forget(guard);
+        self.write_batch(&mut writer, Some(id)).await
+    }
+
+    /// Write the coalesced messages if the batch `id` has not been written.
+    async fn write_batch(&self, writer: &mut W, waiter: Option<u64>) -> IoResult<()> {
+        let (id, waiters, buffer) = {
+            let mut batch = lock(&self.inner.batch);
+            match waiter {
+                // The batch has been taken and written by another task.
+                Some(id) if batch.filling > id => return batch.take_result(id),
+                _ if batch.buffer.is_empty() => return Ok(()),
+                _ => {}
+            }
+            let id = batch.filling;
+            batch.filling += 1;
+            let waiters = std::mem::take(&mut batch.waiters);
+            (id, waiters, std::mem::take(&mut batch.buffer))
+        };
+        // The other waiters get the error later, even if the following
+        // batches are written successfully.
+        let others = waiters - usize::from(waiter.is_some());
+        let mut guard = WriteGuard {
+            batch: &self.inner.batch,
+            id,
+            others,
+        };
+        let BufResult(res, mut buffer) = writer.write_all(buffer).await;
+        guard.others = 0;
+        drop(guard);
+        let mut batch = lock(&self.inner.batch);
+        if let Err(e) = &res {
+            if others > 0 {
+                batch.errors.insert(id, (clone_error(e), others));
+            }
+        }
+        if batch.buffer.is_empty() {
+            // Reuse the allocation.
+            buffer.clear();
+            batch.buffer = buffer;
+        }
+        res
+    }
+
+    /// Flush the writer, after the previous sends complete.
+    pub async fn flush(&self) -> IoResult<()> {
+        let mut writer = self.inner.writer.lock().await;
+        self.write_batch(&mut writer, None).await?;
+        writer.flush().await
+    }
+
+    /// Shutdown the writer, after the previous sends complete.
+    pub async fn shutdown(&self) -> IoResult<()> {
+        let mut writer = self.inner.writer.lock().await;
+        self.write_batch(&mut writer, None).await?;
+        writer.shutdown().await
+    }
+
+    /// Get the inner writer if this is the only handle.
+    pub fn into_inner(self) -> Result<W, Self> {
+        Arc::try_unwrap(self.inner)
+            .map(|shared| shared.writer.into_inner())
+            .map_err(|inner| Self { inner })
+    }
+}
@@ -1,9 +1,9 @@
-use std::io::Cursor;
+use std::{cell::Cell, io::Cursor, rc::Rc, task::Poll};
 
-use compio_buf::{arrayvec::ArrayVec, BufResult, IoBuf, IoBufMut};
+use compio_buf::{arrayvec::ArrayVec, BufChainMut, BufResult, IoBuf, IoBufMut, IoIndexedBuf};
 use compio_io::{
     split, AsyncRead, AsyncReadAt, AsyncReadAtExt, AsyncReadExt, AsyncWrite, AsyncWriteAt,
-    AsyncWriteAtExt, AsyncWriteExt,
+    AsyncWriteAtExt, AsyncWriteExt, SharedWriter,
 };
 
 #[tokio::test]
@@ -118,6 +118,21 @@ async fn readv() {
     assert_eq!(buf[1], [1, 1, 4, 5, 1, 4, 1, 9, 1, 9, 8, 1, 0]);
 }
 
+#[tokio::test]
+async fn readv_chain() {
+    let mut src = &[1u8, 1, 4, 5, 1, 4, 1, 9, 1, 9, 8, 1, 0][..];
+    let mut chain = BufChainMut::new();
+    chain.push(Vec::with_capacity(4));
+    chain.push(ArrayVec::<u8, 3>::new());
+    chain.push(Vec::with_capacity(10));
+    let (len, chain) = src.read_vectored(chain).await.unwrap();
+    assert_eq!(len, 13);
+    assert_eq!(chain.len(), 13);
+    assert_eq!(chain.buf_nth(0).unwrap().as_slice(), [1, 1, 4, 5]);
+    assert_eq!(chain.buf_nth(1).unwrap().as_slice(), [1, 4, 1]);
+    assert_eq!(chain.buf_nth(2).unwrap().as_slice(), [9, 1, 9, 8, 1, 0]);
+}
+
 #[tokio::test]
 async fn writev() {
     let mut dst = Cursor::new([0u8; 10]);
@@ -371,3 +386,178 @@ async fn split_unsplit() {
     let src = read.unsplit(write);
     assert_eq!(src.into_inner(), [1, 1, 4, 2, 2, 2]);
 }
+
+#[tokio::test]
+async fn shared_writer_error() {
+    let writes = Rc::new(Cell::new(0));
+    let writer = SharedWriter::new(FailingWriter {
+        data: vec![],
+        writes: writes.clone(),
+        fail: 1,
+    });
+    // The second write fails, while another task waits for the batch. The next
+    // batch is written successfully before the waiter gets the result.
+    let (a, b, c, d) = futures_util::join!(
+        writer.send(b"aa"),
+        writer.send_coalesced(b"bb"),
+        async {
+            std::future::poll_fn(|cx| {
+                if writes.get() > 1 {
+                    Poll::Ready(())
+                } else {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            })
+            .await;
+            writer.send_coalesced(b"cc").await
+        },
+        writer.send_coalesced(b"dd"),
+    );
+    a.unwrap();
+    assert_eq!(b.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe);
+    c.unwrap();
+    assert_eq!(d.unwrap_err().kind(), std::io::ErrorKind::BrokenPipe);
+    assert_eq!(writer.into_inner().unwrap().data, b"aacc");
+}
+
+#[tokio::test]
+async fn shared_writer_cancel() {
+    let writes = Rc::new(Cell::new(0));
+    let writer = SharedWriter::new(StallingWriter {
+        data: vec![],
+        writes: writes.clone(),
+        stall: 1,
+    });
+    // The task writing the batch is cancelled in the middle of the write, and
+    // the other waiter of the batch gets the error.
+    let (a, (), c) = futures_util::join!(
+        writer.send(b"aa"),
+        async {
+            let stalled = std::future::poll_fn(|cx| {
+                if writes.get() > 1 {
+                    Poll::Ready(())
+                } else {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            });
+            let send = writer.send_coalesced(b"bb");
+            futures_util::future::select(std::pin::pin!(send), std::pin::pin!(stalled)).await;
+        },
+        writer.send_coalesced(b"cc"),
+    );
+    a.unwrap();
+    assert_eq!(c.unwrap_err().kind(), std::io::ErrorKind::Interrupted);
+    writer.send_coalesced(b"").await.unwrap();
+    assert_eq!(writes.get(), 2);
+}
+
+/// A writer never completing the write with the index `stall`, and yielding
+/// before each write.
+#[derive(Debug)]
+struct StallingWriter {
+    data: Vec<u8>,
+    writes: Rc<Cell<usize>>,
+    stall: usize,
+}
+
+impl AsyncWrite for StallingWriter {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let index = self.writes.get();
+        self.writes.set(index + 1);
+        tokio::task::yield_now().await;
+        if index == self.stall {
+            std::future::pending::<()>().await;
+        }
+        self.data.extend_from_slice(buf.as_slice());
+        BufResult(Ok(buf.buf_len()), buf)
+    }
+
+    async fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer failing the write with the index `fail`, and yielding before each
+/// write.
+#[derive(Debug)]
+struct FailingWriter {
+    data: Vec<u8>,
+    writes: Rc<Cell<usize>>,
+    fail: usize,
+}
+
+impl AsyncWrite for FailingWriter {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let index = self.writes.get();
+        self.writes.set(index + 1);
+        tokio::task::yield_now().await;
+        if index == self.fail {
+            return BufResult(Err(std::io::ErrorKind::BrokenPipe.into()), buf);
+        }
+        self.data.extend_from_slice(buf.as_slice());
+        BufResult(Ok(buf.buf_len()), buf)
+    }
+
+    async fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A writer writing at most 2 bytes at a time, and yielding before each
+/// write.
+#[derive(Debug)]
+struct SlowWriter(Vec<u8>);
+
+impl AsyncWrite for SlowWriter {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        tokio::task::yield_now().await;
+        let len = buf.buf_len().min(2);
+        self.0.extend_from_slice(&buf.as_slice()[..len]);
+        BufResult(Ok(len), buf)
+    }
+
+    async fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn shared_writer() {
+    let writer = SharedWriter::new(SlowWriter(vec![]));
+    let (a, b, c) = futures_util::join!(
+        writer.send(b"aaaa"),
+        writer.send_coalesced(b"bbbb"),
+        writer.send(b"cccc"),
+    );
+    a.unwrap();
+    b.unwrap();
+    c.unwrap();
+    writer.flush().await.unwrap();
+    assert_eq!(writer.into_inner().unwrap().0, b"aaaabbbbcccc");
+}
+
+#[tokio::test]
+async fn shared_writer_coalesced() {
+    let writer = SharedWriter::new(SlowWriter(vec![]));
+    let messages = [&b"aaaa"[..], b"bbbb", b"cccc", b"dddd"];
+    let results =
+        futures_util::future::join_all(messages.iter().map(|m| writer.send_coalesced(m))).await;
+    for res in results {
+        res.unwrap();
+    }
+    assert_eq!(writer.into_inner().unwrap().0, b"aaaabbbbccccdddd");
+}
@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{quote, ToTokens};
 use syn::{
     parse::Parse, punctuated::Punctuated, Attribute, Expr, Lit, Meta, Signature, Visibility,
 };
@@ -55,11 +55,86 @@ impl RawBodyItemFn {
                             return Some(quote!(#crate_name::runtime));
                         }
                     }
-                } else {
+                } else if !["start_paused", "threads", "ring_entries", "sqpoll"]
+                    .contains(&ident.as_str())
+                {
                     panic!("Unsupported property {}", ident);
                 }
+            } else if !["sqpoll", "autotune", "env"]
+                .iter()
+                .any(|flag| attr.path().is_ident(flag))
+            {
+                panic!("Unsupported property {}", attr.path().to_token_stream());
             }
         }
         None
     }
+
+    fn flag(&self, key: &str) -> bool {
+        self.args
+            .iter()
+            .any(|attr| matches!(attr, Meta::Path(path) if path.is_ident(key)))
+    }
+
+    fn value(&self, key: &str) -> Option<&Expr> {
+        self.args.iter().find_map(|attr| match attr {
+            Meta::NameValue(name) if name.path.is_ident(key) => Some(&name.value),
+            _ => None,
+        })
+    }
+
+    /// The expression to create the runtime, with the builder options in the
+    /// arguments.
+    pub fn runtime(&self, runtime_mod: &TokenStream) -> TokenStream {
+        let mut options = vec![];
+        // The explicit options override the tuned ones.
+        if self.flag("autotune") {
+            options.push(quote!(builder.autotune();));
+        }
+        if let Some(threads) = self.value("threads") {
+            options.push(quote!(builder.thread_pool_limit(#threads);));
+        }
+        if let Some(entries) = self.value("ring_entries") {
+            options.push(quote!(builder.capacity(#entries);));
+        }
+        if let Some(idle) = self.value("sqpoll") {
+            options.push(quote!(builder.sqpoll(::std::time::Duration::from_millis(#idle));));
+        } else if self.flag("sqpoll") {
+            options.push(quote!(builder.sqpoll(::std::time::Duration::from_secs(1));));
+        }
+        // The environment variables override all of the options.
+        if self.flag("env") {
+            options.push(quote!(builder
+                .load_env()
+                .expect("invalid compio environment variables");));
+        }
+        if options.is_empty() {
+            quote!(#runtime_mod::Runtime::new().expect("cannot create runtime"))
+        } else {
+            quote!(#runtime_mod::RuntimeBuilder::new()
+                .with_proactor({
+                    let mut builder = #runtime_mod::ProactorBuilder::new();
+                    #(#options)*
+                    builder
+                })
+                .build()
+                .expect("cannot create runtime"))
+        }
+    }
+
+    pub fn start_paused(&self) -> bool {
+        for attr in &self.args {
+            if let Meta::NameValue(name) = &attr {
+                if name.path.is_ident("start_paused") {
+                    if let Expr::Lit(lit) = &name.value {
+                        if let Lit::Bool(b) = &lit.lit {
+                            return b.value;
+                        }
+                    }
+                    panic!("`start_paused` should be a bool");
+                }
+            }
+        }
+        false
+    }
 }
@@ -10,6 +10,24 @@ use proc_macro_crate::{crate_name, FoundCrate};
 use quote::{quote, ToTokens};
 use syn::parse_macro_input;
 
+/// Run the async `main` function in a compio runtime.
+///
+/// The runtime could be configured by the arguments:
+/// * `threads = N`: the thread number limit of the blocking thread pool.
+/// * `ring_entries = N`: the capacity of the submission queue.
+/// * `sqpoll` or `sqpoll = MS`: poll the io-uring submission queue with a
+///   kernel thread, which sleeps after being idle for `MS` milliseconds, 1000
+///   by default.
+/// * `autotune`: choose the sizes from the system, see
+///   `ProactorBuilder::autotune`. The other options override them.
+/// * `env`: override the options with the `COMPIO_*` environment variables, see
+///   `ProactorBuilder::load_env`.
+/// * `crate = "path"`: the path of the `compio` crate.
+///
+/// ```ignore
+/// #[compio::main(threads = 4, ring_entries = 4096)]
+/// async fn main() {}
+/// ```
 #[proc_macro_attribute]
 pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
     parse_macro_input!(item as main_fn::CompioMain)
@@ -18,6 +36,10 @@ pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Run the async test function in a compio runtime.
+///
+/// It accepts the arguments of [`main`](macro@main), and
+/// `start_paused = true` to pause the timer clock before the test starts.
 #[proc_macro_attribute]
 pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
     parse_macro_input!(item as test_fn::CompioTest)
@@ -52,9 +52,13 @@ impl ToTokens for CompioMain {
         self.0.vis.to_tokens(tokens);
         self.0.sig.to_tokens(tokens);
         let block = &self.0.body;
+        if self.0.start_paused() {
+            panic!("`start_paused` is only supported by `compio::test`");
+        }
         let runtime_mod = self.0.crate_name().unwrap_or_else(retrieve_runtime_mod);
+        let runtime = self.0.runtime(&runtime_mod);
         tokens.append_all(quote!({
-            #runtime_mod::Runtime::new().expect("cannot create runtime").block_on(async move #block)
+            #runtime.block_on(async move #block)
         }));
     }
 }
@@ -47,8 +47,17 @@ impl ToTokens for CompioTest {
         self.0.sig.to_tokens(tokens);
         let block = &self.0.body;
         let runtime_mod = self.0.crate_name().unwrap_or_else(retrieve_runtime_mod);
+        let block = if self.0.start_paused() {
+            quote!({
+                #runtime_mod::time::pause();
+                #block
+            })
+        } else {
+            quote!(#block)
+        };
+        let runtime = self.0.runtime(&runtime_mod);
         tokens.append_all(quote!({
-            #runtime_mod::Runtime::new().expect("cannot create runtime").block_on(async move #block)
+            #runtime.block_on(async move #block)
         }));
     }
 }
@@ -0,0 +1,256 @@
+use std::{
+    mem::{size_of, size_of_val},
+    os::fd::RawFd,
+};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use crate::PeerCred;
+
+/// A builder of the ancillary data (control messages) sent with
+/// [`UdpSocket::send_msg`] or [`UnixStream::send_msg`].
+///
+/// # Examples
+///
+/// Send a datagram with the DSCP "expedited forwarding" class:
+///
+/// ```
+/// use compio_net::{CMsgBuilder, UdpSocket};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let addr = socket.local_addr().unwrap();
+///
+/// let mut control = CMsgBuilder::new();
+/// control.push(libc::IPPROTO_IP, libc::IP_TOS, 0xb8 as libc::c_int);
+/// socket
+///     .send_msg([b"ping"], control.finish(), addr)
+///     .await
+///     .unwrap();
+/// # })
+/// ```
+///
+/// [`UdpSocket::send_msg`]: crate::UdpSocket::send_msg
+/// [`UnixStream::send_msg`]: crate::UnixStream::send_msg
+#[derive(Debug, Default, Clone)]
+pub struct CMsgBuilder {
+    buffer: Vec<u8>,
+}
+
+impl CMsgBuilder {
+    /// Create an empty [`CMsgBuilder`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a control message of `level` and `ty`, with the bytes of
+    /// `data` as its payload.
+    pub fn push<T: Copy>(&mut self, level: i32, ty: i32, data: T) -> &mut Self {
+        self.push_slice(level, ty, &[data])
+    }
+
+    /// Append a control message of `level` and `ty`, with the bytes of the
+    /// items of `data` as its payload.
+    pub fn push_slice<T: Copy>(&mut self, level: i32, ty: i32, data: &[T]) -> &mut Self {
+        let len = size_of_val(data) as _;
+        let start = self.buffer.len();
+        let space = unsafe { libc::CMSG_SPACE(len) } as usize;
+        self.buffer.resize(start + space, 0);
+        unsafe {
+            let ptr = self.buffer.as_mut_ptr().add(start);
+            let mut header: libc::cmsghdr = std::mem::zeroed();
+            header.cmsg_len = libc::CMSG_LEN(len) as _;
+            header.cmsg_level = level;
+            header.cmsg_type = ty;
+            ptr.cast::<libc::cmsghdr>().write_unaligned(header);
+            std::ptr::copy_nonoverlapping(
+                data.as_ptr().cast::<u8>(),
+                ptr.add(libc::CMSG_LEN(0) as usize),
+                len as usize,
+            );
+        }
+        self
+    }
+
+    /// Append an `SCM_RIGHTS` message passing `fds` to the peer of a Unix
+    /// socket. The fds should be kept open until the message is sent.
+    pub fn push_fds(&mut self, fds: &[RawFd]) -> &mut Self {
+        self.push_slice(libc::SOL_SOCKET, libc::SCM_RIGHTS, fds)
+    }
+
+    /// Append an `SCM_CREDENTIALS` message with the credentials of the
+    /// sender. The kernel checks them, so an unprivileged process can only
+    /// send its own ids and pid.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn push_credentials(&mut self, pid: u32, uid: u32, gid: u32) -> &mut Self {
+        let cred = libc::ucred {
+            pid: pid as _,
+            uid,
+            gid,
+        };
+        self.push(libc::SOL_SOCKET, libc::SCM_CREDENTIALS, cred)
+    }
+
+    /// Total length of the encoded control messages.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if there are no control messages.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Get the encoded control messages.
+    pub fn finish(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+/// An iterator over the control messages received with
+/// [`UdpSocket::recv_msg`], [`UnixStream::recv_msg`] or
+/// [`UnixDatagram::recv_msg`].
+///
+/// # Examples
+///
+/// Pass a fd through a Unix socket:
+///
+/// ```
+/// use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+///
+/// use compio_net::{CMsgBuilder, CMsgIter, UnixDatagram};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let (tx, rx) = UnixDatagram::pair().unwrap();
+/// let file = tempfile::tempfile().unwrap();
+///
+/// let mut control = CMsgBuilder::new();
+/// control.push_fds(&[file.as_raw_fd()]);
+/// tx.send_msg([b"fd"], control.finish()).await.unwrap();
+///
+/// let control = Vec::with_capacity(64);
+/// let (_, (_, control)) = rx.recv_msg([Vec::with_capacity(2)], control).await.unwrap();
+/// let fds = CMsgIter::new(&control).find_map(|msg| msg.fds()).unwrap();
+/// let fd = unsafe { OwnedFd::from_raw_fd(fds[0]) };
+/// assert_ne!(fd.as_raw_fd(), file.as_raw_fd());
+/// # })
+/// ```
+///
+/// [`UdpSocket::recv_msg`]: crate::UdpSocket::recv_msg
+/// [`UnixStream::recv_msg`]: crate::UnixStream::recv_msg
+/// [`UnixDatagram::recv_msg`]: crate::UnixDatagram::recv_msg
+#[derive(Debug, Clone)]
+pub struct CMsgIter<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> CMsgIter<'a> {
+    /// Iterate over the encoded control messages in `buffer`.
+    pub fn new(buffer: &'a [u8]) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<'a> Iterator for CMsgIter<'a> {
+    type Item = CMsg<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.len() < size_of::<libc::cmsghdr>() {
+            return None;
+        }
+        let header = unsafe {
+            self.buffer
+                .as_ptr()
+                .cast::<libc::cmsghdr>()
+                .read_unaligned()
+        };
+        let start = unsafe { libc::CMSG_LEN(0) } as usize;
+        // The type of `cmsg_len` differs between the platforms.
+        #[allow(clippy::unnecessary_cast)]
+        let end = (header.cmsg_len as usize).min(self.buffer.len());
+        if end < start {
+            return None;
+        }
+        let data = &self.buffer[start..end];
+        let space = unsafe { libc::CMSG_SPACE(data.len() as _) } as usize;
+        self.buffer = &self.buffer[space.min(self.buffer.len())..];
+        Some(CMsg {
+            level: header.cmsg_level,
+            ty: header.cmsg_type,
+            data,
+        })
+    }
+}
+
+/// A control message yielded by [`CMsgIter`].
+#[derive(Debug, Clone, Copy)]
+pub struct CMsg<'a> {
+    level: i32,
+    ty: i32,
+    data: &'a [u8],
+}
+
+impl<'a> CMsg<'a> {
+    /// The level of the message, e.g. `SOL_SOCKET`.
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// The type of the message, e.g. `SCM_RIGHTS`.
+    pub fn ty(&self) -> i32 {
+        self.ty
+    }
+
+    /// The payload of the message.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// The TOS field of an IPv4 datagram, or the traffic class of an IPv6
+    /// one, received with `IP_RECVTOS` or `IPV6_RECVTCLASS`, or `None` for
+    /// the other messages.
+    pub fn tos(&self) -> Option<u8> {
+        match (self.level, self.ty) {
+            (libc::IPPROTO_IP, libc::IP_TOS) => self.data.first().copied(),
+            #[cfg(target_vendor = "apple")]
+            (libc::IPPROTO_IP, libc::IP_RECVTOS) => self.data.first().copied(),
+            (libc::IPPROTO_IPV6, libc::IPV6_TCLASS) => {
+                let tclass = self.data.get(..size_of::<libc::c_int>())?;
+                Some(libc::c_int::from_ne_bytes(tclass.try_into().unwrap()) as u8)
+            }
+            _ => None,
+        }
+    }
+
+    /// The fds passed by an `SCM_RIGHTS` message, or `None` for the other
+    /// messages.
+    ///
+    /// The caller owns the fds, and should close them.
+    pub fn fds(&self) -> Option<Vec<RawFd>> {
+        if self.level != libc::SOL_SOCKET || self.ty != libc::SCM_RIGHTS {
+            return None;
+        }
+        let fds = self
+            .data
+            .chunks_exact(size_of::<RawFd>())
+            .map(|chunk| RawFd::from_ne_bytes(chunk.try_into().unwrap()))
+            .collect();
+        Some(fds)
+    }
+
+    /// The credentials of the sender in an `SCM_CREDENTIALS` message, or
+    /// `None` for the other messages. They are received only if `SO_PASSCRED`
+    /// is set on the socket, e.g. with [`UnixStream::set_passcred`].
+    ///
+    /// [`UnixStream::set_passcred`]: crate::UnixStream::set_passcred
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn credentials(&self) -> Option<PeerCred> {
+        if self.level != libc::SOL_SOCKET
+            || self.ty != libc::SCM_CREDENTIALS
+            || self.data.len() < size_of::<libc::ucred>()
+        {
+            return None;
+        }
+        let cred = unsafe { self.data.as_ptr().cast::<libc::ucred>().read_unaligned() };
+        Some(PeerCred::from_ucred(cred))
+    }
+}
@@ -0,0 +1,140 @@
+use std::io;
+
+use compio_driver::RawFd;
+
+/// The credentials of the peer process of a Unix socket, returned by
+/// [`UnixStream::peer_cred`](crate::UnixStream::peer_cred).
+///
+/// They are the effective ids and the pid on Unix, and only the pid on
+/// Windows. They are captured when the connection is established, so they
+/// are still the ones of the connecting process after it changes its
+/// credentials or exits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCred {
+    #[cfg(unix)]
+    uid: u32,
+    #[cfg(unix)]
+    gid: u32,
+    pid: Option<u32>,
+}
+
+impl PeerCred {
+    /// The effective user id of the peer.
+    #[cfg(unix)]
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// The effective group id of the peer.
+    #[cfg(unix)]
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    /// The process id of the peer, if the platform reports it. It is
+    /// reported on Linux, Android, Apple platforms and Windows.
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn peer_cred(fd: RawFd) -> io::Result<PeerCred> {
+    let mut cred = std::mem::MaybeUninit::<libc::ucred>::zeroed();
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    compio_driver::syscall!(libc::getsockopt(
+        fd,
+        libc::SOL_SOCKET,
+        libc::SO_PEERCRED,
+        cred.as_mut_ptr().cast(),
+        &mut len,
+    ))?;
+    Ok(PeerCred::from_ucred(unsafe { cred.assume_init() }))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl PeerCred {
+    pub(crate) fn from_ucred(cred: libc::ucred) -> Self {
+        Self {
+            uid: cred.uid,
+            gid: cred.gid,
+            // No pid if the peer is in another pid namespace.
+            pid: (cred.pid > 0).then_some(cred.pid as u32),
+        }
+    }
+}
+
+#[cfg(all(
+    unix,
+    not(any(target_os = "linux", target_os = "android")),
+    any(
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    )
+))]
+pub(crate) fn peer_cred(fd: RawFd) -> io::Result<PeerCred> {
+    let mut uid = 0;
+    let mut gid = 0;
+    compio_driver::syscall!(libc::getpeereid(fd, &mut uid, &mut gid))?;
+    #[cfg(target_vendor = "apple")]
+    let pid = {
+        let mut pid: libc::pid_t = 0;
+        let mut len = std::mem::size_of::<libc::pid_t>() as libc::socklen_t;
+        compio_driver::syscall!(libc::getsockopt(
+            fd,
+            libc::SOL_LOCAL,
+            libc::LOCAL_PEERPID,
+            (&mut pid as *mut libc::pid_t).cast(),
+            &mut len,
+        ))?;
+        Some(pid as u32)
+    };
+    #[cfg(not(target_vendor = "apple"))]
+    let pid = None;
+    Ok(PeerCred { uid, gid, pid })
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_vendor = "apple",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    ))
+))]
+pub(crate) fn peer_cred(_fd: RawFd) -> io::Result<PeerCred> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the peer credentials are not supported on this platform",
+    ))
+}
+
+#[cfg(windows)]
+pub(crate) fn peer_cred(fd: RawFd) -> io::Result<PeerCred> {
+    use windows_sys::Win32::Networking::WinSock::{WSAIoctl, SIO_AF_UNIX_GETPEERPID};
+
+    let mut pid = 0u32;
+    let mut len = 0;
+    compio_driver::syscall!(
+        SOCKET,
+        WSAIoctl(
+            fd as _,
+            SIO_AF_UNIX_GETPEERPID,
+            std::ptr::null(),
+            0,
+            (&mut pid as *mut u32).cast(),
+            std::mem::size_of::<u32>() as _,
+            &mut len,
+            std::ptr::null_mut(),
+            None,
+        )
+    )?;
+    Ok(PeerCred { pid: Some(pid) })
+}
@@ -0,0 +1,157 @@
+use std::{
+    collections::VecDeque,
+    future::poll_fn,
+    io,
+    sync::{Arc, Mutex},
+    task::{Poll, Waker},
+};
+
+use compio_buf::{BufResult, IoBuf, IoBufMut};
+use compio_io::{AsyncRead, AsyncWrite};
+
+/// Create a pair of connected in-memory streams.
+///
+/// The bytes written to one stream are read from the other, like a
+/// [`UnixStream`](crate::UnixStream) pair, but no fds or ports are used, so
+/// the tests of the protocol code can run anywhere. Each direction buffers
+/// at most `buffer_size` bytes, and the writes wait for the peer to read
+/// when it is full.
+///
+/// Dropping or shutting down a stream closes its writing direction: the
+/// peer reads EOF after the buffered bytes, and its writes fail with
+/// [`io::ErrorKind::BrokenPipe`].
+///
+/// ```
+/// use compio_io::{AsyncReadExt, AsyncWriteExt};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let (mut a, mut b) = compio_net::duplex(64);
+///
+/// a.write_all("ping").await.unwrap();
+/// let (_, buf) = b.read_exact(Vec::with_capacity(4)).await.unwrap();
+/// assert_eq!(buf, b"ping");
+/// # })
+/// ```
+pub fn duplex(buffer_size: usize) -> (DuplexStream, DuplexStream) {
+    assert!(buffer_size > 0, "the buffer size should not be zero");
+    let a = Arc::new(Mutex::new(Pipe::new(buffer_size)));
+    let b = Arc::new(Mutex::new(Pipe::new(buffer_size)));
+    (
+        DuplexStream {
+            read: a.clone(),
+            write: b.clone(),
+        },
+        DuplexStream { read: b, write: a },
+    )
+}
+
+/// One end of an in-memory stream pair, created by [`duplex`].
+#[derive(Debug)]
+pub struct DuplexStream {
+    read: Arc<Mutex<Pipe>>,
+    write: Arc<Mutex<Pipe>>,
+}
+
+#[derive(Debug)]
+struct Pipe {
+    buffer: VecDeque<u8>,
+    capacity: usize,
+    closed: bool,
+    read_waker: Option<Waker>,
+    write_waker: Option<Waker>,
+}
+
+impl Pipe {
+    fn new(capacity: usize) -> Self {
+        Self {
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            closed: false,
+            read_waker: None,
+            write_waker: None,
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        if let Some(waker) = self.read_waker.take() {
+            waker.wake();
+        }
+        if let Some(waker) = self.write_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl AsyncRead for DuplexStream {
+    async fn read<B: IoBufMut>(&mut self, mut buf: B) -> BufResult<usize, B> {
+        let len = poll_fn(|cx| {
+            let mut pipe = self.read.lock().unwrap();
+            let slice = buf.as_mut_slice();
+            if slice.is_empty() {
+                return Poll::Ready(0);
+            }
+            if pipe.buffer.is_empty() {
+                if pipe.closed {
+                    return Poll::Ready(0);
+                }
+                pipe.read_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            let len = slice.len().min(pipe.buffer.len());
+            for (dst, src) in slice.iter_mut().zip(pipe.buffer.drain(..len)) {
+                dst.write(src);
+            }
+            if let Some(waker) = pipe.write_waker.take() {
+                waker.wake();
+            }
+            Poll::Ready(len)
+        })
+        .await;
+        unsafe { buf.set_buf_init(len) };
+        BufResult(Ok(len), buf)
+    }
+}
+
+impl AsyncWrite for DuplexStream {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let res = poll_fn(|cx| {
+            let mut pipe = self.write.lock().unwrap();
+            if pipe.closed {
+                return Poll::Ready(Err(io::Error::from(io::ErrorKind::BrokenPipe)));
+            }
+            let slice = buf.as_slice();
+            if slice.is_empty() {
+                return Poll::Ready(Ok(0));
+            }
+            let len = slice.len().min(pipe.capacity - pipe.buffer.len());
+            if len == 0 {
+                pipe.write_waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            pipe.buffer.extend(&slice[..len]);
+            if let Some(waker) = pipe.read_waker.take() {
+                waker.wake();
+            }
+            Poll::Ready(Ok(len))
+        })
+        .await;
+        BufResult(res, buf)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.write.lock().unwrap().close();
+        Ok(())
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        self.read.lock().unwrap().close();
+        self.write.lock().unwrap().close();
+    }
+}
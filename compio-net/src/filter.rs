@@ -0,0 +1,87 @@
+use std::net::Ipv4Addr;
+
+use libc::{sock_filter, BPF_STMT};
+
+/// A classic BPF program attached to a socket with `SO_ATTACH_FILTER`.
+///
+/// The kernel runs the program for each packet received by the socket, and
+/// drops the packets it rejects. It avoids the wakeups and the copies of the
+/// unwanted packets.
+///
+/// # Examples
+///
+/// Only receive the datagrams from `127.0.0.0/8`:
+///
+/// ```
+/// use std::net::Ipv4Addr;
+///
+/// use compio_net::{SocketFilter, UdpSocket};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+/// let filter = SocketFilter::ipv4_source(Ipv4Addr::new(127, 0, 0, 0), 8);
+/// socket.attach_filter(&filter).unwrap();
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct SocketFilter {
+    insns: Vec<sock_filter>,
+}
+
+impl SocketFilter {
+    /// Create [`SocketFilter`] from the raw instructions.
+    pub fn from_raw(insns: Vec<sock_filter>) -> Self {
+        Self { insns }
+    }
+
+    /// A filter accepting all packets.
+    pub fn accept_all() -> Self {
+        Self::from_raw(vec![ret(u32::MAX)])
+    }
+
+    /// A filter rejecting all packets.
+    ///
+    /// It is useful to drain a socket, e.g. before attaching another filter.
+    pub fn reject_all() -> Self {
+        Self::from_raw(vec![ret(0)])
+    }
+
+    /// A filter accepting only the IPv4 packets from the subnet of `addr`
+    /// with `prefix_len` bits of network mask.
+    ///
+    /// It reads the source address from the IPv4 header, so it only works
+    /// with the IPv4 sockets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix_len` is greater than 32.
+    pub fn ipv4_source(addr: Ipv4Addr, prefix_len: u8) -> Self {
+        assert!(prefix_len <= 32, "invalid prefix length {prefix_len}");
+        let mask = u32::MAX.checked_shl(32 - prefix_len as u32).unwrap_or(0);
+        let net = u32::from(addr) & mask;
+        let code = |c: u32| c as u16;
+        Self::from_raw(unsafe {
+            vec![
+                // The source address in the IPv4 header.
+                BPF_STMT(
+                    code(libc::BPF_LD | libc::BPF_W | libc::BPF_ABS),
+                    (libc::SKF_NET_OFF + 12) as u32,
+                ),
+                BPF_STMT(code(libc::BPF_ALU | libc::BPF_AND | libc::BPF_K), mask),
+                libc::BPF_JUMP(code(libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K), net, 0, 1),
+                ret(u32::MAX),
+                ret(0),
+            ]
+        })
+    }
+
+    /// The raw instructions of the program.
+    pub fn as_raw(&self) -> &[sock_filter] {
+        &self.insns
+    }
+}
+
+/// Return `len` bytes of the packet, or drop it if zero.
+fn ret(len: u32) -> sock_filter {
+    unsafe { BPF_STMT((libc::BPF_RET | libc::BPF_K) as u16, len) }
+}
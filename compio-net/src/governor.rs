@@ -0,0 +1,217 @@
+use std::{
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use compio_runtime::time::now;
+
+use crate::{TcpListener, TcpStream};
+
+/// Balances the accepts of the runtimes sharing one listening socket.
+///
+/// When a listening socket is duplicated into several runtimes, e.g. one per
+/// thread, the kernel doesn't distribute the connections evenly: a runtime
+/// which is less busy wakes up first and accepts most of them. Register the
+/// listener of each runtime to the same governor, and the runtimes which
+/// accept much more than their share in the current window pause for a
+/// while, leaving the connections to the others. The windows and the pauses
+/// follow the [clock](compio_runtime::time::now) of the runtimes.
+///
+/// On the polling driver, a connection wakes up all the runtimes waiting on
+/// the socket, and only one of them gets it, as there are no
+/// `EPOLLEXCLUSIVE` semantics. On Linux,
+/// [`bind_reuse_port`](TcpListener::bind_reuse_port) gives each runtime its
+/// own socket and lets the kernel balance them instead.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use compio_net::{AcceptGovernor, TcpListener};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let governor = AcceptGovernor::new(Duration::from_secs(1));
+/// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+/// // Register the duplicated listeners of the other runtimes likewise.
+/// let _listener = governor.register(listener);
+/// assert_eq!(governor.members(), 1);
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct AcceptGovernor {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    window: Duration,
+    pause: Duration,
+    tolerance: f64,
+    min_accepts: u64,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    start: Instant,
+    // The accepts in the current window, indexed by the member id. `None`
+    // marks the unregistered ids.
+    accepts: Vec<Option<u64>>,
+}
+
+impl State {
+    fn rotate(&mut self, window: Duration) {
+        let now = now();
+        if now.duration_since(self.start) >= window {
+            self.start = now;
+            // Halve the counts to keep some history.
+            for count in self.accepts.iter_mut().flatten() {
+                *count /= 2;
+            }
+        }
+    }
+}
+
+impl AcceptGovernor {
+    /// Create a governor balancing the accepts in each `window`.
+    ///
+    /// By default, a runtime pauses for a tenth of the window when it
+    /// accepts more than 1.5 times its share, after at least 16 accepts in
+    /// the window.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                window,
+                pause: window / 10,
+                tolerance: 1.5,
+                min_accepts: 16,
+                state: Mutex::new(State {
+                    start: now(),
+                    accepts: vec![],
+                }),
+            }),
+        }
+    }
+
+    fn inner_mut(&mut self) -> &mut Inner {
+        Arc::get_mut(&mut self.inner).expect("the governor should not be shared yet")
+    }
+
+    /// Set how long a greedy runtime pauses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the governor has been cloned or registered.
+    pub fn pause(mut self, pause: Duration) -> Self {
+        self.inner_mut().pause = pause;
+        self
+    }
+
+    /// Set the ratio to the fair share above which a runtime is greedy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the governor has been cloned or registered.
+    pub fn tolerance(mut self, tolerance: f64) -> Self {
+        self.inner_mut().tolerance = tolerance.max(1.0);
+        self
+    }
+
+    /// Set the accepts in a window below which a runtime never pauses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the governor has been cloned or registered.
+    pub fn min_accepts(mut self, min_accepts: u64) -> Self {
+        self.inner_mut().min_accepts = min_accepts;
+        self
+    }
+
+    /// Register the listener of the current runtime.
+    pub fn register(&self, listener: TcpListener) -> GovernedListener {
+        let mut state = self.inner.state.lock().unwrap();
+        let id = match state.accepts.iter().position(Option::is_none) {
+            Some(id) => {
+                state.accepts[id] = Some(0);
+                id
+            }
+            None => {
+                state.accepts.push(Some(0));
+                state.accepts.len() - 1
+            }
+        };
+        GovernedListener {
+            listener,
+            governor: self.clone(),
+            id,
+        }
+    }
+
+    /// The number of the registered listeners.
+    pub fn members(&self) -> usize {
+        let state = self.inner.state.lock().unwrap();
+        state.accepts.iter().flatten().count()
+    }
+
+    // Returns how long the member should pause before accepting.
+    fn check(&self, id: usize) -> Option<Duration> {
+        let inner = &self.inner;
+        let mut state = inner.state.lock().unwrap();
+        state.rotate(inner.window);
+        let members = state.accepts.iter().flatten().count() as u64;
+        let total = state.accepts.iter().flatten().sum::<u64>();
+        let count = state.accepts[id].unwrap_or_default();
+        if members < 2 || count < inner.min_accepts {
+            return None;
+        }
+        let share = total as f64 / members as f64;
+        (count as f64 > share * inner.tolerance).then_some(inner.pause)
+    }
+
+    fn record(&self, id: usize) {
+        let mut state = self.inner.state.lock().unwrap();
+        state.rotate(self.inner.window);
+        if let Some(count) = &mut state.accepts[id] {
+            *count += 1;
+        }
+    }
+
+    fn unregister(&self, id: usize) {
+        self.inner.state.lock().unwrap().accepts[id] = None;
+    }
+}
+
+/// A listener registered to an [`AcceptGovernor`].
+#[derive(Debug)]
+pub struct GovernedListener {
+    listener: TcpListener,
+    governor: AcceptGovernor,
+    id: usize,
+}
+
+impl GovernedListener {
+    /// Accepts a new incoming connection, after pausing if this runtime has
+    /// accepted much more than the others.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        while let Some(pause) = self.governor.check(self.id) {
+            compio_runtime::time::sleep(pause).await;
+        }
+        let res = self.listener.accept().await;
+        if res.is_ok() {
+            self.governor.record(self.id);
+        }
+        res
+    }
+
+    /// The inner listener.
+    pub fn listener(&self) -> &TcpListener {
+        &self.listener
+    }
+}
+
+impl Drop for GovernedListener {
+    fn drop(&mut self) {
+        self.governor.unregister(self.id);
+    }
+}
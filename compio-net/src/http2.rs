@@ -0,0 +1,414 @@
+use std::io;
+
+use compio_buf::BufResult;
+use compio_io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+
+/// The connection preface sent by an HTTP/2 client, before its SETTINGS
+/// frame.
+pub const HTTP2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+
+const HEADER_LEN: usize = 9;
+const DEFAULT_MAX_FRAME_SIZE: u32 = 16_384;
+const MAX_MAX_FRAME_SIZE: u32 = (1 << 24) - 1;
+const MAX_WINDOW_SIZE: u32 = (1 << 31) - 1;
+const READ_SIZE: usize = 16 * 1024;
+
+/// An HTTP/2 frame of RFC 9113.
+///
+/// The payload is not interpreted, except by the constructors and
+/// [`Http2Settings::decode`]; the header blocks are HPACK encoded by the
+/// caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Http2Frame {
+    /// The frame type, e.g. [`Http2Frame::DATA`].
+    pub ty: u8,
+    /// The flags of the frame type, e.g. [`Http2Frame::END_STREAM`].
+    pub flags: u8,
+    /// The stream identifier, or 0 for the connection.
+    pub stream_id: u32,
+    /// The payload.
+    pub payload: Vec<u8>,
+}
+
+impl Http2Frame {
+    /// The ACK flag of SETTINGS and PING.
+    pub const ACK: u8 = 0x1;
+    /// The CONTINUATION frame type.
+    pub const CONTINUATION: u8 = 0x9;
+    /// The DATA frame type.
+    pub const DATA: u8 = 0x0;
+    /// The END_HEADERS flag of HEADERS, PUSH_PROMISE and CONTINUATION.
+    pub const END_HEADERS: u8 = 0x4;
+    /// The END_STREAM flag of DATA and HEADERS.
+    pub const END_STREAM: u8 = 0x1;
+    /// The GOAWAY frame type.
+    pub const GOAWAY: u8 = 0x7;
+    /// The HEADERS frame type.
+    pub const HEADERS: u8 = 0x1;
+    /// The PADDED flag of DATA, HEADERS and PUSH_PROMISE.
+    pub const PADDED: u8 = 0x8;
+    /// The PING frame type.
+    pub const PING: u8 = 0x6;
+    /// The PRIORITY frame type.
+    pub const PRIORITY: u8 = 0x2;
+    /// The PUSH_PROMISE frame type.
+    pub const PUSH_PROMISE: u8 = 0x5;
+    /// The RST_STREAM frame type.
+    pub const RST_STREAM: u8 = 0x3;
+    /// The SETTINGS frame type.
+    pub const SETTINGS: u8 = 0x4;
+    /// The WINDOW_UPDATE frame type.
+    pub const WINDOW_UPDATE: u8 = 0x8;
+
+    /// Create a frame. The reserved bit of `stream_id` is cleared.
+    pub fn new(ty: u8, flags: u8, stream_id: u32, payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            ty,
+            flags,
+            stream_id: stream_id & MAX_WINDOW_SIZE,
+            payload: payload.into(),
+        }
+    }
+
+    /// Create a DATA frame.
+    pub fn data(stream_id: u32, data: impl Into<Vec<u8>>, end_stream: bool) -> Self {
+        let flags = if end_stream { Self::END_STREAM } else { 0 };
+        Self::new(Self::DATA, flags, stream_id, data)
+    }
+
+    /// Create a SETTINGS frame.
+    pub fn settings(settings: &Http2Settings) -> Self {
+        Self::new(Self::SETTINGS, 0, 0, settings.encode())
+    }
+
+    /// Create a SETTINGS frame acknowledging the settings of the peer.
+    pub fn settings_ack() -> Self {
+        Self::new(Self::SETTINGS, Self::ACK, 0, vec![])
+    }
+
+    /// Create a PING frame, or its acknowledgement.
+    pub fn ping(data: [u8; 8], ack: bool) -> Self {
+        let flags = if ack { Self::ACK } else { 0 };
+        Self::new(Self::PING, flags, 0, data)
+    }
+
+    /// Create a WINDOW_UPDATE frame.
+    pub fn window_update(stream_id: u32, increment: u32) -> Self {
+        let increment = increment & MAX_WINDOW_SIZE;
+        Self::new(Self::WINDOW_UPDATE, 0, stream_id, increment.to_be_bytes())
+    }
+
+    /// Create a RST_STREAM frame with the error code.
+    pub fn rst_stream(stream_id: u32, error_code: u32) -> Self {
+        Self::new(Self::RST_STREAM, 0, stream_id, error_code.to_be_bytes())
+    }
+
+    /// Create a GOAWAY frame, with the last processed stream and the error
+    /// code.
+    pub fn goaway(last_stream_id: u32, error_code: u32) -> Self {
+        let mut payload = (last_stream_id & MAX_WINDOW_SIZE).to_be_bytes().to_vec();
+        payload.extend_from_slice(&error_code.to_be_bytes());
+        Self::new(Self::GOAWAY, 0, 0, payload)
+    }
+
+    /// Whether the flag is set.
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    fn encode(&self, buffer: &mut Vec<u8>) {
+        let len = self.payload.len() as u32;
+        buffer.extend_from_slice(&len.to_be_bytes()[1..]);
+        buffer.push(self.ty);
+        buffer.push(self.flags);
+        buffer.extend_from_slice(&self.stream_id.to_be_bytes());
+        buffer.extend_from_slice(&self.payload);
+    }
+}
+
+/// The parameters of an HTTP/2 SETTINGS frame. The unset ones are not sent,
+/// and keep their values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Http2Settings {
+    /// SETTINGS_HEADER_TABLE_SIZE.
+    pub header_table_size: Option<u32>,
+    /// SETTINGS_ENABLE_PUSH.
+    pub enable_push: Option<bool>,
+    /// SETTINGS_MAX_CONCURRENT_STREAMS.
+    pub max_concurrent_streams: Option<u32>,
+    /// SETTINGS_INITIAL_WINDOW_SIZE.
+    pub initial_window_size: Option<u32>,
+    /// SETTINGS_MAX_FRAME_SIZE.
+    pub max_frame_size: Option<u32>,
+    /// SETTINGS_MAX_HEADER_LIST_SIZE.
+    pub max_header_list_size: Option<u32>,
+}
+
+impl Http2Settings {
+    /// Decode the payload of a SETTINGS frame. The unknown parameters are
+    /// ignored, and the invalid values fail with
+    /// [`InvalidData`](io::ErrorKind::InvalidData).
+    pub fn decode(payload: &[u8]) -> io::Result<Self> {
+        if !payload.len().is_multiple_of(6) {
+            return Err(invalid("invalid length of the SETTINGS frame"));
+        }
+        let mut settings = Self::default();
+        for param in payload.chunks_exact(6) {
+            let id = u16::from_be_bytes([param[0], param[1]]);
+            let value = u32::from_be_bytes([param[2], param[3], param[4], param[5]]);
+            match id {
+                0x1 => settings.header_table_size = Some(value),
+                0x2 => {
+                    if value > 1 {
+                        return Err(invalid("invalid SETTINGS_ENABLE_PUSH"));
+                    }
+                    settings.enable_push = Some(value == 1);
+                }
+                0x3 => settings.max_concurrent_streams = Some(value),
+                0x4 => {
+                    if value > MAX_WINDOW_SIZE {
+                        return Err(invalid("invalid SETTINGS_INITIAL_WINDOW_SIZE"));
+                    }
+                    settings.initial_window_size = Some(value);
+                }
+                0x5 => {
+                    if !(DEFAULT_MAX_FRAME_SIZE..=MAX_MAX_FRAME_SIZE).contains(&value) {
+                        return Err(invalid("invalid SETTINGS_MAX_FRAME_SIZE"));
+                    }
+                    settings.max_frame_size = Some(value);
+                }
+                0x6 => settings.max_header_list_size = Some(value),
+                _ => {}
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Encode the payload of a SETTINGS frame.
+    pub fn encode(&self) -> Vec<u8> {
+        let params = [
+            (0x1, self.header_table_size),
+            (0x2, self.enable_push.map(u32::from)),
+            (0x3, self.max_concurrent_streams),
+            (0x4, self.initial_window_size),
+            (0x5, self.max_frame_size),
+            (0x6, self.max_header_list_size),
+        ];
+        let mut payload = vec![];
+        for (id, value) in params {
+            if let Some(value) = value {
+                payload.extend_from_slice(&(id as u16).to_be_bytes());
+                payload.extend_from_slice(&value.to_be_bytes());
+            }
+        }
+        payload
+    }
+}
+
+/// HTTP/2 frames read from and written to a stream, e.g. a
+/// [`TcpStream`](crate::TcpStream) or a TLS stream negotiating `h2` with
+/// ALPN.
+///
+/// It is the framing layer to build HTTP/2 or gRPC on: HPACK, the stream
+/// states and the flow control are left to the caller. The frames are read
+/// from the stream into one buffer, and written into another one until
+/// [`flush`](Http2Framed::flush), so several frames are sent with one write.
+///
+/// Cancelling [`read_frame`](Http2Framed::read_frame) keeps the data buffered
+/// before it. Cancelling [`flush`](Http2Framed::flush) may leave the frames
+/// partially written, so the later writes fail instead of corrupting the
+/// stream.
+///
+/// ```
+/// use compio_net::{Http2Frame, Http2Framed, Http2Settings};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let mut client = Http2Framed::new(vec![]);
+/// client.write_preface().unwrap();
+/// client
+///     .write_frame(&Http2Frame::settings(&Http2Settings::default()))
+///     .unwrap();
+/// client.flush().await.unwrap();
+///
+/// let bytes = client.into_inner();
+/// let mut server = Http2Framed::new(bytes.as_slice());
+/// server.read_preface().await.unwrap();
+/// let frame = server.read_frame().await.unwrap().unwrap();
+/// assert_eq!(frame.ty, Http2Frame::SETTINGS);
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct Http2Framed<S> {
+    stream: S,
+    read_buffer: Vec<u8>,
+    // The start of the unread data in `read_buffer`.
+    pos: usize,
+    // The buffer passed to the reads, so the data buffered is kept when a read
+    // is cancelled.
+    read_chunk: Vec<u8>,
+    write_buffer: Vec<u8>,
+    // Set while the write buffer is being written. It remains set if the flush
+    // is cancelled.
+    writing: bool,
+    max_frame_size: u32,
+}
+
+impl<S> Http2Framed<S> {
+    /// Wrap `stream`, reading the frames of the default maximum size.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            read_buffer: Vec::new(),
+            pos: 0,
+            read_chunk: Vec::new(),
+            write_buffer: Vec::new(),
+            writing: false,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+
+    /// The maximum size of the frame payloads read, which should be the
+    /// SETTINGS_MAX_FRAME_SIZE sent to the peer.
+    pub fn max_frame_size(&self) -> u32 {
+        self.max_frame_size
+    }
+
+    /// Set the maximum size of the frame payloads read, after the peer
+    /// acknowledges the SETTINGS_MAX_FRAME_SIZE. It is clamped to the range
+    /// allowed by the RFC.
+    pub fn set_max_frame_size(&mut self, size: u32) {
+        self.max_frame_size = size.clamp(DEFAULT_MAX_FRAME_SIZE, MAX_MAX_FRAME_SIZE);
+    }
+
+    /// Get the reference of the inner stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Get the mutable reference of the inner stream.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Get the inner stream. The buffered data is dropped.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+
+    /// Buffer the client connection preface.
+    pub fn write_preface(&mut self) -> io::Result<()> {
+        self.check_writable()?;
+        self.write_buffer.extend_from_slice(HTTP2_PREFACE);
+        Ok(())
+    }
+
+    /// Buffer `frame` to be written by [`flush`](Http2Framed::flush). It
+    /// fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the
+    /// payload is larger than the maximum frame size.
+    pub fn write_frame(&mut self, frame: &Http2Frame) -> io::Result<()> {
+        self.check_writable()?;
+        if frame.payload.len() > MAX_MAX_FRAME_SIZE as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the frame payload is too large",
+            ));
+        }
+        frame.encode(&mut self.write_buffer);
+        Ok(())
+    }
+
+    fn check_writable(&self) -> io::Result<()> {
+        if self.writing {
+            return Err(io::Error::other("a previous flush is cancelled"));
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsyncRead> Http2Framed<S> {
+    /// Read and check the client connection preface. It fails with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) for the other protocols.
+    pub async fn read_preface(&mut self) -> io::Result<()> {
+        if !self.fill(HTTP2_PREFACE.len()).await? {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let preface = &self.read_buffer[self.pos..self.pos + HTTP2_PREFACE.len()];
+        if preface != HTTP2_PREFACE {
+            return Err(invalid("invalid HTTP/2 connection preface"));
+        }
+        self.pos += HTTP2_PREFACE.len();
+        Ok(())
+    }
+
+    /// Read a frame, or `None` if the stream is closed between frames.
+    ///
+    /// A frame larger than the [maximum](Http2Framed::max_frame_size) fails
+    /// with [`InvalidData`](io::ErrorKind::InvalidData), which is a
+    /// FRAME_SIZE_ERROR of the connection.
+    pub async fn read_frame(&mut self) -> io::Result<Option<Http2Frame>> {
+        if !self.fill(HEADER_LEN).await? {
+            return Ok(None);
+        }
+        let header = &self.read_buffer[self.pos..self.pos + HEADER_LEN];
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]);
+        if len > self.max_frame_size {
+            return Err(invalid("the frame is larger than SETTINGS_MAX_FRAME_SIZE"));
+        }
+        let ty = header[3];
+        let flags = header[4];
+        let stream_id = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+        let len = len as usize;
+        if !self.fill(HEADER_LEN + len).await? {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let start = self.pos + HEADER_LEN;
+        let payload = self.read_buffer[start..start + len].to_vec();
+        self.pos = start + len;
+        Ok(Some(Http2Frame::new(ty, flags, stream_id, payload)))
+    }
+
+    // Make sure there are `len` bytes buffered. Returns `false` if the stream
+    // is closed without any data buffered.
+    async fn fill(&mut self, len: usize) -> io::Result<bool> {
+        while self.read_buffer.len() - self.pos < len {
+            // Move the partial frame to the start, so the buffer doesn't grow
+            // with the frames read.
+            self.read_buffer.drain(..self.pos);
+            self.pos = 0;
+            let mut chunk = std::mem::take(&mut self.read_chunk);
+            chunk.clear();
+            chunk.reserve((len - self.read_buffer.len()).max(READ_SIZE));
+            let BufResult(res, chunk) = self.stream.read(chunk).await;
+            self.read_buffer.extend_from_slice(&chunk);
+            self.read_chunk = chunk;
+            if res? == 0 {
+                if self.read_buffer.is_empty() {
+                    return Ok(false);
+                }
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<S: AsyncWrite> Http2Framed<S> {
+    /// Write the buffered frames, and flush the stream.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.check_writable()?;
+        if !self.write_buffer.is_empty() {
+            let buffer = std::mem::take(&mut self.write_buffer);
+            self.writing = true;
+            let BufResult(res, mut buffer) = self.stream.write_all(buffer).await;
+            self.writing = false;
+            buffer.clear();
+            self.write_buffer = buffer;
+            res?;
+        }
+        self.stream.flush().await
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
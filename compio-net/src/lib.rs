@@ -1,21 +1,77 @@
 //! Network related.
 //!
-//! Currently, TCP/UDP/Unix socket are implemented.
+//! Currently, TCP/UDP/Unix and raw sockets are implemented.
 
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![warn(missing_docs)]
 
+#[cfg(unix)]
+mod cmsg;
+mod cred;
+mod duplex;
+#[cfg(target_os = "linux")]
+mod filter;
+#[cfg(feature = "time")]
+mod governor;
+mod http2;
+#[cfg(feature = "time")]
+mod pacing;
+mod raw;
 mod resolve;
+mod send_file;
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod seqpacket;
+mod sni;
 mod socket;
 pub(crate) mod split;
 mod tcp;
 mod udp;
 mod unix;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod vsock;
 
+#[cfg(unix)]
+pub use cmsg::*;
+pub use cred::PeerCred;
+pub use duplex::*;
+#[cfg(target_os = "linux")]
+pub use filter::*;
+#[cfg(feature = "time")]
+pub use governor::*;
+pub use http2::*;
+#[cfg(feature = "time")]
+pub use pacing::*;
+pub use raw::*;
 pub use resolve::ToSocketAddrsAsync;
-pub(crate) use resolve::{each_addr, first_addr_buf};
+pub(crate) use resolve::{each_addr, first_addr_buf, race_addrs, CONNECTION_ATTEMPT_DELAY};
+pub use send_file::*;
+#[cfg(any(
+    target_os = "android",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub use seqpacket::*;
+pub use sni::*;
 pub(crate) use socket::*;
 pub use split::*;
 pub use tcp::*;
 pub use udp::*;
 pub use unix::*;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use vsock::*;
+
+/// A fd driven in the readiness mode, e.g. to integrate the libraries which
+/// own the IO of the fd. It is the [`Async`](compio_runtime::Async) of the
+/// runtime.
+#[cfg(unix)]
+pub type PollFd<T> = compio_runtime::Async<T>;
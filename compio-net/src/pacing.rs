@@ -0,0 +1,108 @@
+use std::time::{Duration, Instant};
+
+use compio_buf::{BufResult, IntoInner, IoBuf};
+use compio_io::{AsyncWrite, AsyncWriteExt};
+
+/// Spreads the writes over time at a fixed rate.
+///
+/// Large sends, like the segments of a video or the bulk replication between
+/// servers, are split into chunks of at most [`burst`](Pacer::burst) bytes,
+/// and each chunk waits on the timer until the rate allows it. The bytes not
+/// sent while the pacer is idle are not saved up, so a pacer never bursts
+/// more than one chunk.
+///
+/// On Linux, the kernel could pace a socket itself with
+/// [`TcpStream::set_max_pacing_rate`](crate::TcpStream::set_max_pacing_rate),
+/// which is finer and cheaper. A pacer works with any [`AsyncWrite`], e.g.
+/// a TLS stream, and on any platform.
+///
+/// ```
+/// use compio_io::AsyncReadExt;
+/// use compio_net::Pacer;
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let (mut tx, mut rx) = compio_net::duplex(1024);
+/// let mut pacer = Pacer::new(64 * 1024);
+/// let (res, _) = futures_util::join!(
+///     pacer.write_all(&mut tx, vec![0; 512]),
+///     rx.read_exact(Vec::with_capacity(512)),
+/// );
+/// res.unwrap();
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    rate: u64,
+    burst: usize,
+    // The time when the next chunk could be sent.
+    next: Option<Instant>,
+}
+
+impl Pacer {
+    /// Create a pacer sending `rate` bytes per second, in chunks of 10ms.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    pub fn new(rate: u64) -> Self {
+        assert!(rate > 0, "the rate should not be zero");
+        Self {
+            rate,
+            burst: (rate / 100).clamp(1, usize::MAX as u64) as usize,
+            next: None,
+        }
+    }
+
+    /// Set the largest chunk sent at once.
+    pub fn burst(mut self, burst: usize) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// The bytes sent per second.
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+
+    /// Change the bytes sent per second, from the next chunk on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    pub fn set_rate(&mut self, rate: u64) {
+        assert!(rate > 0, "the rate should not be zero");
+        self.rate = rate;
+    }
+
+    /// Wait until `len` bytes could be sent, and account for them.
+    pub async fn wait(&mut self, len: usize) {
+        let now = compio_runtime::time::now();
+        // Don't save up the idle time for a burst.
+        let next = self.next.filter(|next| *next > now).unwrap_or(now);
+        if next > now {
+            compio_runtime::time::sleep(next - now).await;
+        }
+        self.next = Some(next + Duration::from_secs_f64(len as f64 / self.rate as f64));
+    }
+
+    /// Write the entire buffer to `writer`, chunk by chunk at the rate.
+    pub async fn write_all<W: AsyncWrite, T: IoBuf>(
+        &mut self,
+        writer: &mut W,
+        mut buf: T,
+    ) -> BufResult<(), T> {
+        let len = buf.buf_len();
+        let mut pos = 0;
+        while pos < len {
+            let end = len.min(pos + self.burst);
+            self.wait(end - pos).await;
+            let BufResult(res, slice) = writer.write_all(buf.slice(pos..end)).await;
+            buf = slice.into_inner();
+            if let Err(e) = res {
+                return BufResult(Err(e), buf);
+            }
+            pos = end;
+        }
+        BufResult(Ok(()), buf)
+    }
+}
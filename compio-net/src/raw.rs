@@ -0,0 +1,208 @@
+use std::{future::Future, io};
+
+use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
+use socket2::{Domain, Protocol, SockAddr, Type};
+
+use crate::Socket;
+
+/// A raw or ICMP socket, for the protocols without a dedicated socket type,
+/// e.g. ping and traceroute.
+///
+/// It is created with [`Type::RAW`], which usually needs the privilege, or
+/// [`Type::DGRAM`] with [`Protocol::ICMPV4`] or [`Protocol::ICMPV6`], i.e. the
+/// unprivileged ping sockets on Linux, if the group is allowed by
+/// `net.ipv4.ping_group_range`, and on Apple platforms.
+///
+/// The received IPv4 packets of raw sockets start with the IP header, while
+/// the IPv6 ones and the packets of ping sockets start with the ICMP header.
+/// The ping sockets on Linux replace the identifier of the sent echo requests
+/// with the local port, and compute the checksums.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::net::{Ipv4Addr, SocketAddr};
+///
+/// use compio_net::RawSocket;
+/// use socket2::{Domain, Protocol, SockAddr, Type};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let socket = RawSocket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::ICMPV4)).unwrap();
+/// let addr = SockAddr::from(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)));
+/// // An echo request with the type 8, and the checksum computed by the
+/// // kernel.
+/// let request = vec![8, 0, 0, 0, 0, 0, 0, 1];
+/// socket.send_to(request, &addr).await.unwrap();
+/// let (_, reply) = socket.recv_from(Vec::with_capacity(64)).await.unwrap();
+/// assert_eq!(reply[0], 0);
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct RawSocket {
+    inner: Socket,
+}
+
+impl RawSocket {
+    /// Creates a new socket of the domain, type and protocol.
+    pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::new(domain, ty, protocol)?,
+        })
+    }
+
+    /// Creates a new socket of the type and protocol, bound to the local
+    /// address, e.g. to choose the source address.
+    pub fn bind(addr: &SockAddr, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::bind(addr, ty, protocol)?,
+        })
+    }
+
+    /// Connects the socket to the remote address, so that
+    /// [`send`](Self::send) and [`recv`](Self::recv) could be used, and only
+    /// the packets from it are received.
+    pub fn connect(&self, addr: &SockAddr) -> io::Result<()> {
+        self.inner.connect(addr)
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Returns the address of the peer this socket is connected to.
+    pub fn peer_addr(&self) -> io::Result<SockAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Gets the value of the `IP_TTL` option on this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Sets the value of the `IP_TTL` option on this socket, i.e. the
+    /// time-to-live of every IPv4 packet sent, e.g. the hops of traceroute.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IPV6_UNICAST_HOPS` option on this socket.
+    pub fn unicast_hops_v6(&self) -> io::Result<u32> {
+        self.inner.unicast_hops_v6()
+    }
+
+    /// Sets the value of the `IPV6_UNICAST_HOPS` option on this socket, i.e.
+    /// the hop limit of every IPv6 packet sent.
+    pub fn set_unicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        self.inner.set_unicast_hops_v6(hops)
+    }
+
+    /// Gets the value of the `IP_HDRINCL` option on this socket.
+    pub fn header_included_v4(&self) -> io::Result<bool> {
+        self.inner.header_included_v4()
+    }
+
+    /// Sets the value of the `IP_HDRINCL` option on this socket. If set, the
+    /// sent IPv4 packets of raw sockets should start with the IP header.
+    pub fn set_header_included_v4(&self, included: bool) -> io::Result<()> {
+        self.inner.set_header_included_v4(included)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method, e.g. `ICMP6_FILTER`.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
+    /// Receives a packet from the connected peer, returning the original
+    /// buffer and quantity of data received.
+    pub async fn recv<T: IoBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv(buffer).await
+    }
+
+    /// Sends a packet to the connected peer, returning the original buffer
+    /// and quantity of data sent.
+    pub async fn send<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send(buffer).await
+    }
+
+    /// Receives a packet on the socket. On success, returns the number of
+    /// bytes received and the origin.
+    pub async fn recv_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from(buffer).await
+    }
+
+    /// Receives a packet on the socket like [`recv_from`](Self::recv_from),
+    /// without removing it from the queue.
+    pub async fn peek_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SockAddr), T> {
+        self.inner.peek_from(buffer).await
+    }
+
+    /// Receives a packet on the socket into the buffers. On success, returns
+    /// the number of bytes received and the origin.
+    pub async fn recv_from_vectored<T: IoVectoredBufMut>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from_vectored(buffer).await
+    }
+
+    /// Sends a packet on the socket to the given address. On success,
+    /// returns the number of bytes sent.
+    pub async fn send_to<T: IoBuf>(&self, buffer: T, addr: &SockAddr) -> BufResult<usize, T> {
+        self.inner.send_to(buffer, addr).await
+    }
+
+    /// Sends a packet on the socket from the buffers to the given address,
+    /// e.g. the IP header and the payload. On success, returns the number of
+    /// bytes sent.
+    pub async fn send_to_vectored<T: IoVectoredBuf>(
+        &self,
+        buffer: T,
+        addr: &SockAddr,
+    ) -> BufResult<usize, T> {
+        self.inner.send_to_vectored(buffer, addr).await
+    }
+}
+
+impl_try_as_raw_fd!(RawSocket, inner);
+
+impl_attachable!(RawSocket, inner);
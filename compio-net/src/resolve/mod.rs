@@ -12,10 +12,14 @@ use std::{
     future::Future,
     io,
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
+    time::Duration,
 };
 
 use compio_buf::{buf_try, BufResult};
 use either::Either;
+#[cfg(feature = "time")]
+use futures_util::{select, FutureExt};
+use futures_util::{stream::FuturesUnordered, StreamExt};
 pub use sys::resolve_sock_addrs;
 
 /// A trait for objects which can be converted or resolved to one or more
@@ -150,6 +154,81 @@ pub async fn each_addr<T, F: Future<Output = io::Result<T>>>(
     }))
 }
 
+// The delay before starting the next connection attempt, recommended by
+// RFC 8305.
+pub const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// Interleave the address families, starting with the family of the first
+// address, as RFC 8305 section 4.
+fn interleave(addrs: impl Iterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let mut addrs = addrs.peekable();
+    let Some(first) = addrs.peek().map(SocketAddr::is_ipv6) else {
+        return vec![];
+    };
+    let (preferred, others): (Vec<_>, Vec<_>) = addrs.partition(|a| a.is_ipv6() == first);
+    let mut preferred = preferred.into_iter();
+    let mut others = others.into_iter();
+    let mut res = Vec::with_capacity(preferred.len() + others.len());
+    loop {
+        match (preferred.next(), others.next()) {
+            (None, None) => break,
+            (a, b) => res.extend(a.into_iter().chain(b)),
+        }
+    }
+    res
+}
+
+// Race the connection attempts to the addresses, as Happy Eyeballs of
+// RFC 8305. The next attempt starts after `delay`, or right after the last
+// one fails. The first success wins, and the others are cancelled by
+// dropping them.
+pub async fn race_addrs<T, F: Future<Output = io::Result<T>>>(
+    addr: impl ToSocketAddrsAsync,
+    delay: Duration,
+    f: impl Fn(SocketAddr) -> F,
+) -> io::Result<T> {
+    let mut addrs = interleave(addr.to_socket_addrs_async().await?).into_iter();
+    let mut attempts = FuturesUnordered::new();
+    let mut next = addrs.next();
+    let mut last_err = None;
+    loop {
+        if let Some(addr) = next.take() {
+            attempts.push(f(addr));
+        }
+        if attempts.is_empty() {
+            break;
+        }
+        // Without the timers, the next attempt only starts after the last one
+        // fails.
+        #[cfg(not(feature = "time"))]
+        let res = {
+            let _ = delay;
+            attempts.next().await
+        };
+        #[cfg(feature = "time")]
+        let res = if addrs.len() > 0 {
+            select! {
+                res = attempts.next() => res,
+                _ = compio_runtime::time::sleep(delay).fuse() => None,
+            }
+        } else {
+            attempts.next().await
+        };
+        match res {
+            Some(Ok(res)) => return Ok(res),
+            Some(Err(e)) => last_err = Some(e),
+            None => {}
+        }
+        next = addrs.next();
+    }
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any addresses",
+        )
+    }))
+}
+
 pub async fn first_addr_buf<T, B, F: Future<Output = BufResult<T, B>>>(
     addr: impl ToSocketAddrsAsync,
     buffer: B,
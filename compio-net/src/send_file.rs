@@ -0,0 +1,114 @@
+use std::io;
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+use compio_buf::{BufResult, IntoInner, IoBuf};
+use compio_runtime::{RawFd, Runtime, TryAsRawFd};
+
+/// Sends `len` bytes of `file` at `offset` to the connected `socket`,
+/// returning the bytes sent, which are fewer if the file ends first.
+///
+/// The file is sent without copying it through the userspace, with `splice`
+/// on Linux and `TransmitFile` on Windows. Other platforms fall back to
+/// reading the file into a buffer. On Linux, `socket` could also be a pipe.
+///
+/// It is the implementation of [`TcpStream::send_file`] and
+/// `compio_fs::File::send_to_socket`.
+///
+/// [`TcpStream::send_file`]: crate::TcpStream::send_file
+pub async fn send_file(
+    file: &impl TryAsRawFd,
+    socket: &impl TryAsRawFd,
+    offset: u64,
+    len: usize,
+) -> io::Result<usize> {
+    send_file_raw(file.try_as_raw_fd()?, socket.try_as_raw_fd()?, offset, len).await
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+async fn send_file_raw(file: RawFd, socket: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    use compio_driver::{op::Splice, syscall};
+
+    // The file is spliced into a pipe, and then the pipe into the socket.
+    let mut fds = [0; 2];
+    syscall!(libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC))?;
+    let (rx, tx) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+    let mut sent = 0;
+    while sent < len {
+        let op = Splice::new(
+            file,
+            Some(offset + sent as u64),
+            tx.as_raw_fd(),
+            None,
+            len - sent,
+        );
+        let filled = Runtime::current().submit(op).await.0?;
+        if filled == 0 {
+            break;
+        }
+        let mut drained = 0;
+        while drained < filled {
+            let op = Splice::new(rx.as_raw_fd(), None, socket, None, filled - drained);
+            match Runtime::current().submit(op).await.0? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => drained += n,
+            }
+        }
+        sent += filled;
+    }
+    Ok(sent)
+}
+
+#[cfg(windows)]
+async fn send_file_raw(file: RawFd, socket: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use compio_driver::op::TransmitFile;
+
+    let mut sent = 0;
+    while sent < len {
+        // `TransmitFile` sends at most `i32::MAX - 1` bytes at once.
+        let chunk = (len - sent).min(i32::MAX as usize - 1);
+        let op = TransmitFile::new(socket, file, offset + sent as u64, chunk as _);
+        match Runtime::current().submit(op).await.0? {
+            0 => break,
+            n => sent += n,
+        }
+    }
+    Ok(sent)
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+async fn send_file_raw(file: RawFd, socket: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use compio_driver::op::{BufResultExt, ReadAt, Send};
+
+    // There is no zero-copy op, so the file is read into a buffer.
+    let mut buffer = Vec::with_capacity(len.min(65536));
+    let mut sent = 0;
+    while sent < len {
+        buffer.clear();
+        let chunk = (len - sent).min(buffer.capacity());
+        let op = ReadAt::new(file, offset + sent as u64, buffer.slice(..chunk));
+        let BufResult(res, slice) = Runtime::current()
+            .submit(op)
+            .await
+            .into_inner()
+            .map_advanced();
+        buffer = slice.into_inner();
+        let filled = res?;
+        if filled == 0 {
+            break;
+        }
+        let mut written = 0;
+        while written < filled {
+            let op = Send::new(socket, buffer.slice(written..filled));
+            let BufResult(res, slice) = Runtime::current().submit(op).await.into_inner();
+            buffer = slice.into_inner();
+            match res? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => written += n,
+            }
+        }
+        sent += filled;
+    }
+    Ok(sent)
+}
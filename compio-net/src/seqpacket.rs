@@ -0,0 +1,190 @@
+use std::{future::Future, io, path::Path};
+
+use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
+use socket2::{Domain, SockAddr, Type};
+
+use crate::Socket;
+
+/// A Unix sequenced-packet socket server, listening for connections.
+///
+/// The connections keep the message boundaries like datagrams, and deliver
+/// them reliably and in order like streams.
+///
+/// # Examples
+///
+/// ```
+/// use compio_net::{UnixSeqpacket, UnixSeqpacketListener};
+/// use tempfile::tempdir;
+///
+/// let dir = tempdir().unwrap();
+/// let sock_file = dir.path().join("seqpacket.sock");
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async move {
+/// let listener = UnixSeqpacketListener::bind(&sock_file).unwrap();
+///
+/// let tx = UnixSeqpacket::connect(&sock_file).await.unwrap();
+/// let (rx, _) = listener.accept().await.unwrap();
+///
+/// tx.send("hello").await.unwrap();
+/// tx.send("world").await.unwrap();
+///
+/// let (_, buf) = rx.recv(Vec::with_capacity(16)).await.unwrap();
+/// assert_eq!(buf, b"hello");
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct UnixSeqpacketListener {
+    inner: Socket,
+}
+
+impl UnixSeqpacketListener {
+    /// Creates a new listener bound to the specified file path. The file
+    /// path cannot yet exist.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::bind_addr(&SockAddr::unix(path)?)
+    }
+
+    /// Creates a new listener bound to the specified address.
+    pub fn bind_addr(addr: &SockAddr) -> io::Result<Self> {
+        let socket = Socket::bind(addr, Type::SEQPACKET, None)?;
+        socket.listen(1024)?;
+        Ok(Self { inner: socket })
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub async fn accept(&self) -> io::Result<(UnixSeqpacket, SockAddr)> {
+        let (socket, addr) = self.inner.accept().await?;
+        Ok((UnixSeqpacket { inner: socket }, addr))
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Returns the local address that this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+}
+
+impl_try_as_raw_fd!(UnixSeqpacketListener, inner);
+
+impl_attachable!(UnixSeqpacketListener, inner);
+
+/// A connected Unix sequenced-packet socket.
+///
+/// Each [`send`](UnixSeqpacket::send) is received by one
+/// [`recv`](UnixSeqpacket::recv) as a whole message. If the buffer is smaller
+/// than the message, the remaining bytes are discarded.
+#[derive(Debug)]
+pub struct UnixSeqpacket {
+    inner: Socket,
+}
+
+impl UnixSeqpacket {
+    /// Opens a connection to the specified file path.
+    pub async fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::connect_addr(&SockAddr::unix(path)?).await
+    }
+
+    /// Opens a connection to the specified address. It waits for the room in
+    /// the backlog of the listener if it is full.
+    pub async fn connect_addr(addr: &SockAddr) -> io::Result<Self> {
+        let socket = Socket::new(Domain::UNIX, Type::SEQPACKET, None)?;
+        socket.connect_async(addr).await?;
+        Ok(Self { inner: socket })
+    }
+
+    /// Creates an unnamed pair of connected sockets.
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (first, second) = Socket::pair(Domain::UNIX, Type::SEQPACKET, None)?;
+        Ok((Self { inner: first }, Self { inner: second }))
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Returns the address of the remote peer of this connection.
+    pub fn peer_addr(&self) -> io::Result<SockAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Returns the address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Receives a message into the buffer, returning the original buffer and
+    /// the length of the message. It returns `0` when the peer has shut down.
+    pub async fn recv<T: IoBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv(buffer).await
+    }
+
+    /// Receives a message into the buffers.
+    pub async fn recv_vectored<T: IoVectoredBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv_vectored(buffer).await
+    }
+
+    /// Sends the buffer as one message, returning the original buffer and
+    /// quantity of data sent.
+    pub async fn send<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send(buffer).await
+    }
+
+    /// Sends the buffers as one message.
+    pub async fn send_vectored<T: IoVectoredBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send_vectored(buffer).await
+    }
+
+    /// Sends a message with the `MSG_*` flags. The flags are passed to the
+    /// system call as is.
+    pub async fn send_with_flags<T: IoBuf>(&self, buffer: T, flags: i32) -> BufResult<usize, T> {
+        self.inner.send_with_flags(buffer, flags).await
+    }
+
+    /// Shuts down the write half of the connection, so that the peer receives
+    /// an empty message.
+    pub async fn shutdown(&self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+impl_try_as_raw_fd!(UnixSeqpacket, inner);
+
+impl_attachable!(UnixSeqpacket, inner);
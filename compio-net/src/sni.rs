@@ -0,0 +1,272 @@
+use std::{collections::HashMap, io};
+
+use compio_buf::{BufResult, IntoInner, IoBuf};
+use compio_io::AsyncRead;
+
+// The limit of the ClientHello read, which is usually one record of less than
+// 2KiB.
+const MAX_HELLO_LEN: usize = 64 * 1024;
+const READ_SIZE: usize = 4096;
+
+/// Routes the accepted TLS connections by the server name indication (SNI)
+/// of their ClientHello, without terminating TLS.
+///
+/// The ClientHello is [read](read_sni) into a buffer returned to the caller,
+/// so the stream could be proxied as is to another server, after sending the
+/// buffered bytes. The routes match the server names case-insensitively, and
+/// a route like `*.example.com` matches one label under `example.com`. The
+/// exact routes are preferred to the wildcard ones, and the
+/// [fallback](SniRouter::fallback) applies to the other names, and the
+/// ClientHello without SNI.
+///
+/// Bound the read with a timeout, so that a client not sending the
+/// ClientHello doesn't hold the connection forever.
+///
+/// ```
+/// use compio_net::{SniRouter, TcpListener};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let router = SniRouter::new()
+///     .route("api.example.com", "127.0.0.1:8001")
+///     .route("*.example.com", "127.0.0.1:8002")
+///     .fallback("127.0.0.1:8000");
+/// assert_eq!(router.get(Some("API.example.com")), Some(&"127.0.0.1:8001"));
+/// assert_eq!(router.get(Some("www.example.com")), Some(&"127.0.0.1:8002"));
+/// assert_eq!(router.get(None), Some(&"127.0.0.1:8000"));
+///
+/// # if false {
+/// let listener = TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// loop {
+///     let (mut stream, _) = listener.accept().await.unwrap();
+///     let (backend, hello) = router.select(&mut stream, vec![]).await.unwrap();
+///     if let Some(backend) = backend {
+///         // Send `hello` to `backend`, and proxy the rest of `stream`.
+///     }
+/// }
+/// # }
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct SniRouter<T> {
+    exact: HashMap<String, T>,
+    // Keyed by the parent domain of the wildcard.
+    wildcard: HashMap<String, T>,
+    fallback: Option<T>,
+}
+
+impl<T> SniRouter<T> {
+    /// Create a router without routes.
+    pub fn new() -> Self {
+        Self {
+            exact: HashMap::new(),
+            wildcard: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Route the server `name`, or the subdomains with a name like
+    /// `*.example.com`, to `target`. It replaces the previous target of the
+    /// same name.
+    pub fn route(mut self, name: &str, target: T) -> Self {
+        let name = normalize(name);
+        match name.strip_prefix("*.") {
+            Some(parent) => self.wildcard.insert(parent.to_string(), target),
+            None => self.exact.insert(name, target),
+        };
+        self
+    }
+
+    /// Route the other server names, and the connections without SNI, to
+    /// `target`.
+    pub fn fallback(mut self, target: T) -> Self {
+        self.fallback = Some(target);
+        self
+    }
+
+    /// Get the target of `server_name`.
+    pub fn get(&self, server_name: Option<&str>) -> Option<&T> {
+        server_name
+            .and_then(|name| {
+                let name = normalize(name);
+                self.exact.get(&name).or_else(|| {
+                    let (_, parent) = name.split_once('.')?;
+                    self.wildcard.get(parent)
+                })
+            })
+            .or(self.fallback.as_ref())
+    }
+
+    /// Read the ClientHello of `stream` with [`read_sni`], and get the target
+    /// of its server name. The bytes read are returned with `buffer`.
+    pub async fn select<S: AsyncRead>(
+        &self,
+        stream: &mut S,
+        buffer: Vec<u8>,
+    ) -> BufResult<Option<&T>, Vec<u8>> {
+        read_sni(stream, buffer)
+            .await
+            .map_res(|server_name| self.get(server_name.as_deref()))
+    }
+}
+
+impl<T> Default for SniRouter<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn normalize(name: &str) -> String {
+    name.trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// Read the ClientHello of a TLS connection into `buffer`, and get its server
+/// name, or `None` if it doesn't send SNI.
+///
+/// The data is appended to `buffer`, which could start with the bytes already
+/// read from `stream`. The buffer is returned with all bytes read, which are
+/// the ClientHello and maybe more, so the caller should handle them before
+/// the rest of the stream. It fails with
+/// [`InvalidData`](io::ErrorKind::InvalidData) if the connection doesn't
+/// start with a ClientHello, and with
+/// [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) if the peer shuts down
+/// before sending all of it. It waits for the data without a limit, so bound
+/// it with a timeout.
+pub async fn read_sni<S: AsyncRead>(
+    stream: &mut S,
+    mut buffer: Vec<u8>,
+) -> BufResult<Option<String>, Vec<u8>> {
+    loop {
+        match parse_client_hello(&buffer) {
+            Ok(ClientHello::Complete(server_name)) => return BufResult(Ok(server_name), buffer),
+            Ok(ClientHello::Partial) => {}
+            Err(e) => return BufResult(Err(e), buffer),
+        }
+        let len = buffer.len();
+        if len >= MAX_HELLO_LEN {
+            return BufResult(Err(invalid("the ClientHello is too large")), buffer);
+        }
+        buffer.reserve(READ_SIZE);
+        let BufResult(res, slice) = stream.read(buffer.slice(len..)).await;
+        buffer = slice.into_inner();
+        match res {
+            Ok(0) => return BufResult(Err(io::ErrorKind::UnexpectedEof.into()), buffer),
+            Ok(_) => {}
+            Err(e) => return BufResult(Err(e), buffer),
+        }
+    }
+}
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn malformed() -> io::Error {
+    invalid("malformed TLS ClientHello")
+}
+
+enum ClientHello {
+    // More data is needed.
+    Partial,
+    // The server name, if any.
+    Complete(Option<String>),
+}
+
+fn parse_client_hello(data: &[u8]) -> io::Result<ClientHello> {
+    const CONTENT_HANDSHAKE: u8 = 22;
+    const HANDSHAKE_CLIENT_HELLO: u8 = 1;
+
+    // Join the handshake fragments in the records.
+    let mut handshake = Vec::new();
+    let mut records = data;
+    let body = loop {
+        if records.len() < 5 {
+            return Ok(ClientHello::Partial);
+        }
+        if records[0] != CONTENT_HANDSHAKE || records[1] != 3 {
+            return Err(invalid("not a TLS handshake"));
+        }
+        let len = u16::from_be_bytes([records[3], records[4]]) as usize;
+        let Some(fragment) = records.get(5..5 + len) else {
+            return Ok(ClientHello::Partial);
+        };
+        handshake.extend_from_slice(fragment);
+        records = &records[5 + len..];
+        if handshake.len() >= 4 {
+            if handshake[0] != HANDSHAKE_CLIENT_HELLO {
+                return Err(invalid("not a TLS ClientHello"));
+            }
+            let len = u32::from_be_bytes([0, handshake[1], handshake[2], handshake[3]]) as usize;
+            if let Some(body) = handshake.get(4..4 + len) {
+                break body;
+            }
+        }
+    };
+    parse_server_name(body).map(ClientHello::Complete)
+}
+
+fn parse_server_name(body: &[u8]) -> io::Result<Option<String>> {
+    const EXTENSION_SERVER_NAME: u16 = 0;
+    const NAME_TYPE_HOST_NAME: u8 = 0;
+
+    let mut reader = Reader(body);
+    // The legacy version and the random.
+    reader.take(34)?;
+    // The session id, the cipher suites and the compression methods.
+    reader.take_u8_prefixed()?;
+    reader.take_u16_prefixed()?;
+    reader.take_u8_prefixed()?;
+    if reader.0.is_empty() {
+        // No extensions.
+        return Ok(None);
+    }
+    let mut extensions = Reader(reader.take_u16_prefixed()?);
+    while !extensions.0.is_empty() {
+        let ty = extensions.u16()?;
+        let data = extensions.take_u16_prefixed()?;
+        if ty != EXTENSION_SERVER_NAME {
+            continue;
+        }
+        let mut names = Reader(Reader(data).take_u16_prefixed()?);
+        while !names.0.is_empty() {
+            let name_type = names.u8()?;
+            let name = names.take_u16_prefixed()?;
+            if name_type == NAME_TYPE_HOST_NAME {
+                let name = std::str::from_utf8(name).map_err(|_| malformed())?;
+                return Ok(Some(name.to_string()));
+            }
+        }
+        return Ok(None);
+    }
+    Ok(None)
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(malformed());
+        }
+        let (data, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(data)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        self.take(2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn take_u8_prefixed(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u8()? as usize;
+        self.take(len)
+    }
+
+    fn take_u16_prefixed(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.u16()? as usize;
+        self.take(len)
+    }
+}
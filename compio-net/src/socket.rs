@@ -1,30 +1,202 @@
-use std::{future::Future, io, mem::ManuallyDrop};
+#[cfg(unix)]
+use std::cell::Cell;
+use std::{
+    future::Future,
+    io,
+    mem::ManuallyDrop,
+    net::{Ipv4Addr, Ipv6Addr},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use compio_buf::{buf_try, BufResult, IntoInner, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
-use compio_driver::op::{
-    Accept, BufResultExt, CloseSocket, Connect, Recv, RecvFrom, RecvFromVectored, RecvResultExt,
-    RecvVectored, Send, SendTo, SendToVectored, SendVectored, ShutdownSocket,
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+use compio_driver::op::ConnectWithData;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use compio_driver::op::{Interest, PollOnce, RecvMmsg, SendMmsg};
+#[cfg(unix)]
+use compio_driver::op::{RecvMsg, SendZc};
+use compio_driver::{
+    op::{
+        Accept, BufResultExt, CloseSocket, Connect, Recv, RecvFrom, RecvFromVectored,
+        RecvResultExt, RecvVectored, Send, SendMsg, SendTo, SendToVectored, SendVectored,
+        ShutdownSocket,
+    },
+    OpCode,
 };
+#[cfg(feature = "time")]
+use compio_runtime::time::Deadline;
 use compio_runtime::{
-    impl_attachable, impl_try_as_raw_fd, Attacher, Runtime, TryAsRawFd, TryClone,
+    impl_attachable, Attacher, BorrowedBuffer, BufferPool, FromRawFd, IntoRawFd, RawFd, Runtime,
+    TryAsRawFd, TryClone,
 };
+#[cfg(feature = "time")]
+use futures_util::{future::Either, FutureExt};
+use futures_util::{stream, Stream};
+#[cfg(unix)]
+use futures_util::{stream::LocalBoxStream, StreamExt};
 use socket2::{Domain, Protocol, SockAddr, Socket as Socket2, Type};
 
+use crate::TcpKeepalive;
+
+#[cfg(unix)]
+const MSG_PEEK: i32 = libc::MSG_PEEK;
+#[cfg(windows)]
+const MSG_PEEK: i32 = windows_sys::Win32::Networking::WinSock::MSG_PEEK as _;
+
+#[cfg(unix)]
+const ENOBUFS: i32 = libc::ENOBUFS;
+#[cfg(windows)]
+const ENOBUFS: i32 = windows_sys::Win32::Networking::WinSock::WSAENOBUFS;
+
+// Run a connection until `timeout` elapses. The pending operations are
+// cancelled when the future is dropped.
+#[cfg(feature = "time")]
+pub async fn connect_timeout<T>(
+    timeout: Duration,
+    future: impl Future<Output = io::Result<T>>,
+) -> io::Result<T> {
+    if timeout.is_zero() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cannot set a 0 duration timeout",
+        ));
+    }
+    compio_runtime::time::timeout(timeout, future)
+        .await
+        .unwrap_or_else(|_| {
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "connection timed out",
+            ))
+        })
+}
+
+// Submit an operation, interrupted when the deadline of the current scope
+// elapses.
+fn submit<T: OpCode + 'static>(op: T) -> impl Future<Output = BufResult<usize, T>> {
+    let runtime = Runtime::current();
+    #[cfg(not(feature = "time"))]
+    return runtime.submit(op);
+    #[cfg(feature = "time")]
+    match Deadline::current() {
+        Some(deadline) => Either::Left(runtime.submit_until(op, deadline.instant())),
+        None => Either::Right(runtime.submit(op)),
+    }
+}
+
+// The flags are unknown with a deadline, as if the driver doesn't report them.
+fn submit_with_flags<T: OpCode + 'static>(
+    op: T,
+) -> impl Future<Output = (BufResult<usize, T>, u32)> {
+    let runtime = Runtime::current();
+    #[cfg(not(feature = "time"))]
+    return runtime.submit_with_flags(op);
+    #[cfg(feature = "time")]
+    match Deadline::current() {
+        Some(deadline) => Either::Left(
+            runtime
+                .submit_until(op, deadline.instant())
+                .map(|res| (res, 0)),
+        ),
+        None => Either::Right(runtime.submit_with_flags(op)),
+    }
+}
+
+#[cfg(unix)]
+fn submit_boxed<T: OpCode + 'static>(op: Box<T>) -> impl Future<Output = BufResult<usize, Box<T>>> {
+    let runtime = Runtime::current();
+    #[cfg(not(feature = "time"))]
+    return runtime.submit_boxed(op);
+    #[cfg(feature = "time")]
+    match Deadline::current() {
+        Some(deadline) => Either::Left(runtime.submit_boxed_until(op, deadline.instant())),
+        None => Either::Right(runtime.submit_boxed(op)),
+    }
+}
+
+#[cfg(any(
+    windows,
+    target_os = "android",
+    target_os = "freebsd",
+    target_os = "linux",
+    target_vendor = "apple"
+))]
+fn tcp_fastopen_opt() -> (i32, i32) {
+    #[cfg(unix)]
+    {
+        (libc::IPPROTO_TCP, libc::TCP_FASTOPEN)
+    }
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::Networking::WinSock::{IPPROTO_TCP, TCP_FASTOPEN};
+        (IPPROTO_TCP as _, TCP_FASTOPEN as _)
+    }
+}
+
 #[derive(Debug)]
 pub struct Socket {
     socket: Attacher<Socket2>,
+    reading: Exclusive,
+    writing: Exclusive,
+    corked: AtomicBool,
+    #[cfg(unix)]
+    accept_op: OpCache<Accept>,
 }
 
 impl Socket {
     pub fn from_socket2(socket: Socket2) -> Self {
         Self {
             socket: Attacher::new(socket),
+            reading: Exclusive::default(),
+            writing: Exclusive::default(),
+            corked: AtomicBool::new(false),
+            #[cfg(unix)]
+            accept_op: OpCache::default(),
         }
     }
 
     pub fn try_clone(&self) -> io::Result<Self> {
         let socket = self.socket.try_clone()?;
-        Ok(Self { socket })
+        Ok(Self {
+            socket,
+            reading: Exclusive::default(),
+            writing: Exclusive::default(),
+            corked: AtomicBool::new(self.corked.load(Ordering::Relaxed)),
+            #[cfg(unix)]
+            accept_op: OpCache::default(),
+        })
+    }
+
+    /// Wrap a socket created by the standard library or passed by another
+    /// process, setting the nonblocking mode as [`Socket::new`] does. It is
+    /// attached to the driver when first used.
+    pub fn from_std(socket: impl Into<Socket2>) -> io::Result<Self> {
+        let socket = socket.into();
+        if cfg!(unix) {
+            socket.set_nonblocking(true)?;
+        }
+        Ok(Self::from_socket2(socket))
+    }
+
+    /// Get the inner socket in blocking mode, as the standard library
+    /// creates it.
+    pub fn into_std<T: From<Socket2>>(self) -> io::Result<T> {
+        let socket = self.socket.into_inner();
+        socket.set_nonblocking(false)?;
+        Ok(socket.into())
+    }
+
+    /// Mark a read of a stream in flight. In debug builds, it panics if
+    /// another read is in flight.
+    pub fn read_guard(&self) -> ExclusiveGuard<'_> {
+        self.reading.enter("reads")
+    }
+
+    /// Mark a write of a stream in flight. In debug builds, it panics if
+    /// another write is in flight.
+    pub fn write_guard(&self) -> ExclusiveGuard<'_> {
+        self.writing.enter("writes")
     }
 
     pub fn peer_addr(&self) -> io::Result<SockAddr> {
@@ -35,28 +207,670 @@ impl Socket {
         unsafe { self.socket.get_unchecked() }.local_addr()
     }
 
+    pub fn oob_inline(&self) -> io::Result<bool> {
+        unsafe { self.socket.get_unchecked() }.out_of_band_inline()
+    }
+
+    pub fn set_oob_inline(&self, oob_inline: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_out_of_band_inline(oob_inline)
+    }
+
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        unsafe { self.socket.get_unchecked() }.linger()
+    }
+
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_linger(linger)
+    }
+
+    pub fn nodelay(&self) -> io::Result<bool> {
+        unsafe { self.socket.get_unchecked() }.nodelay()
+    }
+
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_nodelay(nodelay)
+    }
+
+    pub fn tcp_keepalive(&self) -> io::Result<Option<TcpKeepalive>> {
+        let socket = unsafe { self.socket.get_unchecked() };
+        if !socket.keepalive()? {
+            return Ok(None);
+        }
+        #[allow(unused_mut)]
+        let mut keepalive = TcpKeepalive::new();
+        #[cfg(not(target_os = "openbsd"))]
+        {
+            keepalive = keepalive.with_time(socket.keepalive_time()?);
+        }
+        #[cfg(not(any(target_os = "openbsd", windows)))]
+        {
+            keepalive = keepalive
+                .with_interval(socket.keepalive_interval()?)
+                .with_retries(socket.keepalive_retries()?);
+        }
+        Ok(Some(keepalive))
+    }
+
+    pub fn set_tcp_keepalive(&self, keepalive: Option<&TcpKeepalive>) -> io::Result<()> {
+        let socket = unsafe { self.socket.get_unchecked() };
+        match keepalive {
+            Some(keepalive) => socket.set_tcp_keepalive(&keepalive.to_socket2()),
+            None => socket.set_keepalive(false),
+        }
+    }
+
+    pub fn ttl(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.ttl()
+    }
+
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_ttl(ttl)
+    }
+
+    pub fn unicast_hops_v6(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.unicast_hops_v6()
+    }
+
+    pub fn set_unicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_unicast_hops_v6(hops)
+    }
+
+    pub fn header_included_v4(&self) -> io::Result<bool> {
+        unsafe { self.socket.get_unchecked() }.header_included_v4()
+    }
+
+    pub fn set_header_included_v4(&self, included: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_header_included_v4(included)
+    }
+
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        unsafe { self.socket.get_unchecked() }.recv_buffer_size()
+    }
+
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_recv_buffer_size(size)
+    }
+
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        unsafe { self.socket.get_unchecked() }.send_buffer_size()
+    }
+
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_send_buffer_size(size)
+    }
+
+    pub fn cork(&self) -> bool {
+        self.corked.load(Ordering::Relaxed)
+    }
+
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        self.set_cork_raw(cork)?;
+        self.corked.store(cork, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Send the partial frames queued by cork, and keep corking.
+    pub fn push_corked(&self) -> io::Result<()> {
+        if self.cork() {
+            self.set_cork_raw(false)?;
+            self.set_cork_raw(true)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    fn set_cork_raw(&self, cork: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_cork(cork)
+    }
+
+    #[cfg(any(
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "openbsd"
+    ))]
+    fn set_cork_raw(&self, cork: bool) -> io::Result<()> {
+        unsafe { self.set_opt(libc::IPPROTO_TCP, libc::TCP_NOPUSH, &(cork as libc::c_int)) }
+    }
+
+    #[cfg(not(any(
+        target_os = "android",
+        target_os = "fuchsia",
+        target_os = "linux",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "tvos",
+        target_os = "watchos",
+        target_os = "freebsd",
+        target_os = "dragonfly",
+        target_os = "openbsd"
+    )))]
+    fn set_cork_raw(&self, _cork: bool) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "cork is not supported on this platform",
+        ))
+    }
+
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn tos(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.tos()
+    }
+
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_tos(tos)
+    }
+
+    #[cfg(unix)]
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        let tclass: libc::c_int = unsafe { self.get_opt(libc::IPPROTO_IPV6, libc::IPV6_TCLASS) }?;
+        Ok(tclass as u32)
+    }
+
+    #[cfg(unix)]
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        unsafe {
+            self.set_opt(
+                libc::IPPROTO_IPV6,
+                libc::IPV6_TCLASS,
+                &(tclass as libc::c_int),
+            )
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn recv_tos(&self) -> io::Result<bool> {
+        let recv: libc::c_int = unsafe { self.get_opt(libc::IPPROTO_IP, libc::IP_RECVTOS) }?;
+        Ok(recv != 0)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        let passcred: libc::c_int = unsafe { self.get_opt(libc::SOL_SOCKET, libc::SO_PASSCRED) }?;
+        Ok(passcred != 0)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        unsafe {
+            self.set_opt(
+                libc::SOL_SOCKET,
+                libc::SO_PASSCRED,
+                &(passcred as libc::c_int),
+            )
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn set_recv_tos(&self, recv: bool) -> io::Result<()> {
+        unsafe { self.set_opt(libc::IPPROTO_IP, libc::IP_RECVTOS, &(recv as libc::c_int)) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn recv_tclass_v6(&self) -> io::Result<bool> {
+        let recv: libc::c_int = unsafe { self.get_opt(libc::IPPROTO_IPV6, libc::IPV6_RECVTCLASS) }?;
+        Ok(recv != 0)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn set_recv_tclass_v6(&self, recv: bool) -> io::Result<()> {
+        unsafe {
+            self.set_opt(
+                libc::IPPROTO_IPV6,
+                libc::IPV6_RECVTCLASS,
+                &(recv as libc::c_int),
+            )
+        }
+    }
+
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.join_multicast_v4(multiaddr, interface)
+    }
+
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.leave_multicast_v4(multiaddr, interface)
+    }
+
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.join_multicast_v6(multiaddr, interface)
+    }
+
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.leave_multicast_v6(multiaddr, interface)
+    }
+
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.multicast_ttl_v4()
+    }
+
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_ttl_v4(ttl)
+    }
+
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.multicast_hops_v6()
+    }
+
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_hops_v6(hops)
+    }
+
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        unsafe { self.socket.get_unchecked() }.multicast_loop_v4()
+    }
+
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_loop_v4(multicast_loop)
+    }
+
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        unsafe { self.socket.get_unchecked() }.multicast_loop_v6()
+    }
+
+    pub fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_loop_v6(multicast_loop)
+    }
+
+    pub fn multicast_if_v4(&self) -> io::Result<Ipv4Addr> {
+        unsafe { self.socket.get_unchecked() }.multicast_if_v4()
+    }
+
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_if_v4(interface)
+    }
+
+    pub fn multicast_if_v6(&self) -> io::Result<u32> {
+        unsafe { self.socket.get_unchecked() }.multicast_if_v6()
+    }
+
+    pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_multicast_if_v6(interface)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn tcp_info(&self) -> io::Result<libc::tcp_info> {
+        unsafe { self.get_opt(libc::IPPROTO_TCP, libc::TCP_INFO) }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn defer_accept(&self) -> io::Result<Option<Duration>> {
+        let secs: libc::c_int = unsafe { self.get_opt(libc::IPPROTO_TCP, libc::TCP_DEFER_ACCEPT) }?;
+        Ok((secs > 0).then(|| Duration::from_secs(secs as _)))
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_defer_accept(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let secs = timeout.map_or(0, |t| t.as_secs().clamp(1, libc::c_int::MAX as _));
+        unsafe {
+            self.set_opt(
+                libc::IPPROTO_TCP,
+                libc::TCP_DEFER_ACCEPT,
+                &(secs as libc::c_int),
+            )
+        }
+    }
+
+    #[cfg(any(
+        windows,
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple"
+    ))]
+    pub fn fastopen(&self) -> io::Result<u32> {
+        let (level, name) = tcp_fastopen_opt();
+        let queue: u32 = unsafe { self.get_opt(level, name) }?;
+        Ok(queue)
+    }
+
+    #[cfg(any(
+        windows,
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple"
+    ))]
+    pub fn set_fastopen(&self, queue: u32) -> io::Result<()> {
+        let (level, name) = tcp_fastopen_opt();
+        // Only Linux takes the length of the queue, and the others a boolean.
+        let value = if cfg!(any(target_os = "linux", target_os = "android")) {
+            queue
+        } else {
+            (queue > 0) as u32
+        };
+        unsafe { self.set_opt(level, name, &value) }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        unsafe { self.socket.get_unchecked() }.cpu_affinity()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_cpu_affinity(cpu)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn device(&self) -> io::Result<Option<String>> {
+        let socket = unsafe { self.socket.get_unchecked() };
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            Ok(socket
+                .device()?
+                .map(|name| String::from_utf8_lossy(&name).into_owned()))
+        }
+        #[cfg(target_vendor = "apple")]
+        {
+            let index = if self.local_addr()?.is_ipv6() {
+                socket.device_index_v6()?
+            } else {
+                socket.device_index_v4()?
+            };
+            let Some(index) = index else {
+                return Ok(None);
+            };
+            let mut name = [0u8; libc::IF_NAMESIZE];
+            let ptr = unsafe { libc::if_indextoname(index.get(), name.as_mut_ptr().cast()) };
+            if ptr.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let name = std::ffi::CStr::from_bytes_until_nul(&name).map_err(io::Error::other)?;
+            Ok(Some(name.to_string_lossy().into_owned()))
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn bind_device(&self, interface: Option<&str>) -> io::Result<()> {
+        let socket = unsafe { self.socket.get_unchecked() };
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        {
+            socket.bind_device(interface.map(str::as_bytes))
+        }
+        #[cfg(target_vendor = "apple")]
+        {
+            let index = interface
+                .map(|name| {
+                    let name = std::ffi::CString::new(name)?;
+                    let index = unsafe { libc::if_nametoindex(name.as_ptr()) };
+                    std::num::NonZeroU32::new(index).ok_or_else(io::Error::last_os_error)
+                })
+                .transpose()?;
+            if self.local_addr()?.is_ipv6() {
+                socket.bind_device_by_index_v6(index)
+            } else {
+                socket.bind_device_by_index_v4(index)
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_filter(&self, filters: &[libc::sock_filter]) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.attach_filter(filters)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn detach_filter(&self) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.detach_filter()
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cbpf(&self, filters: &[libc::sock_filter]) -> io::Result<()> {
+        let prog = libc::sock_fprog {
+            len: filters.len() as _,
+            filter: filters.as_ptr() as *mut _,
+        };
+        // SAFETY: the filters are valid during the call.
+        unsafe { self.set_opt(libc::SOL_SOCKET, libc::SO_ATTACH_REUSEPORT_CBPF, &prog) }
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog_fd: RawFd) -> io::Result<()> {
+        unsafe {
+            self.set_opt(
+                libc::SOL_SOCKET,
+                libc::SO_ATTACH_REUSEPORT_EBPF,
+                &(prog_fd as libc::c_int),
+            )
+        }
+    }
+
+    /// Steer the packets or connections to the socket of the index of the CPU
+    /// receiving them in the `SO_REUSEPORT` group.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cpu(&self, group_size: u32) -> io::Result<()> {
+        if group_size == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the reuseport group should not be empty",
+            ));
+        }
+        let filters = unsafe {
+            [
+                libc::BPF_STMT(
+                    (libc::BPF_LD | libc::BPF_W | libc::BPF_ABS) as _,
+                    (libc::SKF_AD_OFF + libc::SKF_AD_CPU) as _,
+                ),
+                libc::BPF_STMT(
+                    (libc::BPF_ALU | libc::BPF_MOD | libc::BPF_K) as _,
+                    group_size,
+                ),
+                libc::BPF_STMT((libc::BPF_RET | libc::BPF_A) as _, 0),
+            ]
+        };
+        self.attach_reuseport_cbpf(&filters)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu_discover(&self) -> io::Result<libc::c_int> {
+        let (level, name) = self.mtu_discover_opt()?;
+        unsafe { self.get_opt(level, name) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mtu_discover(&self, mode: libc::c_int) -> io::Result<()> {
+        let (level, name) = self.mtu_discover_opt()?;
+        unsafe { self.set_opt(level, name, &mode) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    fn mtu_discover_opt(&self) -> io::Result<(libc::c_int, libc::c_int)> {
+        if self.local_addr()?.is_ipv6() {
+            Ok((libc::IPPROTO_IPV6, libc::IPV6_MTU_DISCOVER))
+        } else {
+            Ok((libc::IPPROTO_IP, libc::IP_MTU_DISCOVER))
+        }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu(&self) -> io::Result<usize> {
+        let mtu: libc::c_int = if self.local_addr()?.is_ipv6() {
+            unsafe { self.get_opt(libc::IPPROTO_IPV6, libc::IPV6_MTU) }?
+        } else {
+            unsafe { self.get_opt(libc::IPPROTO_IP, libc::IP_MTU) }?
+        };
+        Ok(mtu as usize)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn max_pacing_rate(&self) -> io::Result<Option<u64>> {
+        let rate: u64 = unsafe { self.get_opt(libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE) }?;
+        // The kernels before 4.20 return 32 bits.
+        Ok((rate != u64::MAX && rate != u32::MAX as u64).then_some(rate))
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_max_pacing_rate(&self, rate: Option<u64>) -> io::Result<()> {
+        let rate = rate.unwrap_or(u64::MAX);
+        unsafe { self.set_opt(libc::SOL_SOCKET, libc::SO_MAX_PACING_RATE, &rate) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn gso_segment(&self) -> io::Result<u16> {
+        let size: libc::c_int = unsafe { self.get_opt(libc::SOL_UDP, libc::UDP_SEGMENT) }?;
+        Ok(size as u16)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_gso_segment(&self, size: u16) -> io::Result<()> {
+        let size = size as libc::c_int;
+        unsafe { self.set_opt(libc::SOL_UDP, libc::UDP_SEGMENT, &size) }
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn gro(&self) -> io::Result<bool> {
+        let gro: libc::c_int = unsafe { self.get_opt(libc::SOL_UDP, libc::UDP_GRO) }?;
+        Ok(gro != 0)
+    }
+
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_gro(&self, gro: bool) -> io::Result<()> {
+        let gro = gro as libc::c_int;
+        unsafe { self.set_opt(libc::SOL_UDP, libc::UDP_GRO, &gro) }
+    }
+
+    /// Set a socket option with the raw value.
+    ///
+    /// # Safety
+    ///
+    /// `value` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        use compio_driver::AsRawFd;
+
+        let fd = unsafe { self.socket.get_unchecked() }.as_raw_fd();
+        #[cfg(unix)]
+        compio_driver::syscall!(libc::setsockopt(
+            fd,
+            level,
+            name,
+            value as *const _ as *const _,
+            std::mem::size_of::<T>() as _,
+        ))?;
+        #[cfg(windows)]
+        compio_driver::syscall!(
+            SOCKET,
+            windows_sys::Win32::Networking::WinSock::setsockopt(
+                fd as _,
+                level,
+                name,
+                value as *const _ as *const _,
+                std::mem::size_of::<T>() as _,
+            )
+        )?;
+        Ok(())
+    }
+
+    /// Get a socket option as the raw value.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        use compio_driver::AsRawFd;
+
+        let fd = unsafe { self.socket.get_unchecked() }.as_raw_fd();
+        let mut value = std::mem::MaybeUninit::<T>::zeroed();
+        let mut len = std::mem::size_of::<T>() as _;
+        #[cfg(unix)]
+        compio_driver::syscall!(libc::getsockopt(
+            fd,
+            level,
+            name,
+            value.as_mut_ptr() as *mut _,
+            &mut len,
+        ))?;
+        #[cfg(windows)]
+        compio_driver::syscall!(
+            SOCKET,
+            windows_sys::Win32::Networking::WinSock::getsockopt(
+                fd as _,
+                level,
+                name,
+                value.as_mut_ptr() as *mut _,
+                &mut len,
+            )
+        )?;
+        Ok(unsafe { value.assume_init() })
+    }
+
+    #[cfg(unix)]
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_cloexec(cloexec)
+    }
+
+    #[cfg(windows)]
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        unsafe { self.socket.get_unchecked() }.set_no_inherit(cloexec)
+    }
+
     pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
         let socket = Socket2::new(domain, ty, protocol)?;
-        // On Linux we use blocking socket
-        // Newer kernels have the patch that allows to arm io_uring poll mechanism for
-        // non blocking socket when there is no connections in listen queue
-        //
-        // https://patchwork.kernel.org/project/linux-block/patch/f999615b-205c-49b7-b272-c4e42e45e09d@kernel.dk/#22949861
-        if cfg!(all(
-            unix,
-            not(all(target_os = "linux", feature = "io-uring"))
-        )) {
+        // The sockets are nonblocking on unix, so that the ops falling back to
+        // readiness never block the driver.
+        if cfg!(unix) {
             socket.set_nonblocking(true)?;
         }
         Ok(Self::from_socket2(socket))
     }
 
+    #[cfg(unix)]
+    pub fn pair(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<(Self, Self)> {
+        let (first, second) = Socket2::pair(domain, ty, protocol)?;
+        first.set_nonblocking(true)?;
+        second.set_nonblocking(true)?;
+        Ok((Self::from_socket2(first), Self::from_socket2(second)))
+    }
+
     pub fn bind(addr: &SockAddr, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
         let socket = Self::new(addr.domain(), ty, protocol)?;
         unsafe { socket.socket.get_unchecked() }.bind(addr)?;
         Ok(socket)
     }
 
+    pub fn bind_only_v6(
+        addr: &SockAddr,
+        ty: Type,
+        protocol: Option<Protocol>,
+        only_v6: bool,
+    ) -> io::Result<Self> {
+        let socket = Self::new(Domain::IPV6, ty, protocol)?;
+        let inner = unsafe { socket.socket.get_unchecked() };
+        inner.set_only_v6(only_v6)?;
+        inner.bind(addr)?;
+        Ok(socket)
+    }
+
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub fn bind_reuse_port(
+        addr: &SockAddr,
+        ty: Type,
+        protocol: Option<Protocol>,
+    ) -> io::Result<Self> {
+        let socket = Self::new(addr.domain(), ty, protocol)?;
+        let inner = unsafe { socket.socket.get_unchecked() };
+        inner.set_reuse_port(true)?;
+        inner.bind(addr)?;
+        Ok(socket)
+    }
+
     pub fn listen(&self, backlog: i32) -> io::Result<()> {
         unsafe { self.socket.get_unchecked() }.listen(backlog)
     }
@@ -67,7 +881,7 @@ impl Socket {
 
     pub async fn connect_async(&self, addr: &SockAddr) -> io::Result<()> {
         let op = Connect::new(self.try_as_raw_fd()?, addr.clone());
-        let BufResult(res, _op) = Runtime::current().submit(op).await;
+        let BufResult(res, _op) = submit(op).await;
         #[cfg(windows)]
         {
             res?;
@@ -80,24 +894,112 @@ impl Socket {
         }
     }
 
+    #[cfg(any(windows, target_os = "linux", target_os = "android"))]
+    pub async fn connect_with_data<T: IoBuf>(
+        &self,
+        addr: &SockAddr,
+        buffer: T,
+    ) -> BufResult<usize, T> {
+        let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
+        #[cfg(windows)]
+        {
+            // The data is still sent after the handshake without TFO.
+            let (level, name) = tcp_fastopen_opt();
+            let _ = unsafe { self.set_opt(level, name, &1u32) };
+        }
+        let op = ConnectWithData::new(fd, buffer, addr.clone());
+        let BufResult(res, op) = submit(op).await;
+        #[cfg(windows)]
+        let res = res.and_then(|sent| {
+            op.update_context()?;
+            Ok(sent)
+        });
+        #[cfg(unix)]
+        let res = match res {
+            // The SYN is sent without the data.
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => Ok(0),
+            // TFO is disabled for the clients.
+            Err(e) if e.raw_os_error() == Some(libc::EOPNOTSUPP) => {
+                self.connect_async(addr).await.map(|_| 0)
+            }
+            res => res,
+        };
+        BufResult(res, op.into_inner())
+    }
+
     #[cfg(unix)]
     pub async fn accept(&self) -> io::Result<(Self, SockAddr)> {
         use compio_driver::FromRawFd;
 
-        let op = Accept::new(self.try_as_raw_fd()?);
-        let BufResult(res, op) = Runtime::current().submit(op).await;
+        let fd = self.try_as_raw_fd()?;
+        // Reuse the op of the last accept to avoid the allocation.
+        let op = match self.accept_op.take() {
+            Some(mut op) => {
+                op.reset(fd);
+                op
+            }
+            None => Box::new(Accept::new(fd)),
+        };
+        let BufResult(res, op) = submit_boxed(op).await;
+        let addr = op.addr();
+        self.accept_op.put(op);
         let accept_sock = unsafe { Socket2::from_raw_fd(res? as _) };
-        if cfg!(all(
-            unix,
-            not(all(target_os = "linux", feature = "io-uring"))
-        )) {
-            accept_sock.set_nonblocking(true)?;
-        }
+        accept_sock.set_nonblocking(true)?;
         let accept_sock = Self::from_socket2(accept_sock);
-        let addr = op.into_addr();
         Ok((accept_sock, addr))
     }
 
+    #[cfg(unix)]
+    pub fn accept_multi(&self) -> impl Stream<Item = io::Result<Self>> + '_ {
+        use compio_driver::{op::AcceptMulti, FromRawFd};
+
+        // The pending multishot accept, and whether it is supported.
+        let state: (Option<LocalBoxStream<'static, io::Result<usize>>>, bool) = (None, true);
+        stream::unfold(state, move |(mut results, mut multishot)| async move {
+            let res = loop {
+                if !multishot {
+                    break self.accept().await.map(|(socket, _)| socket);
+                }
+                let fd = match self.try_as_raw_fd() {
+                    Ok(fd) => fd,
+                    Err(e) => break Err(e),
+                };
+                let accepted = results
+                    .get_or_insert_with(|| {
+                        Runtime::current()
+                            .submit_multishot(AcceptMulti::new(fd))
+                            .boxed_local()
+                    })
+                    .next()
+                    .await;
+                match accepted {
+                    // The kernel is older than 5.19.
+                    Some(Err(e)) if e.raw_os_error() == Some(libc::EINVAL) => {
+                        multishot = false;
+                        results = None;
+                    }
+                    Some(res) => {
+                        break res.and_then(|fd| {
+                            let socket = unsafe { Socket2::from_raw_fd(fd as _) };
+                            socket.set_nonblocking(true)?;
+                            Ok(Self::from_socket2(socket))
+                        });
+                    }
+                    // The multishot accept ends, and is submitted again.
+                    None => results = None,
+                }
+            };
+            Some((res, (results, multishot)))
+        })
+    }
+
+    #[cfg(windows)]
+    pub fn accept_multi(&self) -> impl Stream<Item = io::Result<Self>> + '_ {
+        stream::unfold((), move |_| async move {
+            Some((self.accept().await.map(|(socket, _)| socket), ()))
+        })
+    }
+
     #[cfg(windows)]
     pub async fn accept(&self) -> io::Result<(Self, SockAddr)> {
         use compio_driver::AsRawFd;
@@ -110,17 +1012,57 @@ impl Socket {
             unsafe { self.socket.get_unchecked() }.protocol()?,
         )?;
         let op = Accept::new(self.try_as_raw_fd()?, accept_sock.as_raw_fd() as _);
-        let BufResult(res, op) = Runtime::current().submit(op).await;
+        let BufResult(res, op) = submit(op).await;
         res?;
         op.update_context()?;
         let addr = op.into_addr()?;
         Ok((Self::from_socket2(accept_sock), addr))
     }
 
+    #[cfg(unix)]
+    pub async fn accept_with_data<B: IoBufMut>(
+        &self,
+        buffer: B,
+    ) -> BufResult<(Self, SockAddr, usize), B> {
+        let ((socket, addr), buffer) = buf_try!(self.accept().await, buffer);
+        let (len, buffer) = buf_try!(socket.recv(buffer).await);
+        BufResult(Ok((socket, addr, len)), buffer)
+    }
+
+    #[cfg(windows)]
+    pub async fn accept_with_data<B: IoBufMut>(
+        &self,
+        buffer: B,
+    ) -> BufResult<(Self, SockAddr, usize), B> {
+        use compio_buf::SetBufInit;
+        use compio_driver::{op::AcceptWithData, AsRawFd};
+
+        let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
+        let (local_addr, buffer) = buf_try!(self.local_addr(), buffer);
+        let socket = unsafe { self.socket.get_unchecked() };
+        let (ty, buffer) = buf_try!(socket.r#type(), buffer);
+        let (protocol, buffer) = buf_try!(socket.protocol(), buffer);
+        let (accept_sock, buffer) =
+            buf_try!(Socket2::new(local_addr.domain(), ty, protocol), buffer);
+        let op = AcceptWithData::new(fd, accept_sock.as_raw_fd() as _, buffer);
+        let BufResult(res, op) = submit(op).await;
+        let res = res.and_then(|len| {
+            op.update_context()?;
+            Ok((Self::from_socket2(accept_sock), op.addr()?, len))
+        });
+        let mut buffer = op.into_inner();
+        if let Ok((_, _, len)) = &res {
+            unsafe { buffer.set_buf_init(*len) };
+        }
+        BufResult(res, buffer)
+    }
+
     pub fn close(self) -> impl Future<Output = io::Result<()>> {
         // Make sure that self won't be dropped after `close` called.
         // Users may call this method and drop the future immediately. In that way the
         // `close` should be cancelled.
+        #[cfg(unix)]
+        drop(self.accept_op.take());
         let this = ManuallyDrop::new(self);
         async move {
             let op = CloseSocket::new(this.try_as_raw_fd()?);
@@ -129,6 +1071,16 @@ impl Socket {
         }
     }
 
+    pub fn abort(self) -> impl Future<Output = io::Result<()>> {
+        // A zero linger timeout makes the close send a RST.
+        let res = self.set_linger(Some(Duration::ZERO));
+        let close = self.close();
+        async move {
+            res?;
+            close.await
+        }
+    }
+
     pub async fn shutdown(&self) -> io::Result<()> {
         let op = ShutdownSocket::new(self.try_as_raw_fd()?, std::net::Shutdown::Write);
         Runtime::current().submit(op).await.0?;
@@ -136,46 +1088,230 @@ impl Socket {
     }
 
     pub async fn recv<B: IoBufMut>(&self, buffer: B) -> BufResult<usize, B> {
+        self.recv_with_flags(buffer, 0).await
+    }
+
+    pub async fn recv_with_flags<B: IoBufMut>(&self, buffer: B, flags: i32) -> BufResult<usize, B> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
-        let op = Recv::new(fd, buffer);
-        Runtime::current()
-            .submit(op)
-            .await
-            .into_inner()
-            .map_advanced()
+        let op = Recv::with_flags(fd, buffer, flags);
+        submit(op).await.into_inner().map_advanced()
+    }
+
+    pub async fn peek<B: IoBufMut>(&self, buffer: B) -> BufResult<usize, B> {
+        self.recv_with_flags(buffer, MSG_PEEK).await
+    }
+
+    #[cfg(unix)]
+    pub fn recv_stream<'a>(
+        &'a self,
+        pool: &'a BufferPool,
+    ) -> impl Stream<Item = io::Result<BorrowedBuffer>> + 'a {
+        if pool.is_provided() {
+            self.recv_multi(pool).left_stream()
+        } else {
+            self.recv_pooled(pool).right_stream()
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn recv_stream<'a>(
+        &'a self,
+        pool: &'a BufferPool,
+    ) -> impl Stream<Item = io::Result<BorrowedBuffer>> + 'a {
+        self.recv_pooled(pool)
+    }
+
+    // The buffers are selected by the kernel for a multishot receive.
+    #[cfg(unix)]
+    fn recv_multi<'a>(
+        &'a self,
+        pool: &'a BufferPool,
+    ) -> impl Stream<Item = io::Result<BorrowedBuffer>> + 'a {
+        use compio_driver::op::RecvMulti;
+
+        // The pending multishot receive, and whether the stream ends.
+        type Results = LocalBoxStream<'static, (io::Result<usize>, u32)>;
+        let state: (Option<Results>, bool) = (None, false);
+        stream::unfold(state, move |(mut results, ended)| async move {
+            if ended {
+                return None;
+            }
+            loop {
+                let fd = match self.try_as_raw_fd() {
+                    Ok(fd) => fd,
+                    Err(e) => return Some((Err(e), (None, true))),
+                };
+                let received = results
+                    .get_or_insert_with(|| {
+                        Runtime::current()
+                            .submit_multishot_with_flags(RecvMulti::new(fd, pool))
+                            .boxed_local()
+                    })
+                    .next()
+                    .await;
+                match received {
+                    Some((Ok(len), flags)) => {
+                        let buffer = unsafe { pool.take_selected(len, flags) };
+                        if len == 0 {
+                            return None;
+                        }
+                        let res = buffer.ok_or_else(|| io::Error::other("no buffer is selected"));
+                        return Some((res, (results, false)));
+                    }
+                    // All the buffers are borrowed. The receive ends, and is
+                    // submitted again on the next poll.
+                    Some((Err(e), _)) if e.raw_os_error() == Some(ENOBUFS) => {
+                        return Some((Err(e), (None, false)));
+                    }
+                    Some((Err(e), _)) => return Some((Err(e), (None, true))),
+                    // The multishot receive ends, and is submitted again.
+                    None => results = None,
+                }
+            }
+        })
+    }
+
+    // The buffers are taken from the pool for the ordinary receives.
+    fn recv_pooled<'a>(
+        &'a self,
+        pool: &'a BufferPool,
+    ) -> impl Stream<Item = io::Result<BorrowedBuffer>> + 'a {
+        stream::unfold(false, move |ended| async move {
+            if ended {
+                return None;
+            }
+            let Some(buffer) = pool.take() else {
+                return Some((Err(io::Error::from_raw_os_error(ENOBUFS)), false));
+            };
+            let BufResult(res, buffer) = self.recv(buffer).await;
+            match res {
+                Ok(0) => None,
+                Ok(_) => Some((Ok(buffer), false)),
+                Err(e) => Some((Err(e), true)),
+            }
+        })
+    }
+
+    #[cfg(unix)]
+    pub fn nread(&self) -> io::Result<usize> {
+        use compio_driver::{syscall, AsRawFd};
+
+        let mut len: libc::c_int = 0;
+        let fd = unsafe { self.socket.get_unchecked() }.as_raw_fd();
+        syscall!(libc::ioctl(fd, libc::FIONREAD, &mut len))?;
+        Ok(len as usize)
+    }
+
+    #[cfg(windows)]
+    pub fn nread(&self) -> io::Result<usize> {
+        use compio_driver::{syscall, AsRawFd};
+        use windows_sys::Win32::Networking::WinSock::{ioctlsocket, FIONREAD};
+
+        let mut len = 0u32;
+        let fd = unsafe { self.socket.get_unchecked() }.as_raw_fd();
+        syscall!(SOCKET, ioctlsocket(fd as _, FIONREAD, &mut len))?;
+        Ok(len as usize)
+    }
+
+    pub async fn recv_buf(&self, mut buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        // Fall back to the hint if the pending bytes are unknown.
+        let additional = self.nread().unwrap_or_default().max(hint).max(1);
+        let len = buffer.len();
+        buffer.reserve(additional);
+        let BufResult(res, slice) = self.recv(buffer.slice(len..len + additional)).await;
+        BufResult(res, slice.into_inner())
+    }
+
+    pub async fn send_buf(&self, buffer: Vec<u8>) -> BufResult<usize, Vec<u8>> {
+        let BufResult(res, mut buffer) = self.send(buffer).await;
+        if let Ok(len) = res {
+            buffer.drain(..len);
+        }
+        BufResult(res, buffer)
+    }
+
+    pub async fn recv_pending(
+        &self,
+        mut buffer: Vec<u8>,
+        hint: usize,
+    ) -> BufResult<usize, Vec<u8>> {
+        let mut received = 0;
+        loop {
+            let (fd, mut buf) = buf_try!(self.try_as_raw_fd(), buffer);
+            let len = buf.len();
+            buf.reserve(hint.max(1));
+            let capacity = buf.capacity();
+            let op = Recv::with_flags(fd, buf.slice(len..capacity), 0);
+            let (res, flags) = submit_with_flags(op).await;
+            let BufResult(res, slice) = res.into_inner().map_advanced();
+            buffer = slice.into_inner();
+            match res {
+                Ok(0) => break,
+                Ok(n) => received += n,
+                Err(e) => return BufResult(Err(e), buffer),
+            }
+            // Skip the extra receive if the driver tells the socket is empty,
+            // or ask the kernel otherwise.
+            let pending = compio_driver::sock_nonempty(flags)
+                .unwrap_or_else(|| self.nread().is_ok_and(|n| n > 0));
+            if !pending {
+                break;
+            }
+        }
+        BufResult(Ok(received), buffer)
     }
 
     pub async fn recv_vectored<V: IoVectoredBufMut>(&self, buffer: V) -> BufResult<usize, V> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
         let op = RecvVectored::new(fd, buffer);
-        Runtime::current()
-            .submit(op)
-            .await
-            .into_inner()
-            .map_advanced()
+        submit(op).await.into_inner().map_advanced()
     }
 
     pub async fn send<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.send_with_flags(buffer, 0).await
+    }
+
+    pub async fn send_with_flags<T: IoBuf>(&self, buffer: T, flags: i32) -> BufResult<usize, T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
-        let op = Send::new(fd, buffer);
-        Runtime::current().submit(op).await.into_inner()
+        let op = Send::with_flags(fd, buffer, flags);
+        submit(op).await.into_inner()
+    }
+
+    pub async fn send_zc<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        #[cfg(unix)]
+        {
+            let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
+            let op = SendZc::new(fd, buffer);
+            submit(op).await.into_inner()
+        }
+        #[cfg(windows)]
+        {
+            self.send(buffer).await
+        }
     }
 
     pub async fn send_vectored<T: IoVectoredBuf>(&self, buffer: T) -> BufResult<usize, T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
         let op = SendVectored::new(fd, buffer);
-        Runtime::current().submit(op).await.into_inner()
+        submit(op).await.into_inner()
     }
 
     pub async fn recv_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SockAddr), T> {
+        self.recv_from_with_flags(buffer, 0).await
+    }
+
+    pub async fn recv_from_with_flags<T: IoBufMut>(
+        &self,
+        buffer: T,
+        flags: i32,
+    ) -> BufResult<(usize, SockAddr), T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
-        let op = RecvFrom::new(fd, buffer);
-        Runtime::current()
-            .submit(op)
-            .await
-            .into_inner()
-            .map_addr()
-            .map_advanced()
+        let op = RecvFrom::with_flags(fd, buffer, flags);
+        submit(op).await.into_inner().map_addr().map_advanced()
+    }
+
+    pub async fn peek_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SockAddr), T> {
+        self.recv_from_with_flags(buffer, MSG_PEEK).await
     }
 
     pub async fn recv_from_vectored<T: IoVectoredBufMut>(
@@ -184,18 +1320,153 @@ impl Socket {
     ) -> BufResult<(usize, SockAddr), T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
         let op = RecvFromVectored::new(fd, buffer);
-        Runtime::current()
-            .submit(op)
-            .await
-            .into_inner()
-            .map_addr()
-            .map_advanced()
+        submit(op).await.into_inner().map_addr().map_advanced()
     }
 
     pub async fn send_to<T: IoBuf>(&self, buffer: T, addr: &SockAddr) -> BufResult<usize, T> {
+        self.send_to_with_flags(buffer, addr, 0).await
+    }
+
+    pub async fn send_to_with_flags<T: IoBuf>(
+        &self,
+        buffer: T,
+        addr: &SockAddr,
+        flags: i32,
+    ) -> BufResult<usize, T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
-        let op = SendTo::new(fd, buffer, addr.clone());
-        Runtime::current().submit(op).await.into_inner()
+        let op = SendTo::with_flags(fd, buffer, addr.clone(), flags);
+        submit(op).await.into_inner()
+    }
+
+    pub async fn send_msg<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+        addr: &SockAddr,
+    ) -> BufResult<usize, (T, C)> {
+        let (fd, buffer) = buf_try!(self.try_as_raw_fd(), (buffer, control));
+        let op = SendMsg::new(fd, buffer.0, buffer.1, addr.clone());
+        submit(op).await.into_inner()
+    }
+
+    #[cfg(unix)]
+    pub async fn send_msg_connected<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<usize, (T, C)> {
+        let (fd, buffer) = buf_try!(self.try_as_raw_fd(), (buffer, control));
+        let op = SendMsg::connected(fd, buffer.0, buffer.1);
+        submit(op).await.into_inner()
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub async fn send_mmsg<T: IoBuf>(
+        &self,
+        buffers: Vec<T>,
+        addrs: Vec<SockAddr>,
+    ) -> BufResult<Vec<usize>, Vec<T>> {
+        let (fd, mut buffers) = buf_try!(self.try_as_raw_fd(), buffers);
+        let mut addrs = addrs;
+        // There is no opcode of `sendmmsg`, and io-uring calls it once on the
+        // thread pool with the nonblocking socket, so wait for the socket to
+        // be writable when it fails with `EAGAIN`.
+        loop {
+            let op = SendMmsg::new(fd, buffers, addrs);
+            let BufResult(res, op) = submit(op).await;
+            match res {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    (buffers, addrs, _) = op.into_inner();
+                    let res = submit(PollOnce::new(fd, Interest::Writable)).await.0;
+                    buffers = buf_try!(res, buffers).1;
+                }
+                res => {
+                    return BufResult(res, op).into_inner().map2(
+                        |n, (buffers, _, mut lens)| {
+                            lens.truncate(n);
+                            (lens, buffers)
+                        },
+                        |(buffers, ..)| buffers,
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub async fn recv_mmsg<T: IoBufMut>(
+        &self,
+        buffers: Vec<T>,
+    ) -> BufResult<Vec<(usize, SockAddr)>, Vec<T>> {
+        let (fd, mut buffers) = buf_try!(self.try_as_raw_fd(), buffers);
+        // There is no opcode of `recvmmsg`, and io-uring calls it once on the
+        // thread pool with the nonblocking socket, so wait for the socket to
+        // be readable first, and again when it fails with `EAGAIN`.
+        let (res, op) = loop {
+            let res = submit(PollOnce::new(fd, Interest::Readable)).await.0;
+            buffers = buf_try!(res, buffers).1;
+            let BufResult(res, op) = submit(RecvMmsg::new(fd, buffers)).await;
+            match res {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => buffers = op.into_inner().0,
+                res => break (res, op),
+            }
+        };
+        BufResult(res, op).into_inner().map2(
+            |n, (mut buffers, msgs)| {
+                let msgs = buffers
+                    .iter_mut()
+                    .zip(msgs)
+                    .take(n)
+                    .map(|(buffer, (len, addr, addr_len))| {
+                        unsafe { buffer.set_buf_init(len) };
+                        (len, unsafe { SockAddr::new(addr, addr_len) })
+                    })
+                    .collect();
+                (msgs, buffers)
+            },
+            |(buffers, _)| buffers,
+        )
+    }
+
+    #[cfg(unix)]
+    pub async fn recv_msg<T: IoVectoredBufMut, C: IoBufMut>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<(usize, SockAddr), (T, C)> {
+        // Don't leak the received fds to the child processes.
+        #[cfg(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        const FLAGS: i32 = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(any(
+            target_os = "android",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "linux",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        const FLAGS: i32 = 0;
+
+        let (fd, buffer) = buf_try!(self.try_as_raw_fd(), (buffer, control));
+        let op = RecvMsg::with_flags(fd, buffer.0, buffer.1, FLAGS);
+        submit(op).await.into_inner().map2(
+            |res, ((mut buffer, mut control), addr, addr_len, control_len)| {
+                unsafe {
+                    buffer.set_buf_init(res);
+                    control.set_buf_init(control_len);
+                }
+                let addr = unsafe { SockAddr::new(addr, addr_len) };
+                ((res, addr), (buffer, control))
+            },
+            |(buffer, ..)| buffer,
+        )
     }
 
     pub async fn send_to_vectored<T: IoVectoredBuf>(
@@ -205,10 +1476,96 @@ impl Socket {
     ) -> BufResult<usize, T> {
         let (fd, buffer) = buf_try!(self.try_as_raw_fd(), buffer);
         let op = SendToVectored::new(fd, buffer, addr.clone());
-        Runtime::current().submit(op).await.into_inner()
+        submit(op).await.into_inner()
+    }
+}
+
+/// Tracks whether an operation is in flight in debug builds. Concurrent reads
+/// or writes on a stream could interleave the data, so the streams only allow
+/// one of each at a time.
+#[derive(Debug, Default)]
+pub struct Exclusive {
+    #[cfg(debug_assertions)]
+    busy: AtomicBool,
+}
+
+impl Exclusive {
+    fn enter(&self, _ops: &'static str) -> ExclusiveGuard<'_> {
+        #[cfg(debug_assertions)]
+        assert!(
+            !self.busy.swap(true, Ordering::AcqRel),
+            "concurrent {_ops} on the same stream; use one reader and one writer at a time, e.g. \
+             with `split`"
+        );
+        ExclusiveGuard(self)
+    }
+}
+
+pub struct ExclusiveGuard<'a>(#[allow(dead_code)] &'a Exclusive);
+
+impl Drop for ExclusiveGuard<'_> {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        self.0.busy.store(false, Ordering::Release);
     }
 }
 
-impl_try_as_raw_fd!(Socket, socket);
+/// A reusable allocation of an operation.
+#[cfg(unix)]
+struct OpCache<T>(Cell<Option<Box<T>>>);
+
+#[cfg(unix)]
+impl<T> Default for OpCache<T> {
+    fn default() -> Self {
+        Self(Cell::new(None))
+    }
+}
+
+#[cfg(unix)]
+impl<T> OpCache<T> {
+    fn take(&self) -> Option<Box<T>> {
+        self.0.take()
+    }
+
+    fn put(&self, op: Box<T>) {
+        self.0.set(Some(op));
+    }
+}
+
+#[cfg(unix)]
+impl<T> std::fmt::Debug for OpCache<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OpCache").finish_non_exhaustive()
+    }
+}
+
+impl TryAsRawFd for Socket {
+    fn try_as_raw_fd(&self) -> io::Result<RawFd> {
+        self.socket.try_as_raw_fd()
+    }
+
+    unsafe fn as_raw_fd_unchecked(&self) -> RawFd {
+        self.socket.as_raw_fd_unchecked()
+    }
+}
+
+impl FromRawFd for Socket {
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self {
+            socket: FromRawFd::from_raw_fd(fd),
+            reading: Exclusive::default(),
+            writing: Exclusive::default(),
+            corked: AtomicBool::new(false),
+            #[cfg(unix)]
+            accept_op: OpCache::default(),
+        }
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.socket.into_raw_fd()
+    }
+}
 
 impl_attachable!(Socket, socket);
@@ -3,7 +3,7 @@ use std::{error::Error, fmt, io, ops::Deref, sync::Arc};
 use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
 use compio_io::{AsyncRead, AsyncWrite};
 
-pub(crate) fn split<T>(stream: &T) -> (ReadHalf<T>, WriteHalf<T>)
+pub(crate) fn split<T>(stream: &T) -> (ReadHalf<'_, T>, WriteHalf<'_, T>)
 where
     for<'a> &'a T: AsyncRead + AsyncWrite,
 {
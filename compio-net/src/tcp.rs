@@ -1,12 +1,112 @@
-use std::{future::Future, io, net::SocketAddr};
+use std::{
+    cell::Cell,
+    future::{poll_fn, Future},
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    rc::Rc,
+    sync::atomic::{AtomicU64, Ordering},
+    task::Poll,
+    time::Duration,
+};
 
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+use compio_buf::{buf_try, IntoInner};
 use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+use compio_io::AsyncWriteExt;
 use compio_io::{AsyncRead, AsyncWrite};
-use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
+#[cfg(target_os = "linux")]
+use compio_runtime::RawFd;
+use compio_runtime::{impl_attachable, impl_try_as_raw_fd, BorrowedBuffer, BufferPool, TryAsRawFd};
+use futures_util::{stream, Stream, StreamExt};
 use socket2::{Protocol, SockAddr, Type};
 
 use crate::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, Socket, ToSocketAddrsAsync, WriteHalf};
 
+/// The TCP keepalive options, i.e. how long a connection is idle before the
+/// first probe, the interval between the probes, and how many probes are
+/// sent before the connection is dropped.
+///
+/// They are set with `TCP_KEEPIDLE` (`TCP_KEEPALIVE` on Apple platforms),
+/// `TCP_KEEPINTVL` and `TCP_KEEPCNT`, or `SIO_KEEPALIVE_VALS` on Windows.
+/// The unset ones keep the system defaults. The interval is ignored on
+/// OpenBSD, and the retries are ignored on OpenBSD and Windows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TcpKeepalive {
+    time: Option<Duration>,
+    interval: Option<Duration>,
+    retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    /// Create the options with the system defaults.
+    pub const fn new() -> Self {
+        Self {
+            time: None,
+            interval: None,
+            retries: None,
+        }
+    }
+
+    /// Set the idle time before the first probe. It is rounded to seconds on
+    /// most platforms.
+    pub const fn with_time(mut self, time: Duration) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Set the interval between the probes.
+    pub const fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = Some(interval);
+        self
+    }
+
+    /// Set the number of the unacknowledged probes before the connection is
+    /// dropped.
+    pub const fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = Some(retries);
+        self
+    }
+
+    /// The idle time before the first probe.
+    pub const fn time(&self) -> Option<Duration> {
+        self.time
+    }
+
+    /// The interval between the probes.
+    pub const fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    /// The number of the probes before the connection is dropped.
+    pub const fn retries(&self) -> Option<u32> {
+        self.retries
+    }
+
+    #[allow(unused_mut)]
+    pub(crate) fn to_socket2(self) -> socket2::TcpKeepalive {
+        let mut keepalive = socket2::TcpKeepalive::new();
+        if let Some(time) = self.time {
+            keepalive = keepalive.with_time(time);
+        }
+        #[cfg(not(target_os = "openbsd"))]
+        if let Some(interval) = self.interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        #[cfg(not(any(target_os = "openbsd", windows)))]
+        if let Some(retries) = self.retries {
+            keepalive = keepalive.with_retries(retries);
+        }
+        keepalive
+    }
+}
+
+#[cfg(unix)]
+const MSG_OOB: i32 = libc::MSG_OOB;
+#[cfg(windows)]
+const MSG_OOB: i32 = windows_sys::Win32::Networking::WinSock::MSG_OOB as _;
+
 /// A TCP socket server, listening for connections.
 ///
 /// You can accept a new connection by using the
@@ -42,9 +142,33 @@ use crate::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, Socket, ToSocketAddrsAsync,
 #[derive(Debug)]
 pub struct TcpListener {
     inner: Socket,
+    stats: AcceptStats,
+    keepalive: Cell<Option<TcpKeepalive>>,
+}
+
+#[derive(Debug, Default)]
+struct AcceptStats {
+    accepted: AtomicU64,
+    errors: AtomicU64,
 }
 
 impl TcpListener {
+    fn from_socket(inner: Socket) -> Self {
+        Self {
+            inner,
+            stats: AcceptStats::default(),
+            keepalive: Cell::default(),
+        }
+    }
+
+    // Apply the options inherited by the accepted connections.
+    fn accepted(&self, inner: Socket) -> io::Result<TcpStream> {
+        if let Some(keepalive) = self.keepalive.get() {
+            inner.set_tcp_keepalive(Some(&keepalive))?;
+        }
+        Ok(TcpStream { inner })
+    }
+
     /// Creates a new `TcpListener`, which will be bound to the specified
     /// address.
     ///
@@ -56,7 +180,67 @@ impl TcpListener {
         super::each_addr(addr, |addr| async move {
             let socket = Socket::bind(&SockAddr::from(addr), Type::STREAM, Some(Protocol::TCP))?;
             socket.listen(128)?;
-            Ok(Self { inner: socket })
+            Ok(Self::from_socket(socket))
+        })
+        .await
+    }
+
+    /// Creates a listener accepting both the IPv4 and the IPv6 connections on
+    /// `port` of all interfaces.
+    ///
+    /// It binds an IPv6 socket with `IPV6_V6ONLY` cleared, and reports the
+    /// IPv4 peers with their IPv4 addresses instead of the mapped ones. On the
+    /// systems not supporting it, e.g. OpenBSD, it binds an IPv6 and an IPv4
+    /// socket on the same port. If IPv6 is unavailable, it only listens on
+    /// IPv4.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this listener.
+    pub fn bind_dual_stack(port: u16) -> io::Result<DualStackListener> {
+        let v6 = SockAddr::from(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)));
+        let bind_v4 = |port| {
+            let addr = SockAddr::from(SocketAddr::from((Ipv4Addr::UNSPECIFIED, port)));
+            let socket = Socket::bind(&addr, Type::STREAM, Some(Protocol::TCP))?;
+            socket.listen(128)?;
+            io::Result::Ok(Self::from_socket(socket))
+        };
+        if let Ok(socket) = Socket::bind_only_v6(&v6, Type::STREAM, Some(Protocol::TCP), false) {
+            socket.listen(128)?;
+            return Ok(DualStackListener::new(vec![Self::from_socket(socket)]));
+        }
+        match Socket::bind_only_v6(&v6, Type::STREAM, Some(Protocol::TCP), true) {
+            Ok(socket) => {
+                socket.listen(128)?;
+                let v6 = Self::from_socket(socket);
+                let v4 = bind_v4(v6.local_addr()?.port())?;
+                Ok(DualStackListener::from_pair(v6, v4))
+            }
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::AddrInUse | io::ErrorKind::PermissionDenied
+                ) =>
+            {
+                Err(e)
+            }
+            // IPv6 is unavailable.
+            Err(_) => Ok(DualStackListener::new(vec![bind_v4(port)?])),
+        }
+    }
+
+    /// Creates a new `TcpListener` with `SO_REUSEPORT`, which will be bound
+    /// to the specified address.
+    ///
+    /// Multiple sockets could be bound to the same address, e.g. one per
+    /// thread, and the kernel distributes the incoming connections among
+    /// them.
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub async fn bind_reuse_port(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
+        super::each_addr(addr, |addr| async move {
+            let socket =
+                Socket::bind_reuse_port(&SockAddr::from(addr), Type::STREAM, Some(Protocol::TCP))?;
+            socket.listen(128)?;
+            Ok(Self::from_socket(socket))
         })
         .await
     }
@@ -71,9 +255,81 @@ impl TcpListener {
     ///
     /// It does not clear the attach state.
     pub fn try_clone(&self) -> io::Result<Self> {
-        Ok(Self {
-            inner: self.inner.try_clone()?,
-        })
+        let listener = Self::from_socket(self.inner.try_clone()?);
+        listener.keepalive.set(self.keepalive.get());
+        Ok(listener)
+    }
+
+    /// Creates a listener from a [`std::net::TcpListener`], e.g. one passed by
+    /// systemd or launchd with socket activation. It is set to the nonblocking
+    /// mode needed by the driver, and attached to the driver of the current
+    /// runtime when first used.
+    pub fn from_std(listener: std::net::TcpListener) -> io::Result<Self> {
+        Ok(Self::from_socket(Socket::from_std(listener)?))
+    }
+
+    /// Converts it into a [`std::net::TcpListener`] in blocking mode, to hand
+    /// it off to other libraries. The keepalive of the accepted connections is
+    /// not kept.
+    pub fn into_std(self) -> io::Result<std::net::TcpListener> {
+        self.inner.into_std()
+    }
+
+    /// Gets the CPU that handles the packets of this socket, i.e. the value
+    /// of `SO_INCOMING_CPU`.
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Sets the value of `SO_INCOMING_CPU`.
+    ///
+    /// In a `SO_REUSEPORT` group, the connections received on `cpu` prefer this
+    /// socket.
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        self.inner.set_incoming_cpu(cpu)
+    }
+
+    /// Gets the network device this socket is bound to, i.e.
+    /// `SO_BINDTODEVICE` on Linux, and `IP_BOUND_IF` on Apple platforms.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn device(&self) -> io::Result<Option<String>> {
+        self.inner.device()
+    }
+
+    /// Binds this socket to the network device `interface`, e.g. `eth0`, so
+    /// that only the connections through it are accepted. `None` removes the
+    /// binding.
+    ///
+    /// It may need `CAP_NET_RAW` on Linux.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn bind_device(&self, interface: Option<&str>) -> io::Result<()> {
+        self.inner.bind_device(interface)
+    }
+
+    /// Steers the connections by the CPU receiving them, with a classic BPF
+    /// program attached to the `SO_REUSEPORT` group of this socket.
+    ///
+    /// The connections received on CPU `n` go to the socket at index
+    /// `n % group_size` of the group, i.e. the order the sockets are bound.
+    /// Bind one socket per core with [`bind_reuse_port`](Self::bind_reuse_port)
+    /// on the runtime pinned to that core, so the connections are handled on
+    /// the CPU that received the interrupt.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cpu(&self, group_size: u32) -> io::Result<()> {
+        self.inner.attach_reuseport_cpu(group_size)
+    }
+
+    /// Attaches an eBPF program of type `BPF_PROG_TYPE_SOCKET_REUSEPORT` to
+    /// the `SO_REUSEPORT` group of this socket, to select the socket of the
+    /// connection.
+    ///
+    /// The program should be loaded elsewhere, and its file descriptor could
+    /// be closed after it is attached.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog_fd: RawFd) -> io::Result<()> {
+        self.inner.attach_reuseport_ebpf(prog_fd)
     }
 
     /// Accepts a new incoming connection from this listener.
@@ -82,11 +338,275 @@ impl TcpListener {
     /// established, the corresponding [`TcpStream`] and the remote peer's
     /// address will be returned.
     pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
-        let (socket, addr) = self.inner.accept().await?;
-        let stream = TcpStream { inner: socket };
+        let res = self.inner.accept().await;
+        self.record_accept(res.is_ok());
+        let (socket, addr) = res?;
+        let stream = self.accepted(socket)?;
         Ok((stream, addr.as_socket().expect("should be SocketAddr")))
     }
 
+    /// Accepts the incoming connections as a stream, with the remote
+    /// addresses, so that the listener composes with the combinators of
+    /// [`StreamExt`].
+    ///
+    /// It calls [`accept`](Self::accept) for each item, and never ends. The
+    /// errors are yielded without ending the stream.
+    ///
+    /// ```
+    /// use compio_net::{TcpListener, TcpStream};
+    /// use futures_util::StreamExt;
+    ///
+    /// # compio_runtime::Runtime::new().unwrap().block_on(async {
+    /// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    /// let client = TcpStream::connect(&addr).await.unwrap();
+    ///
+    /// let mut incoming = std::pin::pin!(listener.incoming());
+    /// let (_stream, peer) = incoming.next().await.unwrap().unwrap();
+    /// assert_eq!(peer, client.local_addr().unwrap());
+    /// # })
+    /// ```
+    pub fn incoming(&self) -> impl Stream<Item = io::Result<(TcpStream, SocketAddr)>> + '_ {
+        stream::unfold((), move |_| async move { Some((self.accept().await, ())) })
+    }
+
+    /// Accepts the incoming connections as a stream, without the remote
+    /// addresses.
+    ///
+    /// On io-uring driver, it submits one multishot accept, which completes
+    /// for every new connection, instead of one operation per connection
+    /// like [`accept`](Self::accept). It falls back to `accept` on other
+    /// drivers, and on the kernels older than 5.19. The errors are yielded
+    /// without ending the stream.
+    ///
+    /// ```
+    /// use compio_net::{TcpListener, TcpStream};
+    /// use futures_util::StreamExt;
+    ///
+    /// # compio_runtime::Runtime::new().unwrap().block_on(async {
+    /// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    /// let _client = TcpStream::connect(&addr).await.unwrap();
+    ///
+    /// let mut incoming = std::pin::pin!(listener.accept_multi());
+    /// let stream = incoming.next().await.unwrap().unwrap();
+    /// assert_eq!(stream.local_addr().unwrap(), addr);
+    /// # })
+    /// ```
+    pub fn accept_multi(&self) -> impl Stream<Item = io::Result<TcpStream>> + '_ {
+        self.inner.accept_multi().map(|res| {
+            self.record_accept(res.is_ok());
+            res.and_then(|inner| self.accepted(inner))
+        })
+    }
+
+    /// Accepts a new incoming connection whose remote address is accepted by
+    /// `filter`.
+    ///
+    /// The connections rejected by `filter` are reset immediately, and this
+    /// function continues to wait for the next one.
+    pub async fn accept_filtered(
+        &self,
+        mut filter: impl FnMut(&SocketAddr) -> bool,
+    ) -> io::Result<(TcpStream, SocketAddr)> {
+        loop {
+            let (stream, addr) = self.accept().await?;
+            if filter(&addr) {
+                return Ok((stream, addr));
+            }
+            stream.abort().await.ok();
+        }
+    }
+
+    /// Accepts a new incoming connection and receives its first data into
+    /// `buffer`, returning the number of bytes received.
+    ///
+    /// It saves a round trip for the protocols in which the client speaks
+    /// first, e.g. HTTP. On Windows, the data is received by `AcceptEx` with
+    /// the connection, and the capacity of `buffer` should also hold two
+    /// socket addresses. On other platforms, the data is received after
+    /// accepting; combine it with
+    /// [`set_defer_accept`](TcpListener::set_defer_accept) on Linux to only
+    /// wake up when the data arrives.
+    pub async fn accept_with_data<B: IoBufMut>(
+        &self,
+        buffer: B,
+    ) -> BufResult<(TcpStream, SocketAddr, usize), B> {
+        let BufResult(res, buffer) = self.inner.accept_with_data(buffer).await;
+        let res = res.and_then(|(socket, addr, len)| {
+            let stream = self.accepted(socket)?;
+            Ok((stream, addr.as_socket().expect("should be SocketAddr"), len))
+        });
+        BufResult(res, buffer)
+    }
+
+    /// Gets the value of the `TCP_DEFER_ACCEPT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_defer_accept`](TcpListener::set_defer_accept).
+    #[cfg(target_os = "linux")]
+    pub fn defer_accept(&self) -> io::Result<Option<Duration>> {
+        self.inner.defer_accept()
+    }
+
+    /// Sets the value of the `TCP_DEFER_ACCEPT` option on this socket.
+    ///
+    /// The connections are only accepted when the data arrives, or after the
+    /// timeout, which is rounded to seconds by the kernel. `None` disables
+    /// it.
+    #[cfg(target_os = "linux")]
+    pub fn set_defer_accept(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_defer_accept(timeout)
+    }
+
+    /// Gets the value of the `TCP_FASTOPEN` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_fastopen`](TcpListener::set_fastopen).
+    #[cfg(any(
+        windows,
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple"
+    ))]
+    pub fn fastopen(&self) -> io::Result<u32> {
+        self.inner.fastopen()
+    }
+
+    /// Sets the value of the `TCP_FASTOPEN` option on this socket, accepting
+    /// the data in the SYN of the clients, e.g. sent with
+    /// [`TcpStream::connect_with`].
+    ///
+    /// `queue` limits the connections pending the handshake with the data on
+    /// Linux, and the other platforms only check whether it's positive. `0`
+    /// disables it.
+    #[cfg(any(
+        windows,
+        target_os = "android",
+        target_os = "freebsd",
+        target_os = "linux",
+        target_vendor = "apple"
+    ))]
+    pub fn set_fastopen(&self, queue: u32) -> io::Result<()> {
+        self.inner.set_fastopen(queue)
+    }
+
+    /// The keepalive set on the connections accepted by this handle.
+    pub fn keepalive(&self) -> Option<TcpKeepalive> {
+        self.keepalive.get()
+    }
+
+    /// Set the keepalive of the connections accepted by this handle from
+    /// now on, as [`TcpStream::set_keepalive`]. `None` leaves the accepted
+    /// connections with the system default.
+    pub fn set_keepalive(&self, keepalive: Option<TcpKeepalive>) {
+        self.keepalive.set(keepalive);
+    }
+
+    fn record_accept(&self, ok: bool) {
+        let counter = if ok {
+            &self.stats.accepted
+        } else {
+            &self.stats.errors
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The number of the connections accepted by this handle.
+    ///
+    /// Sample it periodically to get the accept rate.
+    pub fn accepted_connections(&self) -> u64 {
+        self.stats.accepted.load(Ordering::Relaxed)
+    }
+
+    /// The number of the failed accepts of this handle.
+    pub fn accept_errors(&self) -> u64 {
+        self.stats.errors.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of the established connections waiting in the
+    /// accept queue.
+    ///
+    /// When it reaches [`backlog`](TcpListener::backlog), the new connections
+    /// are dropped or reset by the kernel.
+    #[cfg(target_os = "linux")]
+    pub fn pending_connections(&self) -> io::Result<usize> {
+        Ok(self.inner.tcp_info()?.tcpi_unacked as usize)
+    }
+
+    /// Returns the maximum length of the accept queue, i.e. the backlog
+    /// passed to `listen` capped by `net.core.somaxconn`.
+    #[cfg(target_os = "linux")]
+    pub fn backlog(&self) -> io::Result<usize> {
+        Ok(self.inner.tcp_info()?.tcpi_sacked as usize)
+    }
+
+    /// Gets the value of the `IP_TTL` option on this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Sets the value of the `IP_TTL` option on this socket, i.e. the
+    /// time-to-live of every IPv4 packet sent.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket, i.e. the size
+    /// of the kernel receive buffer. The kernel may adjust it, e.g. double it
+    /// on Linux.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket, i.e. the size
+    /// of the kernel send buffer. The kernel may adjust it, e.g. double it on
+    /// Linux.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    ///
+    /// On Windows, this sets whether the socket handle could be inherited.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
     /// Returns the local address that this listener is bound to.
     ///
     /// This can be useful, for example, when binding to port 0 to
@@ -117,10 +637,93 @@ impl TcpListener {
     }
 }
 
-impl_try_as_raw_fd!(TcpListener, inner);
+impl_try_as_raw_fd!(TcpListener, inner, stats, keepalive);
 
 impl_attachable!(TcpListener, inner);
 
+type AcceptFuture = Pin<Box<dyn Future<Output = io::Result<(TcpStream, SocketAddr)>>>>;
+
+/// A listener accepting both the IPv4 and the IPv6 connections, created by
+/// [`TcpListener::bind_dual_stack`].
+///
+/// It may be backed by one dual-stack socket or two sockets, depending on the
+/// system. The peer addresses of the IPv4 connections are always IPv4.
+pub struct DualStackListener {
+    listeners: Rc<[TcpListener]>,
+    // The accept operations not completed by the previous calls, kept to not
+    // lose the connections.
+    pending: Vec<Option<AcceptFuture>>,
+    next: usize,
+}
+
+impl DualStackListener {
+    fn new(listeners: Vec<TcpListener>) -> Self {
+        let pending = listeners.iter().map(|_| None).collect();
+        Self {
+            listeners: listeners.into(),
+            pending,
+            next: 0,
+        }
+    }
+
+    /// Merge an IPv6 listener and an IPv4 listener into one.
+    pub fn from_pair(v6: TcpListener, v4: TcpListener) -> Self {
+        Self::new(vec![v6, v4])
+    }
+
+    /// The listeners backing this one.
+    pub fn listeners(&self) -> &[TcpListener] {
+        &self.listeners
+    }
+
+    /// Returns the local addresses of the listeners.
+    pub fn local_addrs(&self) -> io::Result<Vec<SocketAddr>> {
+        self.listeners.iter().map(|l| l.local_addr()).collect()
+    }
+
+    /// Accepts a new incoming connection from any of the listeners.
+    ///
+    /// If the returned future is dropped, the accept operations in flight
+    /// are kept for the next call.
+    pub async fn accept(&mut self) -> io::Result<(TcpStream, SocketAddr)> {
+        let res = poll_fn(|cx| {
+            let len = self.listeners.len();
+            for i in (0..len).map(|i| (self.next + i) % len) {
+                let listeners = &self.listeners;
+                let fut = self.pending[i].get_or_insert_with(|| {
+                    let listeners = listeners.clone();
+                    Box::pin(async move { listeners[i].accept().await })
+                });
+                if let Poll::Ready(res) = fut.as_mut().poll(cx) {
+                    self.pending[i] = None;
+                    // Prefer the other listeners next time.
+                    self.next = (i + 1) % len;
+                    return Poll::Ready(res);
+                }
+            }
+            Poll::Pending
+        })
+        .await;
+        let (stream, addr) = res?;
+        let addr = match addr {
+            SocketAddr::V6(v6) => match v6.ip().to_ipv4_mapped() {
+                Some(ip) => SocketAddr::from((ip, v6.port())),
+                None => addr,
+            },
+            SocketAddr::V4(_) => addr,
+        };
+        Ok((stream, addr))
+    }
+}
+
+impl std::fmt::Debug for DualStackListener {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DualStackListener")
+            .field("listeners", &self.listeners)
+            .finish_non_exhaustive()
+    }
+}
+
 /// A TCP stream between a local and a remote socket.
 ///
 /// A TCP stream can either be created by connecting to an endpoint, via the
@@ -142,6 +745,14 @@ impl_attachable!(TcpListener, inner);
 /// stream.write("hello world!").await.unwrap();
 /// # })
 /// ```
+///
+/// # Concurrency
+///
+/// Only one read and one write could be in flight at the same time, because
+/// concurrent reads or writes would interleave the data. Use
+/// [`split`](TcpStream::split) or [`into_split`](TcpStream::into_split) to read
+/// and write concurrently, and serialize the writes from multiple tasks.
+/// Concurrent reads or writes panic in debug builds.
 #[derive(Debug)]
 pub struct TcpStream {
     inner: Socket,
@@ -149,38 +760,118 @@ pub struct TcpStream {
 
 impl TcpStream {
     /// Opens a TCP connection to a remote host.
+    ///
+    /// If `addr` resolves to multiple addresses, the connections are raced
+    /// with the Happy Eyeballs algorithm of RFC 8305: the IPv6 and IPv4
+    /// addresses are interleaved, and each attempt starts 250ms after the
+    /// previous one, or right after it fails. The first established
+    /// connection is returned, and the other attempts are cancelled.
     pub async fn connect(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
-        use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
+        super::race_addrs(addr, super::CONNECTION_ATTEMPT_DELAY, |addr| async move {
+            let socket = Self::connect_socket(addr)?;
+            socket.connect_async(&addr.into()).await?;
+            Ok(Self { inner: socket })
+        })
+        .await
+    }
 
-        super::each_addr(addr, |addr| async move {
-            let addr2 = SockAddr::from(addr);
-            let socket = if cfg!(windows) {
-                let bind_addr = if addr.is_ipv4() {
-                    SockAddr::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))
-                } else if addr.is_ipv6() {
-                    SockAddr::from(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0))
-                } else {
-                    return Err(io::Error::new(
-                        io::ErrorKind::AddrNotAvailable,
-                        "Unsupported address domain.",
-                    ));
-                };
-                Socket::bind(&bind_addr, Type::STREAM, Some(Protocol::TCP))?
+    // Create a socket to connect to `addr`, which is bound on Windows as
+    // `ConnectEx` requires.
+    fn connect_socket(addr: SocketAddr) -> io::Result<Socket> {
+        use std::net::{SocketAddrV4, SocketAddrV6};
+
+        if cfg!(windows) {
+            let bind_addr = if addr.is_ipv4() {
+                SockAddr::from(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0))
+            } else if addr.is_ipv6() {
+                SockAddr::from(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0))
             } else {
-                Socket::new(addr2.domain(), Type::STREAM, Some(Protocol::TCP))?
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrNotAvailable,
+                    "Unsupported address domain.",
+                ));
             };
-            socket.connect_async(&addr2).await?;
+            Socket::bind(&bind_addr, Type::STREAM, Some(Protocol::TCP))
+        } else {
+            Socket::new(
+                SockAddr::from(addr).domain(),
+                Type::STREAM,
+                Some(Protocol::TCP),
+            )
+        }
+    }
+
+    /// Opens a TCP connection to a remote host like
+    /// [`connect`](Self::connect), through the network device `interface`,
+    /// e.g. `eth0` or a VPN tunnel, regardless of the routing table.
+    ///
+    /// See [`bind_device`](Self::bind_device).
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub async fn connect_device(
+        addr: impl ToSocketAddrsAsync,
+        interface: &str,
+    ) -> io::Result<Self> {
+        super::race_addrs(addr, super::CONNECTION_ATTEMPT_DELAY, |addr| async move {
+            let socket = Self::connect_socket(addr)?;
+            socket.bind_device(Some(interface))?;
+            socket.connect_async(&addr.into()).await?;
             Ok(Self { inner: socket })
         })
         .await
     }
 
+    /// Opens a TCP connection to a remote host with TCP Fast Open, sending
+    /// `buffer` in the SYN to save a round trip, and returns the stream after
+    /// all of it is sent.
+    ///
+    /// It connects to the first resolved address. Without a TFO cookie of the
+    /// server, which is got by the first connection to it, or if TFO is
+    /// disabled by the system, the data is sent after the handshake instead.
+    /// The SYN could be replayed, so the data should be idempotent. When the
+    /// data is in the SYN, the connection failures are reported by the next
+    /// read or write.
+    #[cfg(any(windows, target_os = "linux", target_os = "android"))]
+    pub async fn connect_with<T: IoBuf>(
+        addr: impl ToSocketAddrsAsync,
+        buffer: T,
+    ) -> BufResult<Self, T> {
+        super::first_addr_buf(addr, buffer, |addr, buffer| async move {
+            let (socket, buffer) = buf_try!(Self::connect_socket(addr), buffer);
+            let BufResult(res, buffer) = socket.connect_with_data(&addr.into(), buffer).await;
+            let (sent, buffer) = buf_try!(res, buffer);
+            let mut stream = Self { inner: socket };
+            let BufResult(res, buffer) = stream.write_all(buffer.slice(sent..)).await;
+            BufResult(res.map(|_| stream), buffer.into_inner())
+        })
+        .await
+    }
+
+    /// Opens a TCP connection to a remote host, failing with
+    /// [`TimedOut`](io::ErrorKind::TimedOut) if it isn't established within
+    /// `timeout`, including the time resolving the address. The pending
+    /// connect is cancelled on timeout.
+    ///
+    /// It is an error to pass a zero `Duration`.
+    #[cfg(feature = "time")]
+    pub async fn connect_timeout(
+        addr: impl ToSocketAddrsAsync,
+        timeout: Duration,
+    ) -> io::Result<Self> {
+        crate::connect_timeout(timeout, Self::connect(addr)).await
+    }
+
     /// Close the socket. If the returned future is dropped before polling, the
     /// socket won't be closed.
     pub fn close(self) -> impl Future<Output = io::Result<()>> {
         self.inner.close()
     }
 
+    /// Close the connection abortively. The pending data is discarded, and a
+    /// RST is sent to the peer instead of a FIN.
+    pub fn abort(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.abort()
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
     /// It does not clear the attach state.
@@ -190,6 +881,240 @@ impl TcpStream {
         })
     }
 
+    /// Creates a stream from a [`std::net::TcpStream`], setting the nonblocking
+    /// mode needed by the driver.
+    pub fn from_std(stream: std::net::TcpStream) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(stream)?,
+        })
+    }
+
+    /// Converts it into a [`std::net::TcpStream`] in blocking mode.
+    pub fn into_std(self) -> io::Result<std::net::TcpStream> {
+        self.inner.into_std()
+    }
+
+    /// Gets the value of the `SO_LINGER` option on this socket.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.inner.linger()
+    }
+
+    /// Gets the CPU that handles the packets of this connection, i.e. the
+    /// value of `SO_INCOMING_CPU`.
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Sets the value of the `SO_LINGER` option on this socket.
+    ///
+    /// With a timeout set, [`close`](Self::close) waits for the pending data
+    /// to be sent, or the timeout to elapse.
+    pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+        self.inner.set_linger(linger)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.nodelay()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket, i.e.
+    /// whether the small writes are sent at once, instead of being coalesced
+    /// by the Nagle's algorithm.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    /// Gets the keepalive of this connection, or `None` if `SO_KEEPALIVE`
+    /// is not set. The options not supported by the platform are left
+    /// unset.
+    pub fn keepalive(&self) -> io::Result<Option<TcpKeepalive>> {
+        self.inner.tcp_keepalive()
+    }
+
+    /// Sets `SO_KEEPALIVE` and the keepalive options on this connection, or
+    /// clears `SO_KEEPALIVE` with `None`.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use compio_net::{TcpKeepalive, TcpListener, TcpStream};
+    ///
+    /// # compio_runtime::Runtime::new().unwrap().block_on(async {
+    /// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// let stream = TcpStream::connect(listener.local_addr().unwrap())
+    ///     .await
+    ///     .unwrap();
+    /// let keepalive = TcpKeepalive::new()
+    ///     .with_time(Duration::from_secs(60))
+    ///     .with_interval(Duration::from_secs(10))
+    ///     .with_retries(3);
+    /// stream.set_keepalive(Some(keepalive)).unwrap();
+    /// # })
+    /// ```
+    pub fn set_keepalive(&self, keepalive: Option<TcpKeepalive>) -> io::Result<()> {
+        self.inner.set_tcp_keepalive(keepalive.as_ref())
+    }
+
+    /// Gets the value of the `IP_TTL` option on this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Sets the value of the `IP_TTL` option on this socket, i.e. the
+    /// time-to-live of every IPv4 packet sent.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket, i.e. the size
+    /// of the kernel receive buffer. The kernel may adjust it, e.g. double it
+    /// on Linux.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket, i.e. the size
+    /// of the kernel send buffer. The kernel may adjust it, e.g. double it on
+    /// Linux.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    ///
+    /// On Windows, this sets whether the socket handle could be inherited.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
+    /// Gets the pacing rate limit of this socket in bytes per second, i.e.
+    /// `SO_MAX_PACING_RATE`, or `None` if it is unlimited.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn max_pacing_rate(&self) -> io::Result<Option<u64>> {
+        self.inner.max_pacing_rate()
+    }
+
+    /// Limits the rate this socket sends at, in bytes per second, with
+    /// `SO_MAX_PACING_RATE`. `None` removes the limit.
+    ///
+    /// The kernel spreads the segments over time instead of sending a window
+    /// at once. TCP paces itself since Linux 4.13; see [`Pacer`](crate::Pacer)
+    /// for the pacing in user space.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_max_pacing_rate(&self, rate: Option<u64>) -> io::Result<()> {
+        self.inner.set_max_pacing_rate(rate)
+    }
+
+    /// Gets whether the stream is corked by [`set_cork`](Self::set_cork).
+    pub fn cork(&self) -> bool {
+        self.inner.cork()
+    }
+
+    /// Sets whether to cork the stream, with `TCP_CORK` on Linux, or
+    /// `TCP_NOPUSH` on BSD and macOS.
+    ///
+    /// When corked, the partial frames are queued instead of being sent
+    /// immediately. Each [`flush`](AsyncWrite::flush) sends the queued
+    /// frames and keeps corking, so the writes before it, e.g. the header and
+    /// the body of a response, are coalesced into fewer segments. Uncorking
+    /// sends the queued frames too.
+    ///
+    /// It returns [`io::ErrorKind::Unsupported`] on other platforms.
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        self.inner.set_cork(cork)
+    }
+
+    /// Gets the value of the `IP_TOS` option on this socket.
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn tos(&self) -> io::Result<u32> {
+        self.inner.tos()
+    }
+
+    /// Sets the value of the `IP_TOS` option on this socket, i.e. the type of
+    /// service (DSCP and ECN) field of every IPv4 packet sent.
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.inner.set_tos(tos)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option on this socket.
+    #[cfg(unix)]
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.inner.tclass_v6()
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option on this socket, i.e. the
+    /// traffic class field of every IPv6 packet sent.
+    #[cfg(unix)]
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.inner.set_tclass_v6(tclass)
+    }
+
+    /// Gets the network device this socket is bound to, i.e.
+    /// `SO_BINDTODEVICE` on Linux, and `IP_BOUND_IF` on Apple platforms.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn device(&self) -> io::Result<Option<String>> {
+        self.inner.device()
+    }
+
+    /// Binds this socket to the network device `interface`, e.g. `eth0`, so
+    /// that the packets are only sent and received through it. `None` removes
+    /// the binding.
+    ///
+    /// It may need `CAP_NET_RAW` on Linux. To connect through the device, see
+    /// [`connect_device`](Self::connect_device).
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn bind_device(&self, interface: Option<&str>) -> io::Result<()> {
+        self.inner.bind_device(interface)
+    }
+
     /// Returns the socket address of the remote peer of this TCP connection.
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
         self.inner
@@ -204,13 +1129,162 @@ impl TcpStream {
             .map(|addr| addr.as_socket().expect("should be SocketAddr"))
     }
 
+    /// Sends some data with the `MSG_*` flags, e.g. `MSG_MORE` on Linux,
+    /// returning the original buffer and quantity of data sent.
+    ///
+    /// The flags are passed to the system call as is.
+    pub async fn send_with_flags<T: IoBuf>(&self, buffer: T, flags: i32) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_with_flags(buffer, flags).await
+    }
+
+    /// Sends some data without copying it, returning the original buffer and
+    /// quantity of data sent.
+    ///
+    /// The buffer is returned after the kernel notifies that its pages are no
+    /// longer referenced, with `IORING_OP_SEND_ZC` on io-uring driver, which
+    /// needs Linux 6.0. It is an ordinary send on older kernels and other
+    /// drivers. The
+    /// notification costs more than copying a small buffer, so it only pays
+    /// off for large payloads.
+    pub async fn send_zc<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_zc(buffer).await
+    }
+
+    /// Sends `len` bytes of the file at `offset`, returning the bytes sent,
+    /// which are fewer if the file ends first.
+    ///
+    /// The file is sent without copying it through the userspace, with
+    /// `splice` on Linux and `TransmitFile` on Windows. Other platforms fall
+    /// back to reading the file into a buffer.
+    pub async fn send_file(
+        &self,
+        file: &impl TryAsRawFd,
+        offset: u64,
+        len: usize,
+    ) -> io::Result<usize> {
+        let _guard = self.inner.write_guard();
+        crate::send_file(file, &self.inner, offset, len).await
+    }
+
+    /// Receives the data from the peer without removing it from the receive
+    /// queue, so the next read returns the same bytes. It is useful to sniff
+    /// the protocol before handing the stream to its handler, e.g. telling a
+    /// TLS handshake from plaintext.
+    ///
+    /// Like [`read`](compio_io::AsyncRead::read), it waits until some data is
+    /// available, and may return fewer bytes than are buffered.
+    pub async fn peek<B: IoBufMut>(&self, buffer: B) -> BufResult<usize, B> {
+        let _guard = self.inner.read_guard();
+        self.inner.peek(buffer).await
+    }
+
+    /// Receives the data repeatedly into the buffers of `pool`, until the
+    /// peer shuts down the connection or an error occurs.
+    ///
+    /// On io-uring driver since Linux 6.0, the pool is provided to the
+    /// kernel, and a multishot receive picks a buffer only when the data
+    /// arrives, so that an idle connection doesn't hold any buffer. On other
+    /// drivers and older kernels, it takes a buffer for each receive.
+    ///
+    /// The buffers return to the pool when dropped. When all of them are
+    /// borrowed, the stream yields an error of `ENOBUFS`, and receives again
+    /// on the next poll.
+    ///
+    /// ```
+    /// use compio_net::{TcpListener, TcpStream};
+    /// use compio_runtime::Runtime;
+    /// use futures_util::StreamExt;
+    ///
+    /// # Runtime::new().unwrap().block_on(async {
+    /// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    /// let addr = listener.local_addr().unwrap();
+    /// let (client, (server, _)) =
+    ///     futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    ///
+    /// let pool = Runtime::current().create_buffer_pool(16, 1024).unwrap();
+    /// let mut received = std::pin::pin!(server.recv_stream(&pool));
+    /// compio_io::AsyncWriteExt::write_all(&mut &client, "hello")
+    ///     .await
+    ///     .0
+    ///     .unwrap();
+    /// let buffer = received.next().await.unwrap().unwrap();
+    /// assert_eq!(&buffer[..], b"hello");
+    /// # })
+    /// ```
+    pub fn recv_stream<'a>(
+        &'a self,
+        pool: &'a BufferPool,
+    ) -> impl Stream<Item = io::Result<BorrowedBuffer>> + 'a {
+        let guard = self.inner.read_guard();
+        self.inner.recv_stream(pool).map(move |res| {
+            let _guard = &guard;
+            res
+        })
+    }
+
+    /// Sends the buffer as out-of-band (urgent) data.
+    ///
+    /// Only the last byte is marked as urgent by most TCP implementations.
+    pub async fn send_oob<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send_with_flags(buffer, MSG_OOB).await
+    }
+
+    /// Receives the out-of-band (urgent) data.
+    ///
+    /// It fails immediately if there is no urgent data pending, or if
+    /// [`set_oob_inline`](Self::set_oob_inline) is enabled.
+    pub async fn recv_oob<B: IoBufMut>(&self, buffer: B) -> BufResult<usize, B> {
+        self.inner.recv_with_flags(buffer, MSG_OOB).await
+    }
+
+    /// Gets the value of the `SO_OOBINLINE` option on this socket.
+    pub fn oob_inline(&self) -> io::Result<bool> {
+        self.inner.oob_inline()
+    }
+
+    /// Sets the value of the `SO_OOBINLINE` option on this socket.
+    ///
+    /// If set, the out-of-band data is received in the normal data stream.
+    pub fn set_oob_inline(&self, oob_inline: bool) -> io::Result<()> {
+        self.inner.set_oob_inline(oob_inline)
+    }
+
+    /// Receives data into the end of `buffer`, and returns the grown buffer.
+    ///
+    /// The capacity of `buffer` is reserved for the bytes pending on the
+    /// socket, or at least `hint` bytes if fewer are known to be available.
+    pub async fn recv_buf(&self, buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        self.inner.recv_buf(buffer, hint).await
+    }
+
+    /// Sends the data at the front of `buffer`, and returns the rest of it.
+    ///
+    /// The sent bytes are removed from `buffer`, so that more data could be
+    /// appended to it before sending the rest. The allocation is kept.
+    pub async fn send_buf(&self, buffer: Vec<u8>) -> BufResult<usize, Vec<u8>> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_buf(buffer).await
+    }
+
+    /// Receives data into the end of `buffer` until the socket is drained,
+    /// reserving at least `hint` bytes for each receive. It waits only for the
+    /// first bytes, and returns the total length received.
+    ///
+    /// On io-uring, the completion tells whether more data is pending, so no
+    /// extra receive or syscall is needed to find the end.
+    pub async fn recv_pending(&self, buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        self.inner.recv_pending(buffer, hint).await
+    }
+
     /// Splits a [`TcpStream`] into a read half and a write half, which can be
     /// used to read and write the stream concurrently.
     ///
     /// This method is more efficient than
     /// [`into_split`](TcpStream::into_split), but the halves cannot
     /// be moved into independently spawned tasks.
-    pub fn split(&self) -> (ReadHalf<Self>, WriteHalf<Self>) {
+    pub fn split(&self) -> (ReadHalf<'_, Self>, WriteHalf<'_, Self>) {
         crate::split(self)
     }
 
@@ -239,11 +1313,13 @@ impl AsyncRead for TcpStream {
 impl AsyncRead for &TcpStream {
     #[inline]
     async fn read<B: IoBufMut>(&mut self, buf: B) -> BufResult<usize, B> {
+        let _guard = self.inner.read_guard();
         self.inner.recv(buf).await
     }
 
     #[inline]
     async fn read_vectored<V: IoVectoredBufMut>(&mut self, buf: V) -> BufResult<usize, V> {
+        let _guard = self.inner.read_guard();
         self.inner.recv_vectored(buf).await
     }
 }
@@ -273,17 +1349,19 @@ impl AsyncWrite for TcpStream {
 impl AsyncWrite for &TcpStream {
     #[inline]
     async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
         self.inner.send(buf).await
     }
 
     #[inline]
     async fn write_vectored<T: IoVectoredBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
         self.inner.send_vectored(buf).await
     }
 
     #[inline]
     async fn flush(&mut self) -> io::Result<()> {
-        Ok(())
+        self.inner.push_corked()
     }
 
     #[inline]
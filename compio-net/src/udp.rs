@@ -1,11 +1,106 @@
-use std::{future::Future, io, net::SocketAddr};
+use std::{
+    future::Future,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+};
 
 use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+#[cfg(target_os = "linux")]
+use compio_runtime::RawFd;
 use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
 use socket2::{Protocol, SockAddr, Type};
 
 use crate::{Socket, ToSocketAddrsAsync};
 
+/// The path MTU discovery mode of a socket, i.e. `IP_MTU_DISCOVER` and
+/// `IPV6_MTU_DISCOVER`.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MtuDiscover {
+    /// Never set the DF flag, and fragment the datagrams if needed.
+    Dont,
+    /// Use the per-route setting.
+    Want,
+    /// Always set the DF flag. The datagrams larger than the path MTU fail
+    /// with `EMSGSIZE`.
+    Do,
+    /// Set the DF flag and ignore the path MTU, to probe it.
+    Probe,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl MtuDiscover {
+    fn to_raw(self) -> libc::c_int {
+        match self {
+            Self::Dont => libc::IP_PMTUDISC_DONT,
+            Self::Want => libc::IP_PMTUDISC_WANT,
+            Self::Do => libc::IP_PMTUDISC_DO,
+            Self::Probe => libc::IP_PMTUDISC_PROBE,
+        }
+    }
+
+    fn from_raw(mode: libc::c_int) -> io::Result<Self> {
+        match mode {
+            libc::IP_PMTUDISC_DONT => Ok(Self::Dont),
+            libc::IP_PMTUDISC_WANT => Ok(Self::Want),
+            libc::IP_PMTUDISC_DO => Ok(Self::Do),
+            libc::IP_PMTUDISC_PROBE => Ok(Self::Probe),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unknown MTU discover mode {mode}"),
+            )),
+        }
+    }
+}
+
+/// The error of a datagram larger than the path MTU, i.e. `EMSGSIZE`, with
+/// the MTU known by the kernel.
+///
+/// The sends of [`UdpSocket`] return it in an [`io::Error`] of the same kind,
+/// which could be recovered with [`MessageTooLong::get`]. The MTU is only
+/// known on a connected socket.
+#[cfg(any(target_os = "android", target_os = "linux"))]
+#[derive(Debug)]
+pub struct MessageTooLong {
+    mtu: Option<usize>,
+    source: io::Error,
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl MessageTooLong {
+    /// The path MTU to retry with, like [`UdpSocket::mtu`].
+    pub fn mtu(&self) -> Option<usize> {
+        self.mtu
+    }
+
+    /// The underlying error.
+    pub fn io_error(&self) -> &io::Error {
+        &self.source
+    }
+
+    /// Get the error from an [`io::Error`] returned by a send.
+    pub fn get(err: &io::Error) -> Option<&Self> {
+        err.get_ref()?.downcast_ref()
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl std::fmt::Display for MessageTooLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.mtu {
+            Some(mtu) => write!(f, "{} (path MTU {mtu})", self.source),
+            None => write!(f, "{}", self.source),
+        }
+    }
+}
+
+#[cfg(any(target_os = "android", target_os = "linux"))]
+impl std::error::Error for MessageTooLong {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
 /// A UDP socket.
 ///
 /// UDP is "connectionless", unlike TCP. Meaning, regardless of what address
@@ -101,6 +196,21 @@ impl UdpSocket {
         .await
     }
 
+    /// Creates a new `UdpSocket` with `SO_REUSEPORT`, which will be bound
+    /// to the specified address.
+    ///
+    /// Multiple sockets could be bound to the same address, e.g. one per
+    /// thread, and the kernel distributes the incoming datagrams among them.
+    #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+    pub async fn bind_reuse_port(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
+        super::each_addr(addr, |addr| async move {
+            let socket =
+                Socket::bind_reuse_port(&SockAddr::from(addr), Type::DGRAM, Some(Protocol::UDP))?;
+            Ok(Self { inner: socket })
+        })
+        .await
+    }
+
     /// Connects this UDP socket to a remote address, allowing the `send` and
     /// `recv` to be used to send data and also applies filters to only
     /// receive data from the specified address.
@@ -130,6 +240,408 @@ impl UdpSocket {
         })
     }
 
+    /// Creates a socket from a [`std::net::UdpSocket`], e.g. one passed by
+    /// systemd or launchd with socket activation. It is set to the
+    /// nonblocking mode needed by the driver, and attached to the driver of
+    /// the current runtime when first used.
+    pub fn from_std(socket: std::net::UdpSocket) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(socket)?,
+        })
+    }
+
+    /// Converts it into a [`std::net::UdpSocket`] in blocking mode, to hand it
+    /// off to other libraries.
+    pub fn into_std(self) -> io::Result<std::net::UdpSocket> {
+        self.inner.into_std()
+    }
+
+    /// Gets the value of the `IP_TTL` option on this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.inner.ttl()
+    }
+
+    /// Sets the value of the `IP_TTL` option on this socket, i.e. the
+    /// time-to-live of every IPv4 packet sent.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.inner.recv_buffer_size()
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket, i.e. the size
+    /// of the kernel receive buffer. The kernel may adjust it, e.g. double it
+    /// on Linux.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size)
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.inner.send_buffer_size()
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket, i.e. the size
+    /// of the kernel send buffer. The kernel may adjust it, e.g. double it on
+    /// Linux.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size)
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    ///
+    /// On Windows, this sets whether the socket handle could be inherited.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
+    /// Gets the value of the `IP_TOS` option on this socket.
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn tos(&self) -> io::Result<u32> {
+        self.inner.tos()
+    }
+
+    /// Sets the value of the `IP_TOS` option on this socket, i.e. the type of
+    /// service (DSCP and ECN) field of every IPv4 packet sent.
+    #[cfg(not(any(
+        target_os = "fuchsia",
+        target_os = "redox",
+        target_os = "solaris",
+        target_os = "illumos",
+        target_os = "haiku"
+    )))]
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.inner.set_tos(tos)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option on this socket.
+    #[cfg(unix)]
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.inner.tclass_v6()
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option on this socket, i.e. the
+    /// traffic class field of every IPv6 packet sent.
+    #[cfg(unix)]
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.inner.set_tclass_v6(tclass)
+    }
+
+    /// Gets the value of the `IP_RECVTOS` option on this socket.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn recv_tos(&self) -> io::Result<bool> {
+        self.inner.recv_tos()
+    }
+
+    /// Sets `IP_RECVTOS`, so that the TOS field of every IPv4 datagram
+    /// received with [`recv_msg`](Self::recv_msg) is in a control message,
+    /// read by [`CMsg::tos`](crate::CMsg::tos).
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn set_recv_tos(&self, recv: bool) -> io::Result<()> {
+        self.inner.set_recv_tos(recv)
+    }
+
+    /// Gets the value of the `IPV6_RECVTCLASS` option on this socket.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn recv_tclass_v6(&self) -> io::Result<bool> {
+        self.inner.recv_tclass_v6()
+    }
+
+    /// Sets `IPV6_RECVTCLASS`, so that the traffic class of every IPv6
+    /// datagram received with [`recv_msg`](Self::recv_msg) is in a control
+    /// message, read by [`CMsg::tos`](crate::CMsg::tos).
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn set_recv_tclass_v6(&self, recv: bool) -> io::Result<()> {
+        self.inner.set_recv_tclass_v6(recv)
+    }
+
+    /// Joins the IPv4 multicast group `multiaddr` on the interface with the
+    /// address `interface`. Use [`Ipv4Addr::UNSPECIFIED`] to let the system
+    /// choose the interface.
+    pub fn join_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner.join_multicast_v4(multiaddr, interface)
+    }
+
+    /// Leaves the IPv4 multicast group joined by
+    /// [`join_multicast_v4`](Self::join_multicast_v4).
+    pub fn leave_multicast_v4(&self, multiaddr: &Ipv4Addr, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner.leave_multicast_v4(multiaddr, interface)
+    }
+
+    /// Joins the IPv6 multicast group `multiaddr` on the interface with the
+    /// index `interface`. Use `0` to let the system choose the interface.
+    pub fn join_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner.join_multicast_v6(multiaddr, interface)
+    }
+
+    /// Leaves the IPv6 multicast group joined by
+    /// [`join_multicast_v6`](Self::join_multicast_v6).
+    pub fn leave_multicast_v6(&self, multiaddr: &Ipv6Addr, interface: u32) -> io::Result<()> {
+        self.inner.leave_multicast_v6(multiaddr, interface)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_TTL` option on this socket.
+    pub fn multicast_ttl_v4(&self) -> io::Result<u32> {
+        self.inner.multicast_ttl_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_TTL` option on this socket, i.e.
+    /// the time-to-live of the IPv4 multicast datagrams sent. The default 1
+    /// keeps them in the local network.
+    pub fn set_multicast_ttl_v4(&self, ttl: u32) -> io::Result<()> {
+        self.inner.set_multicast_ttl_v4(ttl)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_HOPS` option on this socket.
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        self.inner.multicast_hops_v6()
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_HOPS` option on this socket, the
+    /// IPv6 counterpart of
+    /// [`set_multicast_ttl_v4`](Self::set_multicast_ttl_v4).
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        self.inner.set_multicast_hops_v6(hops)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_LOOP` option on this socket.
+    pub fn multicast_loop_v4(&self) -> io::Result<bool> {
+        self.inner.multicast_loop_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_LOOP` option on this socket, i.e.
+    /// whether the IPv4 multicast datagrams sent are looped back to the local
+    /// sockets.
+    pub fn set_multicast_loop_v4(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.set_multicast_loop_v4(multicast_loop)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_LOOP` option on this socket.
+    pub fn multicast_loop_v6(&self) -> io::Result<bool> {
+        self.inner.multicast_loop_v6()
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_LOOP` option on this socket, the
+    /// IPv6 counterpart of
+    /// [`set_multicast_loop_v4`](Self::set_multicast_loop_v4).
+    pub fn set_multicast_loop_v6(&self, multicast_loop: bool) -> io::Result<()> {
+        self.inner.set_multicast_loop_v6(multicast_loop)
+    }
+
+    /// Gets the value of the `IP_MULTICAST_IF` option on this socket.
+    pub fn multicast_if_v4(&self) -> io::Result<Ipv4Addr> {
+        self.inner.multicast_if_v4()
+    }
+
+    /// Sets the value of the `IP_MULTICAST_IF` option on this socket, i.e.
+    /// the address of the interface to send the IPv4 multicast datagrams.
+    pub fn set_multicast_if_v4(&self, interface: &Ipv4Addr) -> io::Result<()> {
+        self.inner.set_multicast_if_v4(interface)
+    }
+
+    /// Gets the value of the `IPV6_MULTICAST_IF` option on this socket.
+    pub fn multicast_if_v6(&self) -> io::Result<u32> {
+        self.inner.multicast_if_v6()
+    }
+
+    /// Sets the value of the `IPV6_MULTICAST_IF` option on this socket, i.e.
+    /// the index of the interface to send the IPv6 multicast datagrams.
+    pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+        self.inner.set_multicast_if_v6(interface)
+    }
+
+    /// Gets the CPU that handles the packets of this socket, i.e. the value
+    /// of `SO_INCOMING_CPU`.
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<usize> {
+        self.inner.incoming_cpu()
+    }
+
+    /// Sets the value of `SO_INCOMING_CPU`.
+    ///
+    /// In a `SO_REUSEPORT` group, the datagrams received on `cpu` prefer this
+    /// socket.
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: usize) -> io::Result<()> {
+        self.inner.set_incoming_cpu(cpu)
+    }
+
+    /// Gets the network device this socket is bound to, i.e.
+    /// `SO_BINDTODEVICE` on Linux, and `IP_BOUND_IF` on Apple platforms.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn device(&self) -> io::Result<Option<String>> {
+        self.inner.device()
+    }
+
+    /// Binds this socket to the network device `interface`, e.g. `eth0`, so
+    /// that the packets are only sent and received through it. `None` removes
+    /// the binding.
+    ///
+    /// It may need `CAP_NET_RAW` on Linux.
+    #[cfg(any(target_os = "android", target_os = "linux", target_vendor = "apple"))]
+    pub fn bind_device(&self, interface: Option<&str>) -> io::Result<()> {
+        self.inner.bind_device(interface)
+    }
+
+    /// Steers the datagrams by the CPU receiving them, with a classic BPF
+    /// program attached to the `SO_REUSEPORT` group of this socket.
+    ///
+    /// The datagrams received on CPU `n` go to the socket at index
+    /// `n % group_size` of the group, i.e. the order the sockets are bound.
+    /// Bind one socket per core with [`bind_reuse_port`](Self::bind_reuse_port)
+    /// on the runtime pinned to that core, so the datagrams are handled on the
+    /// CPU that received the interrupt.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cpu(&self, group_size: u32) -> io::Result<()> {
+        self.inner.attach_reuseport_cpu(group_size)
+    }
+
+    /// Attaches an eBPF program of type `BPF_PROG_TYPE_SOCKET_REUSEPORT` to
+    /// the `SO_REUSEPORT` group of this socket, to select the socket of the
+    /// datagram.
+    ///
+    /// The program should be loaded elsewhere, and its file descriptor could
+    /// be closed after it is attached.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog_fd: RawFd) -> io::Result<()> {
+        self.inner.attach_reuseport_ebpf(prog_fd)
+    }
+
+    /// Attaches a classic BPF filter to this socket, and the datagrams
+    /// rejected by it are dropped by the kernel.
+    ///
+    /// The datagrams queued before it is attached are not filtered.
+    #[cfg(target_os = "linux")]
+    pub fn attach_filter(&self, filter: &crate::SocketFilter) -> io::Result<()> {
+        self.inner.attach_filter(filter.as_raw())
+    }
+
+    /// Detaches the filter attached by [`attach_filter`](Self::attach_filter).
+    #[cfg(target_os = "linux")]
+    pub fn detach_filter(&self) -> io::Result<()> {
+        self.inner.detach_filter()
+    }
+
+    /// Gets the path MTU discovery mode of this socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu_discover(&self) -> io::Result<MtuDiscover> {
+        MtuDiscover::from_raw(self.inner.mtu_discover()?)
+    }
+
+    /// Sets the path MTU discovery mode of this socket.
+    ///
+    /// With [`MtuDiscover::Do`] or [`MtuDiscover::Probe`], a datagram larger
+    /// than the known path MTU fails to send with `EMSGSIZE`, as a
+    /// [`MessageTooLong`] carrying the MTU to retry with.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_mtu_discover(&self, mode: MtuDiscover) -> io::Result<()> {
+        self.inner.set_mtu_discover(mode.to_raw())
+    }
+
+    /// Gets the pacing rate limit of this socket in bytes per second, i.e.
+    /// `SO_MAX_PACING_RATE`, or `None` if it is unlimited.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn max_pacing_rate(&self) -> io::Result<Option<u64>> {
+        self.inner.max_pacing_rate()
+    }
+
+    /// Limits the rate this socket sends at, in bytes per second, with
+    /// `SO_MAX_PACING_RATE`. `None` removes the limit.
+    ///
+    /// The datagrams are only paced by the `fq` queueing discipline; see
+    /// [`Pacer`](crate::Pacer) for the pacing in user space.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_max_pacing_rate(&self, rate: Option<u64>) -> io::Result<()> {
+        self.inner.set_max_pacing_rate(rate)
+    }
+
+    /// Gets the known path MTU of this socket, i.e. `IP_MTU` and `IPV6_MTU`.
+    ///
+    /// The socket must be connected.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn mtu(&self) -> io::Result<usize> {
+        self.inner.mtu()
+    }
+
+    // Attach the path MTU to `EMSGSIZE`.
+    fn with_mtu<T, B>(&self, res: BufResult<T, B>) -> BufResult<T, B> {
+        #[cfg(any(target_os = "android", target_os = "linux"))]
+        if let BufResult(Err(source), buffer) = res {
+            if source.raw_os_error() != Some(libc::EMSGSIZE) {
+                return BufResult(Err(source), buffer);
+            }
+            let mtu = self.mtu().ok();
+            let err = io::Error::new(source.kind(), MessageTooLong { mtu, source });
+            return BufResult(Err(err), buffer);
+        }
+        res
+    }
+
+    /// Gets the default segment size of the generic segmentation offload
+    /// (GSO), i.e. `UDP_SEGMENT`, or 0 if it is disabled.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn gso_segment(&self) -> io::Result<u16> {
+        self.inner.gso_segment()
+    }
+
+    /// Sets `UDP_SEGMENT`, so that every send larger than `size` is split
+    /// into datagrams of `size` bytes by the kernel or the NIC. 0 disables
+    /// it. See [`send_segmented`](Self::send_segmented) to set the size per
+    /// send.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_gso_segment(&self, size: u16) -> io::Result<()> {
+        self.inner.set_gso_segment(size)
+    }
+
+    /// Gets the value of the `UDP_GRO` option on this socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn gro(&self) -> io::Result<bool> {
+        self.inner.gro()
+    }
+
+    /// Sets `UDP_GRO`, so that the datagrams of a flow with the same size
+    /// could be coalesced, and received at once with
+    /// [`recv_from_segmented`](Self::recv_from_segmented).
+    ///
+    /// The plain receives still get the coalesced datagrams, without the
+    /// boundaries of them.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_gro(&self, gro: bool) -> io::Result<()> {
+        self.inner.set_gro(gro)
+    }
+
     /// Returns the socket address of the remote peer this socket was connected
     /// to.
     ///
@@ -191,6 +703,21 @@ impl UdpSocket {
         self.inner.recv(buffer).await
     }
 
+    /// Receives a packet of data from the connected peer like
+    /// [`recv`](Self::recv), without removing it from the queue.
+    pub async fn peek<T: IoBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.peek(buffer).await
+    }
+
+    /// Receives a packet of data into the end of `buffer`, and returns the
+    /// grown buffer.
+    ///
+    /// The capacity of `buffer` is reserved for the size of the pending
+    /// packet, or at least `hint` bytes if it is unknown.
+    pub async fn recv_buf(&self, buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        self.inner.recv_buf(buffer, hint).await
+    }
+
     /// Receives a packet of data from the socket into the buffer, returning the
     /// original buffer and quantity of data received.
     pub async fn recv_vectored<T: IoVectoredBufMut>(&self, buffer: T) -> BufResult<usize, T> {
@@ -200,13 +727,27 @@ impl UdpSocket {
     /// Sends some data to the socket from the buffer, returning the original
     /// buffer and quantity of data sent.
     pub async fn send<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
-        self.inner.send(buffer).await
+        self.with_mtu(self.inner.send(buffer).await)
+    }
+
+    /// Sends `buffer` as a packet to the connected peer, and returns it
+    /// cleared, so that the allocation could be reused for the next packet.
+    pub async fn send_buf(&self, buffer: Vec<u8>) -> BufResult<usize, Vec<u8>> {
+        self.with_mtu(self.inner.send_buf(buffer).await)
+    }
+
+    /// Sends some data with the `MSG_*` flags, e.g. `MSG_MORE` on Linux,
+    /// returning the original buffer and quantity of data sent.
+    ///
+    /// The flags are passed to the system call as is.
+    pub async fn send_with_flags<T: IoBuf>(&self, buffer: T, flags: i32) -> BufResult<usize, T> {
+        self.with_mtu(self.inner.send_with_flags(buffer, flags).await)
     }
 
     /// Sends some data to the socket from the buffer, returning the original
     /// buffer and quantity of data sent.
     pub async fn send_vectored<T: IoVectoredBuf>(&self, buffer: T) -> BufResult<usize, T> {
-        self.inner.send_vectored(buffer).await
+        self.with_mtu(self.inner.send_vectored(buffer).await)
     }
 
     /// Receives a single datagram message on the socket. On success, returns
@@ -218,6 +759,16 @@ impl UdpSocket {
             .map_res(|(n, addr)| (n, addr.as_socket().expect("should be SocketAddr")))
     }
 
+    /// Receives a single datagram message on the socket like
+    /// [`recv_from`](Self::recv_from), without removing it from the queue, so
+    /// the next receive returns the same datagram.
+    pub async fn peek_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SocketAddr), T> {
+        self.inner
+            .peek_from(buffer)
+            .await
+            .map_res(|(n, addr)| (n, addr.as_socket().expect("should be SocketAddr")))
+    }
+
     /// Receives a single datagram message on the socket. On success, returns
     /// the number of bytes received and the origin.
     pub async fn recv_from_vectored<T: IoVectoredBufMut>(
@@ -237,10 +788,194 @@ impl UdpSocket {
         buffer: T,
         addr: impl ToSocketAddrsAsync,
     ) -> BufResult<usize, T> {
-        super::first_addr_buf(addr, buffer, |addr, buffer| async move {
+        let res = super::first_addr_buf(addr, buffer, |addr, buffer| async move {
             self.inner.send_to(buffer, &SockAddr::from(addr)).await
         })
-        .await
+        .await;
+        self.with_mtu(res)
+    }
+
+    /// Sends data on the socket to the given address with the `MSG_*` flags.
+    /// On success, returns the number of bytes sent.
+    ///
+    /// With `MSG_MORE` on Linux, the data is held until a send without it,
+    /// and they are sent as one datagram.
+    pub async fn send_to_with_flags<T: IoBuf>(
+        &self,
+        buffer: T,
+        addr: impl ToSocketAddrsAsync,
+        flags: i32,
+    ) -> BufResult<usize, T> {
+        let res = super::first_addr_buf(addr, buffer, |addr, buffer| async move {
+            self.inner
+                .send_to_with_flags(buffer, &SockAddr::from(addr), flags)
+                .await
+        })
+        .await;
+        self.with_mtu(res)
+    }
+
+    /// Receives a single datagram message on the socket, and the ancillary
+    /// data into `control`, iterated with [`CMsgIter`]. On success, returns
+    /// the number of bytes received and the origin.
+    ///
+    /// [`CMsgIter`]: crate::CMsgIter
+    #[cfg(unix)]
+    pub async fn recv_msg<T: IoVectoredBufMut, C: IoBufMut>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<(usize, SocketAddr), (T, C)> {
+        self.inner
+            .recv_msg(buffer, control)
+            .await
+            .map_res(|(n, addr)| (n, addr.as_socket().expect("should be SocketAddr")))
+    }
+
+    /// Sends data on the socket to the given address accompanied by the
+    /// ancillary data in `control`, e.g. built with [`CMsgBuilder`]. On
+    /// success, returns the number of bytes sent.
+    ///
+    /// [`CMsgBuilder`]: crate::CMsgBuilder
+    pub async fn send_msg<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+        addr: impl ToSocketAddrsAsync,
+    ) -> BufResult<usize, (T, C)> {
+        let res = super::first_addr_buf(
+            addr,
+            (buffer, control),
+            |addr, (buffer, control)| async move {
+                self.inner
+                    .send_msg(buffer, control, &SockAddr::from(addr))
+                    .await
+            },
+        )
+        .await;
+        self.with_mtu(res)
+    }
+
+    /// Sends `buffer` to the connected peer as the datagrams of
+    /// `segment_size` bytes, with the generic segmentation offload (GSO), i.e.
+    /// `UDP_SEGMENT`. The last datagram could be shorter.
+    ///
+    /// It sends up to 64 datagrams at once, with one system call, and the
+    /// length of `buffer` is limited by the maximum size of a UDP datagram.
+    /// It fails with `EIO` if the NIC doesn't support the checksum offload.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn send_segmented<T: IoBuf>(
+        &self,
+        buffer: T,
+        segment_size: u16,
+    ) -> BufResult<usize, T> {
+        let res = self
+            .inner
+            .send_msg_connected([buffer], segment_control(segment_size))
+            .await;
+        self.with_mtu(res).map_buffer(|([buffer], _)| buffer)
+    }
+
+    /// Sends `buffer` to the given address as the datagrams of
+    /// `segment_size` bytes, like [`send_segmented`](Self::send_segmented).
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn send_to_segmented<T: IoBuf>(
+        &self,
+        buffer: T,
+        segment_size: u16,
+        addr: impl ToSocketAddrsAsync,
+    ) -> BufResult<usize, T> {
+        self.send_msg([buffer], segment_control(segment_size), addr)
+            .await
+            .map_buffer(|([buffer], _)| buffer)
+    }
+
+    /// Receives the datagrams coalesced by the generic receive offload
+    /// (GRO), enabled with [`set_gro`](Self::set_gro). On success, returns
+    /// the number of bytes received, the origin, and the segment size.
+    ///
+    /// The received bytes are the datagrams of the segment size, except that
+    /// the last one could be shorter, so they could be split with
+    /// `buffer.chunks(segment_size)`. The segment size is the number of bytes
+    /// received if the datagram is not coalesced. The buffer should be large
+    /// enough for 64KiB; the coalesced datagrams are truncated otherwise.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn recv_from_segmented<T: IoBufMut>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(usize, SocketAddr, usize), T> {
+        let space = unsafe { libc::CMSG_SPACE(std::mem::size_of::<libc::c_int>() as _) };
+        let control = Vec::with_capacity(space as usize);
+        self.inner.recv_msg([buffer], control).await.map2(
+            |(n, addr), ([buffer], control)| {
+                let segment_size = crate::CMsgIter::new(&control)
+                    .find(|msg| msg.level() == libc::SOL_UDP && msg.ty() == libc::UDP_GRO)
+                    .and_then(|msg| msg.data().try_into().ok())
+                    .map(|size| libc::c_int::from_ne_bytes(size) as usize)
+                    .unwrap_or(n);
+                let addr = addr.as_socket().expect("should be SocketAddr");
+                ((n, addr, segment_size), buffer)
+            },
+            |([buffer], _)| buffer,
+        )
+    }
+
+    /// Sends the datagrams in `buffers` to the connected peer in one
+    /// operation, i.e. `sendmmsg`. On success, returns the number of bytes
+    /// sent of each datagram.
+    ///
+    /// The datagrams are sent in order, and fewer of them may be sent; the
+    /// rest should be sent again.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn send_many<T: IoBuf>(&self, buffers: Vec<T>) -> BufResult<Vec<usize>, Vec<T>> {
+        self.inner.send_mmsg(buffers, vec![]).await
+    }
+
+    /// Sends the datagrams in `buffers` to the addresses of the same index,
+    /// like [`send_many`](Self::send_many).
+    ///
+    /// It fails with [`InvalidInput`](io::ErrorKind::InvalidInput) if the
+    /// numbers of the buffers and the addresses differ.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn send_many_to<T: IoBuf>(
+        &self,
+        buffers: Vec<T>,
+        addrs: &[SocketAddr],
+    ) -> BufResult<Vec<usize>, Vec<T>> {
+        if buffers.len() != addrs.len() {
+            return BufResult(
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the numbers of the buffers and the addresses differ",
+                )),
+                buffers,
+            );
+        }
+        let addrs = addrs.iter().map(|addr| SockAddr::from(*addr)).collect();
+        self.inner.send_mmsg(buffers, addrs).await
+    }
+
+    /// Receives up to one datagram per buffer in one operation, i.e.
+    /// `recvmmsg`. On success, returns the number of bytes received and the
+    /// origin of each datagram, in the order of `buffers`.
+    ///
+    /// It waits for one datagram, and doesn't wait for the rest of the
+    /// buffers to be filled. The buffers after the received datagrams are
+    /// untouched.
+    ///
+    /// There is no such opcode of io-uring, so with the io-uring driver, it
+    /// waits for the socket to be readable, and then receives the datagrams
+    /// on a thread of the pool.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub async fn recv_many<T: IoBufMut>(
+        &self,
+        buffers: Vec<T>,
+    ) -> BufResult<Vec<(usize, SocketAddr)>, Vec<T>> {
+        self.inner.recv_mmsg(buffers).await.map_res(|msgs| {
+            msgs.into_iter()
+                .map(|(n, addr)| (n, addr.as_socket().expect("should be SocketAddr")))
+                .collect()
+        })
     }
 
     /// Sends data on the socket to the given address. On success, returns the
@@ -250,15 +985,23 @@ impl UdpSocket {
         buffer: T,
         addr: impl ToSocketAddrsAsync,
     ) -> BufResult<usize, T> {
-        super::first_addr_buf(addr, buffer, |addr, buffer| async move {
+        let res = super::first_addr_buf(addr, buffer, |addr, buffer| async move {
             self.inner
                 .send_to_vectored(buffer, &SockAddr::from(addr))
                 .await
         })
-        .await
+        .await;
+        self.with_mtu(res)
     }
 }
 
+#[cfg(any(target_os = "android", target_os = "linux"))]
+fn segment_control(segment_size: u16) -> Vec<u8> {
+    let mut control = crate::CMsgBuilder::new();
+    control.push(libc::SOL_UDP, libc::UDP_SEGMENT, segment_size);
+    control.finish()
+}
+
 impl_try_as_raw_fd!(UdpSocket, inner);
 
 impl_attachable!(UdpSocket, inner);
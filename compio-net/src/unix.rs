@@ -1,11 +1,26 @@
+#[cfg(feature = "time")]
+use std::time::Duration;
 use std::{future::Future, io, path::Path};
 
-use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use compio_buf::{buf_try, BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
 use compio_io::{AsyncRead, AsyncWrite};
-use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
+use compio_runtime::{impl_attachable, impl_try_as_raw_fd, TryAsRawFd};
+use futures_util::{stream, Stream};
 use socket2::{Domain, SockAddr, Type};
 
-use crate::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, Socket, WriteHalf};
+use crate::{OwnedReadHalf, OwnedWriteHalf, PeerCred, ReadHalf, Socket, WriteHalf};
+
+// An address in the abstract namespace is the name prefixed with a NUL
+// byte, without a trailing one.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn abstract_addr(name: &[u8]) -> io::Result<SockAddr> {
+    use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+    let mut path = Vec::with_capacity(name.len() + 1);
+    path.push(0);
+    path.extend_from_slice(name);
+    SockAddr::unix(OsStr::from_bytes(&path))
+}
 
 /// A Unix socket server, listening for connections.
 ///
@@ -57,6 +72,14 @@ impl UnixListener {
         Ok(UnixListener { inner: socket })
     }
 
+    /// Creates a new [`UnixListener`] bound to `name` in the abstract
+    /// namespace. The name is not a file, and disappears when the socket is
+    /// closed.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract(name: impl AsRef<[u8]>) -> io::Result<Self> {
+        Self::bind_addr(&abstract_addr(name.as_ref())?)
+    }
+
     /// Close the socket. If the returned future is dropped before polling, the
     /// socket won't be closed.
     pub fn close(self) -> impl Future<Output = io::Result<()>> {
@@ -72,6 +95,24 @@ impl UnixListener {
         })
     }
 
+    /// Creates a listener from a [`std::os::unix::net::UnixListener`], e.g. one
+    /// passed by systemd or launchd with socket activation. It is set to
+    /// the nonblocking mode needed by the driver, and attached to the
+    /// driver of the current runtime when first used.
+    #[cfg(unix)]
+    pub fn from_std(listener: std::os::unix::net::UnixListener) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(listener)?,
+        })
+    }
+
+    /// Converts it into a [`std::os::unix::net::UnixListener`] in blocking
+    /// mode, to hand it off to other libraries.
+    #[cfg(unix)]
+    pub fn into_std(self) -> io::Result<std::os::unix::net::UnixListener> {
+        self.inner.into_std()
+    }
+
     /// Accepts a new incoming connection from this listener.
     ///
     /// This function will yield once a new Unix domain socket connection
@@ -83,6 +124,45 @@ impl UnixListener {
         Ok((stream, addr))
     }
 
+    /// Accepts the incoming connections as a stream, with the remote
+    /// addresses.
+    ///
+    /// It calls [`accept`](Self::accept) for each item, and never ends. The
+    /// errors are yielded without ending the stream.
+    pub fn incoming(&self) -> impl Stream<Item = io::Result<(UnixStream, SockAddr)>> + '_ {
+        stream::unfold((), move |_| async move { Some((self.accept().await, ())) })
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    ///
+    /// On Windows, this sets whether the socket handle could be inherited.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
     /// Returns the local address that this listener is bound to.
     pub fn local_addr(&self) -> io::Result<SockAddr> {
         self.inner.local_addr()
@@ -112,6 +192,14 @@ impl_attachable!(UnixListener, inner);
 /// stream.write("hello world!").await.unwrap();
 /// # })
 /// ```
+///
+/// # Concurrency
+///
+/// Only one read and one write could be in flight at the same time, because
+/// concurrent reads or writes would interleave the data. Use
+/// [`split`](UnixStream::split) or [`into_split`](UnixStream::into_split) to
+/// read and write concurrently, and serialize the writes from multiple tasks.
+/// Concurrent reads or writes panic in debug builds.
 #[derive(Debug)]
 pub struct UnixStream {
     inner: Socket,
@@ -135,6 +223,35 @@ impl UnixStream {
         Ok(unix_stream)
     }
 
+    /// Opens a Unix connection to `name` in the abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_abstract(name: impl AsRef<[u8]>) -> io::Result<Self> {
+        Self::connect_addr(&abstract_addr(name.as_ref())?)
+    }
+
+    /// Opens a Unix connection to the specified file path asynchronously,
+    /// failing with [`TimedOut`](io::ErrorKind::TimedOut) if it isn't
+    /// established within `timeout`, e.g. when the backlog of the listener is
+    /// full. The pending connect is cancelled on timeout.
+    ///
+    /// It is an error to pass a zero `Duration`.
+    #[cfg(feature = "time")]
+    pub async fn connect_timeout(path: impl AsRef<Path>, timeout: Duration) -> io::Result<Self> {
+        Self::connect_addr_timeout(&SockAddr::unix(path)?, timeout).await
+    }
+
+    /// Opens a Unix connection to the specified address asynchronously, like
+    /// [`connect_timeout`](Self::connect_timeout).
+    #[cfg(feature = "time")]
+    pub async fn connect_addr_timeout(addr: &SockAddr, timeout: Duration) -> io::Result<Self> {
+        crate::connect_timeout(timeout, async {
+            let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+            socket.connect_async(addr).await?;
+            Ok(UnixStream { inner: socket })
+        })
+        .await
+    }
+
     /// Close the socket. If the returned future is dropped before polling, the
     /// socket won't be closed.
     pub fn close(self) -> impl Future<Output = io::Result<()>> {
@@ -150,6 +267,65 @@ impl UnixStream {
         })
     }
 
+    /// Creates a stream from a [`std::os::unix::net::UnixStream`], setting the
+    /// nonblocking mode needed by the driver.
+    #[cfg(unix)]
+    pub fn from_std(stream: std::os::unix::net::UnixStream) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(stream)?,
+        })
+    }
+
+    /// Converts it into a [`std::os::unix::net::UnixStream`] in blocking mode.
+    #[cfg(unix)]
+    pub fn into_std(self) -> io::Result<std::os::unix::net::UnixStream> {
+        self.inner.into_std()
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    ///
+    /// On Windows, this sets whether the socket handle could be inherited.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of the `SO_PASSCRED` option on this socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        self.inner.passcred()
+    }
+
+    /// Sets the value of the `SO_PASSCRED` option on this socket. If set, the
+    /// messages received with [`recv_msg`](Self::recv_msg) carry an
+    /// `SCM_CREDENTIALS` message with the credentials of the sender.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        self.inner.set_passcred(passcred)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
     /// Returns the socket path of the remote peer of this connection.
     pub fn peer_addr(&self) -> io::Result<SockAddr> {
         self.inner.peer_addr()
@@ -160,13 +336,94 @@ impl UnixStream {
         self.inner.local_addr()
     }
 
+    /// Returns the credentials of the peer process, to authenticate the
+    /// connecting clients of an IPC server.
+    ///
+    /// It is `SO_PEERCRED` on Linux, `getpeereid` and `LOCAL_PEERPID` on
+    /// the BSDs and Apple platforms, and `SIO_AF_UNIX_GETPEERPID` on
+    /// Windows.
+    pub fn peer_cred(&self) -> io::Result<PeerCred> {
+        crate::cred::peer_cred(self.try_as_raw_fd()?)
+    }
+
+    /// Sends some data with the `MSG_*` flags, e.g. `MSG_MORE` on Linux,
+    /// returning the original buffer and quantity of data sent.
+    ///
+    /// The flags are passed to the system call as is.
+    pub async fn send_with_flags<T: IoBuf>(&self, buffer: T, flags: i32) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_with_flags(buffer, flags).await
+    }
+
+    /// Receives data into the end of `buffer`, and returns the grown buffer.
+    ///
+    /// The capacity of `buffer` is reserved for the bytes pending on the
+    /// socket, or at least `hint` bytes if fewer are known to be available.
+    pub async fn recv_buf(&self, buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        self.inner.recv_buf(buffer, hint).await
+    }
+
+    /// Sends the data at the front of `buffer`, and returns the rest of it.
+    ///
+    /// The sent bytes are removed from `buffer`, so that more data could be
+    /// appended to it before sending the rest. The allocation is kept.
+    pub async fn send_buf(&self, buffer: Vec<u8>) -> BufResult<usize, Vec<u8>> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_buf(buffer).await
+    }
+
+    /// Receives data into the end of `buffer` until the socket is drained,
+    /// reserving at least `hint` bytes for each receive. It waits only for the
+    /// first bytes, and returns the total length received.
+    ///
+    /// On io-uring, the completion tells whether more data is pending, so no
+    /// extra receive or syscall is needed to find the end.
+    pub async fn recv_pending(&self, buffer: Vec<u8>, hint: usize) -> BufResult<usize, Vec<u8>> {
+        self.inner.recv_pending(buffer, hint).await
+    }
+
+    /// Sends data from the buffers accompanied by the control messages, e.g.
+    /// the fds built with [`CMsgBuilder::push_fds`].
+    ///
+    /// [`CMsgBuilder::push_fds`]: crate::CMsgBuilder::push_fds
+    #[cfg(unix)]
+    pub async fn send_msg<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<usize, (T, C)> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_msg_connected(buffer, control).await
+    }
+
+    /// Receives data into the buffers, and the control messages into
+    /// `control`, which can be parsed with [`CMsgIter`].
+    ///
+    /// The received fds are close-on-exec where supported, and owned by the
+    /// caller. The control messages which don't fit in the capacity of
+    /// `control` are discarded.
+    ///
+    /// [`CMsgIter`]: crate::CMsgIter
+    #[cfg(unix)]
+    pub async fn recv_msg<T: IoVectoredBufMut, C: IoBufMut>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<usize, (T, C)> {
+        let _guard = self.inner.read_guard();
+        self.inner
+            .recv_msg(buffer, control)
+            .await
+            .map_res(|(len, _)| len)
+    }
+
     /// Splits a [`UnixStream`] into a read half and a write half, which can be
     /// used to read and write the stream concurrently.
     ///
     /// This method is more efficient than
     /// [`into_split`](UnixStream::into_split), but the halves cannot
     /// be moved into independently spawned tasks.
-    pub fn split(&self) -> (ReadHalf<Self>, WriteHalf<Self>) {
+    pub fn split(&self) -> (ReadHalf<'_, Self>, WriteHalf<'_, Self>) {
         crate::split(self)
     }
 
@@ -195,11 +452,13 @@ impl AsyncRead for UnixStream {
 impl AsyncRead for &UnixStream {
     #[inline]
     async fn read<B: IoBufMut>(&mut self, buf: B) -> BufResult<usize, B> {
+        let _guard = self.inner.read_guard();
         self.inner.recv(buf).await
     }
 
     #[inline]
     async fn read_vectored<V: IoVectoredBufMut>(&mut self, buf: V) -> BufResult<usize, V> {
+        let _guard = self.inner.read_guard();
         self.inner.recv_vectored(buf).await
     }
 }
@@ -229,11 +488,13 @@ impl AsyncWrite for UnixStream {
 impl AsyncWrite for &UnixStream {
     #[inline]
     async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
         self.inner.send(buf).await
     }
 
     #[inline]
     async fn write_vectored<T: IoVectoredBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
         self.inner.send_vectored(buf).await
     }
 
@@ -251,3 +512,276 @@ impl AsyncWrite for &UnixStream {
 impl_try_as_raw_fd!(UnixStream, inner);
 
 impl_attachable!(UnixStream, inner);
+
+/// A Unix datagram socket.
+///
+/// It could send to and receive from any socket path, or be connected to one
+/// peer with [`connect`](UnixDatagram::connect) to use
+/// [`send`](UnixDatagram::send) and [`recv`](UnixDatagram::recv).
+///
+/// # Examples
+///
+/// ```
+/// use compio_net::UnixDatagram;
+/// use tempfile::tempdir;
+///
+/// let dir = tempdir().unwrap();
+/// let rx_path = dir.path().join("rx.sock");
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async move {
+/// let rx = UnixDatagram::bind(&rx_path).unwrap();
+/// let tx = UnixDatagram::unbound().unwrap();
+///
+/// tx.send_to("hello", &rx_path).await.unwrap();
+///
+/// let (_, buf) = rx.recv(Vec::with_capacity(5)).await.unwrap();
+/// assert_eq!(buf, b"hello");
+/// # });
+/// ```
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct UnixDatagram {
+    inner: Socket,
+}
+
+#[cfg(unix)]
+impl UnixDatagram {
+    /// Creates a Unix datagram socket bound to the specified file path. The
+    /// file path cannot yet exist.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::bind_addr(&SockAddr::unix(path)?)
+    }
+
+    /// Creates a Unix datagram socket bound to the specified address.
+    pub fn bind_addr(addr: &SockAddr) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::bind(addr, Type::DGRAM, None)?,
+        })
+    }
+
+    /// Creates a Unix datagram socket bound to `name` in the abstract
+    /// namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract(name: impl AsRef<[u8]>) -> io::Result<Self> {
+        Self::bind_addr(&abstract_addr(name.as_ref())?)
+    }
+
+    /// Creates a Unix datagram socket which is not bound to any address.
+    pub fn unbound() -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::new(Domain::UNIX, Type::DGRAM, None)?,
+        })
+    }
+
+    /// Creates an unnamed pair of connected sockets.
+    pub fn pair() -> io::Result<(Self, Self)> {
+        let (first, second) = Socket::pair(Domain::UNIX, Type::DGRAM, None)?;
+        Ok((Self { inner: first }, Self { inner: second }))
+    }
+
+    /// Connects the socket to the specified file path, so that
+    /// [`send`](UnixDatagram::send) sends to it, and only the datagrams from
+    /// it are received.
+    pub fn connect(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.connect_addr(&SockAddr::unix(path)?)
+    }
+
+    /// Connects the socket to the specified address.
+    pub fn connect_addr(&self, addr: &SockAddr) -> io::Result<()> {
+        self.inner.connect(addr)
+    }
+
+    /// Connects the socket to `name` in the abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_abstract(&self, name: impl AsRef<[u8]>) -> io::Result<()> {
+        self.connect_addr(&abstract_addr(name.as_ref())?)
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Creates a socket from a [`std::os::unix::net::UnixDatagram`], setting
+    /// the nonblocking mode needed by the driver.
+    pub fn from_std(socket: std::os::unix::net::UnixDatagram) -> io::Result<Self> {
+        Ok(Self {
+            inner: Socket::from_std(socket)?,
+        })
+    }
+
+    /// Converts it into a [`std::os::unix::net::UnixDatagram`] in blocking
+    /// mode.
+    pub fn into_std(self) -> io::Result<std::os::unix::net::UnixDatagram> {
+        self.inner.into_std()
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Gets the value of the `SO_PASSCRED` option on this socket.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn passcred(&self) -> io::Result<bool> {
+        self.inner.passcred()
+    }
+
+    /// Sets the value of the `SO_PASSCRED` option on this socket. If set, the
+    /// messages received with [`recv_msg`](Self::recv_msg) carry an
+    /// `SCM_CREDENTIALS` message with the credentials of the sender.
+    #[cfg(any(target_os = "android", target_os = "linux"))]
+    pub fn set_passcred(&self, passcred: bool) -> io::Result<()> {
+        self.inner.set_passcred(passcred)
+    }
+
+    /// Gets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type returned by the option, and the zeroed bytes
+    /// should be a valid `T` in case the option is shorter.
+    pub unsafe fn get_opt<T: Copy>(&self, level: i32, name: i32) -> io::Result<T> {
+        unsafe { self.inner.get_opt(level, name) }
+    }
+
+    /// Sets the value of a socket option with the raw `level` and `name`,
+    /// for the options without a dedicated method.
+    ///
+    /// # Safety
+    ///
+    /// `T` should be the type expected by the option. If it contains
+    /// pointers, they should be valid for the option.
+    pub unsafe fn set_opt<T>(&self, level: i32, name: i32, value: &T) -> io::Result<()> {
+        unsafe { self.inner.set_opt(level, name, value) }
+    }
+
+    /// Returns the address of the connected peer.
+    pub fn peer_addr(&self) -> io::Result<SockAddr> {
+        self.inner.peer_addr()
+    }
+
+    /// Returns the address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SockAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Receives a datagram from the connected peer into the buffer, returning
+    /// the original buffer and quantity of data received.
+    pub async fn recv<T: IoBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv(buffer).await
+    }
+
+    /// Receives a datagram from the connected peer into the buffers.
+    pub async fn recv_vectored<T: IoVectoredBufMut>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.recv_vectored(buffer).await
+    }
+
+    /// Sends a datagram to the connected peer, returning the original buffer
+    /// and quantity of data sent.
+    pub async fn send<T: IoBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send(buffer).await
+    }
+
+    /// Sends a datagram from the buffers to the connected peer.
+    pub async fn send_vectored<T: IoVectoredBuf>(&self, buffer: T) -> BufResult<usize, T> {
+        self.inner.send_vectored(buffer).await
+    }
+
+    /// Receives a datagram on the socket. On success, returns the number of
+    /// bytes received and the address of the sender, which has no path if the
+    /// sender is not bound.
+    pub async fn recv_from<T: IoBufMut>(&self, buffer: T) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from(buffer).await
+    }
+
+    /// Receives a datagram into the buffers. On success, returns the number
+    /// of bytes received and the address of the sender.
+    pub async fn recv_from_vectored<T: IoVectoredBufMut>(
+        &self,
+        buffer: T,
+    ) -> BufResult<(usize, SockAddr), T> {
+        self.inner.recv_from_vectored(buffer).await
+    }
+
+    /// Sends a datagram to the specified file path. On success, returns the
+    /// number of bytes sent.
+    pub async fn send_to<T: IoBuf>(
+        &self,
+        buffer: T,
+        path: impl AsRef<Path>,
+    ) -> BufResult<usize, T> {
+        let (addr, buffer) = buf_try!(SockAddr::unix(path), buffer);
+        self.send_to_addr(buffer, &addr).await
+    }
+
+    /// Sends a datagram to the specified address. On success, returns the
+    /// number of bytes sent.
+    pub async fn send_to_addr<T: IoBuf>(&self, buffer: T, addr: &SockAddr) -> BufResult<usize, T> {
+        self.inner.send_to(buffer, addr).await
+    }
+
+    /// Sends a datagram from the buffers to the specified address.
+    pub async fn send_to_vectored<T: IoVectoredBuf>(
+        &self,
+        buffer: T,
+        addr: &SockAddr,
+    ) -> BufResult<usize, T> {
+        self.inner.send_to_vectored(buffer, addr).await
+    }
+
+    /// Sends a datagram from the buffers to the connected peer, accompanied
+    /// by the control messages.
+    #[cfg(unix)]
+    pub async fn send_msg<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<usize, (T, C)> {
+        self.inner.send_msg_connected(buffer, control).await
+    }
+
+    /// Sends a datagram from the buffers to the specified address,
+    /// accompanied by the control messages.
+    #[cfg(unix)]
+    pub async fn send_msg_to<T: IoVectoredBuf, C: IoBuf>(
+        &self,
+        buffer: T,
+        control: C,
+        addr: &SockAddr,
+    ) -> BufResult<usize, (T, C)> {
+        self.inner.send_msg(buffer, control, addr).await
+    }
+
+    /// Receives a datagram into the buffers, and the control messages into
+    /// `control`. On success, returns the number of bytes received and the
+    /// address of the sender.
+    ///
+    /// See [`UnixStream::recv_msg`] for the received fds.
+    #[cfg(unix)]
+    pub async fn recv_msg<T: IoVectoredBufMut, C: IoBufMut>(
+        &self,
+        buffer: T,
+        control: C,
+    ) -> BufResult<(usize, SockAddr), (T, C)> {
+        self.inner.recv_msg(buffer, control).await
+    }
+}
+
+#[cfg(unix)]
+impl_try_as_raw_fd!(UnixDatagram, inner);
+
+#[cfg(unix)]
+impl_attachable!(UnixDatagram, inner);
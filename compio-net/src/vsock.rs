@@ -0,0 +1,273 @@
+use std::{fmt, future::Future, io};
+
+use compio_buf::{BufResult, IoBuf, IoBufMut, IoVectoredBuf, IoVectoredBufMut};
+use compio_io::{AsyncRead, AsyncWrite};
+use compio_runtime::{impl_attachable, impl_try_as_raw_fd};
+use socket2::{Domain, SockAddr, Type};
+
+use crate::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, Socket, WriteHalf};
+
+/// The address of a vsock socket, i.e. the context id of the VM and the
+/// port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VsockAddr {
+    cid: u32,
+    port: u32,
+}
+
+impl VsockAddr {
+    /// Binds to any context id, i.e. `VMADDR_CID_ANY`.
+    pub const CID_ANY: u32 = libc::VMADDR_CID_ANY;
+    /// The host, i.e. `VMADDR_CID_HOST`.
+    pub const CID_HOST: u32 = libc::VMADDR_CID_HOST;
+    /// The local loopback, i.e. `VMADDR_CID_LOCAL`.
+    pub const CID_LOCAL: u32 = libc::VMADDR_CID_LOCAL;
+    /// Binds to any port, i.e. `VMADDR_PORT_ANY`.
+    pub const PORT_ANY: u32 = libc::VMADDR_PORT_ANY;
+
+    /// Creates an address of the context id and the port.
+    pub const fn new(cid: u32, port: u32) -> Self {
+        Self { cid, port }
+    }
+
+    /// The context id.
+    pub const fn cid(&self) -> u32 {
+        self.cid
+    }
+
+    /// The port.
+    pub const fn port(&self) -> u32 {
+        self.port
+    }
+
+    fn from_sock_addr(addr: SockAddr) -> io::Result<Self> {
+        addr.as_vsock_address()
+            .map(|(cid, port)| Self::new(cid, port))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "not a vsock address"))
+    }
+}
+
+impl fmt::Display for VsockAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.cid, self.port)
+    }
+}
+
+impl From<VsockAddr> for SockAddr {
+    fn from(addr: VsockAddr) -> Self {
+        SockAddr::vsock(addr.cid, addr.port)
+    }
+}
+
+/// A vsock server, listening for the connections between the VMs and the
+/// host.
+///
+/// # Examples
+///
+/// ```no_run
+/// use compio_io::{AsyncReadExt, AsyncWriteExt};
+/// use compio_net::{VsockAddr, VsockListener, VsockStream};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let listener = VsockListener::bind(VsockAddr::new(VsockAddr::CID_ANY, 1234)).unwrap();
+///
+/// let (mut stream, addr) = listener.accept().await.unwrap();
+/// println!("connected from VM {}", addr.cid());
+/// stream.write_all("hello").await.unwrap();
+/// # });
+/// ```
+#[derive(Debug)]
+pub struct VsockListener {
+    inner: Socket,
+}
+
+impl VsockListener {
+    /// Creates a new listener bound to the address.
+    pub fn bind(addr: VsockAddr) -> io::Result<Self> {
+        let socket = Socket::bind(&addr.into(), Type::STREAM, None)?;
+        socket.listen(1024)?;
+        Ok(Self { inner: socket })
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub async fn accept(&self) -> io::Result<(VsockStream, VsockAddr)> {
+        let (socket, addr) = self.inner.accept().await?;
+        Ok((
+            VsockStream { inner: socket },
+            VsockAddr::from_sock_addr(addr)?,
+        ))
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Returns the local address that this listener is bound to, with the
+    /// assigned port if bound to [`VsockAddr::PORT_ANY`].
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        VsockAddr::from_sock_addr(self.inner.local_addr()?)
+    }
+}
+
+impl_try_as_raw_fd!(VsockListener, inner);
+
+impl_attachable!(VsockListener, inner);
+
+/// A vsock stream between a VM and the host, or between the VMs.
+///
+/// # Concurrency
+///
+/// Only one read and one write could be in flight at the same time, because
+/// concurrent reads or writes would interleave the data. Use
+/// [`split`](VsockStream::split) or [`into_split`](VsockStream::into_split) to
+/// read and write concurrently.
+#[derive(Debug)]
+pub struct VsockStream {
+    inner: Socket,
+}
+
+impl VsockStream {
+    /// Opens a connection to the address, e.g. the port on
+    /// [`VsockAddr::CID_HOST`] from a VM.
+    pub async fn connect(addr: VsockAddr) -> io::Result<Self> {
+        let socket = Socket::new(Domain::VSOCK, Type::STREAM, None)?;
+        socket.connect_async(&addr.into()).await?;
+        Ok(Self { inner: socket })
+    }
+
+    /// Close the socket. If the returned future is dropped before polling, the
+    /// socket won't be closed.
+    pub fn close(self) -> impl Future<Output = io::Result<()>> {
+        self.inner.close()
+    }
+
+    /// Creates a new independently owned handle to the underlying socket.
+    ///
+    /// It does not clear the attach state.
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            inner: self.inner.try_clone()?,
+        })
+    }
+
+    /// Sets whether the socket is closed on `exec`, so that it won't be
+    /// inherited by the child processes. It is set by default.
+    pub fn set_cloexec(&self, cloexec: bool) -> io::Result<()> {
+        self.inner.set_cloexec(cloexec)
+    }
+
+    /// Returns the address of the remote peer of this connection.
+    pub fn peer_addr(&self) -> io::Result<VsockAddr> {
+        VsockAddr::from_sock_addr(self.inner.peer_addr()?)
+    }
+
+    /// Returns the address of the local half of this connection.
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        VsockAddr::from_sock_addr(self.inner.local_addr()?)
+    }
+
+    /// Splits a [`VsockStream`] into a read half and a write half, which can
+    /// be used to read and write the stream concurrently.
+    pub fn split(&self) -> (ReadHalf<'_, Self>, WriteHalf<'_, Self>) {
+        crate::split(self)
+    }
+
+    /// Splits a [`VsockStream`] into a read half and a write half, which can
+    /// be moved to separate tasks.
+    pub fn into_split(self) -> (OwnedReadHalf<Self>, OwnedWriteHalf<Self>) {
+        crate::into_split(self)
+    }
+}
+
+impl AsyncRead for VsockStream {
+    #[inline]
+    async fn read<B: IoBufMut>(&mut self, buf: B) -> BufResult<usize, B> {
+        (&*self).read(buf).await
+    }
+
+    #[inline]
+    async fn read_vectored<V: IoVectoredBufMut>(&mut self, buf: V) -> BufResult<usize, V> {
+        (&*self).read_vectored(buf).await
+    }
+}
+
+impl AsyncRead for &VsockStream {
+    #[inline]
+    async fn read<B: IoBufMut>(&mut self, buf: B) -> BufResult<usize, B> {
+        let _guard = self.inner.read_guard();
+        self.inner.recv(buf).await
+    }
+
+    #[inline]
+    async fn read_vectored<V: IoVectoredBufMut>(&mut self, buf: V) -> BufResult<usize, V> {
+        let _guard = self.inner.read_guard();
+        self.inner.recv_vectored(buf).await
+    }
+}
+
+impl AsyncWrite for VsockStream {
+    #[inline]
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        (&*self).write(buf).await
+    }
+
+    #[inline]
+    async fn write_vectored<T: IoVectoredBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        (&*self).write_vectored(buf).await
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> io::Result<()> {
+        (&*self).flush().await
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        (&*self).shutdown().await
+    }
+}
+
+impl AsyncWrite for &VsockStream {
+    #[inline]
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
+        self.inner.send(buf).await
+    }
+
+    #[inline]
+    async fn write_vectored<T: IoVectoredBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        let _guard = self.inner.write_guard();
+        self.inner.send_vectored(buf).await
+    }
+
+    #[inline]
+    async fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    #[inline]
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.inner.shutdown().await
+    }
+}
+
+impl_try_as_raw_fd!(VsockStream, inner);
+
+impl_attachable!(VsockStream, inner);
@@ -0,0 +1,70 @@
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+    time::Duration,
+};
+
+use compio_net::{AcceptGovernor, TcpListener, TcpStream};
+use compio_runtime::{time::MockClock, RuntimeBuilder};
+use futures_util::future::{select, Either};
+
+async fn accept_one(listener: &compio_net::GovernedListener) {
+    let addr = listener.listener().local_addr().unwrap();
+    let (cli, srv) = futures_util::join!(TcpStream::connect(addr), listener.accept());
+    cli.unwrap();
+    srv.unwrap();
+}
+
+#[test]
+fn greedy_pauses() {
+    let clock = MockClock::new();
+    let runtime = RuntimeBuilder::new().clock(clock.clone()).build().unwrap();
+    runtime.block_on(async {
+        let window = Duration::from_secs(200);
+        let pause = Duration::from_secs(50);
+        let governor = AcceptGovernor::new(window).pause(pause).min_accepts(4);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let other = governor.register(listener.try_clone().unwrap());
+        let listener = governor.register(listener);
+        assert_eq!(governor.members(), 2);
+
+        for _ in 0..4 {
+            accept_one(&listener).await;
+        }
+        // It pauses until the window rotates, whatever the real time.
+        let mut accept = pin!(accept_one(&listener));
+        for advance in [Duration::ZERO, pause] {
+            clock.advance(advance);
+            assert!(!accepts_soon(accept.as_mut()).await);
+        }
+        clock.advance(window);
+        accept.await;
+
+        drop(other);
+        assert_eq!(governor.members(), 1);
+        // It never pauses alone.
+        assert!(accepts_soon(pin!(accept_one(&listener))).await);
+    })
+}
+
+// Whether the accept finishes in a while of the real time.
+async fn accepts_soon(accept: Pin<&mut impl Future<Output = ()>>) -> bool {
+    let wait = compio_runtime::spawn_blocking(|| std::thread::sleep(Duration::from_millis(100)));
+    matches!(select(accept, wait).await, Either::Left(_))
+}
+
+#[compio_macros::test]
+async fn fair_never_pauses() {
+    let pause = Duration::from_secs(10);
+    let governor = AcceptGovernor::new(Duration::from_secs(60))
+        .pause(pause)
+        .min_accepts(2);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let a = governor.register(listener.try_clone().unwrap());
+    let b = governor.register(listener);
+
+    for _ in 0..4 {
+        accept_one(&a).await;
+        accept_one(&b).await;
+    }
+}
@@ -0,0 +1,51 @@
+use compio_io::compat::AsyncStream;
+use compio_net::{TcpListener, TcpStream};
+use futures_util::{AsyncReadExt, AsyncWriteExt};
+
+#[compio_macros::test]
+async fn async_stream() {
+    const MSG: &[u8] = b"poll-based traits over completion-based IO";
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    let mut tx = AsyncStream::with_capacity(8, tx);
+    let mut rx = AsyncStream::new(rx);
+
+    let write = async {
+        tx.write_all(MSG).await.unwrap();
+        tx.close().await.unwrap();
+    };
+    let read = async {
+        let mut buf = vec![];
+        rx.read_to_end(&mut buf).await.unwrap();
+        buf
+    };
+    let ((), buf) = futures_util::join!(write, read);
+    assert_eq!(buf, MSG);
+}
+
+#[compio_macros::test]
+async fn async_stream_zero_capacity() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    let mut tx = AsyncStream::with_capacity(0, tx);
+    let mut rx = AsyncStream::with_capacity(0, rx);
+
+    let write = async {
+        tx.write_all(b"hello").await.unwrap();
+        tx.close().await.unwrap();
+    };
+    let read = async {
+        let mut buf = vec![];
+        rx.read_to_end(&mut buf).await.unwrap();
+        buf
+    };
+    let ((), buf) = futures_util::join!(write, read);
+    assert_eq!(buf, b"hello");
+}
@@ -0,0 +1,53 @@
+use std::{io, time::Duration};
+
+use compio_buf::{BufResult, IoBuf};
+use compio_io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use compio_net::{TcpListener, TcpStream, UdpSocket};
+use compio_runtime::time::Deadline;
+
+#[compio_macros::test]
+async fn recv() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    let BufResult(res, buf) = Deadline::after(Duration::from_millis(50))
+        .scope(rx.read(Vec::with_capacity(16)))
+        .await;
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    // The buffer is returned.
+    assert_eq!(buf.capacity(), 16);
+
+    // The stream is still usable.
+    tx.write_all("hello").await.0.unwrap();
+    let (_, buf) = rx.read_exact(buf.slice(..5)).await.unwrap();
+    assert_eq!(buf.as_inner(), b"hello");
+}
+
+#[compio_macros::test]
+async fn elapsed() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let deadline = Deadline::after(Duration::ZERO);
+    let BufResult(res, buf) = deadline
+        .scope(socket.recv_from(Vec::with_capacity(16)))
+        .await;
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+    assert_eq!(buf.capacity(), 16);
+}
+
+#[compio_macros::test]
+async fn accept() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let res = Deadline::after(Duration::from_millis(50))
+        .scope(listener.accept())
+        .await;
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+
+    // Completed in time.
+    let addr = listener.local_addr().unwrap();
+    let (_tx, _rx) = Deadline::after(Duration::from_secs(10))
+        .scope(async { futures_util::try_join!(TcpStream::connect(&addr), listener.accept()) })
+        .await
+        .unwrap();
+}
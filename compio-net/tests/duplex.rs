@@ -0,0 +1,55 @@
+use std::io;
+
+use compio_io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use compio_net::duplex;
+use futures_util::FutureExt;
+
+#[compio_macros::test]
+async fn both_directions() {
+    let (mut a, mut b) = duplex(16);
+
+    a.write_all("ping").await.unwrap();
+    let (_, buf) = b.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"ping");
+
+    b.write_all("pong").await.unwrap();
+    let (_, buf) = a.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"pong");
+}
+
+#[compio_macros::test]
+async fn backpressure() {
+    let (mut a, mut b) = duplex(4);
+
+    let (len, _) = a.write(b"abcdef").await.unwrap();
+    assert_eq!(len, 4);
+    // The buffer is full until the peer reads.
+    assert!(a.write(b"ef").now_or_never().is_none());
+
+    let (writer, reader) = futures_util::join!(
+        a.write_all(b"efgh".to_vec()),
+        b.read_exact(Vec::with_capacity(8))
+    );
+    writer.unwrap();
+    let (_, buf) = reader.unwrap();
+    assert_eq!(buf, b"abcdefgh");
+}
+
+#[compio_macros::test]
+async fn shutdown_and_drop() {
+    let (mut a, mut b) = duplex(16);
+
+    a.write_all("bye").await.unwrap();
+    a.shutdown().await.unwrap();
+    let (_, buf) = b.read_to_end(vec![]).await.unwrap();
+    assert_eq!(buf, b"bye");
+
+    // The other direction is still open.
+    b.write_all("ok").await.unwrap();
+    let (_, buf) = a.read_exact(Vec::with_capacity(2)).await.unwrap();
+    assert_eq!(buf, b"ok");
+
+    drop(a);
+    let err = b.write(b"lost").await.0.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
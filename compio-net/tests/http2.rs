@@ -0,0 +1,186 @@
+use std::{io, time::Duration};
+
+use compio_buf::{BufResult, IoBuf};
+use compio_io::{AsyncWrite, AsyncWriteExt};
+use compio_net::{Http2Frame, Http2Framed, Http2Settings, TcpListener, TcpStream};
+use futures_util::FutureExt;
+
+async fn tcp_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (client, (server, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    (client, server)
+}
+
+#[compio_macros::test]
+async fn frames() {
+    let (client, server) = tcp_pair().await;
+    let mut client = Http2Framed::new(client);
+    let mut server = Http2Framed::new(server);
+
+    let settings = Http2Settings {
+        enable_push: Some(false),
+        initial_window_size: Some(1 << 20),
+        max_frame_size: Some(1 << 15),
+        ..Default::default()
+    };
+    client.write_preface().unwrap();
+    client
+        .write_frame(&Http2Frame::settings(&settings))
+        .unwrap();
+    client
+        .write_frame(&Http2Frame::new(
+            Http2Frame::HEADERS,
+            Http2Frame::END_HEADERS,
+            1,
+            b"headers".to_vec(),
+        ))
+        .unwrap();
+    client
+        .write_frame(&Http2Frame::data(1, vec![7; 1 << 14], true))
+        .unwrap();
+    client
+        .write_frame(&Http2Frame::window_update(0, 1 << 16))
+        .unwrap();
+    client.flush().await.unwrap();
+
+    server.read_preface().await.unwrap();
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame.ty, Http2Frame::SETTINGS);
+    assert!(!frame.has_flag(Http2Frame::ACK));
+    assert_eq!(Http2Settings::decode(&frame.payload).unwrap(), settings);
+
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame.ty, Http2Frame::HEADERS);
+    assert!(frame.has_flag(Http2Frame::END_HEADERS));
+    assert_eq!(frame.stream_id, 1);
+    assert_eq!(frame.payload, b"headers");
+
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, Http2Frame::data(1, vec![7; 1 << 14], true));
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, Http2Frame::window_update(0, 1 << 16));
+
+    server.write_frame(&Http2Frame::settings_ack()).unwrap();
+    server.write_frame(&Http2Frame::goaway(1, 0)).unwrap();
+    server.flush().await.unwrap();
+    drop(server);
+
+    let frame = client.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, Http2Frame::settings_ack());
+    let frame = client.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame.ty, Http2Frame::GOAWAY);
+    assert_eq!(frame.payload, [0, 0, 0, 1, 0, 0, 0, 0]);
+    assert!(client.read_frame().await.unwrap().is_none());
+}
+
+#[compio_macros::test]
+async fn partial_writes() {
+    let (mut client, server) = tcp_pair().await;
+    let mut server = Http2Framed::new(server);
+
+    let mut framed = Http2Framed::new(vec![]);
+    framed
+        .write_frame(&Http2Frame::ping([1; 8], false))
+        .unwrap();
+    framed.flush().await.unwrap();
+    let bytes = framed.into_inner();
+
+    compio_runtime::spawn(async move {
+        for byte in bytes {
+            client.write_all([byte]).await.0.unwrap();
+        }
+        // Close in the middle of the next frame.
+        client.write_all([0, 0]).await.0.unwrap();
+    })
+    .detach();
+
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, Http2Frame::ping([1; 8], false));
+    let err = server.read_frame().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[compio_macros::test]
+async fn invalid() {
+    let mut server = Http2Framed::new(&b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n"[..]);
+    let err = server.read_preface().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut client = Http2Framed::new(vec![]);
+    client
+        .write_frame(&Http2Frame::data(1, vec![0; 1 << 15], false))
+        .unwrap();
+    client.flush().await.unwrap();
+    let bytes = client.into_inner();
+
+    let mut server = Http2Framed::new(bytes.as_slice());
+    let err = server.read_frame().await.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let mut server = Http2Framed::new(bytes.as_slice());
+    server.set_max_frame_size(1 << 15);
+    assert_eq!(
+        server.read_frame().await.unwrap().unwrap().payload.len(),
+        1 << 15
+    );
+
+    let err = Http2Settings::decode(&[0, 5, 0, 0, 0, 1]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = Http2Settings::decode(&[0, 2, 0]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    // The unknown parameters are ignored.
+    let settings = Http2Settings::decode(&[0xff, 0xff, 0, 0, 0, 1]).unwrap();
+    assert_eq!(settings, Http2Settings::default());
+}
+
+#[compio_macros::test]
+async fn cancel() {
+    let (mut client, server) = tcp_pair().await;
+    let mut server = Http2Framed::new(server);
+
+    let mut framed = Http2Framed::new(vec![]);
+    framed
+        .write_frame(&Http2Frame::ping([1; 8], false))
+        .unwrap();
+    framed.flush().await.unwrap();
+    let bytes = framed.into_inner();
+
+    // The data read before the cancelled read is kept.
+    client.write_all(bytes[..5].to_vec()).await.0.unwrap();
+    compio_runtime::time::timeout(Duration::from_millis(100), server.read_frame())
+        .await
+        .unwrap_err();
+    client.write_all(bytes[5..].to_vec()).await.0.unwrap();
+    let frame = server.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, Http2Frame::ping([1; 8], false));
+
+    // The writes after a cancelled flush fail.
+    let mut framed = Http2Framed::new(Pending);
+    framed
+        .write_frame(&Http2Frame::ping([1; 8], false))
+        .unwrap();
+    assert!(framed.flush().now_or_never().is_none());
+    framed
+        .write_frame(&Http2Frame::ping([2; 8], false))
+        .unwrap_err();
+    framed.flush().await.unwrap_err();
+}
+
+struct Pending;
+
+impl AsyncWrite for Pending {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        std::future::pending::<()>().await;
+        BufResult(Ok(0), buf)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+use compio_io::AsyncReadExt;
+use compio_net::{duplex, Pacer};
+
+#[compio_macros::test]
+async fn pacer_spreads_chunks() {
+    let (mut tx, mut rx) = duplex(64 * 1024);
+    let mut pacer = Pacer::new(100_000).burst(10_000);
+
+    let start = Instant::now();
+    let (res, buf) = futures_util::join!(
+        pacer.write_all(&mut tx, vec![1u8; 30_000]),
+        rx.read_exact(Vec::with_capacity(30_000)),
+    );
+    res.0.unwrap();
+    assert_eq!(buf.unwrap().1.len(), 30_000);
+    // The first chunk is sent at once, and the other two wait 100ms each.
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn max_pacing_rate() {
+    use compio_net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let stream = TcpStream::connect(&addr).await.unwrap();
+
+    assert_eq!(stream.max_pacing_rate().unwrap(), None);
+    stream.set_max_pacing_rate(Some(1_000_000)).unwrap();
+    assert_eq!(stream.max_pacing_rate().unwrap(), Some(1_000_000));
+    stream.set_max_pacing_rate(None).unwrap();
+    assert_eq!(stream.max_pacing_rate().unwrap(), None);
+}
@@ -0,0 +1,84 @@
+#![cfg(unix)]
+
+use std::{io::Write, os::unix::net::UnixStream};
+
+use compio_net::PollFd;
+use futures_util::FutureExt;
+
+#[cfg(feature = "time")]
+#[compio_macros::test]
+async fn readable() {
+    use std::io::{ErrorKind, Read};
+
+    let (mut tx, rx) = UnixStream::pair().unwrap();
+    rx.set_nonblocking(true).unwrap();
+    let rx = PollFd::new(rx).unwrap();
+
+    for _ in 0..3 {
+        tx.write_all(b"ping").unwrap();
+        rx.readable().await.unwrap();
+        let mut buf = [0u8; 4];
+        rx.get_ref().read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+        let err = rx.get_ref().read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    }
+
+    // The consumed readiness is not reported again.
+    let res =
+        compio_runtime::time::timeout(std::time::Duration::from_millis(50), rx.readable()).await;
+    assert!(res.is_err());
+}
+
+#[compio_macros::test]
+async fn writable() {
+    let (tx, _rx) = UnixStream::pair().unwrap();
+    tx.set_nonblocking(true).unwrap();
+    let tx = PollFd::new(tx).unwrap();
+
+    tx.writable().await.unwrap();
+    let len = tx
+        .with_raw(|fd| unsafe { libc::write(fd, b"ping".as_ptr().cast(), 4) })
+        .unwrap();
+    assert_eq!(len, 4);
+}
+
+#[compio_macros::test]
+async fn not_ready() {
+    let (_tx, rx) = UnixStream::pair().unwrap();
+    let rx = PollFd::new(rx).unwrap();
+
+    assert!(rx.readable().now_or_never().is_none());
+    // The cancelled wait should not affect the later ones.
+    rx.writable().await.unwrap();
+}
+
+#[compio_macros::test]
+async fn fd_replaced() {
+    use std::os::fd::AsRawFd;
+
+    let (mut tx, rx) = UnixStream::pair().unwrap();
+    let (other, mut other_peer) = UnixStream::pair().unwrap();
+    rx.set_nonblocking(true).unwrap();
+    let rx = PollFd::new(rx).unwrap();
+    // Keep the registered socket open.
+    let registered = rx.get_ref().try_clone().unwrap();
+
+    let mut waits = std::pin::pin!(futures_util::future::join(rx.readable(), rx.readable()));
+    assert!(futures_util::poll!(waits.as_mut()).is_pending());
+    // Replace the fd, so that renewing its interest fails.
+    assert!(unsafe { libc::dup2(other.as_raw_fd(), rx.get_ref().as_raw_fd()) } >= 0);
+    tx.write_all(b"ping").unwrap();
+    // The io-uring driver may submit the waits after the replacement.
+    other_peer.write_all(b"ping").unwrap();
+
+    let (first, _) = waits.await;
+    first.unwrap();
+    drop(registered);
+
+    // The driver is still working.
+    let (mut tx, rx) = UnixStream::pair().unwrap();
+    let rx = PollFd::new(rx).unwrap();
+    tx.write_all(b"ping").unwrap();
+    rx.readable().await.unwrap();
+}
@@ -0,0 +1,73 @@
+use std::{
+    io,
+    net::{Ipv4Addr, SocketAddr},
+};
+
+use compio_net::RawSocket;
+use socket2::{Domain, Protocol, SockAddr, Type};
+
+fn echo_request(id: u16, seq: u16) -> Vec<u8> {
+    let mut packet = vec![8, 0, 0, 0];
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(b"compio");
+    let mut sum = packet
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], c.get(1).copied().unwrap_or(0)]) as u32)
+        .sum::<u32>();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    packet[2..4].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+    packet
+}
+
+// Skip the tests without the privilege, or the ping group.
+fn new_socket(ty: Type) -> Option<RawSocket> {
+    match RawSocket::new(Domain::IPV4, ty, Some(Protocol::ICMPV4)) {
+        Ok(socket) => Some(socket),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => None,
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[compio_macros::test]
+async fn raw_ping() {
+    let Some(socket) = new_socket(Type::RAW) else {
+        return;
+    };
+    socket.set_ttl(16).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 16);
+    assert!(!socket.header_included_v4().unwrap());
+
+    let addr = SockAddr::from(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)));
+    let id = std::process::id() as u16;
+    socket.send_to(echo_request(id, 1), &addr).await.unwrap();
+    // The raw socket also receives the request on the loopback.
+    loop {
+        let ((len, from), packet) = socket.recv_from(Vec::with_capacity(128)).await.unwrap();
+        assert_eq!(len, packet.len());
+        assert_eq!(from.as_socket_ipv4().unwrap().ip(), &Ipv4Addr::LOCALHOST);
+        let icmp = &packet[(packet[0] & 0xf) as usize * 4..];
+        if icmp[0] == 0 && icmp[4..6] == id.to_be_bytes() {
+            assert_eq!(&icmp[6..8], &1u16.to_be_bytes());
+            assert_eq!(&icmp[8..], b"compio");
+            break;
+        }
+    }
+}
+
+#[compio_macros::test]
+async fn dgram_ping() {
+    let Some(socket) = new_socket(Type::DGRAM) else {
+        return;
+    };
+    let addr = SockAddr::from(SocketAddr::from((Ipv4Addr::LOCALHOST, 0)));
+    socket.connect(&addr).unwrap();
+    socket.send(echo_request(0, 2)).await.unwrap();
+    let (_, packet) = socket.recv(Vec::with_capacity(128)).await.unwrap();
+    // No IP header, and the identifier is replaced.
+    assert_eq!(packet[0], 0);
+    assert_eq!(&packet[6..8], &2u16.to_be_bytes());
+    assert_eq!(&packet[8..], b"compio");
+}
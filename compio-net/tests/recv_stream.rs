@@ -0,0 +1,57 @@
+use std::pin::pin;
+
+use compio_io::AsyncWriteExt;
+use compio_net::{TcpListener, TcpStream};
+use compio_runtime::Runtime;
+use futures_util::StreamExt;
+
+async fn connected() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (client, (server, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    (client, server)
+}
+
+#[compio_macros::test]
+async fn recv_until_eof() {
+    let (mut client, server) = connected().await;
+    let pool = Runtime::current().create_buffer_pool(4, 8).unwrap();
+    assert_eq!(pool.buffer_len(), 8);
+
+    client.write_all("hello, ").await.0.unwrap();
+    client.write_all("world").await.0.unwrap();
+    drop(client);
+
+    let mut data = vec![];
+    let mut received = server.recv_stream(&pool).boxed_local();
+    while let Some(buffer) = received.next().await {
+        let buffer = buffer.unwrap();
+        assert!(buffer.len() <= 8);
+        data.extend_from_slice(&buffer);
+    }
+    assert_eq!(data, b"hello, world");
+
+    drop(received);
+    Runtime::current().release_buffer_pool(pool).unwrap();
+}
+
+#[compio_macros::test]
+async fn buffers_exhausted() {
+    let (mut client, server) = connected().await;
+    let pool = Runtime::current().create_buffer_pool(1, 16).unwrap();
+
+    let mut received = pin!(server.recv_stream(&pool));
+    client.write_all("first").await.0.unwrap();
+    let first = received.next().await.unwrap().unwrap();
+    assert_eq!(&first[..], b"first");
+
+    client.write_all("second").await.0.unwrap();
+    let err = received.next().await.unwrap().unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOBUFS));
+
+    // The buffer returns to the pool, and the stream receives again.
+    drop(first);
+    let second = received.next().await.unwrap().unwrap();
+    assert_eq!(&second[..], b"second");
+}
@@ -0,0 +1,121 @@
+use std::io;
+
+use compio_io::{AsyncReadExt, AsyncWriteExt};
+use compio_net::{read_sni, SniRouter, TcpListener, TcpStream};
+
+// A ClientHello with the server name and an unrelated extension, split into
+// records of `fragment` bytes.
+fn client_hello(server_name: Option<&str>, fragment: usize) -> Vec<u8> {
+    let mut extensions = vec![0x00, 0x0b, 0x00, 0x02, 0x01, 0x00];
+    if let Some(name) = server_name {
+        let name = name.as_bytes();
+        let list_len = name.len() as u16 + 3;
+        extensions.extend_from_slice(&[0x00, 0x00]);
+        extensions.extend_from_slice(&(list_len + 2).to_be_bytes());
+        extensions.extend_from_slice(&list_len.to_be_bytes());
+        extensions.push(0x00);
+        extensions.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        extensions.extend_from_slice(name);
+    }
+
+    let mut body = vec![0x03, 0x03];
+    body.extend_from_slice(&[0x42; 32]);
+    body.extend_from_slice(&[0x00, 0x00, 0x02, 0x13, 0x01, 0x01, 0x00]);
+    body.extend_from_slice(&(extensions.len() as u16).to_be_bytes());
+    body.extend_from_slice(&extensions);
+
+    let mut handshake = vec![0x01];
+    handshake.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..]);
+    handshake.extend_from_slice(&body);
+
+    let mut records = vec![];
+    for chunk in handshake.chunks(fragment) {
+        records.extend_from_slice(&[0x16, 0x03, 0x01]);
+        records.extend_from_slice(&(chunk.len() as u16).to_be_bytes());
+        records.extend_from_slice(chunk);
+    }
+    records
+}
+
+async fn pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    (tx, rx)
+}
+
+#[compio_macros::test]
+async fn route() {
+    let router = SniRouter::new()
+        .route("api.example.com", 1)
+        .route("*.example.com", 2)
+        .fallback(0);
+
+    for (name, target) in [
+        (Some("API.Example.com"), 1),
+        (Some("www.example.com"), 2),
+        (Some("a.b.example.com"), 0),
+        (Some("example.com"), 0),
+        (None, 0),
+    ] {
+        let (mut tx, mut rx) = pair().await;
+        let hello = client_hello(name, usize::MAX);
+        tx.write_all(hello.clone()).await.0.unwrap();
+        tx.write_all(b"rest").await.0.unwrap();
+        let (selected, buf) = router.select(&mut rx, vec![]).await.unwrap();
+        assert_eq!(selected, Some(&target));
+
+        // The ClientHello is returned, and the rest is still readable.
+        assert_eq!(buf[..hello.len()], hello);
+        let rest = buf.len() - hello.len();
+        let (_, buf) = rx.read_exact(vec![0; 4 - rest]).await.unwrap();
+        assert_eq!(buf, b"rest"[rest..]);
+    }
+
+    assert_eq!(SniRouter::<()>::new().get(Some("example.com")), None);
+}
+
+#[compio_macros::test]
+async fn fragmented() {
+    let (mut tx, mut rx) = pair().await;
+    let hello = client_hello(Some("example.com"), 16);
+    let (head, tail) = hello.split_at(20);
+    tx.write_all(head.to_vec()).await.0.unwrap();
+    let (res, _) = futures_util::join!(read_sni(&mut rx, vec![]), async {
+        // Trickle the rest.
+        for byte in tail {
+            tx.write_all([*byte]).await.0.unwrap();
+        }
+    });
+    let (name, buf) = res.unwrap();
+    assert_eq!(name.as_deref(), Some("example.com"));
+    assert_eq!(buf, hello);
+}
+
+#[compio_macros::test]
+async fn prefix() {
+    let (mut tx, mut rx) = pair().await;
+    let hello = client_hello(Some("example.com"), 16);
+    tx.write_all(hello[8..].to_vec()).await.0.unwrap();
+    // The first bytes are read by the caller before.
+    let (name, buf) = read_sni(&mut rx, hello[..8].to_vec()).await.unwrap();
+    assert_eq!(name.as_deref(), Some("example.com"));
+    assert_eq!(buf, hello);
+}
+
+#[compio_macros::test]
+async fn invalid() {
+    let (mut tx, mut rx) = pair().await;
+    tx.write_all("GET / HTTP/1.1\r\n\r\n").await.0.unwrap();
+    let (res, buf) = read_sni(&mut rx, vec![]).await.into();
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert!(buf.starts_with(b"GET"));
+
+    let (mut tx, mut rx) = pair().await;
+    let hello = client_hello(Some("example.com"), 16);
+    tx.write_all(hello[..20].to_vec()).await.0.unwrap();
+    drop(tx);
+    let err = read_sni(&mut rx, vec![]).await.0.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -1,4 +1,4 @@
-use compio_net::{TcpListener, TcpStream, ToSocketAddrsAsync};
+use compio_net::{DualStackListener, TcpListener, TcpStream, ToSocketAddrsAsync};
 
 async fn test_impl(addr: impl ToSocketAddrsAsync) {
     let listener = TcpListener::bind(addr).await.unwrap();
@@ -33,3 +33,198 @@ test_accept! {
     (str_port_tuple, ("127.0.0.1", 0)),
     (ip_port_tuple, ("127.0.0.1".parse::<std::net::IpAddr>().unwrap(), 0)),
 }
+
+#[compio_macros::test]
+async fn accept_filtered() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let rejected = TcpStream::connect(&addr).await.unwrap();
+    let accepted = TcpStream::connect(&addr).await.unwrap();
+    let rejected_addr = rejected.local_addr().unwrap();
+
+    let (srv, peer) = listener
+        .accept_filtered(|addr| *addr != rejected_addr)
+        .await
+        .unwrap();
+    assert_eq!(peer, accepted.local_addr().unwrap());
+    assert_eq!(srv.peer_addr().unwrap(), peer);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn reuseport_steering() {
+    let first = TcpListener::bind_reuse_port("127.0.0.1:0").await.unwrap();
+    let addr = first.local_addr().unwrap();
+    let _second = TcpListener::bind_reuse_port(addr).await.unwrap();
+
+    // With a group size of 1, all connections go to the first listener.
+    first.attach_reuseport_cpu(1).unwrap();
+    for _ in 0..4 {
+        let (cli, (srv, _)) =
+            futures_util::try_join!(TcpStream::connect(&addr), first.accept()).unwrap();
+        assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+        srv.incoming_cpu().unwrap();
+    }
+}
+
+#[compio_macros::test]
+async fn accept_with_data() {
+    use compio_io::AsyncWriteExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    #[cfg(target_os = "linux")]
+    {
+        listener
+            .set_defer_accept(Some(std::time::Duration::from_secs(1)))
+            .unwrap();
+        assert!(listener.defer_accept().unwrap().is_some());
+    }
+    let addr = listener.local_addr().unwrap();
+    let task = compio_runtime::spawn(async move {
+        let mut cli = TcpStream::connect(&addr).await.unwrap();
+        cli.write_all("GET / HTTP/1.1\r\n").await.unwrap();
+        cli
+    });
+    let ((srv, peer, len), buf) = listener
+        .accept_with_data(Vec::with_capacity(1024))
+        .await
+        .unwrap();
+    let cli = task.await;
+    assert_eq!(peer, cli.local_addr().unwrap());
+    assert_eq!(srv.peer_addr().unwrap(), peer);
+    assert_eq!(len, 16);
+    assert_eq!(buf, b"GET / HTTP/1.1\r\n");
+}
+
+#[compio_macros::test]
+async fn accept_stats() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let _first = TcpStream::connect(&addr).await.unwrap();
+    let _second = TcpStream::connect(&addr).await.unwrap();
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(listener.pending_connections().unwrap(), 2);
+        assert_eq!(listener.backlog().unwrap(), 128);
+    }
+
+    listener.accept().await.unwrap();
+    assert_eq!(listener.accepted_connections(), 1);
+    assert_eq!(listener.accept_errors(), 0);
+    #[cfg(target_os = "linux")]
+    assert_eq!(listener.pending_connections().unwrap(), 1);
+}
+
+async fn test_dual_stack(mut listener: DualStackListener) {
+    let port = listener.local_addrs().unwrap()[0].port();
+
+    let v4 = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let (_srv, peer) = listener.accept().await.unwrap();
+    assert!(peer.is_ipv4());
+    assert_eq!(peer, v4.local_addr().unwrap());
+
+    let v6 = TcpStream::connect(("::1", port)).await.unwrap();
+    let (_srv, peer) = listener.accept().await.unwrap();
+    assert!(peer.is_ipv6());
+    assert_eq!(peer, v6.local_addr().unwrap());
+}
+
+#[compio_macros::test]
+async fn dual_stack() {
+    test_dual_stack(TcpListener::bind_dual_stack(0).unwrap()).await;
+}
+
+#[compio_macros::test]
+async fn dual_stack_pair() {
+    let v6 = TcpListener::bind("[::1]:0").await.unwrap();
+    let port = v6.local_addr().unwrap().port();
+    let v4 = TcpListener::bind(("127.0.0.1", port)).await.unwrap();
+    let listener = DualStackListener::from_pair(v6, v4);
+    assert_eq!(listener.listeners().len(), 2);
+    test_dual_stack(listener).await;
+}
+
+#[compio_macros::test]
+async fn accept_many() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    // The later accepts reuse the op of the first one.
+    for _ in 0..3 {
+        let cli = TcpStream::connect(&addr).await.unwrap();
+        let (srv, peer) = listener.accept().await.unwrap();
+        assert_eq!(cli.local_addr().unwrap(), peer);
+        assert_eq!(srv.peer_addr().unwrap(), peer);
+    }
+}
+
+#[compio_macros::test]
+async fn accept_multi() {
+    use futures_util::StreamExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    {
+        let mut incoming = std::pin::pin!(listener.accept_multi());
+        for _ in 0..3 {
+            let cli = TcpStream::connect(&addr).await.unwrap();
+            let srv = incoming.next().await.unwrap().unwrap();
+            assert_eq!(cli.local_addr().unwrap(), srv.peer_addr().unwrap());
+        }
+    }
+    assert_eq!(listener.accepted_connections(), 3);
+
+    // The dropped stream doesn't take the later connections.
+    let cli = TcpStream::connect(&addr).await.unwrap();
+    let (srv, peer) = listener.accept().await.unwrap();
+    assert_eq!(cli.local_addr().unwrap(), peer);
+    assert_eq!(srv.peer_addr().unwrap(), peer);
+}
+
+#[compio_macros::test]
+async fn incoming() {
+    use futures_util::StreamExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let clients = [
+        TcpStream::connect(&addr).await.unwrap(),
+        TcpStream::connect(&addr).await.unwrap(),
+    ];
+    let accepted = listener
+        .incoming()
+        .take(2)
+        .map(|res| res.unwrap().1)
+        .collect::<Vec<_>>()
+        .await;
+    let peers = clients.map(|cli| cli.local_addr().unwrap());
+    assert_eq!(accepted, peers);
+}
+
+#[compio_macros::test]
+async fn std_conversion() {
+    use std::io::{Read, Write};
+
+    use compio_io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let listener = TcpListener::from_std(listener).unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let std_cli = std::net::TcpStream::connect(addr).unwrap();
+    let (srv, _) = listener.accept().await.unwrap();
+    let mut cli = TcpStream::from_std(std_cli).unwrap();
+    assert_eq!(cli.peer_addr().unwrap(), addr);
+
+    cli.write_all("ping").await.0.unwrap();
+    let mut srv = srv.into_std().unwrap();
+    let mut buffer = [0; 4];
+    srv.read_exact(&mut buffer).unwrap();
+    assert_eq!(&buffer, b"ping");
+
+    srv.write_all(b"pong").unwrap();
+    let (_, buffer) = cli.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buffer, b"pong");
+
+    let listener = listener.into_std().unwrap();
+    assert_eq!(listener.local_addr().unwrap(), addr);
+}
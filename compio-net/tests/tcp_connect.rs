@@ -1,6 +1,7 @@
 use std::net::{IpAddr, SocketAddr};
 
-use compio_net::{TcpListener, TcpStream, ToSocketAddrsAsync};
+use compio_buf::BufResult;
+use compio_net::{TcpKeepalive, TcpListener, TcpStream, ToSocketAddrsAsync};
 
 async fn test_connect_ip_impl(
     target: impl ToSocketAddrsAsync,
@@ -99,3 +100,475 @@ test_connect! {
 async fn connect_invalid_dst() {
     assert!(TcpStream::connect("127.0.0.0:0").await.is_err());
 }
+
+#[compio_macros::test]
+async fn connect_error() {
+    use compio_driver::ResultExt;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let err = TcpStream::connect(addr).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    #[cfg(unix)]
+    assert_eq!(err.raw_os_error(), Some(libc::ECONNREFUSED));
+    assert!(compio_driver::Error::get(&err).is_none());
+
+    let err: std::io::Error = TcpStream::connect(addr)
+        .await
+        .context("connect")
+        .map_err(|e| e.with_peer(addr))
+        .unwrap_err()
+        .into();
+    let ctx = compio_driver::Error::get(&err).unwrap();
+    assert_eq!(ctx.op(), "connect");
+    assert_eq!(ctx.peer(), Some(addr));
+    #[cfg(unix)]
+    assert_eq!(
+        compio_driver::Error::os_error(&err),
+        Some(libc::ECONNREFUSED)
+    );
+}
+
+#[compio_macros::test]
+async fn recv_buf() {
+    use compio_io::{AsyncWrite, AsyncWriteExt};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    tx.write_all(b"hello world").await.unwrap();
+    tx.shutdown().await.unwrap();
+
+    let mut buffer = b"prefix: ".to_vec();
+    loop {
+        let compio_buf::BufResult(res, buf) = rx.recv_buf(buffer, 4).await;
+        buffer = buf;
+        if res.unwrap() == 0 {
+            break;
+        }
+    }
+    assert_eq!(buffer, b"prefix: hello world");
+}
+
+#[compio_macros::test]
+async fn send_buf() {
+    use compio_io::AsyncReadExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    let mut buffer = b"hello".to_vec();
+    let capacity = buffer.capacity();
+    while !buffer.is_empty() {
+        let compio_buf::BufResult(res, buf) = tx.send_buf(buffer).await;
+        res.unwrap();
+        buffer = buf;
+    }
+    assert_eq!(buffer.capacity(), capacity);
+    buffer.extend_from_slice(b" world");
+    let compio_buf::BufResult(res, buffer) = tx.send_buf(buffer).await;
+    assert_eq!(res.unwrap(), 6);
+    assert!(buffer.is_empty());
+
+    let (_, buffer) = rx.read_exact(vec![0; 11]).await.unwrap();
+    assert_eq!(buffer, b"hello world");
+}
+
+#[compio_macros::test]
+async fn recv_pending() {
+    use compio_io::AsyncWriteExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    tx.write_all(b"hello world").await.unwrap();
+    // The data arrives at once on loopback, and takes more than one receive
+    // with the small reservations. It returns with the stream still open.
+    let BufResult(res, buffer) = rx.recv_pending(b"prefix: ".to_vec(), 1).await;
+    assert_eq!(res.unwrap(), 11);
+    assert_eq!(buffer, b"prefix: hello world");
+}
+
+#[compio_macros::test]
+async fn abort() {
+    use compio_io::AsyncReadExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    tx.abort().await.unwrap();
+
+    let err = rx.read_to_end(vec![]).await.0.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset);
+}
+
+#[compio_macros::test]
+async fn oob() {
+    use compio_io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    tx.write_all("hello").await.unwrap();
+    tx.send_oob("!").await.unwrap();
+
+    let (_, buf) = rx.read_exact(Vec::with_capacity(5)).await.unwrap();
+    assert_eq!(buf, b"hello");
+    let (_, buf) = rx.recv_oob(Vec::with_capacity(1)).await.unwrap();
+    assert_eq!(buf, b"!");
+
+    let (tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    rx.set_oob_inline(true).unwrap();
+    assert!(rx.oob_inline().unwrap());
+
+    tx.send_oob("hello!").await.unwrap();
+    let (_, buf) = rx.read_exact(Vec::with_capacity(6)).await.unwrap();
+    assert_eq!(buf, b"hello!");
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn cork() {
+    use std::time::Duration;
+
+    use compio_io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use compio_runtime::TryAsRawFd;
+
+    fn cork_option(stream: &TcpStream) -> bool {
+        let mut value: libc::c_int = 0;
+        let mut len = std::mem::size_of_val(&value) as libc::socklen_t;
+        let res = unsafe {
+            libc::getsockopt(
+                stream.try_as_raw_fd().unwrap(),
+                libc::IPPROTO_TCP,
+                libc::TCP_CORK,
+                &mut value as *mut _ as *mut _,
+                &mut len,
+            )
+        };
+        assert_eq!(res, 0);
+        value != 0
+    }
+
+    // Whether any data is received, without waiting or consuming it.
+    fn readable(stream: &TcpStream) -> bool {
+        let mut buf = [0u8; 1];
+        let res = unsafe {
+            libc::recv(
+                stream.try_as_raw_fd().unwrap(),
+                buf.as_mut_ptr().cast(),
+                1,
+                libc::MSG_PEEK | libc::MSG_DONTWAIT,
+            )
+        };
+        res > 0
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    assert!(!cork_option(&tx));
+    tx.set_cork(true).unwrap();
+    assert!(tx.cork());
+    assert!(cork_option(&tx));
+
+    // The partial frames are held back, within the 200ms limit of the cork.
+    tx.write_all(b"header ").await.unwrap();
+    tx.write_all(b"body").await.unwrap();
+    compio_runtime::time::sleep(Duration::from_millis(50)).await;
+    assert!(!readable(&rx));
+
+    // The flush pushes them, and the stream stays corked.
+    tx.flush().await.unwrap();
+    assert!(tx.cork());
+    assert!(cork_option(&tx));
+    compio_runtime::time::sleep(Duration::from_millis(10)).await;
+    assert!(readable(&rx));
+    let (_, buf) = rx.read_exact(Vec::with_capacity(11)).await.unwrap();
+    assert_eq!(buf, b"header body");
+
+    tx.set_cork(false).unwrap();
+    assert!(!tx.cork());
+    assert!(!cork_option(&tx));
+}
+
+#[cfg(debug_assertions)]
+#[compio_macros::test]
+#[should_panic(expected = "concurrent reads on the same stream")]
+async fn concurrent_reads() {
+    use compio_io::AsyncRead;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (_tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    let (mut r1, mut r2) = (&rx, &rx);
+    let _ = futures_util::join!(
+        r1.read(Vec::with_capacity(1)),
+        r2.read(Vec::with_capacity(1))
+    );
+}
+
+#[compio_macros::test]
+async fn socket_options() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.set_ttl(42).unwrap();
+    assert_eq!(listener.ttl().unwrap(), 42);
+    listener.set_recv_buffer_size(32 * 1024).unwrap();
+    assert!(listener.recv_buffer_size().unwrap() >= 32 * 1024);
+
+    let addr = listener.local_addr().unwrap();
+    let (stream, _) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    stream.set_nodelay(true).unwrap();
+    assert!(stream.nodelay().unwrap());
+    stream.set_ttl(7).unwrap();
+    assert_eq!(stream.ttl().unwrap(), 7);
+    stream.set_send_buffer_size(32 * 1024).unwrap();
+    assert!(stream.send_buffer_size().unwrap() >= 32 * 1024);
+}
+
+#[compio_macros::test]
+async fn keepalive() {
+    use std::time::Duration;
+
+    let keepalive = TcpKeepalive::new()
+        .with_time(Duration::from_secs(30))
+        .with_interval(Duration::from_secs(5))
+        .with_retries(4);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.set_keepalive(Some(keepalive));
+    let addr = listener.local_addr().unwrap();
+    let (stream, (accepted, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    assert_eq!(stream.keepalive().unwrap(), None);
+    stream.set_keepalive(Some(keepalive)).unwrap();
+    #[cfg(target_os = "linux")]
+    {
+        assert_eq!(stream.keepalive().unwrap(), Some(keepalive));
+        assert_eq!(accepted.keepalive().unwrap(), Some(keepalive));
+    }
+    #[cfg(not(target_os = "linux"))]
+    assert!(accepted.keepalive().unwrap().is_some());
+    stream.set_keepalive(None).unwrap();
+    assert_eq!(stream.keepalive().unwrap(), None);
+}
+
+#[compio_macros::test]
+#[cfg(any(windows, target_os = "linux", target_os = "android"))]
+async fn fast_open() {
+    use compio_io::AsyncReadExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.set_fastopen(16).unwrap();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    assert_eq!(listener.fastopen().unwrap(), 16);
+    let addr = listener.local_addr().unwrap();
+
+    // The first connection gets the cookie, and the second one sends the data
+    // in the SYN if TFO is enabled by the system.
+    for _ in 0..2 {
+        let (res, (mut accepted, _)) =
+            futures_util::join!(TcpStream::connect_with(&addr, "hello"), async {
+                listener.accept().await.unwrap()
+            });
+        let (stream, buf) = res.unwrap();
+        assert_eq!(buf, "hello");
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        let (_, buf) = accepted.read_exact(vec![0; 5]).await.unwrap();
+        assert_eq!(buf, b"hello");
+    }
+}
+
+#[compio_macros::test]
+async fn send_zc() {
+    use compio_buf::{BufResult, IntoInner, IoBuf};
+    use compio_io::AsyncReadExt;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    let data = (0..1 << 20).map(|i| i as u8).collect::<Vec<_>>();
+    let send = async {
+        let mut buffer = data.clone();
+        let mut sent = 0;
+        while sent < buffer.len() {
+            let BufResult(res, slice) = tx.send_zc(buffer.slice(sent..)).await;
+            sent += res.unwrap();
+            buffer = slice.into_inner();
+        }
+        buffer
+    };
+    let (buffer, (_, received)) = futures_util::join!(send, async {
+        rx.read_exact(vec![0; 1 << 20]).await.unwrap()
+    });
+    assert_eq!(buffer, data);
+    assert_eq!(received, data);
+}
+
+#[compio_macros::test]
+async fn send_file() {
+    use std::io::Write;
+
+    use compio_io::AsyncReadExt;
+
+    let data = (0..200_000).map(|i| i as u8).collect::<Vec<_>>();
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(&data).unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    let task = compio_runtime::spawn(async move {
+        // The file ends before the requested length.
+        let sent = tx.send_file(&file, 10, data.len()).await.unwrap();
+        assert_eq!(sent, data.len() - 10);
+        data
+    });
+    let (_, received) = rx.read_to_end(vec![]).await.unwrap();
+    let data = task.await;
+    assert_eq!(received, &data[10..]);
+}
+
+#[compio_macros::test]
+async fn peek() {
+    use compio_io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (mut tx, (mut rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+
+    tx.write_all(b"\x16\x03\x01").await.unwrap();
+
+    let (len, buf) = rx.peek(Vec::with_capacity(1)).await.unwrap();
+    assert_eq!(len, 1);
+    assert_eq!(buf, b"\x16");
+
+    // The peeked bytes are still read.
+    let (_, buf) = rx.read_exact(Vec::with_capacity(3)).await.unwrap();
+    assert_eq!(buf, b"\x16\x03\x01");
+}
+
+#[cfg(feature = "time")]
+#[compio_macros::test]
+async fn connect_timeout() {
+    use std::time::Duration;
+
+    use socket2::{Domain, Socket, Type};
+
+    let listener = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    listener
+        .bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())
+        .unwrap();
+    listener.listen(0).unwrap();
+    let addr = listener.local_addr().unwrap().as_socket().unwrap();
+
+    let stream = TcpStream::connect_timeout(addr, Duration::from_secs(10))
+        .await
+        .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+
+    // Fill the backlog, so that the SYNs are dropped.
+    let mut queued = vec![];
+    while let Ok(stream) = std::net::TcpStream::connect_timeout(&addr, Duration::from_millis(100)) {
+        queued.push(stream);
+        assert!(queued.len() < 64, "the backlog is never full");
+    }
+
+    let err = TcpStream::connect_timeout(addr, Duration::from_millis(100))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+
+    let err = TcpStream::connect_timeout(addr, Duration::ZERO)
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(feature = "time")]
+#[compio_macros::test]
+async fn happy_eyeballs() {
+    use std::time::{Duration, Instant};
+
+    use socket2::{Domain, Socket, Type};
+
+    // A listener with a full backlog, which never completes the handshake.
+    let stuck = Socket::new(Domain::IPV4, Type::STREAM, None).unwrap();
+    stuck
+        .bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())
+        .unwrap();
+    stuck.listen(0).unwrap();
+    let stuck_addr = stuck.local_addr().unwrap().as_socket().unwrap();
+    let mut queued = vec![];
+    while let Ok(stream) =
+        std::net::TcpStream::connect_timeout(&stuck_addr, Duration::from_millis(100))
+    {
+        queued.push(stream);
+        assert!(queued.len() < 64, "the backlog is never full");
+    }
+
+    let refused = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    let listener = TcpListener::bind("[::1]:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // The families are interleaved, so the IPv6 one is tried second, after
+    // the attempt delay.
+    let addrs = [stuck_addr, refused, addr];
+    let start = Instant::now();
+    let (stream, _) =
+        futures_util::try_join!(TcpStream::connect(&addrs[..]), listener.accept()).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert!(start.elapsed() >= Duration::from_millis(250));
+
+    // A failed attempt starts the next one right away.
+    let addrs = [refused, addr];
+    let start = Instant::now();
+    let (stream, _) =
+        futures_util::try_join!(TcpStream::connect(&addrs[..]), listener.accept()).unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert!(start.elapsed() < Duration::from_millis(250));
+
+    let err = TcpStream::connect(&[refused][..]).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn connect_device() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stream, _) =
+        futures_util::try_join!(TcpStream::connect_device(addr, "lo"), listener.accept()).unwrap();
+    assert_eq!(stream.device().unwrap().as_deref(), Some("lo"));
+
+    let err = TcpStream::connect_device(addr, "compio-missing0")
+        .await
+        .unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENODEV));
+}
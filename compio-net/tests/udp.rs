@@ -1,3 +1,5 @@
+use std::net::Ipv4Addr;
+
 use compio_net::UdpSocket;
 
 #[compio_macros::test]
@@ -19,6 +21,24 @@ async fn connect() {
     assert_eq!(active.peer_addr().unwrap(), passive_addr);
 }
 
+#[compio_macros::test]
+async fn send_buf() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    active
+        .connect(passive.local_addr().unwrap())
+        .await
+        .unwrap();
+
+    let compio_buf::BufResult(res, buffer) = active.send_buf(b"foo".to_vec()).await;
+    assert_eq!(res.unwrap(), 3);
+    assert!(buffer.is_empty());
+    assert!(buffer.capacity() >= 3);
+
+    let (_, buffer) = passive.recv(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(buffer, b"foo");
+}
+
 #[compio_macros::test]
 async fn send_to() {
     const MSG: &str = "foo bar baz";
@@ -64,3 +84,349 @@ async fn send_to() {
         active_addr
     );
 }
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn send_to_with_flags() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    // The data sent with `MSG_MORE` is held and sent with the next one.
+    active
+        .send_to_with_flags("foo ", &passive_addr, libc::MSG_MORE)
+        .await
+        .0
+        .unwrap();
+    active.send_to("bar", &passive_addr).await.0.unwrap();
+
+    let (_, buffer) = passive.recv(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(buffer, b"foo bar");
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn mtu() {
+    use compio_net::MtuDiscover;
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    active.connect(passive.local_addr().unwrap()).await.unwrap();
+
+    active.set_mtu_discover(MtuDiscover::Do).unwrap();
+    assert_eq!(active.mtu_discover().unwrap(), MtuDiscover::Do);
+    assert!(active.mtu().unwrap() > 0);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn message_too_long() {
+    use compio_net::{MessageTooLong, MtuDiscover};
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    active.connect(passive.local_addr().unwrap()).await.unwrap();
+    active.set_mtu_discover(MtuDiscover::Do).unwrap();
+
+    let err = active.send(vec![0; 65536]).await.0.unwrap_err();
+    let err = MessageTooLong::get(&err).unwrap();
+    assert_eq!(err.io_error().raw_os_error(), Some(libc::EMSGSIZE));
+    assert_eq!(err.mtu(), Some(active.mtu().unwrap()));
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn tos() {
+    use compio_net::CMsgBuilder;
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    active.set_tos(0x10).unwrap();
+    assert_eq!(active.tos().unwrap(), 0x10);
+
+    let mut control = CMsgBuilder::new();
+    control.push(libc::IPPROTO_IP, libc::IP_TOS, 0xb8 as libc::c_int);
+    active
+        .send_msg([&b"foo "[..], b"bar"], control.finish(), passive_addr)
+        .await
+        .unwrap();
+
+    let (_, buffer) = passive.recv(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(buffer, b"foo bar");
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn filter() {
+    use std::net::Ipv4Addr;
+
+    use compio_net::SocketFilter;
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    passive
+        .attach_filter(&SocketFilter::ipv4_source(Ipv4Addr::new(10, 0, 0, 0), 8))
+        .unwrap();
+    active.send_to("dropped", &passive_addr).await.0.unwrap();
+
+    passive
+        .attach_filter(&SocketFilter::ipv4_source(Ipv4Addr::LOCALHOST, 8))
+        .unwrap();
+    active.send_to("accepted", &passive_addr).await.0.unwrap();
+
+    let (_, buffer) = passive.recv(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(buffer, b"accepted");
+    passive.detach_filter().unwrap();
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn raw_opt() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    unsafe {
+        socket
+            .set_opt(libc::SOL_SOCKET, libc::SO_BROADCAST, &(1 as libc::c_int))
+            .unwrap();
+        let broadcast: libc::c_int = socket
+            .get_opt(libc::SOL_SOCKET, libc::SO_BROADCAST)
+            .unwrap();
+        assert_eq!(broadcast, 1);
+    }
+}
+
+#[compio_macros::test]
+async fn multicast_v4() {
+    let group = Ipv4Addr::new(239, 255, 0, 251);
+    let receiver = UdpSocket::bind("0.0.0.0:0").await.unwrap();
+    let port = receiver.local_addr().unwrap().port();
+    receiver
+        .join_multicast_v4(&group, &Ipv4Addr::LOCALHOST)
+        .unwrap();
+
+    let sender = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    sender.set_multicast_if_v4(&Ipv4Addr::LOCALHOST).unwrap();
+    assert_eq!(sender.multicast_if_v4().unwrap(), Ipv4Addr::LOCALHOST);
+    sender.set_multicast_ttl_v4(0).unwrap();
+    assert_eq!(sender.multicast_ttl_v4().unwrap(), 0);
+    sender.set_multicast_loop_v4(true).unwrap();
+    assert!(sender.multicast_loop_v4().unwrap());
+
+    sender.send_to("hello", (group, port)).await.unwrap();
+    let (_, buf) = receiver.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"hello");
+
+    receiver
+        .leave_multicast_v4(&group, &Ipv4Addr::LOCALHOST)
+        .unwrap();
+}
+
+#[compio_macros::test]
+async fn socket_options() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    socket.set_ttl(16).unwrap();
+    assert_eq!(socket.ttl().unwrap(), 16);
+    socket.set_recv_buffer_size(32 * 1024).unwrap();
+    assert!(socket.recv_buffer_size().unwrap() >= 32 * 1024);
+    socket.set_send_buffer_size(32 * 1024).unwrap();
+    assert!(socket.send_buffer_size().unwrap() >= 32 * 1024);
+}
+
+#[compio_macros::test]
+async fn peek() {
+    const MSG: &str = "foo bar baz";
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active_addr = active.local_addr().unwrap();
+    active.connect(passive_addr).await.unwrap();
+    passive.connect(active_addr).await.unwrap();
+
+    active.send(MSG).await.0.unwrap();
+    let ((_, addr), buffer) = passive.peek_from(Vec::with_capacity(3)).await.unwrap();
+    assert_eq!(addr, active_addr);
+    assert_eq!(buffer, b"foo");
+    let (_, buffer) = passive.peek(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(buffer, MSG.as_bytes());
+
+    // The datagram is still queued after peeking.
+    let ((_, addr), buffer) = passive.recv_from(Vec::with_capacity(20)).await.unwrap();
+    assert_eq!(addr, active_addr);
+    assert_eq!(buffer, MSG.as_bytes());
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn segmentation_offload() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    passive.set_gro(true).unwrap();
+    assert!(passive.gro().unwrap());
+
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active_addr = active.local_addr().unwrap();
+    assert_eq!(active.gso_segment().unwrap(), 0);
+
+    // 3 datagrams of 100 bytes, and one of 50 bytes.
+    let data = (0..350).map(|i| i as u8).collect::<Vec<_>>();
+    let (n, _) = active
+        .send_to_segmented(data.clone(), 100, passive_addr)
+        .await
+        .unwrap();
+    assert_eq!(n, data.len());
+
+    // The datagrams are coalesced again, or received one by one.
+    let mut received = vec![];
+    while received.len() < data.len() {
+        let ((n, addr, segment_size), buffer) = passive
+            .recv_from_segmented(Vec::with_capacity(65536))
+            .await
+            .unwrap();
+        assert_eq!(addr, active_addr);
+        assert_eq!(n, buffer.len());
+        for segment in buffer.chunks(segment_size) {
+            let start = received.len();
+            assert_eq!(segment.len(), 100.min(data.len() - start));
+            received.extend_from_slice(segment);
+        }
+    }
+    assert_eq!(received, data);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn send_recv_many() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active_addr = active.local_addr().unwrap();
+
+    let (lens, _) = active
+        .send_many_to(vec!["foo", "bar baz"], &[passive_addr; 2])
+        .await
+        .unwrap();
+    assert_eq!(lens, [3, 7]);
+
+    active.connect(passive_addr).await.unwrap();
+    let (lens, _) = active.send_many(vec!["qux"]).await.unwrap();
+    assert_eq!(lens, [3]);
+
+    let mut received = vec![];
+    while received.len() < 3 {
+        let buffers = (0..4).map(|_| Vec::with_capacity(16)).collect();
+        let (msgs, buffers) = passive.recv_many(buffers).await.unwrap();
+        assert!(!msgs.is_empty());
+        for ((n, addr), buffer) in msgs.into_iter().zip(buffers) {
+            assert_eq!(addr, active_addr);
+            assert_eq!(n, buffer.len());
+            received.push(String::from_utf8(buffer).unwrap());
+        }
+    }
+    assert_eq!(received, ["foo", "bar baz", "qux"]);
+
+    let err = active.send_many_to(vec!["foo"], &[]).await.0.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn recv_many_wait() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    // The receive starts before anything is sent, and waits for the datagram.
+    let buffers = (0..4).map(|_| Vec::with_capacity(16)).collect();
+    let (res, ()) = futures_util::join!(passive.recv_many(buffers), async {
+        compio_runtime::time::sleep(std::time::Duration::from_millis(100)).await;
+        active.send_to("hello", passive_addr).await.0.unwrap();
+    });
+    let (msgs, buffers) = res.unwrap();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].0, 5);
+    assert_eq!(buffers[0], b"hello");
+}
+
+#[compio_macros::test]
+async fn std_conversion() {
+    let passive = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let passive = UdpSocket::from_std(passive).unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    active.send_to("foo", passive_addr).await.0.unwrap();
+    let (_, buffer) = passive.recv(Vec::with_capacity(8)).await.unwrap();
+    assert_eq!(buffer, b"foo");
+
+    active.send_to("bar", passive_addr).await.0.unwrap();
+    let passive = passive.into_std().unwrap();
+    let mut buffer = [0; 8];
+    let (n, _) = passive.recv_from(&mut buffer).unwrap();
+    assert_eq!(&buffer[..n], b"bar");
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn bind_device() {
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    assert_eq!(active.device().unwrap(), None);
+
+    active.bind_device(Some("lo")).unwrap();
+    assert_eq!(active.device().unwrap().as_deref(), Some("lo"));
+    active.send_to("foo", passive_addr).await.0.unwrap();
+    let (_, buffer) = passive.recv(Vec::with_capacity(8)).await.unwrap();
+    assert_eq!(buffer, b"foo");
+
+    active.bind_device(None).unwrap();
+    assert_eq!(active.device().unwrap(), None);
+}
+
+#[cfg(target_os = "linux")]
+#[compio_macros::test]
+async fn recv_tos() {
+    use compio_net::CMsgIter;
+
+    let passive = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    assert!(!passive.recv_tos().unwrap());
+    passive.set_recv_tos(true).unwrap();
+    assert!(passive.recv_tos().unwrap());
+
+    let active = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let active_addr = active.local_addr().unwrap();
+    active.set_tos(0xb8).unwrap();
+    active.send_to("foo", passive_addr).await.0.unwrap();
+
+    let ((n, addr), (buffer, control)) = passive
+        .recv_msg([Vec::with_capacity(8)], Vec::with_capacity(64))
+        .await
+        .unwrap();
+    assert_eq!((n, addr), (3, active_addr));
+    assert_eq!(buffer[0], b"foo");
+    let tos = CMsgIter::new(&control).find_map(|msg| msg.tos());
+    assert_eq!(tos, Some(0xb8));
+
+    let passive = UdpSocket::bind("[::1]:0").await.unwrap();
+    let passive_addr = passive.local_addr().unwrap();
+    passive.set_recv_tclass_v6(true).unwrap();
+    assert!(passive.recv_tclass_v6().unwrap());
+
+    let active = UdpSocket::bind("[::1]:0").await.unwrap();
+    active.set_tclass_v6(0x28).unwrap();
+    active.send_to("bar", passive_addr).await.0.unwrap();
+
+    let (_, (_, control)) = passive
+        .recv_msg([Vec::with_capacity(8)], Vec::with_capacity(64))
+        .await
+        .unwrap();
+    let tclass = CMsgIter::new(&control).find_map(|msg| msg.tos());
+    assert_eq!(tclass, Some(0x28));
+}
@@ -0,0 +1,62 @@
+#![cfg(unix)]
+
+use compio_net::UnixDatagram;
+
+#[compio_macros::test]
+async fn send_to_recv_from() -> std::io::Result<()> {
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-dgram-tests")
+        .tempdir()
+        .unwrap();
+    let server_path = dir.path().join("server.sock");
+    let client_path = dir.path().join("client.sock");
+
+    let server = UnixDatagram::bind(&server_path)?;
+    let client = UnixDatagram::bind(&client_path)?;
+
+    client.send_to("hello", &server_path).await.0?;
+    let ((len, addr), buf) = server.recv_from(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(len, 5);
+    assert_eq!(buf, b"hello");
+    assert_eq!(addr.as_pathname(), Some(client_path.as_path()));
+
+    server.send_to_addr("world", &addr).await.0?;
+    let (_, buf) = client.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"world");
+    Ok(())
+}
+
+#[compio_macros::test]
+async fn connected() -> std::io::Result<()> {
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-dgram-tests")
+        .tempdir()
+        .unwrap();
+    let server_path = dir.path().join("server.sock");
+
+    let server = UnixDatagram::bind(&server_path)?;
+    let client = UnixDatagram::unbound()?;
+    client.connect(&server_path)?;
+    assert_eq!(
+        client.peer_addr()?.as_pathname(),
+        Some(server_path.as_path())
+    );
+
+    client.send("ping").await.0?;
+    let ((_, addr), buf) = server.recv_from(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"ping");
+    assert!(addr.as_pathname().is_none());
+    Ok(())
+}
+
+#[compio_macros::test]
+async fn pair() -> std::io::Result<()> {
+    let (a, b) = UnixDatagram::pair()?;
+    a.send("first").await.0?;
+    a.send("second").await.0?;
+    let (_, buf) = b.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"first");
+    let (_, buf) = b.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"second");
+    Ok(())
+}
@@ -0,0 +1,109 @@
+#![cfg(unix)]
+
+use std::{
+    fs::File,
+    io::{Read, Seek, Write},
+    os::fd::{AsRawFd, FromRawFd},
+};
+
+use compio_net::{CMsgBuilder, CMsgIter, UnixDatagram, UnixListener, UnixStream};
+
+#[compio_macros::test]
+async fn stream_fd() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("fd.sock");
+    let listener = UnixListener::bind(&path).unwrap();
+    let tx = UnixStream::connect(&path).unwrap();
+    let (rx, _) = listener.accept().await.unwrap();
+
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(b"passed").unwrap();
+
+    let mut control = CMsgBuilder::new();
+    control.push_fds(&[file.as_raw_fd()]);
+    let (len, _) = tx.send_msg([b"hello"], control.finish()).await.unwrap();
+    assert_eq!(len, 5);
+
+    let (len, (buffer, control)) = rx
+        .recv_msg([Vec::with_capacity(5)], Vec::with_capacity(64))
+        .await
+        .unwrap();
+    assert_eq!(len, 5);
+    assert_eq!(buffer[0], b"hello");
+
+    let msgs = CMsgIter::new(&control).collect::<Vec<_>>();
+    assert_eq!(msgs.len(), 1);
+    assert_eq!(msgs[0].level(), libc::SOL_SOCKET);
+    let fds = msgs[0].fds().unwrap();
+    assert_eq!(fds.len(), 1);
+    assert_ne!(fds[0], file.as_raw_fd());
+
+    let mut received = unsafe { File::from_raw_fd(fds[0]) };
+    received.rewind().unwrap();
+    let mut content = String::new();
+    received.read_to_string(&mut content).unwrap();
+    assert_eq!(content, "passed");
+}
+
+#[compio_macros::test]
+async fn datagram_fds() {
+    let (tx, rx) = UnixDatagram::pair().unwrap();
+    let a = tempfile::tempfile().unwrap();
+    let b = tempfile::tempfile().unwrap();
+
+    let mut control = CMsgBuilder::new();
+    control.push_fds(&[a.as_raw_fd(), b.as_raw_fd()]);
+    tx.send_msg([b"two"], control.finish()).await.unwrap();
+
+    let ((len, _), (_, control)) = rx
+        .recv_msg([Vec::with_capacity(8)], Vec::with_capacity(64))
+        .await
+        .unwrap();
+    assert_eq!(len, 3);
+    let fds = CMsgIter::new(&control).find_map(|msg| msg.fds()).unwrap();
+    assert_eq!(fds.len(), 2);
+    for fd in fds {
+        drop(unsafe { File::from_raw_fd(fd) });
+    }
+}
+
+#[compio_macros::test]
+async fn no_control() {
+    let (tx, rx) = UnixDatagram::pair().unwrap();
+    tx.send_msg([b"plain"], Vec::new()).await.unwrap();
+
+    let ((len, _), (_, control)) = rx
+        .recv_msg([Vec::with_capacity(8)], Vec::with_capacity(64))
+        .await
+        .unwrap();
+    assert_eq!(len, 5);
+    assert_eq!(CMsgIter::new(&control).count(), 0);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[compio_macros::test]
+async fn credentials() {
+    let (tx, rx) = UnixDatagram::pair().unwrap();
+    rx.set_passcred(true).unwrap();
+    assert!(rx.passcred().unwrap());
+
+    let (pid, uid, gid) = unsafe { (libc::getpid() as u32, libc::getuid(), libc::getgid()) };
+    let mut control = CMsgBuilder::new();
+    control.push_credentials(pid, uid, gid);
+    tx.send_msg([b"cred"], control.finish()).await.unwrap();
+    // The kernel attaches them even if the sender doesn't.
+    tx.send_msg([b"none"], Vec::new()).await.unwrap();
+
+    for _ in 0..2 {
+        let (_, (_, control)) = rx
+            .recv_msg([Vec::with_capacity(8)], Vec::with_capacity(64))
+            .await
+            .unwrap();
+        let cred = CMsgIter::new(&control)
+            .find_map(|msg| msg.credentials())
+            .unwrap();
+        assert_eq!(cred.pid(), Some(pid));
+        assert_eq!(cred.uid(), uid);
+        assert_eq!(cred.gid(), gid);
+    }
+}
@@ -0,0 +1,42 @@
+#![cfg(target_os = "linux")]
+
+use compio_net::{UnixSeqpacket, UnixSeqpacketListener};
+
+#[compio_macros::test]
+async fn message_boundaries() -> std::io::Result<()> {
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-seqpacket-tests")
+        .tempdir()
+        .unwrap();
+    let sock_path = dir.path().join("seqpacket.sock");
+
+    let listener = UnixSeqpacketListener::bind(&sock_path)?;
+    let client = UnixSeqpacket::connect(&sock_path).await?;
+    let (server, _) = listener.accept().await?;
+
+    client.send("hello").await.0?;
+    client.send("world!").await.0?;
+    let (len, buf) = server.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(len, 5);
+    assert_eq!(buf, b"hello");
+    let (len, buf) = server.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(len, 6);
+    assert_eq!(buf, b"world!");
+
+    client.shutdown().await?;
+    let (len, _) = server.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(len, 0);
+    Ok(())
+}
+
+#[compio_macros::test]
+async fn truncated() -> std::io::Result<()> {
+    let (a, b) = UnixSeqpacket::pair()?;
+    a.send("long message").await.0?;
+    a.send("next").await.0?;
+    let (_, buf) = b.recv(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"long");
+    let (_, buf) = b.recv(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(buf, b"next");
+    Ok(())
+}
@@ -45,3 +45,95 @@ async fn shutdown() -> std::io::Result<()> {
     assert_eq!(n, 0);
     Ok(())
 }
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[compio_macros::test]
+async fn abstract_namespace() -> std::io::Result<()> {
+    let name = format!("compio-uds-tests-{}", std::process::id());
+
+    let listener = UnixListener::bind_abstract(&name)?;
+    assert_eq!(
+        listener.local_addr()?.as_abstract_namespace(),
+        Some(name.as_bytes())
+    );
+
+    let mut client = UnixStream::connect_abstract(&name)?;
+    let (mut server, _) = listener.accept().await?;
+
+    client.write_all("hello").await.0?;
+    let ((), buf) = server.read_exact(Vec::with_capacity(5)).await.unwrap();
+    assert_eq!(&buf[..], b"hello");
+    Ok(())
+}
+
+#[compio_macros::test]
+async fn incoming() -> std::io::Result<()> {
+    use futures_util::StreamExt;
+
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-tests")
+        .tempdir()
+        .unwrap();
+    let sock_path = dir.path().join("incoming.sock");
+
+    let listener = UnixListener::bind(&sock_path)?;
+    let mut incoming = std::pin::pin!(listener.incoming());
+    for _ in 0..2 {
+        let mut client = UnixStream::connect(&sock_path)?;
+        let (mut server, _) = incoming.next().await.unwrap()?;
+        client.write_all("ping").await.0?;
+        let (_, buf) = server.read_exact(Vec::with_capacity(4)).await.unwrap();
+        assert_eq!(&buf[..], b"ping");
+    }
+    Ok(())
+}
+
+#[cfg(feature = "time")]
+#[compio_macros::test]
+async fn connect_timeout() -> std::io::Result<()> {
+    use std::time::Duration;
+
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-tests")
+        .tempdir()
+        .unwrap();
+    let sock_path = dir.path().join("connect_timeout.sock");
+
+    let listener = UnixListener::bind(&sock_path)?;
+    let (mut client, (mut server, _)) = futures_util::try_join!(
+        UnixStream::connect_timeout(&sock_path, Duration::from_secs(10)),
+        listener.accept()
+    )?;
+    client.write_all("ping").await.0?;
+    let (_, buf) = server.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(&buf[..], b"ping");
+
+    let missing = dir.path().join("missing.sock");
+    let err = UnixStream::connect_timeout(&missing, Duration::from_secs(10))
+        .await
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[compio_macros::test]
+async fn peer_cred() -> std::io::Result<()> {
+    let dir = tempfile::Builder::new()
+        .prefix("compio-uds-tests")
+        .tempdir()
+        .unwrap();
+    let sock_path = dir.path().join("peer_cred.sock");
+
+    let listener = UnixListener::bind(&sock_path)?;
+    let client = UnixStream::connect(&sock_path)?;
+    let (server, _) = listener.accept().await?;
+
+    for cred in [client.peer_cred()?, server.peer_cred()?] {
+        assert_eq!(cred.uid(), unsafe { libc::geteuid() });
+        assert_eq!(cred.gid(), unsafe { libc::getegid() });
+        #[cfg(any(target_os = "linux", target_os = "android", target_vendor = "apple"))]
+        assert_eq!(cred.pid(), Some(std::process::id()));
+    }
+    Ok(())
+}
@@ -0,0 +1,47 @@
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use std::io;
+
+use compio_io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use compio_net::{VsockAddr, VsockListener, VsockStream};
+use socket2::SockAddr;
+
+#[test]
+fn addr() {
+    let addr = VsockAddr::new(VsockAddr::CID_HOST, 1234);
+    assert_eq!((addr.cid(), addr.port()), (2, 1234));
+    assert_eq!(addr.to_string(), "2:1234");
+    let sock_addr = SockAddr::from(addr);
+    assert_eq!(sock_addr.as_vsock_address(), Some((2, 1234)));
+}
+
+#[compio_macros::test]
+async fn echo() {
+    // Skip without the vsock loopback transport.
+    let listener =
+        match VsockListener::bind(VsockAddr::new(VsockAddr::CID_LOCAL, VsockAddr::PORT_ANY)) {
+            Ok(listener) => listener,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::AddrNotAvailable | io::ErrorKind::Unsupported
+                ) || e.raw_os_error() == Some(libc::EAFNOSUPPORT) =>
+            {
+                return;
+            }
+            Err(e) => panic!("{e}"),
+        };
+    let addr = listener.local_addr().unwrap();
+    assert_ne!(addr.port(), VsockAddr::PORT_ANY);
+
+    let (client, (mut server, peer)) =
+        futures_util::try_join!(VsockStream::connect(addr), listener.accept()).unwrap();
+    assert_eq!(client.peer_addr().unwrap(), addr);
+    assert_eq!(client.local_addr().unwrap(), peer);
+
+    let mut client = client;
+    client.write_all("hello").await.unwrap();
+    client.shutdown().await.unwrap();
+    let (_, buf) = server.read_to_end(vec![]).await.unwrap();
+    assert_eq!(buf, b"hello");
+}
@@ -0,0 +1,100 @@
+"""An asyncio event loop driven by compio, i.e. io-uring on Linux.
+
+The loop is a ``asyncio.SelectorEventLoop`` with a selector waiting for the
+readiness with the compio driver, so the transports, servers, subprocesses
+and signal handlers of asyncio work unchanged::
+
+    import asyncio
+    import compio
+
+    compio.install()
+    asyncio.run(main())
+"""
+
+import asyncio
+import selectors
+
+from . import _compio
+
+__all__ = (
+    "CompioSelector",
+    "CompioEventLoop",
+    "CompioEventLoopPolicy",
+    "new_event_loop",
+    "install",
+)
+
+
+class CompioSelector(selectors._BaseSelectorImpl):
+    """A selector waiting with the compio driver."""
+
+    def __init__(self):
+        super().__init__()
+        self._poller = _compio.Poller()
+
+    def register(self, fileobj, events, data=None):
+        key = super().register(fileobj, events, data)
+        try:
+            self._poller.register(key.fd, events)
+        except BaseException:
+            super().unregister(fileobj)
+            raise
+        return key
+
+    def unregister(self, fileobj):
+        key = super().unregister(fileobj)
+        try:
+            self._poller.unregister(key.fd)
+        except OSError:
+            # The fd may have been closed.
+            pass
+        return key
+
+    def modify(self, fileobj, events, data=None):
+        try:
+            key = self._fd_to_key[self._fileobj_lookup(fileobj)]
+        except KeyError:
+            raise KeyError(f"{fileobj!r} is not registered") from None
+        if events != key.events:
+            self._poller.modify(key.fd, events)
+            key = key._replace(events=events, data=data)
+            self._fd_to_key[key.fd] = key
+        elif data != key.data:
+            key = key._replace(data=data)
+            self._fd_to_key[key.fd] = key
+        return key
+
+    def select(self, timeout=None):
+        ready = []
+        for fd, events in self._poller.poll(timeout):
+            key = self._fd_to_key.get(fd)
+            if key is not None:
+                ready.append((key, events & key.events))
+        return ready
+
+    def close(self):
+        self._poller.close()
+        super().close()
+
+
+class CompioEventLoop(asyncio.SelectorEventLoop):
+    """An event loop with :class:`CompioSelector`."""
+
+    def __init__(self):
+        super().__init__(CompioSelector())
+
+
+class CompioEventLoopPolicy(asyncio.DefaultEventLoopPolicy):
+    """An event loop policy creating :class:`CompioEventLoop`."""
+
+    _loop_factory = CompioEventLoop
+
+
+def new_event_loop():
+    """Create a :class:`CompioEventLoop`."""
+    return CompioEventLoop()
+
+
+def install():
+    """Set :class:`CompioEventLoopPolicy` as the event loop policy."""
+    asyncio.set_event_loop_policy(CompioEventLoopPolicy())
@@ -0,0 +1,42 @@
+//! Python bindings of compio, providing an asyncio event loop driven by the
+//! compio driver, i.e. io-uring on Linux.
+//!
+//! The extension module `compio._compio` exposes [`Poller`], which waits for
+//! the readiness of the fds with the driver. The Python package `compio`
+//! wraps it as a selector, and provides the event loop and its policy on
+//! top of `asyncio.SelectorEventLoop`:
+//!
+//! ```python
+//! import asyncio
+//! import compio
+//!
+//! compio.install()
+//! asyncio.run(main())
+//! ```
+//!
+//! Only Unix is supported.
+
+#![warn(missing_docs)]
+
+use pyo3::prelude::*;
+
+#[cfg(unix)]
+mod poller;
+
+#[cfg(unix)]
+pub use poller::Poller;
+
+/// The extension module `compio._compio`.
+#[pymodule]
+pub fn _compio(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    #[cfg(unix)]
+    m.add_class::<Poller>()?;
+    m.add("EVENT_READ", EVENT_READ)?;
+    m.add("EVENT_WRITE", EVENT_WRITE)?;
+    Ok(())
+}
+
+/// The readable event, the same as `selectors.EVENT_READ`.
+pub const EVENT_READ: u32 = 1;
+/// The writable event, the same as `selectors.EVENT_WRITE`.
+pub const EVENT_WRITE: u32 = 2;
@@ -0,0 +1,245 @@
+// The wrappers generated by pyo3 convert the returned errors.
+#![allow(clippy::useless_conversion)]
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::poll_fn,
+    io,
+    rc::Rc,
+    task::{Poll, Waker},
+    time::Duration,
+};
+
+use compio_driver::op::{Interest, PollOnce};
+use compio_runtime::{RawFd, Runtime, Task};
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{EVENT_READ, EVENT_WRITE};
+
+/// Wait for the readiness of the fds with the compio driver, like
+/// `select.poll`. The events are level triggered.
+///
+/// A pending poll is submitted to the driver for each registered event, and
+/// submitted again after it is reported.
+#[pyclass(unsendable, module = "compio._compio")]
+pub struct Poller {
+    // `None` after closed.
+    runtime: Option<Runtime>,
+    registrations: HashMap<RawFd, Registration>,
+    shared: Rc<Shared>,
+}
+
+#[derive(Default)]
+struct Registration {
+    events: u32,
+    read: Option<Task<()>>,
+    write: Option<Task<()>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    ready: RefCell<HashMap<RawFd, u32>>,
+    waker: RefCell<Option<Waker>>,
+}
+
+impl Shared {
+    fn wake(&self, fd: RawFd, event: u32) {
+        *self.ready.borrow_mut().entry(fd).or_default() |= event;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+// The runtime is only used on the thread owning the poller, while the GIL is
+// released on the same thread.
+struct AssertSend<T>(T);
+
+// SAFETY: `allow_threads` runs the closure on the calling thread and returns
+// after it, so the wrapped `Rc`s never leave the thread owning the poller,
+// which is `unsendable`. The clones are dropped in the closure, before any
+// other code of the thread could touch the counts again.
+unsafe impl<T> Send for AssertSend<T> {}
+
+impl Poller {
+    fn runtime(&self) -> PyResult<&Runtime> {
+        self.runtime
+            .as_ref()
+            .ok_or_else(|| PyValueError::new_err("I/O operation on closed poller"))
+    }
+
+    fn check_events(events: u32) -> PyResult<()> {
+        if events == 0 || events & !(EVENT_READ | EVENT_WRITE) != 0 {
+            return Err(PyValueError::new_err(format!("Invalid events: {events}")));
+        }
+        Ok(())
+    }
+
+    // Submit the polls of the events not pending.
+    fn arm(&mut self) {
+        let runtime = self.runtime.as_ref().expect("the poller should be open");
+        for (&fd, reg) in &mut self.registrations {
+            let events = reg.events;
+            for (event, interest, task) in [
+                (EVENT_READ, Interest::Readable, &mut reg.read),
+                (EVENT_WRITE, Interest::Writable, &mut reg.write),
+            ] {
+                if events & event == 0 || task.is_some() {
+                    continue;
+                }
+                let shared = self.shared.clone();
+                let rt = runtime.clone();
+                *task = Some(runtime.spawn(async move {
+                    // An error, e.g. of a closed fd, is reported as ready, and
+                    // the following IO gets it.
+                    let _ = rt.submit(PollOnce::new(fd, interest)).await;
+                    shared.wake(fd, event);
+                }));
+            }
+        }
+    }
+
+    // Cancel the pending polls by dropping them in the runtime.
+    fn flush(&self) {
+        if let Some(runtime) = &self.runtime {
+            runtime.block_on(async {});
+        }
+    }
+}
+
+#[pymethods]
+impl Poller {
+    /// Create a poller with a new runtime.
+    #[new]
+    pub fn new() -> PyResult<Self> {
+        Ok(Self {
+            runtime: Some(Runtime::new()?),
+            registrations: HashMap::new(),
+            shared: Rc::default(),
+        })
+    }
+
+    /// Register `fd` for `events`, a mask of `EVENT_READ` and `EVENT_WRITE`.
+    pub fn register(&mut self, fd: RawFd, events: u32) -> PyResult<()> {
+        Self::check_events(events)?;
+        if self.registrations.contains_key(&fd) {
+            return Err(io::Error::from_raw_os_error(libc::EEXIST).into());
+        }
+        self.runtime()?.attach(fd)?;
+        self.registrations.insert(
+            fd,
+            Registration {
+                events,
+                ..Default::default()
+            },
+        );
+        Ok(())
+    }
+
+    /// Change the registered events of `fd`.
+    pub fn modify(&mut self, fd: RawFd, events: u32) -> PyResult<()> {
+        Self::check_events(events)?;
+        self.runtime()?;
+        let reg = self
+            .registrations
+            .get_mut(&fd)
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))?;
+        reg.events = events;
+        if events & EVENT_READ == 0 {
+            reg.read = None;
+        }
+        if events & EVENT_WRITE == 0 {
+            reg.write = None;
+        }
+        self.flush();
+        Ok(())
+    }
+
+    /// Unregister `fd`.
+    pub fn unregister(&mut self, fd: RawFd) -> PyResult<()> {
+        self.runtime()?;
+        self.registrations
+            .remove(&fd)
+            .ok_or_else(|| io::Error::from_raw_os_error(libc::ENOENT))?;
+        self.shared.ready.borrow_mut().remove(&fd);
+        self.flush();
+        Ok(())
+    }
+
+    /// Wait until some fds are ready, or `timeout` seconds elapse, and
+    /// return the list of `(fd, events)`. It waits forever if `timeout` is
+    /// `None`, and doesn't wait if it is not positive.
+    #[pyo3(signature = (timeout = None))]
+    pub fn poll(&mut self, py: Python<'_>, timeout: Option<f64>) -> PyResult<Vec<(RawFd, u32)>> {
+        self.runtime()?;
+        self.arm();
+        let runtime = AssertSend(self.runtime.clone());
+        let shared = AssertSend(self.shared.clone());
+        // The shortest timer makes the runtime poll the driver once without
+        // blocking.
+        let timeout = timeout
+            .and_then(|t| Duration::try_from_secs_f64(t.max(0.0)).ok())
+            .map(|t| t.max(Duration::from_nanos(1)));
+        py.allow_threads(move || {
+            let (runtime, shared) = (runtime, shared);
+            let runtime = runtime.0.as_ref().expect("the poller should be open");
+            let shared = &shared.0;
+            let ready = poll_fn(|cx| {
+                if shared.ready.borrow().is_empty() {
+                    *shared.waker.borrow_mut() = Some(cx.waker().clone());
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            });
+            runtime.block_on(async {
+                match timeout {
+                    Some(timeout) => {
+                        let _ = compio_runtime::time::timeout(timeout, ready).await;
+                    }
+                    None => ready.await,
+                }
+            });
+            shared.waker.take();
+        });
+
+        let mut events = vec![];
+        for (fd, ready) in self.shared.ready.take() {
+            let Some(reg) = self.registrations.get_mut(&fd) else {
+                continue;
+            };
+            if ready & EVENT_READ != 0 {
+                reg.read = None;
+            }
+            if ready & EVENT_WRITE != 0 {
+                reg.write = None;
+            }
+            let ready = ready & reg.events;
+            if ready != 0 {
+                events.push((fd, ready));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Close the poller, and cancel the pending polls. It is a no-op if the
+    /// poller is closed.
+    pub fn close(&mut self) {
+        self.registrations.clear();
+        self.flush();
+        self.runtime = None;
+    }
+
+    /// Whether the poller is closed.
+    #[getter]
+    pub fn closed(&self) -> bool {
+        self.runtime.is_none()
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
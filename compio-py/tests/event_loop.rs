@@ -0,0 +1,127 @@
+#![cfg(unix)]
+
+use pyo3::{prelude::*, types::PyDict};
+
+// Import the package from the source, with the extension module linked in.
+fn run(code: &str) {
+    Python::with_gil(|py| {
+        let module = pyo3::wrap_pymodule!(compio_py::_compio)(py);
+        let sys = py.import_bound("sys").unwrap();
+        sys.getattr("modules")
+            .unwrap()
+            .set_item("compio._compio", module)
+            .unwrap();
+        sys.getattr("path")
+            .unwrap()
+            .call_method1(
+                "insert",
+                (0, concat!(env!("CARGO_MANIFEST_DIR"), "/python")),
+            )
+            .unwrap();
+        let globals = PyDict::new_bound(py);
+        if let Err(e) = py.run_bound(code, Some(&globals), None) {
+            e.print(py);
+            panic!("{e}");
+        }
+    })
+}
+
+#[test]
+fn echo_server() {
+    run(r#"
+import asyncio
+import compio
+
+async def handle(reader, writer):
+    writer.write(await reader.read(100))
+    await writer.drain()
+    writer.close()
+
+async def main():
+    assert isinstance(asyncio.get_running_loop(), compio.CompioEventLoop)
+    server = await asyncio.start_server(handle, "127.0.0.1", 0)
+    port = server.sockets[0].getsockname()[1]
+    async with server:
+        for _ in range(3):
+            reader, writer = await asyncio.open_connection("127.0.0.1", port)
+            writer.write(b"hello")
+            assert await reader.read() == b"hello"
+            writer.close()
+            await writer.wait_closed()
+
+compio.install()
+try:
+    asyncio.run(main())
+finally:
+    asyncio.set_event_loop_policy(None)
+"#);
+}
+
+#[test]
+fn timers_and_threads() {
+    run(r#"
+import asyncio
+import threading
+import time
+import compio
+
+async def main():
+    loop = asyncio.get_running_loop()
+    start = loop.time()
+    await asyncio.sleep(0.05)
+    assert loop.time() - start >= 0.05
+
+    # A callback from another thread wakes the blocked loop.
+    fut = loop.create_future()
+    def set_later():
+        time.sleep(0.05)
+        loop.call_soon_threadsafe(fut.set_result, 42)
+    threading.Thread(target=set_later).start()
+    assert await asyncio.wait_for(fut, 5) == 42
+
+    try:
+        await asyncio.wait_for(loop.create_future(), 0.01)
+    except asyncio.TimeoutError:
+        pass
+    else:
+        raise AssertionError("not timed out")
+
+loop = compio.new_event_loop()
+try:
+    loop.run_until_complete(main())
+finally:
+    loop.close()
+"#);
+}
+
+#[test]
+fn poller() {
+    run(r#"
+import socket
+from compio import _compio
+
+a, b = socket.socketpair()
+poller = _compio.Poller()
+poller.register(a.fileno(), _compio.EVENT_READ | _compio.EVENT_WRITE)
+assert poller.poll(1) == [(a.fileno(), _compio.EVENT_WRITE)]
+
+b.send(b"x")
+poller.modify(a.fileno(), _compio.EVENT_READ)
+assert poller.poll(1) == [(a.fileno(), _compio.EVENT_READ)]
+# Level triggered.
+assert poller.poll(0) == [(a.fileno(), _compio.EVENT_READ)]
+a.recv(1)
+assert poller.poll(0) == []
+
+poller.unregister(a.fileno())
+try:
+    poller.unregister(a.fileno())
+except FileNotFoundError:
+    pass
+else:
+    raise AssertionError("unregistered twice")
+
+poller.close()
+assert poller.closed
+"#);
+}
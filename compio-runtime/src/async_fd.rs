@@ -0,0 +1,145 @@
+use std::io;
+
+use compio_buf::IntoInner;
+use compio_driver::{
+    op::{Interest, PollOnce},
+    AsRawFd, RawFd,
+};
+
+use crate::{Attacher, Runtime};
+
+/// An arbitrary fd driven in the readiness mode, like `async_io::Async`.
+///
+/// The fd is set nonblocking and attached to the current runtime. Wait with
+/// [`readable`](Async::readable) or [`writable`](Async::writable), or do the
+/// IO with [`read_with`](Async::read_with) and
+/// [`write_with`](Async::write_with), which retry on `EWOULDBLOCK`. It could
+/// wrap the fds without a dedicated type, e.g. timerfd, inotify or PTYs.
+///
+/// The runtime only notifies when the fd is ready, and doesn't do any IO on
+/// it, so it could also integrate the libraries which own the IO of the fd,
+/// e.g. the multi interface of libcurl or c-ares: wait for the readiness, and
+/// then hand the fd to the library with [`with_raw`](Async::with_raw).
+///
+/// ```
+/// use std::{
+///     io::{Read, Write},
+///     os::unix::net::UnixStream,
+/// };
+///
+/// use compio_runtime::Async;
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let (mut a, b) = UnixStream::pair().unwrap();
+/// let b = Async::new(b).unwrap();
+///
+/// a.write_all(b"hello").unwrap();
+/// let mut buf = [0u8; 5];
+/// let len = b.read_with(|mut b| b.read(&mut buf)).await.unwrap();
+/// assert_eq!(&buf[..len], b"hello");
+/// # })
+/// ```
+#[derive(Debug)]
+pub struct Async<T: AsRawFd> {
+    inner: Attacher<T>,
+}
+
+impl<T: AsRawFd> Async<T> {
+    /// Set the fd nonblocking, and attach it to the current runtime.
+    pub fn new(io: T) -> io::Result<Self> {
+        let fd = io.as_raw_fd();
+        let flags = compio_driver::syscall!(libc::fcntl(fd, libc::F_GETFL))?;
+        if flags & libc::O_NONBLOCK == 0 {
+            compio_driver::syscall!(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+        }
+        let inner = Attacher::new(io);
+        inner.try_get()?;
+        Ok(Self { inner })
+    }
+
+    /// Wait for the fd to be readable.
+    pub async fn readable(&self) -> io::Result<()> {
+        self.ready(Interest::Readable).await
+    }
+
+    /// Wait for the fd to be writable.
+    pub async fn writable(&self) -> io::Result<()> {
+        self.ready(Interest::Writable).await
+    }
+
+    /// Wait for the fd to be ready with the specified interest.
+    ///
+    /// The readiness may be spurious, so the IO should handle `EAGAIN`.
+    pub async fn ready(&self, interest: Interest) -> io::Result<()> {
+        let fd = self.inner.try_get()?.as_raw_fd();
+        Runtime::current()
+            .submit(PollOnce::new(fd, interest))
+            .await
+            .0?;
+        Ok(())
+    }
+
+    /// Hand the raw fd to `f`, typically between the readiness events.
+    ///
+    /// It fails if the fd is attached to another runtime. The fd should not
+    /// be closed by `f`, because the attach state of the runtime is kept
+    /// until the [`Async`] is dropped.
+    pub fn with_raw<R>(&self, f: impl FnOnce(RawFd) -> R) -> io::Result<R> {
+        let fd = self.inner.try_get()?.as_raw_fd();
+        Ok(f(fd))
+    }
+
+    /// Run the nonblocking read `op` until it doesn't fail with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock), waiting for the fd to be
+    /// readable in between.
+    pub async fn read_with<R>(&self, mut op: impl FnMut(&T) -> io::Result<R>) -> io::Result<R> {
+        loop {
+            match op(self.inner.try_get()?) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.readable().await?,
+                res => return res,
+            }
+        }
+    }
+
+    /// Run the nonblocking write `op` until it doesn't fail with
+    /// [`WouldBlock`](io::ErrorKind::WouldBlock), waiting for the fd to be
+    /// writable in between.
+    pub async fn write_with<R>(&self, mut op: impl FnMut(&T) -> io::Result<R>) -> io::Result<R> {
+        loop {
+            match op(self.inner.try_get()?) {
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => self.writable().await?,
+                res => return res,
+            }
+        }
+    }
+
+    /// Get the reference of the inner source.
+    pub fn get_ref(&self) -> &T {
+        // SAFETY: it is attached in `new`, and the references can't submit
+        // operations.
+        unsafe { self.inner.get_unchecked() }
+    }
+
+    /// Get the mutable reference of the inner source.
+    ///
+    /// # Safety
+    ///
+    /// The fd should not be replaced or closed.
+    pub unsafe fn get_mut(&mut self) -> &mut T {
+        unsafe { self.inner.get_unchecked_mut() }
+    }
+}
+
+impl<T: AsRawFd> AsRawFd for Async<T> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.get_ref().as_raw_fd()
+    }
+}
+
+impl<T: AsRawFd> IntoInner for Async<T> {
+    type Inner = T;
+
+    fn into_inner(self) -> Self::Inner {
+        self.inner.into_inner()
+    }
+}
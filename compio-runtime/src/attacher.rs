@@ -269,7 +269,8 @@ macro_rules! impl_attachable {
 #[macro_export]
 #[doc(hidden)]
 macro_rules! impl_try_as_raw_fd {
-    ($t:ty, $inner:ident) => {
+    // The other fields are initialized with `Default::default()`.
+    ($t:ty, $inner:ident $(, $field:ident)*) => {
         impl $crate::TryAsRawFd for $t {
             fn try_as_raw_fd(&self) -> ::std::io::Result<$crate::RawFd> {
                 self.$inner.try_as_raw_fd()
@@ -283,6 +284,7 @@ macro_rules! impl_try_as_raw_fd {
             unsafe fn from_raw_fd(fd: $crate::RawFd) -> Self {
                 Self {
                     $inner: $crate::FromRawFd::from_raw_fd(fd),
+                    $($field: ::std::default::Default::default(),)*
                 }
             }
         }
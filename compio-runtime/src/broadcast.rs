@@ -0,0 +1,335 @@
+//! A single-producer, multi-consumer broadcast channel.
+//!
+//! Every value sent is stored once, and cloned by each receiver when it is
+//! received. The channel is designed for the tasks on the same thread, and
+//! avoids the atomic operations. Use cheap-to-clone owned buffers, e.g.
+//! `Rc<[u8]>` or `Bytes`, to fan out a message to many connection tasks
+//! without copying it.
+//!
+//! The channel keeps a bounded number of values. When it is full, the oldest
+//! value is dropped, and the receivers which haven't received it are lagging.
+//! The [`LagPolicy`] decides what happens to them.
+//!
+//! ```
+//! use compio_runtime::broadcast;
+//!
+//! # compio_runtime::Runtime::new().unwrap().block_on(async {
+//! let (tx, mut rx1) = broadcast::channel(16);
+//! let mut rx2 = tx.subscribe();
+//!
+//! tx.send("hello").unwrap();
+//! assert_eq!(rx1.recv().await, Ok("hello"));
+//! assert_eq!(rx2.recv().await, Ok("hello"));
+//! # })
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    error::Error,
+    fmt::{Debug, Display},
+    future::poll_fn,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+/// The policy for the receivers which fall behind the channel capacity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LagPolicy {
+    /// The lagging receiver gets [`RecvError::Lagged`] once, and then
+    /// continues from the oldest value kept.
+    #[default]
+    Skip,
+    /// The lagging receiver gets [`RecvError::Lagged`] once, and then it is
+    /// closed. It is useful to drop the slow peers.
+    Disconnect,
+}
+
+/// Create a broadcast channel keeping at most `capacity` values, with
+/// [`LagPolicy::Skip`].
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    channel_with_policy(capacity, LagPolicy::Skip)
+}
+
+/// Create a broadcast channel keeping at most `capacity` values, with the
+/// specified [`LagPolicy`].
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel_with_policy<T: Clone>(
+    capacity: usize,
+    policy: LagPolicy,
+) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be positive");
+    let shared = Rc::new(RefCell::new(Shared {
+        slots: VecDeque::with_capacity(capacity),
+        head: 0,
+        capacity,
+        policy,
+        receivers: 0,
+        next_id: 0,
+        wakers: HashMap::new(),
+        closed: false,
+    }));
+    let sender = Sender { shared };
+    let receiver = sender.subscribe();
+    (sender, receiver)
+}
+
+struct Slot<T> {
+    value: Option<T>,
+    // The receivers which haven't received the value.
+    remaining: usize,
+}
+
+struct Shared<T> {
+    slots: VecDeque<Slot<T>>,
+    // The position of the first slot.
+    head: u64,
+    capacity: usize,
+    policy: LagPolicy,
+    receivers: usize,
+    next_id: u64,
+    wakers: HashMap<u64, Waker>,
+    // The sender has been dropped.
+    closed: bool,
+}
+
+impl<T> Shared<T> {
+    fn tail(&self) -> u64 {
+        self.head + self.slots.len() as u64
+    }
+
+    /// Release the values from `pos` for a receiver that leaves.
+    fn release(&mut self, pos: u64) {
+        let start = pos.saturating_sub(self.head) as usize;
+        for slot in self.slots.iter_mut().skip(start) {
+            slot.remaining -= 1;
+            if slot.remaining == 0 {
+                slot.value = None;
+            }
+        }
+        self.receivers -= 1;
+    }
+
+    fn wake_all(&mut self) {
+        for (_, waker) in self.wakers.drain() {
+            waker.wake();
+        }
+    }
+}
+
+/// The error returned by [`Sender::send`] when there are no receivers.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+impl<T> Debug for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendError").finish_non_exhaustive()
+    }
+}
+
+impl<T> Display for SendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("no receivers of the broadcast channel")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// The error returned by [`Receiver::recv`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RecvError {
+    /// The sender has been dropped and all values have been received, or the
+    /// receiver has been disconnected for lagging.
+    Closed,
+    /// The receiver lagged behind, and the specified number of values were
+    /// dropped before it received them.
+    Lagged(u64),
+}
+
+impl Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Closed => f.write_str("broadcast channel closed"),
+            Self::Lagged(n) => write!(f, "broadcast receiver lagged by {n} values"),
+        }
+    }
+}
+
+impl Error for RecvError {}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TryRecvError {
+    /// There are no new values now.
+    Empty,
+    /// See [`RecvError::Closed`].
+    Closed,
+    /// See [`RecvError::Lagged`].
+    Lagged(u64),
+}
+
+impl Display for TryRecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("broadcast channel empty"),
+            Self::Closed => Display::fmt(&RecvError::Closed, f),
+            Self::Lagged(n) => Display::fmt(&RecvError::Lagged(*n), f),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+/// The sending half of a broadcast channel.
+pub struct Sender<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+}
+
+impl<T: Clone> Sender<T> {
+    /// Send a value to all current receivers.
+    ///
+    /// If the channel is full, the oldest value is dropped. It fails if there
+    /// are no receivers.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut shared = self.shared.borrow_mut();
+        if shared.receivers == 0 {
+            return Err(SendError(value));
+        }
+        if shared.slots.len() == shared.capacity {
+            shared.slots.pop_front();
+            shared.head += 1;
+        }
+        let remaining = shared.receivers;
+        shared.slots.push_back(Slot {
+            value: Some(value),
+            remaining,
+        });
+        shared.wake_all();
+        Ok(())
+    }
+
+    /// Create a new receiver, which receives the values sent after this call.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut shared = self.shared.borrow_mut();
+        shared.receivers += 1;
+        let id = shared.next_id;
+        shared.next_id += 1;
+        Receiver {
+            shared: self.shared.clone(),
+            id,
+            pos: shared.tail(),
+            closed: false,
+        }
+    }
+
+    /// The number of the receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.borrow().receivers
+    }
+}
+
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sender").finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.closed = true;
+        shared.wake_all();
+    }
+}
+
+/// The receiving half of a broadcast channel.
+pub struct Receiver<T> {
+    shared: Rc<RefCell<Shared<T>>>,
+    id: u64,
+    // The position of the next value to receive.
+    pos: u64,
+    // Disconnected for lagging.
+    closed: bool,
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receive the next value without waiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        if self.closed {
+            return Err(TryRecvError::Closed);
+        }
+        let mut shared = self.shared.borrow_mut();
+        if self.pos < shared.head {
+            let lagged = shared.head - self.pos;
+            match shared.policy {
+                LagPolicy::Skip => self.pos = shared.head,
+                LagPolicy::Disconnect => {
+                    shared.release(self.pos);
+                    shared.wakers.remove(&self.id);
+                    self.closed = true;
+                }
+            }
+            return Err(TryRecvError::Lagged(lagged));
+        }
+        if self.pos == shared.tail() {
+            return Err(if shared.closed {
+                TryRecvError::Closed
+            } else {
+                TryRecvError::Empty
+            });
+        }
+        let index = (self.pos - shared.head) as usize;
+        let slot = &mut shared.slots[index];
+        slot.remaining -= 1;
+        let value = if slot.remaining == 0 {
+            slot.value.take()
+        } else {
+            slot.value.clone()
+        };
+        self.pos += 1;
+        Ok(value.expect("the value should be kept for the receiver"))
+    }
+
+    /// Receive the next value, and wait if there are no new values.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        poll_fn(|cx| match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Empty) => {
+                self.shared
+                    .borrow_mut()
+                    .wakers
+                    .insert(self.id, cx.waker().clone());
+                Poll::Pending
+            }
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(n)) => Poll::Ready(Err(RecvError::Lagged(n))),
+        })
+        .await
+    }
+}
+
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Receiver")
+            .field("pos", &self.pos)
+            .field("closed", &self.closed)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.borrow_mut();
+        shared.wakers.remove(&self.id);
+        if !self.closed {
+            shared.release(self.pos);
+        }
+    }
+}
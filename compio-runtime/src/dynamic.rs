@@ -0,0 +1,219 @@
+//! Object-safe runtime traits.
+//!
+//! The plugins could accept `&dyn DynRuntime` instead of being generic over
+//! the runtime, and the tests could inject a fake, e.g. with a manual clock.
+//! The futures are boxed, and the generic helpers are provided on
+//! `dyn DynRuntime`. The I/O methods take an attached raw fd and an owned
+//! `Vec<u8>`; a fake could serve them from memory, and the default
+//! implementations return [`io::ErrorKind::Unsupported`].
+//!
+//! ```
+//! use std::time::Duration;
+//!
+//! use compio_runtime::{dynamic::DynRuntime, Runtime};
+//!
+//! async fn plugin(rt: &dyn DynRuntime) -> i32 {
+//!     rt.sleep(Duration::from_millis(1)).await;
+//!     let a = rt.spawn(async { 1 });
+//!     let b = rt.spawn_blocking(|| 2);
+//!     a.await + b.await
+//! }
+//!
+//! let rt = Runtime::new().unwrap();
+//! assert_eq!(rt.block_on(plugin(&rt)), 3);
+//! ```
+
+use std::{
+    cell::Cell,
+    future::Future,
+    io,
+    rc::Rc,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use compio_buf::{BufResult, IntoInner};
+use compio_driver::{
+    op::{BufResultExt, ReadAt, Recv, Send as SendOp, WriteAt},
+    RawFd,
+};
+use futures_util::{future::LocalBoxFuture, FutureExt};
+
+use crate::Runtime;
+
+fn unsupported(buffer: Vec<u8>) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+    Box::pin(std::future::ready(BufResult(
+        Err(io::ErrorKind::Unsupported.into()),
+        buffer,
+    )))
+}
+
+/// An object-safe runtime.
+pub trait DynRuntime {
+    /// Spawns a task. The returned future completes when the task completes,
+    /// and dropping it cancels the task.
+    fn spawn_boxed(&self, future: LocalBoxFuture<'static, ()>) -> LocalBoxFuture<'static, ()>;
+
+    /// Runs the blocking closure on another thread, and waits for it. The
+    /// closure is not cancelled even if the future is dropped.
+    fn spawn_blocking_boxed(
+        &self,
+        f: Box<dyn FnOnce() + Send + 'static>,
+    ) -> LocalBoxFuture<'static, ()>;
+
+    /// Waits until the duration has elapsed on the clock of the runtime.
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()>;
+
+    /// The current time on the clock of the runtime.
+    fn now(&self) -> Instant;
+
+    /// Attaches the raw fd to the runtime, before the I/O methods are called
+    /// on it.
+    fn attach(&self, _fd: RawFd) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Reads the file at the offset into the buffer, from its initialized
+    /// length.
+    fn read_at(
+        &self,
+        _fd: RawFd,
+        buffer: Vec<u8>,
+        _offset: u64,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        unsupported(buffer)
+    }
+
+    /// Writes the initialized bytes of the buffer to the file at the offset.
+    fn write_at(
+        &self,
+        _fd: RawFd,
+        buffer: Vec<u8>,
+        _offset: u64,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        unsupported(buffer)
+    }
+
+    /// Receives from the socket into the buffer, from its initialized length.
+    fn recv(
+        &self,
+        _fd: RawFd,
+        buffer: Vec<u8>,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        unsupported(buffer)
+    }
+
+    /// Sends the initialized bytes of the buffer to the socket.
+    fn send(
+        &self,
+        _fd: RawFd,
+        buffer: Vec<u8>,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        unsupported(buffer)
+    }
+}
+
+impl dyn DynRuntime + '_ {
+    /// Spawns a task, and returns a future of its output. Dropping the
+    /// future cancels the task.
+    pub fn spawn<F: Future + 'static>(&self, future: F) -> impl Future<Output = F::Output> {
+        let output = Rc::new(Cell::new(None));
+        let task = self.spawn_boxed(Box::pin({
+            let output = output.clone();
+            async move { output.set(Some(future.await)) }
+        }));
+        async move {
+            task.await;
+            output.take().expect("the task should complete")
+        }
+    }
+
+    /// Runs the blocking closure on another thread, and waits for its
+    /// output.
+    pub fn spawn_blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> impl Future<Output = T> {
+        let output = Arc::new(Mutex::new(None));
+        let task = self.spawn_blocking_boxed(Box::new({
+            let output = output.clone();
+            move || *output.lock().unwrap_or_else(PoisonError::into_inner) = Some(f())
+        }));
+        async move {
+            task.await;
+            output
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .take()
+                .expect("the closure should complete")
+        }
+    }
+}
+
+impl DynRuntime for Runtime {
+    fn spawn_boxed(&self, future: LocalBoxFuture<'static, ()>) -> LocalBoxFuture<'static, ()> {
+        Box::pin(self.spawn(future))
+    }
+
+    fn spawn_blocking_boxed(
+        &self,
+        f: Box<dyn FnOnce() + Send + 'static>,
+    ) -> LocalBoxFuture<'static, ()> {
+        // The closures of the runtime should be `Sync`, and the mutex is only
+        // used to move `f` to the thread.
+        let f = Mutex::new(f);
+        Box::pin(
+            self.spawn_blocking(move || f.into_inner().unwrap_or_else(PoisonError::into_inner)()),
+        )
+    }
+
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        Box::pin(crate::time::sleep(duration))
+    }
+
+    fn now(&self) -> Instant {
+        self.inner().now()
+    }
+
+    fn attach(&self, fd: RawFd) -> io::Result<()> {
+        Runtime::attach(self, fd)
+    }
+
+    fn read_at(
+        &self,
+        fd: RawFd,
+        buffer: Vec<u8>,
+        offset: u64,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        let op = self.submit(ReadAt::new(fd, offset, buffer));
+        Box::pin(op.map(|res| res.into_inner().map_advanced()))
+    }
+
+    fn write_at(
+        &self,
+        fd: RawFd,
+        buffer: Vec<u8>,
+        offset: u64,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        let op = self.submit(WriteAt::new(fd, offset, buffer));
+        Box::pin(op.map(IntoInner::into_inner))
+    }
+
+    fn recv(
+        &self,
+        fd: RawFd,
+        buffer: Vec<u8>,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        let op = self.submit(Recv::new(fd, buffer));
+        Box::pin(op.map(|res| res.into_inner().map_advanced()))
+    }
+
+    fn send(
+        &self,
+        fd: RawFd,
+        buffer: Vec<u8>,
+    ) -> LocalBoxFuture<'static, BufResult<usize, Vec<u8>>> {
+        let op = self.submit(SendOp::new(fd, buffer));
+        Box::pin(op.map(IntoInner::into_inner))
+    }
+}
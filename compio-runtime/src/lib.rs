@@ -12,15 +12,28 @@
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 #![warn(missing_docs)]
 
+#[cfg(unix)]
+mod async_fd;
 mod attacher;
 mod runtime;
 
+pub mod broadcast;
+#[cfg(feature = "time")]
+pub mod dynamic;
 #[cfg(feature = "event")]
 pub mod event;
 #[cfg(feature = "time")]
+pub mod retry;
+#[cfg(feature = "time")]
 pub mod time;
 
+#[cfg(unix)]
+pub use async_fd::Async;
 pub use async_task::Task;
 pub use attacher::*;
 use compio_buf::BufResult;
-pub use runtime::{spawn, spawn_blocking, EnterGuard, Runtime, RuntimeBuilder};
+pub use compio_driver::{BorrowedBuffer, BufferPool, PoolStats, ProactorBuilder, QueuePolicy};
+pub use runtime::{
+    run_blocking_on_driver, spawn, spawn_blocking, EnterGuard, Histogram, OpDump, OpLatency,
+    Runtime, RuntimeBuilder, RuntimeDump, TaskDump,
+};
@@ -0,0 +1,164 @@
+//! Retrying the operations failed with transient errors.
+
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    io,
+    time::Duration,
+};
+
+use crate::time::sleep;
+
+/// Retries an idempotent operation, e.g. connecting, resolving or opening,
+/// when it fails with a transient error, with an exponential backoff between
+/// the attempts.
+///
+/// The backoff waits on the timer of the current runtime. Dropping the
+/// returned future cancels the pending attempt or backoff, so the retries
+/// could be bounded in time with [`timeout`](crate::time::timeout).
+///
+/// ```
+/// use std::{cell::Cell, io, time::Duration};
+///
+/// use compio_runtime::retry::RetryPolicy;
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let attempts = Cell::new(0);
+/// let policy = RetryPolicy::new().initial_delay(Duration::from_millis(1));
+/// let res = policy
+///     .retry(|| async {
+///         attempts.set(attempts.get() + 1);
+///         if attempts.get() < 3 {
+///             Err(io::ErrorKind::ConnectionRefused.into())
+///         } else {
+///             Ok(attempts.get())
+///         }
+///     })
+///     .await;
+/// assert_eq!(res.unwrap(), 3);
+/// # })
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_retries: usize,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: f64,
+    jitter: bool,
+    retry_if: fn(&io::Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy retrying the transient errors 3 times, waiting 100ms
+    /// before the first retry, and doubling the delay up to 10s. The delays
+    /// are randomized by default.
+    pub fn new() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            retry_if: is_transient,
+        }
+    }
+
+    /// Set the retries after the first attempt.
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the delay before the first retry.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the upper bound of the delays.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Set the factor by which the delay grows after each retry.
+    pub fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Set whether each delay is randomized between its half and itself, so
+    /// that the clients failed together don't retry together.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Set the errors to retry. By default, they are the refused, reset and
+    /// aborted connections, the timeouts, and the interrupted or would-block
+    /// calls.
+    pub fn retry_if(mut self, retry_if: fn(&io::Error) -> bool) -> Self {
+        self.retry_if = retry_if;
+        self
+    }
+
+    /// The delay before the `retry`-th retry, counted from 0, without the
+    /// jitter.
+    pub fn delay(&self, retry: usize) -> Duration {
+        let factor = self.multiplier.powi(retry.min(i32::MAX as usize) as i32);
+        self.initial_delay
+            .mul_f64(factor.min(u32::MAX as f64))
+            .min(self.max_delay)
+    }
+
+    /// Call `f` until it succeeds, fails with an error not to retry, or runs
+    /// out of retries, and return the last result.
+    pub async fn retry<T, F, Fut>(&self, mut f: F) -> io::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<T>>,
+    {
+        let mut retry = 0;
+        loop {
+            match f().await {
+                Err(e) if retry < self.max_retries && (self.retry_if)(&e) => {
+                    let mut delay = self.delay(retry);
+                    if self.jitter {
+                        delay = delay / 2 + delay.mul_f64(random() / 2.0);
+                    }
+                    sleep(delay).await;
+                    retry += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Whether the error is likely to go away by retrying, which is the default
+/// of [`RetryPolicy::retry_if`].
+pub fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::TimedOut
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+    )
+}
+
+// A random number in [0, 1) from the random keys of the std hasher, which is
+// good enough for the jitter.
+fn random() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
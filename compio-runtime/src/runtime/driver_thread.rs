@@ -0,0 +1,80 @@
+use std::{
+    any::Any,
+    future::Future,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc, Mutex},
+    task::{Context, Poll},
+    thread,
+};
+
+use futures_util::task::AtomicWaker;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A dedicated thread beside the driver, running the short blocking closures
+/// one by one, apart from the blocking pool.
+pub(crate) struct DriverThread {
+    sender: mpsc::Sender<Job>,
+}
+
+impl DriverThread {
+    pub fn new(id: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name(format!("compio-driver-{id}"))
+            .spawn(move || {
+                // Exit when the runtime is dropped.
+                while let Ok(job) = receiver.recv() {
+                    job();
+                }
+            })
+            .expect("cannot spawn the driver thread");
+        Self { sender }
+    }
+
+    pub fn run<T: Send + 'static>(
+        &self,
+        f: impl (FnOnce() -> T) + Send + 'static,
+    ) -> impl Future<Output = T> {
+        let shared = Arc::new(Shared {
+            result: Mutex::new(None),
+            waker: AtomicWaker::new(),
+        });
+        let job = {
+            let shared = shared.clone();
+            Box::new(move || {
+                let res = catch_unwind(AssertUnwindSafe(f));
+                *shared.result.lock().unwrap() = Some(res);
+                // Waking the task notifies the driver.
+                shared.waker.wake();
+            })
+        };
+        self.sender
+            .send(job)
+            .expect("the driver thread should be alive");
+        DriverThreadFuture { shared }
+    }
+}
+
+struct Shared<T> {
+    result: Mutex<Option<Result<T, Box<dyn Any + Send>>>>,
+    waker: AtomicWaker,
+}
+
+struct DriverThreadFuture<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Future for DriverThreadFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        self.shared.waker.register(cx.waker());
+        match self.shared.result.lock().unwrap().take() {
+            Some(Ok(res)) => Poll::Ready(res),
+            Some(Err(e)) => resume_unwind(e),
+            None => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,287 @@
+#[cfg(feature = "backtrace")]
+use std::{backtrace::Backtrace, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use super::stats::{op_name, Histogram, OpLatency};
+
+/// A snapshot of a [`Runtime`], returned by [`Runtime::dump`].
+///
+/// It is useful to find the tasks and operations stuck in a hanging service,
+/// and needs [`RuntimeBuilder::dump`] to be enabled. The [`Display`] output
+/// lists the tasks with the operations they wait for. With the `backtrace`
+/// feature, the tasks record where they are spawned.
+///
+/// [`Runtime`]: crate::Runtime
+/// [`Runtime::dump`]: crate::Runtime::dump
+/// [`RuntimeBuilder::dump`]: crate::RuntimeBuilder::dump
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RuntimeDump {
+    /// The live tasks, ordered by id.
+    pub tasks: Vec<TaskDump>,
+    /// The pending operations, ordered by age from the oldest.
+    pub ops: Vec<OpDump>,
+    /// The number of tasks scheduled to run.
+    pub scheduled: usize,
+    /// The number of pending timers.
+    pub timers: usize,
+}
+
+/// A live task in [`RuntimeDump`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct TaskDump {
+    /// The id of the task, unique in the runtime.
+    pub id: u64,
+    /// The time since the task is spawned.
+    pub age: Duration,
+    /// The backtrace captured when the task is spawned.
+    #[cfg(feature = "backtrace")]
+    pub backtrace: Arc<Backtrace>,
+}
+
+/// A pending operation in [`RuntimeDump`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct OpDump {
+    /// The type name of the operation.
+    pub name: &'static str,
+    /// The id of the task which submitted the operation, if it is submitted
+    /// inside a task.
+    pub task: Option<u64>,
+    /// The time since the operation is submitted.
+    pub age: Duration,
+}
+
+impl RuntimeDump {
+    /// Keep the operations pending for at least `age`, and the tasks which
+    /// submitted them.
+    ///
+    /// With the `backtrace` feature, the [`Display`] output of the returned
+    /// dump shows where the tasks owning the stuck operations are spawned.
+    pub fn stuck(mut self, age: Duration) -> Self {
+        self.ops.retain(|op| op.age >= age);
+        let ops = &self.ops;
+        self.tasks
+            .retain(|task| ops.iter().any(|op| op.task == Some(task.id)));
+        self
+    }
+}
+
+impl Display for RuntimeDump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} tasks ({} scheduled), {} ops, {} timers",
+            self.tasks.len(),
+            self.scheduled,
+            self.ops.len(),
+            self.timers
+        )?;
+        for task in &self.tasks {
+            writeln!(f, "task {} ({:?})", task.id, task.age)?;
+            #[cfg(feature = "backtrace")]
+            writeln!(f, "  spawned at:\n{}", task.backtrace)?;
+            for op in self.ops.iter().filter(|op| op.task == Some(task.id)) {
+                writeln!(f, "  {} ({:?})", op.name, op.age)?;
+            }
+        }
+        for op in self.ops.iter().filter(|op| op.task.is_none()) {
+            writeln!(f, "{} ({:?})", op.name, op.age)?;
+        }
+        Ok(())
+    }
+}
+
+struct TaskInfo {
+    spawned: Instant,
+    #[cfg(feature = "backtrace")]
+    backtrace: Arc<Backtrace>,
+}
+
+struct OpInfo {
+    name: &'static str,
+    task: Option<u64>,
+    submitted: Instant,
+}
+
+pub(crate) struct Registry {
+    // Whether the tasks and ops are tracked for the dump.
+    dump_enabled: bool,
+    next_id: u64,
+    // The task being polled.
+    current: Option<u64>,
+    tasks: HashMap<u64, TaskInfo>,
+    ops: HashMap<usize, OpInfo>,
+    // The completion latency of each operation type, if enabled.
+    latency_enabled: bool,
+    latency: HashMap<&'static str, Histogram>,
+}
+
+impl Registry {
+    pub fn new(dump_enabled: bool) -> Self {
+        Self {
+            dump_enabled,
+            next_id: 0,
+            current: None,
+            tasks: HashMap::new(),
+            ops: HashMap::new(),
+            latency_enabled: false,
+            latency: HashMap::new(),
+        }
+    }
+
+    pub fn dump_enabled(&self) -> bool {
+        self.dump_enabled
+    }
+
+    pub fn add_op<T>(&mut self, key: usize) {
+        // The latency also needs the submission time.
+        if !self.dump_enabled && !self.latency_enabled {
+            return;
+        }
+        self.ops.insert(
+            key,
+            OpInfo {
+                name: std::any::type_name::<T>(),
+                task: self.current,
+                submitted: Instant::now(),
+            },
+        );
+    }
+
+    pub fn remove_op(&mut self, key: usize) {
+        self.ops.remove(&key);
+    }
+
+    // Remove a completed op, and record its latency.
+    pub fn complete_op(&mut self, key: usize) {
+        if let Some(op) = self.ops.remove(&key) {
+            self.record_latency(op.name, op.submitted.elapsed());
+        }
+    }
+
+    pub fn record_latency(&mut self, name: &'static str, latency: Duration) {
+        if self.latency_enabled {
+            self.latency
+                .entry(op_name(name))
+                .or_default()
+                .record(latency);
+        }
+    }
+
+    pub fn latency_enabled(&self) -> bool {
+        self.latency_enabled
+    }
+
+    pub fn set_latency_enabled(&mut self, enabled: bool) {
+        self.latency_enabled = enabled;
+    }
+
+    pub fn op_latency(&self) -> Vec<OpLatency> {
+        let mut ops = self
+            .latency
+            .iter()
+            .map(|(&name, histogram)| OpLatency {
+                name,
+                histogram: histogram.clone(),
+            })
+            .collect::<Vec<_>>();
+        ops.sort_by_key(|op| op.name);
+        ops
+    }
+
+    pub fn reset_latency(&mut self) {
+        self.latency.clear();
+    }
+
+    pub fn dump(&self, scheduled: usize, timers: usize) -> RuntimeDump {
+        let now = Instant::now();
+        let mut tasks = self
+            .tasks
+            .iter()
+            .map(|(&id, info)| TaskDump {
+                id,
+                age: now.duration_since(info.spawned),
+                #[cfg(feature = "backtrace")]
+                backtrace: info.backtrace.clone(),
+            })
+            .collect::<Vec<_>>();
+        tasks.sort_by_key(|task| task.id);
+        let mut ops = self
+            .ops
+            .values()
+            .map(|op| OpDump {
+                name: op.name,
+                task: op.task,
+                age: now.duration_since(op.submitted),
+            })
+            .collect::<Vec<_>>();
+        ops.sort_by_key(|op| std::cmp::Reverse(op.age));
+        RuntimeDump {
+            tasks,
+            ops,
+            scheduled,
+            timers,
+        }
+    }
+}
+
+/// A future registered as a live task until it is dropped.
+pub(crate) struct Tracked<F> {
+    future: F,
+    id: u64,
+    registry: Rc<RefCell<Registry>>,
+}
+
+impl<F> Tracked<F> {
+    pub fn new(future: F, registry: Rc<RefCell<Registry>>) -> Self {
+        let id = {
+            let mut r = registry.borrow_mut();
+            let id = r.next_id;
+            r.next_id += 1;
+            r.tasks.insert(
+                id,
+                TaskInfo {
+                    spawned: Instant::now(),
+                    #[cfg(feature = "backtrace")]
+                    backtrace: Arc::new(Backtrace::force_capture()),
+                },
+            );
+            id
+        };
+        Self {
+            future,
+            id,
+            registry,
+        }
+    }
+}
+
+impl<F: Future> Future for Tracked<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: `future` is never moved.
+        let this = unsafe { self.get_unchecked_mut() };
+        let prev = this.registry.borrow_mut().current.replace(this.id);
+        let res = unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx);
+        this.registry.borrow_mut().current = prev;
+        res
+    }
+}
+
+impl<F> Drop for Tracked<F> {
+    fn drop(&mut self) {
+        self.registry.borrow_mut().tasks.remove(&self.id);
+    }
+}
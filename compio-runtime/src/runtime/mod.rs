@@ -8,26 +8,41 @@ use std::{
         Arc,
     },
     task::{Context, Poll, Waker},
+    time::Instant,
 };
 
 use async_task::{Runnable, Task};
 use compio_buf::IntoInner;
 use compio_driver::{
-    op::Asyncify, AsRawFd, Key, OpCode, Proactor, ProactorBuilder, PushEntry, RawFd,
+    op::Asyncify, AsRawFd, BufferPool, Key, OpCode, PoolStats, Proactor, ProactorBuilder,
+    PushEntry, QueuePolicy, RawFd,
 };
 use compio_log::{debug, instrument};
 use crossbeam_queue::SegQueue;
-use futures_util::{future::Either, FutureExt};
+use futures_util::{future::Either, stream, FutureExt, Stream, StreamExt};
 use smallvec::SmallVec;
 
+mod driver_thread;
+mod dump;
 pub(crate) mod op;
+mod stats;
 #[cfg(feature = "time")]
 pub(crate) mod time;
 
+pub use dump::{OpDump, RuntimeDump, TaskDump};
+pub use stats::{Histogram, OpLatency};
+
 #[cfg(feature = "time")]
-use crate::runtime::time::{TimerFuture, TimerRuntime};
+use crate::runtime::{
+    op::UntilFuture,
+    time::{TimerFuture, TimerRuntime},
+};
 use crate::{
-    runtime::op::{OpFuture, OpRuntime},
+    runtime::{
+        driver_thread::DriverThread,
+        dump::{Registry, Tracked},
+        op::{BoxedOpFuture, MultishotStream, OpFuture, OpRuntime, PoolRoom},
+    },
     BufResult,
 };
 
@@ -51,17 +66,32 @@ pub(crate) struct RuntimeInner {
     op_runtime: RefCell<OpRuntime>,
     #[cfg(feature = "time")]
     timer_runtime: RefCell<TimerRuntime>,
+    registry: Rc<RefCell<Registry>>,
+    driver_thread: once_cell::unsync::OnceCell<DriverThread>,
+    // The submissions waiting for the room in the thread pool.
+    pool_waiters: RefCell<Vec<Waker>>,
 }
 
+// How often the submissions waiting for the thread pool retry, in case the
+// pool is shared and its threads are freed by the other runtimes.
+const POOL_RETRY_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1);
+
+// How often the timers of a clock not moving in realtime are checked.
+#[cfg(feature = "time")]
+const CLOCK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1);
+
 impl RuntimeInner {
-    pub fn new(builder: &ProactorBuilder) -> io::Result<Self> {
+    pub fn new(builder: &RuntimeBuilder) -> io::Result<Self> {
         Ok(Self {
             id: RUNTIME_COUNTER.fetch_add(1, Ordering::AcqRel),
-            driver: RefCell::new(builder.build()?),
+            driver: RefCell::new(builder.proactor_builder.build()?),
             runnables: Arc::new(SegQueue::new()),
             op_runtime: RefCell::default(),
             #[cfg(feature = "time")]
-            timer_runtime: RefCell::new(TimerRuntime::new()),
+            timer_runtime: RefCell::new(TimerRuntime::new(builder.clock.clone())),
+            registry: Rc::new(RefCell::new(Registry::new(builder.dump))),
+            driver_thread: once_cell::unsync::OnceCell::new(),
+            pool_waiters: RefCell::default(),
         })
     }
 
@@ -81,7 +111,12 @@ impl RuntimeInner {
             runnables.push(runnable);
             handle.notify().ok();
         };
-        let (runnable, task) = async_task::spawn_unchecked(future, schedule);
+        let (runnable, task) = if self.registry.borrow().dump_enabled() {
+            let future = Tracked::new(future, self.registry.clone());
+            async_task::spawn_unchecked(future, schedule)
+        } else {
+            async_task::spawn_unchecked(future, schedule)
+        };
         runnable.schedule();
         task
     }
@@ -120,6 +155,15 @@ impl RuntimeInner {
         self.submit(op).map(|BufResult(_, op)| op.into_inner())
     }
 
+    pub fn run_blocking_on_driver<T: Send + 'static>(
+        &self,
+        f: impl (FnOnce() -> T) + Send + 'static,
+    ) -> impl Future<Output = T> {
+        self.driver_thread
+            .get_or_init(|| DriverThread::new(self.id))
+            .run(f)
+    }
+
     pub fn attach(&self, fd: RawFd) -> io::Result<()> {
         self.driver.borrow_mut().attach(fd)
     }
@@ -129,20 +173,211 @@ impl RuntimeInner {
     }
 
     pub fn submit<T: OpCode + 'static>(&self, op: T) -> impl Future<Output = BufResult<usize, T>> {
-        match self.submit_raw(op) {
-            PushEntry::Pending(user_data) => {
-                // Clear previous waker if exists.
-                self.op_runtime.borrow_mut().cancel(*user_data);
+        self.submit_with_flags(op).map(|(res, _)| res)
+    }
+
+    pub fn submit_with_flags<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> impl Future<Output = (BufResult<usize, T>, u32)> {
+        let start = self.latency_start();
+        let res = self.driver.borrow_mut().try_push(op);
+        match res {
+            Ok(PushEntry::Pending(user_data)) => {
+                self.track_op(user_data);
                 Either::Left(OpFuture::new(user_data))
             }
+            Ok(PushEntry::Ready(res)) => {
+                self.record_ready::<T>(start);
+                Either::Right(Either::Left(ready((res, 0))))
+            }
+            Err((e, op)) if self.waits_for_pool(&e) => {
+                Either::Right(Either::Right(Box::pin(submit_with_pool_room(op))))
+            }
+            Err((e, op)) => Either::Right(Either::Left(ready((BufResult(Err(e), op), 0)))),
+        }
+    }
+
+    // Whether the op is rejected by a full thread pool, and should wait for
+    // the room.
+    fn waits_for_pool(&self, e: &io::Error) -> bool {
+        #[cfg(unix)]
+        const BUSY: i32 = libc::EBUSY;
+        #[cfg(windows)]
+        const BUSY: i32 = windows_sys::Win32::Foundation::ERROR_BUSY as _;
+
+        e.raw_os_error() == Some(BUSY)
+            && matches!(
+                self.driver.borrow().thread_pool().policy(),
+                QueuePolicy::Wait(_)
+            )
+    }
+
+    pub fn wait_pool_room(&self, waker: Waker) {
+        self.pool_waiters.borrow_mut().push(waker);
+    }
+
+    pub fn thread_pool_stats(&self) -> PoolStats {
+        self.driver.borrow().thread_pool().stats()
+    }
+
+    pub fn try_submit<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> Result<impl Future<Output = BufResult<usize, T>>, (io::Error, T)> {
+        let start = self.latency_start();
+        let entry = self.driver.borrow_mut().try_push(op)?;
+        Ok(match entry {
+            PushEntry::Pending(user_data) => {
+                self.track_op(user_data);
+                Either::Left(OpFuture::new(user_data).map(|(res, _)| res))
+            }
+            PushEntry::Ready(res) => {
+                self.record_ready::<T>(start);
+                Either::Right(ready(res))
+            }
+        })
+    }
+
+    fn latency_start(&self) -> Option<Instant> {
+        self.registry.borrow().latency_enabled().then(Instant::now)
+    }
+
+    // Record the latency of an op completed on submission.
+    fn record_ready<T>(&self, start: Option<Instant>) {
+        if let Some(start) = start {
+            self.registry
+                .borrow_mut()
+                .record_latency(std::any::type_name::<T>(), start.elapsed());
+        }
+    }
+
+    pub fn set_op_latency_enabled(&self, enabled: bool) {
+        self.registry.borrow_mut().set_latency_enabled(enabled);
+    }
+
+    pub fn op_latency(&self) -> Vec<OpLatency> {
+        self.registry.borrow().op_latency()
+    }
+
+    pub fn reset_op_latency(&self) {
+        self.registry.borrow_mut().reset_latency();
+    }
+
+    #[cfg(feature = "time")]
+    pub fn submit_until<T: OpCode + 'static>(
+        &self,
+        op: T,
+        deadline: Instant,
+    ) -> impl Future<Output = BufResult<usize, T>> {
+        if deadline <= crate::time::now() {
+            return Either::Right(Either::Left(ready(BufResult(
+                Err(op::deadline_elapsed()),
+                op,
+            ))));
+        }
+        let start = self.latency_start();
+        let res = self.driver.borrow_mut().try_push(op);
+        match res {
+            Ok(PushEntry::Pending(user_data)) => {
+                self.track_op(user_data);
+                Either::Left(UntilFuture::new(
+                    *user_data,
+                    OpFuture::new(user_data),
+                    deadline,
+                ))
+            }
+            Ok(PushEntry::Ready(res)) => {
+                self.record_ready::<T>(start);
+                Either::Right(Either::Left(ready(res)))
+            }
+            // The blocking ops couldn't be interrupted.
+            Err((e, op)) if self.waits_for_pool(&e) => Either::Right(Either::Right(Box::pin(
+                submit_with_pool_room(op).map(|(res, _)| res),
+            ))),
+            Err((e, op)) => Either::Right(Either::Left(ready(BufResult(Err(e), op)))),
+        }
+    }
+
+    #[cfg(feature = "time")]
+    pub fn submit_boxed_until<T: OpCode + 'static>(
+        &self,
+        op: Box<T>,
+        deadline: Instant,
+    ) -> impl Future<Output = BufResult<usize, Box<T>>> {
+        if deadline <= crate::time::now() {
+            return Either::Right(ready(BufResult(Err(op::deadline_elapsed()), op)));
+        }
+        let entry = self.driver.borrow_mut().push_boxed(op);
+        match entry {
+            PushEntry::Pending(user_data) => {
+                self.track_op(user_data);
+                Either::Left(UntilFuture::new(
+                    *user_data,
+                    BoxedOpFuture::new(user_data),
+                    deadline,
+                ))
+            }
             PushEntry::Ready(res) => Either::Right(ready(res)),
         }
     }
 
     #[cfg(feature = "time")]
-    pub fn create_timer(&self, delay: std::time::Duration) -> impl Future<Output = ()> {
+    pub fn interrupt_op(&self, user_data: usize) {
+        self.driver.borrow_mut().interrupt(user_data);
+    }
+
+    pub fn submit_boxed<T: OpCode + 'static>(
+        &self,
+        op: Box<T>,
+    ) -> impl Future<Output = BufResult<usize, Box<T>>> {
+        let entry = self.driver.borrow_mut().push_boxed(op);
+        match entry {
+            PushEntry::Pending(user_data) => {
+                self.track_op(user_data);
+                Either::Left(BoxedOpFuture::new(user_data).map(|(res, _)| res))
+            }
+            PushEntry::Ready(res) => Either::Right(ready(res)),
+        }
+    }
+
+    pub fn submit_multishot<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> impl Stream<Item = (io::Result<usize>, u32)> {
+        match self.submit_raw(op) {
+            PushEntry::Pending(user_data) => {
+                self.track_op(user_data);
+                Either::Left(MultishotStream::new(user_data))
+            }
+            PushEntry::Ready(BufResult(res, _)) => Either::Right(stream::once(ready((res, 0)))),
+        }
+    }
+
+    pub fn create_buffer_pool(&self, entries: u16, buffer_len: usize) -> io::Result<BufferPool> {
+        self.driver
+            .borrow_mut()
+            .create_buffer_pool(entries, buffer_len)
+    }
+
+    pub fn release_buffer_pool(&self, pool: BufferPool) -> io::Result<()> {
+        self.driver.borrow_mut().release_buffer_pool(pool)
+    }
+
+    fn track_op<T>(&self, user_data: Key<T>) {
+        // Clear previous waker if exists.
+        self.op_runtime.borrow_mut().cancel(*user_data);
+        self.registry.borrow_mut().add_op::<T>(*user_data);
+    }
+
+    #[cfg(feature = "time")]
+    pub fn create_timer(
+        &self,
+        delay: std::time::Duration,
+        mode: crate::time::TimerMode,
+    ) -> impl Future<Output = ()> {
         let mut timer_runtime = self.timer_runtime.borrow_mut();
-        if let Some(key) = timer_runtime.insert(delay) {
+        if let Some(key) = timer_runtime.insert(delay, mode) {
             Either::Left(TimerFuture::new(key))
         } else {
             Either::Right(std::future::ready(()))
@@ -150,12 +385,41 @@ impl RuntimeInner {
     }
 
     pub fn cancel_op<T>(&self, user_data: Key<T>) {
+        self.registry.borrow_mut().remove_op(*user_data);
         let completed = self.op_runtime.borrow_mut().cancel(*user_data);
         if !completed {
             self.driver.borrow_mut().cancel(*user_data);
         }
     }
 
+    pub fn cancel_multishot<T>(&self, user_data: Key<T>) {
+        self.registry.borrow_mut().remove_op(*user_data);
+        self.op_runtime.borrow_mut().cancel(*user_data);
+        // The op is woken by every result, but it is still in flight until
+        // the final one.
+        self.driver.borrow_mut().cancel(*user_data);
+    }
+
+    #[cfg(feature = "time")]
+    pub fn now(&self) -> Instant {
+        self.timer_runtime.borrow().now()
+    }
+
+    #[cfg(feature = "time")]
+    pub fn pause_timer(&self) {
+        self.timer_runtime.borrow_mut().pause();
+    }
+
+    #[cfg(feature = "time")]
+    pub fn resume_timer(&self) {
+        self.timer_runtime.borrow_mut().resume();
+    }
+
+    #[cfg(feature = "time")]
+    pub fn advance_timer(&self, duration: std::time::Duration) {
+        self.timer_runtime.borrow_mut().advance(duration);
+    }
+
     #[cfg(feature = "time")]
     pub fn cancel_timer(&self, key: usize) {
         self.timer_runtime.borrow_mut().cancel(key);
@@ -165,14 +429,40 @@ impl RuntimeInner {
         &self,
         cx: &mut Context,
         user_data: Key<T>,
-    ) -> Poll<BufResult<usize, T>> {
+    ) -> Poll<(BufResult<usize, Box<T>>, u32)> {
         instrument!(compio_log::Level::DEBUG, "poll_task", ?user_data,);
         let mut op_runtime = self.op_runtime.borrow_mut();
         let mut driver = self.driver.borrow_mut();
         if driver.has_result(*user_data) {
             debug!("has result");
             op_runtime.cancel(*user_data);
-            Poll::Ready(driver.pop::<T>(user_data))
+            self.registry.borrow_mut().complete_op(*user_data);
+            Poll::Ready(driver.pop_boxed_with_flags::<T>(user_data))
+        } else {
+            debug!("update waker");
+            op_runtime.update_waker(*user_data, cx.waker().clone());
+            Poll::Pending
+        }
+    }
+
+    // Returns a result with its flags, and whether it is the final one.
+    pub fn poll_multishot<T: OpCode>(
+        &self,
+        cx: &mut Context,
+        user_data: Key<T>,
+    ) -> Poll<((io::Result<usize>, u32), bool)> {
+        instrument!(compio_log::Level::DEBUG, "poll_multishot", ?user_data);
+        let mut op_runtime = self.op_runtime.borrow_mut();
+        let mut driver = self.driver.borrow_mut();
+        if let Some(res) = driver.pop_multishot_with_flags(&user_data) {
+            debug!("has more");
+            Poll::Ready((res, false))
+        } else if driver.has_result(*user_data) {
+            debug!("has result");
+            op_runtime.cancel(*user_data);
+            self.registry.borrow_mut().complete_op(*user_data);
+            let (BufResult(res, _), flags) = driver.pop_boxed_with_flags(user_data);
+            Poll::Ready(((res, flags), true))
         } else {
             debug!("update waker");
             op_runtime.update_waker(*user_data, cx.waker().clone());
@@ -194,35 +484,97 @@ impl RuntimeInner {
         }
     }
 
+    pub fn dump(&self) -> RuntimeDump {
+        #[cfg(not(feature = "time"))]
+        let timers = 0;
+        #[cfg(feature = "time")]
+        let timers = self.timer_runtime.borrow().len();
+        self.registry.borrow().dump(self.runnables.len(), timers)
+    }
+
     fn poll(&self) {
         instrument!(compio_log::Level::DEBUG, "poll");
         #[cfg(not(feature = "time"))]
-        let timeout = None;
+        let timeout: Option<std::time::Duration> = None;
         #[cfg(feature = "time")]
-        let timeout = self.timer_runtime.borrow().min_timeout();
+        let (timeout, paused) = {
+            let timer_runtime = self.timer_runtime.borrow();
+            let timeout = timer_runtime.min_timeout();
+            if timer_runtime.is_paused() {
+                // Don't wait for the paused clock.
+                (timeout.map(|_| std::time::Duration::ZERO), timeout)
+            } else if !timer_runtime.is_realtime() {
+                // The clock may be moved by another thread, check it again
+                // later.
+                (timeout.map(|t| CLOCK_POLL_INTERVAL.min(t)), None)
+            } else {
+                (timeout, None)
+            }
+        };
+        let timeout = if self.pool_waiters.borrow().is_empty() {
+            timeout
+        } else {
+            Some(timeout.map_or(POOL_RETRY_INTERVAL, |t| t.min(POOL_RETRY_INTERVAL)))
+        };
         debug!("timeout: {:?}", timeout);
 
         let mut entries = SmallVec::<[usize; 1024]>::new();
         let mut driver = self.driver.borrow_mut();
-        match driver.poll(timeout, &mut entries) {
+        #[cfg_attr(not(feature = "time"), allow(unused_variables))]
+        let idle = match driver.poll(timeout, &mut entries) {
             Ok(_) => {
                 debug!("poll driver ok, entries: {}", entries.len());
+                let idle = entries.is_empty();
                 for entry in entries {
                     self.op_runtime.borrow_mut().wake(entry);
                 }
+                // Some threads of the pool may be free now.
+                for waker in self.pool_waiters.take() {
+                    waker.wake();
+                }
+                idle
             }
             Err(e) => match e.kind() {
                 io::ErrorKind::TimedOut | io::ErrorKind::Interrupted => {
                     debug!("expected error: {e}");
+                    true
                 }
                 _ => panic!("{:?}", e),
             },
-        }
+        };
         #[cfg(feature = "time")]
-        self.timer_runtime.borrow_mut().wake();
+        {
+            let mut timer_runtime = self.timer_runtime.borrow_mut();
+            match paused {
+                // Nothing else to do, jump to the next timer.
+                Some(next) if idle && self.runnables.is_empty() => timer_runtime.advance(next),
+                _ => timer_runtime.wake(),
+            }
+        }
     }
 }
 
+// Submit an op rejected by the full thread pool again, once there may be
+// the room.
+async fn submit_with_pool_room<T: OpCode + 'static>(mut op: T) -> (BufResult<usize, T>, u32) {
+    let user_data = loop {
+        PoolRoom::default().await;
+        let runtime = Runtime::current();
+        let inner = runtime.inner();
+        let res = inner.driver.borrow_mut().try_push(op);
+        match res {
+            Ok(PushEntry::Pending(user_data)) => {
+                inner.track_op(user_data);
+                break user_data;
+            }
+            Ok(PushEntry::Ready(res)) => return (res, 0),
+            Err((e, rejected)) if inner.waits_for_pool(&e) => op = rejected,
+            Err((e, rejected)) => return (BufResult(Err(e), rejected), 0),
+        }
+    };
+    OpFuture::new(user_data).await
+}
+
 impl AsRawFd for RuntimeInner {
     fn as_raw_fd(&self) -> RawFd {
         self.driver.borrow().as_raw_fd()
@@ -326,7 +678,7 @@ impl Runtime {
     /// drop(enter1);
     /// drop(enter2);
     /// ```
-    pub fn enter(&self) -> EnterGuard {
+    pub fn enter(&self) -> EnterGuard<'_> {
         EnterGuard::new(self)
     }
 
@@ -354,6 +706,25 @@ impl Runtime {
         self.inner.spawn_blocking(f)
     }
 
+    /// Runs a short blocking closure on a dedicated thread beside the driver,
+    /// and wait for it.
+    ///
+    /// The closures run one by one on the thread, which is created on the
+    /// first call. Unlike [`Runtime::spawn_blocking`], they never queue behind
+    /// the bulk blocking operations in the thread pool, e.g. the file IO. It
+    /// suits the latency sensitive calls like `getrandom` or a few `ioctl`s.
+    /// Long closures delay the others, so use [`Runtime::spawn_blocking`] for
+    /// them.
+    ///
+    /// The closure will not be cancelled even if the future is dropped. If it
+    /// panics, the panic is resumed when the future is polled.
+    pub fn run_blocking_on_driver<T: Send + 'static>(
+        &self,
+        f: impl (FnOnce() -> T) + Send + 'static,
+    ) -> impl Future<Output = T> {
+        self.inner.run_blocking_on_driver(f)
+    }
+
     /// Attach a raw file descriptor/handle/socket to the runtime.
     ///
     /// You only need this when authoring your own high-level APIs. High-level
@@ -362,12 +733,226 @@ impl Runtime {
         self.inner.attach(fd)
     }
 
+    /// Take a snapshot of the live tasks, the pending operations and the
+    /// queue lengths, to debug a hanging runtime.
+    ///
+    /// The tasks and the operations are only tracked if the runtime is built
+    /// with [`RuntimeBuilder::dump`], otherwise they are empty.
+    ///
+    /// ```
+    /// # let runtime = compio_runtime::RuntimeBuilder::new().dump(true).build().unwrap();
+    /// # runtime.block_on(async {
+    /// let dump = compio_runtime::Runtime::current().dump();
+    /// // The task of `block_on`.
+    /// assert_eq!(dump.tasks.len(), 1);
+    /// println!("{dump}");
+    /// # })
+    /// ```
+    pub fn dump(&self) -> RuntimeDump {
+        self.inner.dump()
+    }
+
+    /// Start or stop recording the completion latency of the operations,
+    /// which is disabled by default. The records are kept when stopped.
+    ///
+    /// The latency is from the submission to the completion observed by the
+    /// runtime, and the operations of the same type are counted together in
+    /// a [`Histogram`], e.g. to see whether the p99 of `Sync` regresses
+    /// after a kernel upgrade. The cancelled operations are not counted.
+    ///
+    /// ```
+    /// use compio_runtime::Runtime;
+    ///
+    /// # Runtime::new().unwrap().block_on(async {
+    /// let runtime = Runtime::current();
+    /// runtime.set_op_latency_enabled(true);
+    /// compio_runtime::spawn_blocking(|| {}).await;
+    /// for op in runtime.op_latency() {
+    ///     println!("{op}");
+    /// }
+    /// # })
+    /// ```
+    pub fn set_op_latency_enabled(&self, enabled: bool) {
+        self.inner.set_op_latency_enabled(enabled)
+    }
+
+    /// Get the latency recorded for each operation type, ordered by the type
+    /// names. See [`set_op_latency_enabled`](Self::set_op_latency_enabled).
+    pub fn op_latency(&self) -> Vec<OpLatency> {
+        self.inner.op_latency()
+    }
+
+    /// Clear the recorded latency.
+    pub fn reset_op_latency(&self) {
+        self.inner.reset_op_latency()
+    }
+
+    /// Get the counters of the thread pool running the blocking operations,
+    /// to observe whether it is saturated.
+    ///
+    /// When all the threads are busy, the new blocking operations are
+    /// queued, rejected or wait, as configured by
+    /// [`ProactorBuilder::thread_pool_policy`].
+    pub fn thread_pool_stats(&self) -> PoolStats {
+        self.inner.thread_pool_stats()
+    }
+
     /// Submit an operation to the runtime.
     ///
     /// You only need this when authoring your own [`OpCode`].
     pub fn submit<T: OpCode + 'static>(&self, op: T) -> impl Future<Output = BufResult<usize, T>> {
         self.inner.submit(op)
     }
+
+    /// Submit an operation to the runtime, and call `f` with its result when
+    /// it completes, without constructing a future.
+    ///
+    /// It is for the callback-driven code on the thread of the runtime,
+    /// e.g. a C library calling back into Rust, which could start an IO from
+    /// a plain function. The operation runs in a detached task: `f` is
+    /// called when the runtime is polled, e.g. in [`Runtime::block_on`], and
+    /// the operation could not be cancelled.
+    ///
+    /// ```
+    /// use compio_buf::BufResult;
+    /// use compio_driver::op::Asyncify;
+    /// use compio_runtime::Runtime;
+    ///
+    /// let runtime = Runtime::new().unwrap();
+    /// let op = Asyncify::new(|| BufResult(Ok(42), ()));
+    /// runtime.submit_with(op, |BufResult(res, _)| assert_eq!(res.unwrap(), 42));
+    /// ```
+    pub fn submit_with<T: OpCode + 'static>(
+        &self,
+        op: T,
+        f: impl FnOnce(BufResult<usize, T>) + 'static,
+    ) {
+        let op = self.submit(op);
+        self.spawn(async move { f(op.await) }).detach();
+    }
+
+    /// Submit an operation to the runtime, and get the flags of the
+    /// completion with the result.
+    ///
+    /// The flags are the raw CQE flags on io-uring driver, which tell the
+    /// caller whether the socket has more data to read, or a multishot
+    /// operation has more completions. They are always `0` on other drivers.
+    ///
+    /// You only need this when authoring your own [`OpCode`].
+    pub fn submit_with_flags<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> impl Future<Output = (BufResult<usize, T>, u32)> {
+        self.inner.submit_with_flags(op)
+    }
+
+    /// Submit an operation to the runtime, or get it back if the driver
+    /// rejects it before submitting it.
+    ///
+    /// An operation is rejected when it cannot be started at all, e.g. when
+    /// the blocking thread pool is full. The caller could fall back to
+    /// another way for this operation, like running it on its own thread,
+    /// instead of getting an opaque error from [`Runtime::submit`].
+    ///
+    /// You only need this when authoring your own [`OpCode`].
+    pub fn try_submit<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> Result<impl Future<Output = BufResult<usize, T>>, (io::Error, T)> {
+        self.inner.try_submit(op)
+    }
+
+    /// Submit a multishot operation to the runtime, and get its results as a
+    /// stream, which ends after the final result.
+    ///
+    /// On io-uring driver, an operation like
+    /// [`AcceptMulti`](compio_driver::op::AcceptMulti) completes many times
+    /// from one submission. On other drivers, or when the kernel ends the
+    /// operation, the stream yields the final result only, and the caller
+    /// should submit it again if needed. Dropping the stream cancels the
+    /// operation.
+    ///
+    /// You only need this when authoring your own [`OpCode`].
+    pub fn submit_multishot<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> impl Stream<Item = io::Result<usize>> {
+        self.inner.submit_multishot(op).map(|(res, _)| res)
+    }
+
+    /// Submit a multishot operation to the runtime, and get its results with
+    /// the flags of their completions, like the buffers selected by
+    /// [`RecvMulti`](compio_driver::op::RecvMulti).
+    ///
+    /// You only need this when authoring your own [`OpCode`].
+    pub fn submit_multishot_with_flags<T: OpCode + 'static>(
+        &self,
+        op: T,
+    ) -> impl Stream<Item = (io::Result<usize>, u32)> {
+        self.inner.submit_multishot(op)
+    }
+
+    /// Create a [`BufferPool`] of `entries` buffers, each of `buffer_len`
+    /// bytes, for the receives selecting the buffers when the data arrives.
+    /// The `entries` should be a power of 2, at most 32768.
+    ///
+    /// The pool is registered to the kernel on io-uring driver since Linux
+    /// 6.0, until it is released with [`Runtime::release_buffer_pool`] or
+    /// the runtime is dropped.
+    pub fn create_buffer_pool(&self, entries: u16, buffer_len: usize) -> io::Result<BufferPool> {
+        self.inner.create_buffer_pool(entries, buffer_len)
+    }
+
+    /// Release a pool created by [`Runtime::create_buffer_pool`].
+    pub fn release_buffer_pool(&self, pool: BufferPool) -> io::Result<()> {
+        self.inner.release_buffer_pool(pool)
+    }
+
+    /// Submit an operation to the runtime, which is interrupted if it
+    /// doesn't complete before `deadline`.
+    ///
+    /// Unlike dropping the future of [`Runtime::submit`] on a timeout, the
+    /// interrupted operation still completes with its buffers, and a
+    /// [`TimedOut`](io::ErrorKind::TimedOut) error. It is not submitted if
+    /// `deadline` has elapsed. The blocking operations on the thread pool
+    /// couldn't be interrupted. See also [`Deadline`](crate::time::Deadline).
+    ///
+    /// You only need this when authoring your own [`OpCode`].
+    #[cfg(feature = "time")]
+    pub fn submit_until<T: OpCode + 'static>(
+        &self,
+        op: T,
+        deadline: Instant,
+    ) -> impl Future<Output = BufResult<usize, T>> {
+        self.inner.submit_until(op, deadline)
+    }
+
+    /// Submit an operation allocated by the caller like
+    /// [`Runtime::submit_boxed`], which is interrupted at `deadline` like
+    /// [`Runtime::submit_until`].
+    #[cfg(feature = "time")]
+    pub fn submit_boxed_until<T: OpCode + 'static>(
+        &self,
+        op: Box<T>,
+        deadline: Instant,
+    ) -> impl Future<Output = BufResult<usize, Box<T>>> {
+        self.inner.submit_boxed_until(op, deadline)
+    }
+
+    /// Submit an operation allocated by the caller to the runtime.
+    ///
+    /// The allocation is returned with the result, so it could be reused for
+    /// the next operation, e.g. the next accept on the same listener. It is
+    /// not reused on IOCP driver.
+    ///
+    /// You only need this when authoring your own high performance
+    /// operations.
+    pub fn submit_boxed<T: OpCode + 'static>(
+        &self,
+        op: Box<T>,
+    ) -> impl Future<Output = BufResult<usize, Box<T>>> {
+        self.inner.submit_boxed(op)
+    }
 }
 
 impl AsRawFd for Runtime {
@@ -394,6 +979,9 @@ impl criterion::async_executor::AsyncExecutor for &Runtime {
 #[derive(Debug, Clone)]
 pub struct RuntimeBuilder {
     proactor_builder: ProactorBuilder,
+    #[cfg(feature = "time")]
+    clock: Arc<dyn crate::time::Clock>,
+    dump: bool,
 }
 
 impl Default for RuntimeBuilder {
@@ -407,19 +995,48 @@ impl RuntimeBuilder {
     pub fn new() -> Self {
         Self {
             proactor_builder: ProactorBuilder::new(),
+            #[cfg(feature = "time")]
+            clock: Arc::new(crate::time::SystemClock),
+            dump: false,
         }
     }
 
+    /// Set the clock of the timers, e.g. a [`MockClock`] in the tests. It
+    /// is the [`SystemClock`] by default.
+    ///
+    /// [`MockClock`]: crate::time::MockClock
+    /// [`SystemClock`]: crate::time::SystemClock
+    #[cfg(feature = "time")]
+    pub fn clock(&mut self, clock: impl crate::time::Clock) -> &mut Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Track the tasks and the pending operations for [`Runtime::dump`]. It
+    /// costs a map update and a clock read for each task and operation, so
+    /// it is disabled by default.
+    pub fn dump(&mut self, enabled: bool) -> &mut Self {
+        self.dump = enabled;
+        self
+    }
+
     /// Replace proactor builder.
     pub fn with_proactor(&mut self, builder: ProactorBuilder) -> &mut Self {
         self.proactor_builder = builder;
         self
     }
 
+    /// Override the options of the proactor builder with the `COMPIO_*`
+    /// environment variables. See [`ProactorBuilder::load_env`].
+    pub fn load_env(&mut self) -> io::Result<&mut Self> {
+        self.proactor_builder.load_env()?;
+        Ok(self)
+    }
+
     /// Build [`Runtime`].
     pub fn build(&self) -> io::Result<Runtime> {
         Ok(Runtime {
-            inner: Rc::new(RuntimeInner::new(&self.proactor_builder)?),
+            inner: Rc::new(RuntimeInner::new(self)?),
         })
     }
 }
@@ -514,3 +1131,16 @@ pub fn spawn_blocking<T: Send + 'static>(
 ) -> impl Future<Output = T> {
     Runtime::current().spawn_blocking(f)
 }
+
+/// Runs a short blocking closure on a dedicated thread beside the driver, and
+/// wait for it. See [`Runtime::run_blocking_on_driver`].
+///
+/// ## Panics
+///
+/// This method doesn't create runtime. It tries to obtain the current runtime
+/// by [`Runtime::current`].
+pub fn run_blocking_on_driver<T: Send + 'static>(
+    f: impl (FnOnce() -> T) + Send + 'static,
+) -> impl Future<Output = T> {
+    Runtime::current().run_blocking_on_driver(f)
+}
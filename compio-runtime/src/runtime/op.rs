@@ -1,12 +1,14 @@
 use std::{
     collections::HashMap,
     future::Future,
+    io,
     pin::Pin,
     task::{Context, Poll, Waker},
 };
 
 use compio_buf::BufResult;
 use compio_driver::{Key, OpCode};
+use futures_util::Stream;
 
 use crate::runtime::{FutureState, Runtime};
 
@@ -49,10 +51,31 @@ impl<T> OpFuture<T> {
 }
 
 impl<T: OpCode> Future for OpFuture<T> {
-    type Output = BufResult<usize, T>;
+    type Output = (BufResult<usize, T>, u32);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Runtime::current()
+            .inner()
+            .poll_task(cx, self.user_data)
+            .map(|(res, flags)| (res.map_buffer(|op| *op), flags))
+    }
+}
+
+/// An [`OpFuture`] returning the allocation of the operation.
+#[derive(Debug)]
+pub struct BoxedOpFuture<T>(OpFuture<T>);
+
+impl<T> BoxedOpFuture<T> {
+    pub fn new(user_data: Key<T>) -> Self {
+        Self(OpFuture::new(user_data))
+    }
+}
+
+impl<T: OpCode> Future for BoxedOpFuture<T> {
+    type Output = (BufResult<usize, Box<T>>, u32);
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        Runtime::current().inner().poll_task(cx, self.user_data)
+        Runtime::current().inner().poll_task(cx, self.0.user_data)
     }
 }
 
@@ -61,3 +84,122 @@ impl<T> Drop for OpFuture<T> {
         Runtime::current().inner().cancel_op(self.user_data)
     }
 }
+
+/// An operation interrupted when the deadline elapses. It completes with the
+/// buffers, and a [`TimedOut`](io::ErrorKind::TimedOut) error if it is
+/// interrupted.
+#[cfg(feature = "time")]
+pub struct UntilFuture<F> {
+    user_data: usize,
+    future: F,
+    // `None` after the op is interrupted.
+    timer: Option<futures_util::future::LocalBoxFuture<'static, ()>>,
+}
+
+#[cfg(feature = "time")]
+impl<F> UntilFuture<F> {
+    pub fn new(user_data: usize, future: F, deadline: std::time::Instant) -> Self {
+        Self {
+            user_data,
+            future,
+            timer: Some(Box::pin(crate::time::sleep_until(deadline))),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl<T, F: Future<Output = (BufResult<usize, T>, u32)> + Unpin> Future for UntilFuture<F> {
+    type Output = BufResult<usize, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        #[cfg(unix)]
+        const INTERRUPTED: i32 = libc::ECANCELED;
+        #[cfg(windows)]
+        const INTERRUPTED: i32 = windows_sys::Win32::Foundation::ERROR_OPERATION_ABORTED as _;
+
+        let this = self.get_mut();
+        if let Poll::Ready((BufResult(res, op), _)) = Pin::new(&mut this.future).poll(cx) {
+            let res = match res {
+                Err(e) if this.timer.is_none() && e.raw_os_error() == Some(INTERRUPTED) => {
+                    Err(deadline_elapsed())
+                }
+                res => res,
+            };
+            return Poll::Ready(BufResult(res, op));
+        }
+        if let Some(timer) = &mut this.timer {
+            if timer.as_mut().poll(cx).is_ready() {
+                this.timer = None;
+                Runtime::current().inner().interrupt_op(this.user_data);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(feature = "time")]
+pub fn deadline_elapsed() -> io::Error {
+    io::Error::new(io::ErrorKind::TimedOut, "deadline has elapsed")
+}
+
+/// The stream of the results of a multishot operation.
+#[derive(Debug)]
+pub struct MultishotStream<T> {
+    // `None` after the final result.
+    user_data: Option<Key<T>>,
+}
+
+impl<T> MultishotStream<T> {
+    pub fn new(user_data: Key<T>) -> Self {
+        Self {
+            user_data: Some(user_data),
+        }
+    }
+}
+
+impl<T: OpCode> Stream for MultishotStream<T> {
+    type Item = (io::Result<usize>, u32);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let Some(user_data) = self.user_data else {
+            return Poll::Ready(None);
+        };
+        let (res, last) =
+            std::task::ready!(Runtime::current().inner().poll_multishot(cx, user_data));
+        if last {
+            self.user_data = None;
+        }
+        Poll::Ready(Some(res))
+    }
+}
+
+impl<T> Drop for MultishotStream<T> {
+    fn drop(&mut self) {
+        if let Some(user_data) = self.user_data {
+            Runtime::current().inner().cancel_multishot(user_data)
+        }
+    }
+}
+
+/// Waits until the runtime polls the driver again, when some threads of the
+/// pool may be free.
+#[derive(Debug, Default)]
+pub struct PoolRoom {
+    waited: bool,
+}
+
+impl Future for PoolRoom {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.waited {
+            Poll::Ready(())
+        } else {
+            self.waited = true;
+            Runtime::current()
+                .inner()
+                .wait_pool_room(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::{fmt, ops::Range, time::Duration};
+
+// Each power of two of nanoseconds is split into 16 buckets, so a recorded
+// value is off by at most 1/16 of it.
+const SUB_BITS: u32 = 4;
+const SUB_BUCKETS: usize = 1 << SUB_BITS;
+const BUCKETS: usize = (64 - SUB_BITS as usize + 1) * SUB_BUCKETS;
+
+/// A histogram of durations, with the log-linear buckets like HDR
+/// histograms.
+///
+/// The values below 16ns are counted exactly, and the larger ones are
+/// counted in 16 buckets for each power of two, with the relative error
+/// below 6.25%.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Histogram {
+    counts: Box<[u64]>,
+    count: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+impl Histogram {
+    /// Create an empty histogram.
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; BUCKETS].into_boxed_slice(),
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    fn index(value: u64) -> usize {
+        if value < SUB_BUCKETS as u64 {
+            return value as usize;
+        }
+        let shift = 63 - value.leading_zeros() - SUB_BITS;
+        (shift as usize + 1) * SUB_BUCKETS + ((value >> shift) as usize & (SUB_BUCKETS - 1))
+    }
+
+    fn range(index: usize) -> Range<u64> {
+        if index < SUB_BUCKETS {
+            return index as u64..index as u64 + 1;
+        }
+        let shift = index / SUB_BUCKETS - 1;
+        let start = ((SUB_BUCKETS + index % SUB_BUCKETS) as u64) << shift;
+        start..start.saturating_add(1 << shift)
+    }
+
+    /// Record a duration. The durations longer than `u64::MAX` nanoseconds
+    /// are saturated.
+    pub fn record(&mut self, duration: Duration) {
+        let value = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+        self.counts[Self::index(value)] += 1;
+        self.count += 1;
+        self.sum += value as u128;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    /// Add the records of `other`.
+    pub fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// The number of the records.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Whether there is no record.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The shortest duration, or zero if empty.
+    pub fn min(&self) -> Duration {
+        if self.is_empty() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos(self.min)
+        }
+    }
+
+    /// The longest duration, or zero if empty.
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max)
+    }
+
+    /// The mean of the durations, or zero if empty.
+    pub fn mean(&self) -> Duration {
+        if self.is_empty() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.sum / self.count as u128) as u64)
+        }
+    }
+
+    /// The duration that the fraction `q` of the records are not longer
+    /// than, e.g. `0.99` for p99. It is the upper bound of the bucket, and
+    /// not longer than [`max`](Self::max). Returns zero if empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not in `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Duration {
+        assert!((0.0..=1.0).contains(&q), "the quantile should be in [0, 1]");
+        if self.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = ((q * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let end = Self::range(index).end - 1;
+                return Duration::from_nanos(end.clamp(self.min, self.max));
+            }
+        }
+        self.max()
+    }
+
+    /// The buckets with records, and the ranges of the durations counted in
+    /// them.
+    pub fn buckets(&self) -> impl Iterator<Item = (Range<Duration>, u64)> + '_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(index, &count)| {
+                let range = Self::range(index);
+                (
+                    Duration::from_nanos(range.start)..Duration::from_nanos(range.end),
+                    count,
+                )
+            })
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Histogram")
+            .field("count", &self.count)
+            .field("min", &self.min())
+            .field("mean", &self.mean())
+            .field("p99", &self.quantile(0.99))
+            .field("max", &self.max())
+            .finish()
+    }
+}
+
+/// The completion latency of an operation type, returned by
+/// [`Runtime::op_latency`].
+///
+/// [`Runtime::op_latency`]: crate::Runtime::op_latency
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct OpLatency {
+    /// The type name of the operation, without the generic parameters.
+    pub name: &'static str,
+    /// The durations from the submissions to the completions.
+    pub histogram: Histogram,
+}
+
+impl fmt::Display for OpLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let h = &self.histogram;
+        write!(
+            f,
+            "{}: count {}, p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+            self.name,
+            h.count(),
+            h.quantile(0.5),
+            h.quantile(0.9),
+            h.quantile(0.99),
+            h.max()
+        )
+    }
+}
+
+// The type name without the generic parameters, so that the operations on
+// the different buffers are counted together.
+pub(crate) fn op_name(name: &'static str) -> &'static str {
+    name.split('<').next().unwrap_or(name)
+}
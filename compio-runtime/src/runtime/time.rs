@@ -1,14 +1,23 @@
 use std::{
-    collections::BinaryHeap,
+    cell::Cell,
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap},
     future::Future,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll, Waker},
     time::{Duration, Instant},
 };
 
 use slab::Slab;
 
-use crate::runtime::{FutureState, Runtime};
+use crate::{
+    runtime::{FutureState, Runtime},
+    time::{Clock, TimerMode},
+};
+
+// The granularity of the coarse timers.
+const COARSE_TICK: Duration = Duration::from_millis(1);
 
 #[derive(Debug)]
 struct TimerEntry {
@@ -37,20 +46,83 @@ impl Ord for TimerEntry {
 }
 
 pub struct TimerRuntime {
+    clock: Arc<dyn Clock>,
+    // The latest time read from the clock, so that the time never goes back
+    // even if the clock does.
+    last: Cell<Instant>,
     time: Instant,
+    // The elapsed time frozen when the clock is paused.
+    paused: Option<Duration>,
     tasks: Slab<FutureState>,
-    wheel: BinaryHeap<TimerEntry>,
+    // The earliest timer on the top.
+    wheel: BinaryHeap<Reverse<TimerEntry>>,
+    // The coarse timers, bucketed by the tick they expire in, so that a
+    // timer is only pushed to its bucket, and a bucket expires at once.
+    coarse: BTreeMap<u64, Vec<usize>>,
 }
 
 impl TimerRuntime {
-    pub fn new() -> Self {
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        let time = clock.now();
         Self {
-            time: Instant::now(),
+            clock,
+            last: Cell::new(time),
+            time,
+            paused: None,
             tasks: Slab::default(),
             wheel: BinaryHeap::default(),
+            coarse: BTreeMap::default(),
+        }
+    }
+
+    fn clock_now(&self) -> Instant {
+        let now = self.clock.now().max(self.last.get());
+        self.last.set(now);
+        now
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.paused
+            .unwrap_or_else(|| self.clock_now().saturating_duration_since(self.time))
+    }
+
+    /// The current time of the timers, frozen when the clock is paused.
+    pub fn now(&self) -> Instant {
+        self.time + self.elapsed()
+    }
+
+    pub fn is_realtime(&self) -> bool {
+        self.clock.is_realtime()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.is_some()
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = Some(self.elapsed());
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(elapsed) = self.paused.take() {
+            // Continue from the paused time.
+            let now = self.clock_now();
+            self.time = now.checked_sub(elapsed).unwrap_or(now);
+        }
+    }
+
+    /// Move the paused clock forward, and wake the expired timers.
+    pub fn advance(&mut self, duration: Duration) {
+        if let Some(elapsed) = &mut self.paused {
+            *elapsed += duration;
+            self.wake();
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
     pub fn is_completed(&self, key: usize) -> bool {
         self.tasks
             .get(key)
@@ -58,15 +130,21 @@ impl TimerRuntime {
             .unwrap_or_default()
     }
 
-    pub fn insert(&mut self, mut delay: Duration) -> Option<usize> {
+    pub fn insert(&mut self, mut delay: Duration, mode: TimerMode) -> Option<usize> {
         if delay.is_zero() {
             return None;
         }
-        let elapsed = self.time.elapsed();
+        let elapsed = self.elapsed();
         let key = self.tasks.insert(FutureState::Active(None));
         delay += elapsed;
-        let entry = TimerEntry { key, delay };
-        self.wheel.push(entry);
+        match mode {
+            TimerMode::Precise => self.wheel.push(Reverse(TimerEntry { key, delay })),
+            TimerMode::Coarse => {
+                // Round up to the next tick.
+                let tick = delay.as_nanos().div_ceil(COARSE_TICK.as_nanos()) as u64;
+                self.coarse.entry(tick).or_default().push(key);
+            }
+        }
         Some(key)
     }
 
@@ -81,32 +159,45 @@ impl TimerRuntime {
     }
 
     pub fn min_timeout(&self) -> Option<Duration> {
-        let elapsed = self.time.elapsed();
-        self.wheel.peek().map(|entry| {
-            if entry.delay > elapsed {
-                entry.delay - elapsed
-            } else {
-                Duration::ZERO
-            }
-        })
+        let elapsed = self.elapsed();
+        let precise = self.wheel.peek().map(|Reverse(entry)| entry.delay);
+        let coarse = self.coarse.keys().next().map(|&tick| tick_delay(tick));
+        let delay = precise.into_iter().chain(coarse).min()?;
+        Some(delay.saturating_sub(elapsed))
     }
 
     pub fn wake(&mut self) {
-        let elapsed = self.time.elapsed();
-        while let Some(entry) = self.wheel.pop() {
+        let elapsed = self.elapsed();
+        while let Some(Reverse(entry)) = self.wheel.pop() {
             if entry.delay <= elapsed {
-                if let Some(state) = self.tasks.get_mut(entry.key) {
-                    let old_state = std::mem::replace(state, FutureState::Completed);
-                    if let FutureState::Active(Some(waker)) = old_state {
-                        waker.wake();
-                    }
-                }
+                self.expire(entry.key);
             } else {
-                self.wheel.push(entry);
+                self.wheel.push(Reverse(entry));
+                break;
+            }
+        }
+        while let Some(entry) = self.coarse.first_entry() {
+            if tick_delay(*entry.key()) > elapsed {
                 break;
             }
+            for key in entry.remove() {
+                self.expire(key);
+            }
         }
     }
+
+    fn expire(&mut self, key: usize) {
+        if let Some(state) = self.tasks.get_mut(key) {
+            let old_state = std::mem::replace(state, FutureState::Completed);
+            if let FutureState::Active(Some(waker)) = old_state {
+                waker.wake();
+            }
+        }
+    }
+}
+
+fn tick_delay(tick: u64) -> Duration {
+    Duration::from_nanos(tick * COARSE_TICK.as_nanos() as u64)
 }
 
 pub struct TimerFuture {
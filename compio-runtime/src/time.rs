@@ -2,8 +2,10 @@
 
 use std::{
     error::Error,
-    fmt::Display,
-    future::Future,
+    fmt::{Debug, Display},
+    future::{poll_fn, Future},
+    sync::{Arc, Mutex, PoisonError},
+    task::Poll,
     time::{Duration, Instant},
 };
 
@@ -11,6 +13,9 @@ use futures_util::{select, FutureExt};
 
 use crate::Runtime;
 
+mod deadline;
+pub use deadline::*;
+
 /// Waits until `duration` has elapsed.
 ///
 /// Equivalent to [`sleep_until(Instant::now() + duration)`](sleep_until). An
@@ -33,7 +38,40 @@ use crate::Runtime;
 /// # })
 /// ```
 pub async fn sleep(duration: Duration) {
-    Runtime::current().inner().create_timer(duration).await
+    sleep_with(duration, TimerMode::Precise).await
+}
+
+/// The precision of a timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimerMode {
+    /// Expires as close to the deadline as the driver could wait, e.g. in
+    /// nanoseconds with io-uring. It is used by [`sleep`] and [`timeout`].
+    #[default]
+    Precise,
+    /// Expires at the next millisecond tick after the deadline, together with
+    /// the other timers in the tick, so the runtime wakes up less. The timers
+    /// are kept in a bucket per tick instead of the heap of the precise ones,
+    /// so it is cheaper for the many timeouts which are mostly cancelled
+    /// before expiring, like the connection timeouts of a server.
+    Coarse,
+}
+
+/// Waits until `duration` has elapsed, with the precision of `mode`.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use compio_runtime::time::{sleep_with, TimerMode};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// sleep_with(Duration::from_millis(10), TimerMode::Coarse).await;
+/// # })
+/// ```
+pub async fn sleep_with(duration: Duration, mode: TimerMode) {
+    Runtime::current()
+        .inner()
+        .create_timer(duration, mode)
+        .await
 }
 
 /// Waits until `deadline` is reached.
@@ -55,7 +93,7 @@ pub async fn sleep(duration: Duration) {
 /// # })
 /// ```
 pub async fn sleep_until(deadline: Instant) {
-    sleep(deadline - Instant::now()).await
+    sleep(deadline.saturating_duration_since(now())).await
 }
 
 /// Error returned by [`timeout`] or [`timeout_at`].
@@ -76,9 +114,19 @@ impl Error for Elapsed {}
 /// value is returned. Otherwise, an error is returned and the future is
 /// canceled.
 pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    timeout_with(duration, TimerMode::Precise, future).await
+}
+
+/// Require a [`Future`] to complete before the specified duration has
+/// elapsed, with the precision of `mode`, like [`timeout`].
+pub async fn timeout_with<F: Future>(
+    duration: Duration,
+    mode: TimerMode,
+    future: F,
+) -> Result<F::Output, Elapsed> {
     select! {
         res = future.fuse() => Ok(res),
-        _ = sleep(duration).fuse() => Err(Elapsed),
+        _ = sleep_with(duration, mode).fuse() => Err(Elapsed),
     }
 }
 
@@ -87,7 +135,7 @@ pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Outp
 /// If the future completes before the instant is reached, then the completed
 /// value is returned. Otherwise, an error is returned.
 pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Result<F::Output, Elapsed> {
-    timeout(deadline - Instant::now(), future).await
+    timeout(deadline.saturating_duration_since(now()), future).await
 }
 
 /// Interval returned by [`interval`] and [`interval_at`]
@@ -120,7 +168,7 @@ impl Interval {
             self.first_ticked = true;
             self.start
         } else {
-            let now = Instant::now();
+            let now = now();
             let next = now + self.period
                 - Duration::from_nanos(
                     ((now - self.start).as_nanos() % self.period.as_nanos()) as _,
@@ -138,7 +186,7 @@ impl Interval {
 /// be dropped. This cancels the interval.
 ///
 /// This function is equivalent to
-/// [`interval_at(Instant::now(), period)`](interval_at).
+/// [`interval_at(now(), period)`](interval_at).
 ///
 /// # Panics
 ///
@@ -195,7 +243,7 @@ impl Interval {
 /// [`sleep`]: crate::time::sleep()
 /// [`.tick().await`]: Interval::tick
 pub fn interval(period: Duration) -> Interval {
-    interval_at(Instant::now(), period)
+    interval_at(now(), period)
 }
 
 /// Creates new [`Interval`] that yields with interval of `period` with the
@@ -230,3 +278,156 @@ pub fn interval_at(start: Instant, period: Duration) -> Interval {
     assert!(period > Duration::ZERO, "`period` must be non-zero.");
     Interval::new(start, period)
 }
+
+/// Pause the timer clock of the current runtime.
+///
+/// The paused clock only moves with [`advance`]. When the runtime has
+/// nothing else to do, it jumps to the next timer instead of waiting, so the
+/// timeouts in tests complete instantly and deterministically. It freezes
+/// [`now`], but doesn't affect [`Instant::now`].
+///
+/// It does nothing if the clock is already paused.
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use compio_runtime::time::{pause, sleep};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// pause();
+/// let start = Instant::now();
+/// sleep(Duration::from_secs(60)).await;
+/// assert!(start.elapsed() < Duration::from_secs(60));
+/// # })
+/// ```
+///
+/// ## Panics
+///
+/// This method panics if there are no running [`Runtime`].
+pub fn pause() {
+    Runtime::current().inner().pause_timer()
+}
+
+/// Resume the timer clock of the current runtime from the paused time.
+///
+/// ## Panics
+///
+/// This method panics if there are no running [`Runtime`].
+pub fn resume() {
+    Runtime::current().inner().resume_timer()
+}
+
+/// Move the paused timer clock forward by `duration`, and let the tasks woken
+/// by the expired timers run before returning.
+///
+/// It does nothing if the clock is not paused.
+///
+/// ## Panics
+///
+/// This method panics if there are no running [`Runtime`].
+pub async fn advance(duration: Duration) {
+    Runtime::current().inner().advance_timer(duration);
+    // Yield once, after the woken tasks.
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// The current time of the timers of the current runtime.
+///
+/// It is read from the [`Clock`] of the runtime, never goes back, and is
+/// frozen when the clock is [paused](pause). The deadlines compared with the
+/// timers should use it instead of [`Instant::now`]. It falls back to
+/// [`Instant::now`] if there are no running [`Runtime`].
+pub fn now() -> Instant {
+    Runtime::try_current().map_or_else(Instant::now, |rt| rt.inner().now())
+}
+
+/// A source of the monotonic time for the timers of a runtime, set with
+/// [`RuntimeBuilder::clock`](crate::RuntimeBuilder::clock).
+///
+/// The runtime never lets its time go back, even if the clock does.
+pub trait Clock: Debug + Send + Sync + 'static {
+    /// The current time.
+    fn now(&self) -> Instant;
+
+    /// Whether the time moves on its own. If not, the runtime doesn't wait
+    /// for the timers in the driver, but checks the clock every millisecond.
+    fn is_realtime(&self) -> bool {
+        true
+    }
+}
+
+/// The clock of [`Instant::now`], used by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock only moving with [`MockClock::advance`], for the tests. The clones
+/// share the time, so one could be kept to move the clock of a runtime.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use compio_runtime::{
+///     time::{now, sleep_until, MockClock},
+///     RuntimeBuilder,
+/// };
+///
+/// let clock = MockClock::new();
+/// let rt = RuntimeBuilder::new().clock(clock.clone()).build().unwrap();
+/// rt.block_on(async {
+///     let deadline = now() + Duration::from_secs(3600);
+///     let task = compio_runtime::spawn(sleep_until(deadline));
+///     clock.advance(Duration::from_secs(3600));
+///     task.await;
+/// })
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl MockClock {
+    /// Create a clock starting from [`Instant::now`].
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move the clock forward by `duration`. The expired timers are woken
+    /// when the runtime polls the driver next time.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner) += duration;
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_realtime(&self) -> bool {
+        false
+    }
+}
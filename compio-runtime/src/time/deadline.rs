@@ -0,0 +1,134 @@
+use std::{
+    cell::Cell,
+    future::Future,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use super::now;
+
+thread_local! {
+    static CURRENT: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// A deadline of a request, which bounds the IO within its
+/// [scope](Deadline::scope).
+///
+/// The socket operations of `compio-net`, e.g. connect, accept, recv and
+/// send, are interrupted with a [`TimedOut`](io::ErrorKind::TimedOut) error
+/// when the deadline of the scope elapses, and the buffers are returned as
+/// usual. A handler could set one deadline for a request, instead of
+/// wrapping every call in [`timeout`](super::timeout).
+///
+/// The scope is bound to the future, like a task-local value: the tasks
+/// spawned inside don't inherit it. The nested scopes could only shorten
+/// the deadline.
+///
+/// ```
+/// use std::{io, time::Duration};
+///
+/// use compio_runtime::time::{sleep, Deadline};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let res = Deadline::after(Duration::from_millis(10))
+///     .scope(async {
+///         assert!(Deadline::current().is_some());
+///         Deadline::bound(async {
+///             sleep(Duration::from_secs(10)).await;
+///             io::Result::Ok(())
+///         })
+///         .await
+///     })
+///     .await;
+/// assert_eq!(res.unwrap_err().kind(), io::ErrorKind::TimedOut);
+/// # })
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    /// The deadline at `instant`, on the clock of [`now`].
+    pub const fn at(instant: Instant) -> Self {
+        Self(instant)
+    }
+
+    /// The deadline `duration` after [`now`].
+    pub fn after(duration: Duration) -> Self {
+        Self(now() + duration)
+    }
+
+    /// The instant of the deadline.
+    pub const fn instant(&self) -> Instant {
+        self.0
+    }
+
+    /// The time left before the deadline, or zero if it has elapsed.
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(now())
+    }
+
+    /// Whether the deadline has elapsed.
+    pub fn is_elapsed(&self) -> bool {
+        self.0 <= now()
+    }
+
+    /// The deadline of the current scope, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.get().map(Self)
+    }
+
+    /// Run `future` within the deadline. If there is already a deadline in
+    /// the scope, the earlier one applies.
+    pub fn scope<F: Future>(self, future: F) -> Scope<F> {
+        Scope {
+            deadline: self.0,
+            future,
+        }
+    }
+
+    /// Run `future` until the deadline of the current scope, if any, and
+    /// fail with a [`TimedOut`](io::ErrorKind::TimedOut) error when it
+    /// elapses. The future is dropped on timeout, so it is for the
+    /// operations without buffers, e.g. a whole handshake.
+    pub async fn bound<T>(future: impl Future<Output = io::Result<T>>) -> io::Result<T> {
+        match Self::current() {
+            Some(deadline) => super::timeout_at(deadline.0, future)
+                .await
+                .unwrap_or_else(|_| Err(crate::runtime::op::deadline_elapsed())),
+            None => future.await,
+        }
+    }
+}
+
+/// The future returned by [`Deadline::scope`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Scope<F> {
+    deadline: Instant,
+    future: F,
+}
+
+impl<F: Future> Future for Scope<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // SAFETY: the future is never moved.
+        let this = unsafe { self.get_unchecked_mut() };
+        let deadline = CURRENT
+            .get()
+            .map_or(this.deadline, |d| d.min(this.deadline));
+        let _guard = ScopeGuard(CURRENT.replace(Some(deadline)));
+        unsafe { Pin::new_unchecked(&mut this.future) }.poll(cx)
+    }
+}
+
+// Restore the outer deadline, even if the future panics.
+struct ScopeGuard(Option<Instant>);
+
+impl Drop for ScopeGuard {
+    fn drop(&mut self) {
+        CURRENT.set(self.0);
+    }
+}
@@ -0,0 +1,72 @@
+#![cfg(unix)]
+
+use std::{
+    io::{self, Read, Write},
+    os::unix::net::UnixStream,
+};
+
+use compio_buf::IntoInner;
+use compio_runtime::{Async, Runtime};
+
+#[test]
+fn read_write() {
+    Runtime::new().unwrap().block_on(async {
+        let (a, b) = UnixStream::pair().unwrap();
+        let a = Async::new(a).unwrap();
+        let b = Async::new(b).unwrap();
+
+        let mut buf = [0u8; 5];
+        let read = b.read_with(|mut b| b.read(&mut buf));
+        let write = async {
+            a.writable().await.unwrap();
+            a.write_with(|mut a| a.write(b"hello")).await
+        };
+        let (read, write) = futures_util::join!(read, write);
+        assert_eq!(write.unwrap(), 5);
+        assert_eq!(read.unwrap(), 5);
+        assert_eq!(&buf, b"hello");
+
+        let a = a.into_inner();
+        let err = a.try_clone().unwrap().read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn timerfd() {
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    Runtime::new().unwrap().block_on(async {
+        let fd = unsafe { libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC) };
+        assert!(fd >= 0);
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+        let spec = libc::itimerspec {
+            it_interval: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 0,
+            },
+            it_value: libc::timespec {
+                tv_sec: 0,
+                tv_nsec: 1_000_000,
+            },
+        };
+        let res = unsafe { libc::timerfd_settime(fd.as_raw_fd(), 0, &spec, std::ptr::null_mut()) };
+        assert_eq!(res, 0);
+
+        let timer = Async::new(fd).unwrap();
+        let expirations = timer
+            .read_with(|fd| {
+                let mut count = 0u64;
+                let res = unsafe { libc::read(fd.as_raw_fd(), (&mut count as *mut u64).cast(), 8) };
+                if res < 0 {
+                    Err(io::Error::last_os_error())
+                } else {
+                    Ok(count)
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(expirations, 1);
+    })
+}
@@ -0,0 +1,95 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::{Arc, Barrier},
+    time::Duration,
+};
+
+use compio_buf::BufResult;
+use compio_driver::{op::Asyncify, ProactorBuilder, QueuePolicy};
+use compio_runtime::{Runtime, RuntimeBuilder};
+
+#[test]
+fn run_blocking_on_driver() {
+    let mut proactor = ProactorBuilder::new();
+    proactor.thread_pool_limit(1);
+    let runtime = RuntimeBuilder::new()
+        .with_proactor(proactor)
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // Occupy the only thread of the blocking pool.
+        let barrier = Arc::new(Barrier::new(2));
+        let b = barrier.clone();
+        let bulk = compio_runtime::spawn_blocking(move || {
+            b.wait();
+        });
+        let bulk = compio_runtime::spawn(bulk);
+
+        let names = [
+            compio_runtime::run_blocking_on_driver(|| {
+                std::thread::current().name().map(String::from)
+            })
+            .await,
+            compio_runtime::run_blocking_on_driver(|| {
+                std::thread::current().name().map(String::from)
+            })
+            .await,
+        ];
+        assert!(names[0].as_ref().unwrap().starts_with("compio-driver-"));
+        assert_eq!(names[0], names[1]);
+
+        barrier.wait();
+        bulk.await;
+    })
+}
+
+#[test]
+fn wait_for_pool() {
+    let mut proactor = ProactorBuilder::new();
+    proactor
+        .thread_pool_limit(1)
+        .thread_pool_policy(QueuePolicy::Wait(0));
+    let runtime = RuntimeBuilder::new()
+        .with_proactor(proactor)
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let barrier = Arc::new(Barrier::new(2));
+        let b = barrier.clone();
+        let first = compio_runtime::spawn(compio_runtime::spawn_blocking(move || {
+            b.wait();
+            1
+        }));
+        // The second one waits for the only thread instead of failing.
+        let second = compio_runtime::spawn(compio_runtime::spawn_blocking(|| 2));
+        compio_runtime::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(!second.is_finished());
+        let stats = Runtime::current().thread_pool_stats();
+        assert_eq!(stats.threads, 1);
+        assert_eq!(stats.active, 1);
+        assert!(stats.rejected >= 1);
+
+        barrier.wait();
+        assert_eq!(first.await, 1);
+        assert_eq!(second.await, 2);
+    })
+}
+
+#[test]
+fn submit_with_callback() {
+    let runtime = Runtime::new().unwrap();
+    let result = Rc::new(Cell::new(None));
+    let r = result.clone();
+    // Submitted outside of any future, like from a callback of C code.
+    let op = Asyncify::new(|| BufResult(Ok(42), ()));
+    runtime.submit_with(op, move |BufResult(res, _)| r.set(Some(res.unwrap())));
+    assert_eq!(result.get(), None);
+
+    runtime.block_on(async {
+        while result.get().is_none() {
+            compio_runtime::time::sleep(Duration::from_millis(1)).await;
+        }
+    });
+    assert_eq!(result.get(), Some(42));
+}
@@ -0,0 +1,57 @@
+use compio_runtime::broadcast::{self, LagPolicy, RecvError, TryRecvError};
+
+#[test]
+fn broadcast_fan_out() {
+    compio_runtime::Runtime::new().unwrap().block_on(async {
+        let (tx, rx) = broadcast::channel::<u32>(4);
+        let tasks = (0..8)
+            .map(|_| {
+                let mut rx = tx.subscribe();
+                compio_runtime::spawn(async move {
+                    let mut sum = 0;
+                    while let Ok(v) = rx.recv().await {
+                        sum += v;
+                    }
+                    sum
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(rx);
+        for i in 0..4 {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+        for task in tasks {
+            assert_eq!(task.await, 6);
+        }
+    })
+}
+
+#[test]
+fn broadcast_lagged() {
+    let (tx, mut rx) = broadcast::channel(2);
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+    assert_eq!(rx.try_recv(), Ok(3));
+    assert_eq!(rx.try_recv(), Ok(4));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn broadcast_disconnect() {
+    compio_runtime::Runtime::new().unwrap().block_on(async {
+        let (tx, mut slow) = broadcast::channel_with_policy(2, LagPolicy::Disconnect);
+        let mut fast = tx.subscribe();
+        for i in 0..3 {
+            tx.send(i).unwrap();
+            assert_eq!(fast.recv().await, Ok(i));
+        }
+        assert_eq!(slow.recv().await, Err(RecvError::Lagged(1)));
+        assert_eq!(slow.recv().await, Err(RecvError::Closed));
+        assert_eq!(tx.receiver_count(), 1);
+        drop(fast);
+        assert_eq!(tx.send(3).unwrap_err().0, 3);
+    })
+}
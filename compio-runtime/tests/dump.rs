@@ -0,0 +1,150 @@
+use std::{
+    sync::{Arc, Barrier},
+    time::Duration,
+};
+
+use compio_runtime::{Runtime, RuntimeBuilder};
+
+#[test]
+fn dump() {
+    let runtime = RuntimeBuilder::new().dump(true).build().unwrap();
+    runtime.block_on(async {
+        let barrier = Arc::new(Barrier::new(2));
+        let b = barrier.clone();
+        let blocked = compio_runtime::spawn(async move {
+            compio_runtime::spawn_blocking(move || {
+                b.wait();
+            })
+            .await
+        });
+        let dumper = compio_runtime::spawn(async move {
+            let dump = Runtime::current().dump();
+            barrier.wait();
+            dump
+        });
+        let dump = dumper.await;
+        blocked.await;
+
+        // The tasks of `block_on`, `blocked` and `dumper`.
+        assert_eq!(dump.tasks.len(), 3);
+        assert_eq!(dump.ops.len(), 1);
+        let op = &dump.ops[0];
+        assert!(op.name.contains("Asyncify"));
+        assert_eq!(op.task, Some(dump.tasks[1].id));
+        assert!(dump.to_string().contains("Asyncify"));
+
+        let dump = Runtime::current().dump();
+        assert_eq!(dump.tasks.len(), 1);
+        assert!(dump.ops.is_empty());
+    })
+}
+
+#[test]
+fn dump_stuck() {
+    let runtime = RuntimeBuilder::new().dump(true).build().unwrap();
+    runtime.block_on(async {
+        let barrier = Arc::new(Barrier::new(2));
+        let b = barrier.clone();
+        let blocked = compio_runtime::spawn(async move {
+            compio_runtime::spawn_blocking(move || {
+                b.wait();
+            })
+            .await
+        });
+        let dumper = compio_runtime::spawn(async move {
+            std::thread::sleep(Duration::from_millis(10));
+            let dump = Runtime::current().dump();
+            barrier.wait();
+            dump
+        });
+        let dump = dumper.await;
+        blocked.await;
+
+        assert!(dump.clone().stuck(Duration::from_secs(60)).ops.is_empty());
+        let stuck = dump.stuck(Duration::from_millis(10));
+        assert_eq!(stuck.ops.len(), 1);
+        assert_eq!(stuck.tasks.len(), 1);
+        assert_eq!(stuck.ops[0].task, Some(stuck.tasks[0].id));
+        #[cfg(feature = "backtrace")]
+        assert!(stuck.to_string().contains("spawned at"));
+    })
+}
+
+#[test]
+fn dump_disabled() {
+    Runtime::new().unwrap().block_on(async {
+        let task = compio_runtime::spawn(async {
+            compio_runtime::spawn_blocking(|| {}).await;
+            Runtime::current().dump()
+        });
+        let dump = task.await;
+        assert!(dump.tasks.is_empty());
+        assert!(dump.ops.is_empty());
+    })
+}
+
+#[test]
+fn op_latency() {
+    Runtime::new().unwrap().block_on(async {
+        let runtime = Runtime::current();
+        compio_runtime::spawn_blocking(|| {}).await;
+        assert!(runtime.op_latency().is_empty());
+
+        runtime.set_op_latency_enabled(true);
+        for _ in 0..3 {
+            compio_runtime::spawn_blocking(|| std::thread::sleep(Duration::from_millis(10))).await;
+        }
+        runtime.set_op_latency_enabled(false);
+        compio_runtime::spawn_blocking(|| {}).await;
+
+        let latency = runtime.op_latency();
+        assert_eq!(latency.len(), 1);
+        let op = &latency[0];
+        assert!(op.name.ends_with("Asyncify"), "{}", op.name);
+        assert_eq!(op.histogram.count(), 3);
+        assert!(op.histogram.min() >= Duration::from_millis(10));
+        assert!(op.histogram.quantile(0.99) <= op.histogram.max());
+        assert!(op.to_string().contains("count 3"));
+
+        runtime.reset_op_latency();
+        assert!(runtime.op_latency().is_empty());
+    })
+}
+
+#[test]
+fn histogram() {
+    use compio_runtime::Histogram;
+
+    let mut histogram = Histogram::new();
+    assert_eq!(histogram.quantile(0.5), Duration::ZERO);
+    for micros in 1..=100 {
+        histogram.record(Duration::from_micros(micros));
+    }
+    assert_eq!(histogram.count(), 100);
+    assert_eq!(histogram.min(), Duration::from_micros(1));
+    assert_eq!(histogram.max(), Duration::from_micros(100));
+    assert_eq!(histogram.mean(), Duration::from_nanos(50500));
+    assert_eq!(histogram.quantile(1.0), Duration::from_micros(100));
+
+    // The buckets are within 1/16 of the values.
+    for (q, micros) in [(0.5, 50), (0.9, 90), (0.99, 99)] {
+        let value = histogram.quantile(q).as_nanos() as f64;
+        let expected = Duration::from_micros(micros).as_nanos() as f64;
+        assert!(
+            value >= expected && value <= expected * 17.0 / 16.0,
+            "{q}: {value}"
+        );
+    }
+    let total: u64 = histogram.buckets().map(|(_, count)| count).sum();
+    assert_eq!(total, 100);
+    for (range, _) in histogram.buckets() {
+        assert!(range.start < range.end);
+    }
+
+    let mut merged = Histogram::new();
+    merged.record(Duration::from_secs(1));
+    merged.merge(&histogram);
+    assert_eq!(merged.count(), 101);
+    assert_eq!(merged.max(), Duration::from_secs(1));
+    assert_eq!(merged.min(), Duration::from_micros(1));
+}
@@ -0,0 +1,141 @@
+use std::{
+    cell::{Cell, RefCell},
+    time::{Duration, Instant},
+};
+
+use compio_runtime::{dynamic::DynRuntime, Runtime};
+use futures_util::future::LocalBoxFuture;
+
+async fn plugin(rt: &dyn DynRuntime) -> u64 {
+    let start = rt.now();
+    rt.sleep(Duration::from_millis(20)).await;
+    let elapsed = rt.now() - start;
+    let task = rt.spawn(async { 1 });
+    let blocking = rt.spawn_blocking(|| 2);
+    elapsed.as_millis() as u64 * 10 + task.await + blocking.await
+}
+
+// Runs the tasks on the real runtime, with a manual clock.
+struct FakeRuntime {
+    now: Cell<Instant>,
+    sleeps: RefCell<Vec<Duration>>,
+}
+
+impl DynRuntime for FakeRuntime {
+    fn spawn_boxed(&self, future: LocalBoxFuture<'static, ()>) -> LocalBoxFuture<'static, ()> {
+        Box::pin(compio_runtime::spawn(future))
+    }
+
+    fn spawn_blocking_boxed(
+        &self,
+        f: Box<dyn FnOnce() + Send + 'static>,
+    ) -> LocalBoxFuture<'static, ()> {
+        f();
+        Box::pin(async {})
+    }
+
+    fn sleep(&self, duration: Duration) -> LocalBoxFuture<'static, ()> {
+        self.sleeps.borrow_mut().push(duration);
+        self.now.set(self.now.get() + duration);
+        Box::pin(async {})
+    }
+
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+#[test]
+fn runtime() {
+    let rt = Runtime::new().unwrap();
+    let res = rt.block_on(plugin(&rt));
+    assert!(res >= 203, "{res}");
+}
+
+#[test]
+fn fake() {
+    let fake = FakeRuntime {
+        now: Cell::new(Instant::now()),
+        sleeps: RefCell::new(vec![]),
+    };
+    let res = Runtime::new().unwrap().block_on(plugin(&fake));
+    assert_eq!(res, 203);
+    assert_eq!(*fake.sleeps.borrow(), [Duration::from_millis(20)]);
+}
+
+#[test]
+fn cancel() {
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let dyn_rt: &dyn DynRuntime = &rt;
+        let done = std::rc::Rc::new(Cell::new(false));
+        let task = dyn_rt.spawn({
+            let done = done.clone();
+            async move {
+                compio_runtime::time::sleep(Duration::from_millis(10)).await;
+                done.set(true);
+            }
+        });
+        drop(task);
+        compio_runtime::time::sleep(Duration::from_millis(30)).await;
+        assert!(!done.get());
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn io() {
+    use std::{io::Read, os::fd::AsRawFd};
+
+    let (a, mut b) = std::os::unix::net::UnixStream::pair().unwrap();
+    a.set_nonblocking(true).unwrap();
+    let path = std::env::temp_dir().join(format!("compio-dynamic-{}", std::process::id()));
+    let file = std::fs::File::create_new(&path).unwrap();
+
+    let rt = Runtime::new().unwrap();
+    rt.block_on(async {
+        let dyn_rt: &dyn DynRuntime = &rt;
+        dyn_rt.attach(a.as_raw_fd()).unwrap();
+        dyn_rt.attach(file.as_raw_fd()).unwrap();
+
+        let (n, _) = dyn_rt
+            .write_at(file.as_raw_fd(), b"hello".to_vec(), 2)
+            .await
+            .unwrap();
+        assert_eq!(n, 5);
+        let (n, buf) = dyn_rt
+            .read_at(file.as_raw_fd(), Vec::with_capacity(8), 3)
+            .await
+            .unwrap();
+        assert_eq!(n, 4);
+        assert_eq!(buf, b"ello");
+
+        let (n, _) = dyn_rt.send(a.as_raw_fd(), b"ping".to_vec()).await.unwrap();
+        assert_eq!(n, 4);
+        let mut ping = [0; 4];
+        b.read_exact(&mut ping).unwrap();
+        assert_eq!(&ping, b"ping");
+
+        std::io::Write::write_all(&mut b, b"pong").unwrap();
+        let (n, buf) = dyn_rt
+            .recv(a.as_raw_fd(), Vec::with_capacity(4))
+            .await
+            .unwrap();
+        assert_eq!(n, 4);
+        assert_eq!(buf, b"pong");
+    });
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn unsupported() {
+    let fake = FakeRuntime {
+        now: Cell::new(Instant::now()),
+        sleeps: RefCell::new(vec![]),
+    };
+    let rt: &dyn DynRuntime = &fake;
+    let compio_buf::BufResult(res, buf) =
+        Runtime::new().unwrap().block_on(rt.recv(0 as _, vec![1]));
+    assert_eq!(res.unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+    assert_eq!(buf, [1]);
+}
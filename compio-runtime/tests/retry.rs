@@ -0,0 +1,93 @@
+use std::{cell::Cell, io, time::Duration};
+
+use compio_runtime::{
+    retry::RetryPolicy,
+    time::{pause, timeout},
+    Runtime,
+};
+
+async fn fail_with(attempts: &Cell<usize>, kind: io::ErrorKind, until: usize) -> io::Result<()> {
+    attempts.set(attempts.get() + 1);
+    if attempts.get() < until {
+        Err(kind.into())
+    } else {
+        Ok(())
+    }
+}
+
+#[test]
+fn delays() {
+    let policy = RetryPolicy::new()
+        .initial_delay(Duration::from_secs(1))
+        .multiplier(3.0)
+        .max_delay(Duration::from_secs(20));
+    assert_eq!(policy.delay(0), Duration::from_secs(1));
+    assert_eq!(policy.delay(2), Duration::from_secs(9));
+    assert_eq!(policy.delay(3), Duration::from_secs(20));
+    assert_eq!(policy.delay(usize::MAX), Duration::from_secs(20));
+}
+
+#[test]
+fn retry_transient() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let policy = RetryPolicy::new().max_retries(5);
+
+        let attempts = Cell::new(0);
+        let res = policy
+            .retry(|| fail_with(&attempts, io::ErrorKind::ConnectionReset, 4))
+            .await;
+        assert!(res.is_ok());
+        assert_eq!(attempts.get(), 4);
+
+        // Out of retries.
+        let attempts = Cell::new(0);
+        let err = policy
+            .retry(|| fail_with(&attempts, io::ErrorKind::TimedOut, 10))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(attempts.get(), 6);
+    })
+}
+
+#[test]
+fn no_retry_permanent() {
+    Runtime::new().unwrap().block_on(async {
+        let attempts = Cell::new(0);
+        let err = RetryPolicy::new()
+            .retry(|| fail_with(&attempts, io::ErrorKind::NotFound, 2))
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert_eq!(attempts.get(), 1);
+
+        let attempts = Cell::new(0);
+        RetryPolicy::new()
+            .retry_if(|e| e.kind() == io::ErrorKind::NotFound)
+            .retry(|| fail_with(&attempts, io::ErrorKind::NotFound, 2))
+            .await
+            .unwrap();
+        assert_eq!(attempts.get(), 2);
+    })
+}
+
+#[test]
+fn cancel_backoff() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let attempts = Cell::new(0);
+        let policy = RetryPolicy::new()
+            .max_retries(usize::MAX)
+            .initial_delay(Duration::from_secs(1))
+            .jitter(false);
+        let res = timeout(
+            Duration::from_millis(3500),
+            policy.retry(|| fail_with(&attempts, io::ErrorKind::ConnectionRefused, usize::MAX)),
+        )
+        .await;
+        assert!(res.is_err());
+        // Attempts at 0s, 1s and 3s.
+        assert_eq!(attempts.get(), 3);
+    })
+}
@@ -0,0 +1,173 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use compio_runtime::{
+    time::{
+        advance, now, pause, resume, sleep, sleep_with, timeout, timeout_at, Clock, MockClock,
+        TimerMode,
+    },
+    Runtime, RuntimeBuilder,
+};
+
+#[test]
+fn paused_sleep() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let start = Instant::now();
+        sleep(Duration::from_secs(3600)).await;
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        resume();
+        let start = Instant::now();
+        sleep(Duration::from_millis(10)).await;
+        assert!(start.elapsed() >= Duration::from_millis(10));
+    })
+}
+
+#[test]
+fn earliest_first() {
+    Runtime::new().unwrap().block_on(async {
+        let long = compio_runtime::spawn(sleep(Duration::from_secs(30)));
+        // Let the task start the timer.
+        sleep(Duration::ZERO).await;
+        let start = Instant::now();
+        sleep(Duration::from_millis(10)).await;
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert!(!long.is_finished());
+    })
+}
+
+#[test]
+fn paused_advance() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let task = compio_runtime::spawn(timeout(
+            Duration::from_secs(10),
+            std::future::pending::<()>(),
+        ));
+        // Let the task start the timer.
+        advance(Duration::ZERO).await;
+        advance(Duration::from_secs(5)).await;
+        assert!(!task.is_finished());
+        advance(Duration::from_secs(5)).await;
+        assert!(task.is_finished());
+        assert!(task.await.is_err());
+    })
+}
+
+#[test]
+fn coarse_rounds_up() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let precise =
+            compio_runtime::spawn(sleep_with(Duration::from_micros(1500), TimerMode::Precise));
+        let coarse =
+            compio_runtime::spawn(sleep_with(Duration::from_micros(1500), TimerMode::Coarse));
+        advance(Duration::ZERO).await;
+        advance(Duration::from_micros(1500)).await;
+        assert!(precise.is_finished());
+        // The coarse timer waits for the next millisecond tick.
+        assert!(!coarse.is_finished());
+        advance(Duration::from_micros(500)).await;
+        assert!(coarse.is_finished());
+    })
+}
+
+#[test]
+fn coarse_before_precise() {
+    Runtime::new().unwrap().block_on(async {
+        pause();
+        let precise =
+            compio_runtime::spawn(sleep_with(Duration::from_millis(3), TimerMode::Precise));
+        let coarse = (0..100)
+            .map(|i| {
+                compio_runtime::spawn(sleep_with(
+                    Duration::from_micros(1000 + i),
+                    TimerMode::Coarse,
+                ))
+            })
+            .collect::<Vec<_>>();
+        advance(Duration::ZERO).await;
+        // The coarse timers expire at their ticks, before the later precise one.
+        advance(Duration::from_millis(2)).await;
+        assert!(coarse.iter().all(|task| task.is_finished()));
+        assert!(!precise.is_finished());
+        advance(Duration::from_millis(1)).await;
+        assert!(precise.is_finished());
+    })
+}
+
+#[test]
+fn mock_clock() {
+    let clock = MockClock::new();
+    let rt = RuntimeBuilder::new().clock(clock.clone()).build().unwrap();
+    rt.block_on(async {
+        let start = now();
+        let task = compio_runtime::spawn(timeout_at(
+            start + Duration::from_secs(10),
+            std::future::pending::<()>(),
+        ));
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(now() - start, Duration::from_secs(5));
+        assert!(!task.is_finished());
+        clock.advance(Duration::from_secs(5));
+        assert!(task.await.is_err());
+    })
+}
+
+#[derive(Debug)]
+struct BackwardClock(Arc<Mutex<Instant>>);
+
+impl Clock for BackwardClock {
+    fn now(&self) -> Instant {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[test]
+fn monotonic() {
+    let time = Arc::new(Mutex::new(Instant::now()));
+    let rt = RuntimeBuilder::new()
+        .clock(BackwardClock(time.clone()))
+        .build()
+        .unwrap();
+    rt.block_on(async {
+        *time.lock().unwrap() += Duration::from_secs(10);
+        let later = now();
+        *time.lock().unwrap() -= Duration::from_secs(5);
+        assert_eq!(now(), later);
+        pause();
+        let paused = now();
+        advance(Duration::from_secs(1)).await;
+        assert_eq!(now() - paused, Duration::from_secs(1));
+    })
+}
+
+#[test]
+fn deadline_scope() {
+    use compio_runtime::time::Deadline;
+
+    Runtime::new().unwrap().block_on(async {
+        assert_eq!(Deadline::current(), None);
+        let outer = Deadline::after(Duration::from_secs(10));
+        let inner = Deadline::after(Duration::from_secs(1));
+        outer
+            .scope(async {
+                assert_eq!(Deadline::current(), Some(outer));
+                // The nested scopes could only shorten the deadline.
+                Deadline::after(Duration::from_secs(60))
+                    .scope(async { assert_eq!(Deadline::current(), Some(outer)) })
+                    .await;
+                inner
+                    .scope(async { assert_eq!(Deadline::current(), Some(inner)) })
+                    .await;
+                // The spawned tasks don't inherit it.
+                compio_runtime::spawn(async { assert_eq!(Deadline::current(), None) }).await;
+                assert_eq!(Deadline::current(), Some(outer));
+            })
+            .await;
+        assert_eq!(Deadline::current(), None);
+    })
+}
@@ -0,0 +1,190 @@
+//! A scripted mock stream.
+
+use std::{collections::VecDeque, io, time::Duration};
+
+use compio_buf::{BufResult, IoBuf, IoBufMut};
+use compio_io::{AsyncRead, AsyncWrite};
+
+#[derive(Debug)]
+enum Action {
+    Read(Vec<u8>),
+    Write(Vec<u8>),
+    Wait(Duration),
+    ReadError(io::Error),
+    WriteError(io::Error),
+}
+
+/// A builder of the script of a [`Mock`].
+///
+/// The actions happen in the order they are added. Each [`Builder::read`]
+/// is returned by its own reads, so split the data into several reads to
+/// test the partial reads.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use compio_io::{AsyncReadExt, AsyncWriteExt};
+/// use compio_test::io::Builder;
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// compio_test::time::pause();
+/// let mut mock = Builder::new()
+///     .write(b"PING\r\n")
+///     .wait(Duration::from_secs(1))
+///     .read(b"PO")
+///     .read(b"NG\r\n")
+///     .build();
+///
+/// mock.write_all("PING\r\n").await.unwrap();
+/// let (_, buf) = mock.read_exact(Vec::with_capacity(6)).await.unwrap();
+/// assert_eq!(buf, b"PONG\r\n");
+/// # })
+/// ```
+#[derive(Debug, Default)]
+pub struct Builder {
+    actions: VecDeque<Action>,
+}
+
+impl Builder {
+    /// Create an empty script.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The next read returns `data`, or a part of it if the buffer is
+    /// smaller.
+    pub fn read(mut self, data: &[u8]) -> Self {
+        self.actions.push_back(Action::Read(data.to_vec()));
+        self
+    }
+
+    /// The next writes should write `data`, or the mock panics.
+    pub fn write(mut self, data: &[u8]) -> Self {
+        self.actions.push_back(Action::Write(data.to_vec()));
+        self
+    }
+
+    /// The next read or write waits for `duration` on the timer clock.
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.actions.push_back(Action::Wait(duration));
+        self
+    }
+
+    /// The next read fails with `error`.
+    pub fn read_error(mut self, error: io::Error) -> Self {
+        self.actions.push_back(Action::ReadError(error));
+        self
+    }
+
+    /// The next write fails with `error`.
+    pub fn write_error(mut self, error: io::Error) -> Self {
+        self.actions.push_back(Action::WriteError(error));
+        self
+    }
+
+    /// Build the [`Mock`].
+    pub fn build(self) -> Mock {
+        Mock {
+            actions: self.actions,
+        }
+    }
+}
+
+/// A stream following the script of a [`Builder`].
+///
+/// It panics when the reads and writes differ from the script, and when it
+/// is dropped before the script finishes. The reads return EOF after the
+/// script finishes.
+#[derive(Debug)]
+pub struct Mock {
+    actions: VecDeque<Action>,
+}
+
+impl Mock {
+    async fn wait(&mut self) {
+        while let Some(Action::Wait(duration)) = self.actions.front() {
+            let duration = *duration;
+            self.actions.pop_front();
+            compio_runtime::time::sleep(duration).await;
+        }
+    }
+}
+
+impl AsyncRead for Mock {
+    async fn read<B: IoBufMut>(&mut self, mut buf: B) -> BufResult<usize, B> {
+        self.wait().await;
+        let len = match self.actions.front_mut() {
+            None => 0,
+            Some(Action::Read(data)) => {
+                let slice = buf.as_mut_slice();
+                let len = slice.len().min(data.len());
+                for (dst, src) in slice.iter_mut().zip(data.drain(..len)) {
+                    dst.write(src);
+                }
+                if data.is_empty() {
+                    self.actions.pop_front();
+                }
+                len
+            }
+            Some(Action::ReadError(_)) => {
+                let Some(Action::ReadError(e)) = self.actions.pop_front() else {
+                    unreachable!()
+                };
+                return BufResult(Err(e), buf);
+            }
+            Some(action) => panic!("unexpected read, the next action is {action:?}"),
+        };
+        unsafe { buf.set_buf_init(len) };
+        BufResult(Ok(len), buf)
+    }
+}
+
+impl AsyncWrite for Mock {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        self.wait().await;
+        let slice = buf.as_slice();
+        match self.actions.front_mut() {
+            Some(Action::Write(data)) => {
+                let len = slice.len().min(data.len());
+                assert_eq!(
+                    &slice[..len],
+                    &data[..len],
+                    "the written data differs from the script"
+                );
+                data.drain(..len);
+                if data.is_empty() {
+                    self.actions.pop_front();
+                }
+                BufResult(Ok(len), buf)
+            }
+            Some(Action::WriteError(_)) => {
+                let Some(Action::WriteError(e)) = self.actions.pop_front() else {
+                    unreachable!()
+                };
+                BufResult(Err(e), buf)
+            }
+            Some(action) => panic!("unexpected write, the next action is {action:?}"),
+            None => panic!("unexpected write, the script has finished"),
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for Mock {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            assert!(
+                self.actions.is_empty(),
+                "the mock is dropped with the remaining actions {:?}",
+                self.actions
+            );
+        }
+    }
+}
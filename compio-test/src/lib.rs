@@ -0,0 +1,25 @@
+//! Testing utilities for compio.
+//!
+//! It brings together the pieces for deterministic protocol tests:
+//!
+//! * [`io::Builder`] scripts a mock stream: the data and order of the reads,
+//!   the partial reads, the expected writes, the delays and the errors.
+//! * [`duplex`] creates a pair of connected in-memory streams.
+//! * [`time`] pauses and advances the timer clock.
+//!
+//! Run the tests with the paused clock, e.g. with
+//! `#[compio_macros::test(start_paused = true)]`, so that the scripted
+//! delays and the timeouts of the protocol complete instantly, and in the
+//! same order on every run.
+
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+#![warn(missing_docs)]
+
+pub mod io;
+
+pub use compio_net::{duplex, DuplexStream};
+
+/// Control the timer clock of the current runtime.
+pub mod time {
+    pub use compio_runtime::time::{advance, pause, resume};
+}
@@ -0,0 +1,63 @@
+use std::{
+    io,
+    time::{Duration, Instant},
+};
+
+use compio_io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use compio_test::io::Builder;
+
+#[compio_macros::test]
+async fn partial_reads() {
+    let mut mock = Builder::new().read(b"hello").read(b" world").build();
+
+    let (len, buf) = mock.read(Vec::with_capacity(3)).await.unwrap();
+    assert_eq!(len, 3);
+    assert_eq!(buf, b"hel");
+    let (len, buf) = mock.read(Vec::with_capacity(16)).await.unwrap();
+    assert_eq!(len, 2);
+    assert_eq!(buf, b"lo");
+    let (_, buf) = mock.read_to_end(vec![]).await.unwrap();
+    assert_eq!(buf, b" world");
+}
+
+#[compio_macros::test(start_paused = true)]
+async fn scripted_delay() {
+    let mut mock = Builder::new()
+        .write(b"req")
+        .wait(Duration::from_secs(30))
+        .read(b"resp")
+        .build();
+
+    mock.write_all("req").await.unwrap();
+    let start = Instant::now();
+    let (_, buf) = mock.read_exact(Vec::with_capacity(4)).await.unwrap();
+    assert_eq!(buf, b"resp");
+    // The paused clock jumps over the delay.
+    assert!(start.elapsed() < Duration::from_secs(30));
+}
+
+#[compio_macros::test]
+async fn errors() {
+    let mut mock = Builder::new()
+        .read_error(io::ErrorKind::ConnectionReset.into())
+        .write_error(io::ErrorKind::BrokenPipe.into())
+        .build();
+
+    let err = mock.read(Vec::with_capacity(4)).await.0.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::ConnectionReset);
+    let err = mock.write(b"data").await.0.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[compio_macros::test]
+#[should_panic(expected = "differs from the script")]
+async fn wrong_write() {
+    let mut mock = Builder::new().write(b"expected").build();
+    let _ = mock.write(b"actual").await;
+}
+
+#[compio_macros::test]
+#[should_panic(expected = "remaining actions")]
+async fn unfinished() {
+    let _mock = Builder::new().read(b"unread").build();
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::{future::Future, io};
 
 use compio_io::{compat::SyncStream, AsyncRead, AsyncWrite};
 
@@ -61,6 +61,44 @@ impl TlsConnector {
             TlsConnectorInner::Rustls(c) => handshake_rustls(c.connect(domain, stream)).await,
         }
     }
+
+    /// Connects the provided stream like [`connect`](TlsConnector::connect),
+    /// sending `data` as the TLS 1.3 early data, i.e. 0-RTT, when resuming a
+    /// session. Returns the stream, and the length of `data` accepted by the
+    /// server as the early data.
+    ///
+    /// The rest of `data` should be written to the stream after the
+    /// handshake, and all of it is if the server rejects the early data, or
+    /// there is no session to resume. The early data could be replayed by an
+    /// attacker, so only send the idempotent requests this way.
+    ///
+    /// It needs `enable_early_data` of [`rustls::ClientConfig`], and the
+    /// early data is never sent with [`native_tls`].
+    pub async fn connect_with_early_data<S: AsyncRead + AsyncWrite>(
+        &self,
+        domain: &str,
+        stream: S,
+        data: &[u8],
+    ) -> io::Result<(TlsStream<S>, usize)> {
+        match &self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsConnectorInner::NativeTls(_) => {
+                let _ = data;
+                Ok((self.connect(domain, stream).await?, 0))
+            }
+            #[cfg(feature = "rustls")]
+            TlsConnectorInner::Rustls(c) => {
+                let (written, res) = c.connect_with_early_data(domain, stream, data);
+                let stream = handshake_rustls(res).await?;
+                let accepted = if stream.is_early_data_accepted() {
+                    written
+                } else {
+                    0
+                };
+                Ok((stream, accepted))
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -111,6 +149,95 @@ impl TlsAcceptor {
             TlsAcceptorInner::Rustls(c) => handshake_rustls(c.accept(stream)).await,
         }
     }
+
+    /// Accepts a client connection like [`accept`](TlsAcceptor::accept), and
+    /// returns the TLS 1.3 early data, i.e. 0-RTT, sent by the client.
+    ///
+    /// When the early data is accepted, it returns once the server has sent
+    /// its handshake messages, before the client finishes the handshake, so
+    /// the early request could be handled one round trip earlier. The
+    /// handshake completes on the next read from the stream. The early data
+    /// delayed by the network is read from the stream before the data after
+    /// the handshake. Set `send_half_rtt_data` of [`rustls::ServerConfig`] to
+    /// write the response before the client finishes, too.
+    ///
+    /// It needs `max_early_data_size` of [`rustls::ServerConfig`], and the
+    /// early data is never accepted with [`native_tls`]. See [`EarlyData`]
+    /// for the replay attacks.
+    pub async fn accept_with_early_data<S: AsyncRead + AsyncWrite>(
+        &self,
+        stream: S,
+    ) -> io::Result<(TlsStream<S>, EarlyData)> {
+        let mut stream = match &self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsAcceptorInner::NativeTls(_) => self.accept(stream).await?,
+            #[cfg(feature = "rustls")]
+            TlsAcceptorInner::Rustls(c) => {
+                handshake_rustls(c.accept_with_early_data(stream)).await?
+            }
+        };
+        let data = stream.take_early_data()?;
+        Ok((stream, EarlyData(data)))
+    }
+
+    /// Accepts a client connection like [`accept`](TlsAcceptor::accept), and
+    /// verifies the DER certificates sent by the client with the async
+    /// `verify`, e.g. checking the revocation with an OCSP responder, or a
+    /// policy service over the network.
+    ///
+    /// The backends verify the certificates synchronously during the
+    /// handshake, so `verify` runs after it completes, before any data is
+    /// read. If it fails, the stream is shut down, and its error is
+    /// returned. The client certificates should be requested by the server
+    /// config, e.g. with `rustls::ServerConfig::with_client_cert_verifier`,
+    /// otherwise the chain is empty; [`native_tls`] never requests them.
+    pub async fn accept_verified<S, F, Fut>(&self, stream: S, verify: F) -> io::Result<TlsStream<S>>
+    where
+        S: AsyncRead + AsyncWrite,
+        F: FnOnce(Vec<Vec<u8>>) -> Fut,
+        Fut: Future<Output = io::Result<()>>,
+    {
+        let mut stream = self.accept(stream).await?;
+        let certs = stream.peer_certificates()?;
+        if let Err(e) = verify(certs).await {
+            stream.shutdown().await.ok();
+            return Err(e);
+        }
+        Ok(stream)
+    }
+}
+
+/// The TLS 1.3 early data, i.e. 0-RTT, received before the handshake
+/// completes, by [`TlsAcceptor::accept_with_early_data`].
+///
+/// Unlike the data read from the stream, it is not protected from replay: an
+/// attacker could capture the ClientHello and resend it with the same early
+/// data, maybe to another server sharing the session tickets. Only handle the
+/// idempotent requests in the early data, or wait for the handshake, so that
+/// they are safe to be handled twice.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EarlyData(Vec<u8>);
+
+impl EarlyData {
+    /// The length of the early data.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no early data is received.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The replayable early data.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Take the replayable early data.
+    pub fn into_inner(self) -> Vec<u8> {
+        self.0
+    }
 }
 
 #[cfg(feature = "native-tls")]
@@ -129,7 +256,7 @@ async fn handshake_native_tls<S: AsyncRead + AsyncWrite>(
                 return Ok(TlsStream::from(s));
             }
             Err(e) => match e {
-                HandshakeError::Failure(e) => return Err(io::Error::new(io::ErrorKind::Other, e)),
+                HandshakeError::Failure(e) => return Err(io::Error::other(e)),
                 HandshakeError::WouldBlock(mut mid_stream) => {
                     if mid_stream.get_mut().flush_write_buf().await? == 0 {
                         mid_stream.get_mut().fill_read_buf().await?;
@@ -157,7 +284,7 @@ where
                 return Ok(s);
             }
             Err(e) => match e {
-                HandshakeError::Rustls(e) => return Err(io::Error::new(io::ErrorKind::Other, e)),
+                HandshakeError::Rustls(e) => return Err(io::Error::other(e)),
                 HandshakeError::System(e) => return Err(e),
                 HandshakeError::WouldBlock(mut mid_stream) => {
                     if mid_stream.get_mut().flush_write_buf().await? == 0 {
@@ -1,4 +1,8 @@
-use std::{io, ops::DerefMut, sync::Arc};
+use std::{
+    io::{self, Write},
+    ops::DerefMut,
+    sync::Arc,
+};
 
 use compio_io::{compat::SyncStream, AsyncRead, AsyncWrite};
 use rustls::{
@@ -18,6 +22,9 @@ pub struct MidStream<S, C> {
     stream: SyncStream<S>,
     conn: C,
     result_fn: fn(SyncStream<S>, C) -> TlsStream<S>,
+    // Whether to return the stream before the handshake completes, when it
+    // waits to read.
+    early_fn: fn(&mut C) -> bool,
 }
 
 impl<S, C> MidStream<S, C> {
@@ -30,6 +37,7 @@ impl<S, C> MidStream<S, C> {
             stream,
             conn,
             result_fn,
+            early_fn: |_| false,
         }
     }
 
@@ -80,6 +88,13 @@ impl<S, C> MidStream<S, C> {
                     Err(HandshakeError::System(err))
                 }
                 (_, false) => Ok((self.result_fn)(self.stream, self.conn)),
+                (_, true)
+                    if read_would_block
+                        && !self.conn.wants_write()
+                        && (self.early_fn)(&mut self.conn) =>
+                {
+                    Ok((self.result_fn)(self.stream, self.conn))
+                }
                 (_, true) if write_would_block || read_would_block => {
                     Err(HandshakeError::WouldBlock(self))
                 }
@@ -99,20 +114,43 @@ impl TlsConnector {
         domain: &str,
         stream: S,
     ) -> Result<TlsStream<S>, HandshakeError<S, ClientConnection>> {
-        let conn = ClientConnection::new(
-            self.0.clone(),
-            ServerName::try_from(domain)
-                .map_err(|e| HandshakeError::System(io::Error::new(io::ErrorKind::Other, e)))?
-                .to_owned(),
-        )
-        .map_err(HandshakeError::Rustls)?;
+        self.connect_with_early_data(domain, stream, &[]).1
+    }
 
-        MidStream::new(
+    // Returns the length of the early data written, which is sent with the
+    // ClientHello.
+    #[allow(clippy::result_large_err)]
+    pub fn connect_with_early_data<S: AsyncRead + AsyncWrite>(
+        &self,
+        domain: &str,
+        stream: S,
+        data: &[u8],
+    ) -> (
+        usize,
+        Result<TlsStream<S>, HandshakeError<S, ClientConnection>>,
+    ) {
+        let conn = ServerName::try_from(domain)
+            .map_err(|e| HandshakeError::System(io::Error::other(e)))
+            .and_then(|name| {
+                ClientConnection::new(self.0.clone(), name.to_owned())
+                    .map_err(HandshakeError::Rustls)
+            });
+        let mut conn = match conn {
+            Ok(conn) => conn,
+            Err(e) => return (0, Err(e)),
+        };
+        let written = match conn.early_data() {
+            Some(mut early_data) if !data.is_empty() => early_data.write(data).unwrap_or(0),
+            _ => 0,
+        };
+
+        let res = MidStream::new(
             SyncStream::new(stream),
             conn,
             TlsStream::<S>::new_rustls_client,
         )
-        .handshake()
+        .handshake();
+        (written, res)
     }
 }
 
@@ -124,14 +162,34 @@ impl TlsAcceptor {
     pub fn accept<S: AsyncRead + AsyncWrite>(
         &self,
         stream: S,
+    ) -> Result<TlsStream<S>, HandshakeError<S, ServerConnection>> {
+        self.accept_impl(stream, |_| false)
+    }
+
+    // Returns the stream before the client finishes the handshake, when the
+    // early data is accepted, and the server has nothing more to read.
+    #[allow(clippy::result_large_err)]
+    pub fn accept_with_early_data<S: AsyncRead + AsyncWrite>(
+        &self,
+        stream: S,
+    ) -> Result<TlsStream<S>, HandshakeError<S, ServerConnection>> {
+        self.accept_impl(stream, |conn| conn.early_data().is_some())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn accept_impl<S: AsyncRead + AsyncWrite>(
+        &self,
+        stream: S,
+        early_fn: fn(&mut ServerConnection) -> bool,
     ) -> Result<TlsStream<S>, HandshakeError<S, ServerConnection>> {
         let conn = ServerConnection::new(self.0.clone()).map_err(HandshakeError::Rustls)?;
 
-        MidStream::new(
+        let mut mid = MidStream::new(
             SyncStream::new(stream),
             conn,
             TlsStream::<S>::new_rustls_server,
-        )
-        .handshake()
+        );
+        mid.early_fn = early_fn;
+        mid.handshake()
     }
 }
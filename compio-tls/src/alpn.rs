@@ -0,0 +1,130 @@
+use std::{fmt, future::Future, io, pin::Pin};
+
+use crate::TlsStream;
+
+type Handler<S, T> = Box<dyn Fn(TlsStream<S>) -> Pin<Box<dyn Future<Output = io::Result<T>>>>>;
+
+/// Dispatches the accepted TLS streams to the handlers by the protocol
+/// negotiated with ALPN, so that one listener could serve e.g. HTTP/2, HTTP/1.1
+/// and custom protocols.
+///
+/// The server should accept the [`protocols`](AlpnDispatcher::protocols) of the
+/// routes, e.g. with `rustls::ServerConfig::alpn_protocols` or
+/// `native_tls::TlsAcceptorBuilder::accept_alpn`. The
+/// [fallback](AlpnDispatcher::fallback) handles the others, and the streams
+/// without ALPN.
+///
+/// ```
+/// use compio_net::TcpStream;
+/// use compio_tls::{AlpnDispatcher, TlsAcceptor, TlsStream};
+///
+/// async fn serve_h2(stream: TlsStream<TcpStream>) -> std::io::Result<()> {
+///     // ...
+/// #   Ok(())
+/// }
+///
+/// async fn serve_http1(stream: TlsStream<TcpStream>) -> std::io::Result<()> {
+///     // ...
+/// #   Ok(())
+/// }
+///
+/// async fn serve(acceptor: &TlsAcceptor, stream: TcpStream) -> std::io::Result<()> {
+///     let dispatcher = AlpnDispatcher::new()
+///         .route("h2", serve_h2)
+///         .fallback(serve_http1);
+///     let stream = acceptor.accept(stream).await?;
+///     dispatcher.dispatch(stream).await
+/// }
+/// ```
+pub struct AlpnDispatcher<S, T> {
+    routes: Vec<(Vec<u8>, Handler<S, T>)>,
+    fallback: Option<Handler<S, T>>,
+}
+
+impl<S: 'static, T: 'static> AlpnDispatcher<S, T> {
+    /// Create a dispatcher without routes.
+    pub fn new() -> Self {
+        Self {
+            routes: vec![],
+            fallback: None,
+        }
+    }
+
+    /// Handle the streams negotiating `protocol` with `handler`. It replaces
+    /// the previous handler of the same protocol.
+    pub fn route<F: Future<Output = io::Result<T>> + 'static>(
+        mut self,
+        protocol: impl Into<Vec<u8>>,
+        handler: impl Fn(TlsStream<S>) -> F + 'static,
+    ) -> Self {
+        let protocol = protocol.into();
+        let handler = boxed(handler);
+        match self.routes.iter_mut().find(|(p, _)| *p == protocol) {
+            Some((_, h)) => *h = handler,
+            None => self.routes.push((protocol, handler)),
+        }
+        self
+    }
+
+    /// Handle the streams negotiating the other protocols, or without ALPN,
+    /// with `handler`.
+    pub fn fallback<F: Future<Output = io::Result<T>> + 'static>(
+        mut self,
+        handler: impl Fn(TlsStream<S>) -> F + 'static,
+    ) -> Self {
+        self.fallback = Some(boxed(handler));
+        self
+    }
+
+    /// The protocols of the routes, in the order they are added.
+    pub fn protocols(&self) -> impl Iterator<Item = &[u8]> {
+        self.routes.iter().map(|(p, _)| p.as_slice())
+    }
+
+    /// Run the handler of the protocol negotiated by `stream`. It fails with
+    /// [`InvalidData`](io::ErrorKind::InvalidData) if no handler matches.
+    pub async fn dispatch(&self, stream: TlsStream<S>) -> io::Result<T> {
+        let handler = {
+            let alpn = stream.negotiated_alpn();
+            alpn.as_deref()
+                .and_then(|alpn| self.routes.iter().find(|(p, _)| p == alpn))
+                .map(|(_, h)| h)
+                .or(self.fallback.as_ref())
+        };
+        match handler {
+            Some(handler) => handler(stream).await,
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no handler for the ALPN protocol",
+            )),
+        }
+    }
+}
+
+fn boxed<S, T, F: Future<Output = io::Result<T>> + 'static>(
+    handler: impl Fn(TlsStream<S>) -> F + 'static,
+) -> Handler<S, T> {
+    Box::new(move |stream| Box::pin(handler(stream)))
+}
+
+impl<S: 'static, T: 'static> Default for AlpnDispatcher<S, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, T> fmt::Debug for AlpnDispatcher<S, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlpnDispatcher")
+            .field(
+                "protocols",
+                &self
+                    .routes
+                    .iter()
+                    .map(|(p, _)| String::from_utf8_lossy(p))
+                    .collect::<Vec<_>>(),
+            )
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
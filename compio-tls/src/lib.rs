@@ -13,7 +13,17 @@ pub use native_tls;
 pub use rustls;
 
 mod adapter;
+mod alpn;
+#[cfg(feature = "reload")]
+mod reload;
+#[cfg(feature = "rustls")]
+mod session;
 mod stream;
 
 pub use adapter::*;
+pub use alpn::*;
+#[cfg(feature = "reload")]
+pub use reload::*;
+#[cfg(feature = "rustls")]
+pub use session::*;
 pub use stream::*;
@@ -0,0 +1,145 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock, Weak},
+    time::Duration,
+};
+
+use compio_buf::BufResult;
+use compio_io::{AsyncRead, AsyncReadAtExt, AsyncWrite};
+
+use crate::{TlsAcceptor, TlsStream};
+
+type LoadFn = dyn Fn(&[u8], &[u8]) -> io::Result<TlsAcceptor> + Send + Sync;
+
+/// A [`TlsAcceptor`] reloading the certificate and the key from the files
+/// when they change, so that a renewed certificate is served without
+/// restarting.
+///
+/// The files are checked on the runtime timer by [`run`](CertReloader::run),
+/// or by [`reload`](CertReloader::reload). The new pair is validated by
+/// building an acceptor from it, and the acceptor is swapped atomically, so
+/// the connections always get a consistent pair. If it fails to load, e.g.
+/// when a file is being written, the previous acceptor is kept and the files
+/// are checked again later. The established connections are not affected.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use compio_net::TcpListener;
+/// use compio_tls::{native_tls, CertReloader, TlsAcceptor};
+///
+/// # compio_runtime::Runtime::new().unwrap().block_on(async {
+/// let reloader = CertReloader::new("cert.pem", "key.pem", |cert, key| {
+///     let identity =
+///         native_tls::Identity::from_pkcs8(cert, key).map_err(std::io::Error::other)?;
+///     let acceptor = native_tls::TlsAcceptor::new(identity).map_err(std::io::Error::other)?;
+///     Ok(TlsAcceptor::from(acceptor))
+/// })
+/// .await
+/// .unwrap();
+/// compio_runtime::spawn(reloader.clone().run(Duration::from_secs(60))).detach();
+///
+/// let listener = TcpListener::bind("0.0.0.0:443").await.unwrap();
+/// loop {
+///     let (stream, _) = listener.accept().await.unwrap();
+///     let stream = reloader.accept(stream).await.unwrap();
+///     // ...
+/// }
+/// # })
+/// ```
+pub struct CertReloader {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    load: Box<LoadFn>,
+    acceptor: RwLock<TlsAcceptor>,
+    // The contents of the loaded certificate and key.
+    loaded: Mutex<(Vec<u8>, Vec<u8>)>,
+}
+
+impl CertReloader {
+    /// Create a reloader of the PEM or DER files, with `load` building the
+    /// acceptor from the contents of the certificate and the key. It fails if
+    /// the files couldn't be read or loaded.
+    ///
+    /// `load` should fail if the key doesn't match the certificate, which is
+    /// not checked by all the backends.
+    pub async fn new(
+        cert_path: impl Into<PathBuf>,
+        key_path: impl Into<PathBuf>,
+        load: impl Fn(&[u8], &[u8]) -> io::Result<TlsAcceptor> + Send + Sync + 'static,
+    ) -> io::Result<Arc<Self>> {
+        let cert_path = cert_path.into();
+        let key_path = key_path.into();
+        let (cert, key) = read_pair(&cert_path, &key_path).await?;
+        let acceptor = load(&cert, &key)?;
+        Ok(Arc::new(Self {
+            cert_path,
+            key_path,
+            load: Box::new(load),
+            acceptor: RwLock::new(acceptor),
+            loaded: Mutex::new((cert, key)),
+        }))
+    }
+
+    /// The current acceptor.
+    pub fn acceptor(&self) -> TlsAcceptor {
+        self.acceptor.read().unwrap().clone()
+    }
+
+    /// Accept a client connection with the current acceptor.
+    pub async fn accept<S: AsyncRead + AsyncWrite>(&self, stream: S) -> io::Result<TlsStream<S>> {
+        self.acceptor().accept(stream).await
+    }
+
+    /// Reload the files if they changed, and returns whether the acceptor is
+    /// swapped. The previous acceptor is kept on error.
+    pub async fn reload(&self) -> io::Result<bool> {
+        let (cert, key) = read_pair(&self.cert_path, &self.key_path).await?;
+        {
+            let loaded = self.loaded.lock().unwrap();
+            if loaded.0 == cert && loaded.1 == key {
+                return Ok(false);
+            }
+        }
+        let acceptor = (self.load)(&cert, &key)?;
+        *self.acceptor.write().unwrap() = acceptor;
+        *self.loaded.lock().unwrap() = (cert, key);
+        Ok(true)
+    }
+
+    /// Check the files every `interval` until the reloader is dropped. The
+    /// errors are ignored, and the files are checked again in the next
+    /// interval.
+    pub async fn run(self: Arc<Self>, interval: Duration) {
+        let this = Arc::downgrade(&self);
+        drop(self);
+        loop {
+            compio_runtime::time::sleep(interval).await;
+            let Some(this) = Weak::upgrade(&this) else {
+                break;
+            };
+            this.reload().await.ok();
+        }
+    }
+}
+
+impl fmt::Debug for CertReloader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertReloader")
+            .field("cert_path", &self.cert_path)
+            .field("key_path", &self.key_path)
+            .finish_non_exhaustive()
+    }
+}
+
+async fn read_pair(cert_path: &Path, key_path: &Path) -> io::Result<(Vec<u8>, Vec<u8>)> {
+    Ok((read(cert_path).await?, read(key_path).await?))
+}
+
+async fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let file = compio_fs::File::open(path).await?;
+    let BufResult(res, buffer) = file.read_to_end_at(Vec::new(), 0).await;
+    res?;
+    Ok(buffer)
+}
@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt, io,
+    sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, Weak},
+    time::{Duration, Instant},
+};
+
+use rustls::{
+    client::{
+        ClientSessionMemoryCache, ClientSessionStore, Tls12ClientSessionValue,
+        Tls13ClientSessionValue,
+    },
+    pki_types::ServerName,
+    server::{ProducesTickets, StoresServerSessions},
+    NamedGroup,
+};
+
+/// A server-side cache of the TLS sessions, for the resumption with session
+/// IDs, and the stateful TLS 1.3 tickets.
+///
+/// It keeps `capacity` sessions at most, evicting the oldest ones, and the
+/// sessions older than `ttl` are not resumed, and evicted when a new session
+/// is put. Set it as `rustls::ServerConfig::session_storage`.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use compio_tls::ServerSessionCache;
+/// use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+///
+/// # fn main() -> Result<(), rustls::Error> {
+/// # let certs: Vec<CertificateDer<'static>> = vec![];
+/// # let key = PrivateKeyDer::Pkcs8(vec![].into());
+/// let mut config = rustls::ServerConfig::builder()
+///     .with_no_client_auth()
+///     .with_single_cert(certs, key)?;
+/// config.session_storage = ServerSessionCache::new(1024, Duration::from_secs(3600));
+/// # Ok(())
+/// # }
+/// ```
+pub struct ServerSessionCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<SessionMap>,
+}
+
+#[derive(Default)]
+struct SessionMap {
+    sessions: HashMap<Vec<u8>, (Vec<u8>, Instant)>,
+    // The keys in the order they are put, might be removed from `sessions`.
+    order: VecDeque<Vec<u8>>,
+}
+
+impl ServerSessionCache {
+    /// Create a cache of `capacity` sessions, living for `ttl`.
+    pub fn new(capacity: usize, ttl: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            capacity,
+            ttl,
+            inner: Mutex::default(),
+        })
+    }
+
+    /// The number of the cached sessions, including the expired ones not
+    /// evicted yet.
+    pub fn len(&self) -> usize {
+        self.lock().sessions.len()
+    }
+
+    /// Whether no session is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all the sessions, so that none is resumed.
+    pub fn clear(&self) {
+        *self.lock() = SessionMap::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, SessionMap> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl StoresServerSessions for ServerSessionCache {
+    fn put(&self, key: Vec<u8>, value: Vec<u8>) -> bool {
+        if self.capacity == 0 {
+            return false;
+        }
+        let now = Instant::now();
+        let mut inner = self.lock();
+        let SessionMap { sessions, order } = &mut *inner;
+        // Evict the expired sessions, which are put first.
+        while let Some(oldest) = order.front() {
+            match sessions.get(oldest) {
+                Some((_, created)) if now.duration_since(*created) < self.ttl => break,
+                Some(_) => {
+                    sessions.remove(oldest);
+                }
+                None => {}
+            }
+            order.pop_front();
+        }
+        if sessions.insert(key.clone(), (value, now)).is_none() {
+            order.push_back(key);
+        }
+        while sessions.len() > self.capacity {
+            let Some(oldest) = order.pop_front() else {
+                break;
+            };
+            sessions.remove(&oldest);
+        }
+        // Drop the keys taken from the map.
+        if order.len() > 2 * self.capacity {
+            order.retain(|key| sessions.contains_key(key));
+        }
+        true
+    }
+
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let inner = self.lock();
+        let (value, created) = inner.sessions.get(key)?;
+        (created.elapsed() < self.ttl).then(|| value.clone())
+    }
+
+    fn take(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let mut inner = self.lock();
+        let (value, created) = inner.sessions.remove(key)?;
+        (created.elapsed() < self.ttl).then_some(value)
+    }
+
+    fn can_cache(&self) -> bool {
+        self.capacity > 0
+    }
+}
+
+impl fmt::Debug for ServerSessionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerSessionCache")
+            .field("capacity", &self.capacity)
+            .field("ttl", &self.ttl)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+type TicketerFn = dyn Fn() -> Result<Arc<dyn ProducesTickets>, rustls::Error> + Send + Sync;
+// The current and the previous keys.
+type Keys = (Arc<dyn ProducesTickets>, Option<Arc<dyn ProducesTickets>>);
+
+/// The stateless TLS session tickets, with the keys rotated by the runtime
+/// timer.
+///
+/// The tickets are encrypted by the current key, and the ones encrypted by the
+/// previous key could still be decrypted, so a ticket lives for one to two
+/// rotation intervals. Set it as `rustls::ServerConfig::ticketer`, and spawn
+/// [`run`](TicketRotator::run) on the runtime.
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use compio_tls::TicketRotator;
+///
+/// # fn main() -> std::io::Result<()> {
+/// # let mut config: rustls::ServerConfig = unimplemented!();
+/// let rotator = TicketRotator::new(Duration::from_secs(3600), || {
+///     rustls::crypto::ring::Ticketer::new()
+/// })?;
+/// config.ticketer = rotator.clone();
+/// compio_runtime::spawn(rotator.run()).detach();
+/// # Ok(())
+/// # }
+/// ```
+pub struct TicketRotator {
+    interval: Duration,
+    ticketer: Box<TicketerFn>,
+    keys: RwLock<Keys>,
+}
+
+impl TicketRotator {
+    /// Create a rotator, with the keys created by `ticketer` every
+    /// `interval`.
+    pub fn new<F>(interval: Duration, ticketer: F) -> io::Result<Arc<Self>>
+    where
+        F: Fn() -> Result<Arc<dyn ProducesTickets>, rustls::Error> + Send + Sync + 'static,
+    {
+        let current = ticketer().map_err(io::Error::other)?;
+        Ok(Arc::new(Self {
+            interval,
+            ticketer: Box::new(ticketer),
+            keys: RwLock::new((current, None)),
+        }))
+    }
+
+    /// Rotate the keys now. The tickets encrypted by the previous key are no
+    /// longer decrypted.
+    pub fn rotate(&self) -> io::Result<()> {
+        let next = (self.ticketer)().map_err(io::Error::other)?;
+        let mut keys = self.keys.write().unwrap_or_else(PoisonError::into_inner);
+        let current = std::mem::replace(&mut keys.0, next);
+        keys.1 = Some(current);
+        Ok(())
+    }
+
+    /// Rotate the keys every interval until the rotator is dropped. A failed
+    /// rotation is retried in the next interval, and the current key is kept.
+    pub async fn run(self: Arc<Self>) {
+        let interval = self.interval;
+        let this = Arc::downgrade(&self);
+        drop(self);
+        loop {
+            compio_runtime::time::sleep(interval).await;
+            let Some(this) = Weak::upgrade(&this) else {
+                break;
+            };
+            this.rotate().ok();
+        }
+    }
+}
+
+impl ProducesTickets for TicketRotator {
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn lifetime(&self) -> u32 {
+        self.interval
+            .as_secs()
+            .saturating_mul(2)
+            .min(u32::MAX as u64) as u32
+    }
+
+    fn encrypt(&self, plain: &[u8]) -> Option<Vec<u8>> {
+        self.keys
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .0
+            .encrypt(plain)
+    }
+
+    fn decrypt(&self, cipher: &[u8]) -> Option<Vec<u8>> {
+        let keys = self.keys.read().unwrap_or_else(PoisonError::into_inner);
+        keys.0
+            .decrypt(cipher)
+            .or_else(|| keys.1.as_ref()?.decrypt(cipher))
+    }
+}
+
+impl fmt::Debug for TicketRotator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TicketRotator")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A client-side store of the TLS sessions to resume, shared by the
+/// connectors.
+///
+/// It keeps the sessions of `capacity` servers at most, and could be cleared,
+/// e.g. when the trusted roots change. Set it with
+/// `rustls::client::Resumption::store` as
+/// `rustls::ClientConfig::resumption`.
+pub struct ClientSessionCache {
+    capacity: usize,
+    inner: RwLock<Arc<ClientSessionMemoryCache>>,
+}
+
+impl ClientSessionCache {
+    /// Create a store of the sessions of `capacity` servers.
+    pub fn new(capacity: usize) -> Arc<Self> {
+        Arc::new(Self {
+            capacity,
+            inner: RwLock::new(Arc::new(ClientSessionMemoryCache::new(capacity))),
+        })
+    }
+
+    /// Remove all the sessions, so that the next connections do full
+    /// handshakes.
+    pub fn clear(&self) {
+        *self.inner.write().unwrap_or_else(PoisonError::into_inner) =
+            Arc::new(ClientSessionMemoryCache::new(self.capacity));
+    }
+
+    fn store(&self) -> Arc<ClientSessionMemoryCache> {
+        self.inner.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+}
+
+impl ClientSessionStore for ClientSessionCache {
+    fn set_kx_hint(&self, server_name: ServerName<'static>, group: NamedGroup) {
+        self.store().set_kx_hint(server_name, group)
+    }
+
+    fn kx_hint(&self, server_name: &ServerName<'_>) -> Option<NamedGroup> {
+        self.store().kx_hint(server_name)
+    }
+
+    fn set_tls12_session(&self, server_name: ServerName<'static>, value: Tls12ClientSessionValue) {
+        self.store().set_tls12_session(server_name, value)
+    }
+
+    fn tls12_session(&self, server_name: &ServerName<'_>) -> Option<Tls12ClientSessionValue> {
+        self.store().tls12_session(server_name)
+    }
+
+    fn remove_tls12_session(&self, server_name: &ServerName<'static>) {
+        self.store().remove_tls12_session(server_name)
+    }
+
+    fn insert_tls13_ticket(
+        &self,
+        server_name: ServerName<'static>,
+        value: Tls13ClientSessionValue,
+    ) {
+        self.store().insert_tls13_ticket(server_name, value)
+    }
+
+    fn take_tls13_ticket(
+        &self,
+        server_name: &ServerName<'static>,
+    ) -> Option<Tls13ClientSessionValue> {
+        self.store().take_tls13_ticket(server_name)
+    }
+}
+
+impl fmt::Debug for ClientSessionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientSessionCache")
+            .field("capacity", &self.capacity)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,4 +1,4 @@
-use std::{io, mem::MaybeUninit};
+use std::{borrow::Cow, io, mem::MaybeUninit};
 
 use compio_buf::{BufResult, IoBuf, IoBufMut};
 use compio_io::{compat::SyncStream, AsyncRead, AsyncWrite};
@@ -87,6 +87,52 @@ impl<S> TlsStream<S> {
     pub(crate) fn new_rustls_server(s: SyncStream<S>, conn: rustls::ServerConnection) -> Self {
         Self(TlsStreamInner::Rustls(rtls::TlsStream::new_server(s, conn)))
     }
+
+    #[cfg(feature = "rustls")]
+    pub(crate) fn is_early_data_accepted(&self) -> bool {
+        match &self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsStreamInner::NativeTls(_) => false,
+            TlsStreamInner::Rustls(s) => s.is_early_data_accepted(),
+        }
+    }
+
+    pub(crate) fn take_early_data(&mut self) -> io::Result<Vec<u8>> {
+        match &mut self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsStreamInner::NativeTls(_) => Ok(vec![]),
+            #[cfg(feature = "rustls")]
+            TlsStreamInner::Rustls(s) => s.take_early_data(),
+        }
+    }
+
+    /// Returns the DER certificates sent by the peer, starting with its own
+    /// certificate, or an empty chain if it didn't send one.
+    ///
+    /// Only the certificate of the peer is returned with [`native_tls`].
+    pub fn peer_certificates(&self) -> io::Result<Vec<Vec<u8>>> {
+        match &self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsStreamInner::NativeTls(s) => {
+                let cert = s.peer_certificate().map_err(io::Error::other)?;
+                cert.map(|cert| cert.to_der().map_err(io::Error::other))
+                    .into_iter()
+                    .collect()
+            }
+            #[cfg(feature = "rustls")]
+            TlsStreamInner::Rustls(s) => Ok(s.peer_certificates()),
+        }
+    }
+
+    /// Returns the protocol negotiated with ALPN, if any.
+    pub fn negotiated_alpn(&self) -> Option<Cow<'_, [u8]>> {
+        match &self.0 {
+            #[cfg(feature = "native-tls")]
+            TlsStreamInner::NativeTls(s) => s.negotiated_alpn().ok().flatten().map(Cow::Owned),
+            #[cfg(feature = "rustls")]
+            TlsStreamInner::Rustls(s) => s.negotiated_alpn().map(Cow::Borrowed),
+        }
+    }
 }
 
 #[cfg(feature = "native-tls")]
@@ -57,12 +57,51 @@ impl TlsConnection {
             Self::Server(c) => c.wants_write(),
         }
     }
+
+    pub fn alpn_protocol(&self) -> Option<&[u8]> {
+        match self {
+            Self::Client(c) => c.alpn_protocol(),
+            Self::Server(c) => c.alpn_protocol(),
+        }
+    }
+
+    pub fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        let certs = match self {
+            Self::Client(c) => c.peer_certificates(),
+            Self::Server(c) => c.peer_certificates(),
+        };
+        certs
+            .unwrap_or_default()
+            .iter()
+            .map(|cert| cert.to_vec())
+            .collect()
+    }
+
+    pub fn is_early_data_accepted(&self) -> bool {
+        match self {
+            Self::Client(c) => c.is_early_data_accepted(),
+            Self::Server(_) => false,
+        }
+    }
+
+    pub fn take_early_data(&mut self) -> io::Result<Vec<u8>> {
+        let mut data = vec![];
+        if let Self::Server(c) = self {
+            if let Some(mut early_data) = c.early_data() {
+                io::Read::read_to_end(&mut early_data, &mut data)?;
+            }
+        }
+        Ok(data)
+    }
 }
 
 #[derive(Debug)]
 pub struct TlsStream<S> {
     inner: S,
     conn: TlsConnection,
+    // The early data received after it is taken, which is read before the
+    // data after the handshake.
+    late_early_data: Option<Vec<u8>>,
 }
 
 impl<S> TlsStream<S> {
@@ -70,6 +109,7 @@ impl<S> TlsStream<S> {
         Self {
             inner,
             conn: TlsConnection::Client(conn),
+            late_early_data: None,
         }
     }
 
@@ -77,25 +117,59 @@ impl<S> TlsStream<S> {
         Self {
             inner,
             conn: TlsConnection::Server(conn),
+            late_early_data: None,
         }
     }
 
     pub fn get_mut(&mut self) -> &mut S {
         &mut self.inner
     }
+
+    pub fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.conn.alpn_protocol()
+    }
+
+    pub fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        self.conn.peer_certificates()
+    }
+
+    pub fn is_early_data_accepted(&self) -> bool {
+        self.conn.is_early_data_accepted()
+    }
+
+    pub fn take_early_data(&mut self) -> io::Result<Vec<u8>> {
+        self.late_early_data = Some(vec![]);
+        self.conn.take_early_data()
+    }
 }
 
 impl<S: io::Read> TlsStream<S> {
-    fn read_impl<T>(&mut self, mut f: impl FnMut(Reader) -> io::Result<T>) -> io::Result<T> {
+    fn read_impl<T>(
+        &mut self,
+        mut f: impl FnMut(&mut dyn io::Read) -> io::Result<T>,
+    ) -> io::Result<T> {
         loop {
             while self.conn.wants_read() {
                 self.conn.read_tls(&mut self.inner)?;
-                self.conn
-                    .process_new_packets()
-                    .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+                self.conn.process_new_packets().map_err(io::Error::other)?;
+                if let Some(late) = &mut self.late_early_data {
+                    late.extend(self.conn.take_early_data()?);
+                }
+            }
+
+            if let Some(late) = self
+                .late_early_data
+                .as_mut()
+                .filter(|late| !late.is_empty())
+            {
+                let mut rest = late.as_slice();
+                let res = f(&mut rest);
+                let len = late.len() - rest.len();
+                late.drain(..len);
+                return res;
             }
 
-            match f(self.conn.reader()) {
+            match f(&mut self.conn.reader()) {
                 Ok(len) => {
                     return Ok(len);
                 }
@@ -108,12 +182,12 @@ impl<S: io::Read> TlsStream<S> {
 
 impl<S: io::Read> io::Read for TlsStream<S> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.read_impl(|mut reader| reader.read(buf))
+        self.read_impl(|reader| reader.read(buf))
     }
 
     #[cfg(feature = "read_buf")]
     fn read_buf(&mut self, mut buf: io::BorrowedCursor<'_>) -> io::Result<()> {
-        self.read_impl(|mut reader| reader.read_buf(buf.reborrow()))
+        self.read_impl(|reader| reader.read_buf(buf.reborrow()))
     }
 }
 
@@ -0,0 +1,82 @@
+#![cfg(feature = "native-tls")]
+
+use std::io;
+
+use compio_io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use compio_net::{TcpListener, TcpStream};
+use compio_tls::{native_tls, AlpnDispatcher, TlsAcceptor, TlsConnector, TlsStream};
+
+const CERT: &[u8] = include_bytes!("certs/cert.pem");
+const KEY: &[u8] = include_bytes!("certs/key.pem");
+
+async fn reply(mut stream: TlsStream<TcpStream>, reply: &'static str) -> io::Result<()> {
+    stream.write_all(reply).await.0?;
+    stream.flush().await
+}
+
+// Connect to a server dispatching one connection, and read its reply.
+async fn request(
+    dispatcher: AlpnDispatcher<TcpStream, ()>,
+    protocols: &[&str],
+) -> (io::Result<()>, Vec<u8>) {
+    let identity = native_tls::Identity::from_pkcs8(CERT, KEY).unwrap();
+    let acceptor = TlsAcceptor::from(
+        native_tls::TlsAcceptor::builder(identity)
+            .accept_alpn(&["h2", "http/1.1"])
+            .build()
+            .unwrap(),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = compio_runtime::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let stream = acceptor.accept(stream).await.unwrap();
+        dispatcher.dispatch(stream).await
+    });
+
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::builder()
+            .add_root_certificate(native_tls::Certificate::from_pem(CERT).unwrap())
+            .request_alpns(protocols)
+            .build()
+            .unwrap(),
+    );
+    let stream = TcpStream::connect(&addr).await.unwrap();
+    let mut stream = connector.connect("localhost", stream).await.unwrap();
+    let res = server.await;
+    let (_, buf) = stream.read(Vec::with_capacity(16)).await.unwrap();
+    (res, buf)
+}
+
+fn dispatcher() -> AlpnDispatcher<TcpStream, ()> {
+    AlpnDispatcher::new()
+        .route("h2", |s| reply(s, "h2"))
+        .route("http/1.1", |s| reply(s, "http/1.1"))
+}
+
+#[compio_macros::test]
+async fn dispatch() {
+    let dispatcher = dispatcher();
+    assert_eq!(
+        dispatcher.protocols().collect::<Vec<_>>(),
+        [&b"h2"[..], b"http/1.1"]
+    );
+    let (res, buf) = request(dispatcher, &["h2", "http/1.1"]).await;
+    res.unwrap();
+    assert_eq!(buf, b"h2");
+
+    let (res, buf) = request(self::dispatcher(), &["http/1.1"]).await;
+    res.unwrap();
+    assert_eq!(buf, b"http/1.1");
+}
+
+#[compio_macros::test]
+async fn fallback() {
+    let dispatcher = dispatcher().fallback(|s| reply(s, "fallback"));
+    let (res, buf) = request(dispatcher, &[]).await;
+    res.unwrap();
+    assert_eq!(buf, b"fallback");
+
+    let (res, _) = request(self::dispatcher(), &[]).await;
+    assert_eq!(res.unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
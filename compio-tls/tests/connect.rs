@@ -1,3 +1,4 @@
+#[cfg(feature = "rustls")]
 use std::sync::Arc;
 
 use compio_io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
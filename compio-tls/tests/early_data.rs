@@ -0,0 +1,102 @@
+use compio_io::{AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use compio_net::{TcpListener, TcpStream};
+use compio_tls::{TlsAcceptor, TlsConnector};
+
+const CERT: &[u8] = include_bytes!("certs/cert.pem");
+const KEY: &[u8] = include_bytes!("certs/key.pem");
+
+#[cfg(feature = "native-tls")]
+#[compio_macros::test]
+async fn native_fallback() {
+    use compio_tls::native_tls;
+
+    let identity = native_tls::Identity::from_pkcs8(CERT, KEY).unwrap();
+    let acceptor = TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = compio_runtime::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (mut stream, early_data) = acceptor.accept_with_early_data(stream).await.unwrap();
+        assert!(early_data.is_empty());
+        let (_, buf) = stream.read_exact(vec![0; 5]).await.unwrap();
+        buf
+    });
+
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::builder()
+            .add_root_certificate(native_tls::Certificate::from_pem(CERT).unwrap())
+            .build()
+            .unwrap(),
+    );
+    let stream = TcpStream::connect(&addr).await.unwrap();
+    let data = b"hello";
+    let (mut stream, accepted) = connector
+        .connect_with_early_data("localhost", stream, data)
+        .await
+        .unwrap();
+    // The early data is never sent with native-tls.
+    assert_eq!(accepted, 0);
+    stream.write_all(data[accepted..].to_vec()).await.0.unwrap();
+    stream.flush().await.unwrap();
+    assert_eq!(server.await, b"hello");
+}
+
+#[cfg(feature = "rustls")]
+#[compio_macros::test]
+async fn rtls_resumed() {
+    use std::sync::Arc;
+
+    let certs = rustls_pemfile::certs(&mut &*CERT)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = rustls_pemfile::private_key(&mut &*KEY).unwrap().unwrap();
+    let mut config = rustls::ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs.clone(), key)
+        .unwrap();
+    config.max_early_data_size = 1024;
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add_parsable_certificates(certs);
+    let mut config = rustls::ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.enable_early_data = true;
+    let connector = TlsConnector::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = compio_runtime::spawn(async move {
+        let mut received = vec![];
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (mut stream, early_data) = acceptor.accept_with_early_data(stream).await.unwrap();
+            // The rest of the request completes the handshake.
+            let len = 11 - early_data.len();
+            let (_, buf) = stream.read_exact(vec![0; len]).await.unwrap();
+            stream.write_all("ok").await.0.unwrap();
+            stream.flush().await.unwrap();
+            received.push((early_data.into_inner(), buf));
+        }
+        received
+    });
+
+    // The first connection gets the ticket, and the second one resumes it.
+    for _ in 0..2 {
+        let stream = TcpStream::connect(&addr).await.unwrap();
+        let data = b"hello";
+        let (mut stream, accepted) = connector
+            .connect_with_early_data("localhost", stream, data)
+            .await
+            .unwrap();
+        stream.write_all(data[accepted..].to_vec()).await.0.unwrap();
+        stream.write_all(" world").await.0.unwrap();
+        stream.flush().await.unwrap();
+        let (_, buf) = stream.read_exact(vec![0; 2]).await.unwrap();
+        assert_eq!(buf, b"ok");
+    }
+    let received = server.await;
+    assert_eq!(received[0], (vec![], b"hello world".to_vec()));
+    assert_eq!(received[1], (b"hello".to_vec(), b" world".to_vec()));
+}
@@ -0,0 +1,71 @@
+#![cfg(all(feature = "native-tls", feature = "reload"))]
+
+use std::{io, path::Path, sync::Arc};
+
+use compio_net::{TcpListener, TcpStream};
+use compio_tls::{native_tls, CertReloader, TlsAcceptor, TlsConnector};
+
+const CERT: &[u8] = include_bytes!("certs/cert.pem");
+const KEY: &[u8] = include_bytes!("certs/key.pem");
+const CERT2: &[u8] = include_bytes!("certs/cert2.pem");
+const KEY2: &[u8] = include_bytes!("certs/key2.pem");
+
+fn load(cert: &[u8], key: &[u8]) -> io::Result<TlsAcceptor> {
+    let identity = native_tls::Identity::from_pkcs8(cert, key).map_err(io::Error::other)?;
+    let acceptor = native_tls::TlsAcceptor::new(identity).map_err(io::Error::other)?;
+    Ok(TlsAcceptor::from(acceptor))
+}
+
+fn write_pair(dir: &Path, cert: &[u8], key: &[u8]) {
+    std::fs::write(dir.join("cert.pem"), cert).unwrap();
+    std::fs::write(dir.join("key.pem"), key).unwrap();
+}
+
+// Whether a client trusting `root` connects to the server of `reloader`.
+async fn connects(reloader: &Arc<CertReloader>, root: &[u8]) -> bool {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = compio_runtime::spawn({
+        let reloader = reloader.clone();
+        async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            reloader.accept(stream).await.is_ok()
+        }
+    });
+
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::builder()
+            .add_root_certificate(native_tls::Certificate::from_pem(root).unwrap())
+            .build()
+            .unwrap(),
+    );
+    let stream = TcpStream::connect(&addr).await.unwrap();
+    let connected = connector.connect("localhost", stream).await.is_ok();
+    let accepted = server.await;
+    connected && accepted
+}
+
+#[compio_macros::test]
+async fn reload() {
+    let dir = tempfile::tempdir().unwrap();
+    write_pair(dir.path(), CERT, KEY);
+    let reloader = CertReloader::new(
+        dir.path().join("cert.pem"),
+        dir.path().join("key.pem"),
+        load,
+    )
+    .await
+    .unwrap();
+    assert!(connects(&reloader, CERT).await);
+    assert!(!reloader.reload().await.unwrap());
+
+    write_pair(dir.path(), CERT2, KEY2);
+    assert!(reloader.reload().await.unwrap());
+    assert!(connects(&reloader, CERT2).await);
+    assert!(!connects(&reloader, CERT).await);
+
+    // An invalid pair is rejected, and the previous one is kept.
+    std::fs::write(dir.path().join("key.pem"), &KEY2[..KEY2.len() / 2]).unwrap();
+    reloader.reload().await.unwrap_err();
+    assert!(connects(&reloader, CERT2).await);
+}
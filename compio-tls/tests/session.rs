@@ -0,0 +1,29 @@
+#[cfg(feature = "rustls")]
+#[test]
+fn server_session_cache() {
+    use std::time::Duration;
+
+    use compio_tls::{rustls::server::StoresServerSessions, ServerSessionCache};
+
+    let cache = ServerSessionCache::new(4, Duration::from_millis(100));
+    assert!(cache.put(b"a".to_vec(), b"1".to_vec()));
+    assert!(cache.put(b"b".to_vec(), b"2".to_vec()));
+    assert_eq!(cache.get(b"a"), Some(b"1".to_vec()));
+
+    // The expired sessions are not resumed, and evicted by the next put.
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(cache.get(b"a"), None);
+    assert_eq!(cache.len(), 2);
+    assert!(cache.put(b"c".to_vec(), b"3".to_vec()));
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.take(b"c"), Some(b"3".to_vec()));
+    assert!(cache.is_empty());
+
+    // The oldest sessions are evicted at the capacity.
+    for i in 0..10u8 {
+        assert!(cache.put(vec![i], vec![i]));
+    }
+    assert_eq!(cache.len(), 4);
+    assert_eq!(cache.get(&[5]), None);
+    assert_eq!(cache.get(&[6]), Some(vec![6]));
+}
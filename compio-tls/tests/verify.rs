@@ -0,0 +1,73 @@
+#![cfg(feature = "native-tls")]
+
+use std::io;
+
+use compio_io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use compio_net::{TcpListener, TcpStream};
+use compio_tls::{native_tls, TlsAcceptor, TlsConnector, TlsStream};
+
+const CERT: &[u8] = include_bytes!("certs/cert.pem");
+const KEY: &[u8] = include_bytes!("certs/key.pem");
+
+// Verify the client of a connection with `verify`, and connect to it.
+async fn verified(
+    verify: fn(Vec<Vec<u8>>) -> io::Result<()>,
+) -> (io::Result<TlsStream<TcpStream>>, TlsStream<TcpStream>) {
+    let identity = native_tls::Identity::from_pkcs8(CERT, KEY).unwrap();
+    let acceptor = TlsAcceptor::from(native_tls::TlsAcceptor::new(identity).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = compio_runtime::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        acceptor
+            .accept_verified(stream, |certs| async move {
+                // Yield to the runtime, like a request to a remote service.
+                compio_runtime::spawn(async {}).await;
+                verify(certs)
+            })
+            .await
+    });
+
+    let connector = TlsConnector::from(
+        native_tls::TlsConnector::builder()
+            .add_root_certificate(native_tls::Certificate::from_pem(CERT).unwrap())
+            .build()
+            .unwrap(),
+    );
+    let stream = TcpStream::connect(&addr).await.unwrap();
+    let stream = connector.connect("localhost", stream).await.unwrap();
+    (server.await, stream)
+}
+
+#[compio_macros::test]
+async fn accepted() {
+    let (server, mut client) = verified(|certs| {
+        // The client certificates are never requested by native-tls.
+        assert!(certs.is_empty());
+        Ok(())
+    })
+    .await;
+    let mut server = server.unwrap();
+
+    let der = native_tls::Certificate::from_pem(CERT)
+        .unwrap()
+        .to_der()
+        .unwrap();
+    assert_eq!(client.peer_certificates().unwrap(), [der]);
+
+    client.write_all("ping").await.0.unwrap();
+    client.flush().await.unwrap();
+    let (_, buf) = server.read_exact(vec![0; 4]).await.unwrap();
+    assert_eq!(buf, b"ping");
+}
+
+#[compio_macros::test]
+async fn rejected() {
+    let (server, mut client) =
+        verified(|_| Err(io::Error::new(io::ErrorKind::PermissionDenied, "revoked"))).await;
+    assert_eq!(server.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+
+    // The stream is closed by the server.
+    let res = client.read(Vec::with_capacity(8)).await.0;
+    assert!(matches!(res, Ok(0) | Err(_)));
+}
@@ -0,0 +1,524 @@
+//! Drive the multi socket interface of libcurl with compio, so the transfers
+//! written against libcurl run on the compio event loop without a side thread.
+//!
+//! libcurl tells which sockets to watch with `CURLMOPT_SOCKETFUNCTION`, and
+//! when to time out with `CURLMOPT_TIMERFUNCTION`. The adapter waits for them
+//! with [`PollFd`] and the compio timer, and reports the events back with
+//! `curl_multi_socket_action`. c-ares could be driven in the same way with
+//! `ares_set_socket_callback` and `ares_process_fd`.
+//!
+//! libcurl is loaded when the example starts, so the development files are not
+//! needed. Without arguments, the example serves a few slow pages locally and
+//! fetches them concurrently. Otherwise it fetches the URLs in the arguments.
+//!
+//! ```text
+//! cargo run --example curl --features macros,time -- http://example.com/
+//! ```
+//!
+//! [`PollFd`]: compio::net::PollFd
+
+#[cfg(unix)]
+mod curl {
+    use std::{
+        cell::RefCell,
+        collections::HashMap,
+        ffi::{c_char, c_int, c_long, c_void, CStr, CString},
+        io,
+        os::fd::{AsRawFd, RawFd},
+        rc::Rc,
+        sync::OnceLock,
+        time::{Duration, Instant},
+    };
+
+    use compio::net::PollFd;
+    use futures_util::{
+        future::{select_all, LocalBoxFuture},
+        FutureExt,
+    };
+
+    const CURL_GLOBAL_DEFAULT: c_long = 3;
+    const CURLOPT_WRITEDATA: c_int = 10001;
+    const CURLOPT_URL: c_int = 10002;
+    const CURLOPT_WRITEFUNCTION: c_int = 20011;
+    const CURLINFO_RESPONSE_CODE: c_int = 0x200002;
+    const CURLMOPT_SOCKETFUNCTION: c_int = 20001;
+    const CURLMOPT_SOCKETDATA: c_int = 10002;
+    const CURLMOPT_TIMERFUNCTION: c_int = 20004;
+    const CURLMOPT_TIMERDATA: c_int = 10005;
+    const CURLMSG_DONE: c_int = 1;
+
+    const CURL_POLL_IN: c_int = 1;
+    const CURL_POLL_OUT: c_int = 2;
+    const CURL_POLL_REMOVE: c_int = 4;
+    const CURL_CSELECT_IN: c_int = 1;
+    const CURL_CSELECT_OUT: c_int = 2;
+    const CURL_CSELECT_ERR: c_int = 4;
+    const CURL_SOCKET_TIMEOUT: RawFd = -1;
+
+    type SocketCallback =
+        extern "C" fn(*mut c_void, RawFd, c_int, *mut c_void, *mut c_void) -> c_int;
+    type TimerCallback = extern "C" fn(*mut c_void, c_long, *mut c_void) -> c_int;
+    type WriteCallback = extern "C" fn(*const c_char, usize, usize, *mut c_void) -> usize;
+
+    #[repr(C)]
+    union CurlMsgData {
+        #[allow(dead_code)]
+        whatever: *mut c_void,
+        result: c_int,
+    }
+
+    #[repr(C)]
+    struct CurlMsg {
+        msg: c_int,
+        easy: *mut c_void,
+        data: CurlMsgData,
+    }
+
+    /// The functions of libcurl used by the adapter.
+    struct Lib {
+        easy_init: unsafe extern "C" fn() -> *mut c_void,
+        easy_setopt: unsafe extern "C" fn(*mut c_void, c_int, ...) -> c_int,
+        easy_getinfo: unsafe extern "C" fn(*mut c_void, c_int, ...) -> c_int,
+        easy_cleanup: unsafe extern "C" fn(*mut c_void),
+        easy_strerror: unsafe extern "C" fn(c_int) -> *const c_char,
+        multi_init: unsafe extern "C" fn() -> *mut c_void,
+        multi_setopt: unsafe extern "C" fn(*mut c_void, c_int, ...) -> c_int,
+        multi_add_handle: unsafe extern "C" fn(*mut c_void, *mut c_void) -> c_int,
+        multi_remove_handle: unsafe extern "C" fn(*mut c_void, *mut c_void) -> c_int,
+        multi_socket_action: unsafe extern "C" fn(*mut c_void, RawFd, c_int, *mut c_int) -> c_int,
+        multi_info_read: unsafe extern "C" fn(*mut c_void, *mut c_int) -> *mut CurlMsg,
+        multi_cleanup: unsafe extern "C" fn(*mut c_void) -> c_int,
+    }
+
+    fn dl_error() -> String {
+        let err = unsafe { libc::dlerror() };
+        if err.is_null() {
+            "unknown error".into()
+        } else {
+            unsafe { CStr::from_ptr(err) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    impl Lib {
+        /// Load libcurl once. It is never unloaded.
+        fn get() -> io::Result<&'static Self> {
+            static LIB: OnceLock<Result<Lib, String>> = OnceLock::new();
+            LIB.get_or_init(|| unsafe { Self::open() })
+                .as_ref()
+                .map_err(|e| io::Error::other(format!("cannot load libcurl: {e}")))
+        }
+
+        #[allow(clippy::missing_transmute_annotations)]
+        unsafe fn open() -> Result<Self, String> {
+            let handle = [
+                "libcurl.so.4",
+                "libcurl.so",
+                "libcurl.4.dylib",
+                "libcurl.dylib",
+            ]
+            .into_iter()
+            .map(|name| {
+                let name = CString::new(name).unwrap();
+                libc::dlopen(name.as_ptr(), libc::RTLD_NOW)
+            })
+            .find(|handle| !handle.is_null())
+            .ok_or_else(dl_error)?;
+
+            macro_rules! load {
+                ($name:literal) => {{
+                    let sym = libc::dlsym(handle, concat!($name, "\0").as_ptr().cast());
+                    if sym.is_null() {
+                        return Err(dl_error());
+                    }
+                    std::mem::transmute::<*mut c_void, _>(sym)
+                }};
+            }
+
+            let global_init: unsafe extern "C" fn(c_long) -> c_int = load!("curl_global_init");
+            if global_init(CURL_GLOBAL_DEFAULT) != 0 {
+                return Err("curl_global_init failed".into());
+            }
+            Ok(Self {
+                easy_init: load!("curl_easy_init"),
+                easy_setopt: load!("curl_easy_setopt"),
+                easy_getinfo: load!("curl_easy_getinfo"),
+                easy_cleanup: load!("curl_easy_cleanup"),
+                easy_strerror: load!("curl_easy_strerror"),
+                multi_init: load!("curl_multi_init"),
+                multi_setopt: load!("curl_multi_setopt"),
+                multi_add_handle: load!("curl_multi_add_handle"),
+                multi_remove_handle: load!("curl_multi_remove_handle"),
+                multi_socket_action: load!("curl_multi_socket_action"),
+                multi_info_read: load!("curl_multi_info_read"),
+                multi_cleanup: load!("curl_multi_cleanup"),
+            })
+        }
+
+        fn strerror(&self, code: c_int) -> String {
+            unsafe { CStr::from_ptr((self.easy_strerror)(code)) }
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+
+    /// A socket owned by libcurl.
+    struct Socket(RawFd);
+
+    impl AsRawFd for Socket {
+        fn as_raw_fd(&self) -> RawFd {
+            self.0
+        }
+    }
+
+    /// The state updated by the callbacks of libcurl.
+    #[derive(Default)]
+    struct State {
+        // The sockets to watch, with the `CURL_POLL_*` flags.
+        sockets: HashMap<RawFd, (Rc<PollFd<Socket>>, c_int)>,
+        // When to call `curl_multi_socket_action` with `CURL_SOCKET_TIMEOUT`.
+        deadline: Option<Instant>,
+        // The error to report after the callback returns.
+        error: Option<io::Error>,
+    }
+
+    extern "C" fn on_socket(
+        _easy: *mut c_void,
+        fd: RawFd,
+        what: c_int,
+        userp: *mut c_void,
+        _socketp: *mut c_void,
+    ) -> c_int {
+        let mut state = unsafe { &*(userp as *const RefCell<State>) }.borrow_mut();
+        if what == CURL_POLL_REMOVE {
+            state.sockets.remove(&fd);
+        } else if let Some((_, interest)) = state.sockets.get_mut(&fd) {
+            *interest = what;
+        } else {
+            match PollFd::new(Socket(fd)) {
+                Ok(poll_fd) => {
+                    state.sockets.insert(fd, (Rc::new(poll_fd), what));
+                }
+                Err(e) => {
+                    state.error = Some(e);
+                    return -1;
+                }
+            }
+        }
+        0
+    }
+
+    extern "C" fn on_timer(_multi: *mut c_void, timeout_ms: c_long, userp: *mut c_void) -> c_int {
+        let mut state = unsafe { &*(userp as *const RefCell<State>) }.borrow_mut();
+        state.deadline = u64::try_from(timeout_ms)
+            .ok()
+            .map(|ms| Instant::now() + Duration::from_millis(ms));
+        0
+    }
+
+    extern "C" fn on_write(
+        ptr: *const c_char,
+        size: usize,
+        nmemb: usize,
+        userdata: *mut c_void,
+    ) -> usize {
+        let body = unsafe { &mut *(userdata as *mut Vec<u8>) };
+        let len = size * nmemb;
+        body.extend_from_slice(unsafe { std::slice::from_raw_parts(ptr.cast(), len) });
+        len
+    }
+
+    /// A finished transfer.
+    pub struct Response {
+        pub status: c_long,
+        pub body: Vec<u8>,
+    }
+
+    /// An easy handle added to the multi handle.
+    struct Transfer<'a> {
+        multi: &'a Multi,
+        easy: *mut c_void,
+        // Boxed to keep the address passed to libcurl.
+        #[allow(clippy::box_collection)]
+        body: Box<Vec<u8>>,
+        result: Option<c_int>,
+    }
+
+    impl Drop for Transfer<'_> {
+        fn drop(&mut self) {
+            let lib = self.multi.lib;
+            unsafe {
+                (lib.multi_remove_handle)(self.multi.handle, self.easy);
+                (lib.easy_cleanup)(self.easy);
+            }
+        }
+    }
+
+    /// A libcurl multi handle driven by the current compio runtime.
+    pub struct Multi {
+        lib: &'static Lib,
+        handle: *mut c_void,
+        // Boxed to be passed to the callbacks.
+        state: Box<RefCell<State>>,
+    }
+
+    impl Multi {
+        pub fn new() -> io::Result<Self> {
+            let lib = Lib::get()?;
+            let handle = unsafe { (lib.multi_init)() };
+            if handle.is_null() {
+                return Err(io::Error::other("curl_multi_init failed"));
+            }
+            let this = Self {
+                lib,
+                handle,
+                state: Box::default(),
+            };
+            let userp = &*this.state as *const RefCell<State> as *mut c_void;
+            unsafe {
+                (lib.multi_setopt)(handle, CURLMOPT_SOCKETFUNCTION, on_socket as SocketCallback);
+                (lib.multi_setopt)(handle, CURLMOPT_SOCKETDATA, userp);
+                (lib.multi_setopt)(handle, CURLMOPT_TIMERFUNCTION, on_timer as TimerCallback);
+                (lib.multi_setopt)(handle, CURLMOPT_TIMERDATA, userp);
+            }
+            Ok(this)
+        }
+
+        fn add(&self, url: &str) -> io::Result<Transfer<'_>> {
+            let url = CString::new(url)?;
+            let easy = unsafe { (self.lib.easy_init)() };
+            if easy.is_null() {
+                return Err(io::Error::other("curl_easy_init failed"));
+            }
+            let mut transfer = Transfer {
+                multi: self,
+                easy,
+                body: Box::default(),
+                result: None,
+            };
+            let body = &mut *transfer.body as *mut Vec<u8> as *mut c_void;
+            let lib = self.lib;
+            let code = unsafe {
+                // libcurl copies the URL.
+                (lib.easy_setopt)(easy, CURLOPT_URL, url.as_ptr());
+                (lib.easy_setopt)(easy, CURLOPT_WRITEFUNCTION, on_write as WriteCallback);
+                (lib.easy_setopt)(easy, CURLOPT_WRITEDATA, body);
+                (lib.multi_add_handle)(self.handle, easy)
+            };
+            self.check(code)?;
+            Ok(transfer)
+        }
+
+        fn check(&self, code: c_int) -> io::Result<()> {
+            match self.state.borrow_mut().error.take() {
+                Some(e) => Err(e),
+                None if code != 0 => Err(io::Error::other(format!("curl multi error {code}"))),
+                None => Ok(()),
+            }
+        }
+
+        /// Wait for a socket to be ready or the timer to expire, and return
+        /// the arguments of `curl_multi_socket_action`.
+        async fn wait(&self) -> (RawFd, c_int) {
+            let (sockets, deadline) = {
+                let state = self.state.borrow();
+                let sockets = state
+                    .sockets
+                    .iter()
+                    .map(|(&fd, (poll_fd, what))| (fd, poll_fd.clone(), *what))
+                    .collect::<Vec<_>>();
+                (sockets, state.deadline)
+            };
+            let mut waits: Vec<LocalBoxFuture<(RawFd, c_int)>> = vec![];
+            for (fd, poll_fd, what) in sockets {
+                if what & CURL_POLL_IN != 0 {
+                    let poll_fd = poll_fd.clone();
+                    waits.push(
+                        async move {
+                            match poll_fd.readable().await {
+                                Ok(()) => (fd, CURL_CSELECT_IN),
+                                Err(_) => (fd, CURL_CSELECT_ERR),
+                            }
+                        }
+                        .boxed_local(),
+                    );
+                }
+                if what & CURL_POLL_OUT != 0 {
+                    waits.push(
+                        async move {
+                            match poll_fd.writable().await {
+                                Ok(()) => (fd, CURL_CSELECT_OUT),
+                                Err(_) => (fd, CURL_CSELECT_ERR),
+                            }
+                        }
+                        .boxed_local(),
+                    );
+                }
+            }
+            if let Some(deadline) = deadline {
+                waits.push(
+                    async move {
+                        compio::time::sleep_until(deadline).await;
+                        (CURL_SOCKET_TIMEOUT, 0)
+                    }
+                    .boxed_local(),
+                );
+            }
+            if waits.is_empty() {
+                return (CURL_SOCKET_TIMEOUT, 0);
+            }
+            // The other waits are cancelled when dropped.
+            select_all(waits).await.0
+        }
+
+        /// Fetch the URLs concurrently, and return the responses in order.
+        pub async fn fetch(&self, urls: &[&str]) -> io::Result<Vec<Result<Response, String>>> {
+            let mut transfers = urls
+                .iter()
+                .map(|url| self.add(url))
+                .collect::<io::Result<Vec<_>>>()?;
+            while transfers.iter().any(|t| t.result.is_none()) {
+                let (fd, mask) = self.wait().await;
+                if fd == CURL_SOCKET_TIMEOUT {
+                    // The timer is single shot.
+                    self.state.borrow_mut().deadline = None;
+                }
+                let mut running = 0;
+                let code =
+                    unsafe { (self.lib.multi_socket_action)(self.handle, fd, mask, &mut running) };
+                self.check(code)?;
+                loop {
+                    let mut left = 0;
+                    let msg = unsafe { (self.lib.multi_info_read)(self.handle, &mut left) };
+                    let Some(msg) = (unsafe { msg.as_ref() }) else {
+                        break;
+                    };
+                    if msg.msg != CURLMSG_DONE {
+                        continue;
+                    }
+                    if let Some(t) = transfers.iter_mut().find(|t| t.easy == msg.easy) {
+                        t.result = Some(unsafe { msg.data.result });
+                    }
+                }
+            }
+            Ok(transfers
+                .iter_mut()
+                .map(|t| match t.result {
+                    Some(0) => {
+                        let mut status: c_long = 0;
+                        unsafe {
+                            (self.lib.easy_getinfo)(
+                                t.easy,
+                                CURLINFO_RESPONSE_CODE,
+                                &mut status as *mut c_long,
+                            )
+                        };
+                        Ok(Response {
+                            status,
+                            body: std::mem::take(&mut *t.body),
+                        })
+                    }
+                    Some(code) => Err(self.lib.strerror(code)),
+                    None => unreachable!("all transfers are done"),
+                })
+                .collect())
+        }
+    }
+
+    impl Drop for Multi {
+        fn drop(&mut self) {
+            unsafe { (self.lib.multi_cleanup)(self.handle) };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod server {
+    use std::{io, net::SocketAddr, time::Duration};
+
+    use compio::{
+        io::{AsyncRead, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+        runtime::spawn,
+        BufResult,
+    };
+
+    async fn handle(mut stream: TcpStream) -> io::Result<()> {
+        let mut request = vec![];
+        while !request.ends_with(b"\r\n\r\n") {
+            let BufResult(res, buf) = stream.read(Vec::with_capacity(1024)).await;
+            if res? == 0 {
+                return Ok(());
+            }
+            request.extend_from_slice(&buf);
+        }
+        let path = request
+            .split(|&b| b == b' ')
+            .nth(1)
+            .map(|path| String::from_utf8_lossy(path).into_owned())
+            .unwrap_or_default();
+        // Slow pages show the transfers are concurrent.
+        compio::time::sleep(Duration::from_millis(200)).await;
+        let body = format!("Hello from {path}!");
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response).await.0?;
+        Ok(())
+    }
+
+    /// Serve the slow pages.
+    pub async fn serve() -> io::Result<SocketAddr> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                spawn(handle(stream)).detach();
+            }
+        })
+        .detach();
+        Ok(addr)
+    }
+}
+
+#[cfg(unix)]
+#[compio::main]
+async fn main() {
+    let mut urls = std::env::args().skip(1).collect::<Vec<_>>();
+    if urls.is_empty() {
+        let addr = server::serve().await.unwrap();
+        urls = ["a", "b", "c"]
+            .map(|page| format!("http://{addr}/{page}"))
+            .into();
+    }
+    let urls = urls.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let multi = match curl::Multi::new() {
+        Ok(multi) => multi,
+        Err(e) => {
+            eprintln!("{e}");
+            return;
+        }
+    };
+    let start = std::time::Instant::now();
+    let responses = multi.fetch(&urls).await.unwrap();
+    for (url, response) in urls.iter().zip(responses) {
+        match response {
+            Ok(response) => println!(
+                "{url}: {} with {} bytes, {:?}",
+                response.status,
+                response.body.len(),
+                String::from_utf8_lossy(&response.body[..response.body.len().min(32)])
+            ),
+            Err(e) => println!("{url}: {e}"),
+        }
+    }
+    println!("Fetched {} URLs in {:?}", urls.len(), start.elapsed());
+}
+
+#[cfg(not(unix))]
+fn main() {
+    println!("This example requires libcurl on unix.");
+}
@@ -0,0 +1,225 @@
+//! Building blocks of a DNS server: a UDP socket and a TCP listener sharing
+//! the same handler, with the length-prefixed framing of DNS over TCP, and the
+//! truncation of UDP responses which makes the clients retry over TCP.
+//!
+//! The example serves A records of `127.0.0.x`, and the name `big.example`
+//! has too many records to fit in a UDP response.
+
+use std::{io, net::SocketAddr, rc::Rc};
+
+use compio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, UdpSocket},
+    runtime::spawn,
+    BufResult,
+};
+
+/// The maximum size of a DNS message over UDP without EDNS.
+const UDP_PAYLOAD_SIZE: usize = 512;
+
+const FLAG_QR: u16 = 0x8000;
+const FLAG_TC: u16 = 0x0200;
+
+type Handler = Rc<dyn Fn(&[u8]) -> Option<Vec<u8>>>;
+
+fn u16_at(msg: &[u8], pos: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(msg.get(pos..pos + 2)?.try_into().ok()?))
+}
+
+/// Returns the offset after the encoded name starting at `pos`.
+fn skip_name(msg: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *msg.get(pos)? as usize;
+        if len == 0 {
+            return Some(pos + 1);
+        } else if len & 0xC0 == 0xC0 {
+            return Some(pos + 2);
+        }
+        pos += len + 1;
+    }
+}
+
+/// Returns the offset where the question section ends.
+fn question_end(msg: &[u8]) -> Option<usize> {
+    let mut pos = 12;
+    for _ in 0..u16_at(msg, 4)? {
+        pos = skip_name(msg, pos)? + 4;
+    }
+    (pos <= msg.len()).then_some(pos)
+}
+
+/// Truncate a response to the header and the question section, and set the
+/// TC flag, so that the client retries over TCP.
+fn truncate(mut msg: Vec<u8>) -> Vec<u8> {
+    let end = question_end(&msg).unwrap_or(12).min(msg.len());
+    msg.truncate(end);
+    if msg.len() >= 12 {
+        let flags = u16_at(&msg, 2).unwrap() | FLAG_TC;
+        msg[2..4].copy_from_slice(&flags.to_be_bytes());
+        // Clear ANCOUNT, NSCOUNT and ARCOUNT.
+        msg[6..12].fill(0);
+    }
+    msg
+}
+
+/// Serve the queries on a UDP socket.
+async fn serve_udp(socket: UdpSocket, handler: Handler) -> io::Result<()> {
+    let mut buffer = Vec::with_capacity(UDP_PAYLOAD_SIZE);
+    loop {
+        buffer.clear();
+        let BufResult(res, buf) = socket.recv_from(buffer).await;
+        buffer = buf;
+        let (_, peer) = res?;
+        if let Some(mut response) = handler(&buffer) {
+            if response.len() > UDP_PAYLOAD_SIZE {
+                response = truncate(response);
+            }
+            socket.send_to(response, peer).await.0?;
+        }
+    }
+}
+
+/// Read a length-prefixed message.
+async fn read_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let BufResult(res, len) = stream.read_exact([0u8; 2]).await;
+    res?;
+    let len = u16::from_be_bytes(len) as usize;
+    let BufResult(res, msg) = stream.read_exact(Vec::with_capacity(len)).await;
+    res?;
+    Ok(msg)
+}
+
+/// Write a length-prefixed message.
+async fn write_frame(stream: &mut TcpStream, msg: &[u8]) -> io::Result<()> {
+    let len = u16::try_from(msg.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "message too long"))?;
+    let mut frame = Vec::with_capacity(msg.len() + 2);
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(msg);
+    stream.write_all(frame).await.0?;
+    Ok(())
+}
+
+/// Serve the queries on a TCP listener, one task per connection.
+async fn serve_tcp(listener: TcpListener, handler: Handler) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let handler = handler.clone();
+        spawn(async move {
+            while let Ok(query) = read_frame(&mut stream).await {
+                let Some(response) = handler(&query) else {
+                    break;
+                };
+                if write_frame(&mut stream, &response).await.is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+    }
+}
+
+/// Bind a UDP socket and a TCP listener on the same address, and serve the
+/// queries with `handler` on both of them.
+async fn serve(
+    addr: SocketAddr,
+    handler: impl Fn(&[u8]) -> Option<Vec<u8>> + 'static,
+) -> io::Result<SocketAddr> {
+    let udp = UdpSocket::bind(addr).await?;
+    let addr = udp.local_addr()?;
+    let tcp = TcpListener::bind(addr).await?;
+    let handler: Handler = Rc::new(handler);
+    spawn(serve_udp(udp, handler.clone())).detach();
+    spawn(serve_tcp(tcp, handler)).detach();
+    Ok(addr)
+}
+
+fn encode_name(name: &str, out: &mut Vec<u8>) {
+    for label in name.trim_end_matches('.').split('.') {
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+}
+
+fn decode_name(msg: &[u8], pos: usize) -> Option<String> {
+    let mut labels = vec![];
+    let mut pos = pos;
+    loop {
+        let len = *msg.get(pos)? as usize;
+        if len == 0 {
+            break;
+        }
+        labels.push(std::str::from_utf8(msg.get(pos + 1..pos + 1 + len)?).ok()?);
+        pos += len + 1;
+    }
+    Some(labels.join("."))
+}
+
+/// A toy handler answering A queries.
+fn handle(query: &[u8]) -> Option<Vec<u8>> {
+    let end = question_end(query)?;
+    if u16_at(query, 4)? != 1 {
+        return None;
+    }
+    let name = decode_name(query, 12)?;
+    let count = if name == "big.example" { 64 } else { 1 };
+
+    let mut response = Vec::with_capacity(end + count * 16);
+    response.extend_from_slice(&query[..2]);
+    let flags = FLAG_QR | (u16_at(query, 2)? & 0x0100) | 0x0080;
+    response.extend_from_slice(&flags.to_be_bytes());
+    response.extend_from_slice(&1u16.to_be_bytes());
+    response.extend_from_slice(&(count as u16).to_be_bytes());
+    response.extend_from_slice(&[0; 4]);
+    response.extend_from_slice(&query[12..end]);
+    for i in 0..count {
+        // Pointer to the name in the question, type A, class IN, TTL 60.
+        response.extend_from_slice(&[0xC0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+        response.extend_from_slice(&[127, 0, 0, (i + 1) as u8]);
+    }
+    Some(response)
+}
+
+fn build_query(id: u16, name: &str) -> Vec<u8> {
+    let mut query = Vec::with_capacity(32);
+    query.extend_from_slice(&id.to_be_bytes());
+    // RD, one question.
+    query.extend_from_slice(&[0x01, 0x00, 0, 1, 0, 0, 0, 0, 0, 0]);
+    encode_name(name, &mut query);
+    query.extend_from_slice(&[0, 1, 0, 1]);
+    query
+}
+
+/// Query over UDP, and retry over TCP if the response is truncated.
+async fn resolve(server: SocketAddr, name: &str) -> io::Result<(bool, Vec<u8>)> {
+    let query = build_query(0x1234, name);
+    let socket = UdpSocket::bind("127.0.0.1:0").await?;
+    socket.connect(server).await?;
+    let BufResult(res, query) = socket.send(query).await;
+    res?;
+    let BufResult(res, response) = socket.recv(Vec::with_capacity(UDP_PAYLOAD_SIZE)).await;
+    let len = res?;
+    if len >= 4 && u16_at(&response, 2).unwrap() & FLAG_TC == 0 {
+        return Ok((false, response));
+    }
+    let mut stream = TcpStream::connect(server).await?;
+    write_frame(&mut stream, &query).await?;
+    let response = read_frame(&mut stream).await?;
+    Ok((true, response))
+}
+
+#[compio::main]
+async fn main() {
+    let addr = serve("127.0.0.1:0".parse().unwrap(), handle).await.unwrap();
+    println!("Serving DNS on {addr}");
+
+    for name in ["small.example", "big.example"] {
+        let (tcp, response) = resolve(addr, name).await.unwrap();
+        let answers = u16_at(&response, 6).unwrap();
+        println!(
+            "{name}: {answers} answers over {}",
+            if tcp { "TCP" } else { "UDP" }
+        );
+    }
+}
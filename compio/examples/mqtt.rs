@@ -0,0 +1,563 @@
+//! A minimal MQTT 3.1.1 client, supporting QoS 0 and 1 with keepalive pings.
+//!
+//! The client is generic over the read and write halves of the transport, so
+//! that it could run over any stream which could be split, such as TCP or
+//! unix sockets. With the `native-tls` feature, it could connect with TLS
+//! too, driving the TLS session over the halves of the TCP stream.
+//!
+//! ```text
+//! cargo run --example mqtt --features macros,time -- 127.0.0.1:1883
+//! cargo run --example mqtt --features macros,time,native-tls -- --tls broker.example.com:8883
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+use compio::{
+    buf::{IntoInner, IoBuf},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    runtime::{spawn, Task},
+    time::interval,
+    BufResult,
+};
+use futures_channel::{mpsc, oneshot};
+use futures_util::{lock::Mutex, StreamExt};
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const PINGRESP: u8 = 0xD0;
+const DISCONNECT: u8 = 0xE0;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// A control packet with its fixed header byte and the body.
+struct Packet {
+    header: u8,
+    body: Vec<u8>,
+}
+
+impl Packet {
+    fn new(header: u8) -> Self {
+        Self {
+            header,
+            body: vec![],
+        }
+    }
+
+    fn u16(mut self, v: u16) -> Self {
+        self.body.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    fn u8(mut self, v: u8) -> Self {
+        self.body.push(v);
+        self
+    }
+
+    fn string(self, v: &str) -> Self {
+        self.u16(v.len() as u16).bytes(v.as_bytes())
+    }
+
+    fn bytes(mut self, v: &[u8]) -> Self {
+        self.body.extend_from_slice(v);
+        self
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.body.len() + 5);
+        out.push(self.header);
+        let mut len = self.body.len();
+        loop {
+            let mut byte = (len % 128) as u8;
+            len /= 128;
+            if len > 0 {
+                byte |= 0x80;
+            }
+            out.push(byte);
+            if len == 0 {
+                break;
+            }
+        }
+        out.extend_from_slice(&self.body);
+        out
+    }
+
+    /// Try to decode a packet from `buf`, returning the packet and the
+    /// consumed bytes.
+    fn decode(buf: &[u8]) -> io::Result<Option<(Self, usize)>> {
+        let mut len = 0usize;
+        let mut i = 1;
+        loop {
+            let Some(&byte) = buf.get(i) else {
+                return Ok(None);
+            };
+            len += ((byte & 0x7F) as usize) << (7 * (i - 1));
+            i += 1;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            if i > 4 {
+                return Err(invalid("malformed remaining length"));
+            }
+        }
+        if buf.len() < i + len {
+            return Ok(None);
+        }
+        let packet = Self {
+            header: buf[0],
+            body: buf[i..i + len].to_vec(),
+        };
+        Ok(Some((packet, i + len)))
+    }
+}
+
+/// A message received from a subscription.
+#[derive(Debug)]
+struct Message {
+    topic: String,
+    payload: Vec<u8>,
+}
+
+struct PacketReader<R> {
+    reader: R,
+    buffer: Vec<u8>,
+}
+
+impl<R: AsyncRead> PacketReader<R> {
+    async fn next(&mut self) -> io::Result<Packet> {
+        loop {
+            if let Some((packet, used)) = Packet::decode(&self.buffer)? {
+                self.buffer.drain(..used);
+                return Ok(packet);
+            }
+            let mut buffer = std::mem::take(&mut self.buffer);
+            let len = buffer.len();
+            buffer.reserve(4096);
+            let BufResult(res, slice) = self.reader.read(buffer.slice(len..)).await;
+            self.buffer = slice.into_inner();
+            if res? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+        }
+    }
+}
+
+struct Inner<W> {
+    writer: Mutex<W>,
+    next_id: Cell<u16>,
+    pending: RefCell<HashMap<u16, oneshot::Sender<Packet>>>,
+}
+
+impl<W: AsyncWrite> Inner<W> {
+    async fn send(&self, packet: Packet) -> io::Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer.write_all(packet.encode()).await.0?;
+        writer.flush().await
+    }
+
+    /// Send a packet with a new packet identifier, and wait for the
+    /// acknowledgement.
+    async fn request(&self, packet: impl FnOnce(u16) -> Packet) -> io::Result<Packet> {
+        let id = self.next_id.get();
+        self.next_id.set(id.checked_add(1).unwrap_or(1));
+        let (tx, rx) = oneshot::channel();
+        self.pending.borrow_mut().insert(id, tx);
+        self.send(packet(id)).await?;
+        rx.await
+            .map_err(|_| io::Error::from(io::ErrorKind::ConnectionAborted))
+    }
+
+    fn acknowledge(&self, packet: Packet) {
+        if packet.body.len() >= 2 {
+            let id = u16::from_be_bytes([packet.body[0], packet.body[1]]);
+            if let Some(tx) = self.pending.borrow_mut().remove(&id) {
+                tx.send(packet).ok();
+            }
+        }
+    }
+}
+
+struct Client<W> {
+    inner: Rc<Inner<W>>,
+    _reader: Task<()>,
+    _ping: Option<Task<()>>,
+}
+
+impl<W: AsyncWrite + 'static> Client<W> {
+    /// Connect to the broker, returning the client and the stream of the
+    /// received messages. A zero `keep_alive` disables the pings.
+    async fn connect<R: AsyncRead + 'static>(
+        reader: R,
+        writer: W,
+        client_id: &str,
+        keep_alive: Duration,
+    ) -> io::Result<(Self, mpsc::UnboundedReceiver<Message>)> {
+        let inner = Rc::new(Inner {
+            writer: Mutex::new(writer),
+            next_id: Cell::new(1),
+            pending: RefCell::default(),
+        });
+        let mut reader = PacketReader {
+            reader,
+            buffer: Vec::with_capacity(4096),
+        };
+
+        let connect = Packet::new(CONNECT)
+            .string("MQTT")
+            .u8(4)
+            // Clean session.
+            .u8(0x02)
+            .u16(keep_alive.as_secs().min(u16::MAX as u64) as u16)
+            .string(client_id);
+        inner.send(connect).await?;
+        let connack = reader.next().await?;
+        if connack.header != CONNACK || connack.body.len() != 2 {
+            return Err(invalid("expected CONNACK"));
+        }
+        if connack.body[1] != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("connection refused with code {}", connack.body[1]),
+            ));
+        }
+
+        let (tx, rx) = mpsc::unbounded();
+        let reader = spawn(Self::read_loop(reader, Rc::downgrade(&inner), tx));
+        let ping = (!keep_alive.is_zero())
+            .then(|| spawn(Self::ping_loop(Rc::downgrade(&inner), keep_alive)));
+        Ok((
+            Self {
+                inner,
+                _reader: reader,
+                _ping: ping,
+            },
+            rx,
+        ))
+    }
+
+    async fn read_loop<R: AsyncRead>(
+        mut reader: PacketReader<R>,
+        inner: Weak<Inner<W>>,
+        tx: mpsc::UnboundedSender<Message>,
+    ) {
+        while let Ok(packet) = reader.next().await {
+            let Some(inner) = inner.upgrade() else { break };
+            match packet.header & 0xF0 {
+                PUBLISH => {
+                    let qos = (packet.header >> 1) & 0x03;
+                    let body = &packet.body;
+                    if body.len() < 2 {
+                        break;
+                    }
+                    let topic_len = u16::from_be_bytes([body[0], body[1]]) as usize;
+                    let mut pos = 2 + topic_len;
+                    let id_len = if qos > 0 { 2 } else { 0 };
+                    if body.len() < pos + id_len {
+                        break;
+                    }
+                    let topic = String::from_utf8_lossy(&body[2..pos]).into_owned();
+                    if qos > 0 {
+                        let ack = Packet::new(PUBACK).bytes(&body[pos..pos + 2]);
+                        pos += 2;
+                        if inner.send(ack).await.is_err() {
+                            break;
+                        }
+                    }
+                    let payload = body[pos..].to_vec();
+                    tx.unbounded_send(Message { topic, payload }).ok();
+                }
+                PUBACK | SUBACK => inner.acknowledge(packet),
+                PINGRESP => {}
+                _ => break,
+            }
+        }
+        // Wake up all requests waiting for acknowledgements.
+        if let Some(inner) = inner.upgrade() {
+            inner.pending.borrow_mut().clear();
+        }
+    }
+
+    async fn ping_loop(inner: Weak<Inner<W>>, keep_alive: Duration) {
+        let mut interval = interval(keep_alive);
+        // The first tick completes immediately.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(inner) = inner.upgrade() else { break };
+            if inner.send(Packet::new(PINGREQ)).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    async fn subscribe(&self, topic: &str, qos: u8) -> io::Result<u8> {
+        let ack = self
+            .inner
+            .request(|id| Packet::new(SUBSCRIBE).u16(id).string(topic).u8(qos))
+            .await?;
+        match ack.body.get(2) {
+            Some(0x80) | None => Err(io::Error::other("subscription rejected")),
+            Some(&granted) => Ok(granted),
+        }
+    }
+
+    async fn publish(&self, topic: &str, qos: u8, payload: &[u8]) -> io::Result<()> {
+        let header = PUBLISH | (qos << 1);
+        match qos {
+            0 => {
+                let packet = Packet::new(header).string(topic).bytes(payload);
+                self.inner.send(packet).await
+            }
+            1 => {
+                self.inner
+                    .request(|id| Packet::new(header).string(topic).u16(id).bytes(payload))
+                    .await?;
+                Ok(())
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "QoS 2 is not supported",
+            )),
+        }
+    }
+
+    async fn disconnect(self) -> io::Result<()> {
+        self.inner.send(Packet::new(DISCONNECT)).await
+    }
+}
+
+#[cfg(feature = "native-tls")]
+mod tls {
+    //! A TLS session shared by the read and write halves of a stream.
+    //!
+    //! The TLS stream reads and writes the ciphertext from buffers, which are
+    //! filled and flushed by the halves, so a read waiting for the broker
+    //! doesn't block the writes.
+
+    use std::{
+        cell::RefCell,
+        io::{self, Read, Write},
+        mem::MaybeUninit,
+        rc::Rc,
+    };
+
+    use compio::{
+        buf::{IoBuf, IoBufMut},
+        io::{AsyncRead, AsyncWrite, AsyncWriteExt},
+        native_tls::{HandshakeError, TlsConnector},
+        BufResult,
+    };
+
+    #[derive(Default)]
+    struct Buffers {
+        incoming: Vec<u8>,
+        outgoing: Vec<u8>,
+    }
+
+    struct Shim(Rc<RefCell<Buffers>>);
+
+    impl Read for Shim {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut buffers = self.0.borrow_mut();
+            if buffers.incoming.is_empty() {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            let len = buf.len().min(buffers.incoming.len());
+            buf[..len].copy_from_slice(&buffers.incoming[..len]);
+            buffers.incoming.drain(..len);
+            Ok(len)
+        }
+    }
+
+    impl Write for Shim {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().outgoing.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Session {
+        stream: RefCell<compio::native_tls::TlsStream<Shim>>,
+        buffers: Rc<RefCell<Buffers>>,
+    }
+
+    impl Session {
+        async fn fill(buffers: &RefCell<Buffers>, reader: &mut impl AsyncRead) -> io::Result<()> {
+            let BufResult(res, buffer) = reader.read(Vec::with_capacity(4096)).await;
+            if res? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            buffers.borrow_mut().incoming.extend_from_slice(&buffer);
+            Ok(())
+        }
+
+        async fn flush(buffers: &RefCell<Buffers>, writer: &mut impl AsyncWrite) -> io::Result<()> {
+            let outgoing = std::mem::take(&mut buffers.borrow_mut().outgoing);
+            if !outgoing.is_empty() {
+                writer.write_all(outgoing).await.0?;
+            }
+            writer.flush().await
+        }
+    }
+
+    /// The reading half of the TLS stream.
+    pub struct TlsReader<R> {
+        reader: R,
+        session: Rc<Session>,
+    }
+
+    /// The writing half of the TLS stream.
+    pub struct TlsWriter<W> {
+        writer: W,
+        session: Rc<Session>,
+    }
+
+    /// Do the client handshake with `domain` over the halves of a stream.
+    pub async fn connect<R: AsyncRead, W: AsyncWrite>(
+        domain: &str,
+        mut reader: R,
+        mut writer: W,
+    ) -> io::Result<(TlsReader<R>, TlsWriter<W>)> {
+        let buffers = Rc::new(RefCell::new(Buffers::default()));
+        let connector = TlsConnector::new().map_err(io::Error::other)?;
+        let mut res = connector.connect(domain, Shim(buffers.clone()));
+        let stream = loop {
+            match res {
+                Ok(stream) => break stream,
+                Err(HandshakeError::Failure(e)) => return Err(io::Error::other(e)),
+                Err(HandshakeError::WouldBlock(mid)) => {
+                    Session::flush(&buffers, &mut writer).await?;
+                    Session::fill(&buffers, &mut reader).await?;
+                    res = mid.handshake();
+                }
+            }
+        };
+        // The last flight of the handshake.
+        Session::flush(&buffers, &mut writer).await?;
+        let session = Rc::new(Session {
+            stream: RefCell::new(stream),
+            buffers,
+        });
+        Ok((
+            TlsReader {
+                reader,
+                session: session.clone(),
+            },
+            TlsWriter { writer, session },
+        ))
+    }
+
+    impl<R: AsyncRead> AsyncRead for TlsReader<R> {
+        async fn read<B: IoBufMut>(&mut self, mut buf: B) -> BufResult<usize, B> {
+            let slice: &mut [MaybeUninit<u8>] = buf.as_mut_slice();
+            let mut plain = vec![0; slice.len()];
+            loop {
+                // The records written while reading, e.g. the responses to
+                // the key updates, are sent with the next write.
+                let res = self.session.stream.borrow_mut().read(&mut plain);
+                match res {
+                    Ok(len) => {
+                        let slice: &mut [MaybeUninit<u8>] = buf.as_mut_slice();
+                        for (dst, src) in slice.iter_mut().zip(&plain[..len]) {
+                            dst.write(*src);
+                        }
+                        unsafe { buf.set_buf_init(len) };
+                        return BufResult(Ok(len), buf);
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        if let Err(e) = Session::fill(&self.session.buffers, &mut self.reader).await
+                        {
+                            return BufResult(Err(e), buf);
+                        }
+                    }
+                    Err(e) => return BufResult(Err(e), buf),
+                }
+            }
+        }
+    }
+
+    impl<W: AsyncWrite> AsyncWrite for TlsWriter<W> {
+        async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+            // The ciphertext is buffered, so the write never blocks.
+            let res = self.session.stream.borrow_mut().write(buf.as_slice());
+            BufResult(res, buf)
+        }
+
+        async fn flush(&mut self) -> io::Result<()> {
+            Session::flush(&self.session.buffers, &mut self.writer).await
+        }
+
+        async fn shutdown(&mut self) -> io::Result<()> {
+            self.session.stream.borrow_mut().shutdown()?;
+            self.flush().await?;
+            self.writer.shutdown().await
+        }
+    }
+}
+
+#[compio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1).peekable();
+    let tls = args.next_if(|arg| arg == "--tls").is_some();
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:1883".to_string());
+    let stream = TcpStream::connect(&addr).await.unwrap();
+    let (reader, writer) = stream.into_split();
+    if tls {
+        #[cfg(feature = "native-tls")]
+        {
+            let domain = addr
+                .rsplit_once(':')
+                .map_or(addr.as_str(), |(host, _)| host);
+            let (reader, writer) = tls::connect(domain, reader, writer).await.unwrap();
+            return run(reader, writer).await;
+        }
+        #[cfg(not(feature = "native-tls"))]
+        panic!("TLS needs the native-tls feature");
+    }
+    run(reader, writer).await
+}
+
+async fn run<R: AsyncRead + 'static, W: AsyncWrite + 'static>(reader: R, writer: W) {
+    let (client, mut messages) =
+        Client::connect(reader, writer, "compio-example", Duration::from_secs(30))
+            .await
+            .unwrap();
+
+    let granted = client.subscribe("compio/example", 1).await.unwrap();
+    println!("Subscribed with QoS {granted}");
+    client
+        .publish("compio/example", 0, b"Hello with QoS 0")
+        .await
+        .unwrap();
+    client
+        .publish("compio/example", 1, b"Hello with QoS 1")
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let message = messages.next().await.unwrap();
+        println!(
+            "{}: {}",
+            message.topic,
+            String::from_utf8_lossy(&message.payload)
+        );
+    }
+    client.disconnect().await.unwrap();
+}
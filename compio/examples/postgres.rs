@@ -0,0 +1,338 @@
+//! A minimal PostgreSQL client speaking the frontend/backend protocol 3.0.
+//!
+//! It supports the startup flow with trust or cleartext password
+//! authentication, the simple query protocol and unnamed prepared statements
+//! of the extended query protocol.
+//!
+//! ```text
+//! PGPASSWORD=secret cargo run --example postgres --features macros -- 127.0.0.1:5432 postgres postgres
+//! ```
+
+use std::io;
+
+use compio::{
+    buf::{IntoInner, IoBuf},
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    BufResult,
+};
+
+const PROTOCOL_VERSION: i32 = 196608;
+
+/// A backend message with its type byte and body.
+struct Message {
+    tag: u8,
+    body: Vec<u8>,
+}
+
+/// Builder of a frontend message, which fills the length after the body is
+/// written.
+struct MessageBuilder {
+    buffer: Vec<u8>,
+    start: usize,
+}
+
+impl MessageBuilder {
+    fn new(buffer: Vec<u8>, tag: Option<u8>) -> Self {
+        let mut buffer = buffer;
+        if let Some(tag) = tag {
+            buffer.push(tag);
+        }
+        let start = buffer.len();
+        buffer.extend_from_slice(&[0; 4]);
+        Self { buffer, start }
+    }
+
+    fn i16(mut self, v: i16) -> Self {
+        self.buffer.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    fn i32(mut self, v: i32) -> Self {
+        self.buffer.extend_from_slice(&v.to_be_bytes());
+        self
+    }
+
+    fn bytes(mut self, v: &[u8]) -> Self {
+        self.buffer.extend_from_slice(v);
+        self
+    }
+
+    fn cstr(mut self, v: &str) -> Self {
+        self.buffer.extend_from_slice(v.as_bytes());
+        self.buffer.push(0);
+        self
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let len = (self.buffer.len() - self.start) as i32;
+        self.buffer[self.start..self.start + 4].copy_from_slice(&len.to_be_bytes());
+        self.buffer
+    }
+}
+
+/// A cursor over the body of a backend message.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "message too short",
+            ));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn i16(&mut self) -> io::Result<i16> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn cstr(&mut self) -> io::Result<&'a str> {
+        let pos = self
+            .0
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unterminated string"))?;
+        let s = std::str::from_utf8(&self.0[..pos])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0 = &self.0[pos + 1..];
+        Ok(s)
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl Connection {
+    async fn connect(addr: &str, user: &str, database: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        let mut conn = Self {
+            stream,
+            buffer: Vec::with_capacity(4096),
+        };
+        let startup = MessageBuilder::new(conn.take_buffer(), None)
+            .i32(PROTOCOL_VERSION)
+            .cstr("user")
+            .cstr(user)
+            .cstr("database")
+            .cstr(database)
+            .bytes(&[0])
+            .finish();
+        conn.send(startup).await?;
+        loop {
+            let msg = conn.recv().await?;
+            match msg.tag {
+                b'R' => {
+                    let mut r = Reader(&msg.body);
+                    match r.i32()? {
+                        0 => {}
+                        3 => {
+                            let password = std::env::var("PGPASSWORD").unwrap_or_default();
+                            let msg = MessageBuilder::new(conn.take_buffer(), Some(b'p'))
+                                .cstr(&password)
+                                .finish();
+                            conn.send(msg).await?;
+                        }
+                        code => {
+                            return Err(io::Error::new(
+                                io::ErrorKind::Unsupported,
+                                format!("unsupported authentication method {code}"),
+                            ));
+                        }
+                    }
+                }
+                b'E' => return Err(server_error(&msg.body)),
+                b'Z' => break,
+                // ParameterStatus, BackendKeyData and notices.
+                _ => {}
+            }
+        }
+        Ok(conn)
+    }
+
+    fn take_buffer(&mut self) -> Vec<u8> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+        buffer
+    }
+
+    async fn send(&mut self, buffer: Vec<u8>) -> io::Result<()> {
+        let BufResult(res, buffer) = self.stream.write_all(buffer).await;
+        self.buffer = buffer;
+        res
+    }
+
+    async fn read_exact(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = self.take_buffer();
+        buffer.reserve(len);
+        let BufResult(res, slice) = self.stream.read_exact(buffer.slice(..len)).await;
+        let buffer = slice.into_inner();
+        match res {
+            Ok(()) => Ok(buffer),
+            Err(e) => {
+                self.buffer = buffer;
+                Err(e)
+            }
+        }
+    }
+
+    async fn recv(&mut self) -> io::Result<Message> {
+        let header = self.read_exact(5).await?;
+        let tag = header[0];
+        let len = i32::from_be_bytes(header[1..5].try_into().unwrap());
+        self.buffer = header;
+        let len = usize::try_from(len - 4)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid message length"))?;
+        let body = self.read_exact(len).await?;
+        Ok(Message { tag, body })
+    }
+
+    /// Collect the rows until `ReadyForQuery`, returning the column names and
+    /// the text values.
+    async fn collect_rows(&mut self) -> io::Result<(Vec<String>, Vec<Vec<Option<String>>>)> {
+        let mut columns = vec![];
+        let mut rows = vec![];
+        let mut error = None;
+        loop {
+            let msg = self.recv().await?;
+            let mut r = Reader(&msg.body);
+            match msg.tag {
+                b'T' => {
+                    let n = r.i16()?;
+                    columns.clear();
+                    for _ in 0..n {
+                        columns.push(r.cstr()?.to_string());
+                        // Table OID, column number, type OID, size, modifier and format.
+                        r.take(18)?;
+                    }
+                }
+                b'D' => {
+                    let n = r.i16()?;
+                    let mut row = Vec::with_capacity(n as usize);
+                    for _ in 0..n {
+                        let len = r.i32()?;
+                        row.push(if len < 0 {
+                            None
+                        } else {
+                            Some(String::from_utf8_lossy(r.take(len as usize)?).into_owned())
+                        });
+                    }
+                    rows.push(row);
+                }
+                b'E' => error = Some(server_error(&msg.body)),
+                b'Z' => break,
+                // CommandComplete, ParseComplete, BindComplete, NoData and notices.
+                _ => {}
+            }
+        }
+        match error {
+            Some(e) => Err(e),
+            None => Ok((columns, rows)),
+        }
+    }
+
+    /// Run a query with the simple query protocol.
+    async fn simple_query(
+        &mut self,
+        query: &str,
+    ) -> io::Result<(Vec<String>, Vec<Vec<Option<String>>>)> {
+        let msg = MessageBuilder::new(self.take_buffer(), Some(b'Q'))
+            .cstr(query)
+            .finish();
+        self.send(msg).await?;
+        self.collect_rows().await
+    }
+
+    /// Run a query with an unnamed prepared statement and text parameters.
+    async fn query(
+        &mut self,
+        query: &str,
+        params: &[&str],
+    ) -> io::Result<(Vec<String>, Vec<Vec<Option<String>>>)> {
+        let buffer = self.take_buffer();
+        let buffer = MessageBuilder::new(buffer, Some(b'P'))
+            .cstr("")
+            .cstr(query)
+            .i16(0)
+            .finish();
+        let mut bind = MessageBuilder::new(buffer, Some(b'B'))
+            .cstr("")
+            .cstr("")
+            .i16(0)
+            .i16(params.len() as i16);
+        for param in params {
+            bind = bind.i32(param.len() as i32).bytes(param.as_bytes());
+        }
+        let buffer = bind.i16(0).finish();
+        let buffer = MessageBuilder::new(buffer, Some(b'D'))
+            .bytes(b"P")
+            .cstr("")
+            .finish();
+        let buffer = MessageBuilder::new(buffer, Some(b'E'))
+            .cstr("")
+            .i32(0)
+            .finish();
+        let buffer = MessageBuilder::new(buffer, Some(b'S')).finish();
+        self.send(buffer).await?;
+        self.collect_rows().await
+    }
+
+    async fn terminate(mut self) -> io::Result<()> {
+        let msg = MessageBuilder::new(self.take_buffer(), Some(b'X')).finish();
+        self.send(msg).await?;
+        self.stream.close().await
+    }
+}
+
+fn server_error(body: &[u8]) -> io::Error {
+    let mut r = Reader(body);
+    let mut message = String::new();
+    while let Ok(field) = r.take(1) {
+        if field[0] == 0 {
+            break;
+        }
+        let Ok(value) = r.cstr() else { break };
+        if field[0] == b'M' {
+            message = value.to_string();
+        }
+    }
+    io::Error::other(message)
+}
+
+fn print_rows((columns, rows): (Vec<String>, Vec<Vec<Option<String>>>)) {
+    println!("{}", columns.join(" | "));
+    for row in rows {
+        let row = row
+            .into_iter()
+            .map(|v| v.unwrap_or_else(|| "NULL".to_string()))
+            .collect::<Vec<_>>();
+        println!("{}", row.join(" | "));
+    }
+}
+
+#[compio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let addr = args.next().unwrap_or_else(|| "127.0.0.1:5432".to_string());
+    let user = args.next().unwrap_or_else(|| "postgres".to_string());
+    let database = args.next().unwrap_or_else(|| user.clone());
+
+    let mut conn = Connection::connect(&addr, &user, &database).await.unwrap();
+    print_rows(conn.simple_query("SELECT version()").await.unwrap());
+    print_rows(
+        conn.query("SELECT $1::int + 1 AS answer", &["41"])
+            .await
+            .unwrap(),
+    );
+    conn.terminate().await.unwrap();
+}
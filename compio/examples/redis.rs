@@ -0,0 +1,312 @@
+//! A minimal Redis client speaking RESP3, with pipelining and a small
+//! connection pool.
+//!
+//! ```text
+//! cargo run --example redis --features macros,time -- 127.0.0.1:6379
+//! ```
+
+use std::{cell::RefCell, io, rc::Rc, time::Duration};
+
+use compio::{
+    buf::{IntoInner, IoBuf},
+    io::{AsyncRead, AsyncWriteExt},
+    net::TcpStream,
+    time::timeout,
+    BufResult,
+};
+use futures_util::future::join_all;
+
+/// A RESP3 value.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Simple(String),
+    Error(String),
+    Integer(i64),
+    Bulk(Vec<u8>),
+    Array(Vec<Value>),
+    Null,
+    Boolean(bool),
+    Double(f64),
+    BigNumber(String),
+    BulkError(String),
+    Verbatim(String, String),
+    Map(Vec<(Value, Value)>),
+    Set(Vec<Value>),
+    Push(Vec<Value>),
+}
+
+/// The largest bulk string accepted from the server, the same as the default
+/// `proto-max-bulk-len` of Redis.
+const MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+
+/// The largest number of elements accepted in an aggregate value.
+const MAX_AGGREGATE_LEN: usize = 1024 * 1024;
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// Encode a command as an array of bulk strings.
+fn encode(args: &[&[u8]], out: &mut Vec<u8>) {
+    out.extend_from_slice(format!("*{}\r\n", args.len()).as_bytes());
+    for arg in args {
+        out.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        out.extend_from_slice(arg);
+        out.extend_from_slice(b"\r\n");
+    }
+}
+
+/// Try to decode a value from `buf`. Returns the value and the consumed
+/// bytes, or `None` if `buf` doesn't contain a complete value.
+fn decode(buf: &[u8]) -> io::Result<Option<(Value, usize)>> {
+    fn line(buf: &[u8]) -> Option<(&[u8], usize)> {
+        buf.windows(2)
+            .position(|w| w == b"\r\n")
+            .map(|pos| (&buf[..pos], pos + 2))
+    }
+
+    fn text(bytes: &[u8]) -> io::Result<String> {
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid utf-8"))
+    }
+
+    fn number<T: std::str::FromStr>(bytes: &[u8]) -> io::Result<T> {
+        std::str::from_utf8(bytes)
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| invalid("invalid number"))
+    }
+
+    fn aggregate(buf: &[u8], start: usize, len: usize) -> io::Result<Option<(Vec<Value>, usize)>> {
+        // The length comes from the peer, so don't trust it for the allocation.
+        // Every value takes at least 3 bytes.
+        let mut values = Vec::with_capacity(len.min((buf.len() - start) / 3));
+        let mut pos = start;
+        for _ in 0..len {
+            match decode(&buf[pos..])? {
+                Some((value, used)) => {
+                    values.push(value);
+                    pos += used;
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(Some((values, pos)))
+    }
+
+    let Some(&tag) = buf.first() else {
+        return Ok(None);
+    };
+    let Some((header, header_len)) = line(&buf[1..]) else {
+        return Ok(None);
+    };
+    let header_len = header_len + 1;
+    let value = match tag {
+        b'+' => (Value::Simple(text(header)?), header_len),
+        b'-' => (Value::Error(text(header)?), header_len),
+        b':' => (Value::Integer(number(header)?), header_len),
+        b'_' => (Value::Null, header_len),
+        b'#' => (Value::Boolean(header == b"t"), header_len),
+        b',' => (Value::Double(number(header)?), header_len),
+        b'(' => (Value::BigNumber(text(header)?), header_len),
+        b'$' | b'!' | b'=' => {
+            let len: i64 = number(header)?;
+            if len < 0 {
+                return Ok(Some((Value::Null, header_len)));
+            }
+            let len = usize::try_from(len)
+                .ok()
+                .filter(|len| *len <= MAX_BULK_LEN)
+                .ok_or_else(|| invalid("bulk string too long"))?;
+            let end = header_len
+                .checked_add(len)
+                .ok_or_else(|| invalid("bulk string too long"))?;
+            if buf.len() < end + 2 {
+                return Ok(None);
+            }
+            if &buf[end..end + 2] != b"\r\n" {
+                return Err(invalid("missing CRLF after bulk string"));
+            }
+            let data = &buf[header_len..end];
+            let value = match tag {
+                b'$' => Value::Bulk(data.to_vec()),
+                b'!' => Value::BulkError(text(data)?),
+                _ => {
+                    let data = text(data)?;
+                    let (format, data) = data
+                        .split_once(':')
+                        .ok_or_else(|| invalid("invalid verbatim string"))?;
+                    Value::Verbatim(format.to_string(), data.to_string())
+                }
+            };
+            (value, end + 2)
+        }
+        b'*' | b'~' | b'>' | b'%' | b'|' => {
+            let len: i64 = number(header)?;
+            if len < 0 {
+                return Ok(Some((Value::Null, header_len)));
+            }
+            let len = usize::try_from(len)
+                .ok()
+                .filter(|len| *len <= MAX_AGGREGATE_LEN)
+                .ok_or_else(|| invalid("aggregate too long"))?;
+            let count = if matches!(tag, b'%' | b'|') {
+                len.checked_mul(2)
+                    .ok_or_else(|| invalid("aggregate too long"))?
+            } else {
+                len
+            };
+            let Some((mut values, used)) = aggregate(buf, header_len, count)? else {
+                return Ok(None);
+            };
+            if tag == b'|' {
+                // Attributes are out-of-band metadata of the following value.
+                return Ok(decode(&buf[used..])?.map(|(v, n)| (v, used + n)));
+            }
+            let value = match tag {
+                b'*' => Value::Array(values),
+                b'~' => Value::Set(values),
+                b'>' => Value::Push(values),
+                _ => {
+                    let mut pairs = Vec::with_capacity(len);
+                    let mut iter = values.drain(..);
+                    while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
+                        pairs.push((k, v));
+                    }
+                    Value::Map(pairs)
+                }
+            };
+            (value, used)
+        }
+        _ => return Err(invalid("unknown RESP type")),
+    };
+    Ok(Some(value))
+}
+
+struct Connection {
+    stream: TcpStream,
+    read_buf: Vec<u8>,
+    write_buf: Vec<u8>,
+}
+
+impl Connection {
+    async fn connect(addr: &str) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr).await?;
+        let mut conn = Self {
+            stream,
+            read_buf: Vec::with_capacity(4096),
+            write_buf: Vec::with_capacity(4096),
+        };
+        match conn.query(&[&[b"HELLO", b"3"]]).await?.pop() {
+            Some(Value::Map(_)) => Ok(conn),
+            Some(Value::Error(e)) => Err(io::Error::other(e)),
+            _ => Err(invalid("unexpected HELLO response")),
+        }
+    }
+
+    async fn fill(&mut self) -> io::Result<()> {
+        let mut buf = std::mem::take(&mut self.read_buf);
+        let len = buf.len();
+        buf.reserve(4096);
+        let BufResult(res, slice) = self.stream.read(buf.slice(len..)).await;
+        self.read_buf = slice.into_inner();
+        match res? {
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn next_value(&mut self) -> io::Result<Value> {
+        loop {
+            if let Some((value, used)) = decode(&self.read_buf)? {
+                self.read_buf.drain(..used);
+                return Ok(value);
+            }
+            self.fill().await?;
+        }
+    }
+
+    /// Send all commands at once, and then read the replies in order.
+    async fn query(&mut self, commands: &[&[&[u8]]]) -> io::Result<Vec<Value>> {
+        let mut buf = std::mem::take(&mut self.write_buf);
+        buf.clear();
+        for args in commands {
+            encode(args, &mut buf);
+        }
+        let BufResult(res, buf) = self.stream.write_all(buf).await;
+        self.write_buf = buf;
+        res?;
+        let mut replies = Vec::with_capacity(commands.len());
+        while replies.len() < commands.len() {
+            match self.next_value().await? {
+                // Out-of-band pushes are not replies.
+                Value::Push(_) => {}
+                value => replies.push(value),
+            }
+        }
+        Ok(replies)
+    }
+}
+
+/// A pool of idle connections shared by the tasks on the same thread.
+#[derive(Clone)]
+struct Pool {
+    addr: Rc<str>,
+    idle: Rc<RefCell<Vec<Connection>>>,
+    max_idle: usize,
+    timeout: Duration,
+}
+
+impl Pool {
+    fn new(addr: &str, max_idle: usize, timeout: Duration) -> Self {
+        Self {
+            addr: addr.into(),
+            idle: Rc::default(),
+            max_idle,
+            timeout,
+        }
+    }
+
+    /// Run a pipeline on a pooled connection. The connection is returned to
+    /// the pool only if the pipeline succeeds.
+    async fn query(&self, commands: &[&[&[u8]]]) -> io::Result<Vec<Value>> {
+        let conn = self.idle.borrow_mut().pop();
+        let mut conn = match conn {
+            Some(conn) => conn,
+            None => Connection::connect(&self.addr).await?,
+        };
+        let replies = timeout(self.timeout, conn.query(commands))
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+        let mut idle = self.idle.borrow_mut();
+        if idle.len() < self.max_idle {
+            idle.push(conn);
+        }
+        Ok(replies)
+    }
+}
+
+#[compio::main]
+async fn main() {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "127.0.0.1:6379".to_string());
+    let pool = Pool::new(&addr, 4, Duration::from_secs(5));
+
+    let tasks = (0..8).map(|i| {
+        let pool = pool.clone();
+        compio::runtime::spawn(async move {
+            let key = format!("compio:example:{i}");
+            let value = i.to_string();
+            pool.query(&[
+                &[b"SET", key.as_bytes(), value.as_bytes()],
+                &[b"INCR", key.as_bytes()],
+                &[b"GET", key.as_bytes()],
+                &[b"DEL", key.as_bytes()],
+            ])
+            .await
+        })
+    });
+    for replies in join_all(tasks).await {
+        println!("{:?}", replies.unwrap());
+    }
+}
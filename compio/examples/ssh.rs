@@ -0,0 +1,97 @@
+//! Runs a command over SSH with russh, on a TCP stream of compio.
+//!
+//! russh spawns its session on the tokio runtime, so it runs in another
+//! thread, and talks to the stream through [`bridge::duplex`].
+//!
+//! ```text
+//! cargo run --example ssh --features macros,tokio-compat -- 127.0.0.1:22 user password 'uname -a'
+//! ```
+
+use std::{io, sync::Arc};
+
+use compio::{compat::bridge, net::TcpStream, runtime::spawn};
+use russh::{client, keys::PublicKeyOrCertificate, ChannelMsg, Disconnect};
+
+struct Client;
+
+impl client::Handler for Client {
+    type Error = russh::Error;
+
+    // An example only, a real client should check the known hosts.
+    async fn check_server_key(&mut self, _: &PublicKeyOrCertificate) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+}
+
+fn other(e: russh::Error) -> io::Error {
+    io::Error::other(e.to_string())
+}
+
+/// Authenticates with the password, and returns the output and the exit
+/// status of the command.
+async fn exec(
+    duplex: bridge::Duplex,
+    user: String,
+    password: String,
+    command: String,
+) -> io::Result<(Vec<u8>, Option<u32>)> {
+    let config = Arc::new(client::Config::default());
+    let mut session = client::connect_stream(config, duplex, Client)
+        .await
+        .map_err(other)?;
+    if !session
+        .authenticate_password(user, password)
+        .await
+        .map_err(other)?
+        .success()
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "authentication failed",
+        ));
+    }
+
+    let mut channel = session.channel_open_session().await.map_err(other)?;
+    channel.exec(true, command).await.map_err(other)?;
+    let mut output = vec![];
+    let mut status = None;
+    while let Some(msg) = channel.wait().await {
+        match msg {
+            ChannelMsg::Data { data } => output.extend_from_slice(&data),
+            ChannelMsg::ExitStatus { exit_status } => status = Some(exit_status),
+            _ => {}
+        }
+    }
+    session
+        .disconnect(Disconnect::ByApplication, "", "en")
+        .await
+        .map_err(other)?;
+    Ok((output, status))
+}
+
+#[compio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut arg = |name| args.next().unwrap_or_else(|| panic!("missing {name}"));
+    let (addr, user, password, command) =
+        (arg("addr"), arg("user"), arg("password"), arg("command"));
+
+    let stream = TcpStream::connect(addr.as_str()).await.unwrap();
+    let (duplex, pump) = bridge::duplex(stream, 8192);
+    let pump = spawn(pump);
+
+    let (tx, rx) = futures_channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let res = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(exec(duplex, user, password, command));
+        tx.send(res).ok();
+    });
+
+    let (output, status) = rx.await.unwrap().unwrap();
+    print!("{}", String::from_utf8_lossy(&output));
+    println!("exit status: {status:?}");
+    pump.await.unwrap();
+}
@@ -0,0 +1,256 @@
+//! Resumable chunked file transfer with integrity checks.
+//!
+//! The sender sends a manifest with the checksums of all chunks. The receiver
+//! checks the chunks it already has, and asks for the missing ones only. The
+//! sender reads the chunks concurrently with `read_at`, and the receiver
+//! verifies each chunk before writing it with `write_at`.
+//!
+//! The example interrupts the first transfer in the middle, and resumes it
+//! with a second one.
+
+use std::{io, path::Path};
+
+use compio::{
+    buf::{IntoInner, IoBuf},
+    fs::{File, OpenOptions},
+    io::{AsyncReadAtExt, AsyncReadExt, AsyncWrite, AsyncWriteAtExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    BufResult,
+};
+use futures_util::{stream, StreamExt, TryStreamExt};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+const CONCURRENCY: usize = 4;
+
+/// FNV-1a, a simple non-cryptographic hash. Use a cryptographic one if the
+/// peer is not trusted.
+fn checksum(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf29ce484222325, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+struct Manifest {
+    size: u64,
+    chunk_size: usize,
+    checksums: Vec<u64>,
+}
+
+impl Manifest {
+    async fn from_file(file: &File, chunk_size: usize) -> io::Result<Self> {
+        let size = file.metadata().await?.len();
+        let count = size.div_ceil(chunk_size as u64) as usize;
+        let mut checksums = Vec::with_capacity(count);
+        let mut buffer = Vec::with_capacity(chunk_size);
+        for index in 0..count {
+            let (chunk, buf) = read_chunk(file, buffer, size, chunk_size, index).await?;
+            checksums.push(chunk);
+            buffer = buf;
+        }
+        Ok(Self {
+            size,
+            chunk_size,
+            checksums,
+        })
+    }
+
+    fn chunk_len(&self, index: usize) -> usize {
+        let offset = (index * self.chunk_size) as u64;
+        (self.size - offset).min(self.chunk_size as u64) as usize
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(16 + self.checksums.len() * 8);
+        out.extend_from_slice(&self.size.to_be_bytes());
+        out.extend_from_slice(&(self.chunk_size as u32).to_be_bytes());
+        out.extend_from_slice(&(self.checksums.len() as u32).to_be_bytes());
+        for checksum in &self.checksums {
+            out.extend_from_slice(&checksum.to_be_bytes());
+        }
+        out
+    }
+
+    async fn decode(stream: &mut TcpStream) -> io::Result<Self> {
+        let header = read_exact(stream, 16).await?;
+        let size = u64::from_be_bytes(header[..8].try_into().unwrap());
+        let chunk_size = u32::from_be_bytes(header[8..12].try_into().unwrap()) as usize;
+        let count = u32::from_be_bytes(header[12..].try_into().unwrap()) as usize;
+        if chunk_size == 0 || size.div_ceil(chunk_size as u64) != count as u64 {
+            return Err(invalid("invalid manifest"));
+        }
+        let checksums = read_exact(stream, count * 8)
+            .await?
+            .chunks_exact(8)
+            .map(|c| u64::from_be_bytes(c.try_into().unwrap()))
+            .collect();
+        Ok(Self {
+            size,
+            chunk_size,
+            checksums,
+        })
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+async fn read_exact(stream: &mut TcpStream, len: usize) -> io::Result<Vec<u8>> {
+    let BufResult(res, buf) = stream.read_exact(Vec::with_capacity(len)).await;
+    res?;
+    Ok(buf)
+}
+
+async fn read_u32(stream: &mut TcpStream) -> io::Result<u32> {
+    let BufResult(res, buf) = stream.read_exact([0u8; 4]).await;
+    res?;
+    Ok(u32::from_be_bytes(buf))
+}
+
+/// Read a chunk of the file, returning its checksum and the buffer.
+async fn read_chunk(
+    file: &File,
+    mut buffer: Vec<u8>,
+    size: u64,
+    chunk_size: usize,
+    index: usize,
+) -> io::Result<(u64, Vec<u8>)> {
+    let offset = (index * chunk_size) as u64;
+    let len = (size.saturating_sub(offset)).min(chunk_size as u64) as usize;
+    buffer.clear();
+    buffer.reserve(len);
+    let BufResult(res, slice) = file.read_exact_at(buffer.slice(..len), offset).await;
+    let buffer = slice.into_inner();
+    res?;
+    Ok((checksum(&buffer), buffer))
+}
+
+/// Send `file` to the peer. If `limit` is set, the connection is dropped after
+/// sending that many chunks, to simulate an interruption.
+async fn send(mut stream: TcpStream, file: &File, limit: Option<usize>) -> io::Result<()> {
+    let manifest = Manifest::from_file(file, CHUNK_SIZE).await?;
+    stream.write_all(manifest.encode()).await.0?;
+
+    let count = read_u32(&mut stream).await? as usize;
+    let missing = read_exact(&mut stream, count * 4).await?;
+    let missing = missing
+        .chunks_exact(4)
+        .map(|c| u32::from_be_bytes(c.try_into().unwrap()) as usize)
+        .take(limit.unwrap_or(usize::MAX));
+
+    let manifest = &manifest;
+    let mut chunks = stream::iter(missing)
+        .map(|index| async move {
+            if index >= manifest.checksums.len() {
+                return Err(invalid("invalid chunk index"));
+            }
+            let len = manifest.chunk_len(index);
+            let BufResult(res, buffer) = file
+                .read_exact_at(
+                    Vec::with_capacity(len),
+                    (index * manifest.chunk_size) as u64,
+                )
+                .await;
+            res.map(|()| (index, buffer))
+        })
+        .buffered(CONCURRENCY);
+    while let Some((index, buffer)) = chunks.try_next().await? {
+        let mut frame = Vec::with_capacity(buffer.len() + 8);
+        frame.extend_from_slice(&(index as u32).to_be_bytes());
+        frame.extend_from_slice(&(buffer.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&buffer);
+        stream.write_all(frame).await.0?;
+    }
+    stream.shutdown().await
+}
+
+/// Receive a file from the peer into `path`, returning the number of the
+/// chunks received and the total number of the chunks.
+async fn receive(mut stream: TcpStream, path: &Path) -> io::Result<(usize, usize)> {
+    let manifest = Manifest::decode(&mut stream).await?;
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .open(path)
+        .await?;
+    let existing = file.metadata().await?.len();
+
+    let mut missing = vec![];
+    let mut buffer = Vec::with_capacity(manifest.chunk_size);
+    for (index, &expected) in manifest.checksums.iter().enumerate() {
+        let end = (index * manifest.chunk_size + manifest.chunk_len(index)) as u64;
+        if end > existing {
+            missing.push(index as u32);
+            continue;
+        }
+        let (actual, buf) =
+            read_chunk(&file, buffer, manifest.size, manifest.chunk_size, index).await?;
+        buffer = buf;
+        if actual != expected {
+            missing.push(index as u32);
+        }
+    }
+
+    let mut request = Vec::with_capacity(4 + missing.len() * 4);
+    request.extend_from_slice(&(missing.len() as u32).to_be_bytes());
+    for index in &missing {
+        request.extend_from_slice(&index.to_be_bytes());
+    }
+    stream.write_all(request).await.0?;
+
+    let mut received = 0;
+    while received < missing.len() {
+        let index = match read_u32(&mut stream).await {
+            Ok(index) => index as usize,
+            // The sender is interrupted. The received chunks are kept.
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e),
+        };
+        let len = read_u32(&mut stream).await? as usize;
+        if index >= manifest.checksums.len() || len != manifest.chunk_len(index) {
+            return Err(invalid("unexpected chunk"));
+        }
+        let data = read_exact(&mut stream, len).await?;
+        if checksum(&data) != manifest.checksums[index] {
+            return Err(invalid("checksum mismatch"));
+        }
+        let offset = (index * manifest.chunk_size) as u64;
+        file.write_all_at(data, offset).await.0?;
+        received += 1;
+    }
+    file.sync_all().await?;
+    Ok((received, manifest.checksums.len()))
+}
+
+async fn transfer(source: &File, dest: &Path, limit: Option<usize>) -> (usize, usize) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, (rx, _)) =
+        futures_util::try_join!(TcpStream::connect(&addr), listener.accept()).unwrap();
+    let (sent, received) = futures_util::join!(send(tx, source, limit), receive(rx, dest));
+    sent.unwrap();
+    received.unwrap()
+}
+
+#[compio::main]
+async fn main() {
+    let dir = tempfile::tempdir().unwrap();
+    let source_path = dir.path().join("source");
+    let dest_path = dir.path().join("dest");
+
+    let content = (0..1_000_000u32)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    std::fs::write(&source_path, &content).unwrap();
+    let source = File::open(&source_path).await.unwrap();
+
+    let (received, total) = transfer(&source, &dest_path, Some(5)).await;
+    println!("Interrupted after {received} of {total} chunks");
+
+    let (received, total) = transfer(&source, &dest_path, None).await;
+    println!("Resumed with {received} of {total} chunks");
+
+    assert_eq!(std::fs::read(&dest_path).unwrap(), content);
+    println!("Transferred file verified");
+}
@@ -0,0 +1,250 @@
+//! Channels and streams between the compio runtime and the other ones.
+//!
+//! The channels are the ones of [`futures_channel::mpsc`], which could be
+//! awaited on any runtime, in any thread, and wake the tasks of the other
+//! one, so the components kept on another runtime could talk to the ones
+//! migrated to compio.
+//!
+//! ```
+//! use compio::compat::bridge;
+//! use futures_util::{SinkExt, StreamExt};
+//!
+//! let (mut tx, mut rx) = bridge::channel(16);
+//! let (mut back_tx, mut back_rx) = bridge::channel(16);
+//! let tokio = std::thread::spawn(move || {
+//!     tokio::runtime::Builder::new_current_thread()
+//!         .build()
+//!         .unwrap()
+//!         .block_on(async move {
+//!             while let Some(i) = rx.next().await {
+//!                 back_tx.send(i * 2).await.unwrap();
+//!             }
+//!         })
+//! });
+//! compio::runtime::Runtime::new()
+//!     .unwrap()
+//!     .block_on(async move {
+//!         tx.send(21).await.unwrap();
+//!         assert_eq!(back_rx.next().await, Some(42));
+//!     });
+//! tokio.join().unwrap();
+//! ```
+//!
+//! The libraries that need another runtime, e.g. an SSH client spawning its
+//! session tasks on tokio, could run over a compio stream with [`duplex`].
+
+use std::{
+    fmt,
+    future::{Future, poll_fn},
+    io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use compio_buf::BufResult;
+use compio_io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+#[doc(no_inline)]
+pub use futures_channel::mpsc::{Receiver, SendError, Sender, TryRecvError, TrySendError, channel};
+use futures_util::StreamExt;
+
+/// Connects a compio stream to a [`Duplex`], which implements the futures IO
+/// traits, and the tokio ones with the `tokio-compat` feature, and could be
+/// moved to another thread.
+///
+/// The returned future copies the data between them, reading `capacity`
+/// bytes at a time, and should be spawned on the compio runtime. It completes
+/// when both directions are closed, and returns the error of writing to the
+/// stream. The errors of reading are returned by [`Duplex`] instead.
+///
+/// ```
+/// use compio::{
+///     compat::bridge,
+///     io::{AsyncReadExt, AsyncWriteExt},
+///     net::{TcpListener, TcpStream},
+///     runtime::spawn,
+/// };
+/// use futures_util::{AsyncReadExt as _, AsyncWriteExt as _};
+///
+/// # compio::runtime::Runtime::new().unwrap().block_on(async {
+/// let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+/// let addr = listener.local_addr().unwrap();
+/// let (stream, (mut server, _)) =
+///     futures_util::try_join!(TcpStream::connect(addr), listener.accept()).unwrap();
+///
+/// let (mut duplex, pump) = bridge::duplex(stream, 1024);
+/// let pump = spawn(pump);
+/// let tokio = std::thread::spawn(move || {
+///     tokio::runtime::Builder::new_current_thread()
+///         .build()
+///         .unwrap()
+///         .block_on(async move {
+///             duplex.write_all(b"ping").await.unwrap();
+///             duplex.close().await.unwrap();
+///             let mut buf = vec![];
+///             duplex.read_to_end(&mut buf).await.unwrap();
+///             buf
+///         })
+/// });
+///
+/// let (_, buf) = server.read_to_end(vec![]).await.unwrap();
+/// assert_eq!(buf, b"ping");
+/// server.write_all(b"pong").await.unwrap();
+/// drop(server);
+/// pump.await.unwrap();
+/// assert_eq!(tokio.join().unwrap(), b"pong");
+/// # })
+/// ```
+pub fn duplex<S>(stream: S, capacity: usize) -> (Duplex, impl Future<Output = io::Result<()>>)
+where
+    S: 'static,
+    for<'a> &'a S: AsyncRead + AsyncWrite,
+{
+    let (mut incoming_tx, incoming_rx) = channel(1);
+    let (outgoing_tx, mut outgoing_rx) = channel::<Vec<u8>>(1);
+    let duplex = Duplex {
+        incoming: incoming_rx,
+        read_buffer: vec![],
+        read_pos: 0,
+        outgoing: Some(outgoing_tx),
+    };
+    let capacity = capacity.max(1);
+    let pump = async move {
+        let read = async {
+            loop {
+                let BufResult(res, buffer) = (&stream).read(Vec::with_capacity(capacity)).await;
+                if poll_fn(|cx| incoming_tx.poll_ready(cx)).await.is_err() {
+                    break;
+                }
+                let eof = matches!(res, Ok(0) | Err(_));
+                if incoming_tx.start_send(res.map(|_| buffer)).is_err() || eof {
+                    break;
+                }
+            }
+            incoming_tx.close_channel();
+            Ok(())
+        };
+        let write = async {
+            let mut stream = &stream;
+            while let Some(buffer) = outgoing_rx.next().await {
+                stream.write_all(buffer).await.0?;
+            }
+            stream.shutdown().await
+        };
+        futures_util::try_join!(read, write).map(|_| ())
+    };
+    (duplex, pump)
+}
+
+/// A stream connected to a compio stream by [`duplex`].
+///
+/// The writes are sent to the compio runtime and complete before the data is
+/// written to the stream, so flushing returns immediately. Closing or
+/// shutting it down shuts down the write half of the compio stream, after the
+/// sent data is written.
+pub struct Duplex {
+    incoming: Receiver<io::Result<Vec<u8>>>,
+    read_buffer: Vec<u8>,
+    read_pos: usize,
+    outgoing: Option<Sender<Vec<u8>>>,
+}
+
+impl Duplex {
+    fn poll_read_slice(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        while self.read_pos == self.read_buffer.len() {
+            match ready!(self.incoming.poll_next_unpin(cx)) {
+                Some(Ok(buffer)) if !buffer.is_empty() => {
+                    self.read_buffer = buffer;
+                    self.read_pos = 0;
+                }
+                Some(Err(e)) => return Poll::Ready(Err(e)),
+                Some(Ok(_)) | None => return Poll::Ready(Ok(0)),
+            }
+        }
+        let rest = &self.read_buffer[self.read_pos..];
+        let len = rest.len().min(buf.len());
+        buf[..len].copy_from_slice(&rest[..len]);
+        self.read_pos += len;
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_write_slice(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let Some(outgoing) = &mut self.outgoing else {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        };
+        if ready!(outgoing.poll_ready(cx)).is_err() || outgoing.start_send(buf.to_vec()).is_err() {
+            return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+}
+
+impl futures_util::AsyncRead for Duplex {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_read_slice(cx, buf)
+    }
+}
+
+impl futures_util::AsyncWrite for Duplex {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_slice(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().outgoing = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-compat")]
+impl tokio::io::AsyncRead for Duplex {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let len = ready!(
+            self.get_mut()
+                .poll_read_slice(cx, buf.initialize_unfilled())
+        )?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "tokio-compat")]
+impl tokio::io::AsyncWrite for Duplex {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_slice(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().outgoing = None;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for Duplex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Duplex").finish_non_exhaustive()
+    }
+}
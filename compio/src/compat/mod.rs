@@ -0,0 +1,7 @@
+//! Facades shaped like the APIs of other runtimes, and bridges to them, to
+//! migrate the code to compio step by step.
+
+#[cfg(feature = "bridge")]
+pub mod bridge;
+#[cfg(feature = "tokio-compat")]
+pub mod tokio;
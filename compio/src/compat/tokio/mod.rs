@@ -0,0 +1,41 @@
+//! A facade shaped like tokio, implemented over compio.
+//!
+//! The types mirror the tokio ones, so a module could switch the imports from
+//! `tokio` to `compio::compat::tokio` and keep the code using the tokio IO
+//! traits, e.g. `tokio::io::AsyncReadExt`, while the rest of the codebase is
+//! migrated later. The tasks still run on the compio runtime, and are not
+//! [`Send`].
+//!
+//! ```
+//! use compio::compat::tokio::{
+//!     net::{TcpListener, TcpStream},
+//!     spawn,
+//! };
+//! use tokio::io::{AsyncReadExt, AsyncWriteExt};
+//!
+//! # compio::runtime::Runtime::new().unwrap().block_on(async {
+//! let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+//! let addr = listener.local_addr().unwrap();
+//! let server = spawn(async move {
+//!     let (mut stream, _) = listener.accept().await.unwrap();
+//!     let mut buf = [0; 5];
+//!     stream.read_exact(&mut buf).await.unwrap();
+//!     stream.write_all(&buf).await.unwrap();
+//!     stream.flush().await.unwrap();
+//! });
+//!
+//! let mut stream = TcpStream::connect(addr).await.unwrap();
+//! stream.write_all(b"hello").await.unwrap();
+//! stream.flush().await.unwrap();
+//! let mut buf = [0; 5];
+//! stream.read_exact(&mut buf).await.unwrap();
+//! assert_eq!(&buf, b"hello");
+//! server.await.unwrap();
+//! # })
+//! ```
+
+pub mod net;
+pub mod task;
+
+#[doc(no_inline)]
+pub use task::{spawn, spawn_blocking, JoinHandle};
@@ -0,0 +1,151 @@
+//! TCP types implementing the tokio IO traits.
+
+use std::{
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use compio_io::compat::AsyncStream;
+use compio_net::ToSocketAddrsAsync;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A TCP stream like `tokio::net::TcpStream`.
+///
+/// It implements [`AsyncRead`] and [`AsyncWrite`] of tokio over the
+/// completion-based IO, with the internal buffers. Like tokio, the writes are
+/// not buffered: [`poll_write`](AsyncWrite::poll_write) completes after the
+/// data is sent, and [`poll_flush`](AsyncWrite::poll_flush) has nothing to
+/// do.
+#[derive(Debug)]
+pub struct TcpStream {
+    inner: AsyncStream<compio_net::TcpStream>,
+    // The length of the write in progress, returned when it completes.
+    write_len: usize,
+}
+
+impl TcpStream {
+    /// Open a TCP connection to the remote host.
+    pub async fn connect(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
+        Ok(compio_net::TcpStream::connect(addr).await?.into())
+    }
+
+    /// Returns the socket address of the remote peer.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().peer_addr()
+    }
+
+    /// Returns the socket address of the local half.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().local_addr()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.get_ref().set_nodelay(nodelay)
+    }
+
+    /// Get the reference of the compio stream.
+    pub fn get_ref(&self) -> &compio_net::TcpStream {
+        self.inner.get_ref()
+    }
+}
+
+impl From<compio_net::TcpStream> for TcpStream {
+    fn from(stream: compio_net::TcpStream) -> Self {
+        Self {
+            inner: AsyncStream::new(stream),
+            write_len: 0,
+        }
+    }
+}
+
+impl AsyncRead for TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let inner = Pin::new(&mut self.get_mut().inner);
+        let len = ready!(futures_util::AsyncRead::poll_read(
+            inner,
+            cx,
+            buf.initialize_unfilled()
+        ))?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let mut inner = Pin::new(&mut this.inner);
+        if this.write_len == 0 {
+            // The buffer is empty, so the data is buffered immediately.
+            this.write_len = ready!(futures_util::AsyncWrite::poll_write(
+                inner.as_mut(),
+                cx,
+                buf
+            ))?;
+        }
+        let res = ready!(futures_util::AsyncWrite::poll_flush(inner, cx));
+        let len = std::mem::take(&mut this.write_len);
+        Poll::Ready(res.map(|()| len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // Only complete the write in progress, if the last write is cancelled.
+        let this = self.get_mut();
+        let res = ready!(futures_util::AsyncWrite::poll_flush(
+            Pin::new(&mut this.inner),
+            cx
+        ));
+        this.write_len = 0;
+        Poll::Ready(res)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        futures_util::AsyncWrite::poll_close(Pin::new(&mut self.get_mut().inner), cx)
+    }
+}
+
+/// A TCP listener like `tokio::net::TcpListener`.
+#[derive(Debug)]
+pub struct TcpListener {
+    inner: compio_net::TcpListener,
+}
+
+impl TcpListener {
+    /// Creates a new TCP listener bound to the address.
+    pub async fn bind(addr: impl ToSocketAddrsAsync) -> io::Result<Self> {
+        Ok(compio_net::TcpListener::bind(addr).await?.into())
+    }
+
+    /// Accepts a new incoming connection.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (stream, addr) = self.inner.accept().await?;
+        Ok((stream.into(), addr))
+    }
+
+    /// Returns the local address that this listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr()
+    }
+
+    /// Get the reference of the compio listener.
+    pub fn get_ref(&self) -> &compio_net::TcpListener {
+        &self.inner
+    }
+}
+
+impl From<compio_net::TcpListener> for TcpListener {
+    fn from(inner: compio_net::TcpListener) -> Self {
+        Self { inner }
+    }
+}
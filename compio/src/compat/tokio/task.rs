@@ -0,0 +1,183 @@
+//! Tasks like `tokio::task`.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    future::Future,
+    io,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{ready, Context, Poll},
+    thread,
+};
+
+use compio_runtime::Task;
+use futures_util::FutureExt;
+
+/// Spawns a new task on the current runtime, like `tokio::spawn`.
+///
+/// Unlike [`compio::runtime::spawn`](crate::runtime::spawn), the task keeps
+/// running after the [`JoinHandle`] is dropped, and a panic of it is returned
+/// by the handle instead of resumed in the runtime.
+///
+/// ## Panics
+///
+/// Panics if called out of a compio runtime.
+pub fn spawn<F: Future + 'static>(future: F) -> JoinHandle<F::Output> {
+    JoinHandle::new(compio_runtime::spawn(
+        AssertUnwindSafe(future).catch_unwind(),
+    ))
+}
+
+/// Runs the blocking closure on a thread of the pool, like
+/// `tokio::task::spawn_blocking`.
+///
+/// ## Panics
+///
+/// Panics if called out of a compio runtime.
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    // The closures of compio should be `Sync`, and the mutex is only used to
+    // move `f` to the thread. The panic is caught on the thread, and resumed
+    // in the task to be returned by the handle.
+    let f = Mutex::new(f);
+    spawn(async move {
+        compio_runtime::spawn_blocking(move || {
+            catch_unwind(AssertUnwindSafe(
+                f.into_inner().unwrap_or_else(PoisonError::into_inner),
+            ))
+        })
+        .await
+        .unwrap_or_else(|e| resume_unwind(e))
+    })
+}
+
+/// An owned permission to join on a task, like `tokio::task::JoinHandle`.
+///
+/// Dropping the handle detaches the task.
+pub struct JoinHandle<T> {
+    // `None` after aborted or joined.
+    task: RefCell<Option<Task<thread::Result<T>>>>,
+}
+
+impl<T> JoinHandle<T> {
+    fn new(task: Task<thread::Result<T>>) -> Self {
+        Self {
+            task: RefCell::new(Some(task)),
+        }
+    }
+
+    /// Abort the task. It has no effect if the task is finished, and the
+    /// handle returns a cancelled [`JoinError`] otherwise.
+    pub fn abort(&self) {
+        let mut task = self.task.borrow_mut();
+        if !task.as_ref().is_some_and(Task::is_finished) {
+            task.take();
+        }
+    }
+
+    /// Whether the task is finished, or aborted.
+    pub fn is_finished(&self) -> bool {
+        self.task.borrow().as_ref().is_none_or(Task::is_finished)
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let slot = self.get_mut().task.get_mut();
+        let Some(task) = slot else {
+            return Poll::Ready(Err(JoinError(Repr::Cancelled)));
+        };
+        let res = ready!(Pin::new(task).poll(cx));
+        *slot = None;
+        Poll::Ready(res.map_err(|e| JoinError(Repr::Panic(e))))
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.get_mut().take() {
+            task.detach();
+        }
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// The error of a task that was aborted or panicked, like
+/// `tokio::task::JoinError`.
+pub struct JoinError(Repr);
+
+enum Repr {
+    Cancelled,
+    Panic(Box<dyn Any + Send + 'static>),
+}
+
+impl JoinError {
+    /// Whether the task was aborted.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.0, Repr::Cancelled)
+    }
+
+    /// Whether the task panicked.
+    pub fn is_panic(&self) -> bool {
+        matches!(self.0, Repr::Panic(_))
+    }
+
+    /// Consume the error, returning the panic payload.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the task was aborted.
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic.")
+    }
+
+    /// Consume the error, returning the panic payload if the task panicked.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.0 {
+            Repr::Panic(p) => Ok(p),
+            repr => Err(JoinError(repr)),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Repr::Cancelled => f.write_str("task was cancelled"),
+            Repr::Panic(_) => f.write_str("task panicked"),
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Repr::Cancelled => f.write_str("JoinError::Cancelled"),
+            Repr::Panic(_) => f.write_str("JoinError::Panic(...)"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+impl From<JoinError> for io::Error {
+    fn from(err: JoinError) -> Self {
+        io::Error::other(err.to_string())
+    }
+}
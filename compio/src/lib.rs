@@ -30,6 +30,8 @@ pub use buf::bumpalo;
 pub use buf::bytes;
 #[doc(no_inline)]
 pub use buf::BufResult;
+#[cfg(feature = "bridge")]
+pub mod compat;
 #[cfg(feature = "dispatcher")]
 #[doc(inline)]
 pub use compio_dispatcher as dispatcher;
@@ -44,6 +46,8 @@ pub use compio_signal as signal;
 #[cfg(feature = "tls")]
 #[doc(inline)]
 pub use compio_tls as tls;
+#[doc(no_inline)]
+pub use driver::{Error, ResultExt};
 #[cfg(feature = "event")]
 #[doc(no_inline)]
 pub use runtime::event;
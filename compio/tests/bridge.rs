@@ -0,0 +1,59 @@
+use compio::{
+    compat::bridge::{self, TryRecvError},
+    runtime::spawn,
+};
+use futures_util::{SinkExt, StreamExt};
+
+#[test]
+fn channel() {
+    // Capacity 1 to wait on the both sides.
+    let (tx, mut rx) = bridge::channel::<usize>(1);
+    let (mut back_tx, mut back_rx) = bridge::channel(1);
+    let tokio = std::thread::spawn(move || {
+        tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap()
+            .block_on(async move {
+                let mut sum = 0;
+                while let Some(i) = rx.next().await {
+                    sum += i;
+                    back_tx.send(i * 2).await.unwrap();
+                }
+                sum
+            })
+    });
+    compio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(async move {
+            let sender = spawn({
+                let mut tx = tx.clone();
+                async move {
+                    for i in 0..1000 {
+                        tx.send(i).await.unwrap();
+                    }
+                }
+            });
+            drop(tx);
+            for i in 0..1000 {
+                assert_eq!(back_rx.next().await, Some(i * 2));
+            }
+            sender.await;
+            assert_eq!(back_rx.next().await, None);
+        });
+    assert_eq!(tokio.join().unwrap(), 999 * 1000 / 2);
+}
+
+#[test]
+fn channel_try() {
+    let (mut tx, mut rx) = bridge::channel(0);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    tx.try_send(1).unwrap();
+    assert!(tx.try_send(2).unwrap_err().is_full());
+    rx.close();
+    assert!(tx.is_closed());
+    assert!(tx.try_send(3).unwrap_err().is_disconnected());
+    // The buffered message is still received.
+    assert_eq!(rx.try_recv(), Ok(1));
+    drop(tx);
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Closed));
+}
@@ -177,6 +177,22 @@ async fn arena() {
     assert_eq!(buffer.len(), read);
 }
 
+#[cfg(feature = "time")]
+#[compio_macros::test(start_paused = true)]
+async fn start_paused() {
+    let start = std::time::Instant::now();
+    compio::time::sleep(Duration::from_secs(3600)).await;
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[compio_macros::test(autotune, threads = 2, ring_entries = 256, sqpoll)]
+async fn configured() {
+    let file = File::open("Cargo.toml").await.unwrap();
+    let (_, buffer) = file.read_at(Vec::with_capacity(9), 0).await.unwrap();
+    assert_eq!(buffer, b"[package]");
+    assert_eq!(compio::runtime::spawn_blocking(|| 42).await, 42);
+}
+
 #[compio_macros::test]
 async fn wake_cross_thread() {
     let (tx, rx) = futures_channel::oneshot::channel::<()>();
@@ -0,0 +1,117 @@
+use std::{net::Ipv4Addr, time::Duration};
+
+use compio::compat::{
+    bridge,
+    tokio::{
+        net::{TcpListener, TcpStream},
+        spawn, spawn_blocking,
+    },
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[compio_macros::test]
+async fn tcp() {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![];
+        stream.read_to_end(&mut buf).await.unwrap();
+        stream.write_all(&buf).await.unwrap();
+        stream.shutdown().await.unwrap();
+        buf.len()
+    });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    stream.set_nodelay(true).unwrap();
+    let data = vec![42u8; 100_000];
+    stream.write_all(&data).await.unwrap();
+    stream.shutdown().await.unwrap();
+    let mut buf = vec![];
+    stream.read_to_end(&mut buf).await.unwrap();
+    assert_eq!(buf, data);
+    assert_eq!(server.await.unwrap(), data.len());
+}
+
+#[compio_macros::test]
+async fn tcp_write_through() {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = [0; 4];
+        stream.read_exact(&mut buf).await.unwrap();
+        stream.write_all(b"pong").await.unwrap();
+        buf
+    });
+
+    // The request is sent without flushing.
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"ping").await.unwrap();
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"pong");
+    assert_eq!(&server.await.unwrap(), b"ping");
+}
+
+#[compio_macros::test]
+async fn join_handle() {
+    // Detached on drop.
+    let (tx, rx) = futures_channel::oneshot::channel();
+    drop(spawn(async move { tx.send(1).unwrap() }));
+    assert_eq!(rx.await.unwrap(), 1);
+
+    let err = spawn(async { panic!("boom") }).await.unwrap_err();
+    assert!(err.is_panic());
+    assert_eq!(*err.into_panic().downcast::<&str>().unwrap(), "boom");
+
+    let handle = spawn(compio::runtime::time::sleep(Duration::from_secs(10)));
+    assert!(!handle.is_finished());
+    handle.abort();
+    assert!(handle.is_finished());
+    assert!(handle.await.unwrap_err().is_cancelled());
+
+    let handle = spawn(async { 1 });
+    compio::runtime::time::sleep(Duration::from_millis(10)).await;
+    assert!(handle.is_finished());
+    // No effect after finished.
+    handle.abort();
+    assert_eq!(handle.await.unwrap(), 1);
+
+    assert_eq!(spawn_blocking(|| 2).await.unwrap(), 2);
+    let err = spawn_blocking(|| panic!("boom")).await.unwrap_err();
+    assert!(err.is_panic());
+}
+
+#[test]
+fn duplex() {
+    compio::runtime::Runtime::new().unwrap().block_on(async {
+        let listener = compio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stream, (server, _)) =
+            futures_util::try_join!(compio::net::TcpStream::connect(addr), listener.accept())
+                .unwrap();
+
+        let (mut duplex, pump) = bridge::duplex(stream, 3);
+        let pump = spawn(pump);
+        let tokio = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async move {
+                    duplex.write_all(b"hello").await.unwrap();
+                    duplex.shutdown().await.unwrap();
+                    let mut buf = vec![];
+                    duplex.read_to_end(&mut buf).await.unwrap();
+                    buf
+                })
+        });
+
+        let (mut read, mut write) = compio::io::split(server);
+        compio::io::copy(&mut read, &mut write).await.unwrap();
+        drop((read, write));
+        pump.await.unwrap().unwrap();
+        assert_eq!(tokio.join().unwrap(), b"hello");
+    });
+}
@@ -0,0 +1,60 @@
+#![no_main]
+
+use compio_buf::{BufCursor, BufPut};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte splits the input into the decoded bytes and the calls.
+fuzz_target!(|data: &[u8]| {
+    let Some((&split, data)) = data.split_first() else {
+        return;
+    };
+    let (bytes, calls) = data.split_at((split as usize).min(data.len()));
+
+    // Decoding arbitrary bytes never reads past the end, and the failed
+    // reads don't advance.
+    let mut cursor = BufCursor::new(bytes.to_vec());
+    for &call in calls {
+        let pos = cursor.position();
+        let read = match call % 6 {
+            0 => cursor.get_u8().map(|_| 1),
+            1 => cursor.get_u16().map(|_| 2),
+            2 => cursor.get_u32_le().map(|_| 4),
+            3 => cursor.get_i64().map(|_| 8),
+            4 => cursor.get_slice(call as usize / 6).map(<[u8]>::len),
+            _ => cursor.advance(call as usize / 6).map(|_| call as usize / 6),
+        };
+        match read {
+            Some(len) => assert_eq!(cursor.position(), pos + len),
+            None => assert_eq!(cursor.position(), pos),
+        }
+        assert!(cursor.position() <= bytes.len());
+        assert_eq!(cursor.remaining(), &bytes[cursor.position()..]);
+    }
+
+    // Encoding and decoding the same values round-trips.
+    let mut buf = vec![];
+    for chunk in bytes.chunks(8) {
+        let mut value = [0u8; 8];
+        value[..chunk.len()].copy_from_slice(chunk);
+        let value = u64::from_le_bytes(value);
+        match chunk.len() % 4 {
+            0 => buf.put_u64(value),
+            1 => buf.put_u8(value as u8),
+            2 => buf.put_u16_le(value as u16),
+            _ => buf.put_i32(value as i32),
+        };
+    }
+    let mut cursor = BufCursor::new(buf);
+    for chunk in bytes.chunks(8) {
+        let mut value = [0u8; 8];
+        value[..chunk.len()].copy_from_slice(chunk);
+        let value = u64::from_le_bytes(value);
+        match chunk.len() % 4 {
+            0 => assert_eq!(cursor.get_u64(), Some(value)),
+            1 => assert_eq!(cursor.get_u8(), Some(value as u8)),
+            2 => assert_eq!(cursor.get_u16_le(), Some(value as u16)),
+            _ => assert_eq!(cursor.get_i32(), Some(value as i32)),
+        }
+    }
+    assert!(cursor.remaining().is_empty());
+});